@@ -1,29 +1,38 @@
 use cosmwasm_std::{
     attr, entry_point, from_binary, to_binary, Addr, Binary, CosmosMsg, Deps, DepsMut, Env,
-    MessageInfo, Order, Reply, ReplyOn, Response, StdError, StdResult, SubMsg, WasmMsg,
+    MessageInfo, Order, Reply, ReplyOn, Response, StdError, StdResult, Storage, SubMsg, WasmMsg,
 };
+use cw_storage_plus::Map;
 
 use crate::error::ContractError;
 use crate::migration;
-use crate::querier::query_pair_info;
+use crate::querier::{query_multi_pair_info, query_pair_info};
 
 use crate::state::{
-    pair_key, read_pairs, Config, TmpPairInfo, CONFIG, OWNERSHIP_PROPOSAL, PAIRS, PAIR_CONFIGS,
+    multi_pair_key, pair_key, read_default_pair, read_pairs, read_pairs_by_assets,
+    read_pairs_to_refresh, Config, PendingRefresh, TmpPairInfo, CONFIG, MULTI_PAIRS,
+    OWNERSHIP_PROPOSAL, PAIRS, PAIR_CONFIGS, PAIR_FEE_OVERRIDES, PAIR_STATUSES, PENDING_REFRESH,
     TMP_PAIR_INFO,
 };
 
 use crate::response::MsgInstantiateContractResponse;
 
-use astroport::asset::{addr_validate_to_lower, AssetInfo, PairInfo};
+use astroport::asset::{addr_validate_to_lower, AssetInfo, MultiPairInfo, PairInfo};
 use astroport::factory::{
-    ConfigResponse, ExecuteMsg, FeeInfoResponse, InstantiateMsg, MigrateMsg, PairConfig, PairType,
-    PairsResponse, QueryMsg,
+    ConfigResponse, ExecuteMsg, FeeInfoResponse, FeeOverride, InstantiateMsg, MigrateMsg,
+    PairConfig, PairStatus, PairType, PairsResponse, QueryMsg,
 };
 
-use crate::migration::migrate_pair_configs_to_v120;
+use crate::migration::{
+    migrate_multi_pairs_to_v140, migrate_pair_configs_to_v120, migrate_pair_configs_to_v140,
+    migrate_pairs_to_v130, pairs_migration_pending,
+};
 use astroport::common::{claim_ownership, drop_ownership_proposal, propose_new_owner};
 use astroport::generator::ExecuteMsg::DeactivatePool;
-use astroport::pair::InstantiateMsg as PairInstantiateMsg;
+use astroport::pair::{ExecuteMsg as PairExecuteMsg, InstantiateMsg as PairInstantiateMsg};
+use astroport::pair_stable_multi::{
+    InstantiateMsg as MultiPairInstantiateMsg, MAX_ASSETS_NUM, MIN_ASSETS_NUM,
+};
 use cw2::{get_contract_version, set_contract_version};
 use protobuf::Message;
 use std::collections::HashSet;
@@ -34,6 +43,8 @@ const CONTRACT_NAME: &str = "astroport-factory";
 const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
 /// A `reply` call code ID used in a sub-message.
 const INSTANTIATE_PAIR_REPLY_ID: u64 = 1;
+/// A `reply` call code ID used in a sub-message that instantiates a pool with more than two assets.
+const INSTANTIATE_MULTI_PAIR_REPLY_ID: u64 = 2;
 
 /// ## Description
 /// Creates a new contract with the specified parameters packed in the `msg` variable.
@@ -61,6 +72,7 @@ pub fn instantiate(
         fee_address: None,
         generator_address: None,
         whitelist_code_id: msg.whitelist_code_id,
+        guardian: None,
     };
 
     if let Some(generator_address) = msg.generator_address {
@@ -89,6 +101,7 @@ pub fn instantiate(
         if !pc.valid_fee_bps() {
             return Err(ContractError::PairConfigInvalidFeeBps {});
         }
+        assert_maker_fee_mode(pc)?;
         PAIR_CONFIGS.save(deps.storage, pc.clone().pair_type.to_string(), pc)?;
     }
     CONFIG.save(deps.storage, &config)?;
@@ -136,8 +149,36 @@ pub struct UpdateConfig {
 ///             init_params,
 ///         }** Creates a new pair with the specified input parameters.
 ///
-/// * **ExecuteMsg::Deregister { asset_infos }** Removes an existing pair from the factory.
-/// * The asset information is for the assets that are traded in the pair.
+/// * **ExecuteMsg::CreateMultiPair {
+///             pair_type,
+///             asset_infos,
+///             init_params,
+///         }** Creates a new pool with more than two assets using the specified input parameters.
+///
+/// * **ExecuteMsg::SetPairFeeOverride {
+///             pair_addr,
+///             total_fee_bps,
+///             maker_fee_bps,
+///         }** Sets fees for a specific pair that take precedence over its pair type fees.
+///
+/// * **ExecuteMsg::RemovePairFeeOverride { pair_addr }** Removes the fee override of a specific pair.
+///
+/// * **ExecuteMsg::SetGuardian { guardian }** Sets or removes the address that can halt pairs.
+///
+/// * **ExecuteMsg::SetPairStatus { pair_addr, status }** Sets the trading state of a specific pair.
+///
+/// * **ExecuteMsg::RefreshPairs { limit }** Pushes the current fee and Generator information to the next batch of pairs
+/// that cache outdated parameters.
+///
+/// * **ExecuteMsg::MigratePairs { limit }** Moves the next batch of pairs registered before v1.3.0 to the storage
+/// where pairs are also keyed by their type. Other messages are rejected until all pairs are moved.
+///
+/// * **ExecuteMsg::Deregister { asset_infos, pair_type }** Removes an existing pair from the factory.
+/// * The asset information is for the assets that are traded in the pair. The pair type can be omitted
+/// if only one pool exists for the assets.
+///
+/// * **ExecuteMsg::DeregisterMultiPair { asset_infos, pair_type }** Removes an existing pool with more than two assets
+/// from the factory. The pair type can be omitted if only one pool exists for the assets.
 ///
 /// * **ExecuteMsg::ProposeNewOwner { owner, expires_in }** Creates a request to change contract ownership.
 ///
@@ -151,6 +192,10 @@ pub fn execute(
     info: MessageInfo,
     msg: ExecuteMsg,
 ) -> Result<Response, ContractError> {
+    if pairs_migration_pending(deps.storage) && !matches!(msg, ExecuteMsg::MigratePairs { .. }) {
+        return Err(ContractError::PairsMigrationPending {});
+    }
+
     match msg {
         ExecuteMsg::UpdateConfig {
             token_code_id,
@@ -174,7 +219,41 @@ pub fn execute(
             asset_infos,
             init_params,
         } => execute_create_pair(deps, env, pair_type, asset_infos, init_params),
-        ExecuteMsg::Deregister { asset_infos } => deregister(deps, info, asset_infos),
+        ExecuteMsg::CreateMultiPair {
+            pair_type,
+            asset_infos,
+            init_params,
+        } => execute_create_multi_pair(deps, env, pair_type, asset_infos, init_params),
+        ExecuteMsg::SetPairFeeOverride {
+            pair_addr,
+            total_fee_bps,
+            maker_fee_bps,
+        } => execute_set_pair_fee_override(
+            deps,
+            info,
+            pair_addr,
+            FeeOverride {
+                total_fee_bps,
+                maker_fee_bps,
+            },
+        ),
+        ExecuteMsg::RemovePairFeeOverride { pair_addr } => {
+            execute_remove_pair_fee_override(deps, info, pair_addr)
+        }
+        ExecuteMsg::SetGuardian { guardian } => execute_set_guardian(deps, info, guardian),
+        ExecuteMsg::SetPairStatus { pair_addr, status } => {
+            execute_set_pair_status(deps, info, pair_addr, status)
+        }
+        ExecuteMsg::RefreshPairs { limit } => execute_refresh_pairs(deps, limit),
+        ExecuteMsg::MigratePairs { limit } => execute_migrate_pairs(deps, limit),
+        ExecuteMsg::Deregister {
+            asset_infos,
+            pair_type,
+        } => deregister(deps, info, asset_infos, pair_type),
+        ExecuteMsg::DeregisterMultiPair {
+            asset_infos,
+            pair_type,
+        } => deregister_multi_pair(deps, info, asset_infos, pair_type),
         ExecuteMsg::ProposeNewOwner { owner, expires_in } => {
             let config: Config = CONFIG.load(deps.storage)?;
 
@@ -236,12 +315,12 @@ pub fn execute_update_config(
         return Err(ContractError::Unauthorized {});
     }
 
-    if let Some(fee_address) = param.fee_address {
+    if let Some(fee_address) = &param.fee_address {
         // Validate address format
         config.fee_address = Some(addr_validate_to_lower(deps.api, fee_address.as_str())?);
     }
 
-    if let Some(generator_address) = param.generator_address {
+    if let Some(generator_address) = &param.generator_address {
         // Validate the address format
         config.generator_address = Some(addr_validate_to_lower(
             deps.api,
@@ -259,7 +338,31 @@ pub fn execute_update_config(
 
     CONFIG.save(deps.storage, &config)?;
 
-    Ok(Response::new().add_attribute("action", "update_config"))
+    let mut response = Response::new().add_attribute("action", "update_config");
+
+    // The pairs cache the fee and Generator addresses, so they have to be refreshed
+    if param.fee_address.is_some() || param.generator_address.is_some() {
+        let pair_types = caching_pair_types(deps.as_ref())?;
+        let (messages, finished) = schedule_pairs_refresh(deps, pair_types)?;
+        response = response
+            .add_messages(messages)
+            .add_attribute("refresh_finished", finished.to_string());
+    }
+
+    Ok(response)
+}
+
+/// ## Description
+/// Checks that a pair type supports the way its Maker fee is collected. Only XYK pairs can collect
+/// the Maker fee by minting LP tokens.
+/// ## Params
+/// * **pair_config** is an object of type [`PairConfig`]. This is the pair type configuration to check.
+fn assert_maker_fee_mode(pair_config: &PairConfig) -> Result<(), ContractError> {
+    if pair_config.maker_fee_as_lp && pair_config.pair_type != (PairType::Xyk {}) {
+        return Err(ContractError::PairConfigMakerFeeAsLpNotSupported {});
+    }
+
+    Ok(())
 }
 
 /// ## Description
@@ -290,6 +393,7 @@ pub fn execute_update_pair_config(
     if !pair_config.valid_fee_bps() {
         return Err(ContractError::PairConfigInvalidFeeBps {});
     }
+    assert_maker_fee_mode(&pair_config)?;
 
     PAIR_CONFIGS.save(
         deps.storage,
@@ -297,7 +401,411 @@ pub fn execute_update_pair_config(
         &pair_config,
     )?;
 
-    Ok(Response::new().add_attribute("action", "update_pair_config"))
+    let mut response = Response::new().add_attribute("action", "update_pair_config");
+
+    // The pairs of this type cache its fees, so they have to be refreshed
+    if caches_factory_params(&pair_config.pair_type) {
+        let (messages, finished) = schedule_pairs_refresh(deps, vec![pair_config.pair_type])?;
+        response = response
+            .add_messages(messages)
+            .add_attribute("refresh_finished", finished.to_string());
+    }
+
+    Ok(response)
+}
+
+/// ## Description
+/// Sets fees for a specific pair that take precedence over the fees of its pair type.
+/// Returns a [`ContractError`] on failure.
+///
+/// ## Params
+/// * **deps** is an object of type [`DepsMut`].
+///
+/// * **info** is an object of type [`MessageInfo`].
+///
+/// * **pair_addr** is an object of type [`String`]. This is the pair for which we set the fee override.
+///
+/// * **fee_override** is an object of type [`FeeOverride`]. These are the fees to use for the pair.
+///
+/// ## Executor
+/// Only the owner can execute this.
+pub fn execute_set_pair_fee_override(
+    deps: DepsMut,
+    info: MessageInfo,
+    pair_addr: String,
+    fee_override: FeeOverride,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+
+    // Permission check
+    if info.sender != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    // Validate total and maker fee bps
+    if !fee_override.valid_fee_bps() {
+        return Err(ContractError::FeeOverrideInvalidFeeBps {});
+    }
+
+    let pair_addr = addr_validate_to_lower(deps.api, &pair_addr)?;
+    let pair_type = assert_pair_registered(deps.as_ref(), &pair_addr)?;
+
+    PAIR_FEE_OVERRIDES.save(deps.storage, &pair_addr, &fee_override)?;
+
+    let mut messages = vec![];
+    if caches_factory_params(&pair_type) {
+        messages.push(refresh_pair_msg(&pair_addr)?);
+    }
+
+    Ok(Response::new().add_messages(messages).add_attributes(vec![
+        attr("action", "set_pair_fee_override"),
+        attr("pair_contract_addr", pair_addr),
+        attr("total_fee_bps", fee_override.total_fee_bps.to_string()),
+        attr("maker_fee_bps", fee_override.maker_fee_bps.to_string()),
+    ]))
+}
+
+/// ## Description
+/// Removes the fee override of a specific pair, so the pair uses the fees of its pair type again.
+/// Returns a [`ContractError`] on failure.
+///
+/// ## Params
+/// * **deps** is an object of type [`DepsMut`].
+///
+/// * **info** is an object of type [`MessageInfo`].
+///
+/// * **pair_addr** is an object of type [`String`]. This is the pair for which we remove the fee override.
+///
+/// ## Executor
+/// Only the owner can execute this.
+pub fn execute_remove_pair_fee_override(
+    deps: DepsMut,
+    info: MessageInfo,
+    pair_addr: String,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+
+    // Permission check
+    if info.sender != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let pair_addr = addr_validate_to_lower(deps.api, &pair_addr)?;
+    PAIR_FEE_OVERRIDES.remove(deps.storage, &pair_addr);
+
+    // A deregistered pair doesn't have to be refreshed
+    let mut messages = vec![];
+    if let Ok(pair_type) = assert_pair_registered(deps.as_ref(), &pair_addr) {
+        if caches_factory_params(&pair_type) {
+            messages.push(refresh_pair_msg(&pair_addr)?);
+        }
+    }
+
+    Ok(Response::new().add_messages(messages).add_attributes(vec![
+        attr("action", "remove_pair_fee_override"),
+        attr("pair_contract_addr", pair_addr),
+    ]))
+}
+
+/// ## Description
+/// Sets or removes the guardian that can halt pairs in an incident. Returns a [`ContractError`] on failure.
+///
+/// ## Params
+/// * **deps** is an object of type [`DepsMut`].
+///
+/// * **info** is an object of type [`MessageInfo`].
+///
+/// * **guardian** is an [`Option`] field of type [`String`]. This is the new guardian address.
+///
+/// ## Executor
+/// Only the owner can execute this.
+pub fn execute_set_guardian(
+    deps: DepsMut,
+    info: MessageInfo,
+    guardian: Option<String>,
+) -> Result<Response, ContractError> {
+    let mut config = CONFIG.load(deps.storage)?;
+
+    // Permission check
+    if info.sender != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    config.guardian = guardian
+        .map(|guardian| addr_validate_to_lower(deps.api, &guardian))
+        .transpose()?;
+    CONFIG.save(deps.storage, &config)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "set_guardian"),
+        attr(
+            "guardian",
+            config
+                .guardian
+                .map(|guardian| guardian.to_string())
+                .unwrap_or_default(),
+        ),
+    ]))
+}
+
+/// ## Description
+/// Sets the trading state of a specific pair. Pairs that cache the factory parameters are told to refresh them.
+/// Custom pairs are not maintained in this repository and may not support the refresh, so the state is only stored
+/// and they have to query it with [`QueryMsg::PairStatus`] on every swap and deposit to enforce it.
+/// Returns a [`ContractError`] on failure.
+///
+/// ## Params
+/// * **deps** is an object of type [`DepsMut`].
+///
+/// * **info** is an object of type [`MessageInfo`].
+///
+/// * **pair_addr** is an object of type [`String`]. This is the pair for which we set the state.
+///
+/// * **status** is an object of type [`PairStatus`]. This is the new state of the pair.
+///
+/// ## Executor
+/// The owner can set any state. The guardian can only halt a pair. Restrictions that are already in place are kept,
+/// so pausing deposits in a pair with paused swaps makes it withdraw-only.
+pub fn execute_set_pair_status(
+    deps: DepsMut,
+    info: MessageInfo,
+    pair_addr: String,
+    mut status: PairStatus,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+
+    // Permission check
+    let is_guardian = config.guardian.as_ref() == Some(&info.sender);
+    if info.sender != config.owner && !(is_guardian && status != PairStatus::Active) {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let pair_addr = addr_validate_to_lower(deps.api, &pair_addr)?;
+    let pair_type = assert_pair_registered(deps.as_ref(), &pair_addr)?;
+
+    // The guardian can't lift the restrictions set before
+    if info.sender != config.owner {
+        let current = PAIR_STATUSES
+            .may_load(deps.storage, &pair_addr)?
+            .unwrap_or_default();
+        status = combine_pair_statuses(&current, &status);
+    }
+
+    if status == PairStatus::Active {
+        PAIR_STATUSES.remove(deps.storage, &pair_addr);
+    } else {
+        PAIR_STATUSES.save(deps.storage, &pair_addr, &status)?;
+    }
+
+    let mut messages = vec![];
+    if caches_factory_params(&pair_type) {
+        messages.push(refresh_pair_msg(&pair_addr)?);
+    }
+
+    Ok(Response::new().add_messages(messages).add_attributes(vec![
+        attr("action", "set_pair_status"),
+        attr("pair_contract_addr", pair_addr),
+        attr("status", status.to_string()),
+    ]))
+}
+
+/// ## Description
+/// Returns the state that keeps the restrictions of both specified states.
+/// ## Params
+/// * **current** is an object of type [`PairStatus`]. This is the current state of the pair.
+///
+/// * **requested** is an object of type [`PairStatus`]. This is the state to add the restrictions of.
+fn combine_pair_statuses(current: &PairStatus, requested: &PairStatus) -> PairStatus {
+    match (
+        current.swaps_allowed() && requested.swaps_allowed(),
+        current.deposits_allowed() && requested.deposits_allowed(),
+    ) {
+        (true, true) => PairStatus::Active,
+        (false, true) => PairStatus::SwapsPaused,
+        (true, false) => PairStatus::DepositsPaused,
+        (false, false) => PairStatus::WithdrawOnly,
+    }
+}
+
+/// ## Description
+/// Returns a [`ContractError`] if the specified contract is not a pair registered in the factory,
+/// otherwise returns the type of the pair.
+/// ## Params
+/// * **deps** is an object of type [`Deps`].
+///
+/// * **pair_addr** is an object of type [`Addr`]. This is the contract to check.
+fn assert_pair_registered(deps: Deps, pair_addr: &Addr) -> Result<PairType, ContractError> {
+    if let Ok(pair_info) = query_pair_info(deps, pair_addr) {
+        let registered_addr = PAIRS.may_load(
+            deps.storage,
+            (
+                &pair_key(&pair_info.asset_infos),
+                pair_info.pair_type.to_string().as_bytes(),
+            ),
+        )?;
+        if registered_addr.as_ref() == Some(pair_addr) {
+            return Ok(pair_info.pair_type);
+        }
+    }
+
+    // Pools with more than two assets are registered separately
+    if let Ok(pair_info) = query_multi_pair_info(deps, pair_addr) {
+        let registered_addr = MULTI_PAIRS.may_load(
+            deps.storage,
+            (
+                &multi_pair_key(&pair_info.asset_infos),
+                pair_info.pair_type.to_string().as_bytes(),
+            ),
+        )?;
+        if registered_addr.as_ref() == Some(pair_addr) {
+            return Ok(pair_info.pair_type);
+        }
+    }
+
+    Err(ContractError::PairNotRegistered {})
+}
+
+/// ## Description
+/// Pushes the current fee and Generator information to the next batch of pairs whose cached parameters
+/// are outdated. Returns a [`ContractError`] on failure.
+///
+/// ## Params
+/// * **deps** is an object of type [`DepsMut`].
+///
+/// * **limit** is an [`Option`] field of type [`u32`]. This is the maximum number of pairs to refresh.
+///
+/// ## Executor
+/// Anyone can execute this.
+pub fn execute_refresh_pairs(deps: DepsMut, limit: Option<u32>) -> Result<Response, ContractError> {
+    let (messages, finished) = match PENDING_REFRESH.may_load(deps.storage)? {
+        Some(pending) => refresh_pairs_batch(deps, pending, limit)?,
+        None => (vec![], true),
+    };
+
+    Ok(Response::new()
+        .add_attributes(vec![
+            attr("action", "refresh_pairs"),
+            attr("pairs_refreshed", messages.len().to_string()),
+            attr("refresh_finished", finished.to_string()),
+        ])
+        .add_messages(messages))
+}
+
+/// ## Description
+/// Moves the next batch of pairs registered before v1.3.0 to the [`PAIRS`] map. Returns a [`ContractError`] on failure.
+///
+/// ## Params
+/// * **deps** is an object of type [`DepsMut`].
+///
+/// * **limit** is an [`Option`] field of type [`u32`]. This is the maximum number of pairs to move.
+///
+/// ## Executor
+/// Anyone can execute this.
+pub fn execute_migrate_pairs(deps: DepsMut, limit: Option<u32>) -> Result<Response, ContractError> {
+    let (migrated, finished) = migrate_pairs_to_v130(deps, limit)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "migrate_pairs"),
+        attr("pairs_migrated", migrated.to_string()),
+        attr("migration_finished", finished.to_string()),
+    ]))
+}
+
+/// ## Description
+/// Returns true if pairs of the specified type cache the factory parameters and have to be refreshed when they change.
+/// All pairs maintained in this repository cache them. Custom pairs may not support the refresh.
+/// ## Params
+/// * **pair_type** is an object of type [`PairType`].
+fn caches_factory_params(pair_type: &PairType) -> bool {
+    !matches!(pair_type, PairType::Custom(_))
+}
+
+/// ## Description
+/// Returns all configured pair types whose pairs cache the factory parameters.
+/// ## Params
+/// * **deps** is an object of type [`Deps`].
+fn caching_pair_types(deps: Deps) -> StdResult<Vec<PairType>> {
+    let mut pair_types = vec![];
+    for item in PAIR_CONFIGS.range(deps.storage, None, None, Order::Ascending) {
+        let (_, pair_config) = item?;
+        if caches_factory_params(&pair_config.pair_type) {
+            pair_types.push(pair_config.pair_type);
+        }
+    }
+
+    Ok(pair_types)
+}
+
+/// ## Description
+/// Returns a message that makes a pair refresh the factory parameters it caches.
+/// ## Params
+/// * **pair_addr** is an object of type [`Addr`]. This is the pair to refresh.
+fn refresh_pair_msg(pair_addr: &Addr) -> StdResult<CosmosMsg> {
+    Ok(CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: pair_addr.to_string(),
+        msg: to_binary(&PairExecuteMsg::RefreshFactoryParams {})?,
+        funds: vec![],
+    }))
+}
+
+/// ## Description
+/// Schedules a refresh of all pairs of the specified types and refreshes the first batch right away.
+/// Returns the refresh messages and whether all pairs were refreshed.
+/// ## Params
+/// * **deps** is an object of type [`DepsMut`].
+///
+/// * **pair_types** is a vector that contains objects of type [`PairType`]. These are the pair types to refresh.
+fn schedule_pairs_refresh(
+    deps: DepsMut,
+    pair_types: Vec<PairType>,
+) -> StdResult<(Vec<CosmosMsg>, bool)> {
+    let mut pair_types: Vec<String> = pair_types.iter().map(|t| t.to_string()).collect();
+    if let Some(pending) = PENDING_REFRESH.may_load(deps.storage)? {
+        for pair_type in pending.pair_types {
+            if !pair_types.contains(&pair_type) {
+                pair_types.push(pair_type);
+            }
+        }
+    }
+
+    // The pairs refreshed so far cache outdated parameters again, so the refresh starts from the first pair
+    let pending = PendingRefresh {
+        pair_types,
+        last_key: None,
+        multi_pairs: false,
+    };
+
+    refresh_pairs_batch(deps, pending, None)
+}
+
+/// ## Description
+/// Returns the messages that refresh the next batch of pending pairs and saves the progress.
+/// Also returns whether all pairs were refreshed.
+/// ## Params
+/// * **deps** is an object of type [`DepsMut`].
+///
+/// * **pending** is an object of type [`PendingRefresh`]. These are the pairs that have to be refreshed.
+///
+/// * **limit** is an [`Option`] field of type [`u32`]. This is the maximum number of pairs to refresh.
+fn refresh_pairs_batch(
+    deps: DepsMut,
+    pending: PendingRefresh,
+    limit: Option<u32>,
+) -> StdResult<(Vec<CosmosMsg>, bool)> {
+    let (pairs, remaining) = read_pairs_to_refresh(deps.as_ref(), &pending, limit)?;
+    let finished = remaining.is_none();
+
+    match remaining {
+        Some(remaining) => PENDING_REFRESH.save(deps.storage, &remaining)?,
+        None => PENDING_REFRESH.remove(deps.storage),
+    }
+
+    let messages = pairs
+        .iter()
+        .map(refresh_pair_msg)
+        .collect::<StdResult<Vec<_>>>()?;
+
+    Ok((messages, finished))
 }
 
 /// ## Description
@@ -330,8 +838,9 @@ pub fn execute_create_pair(
 
     let config = CONFIG.load(deps.storage)?;
 
+    let pair_key = pair_key(&asset_infos);
     if PAIRS
-        .may_load(deps.storage, &pair_key(&asset_infos))?
+        .may_load(deps.storage, (&pair_key, pair_type.to_string().as_bytes()))?
         .is_some()
     {
         return Err(ContractError::PairWasCreated {});
@@ -347,8 +856,13 @@ pub fn execute_create_pair(
         return Err(ContractError::PairConfigDisabled {});
     }
 
-    let pair_key = pair_key(&asset_infos);
-    TMP_PAIR_INFO.save(deps.storage, &TmpPairInfo { pair_key })?;
+    TMP_PAIR_INFO.save(
+        deps.storage,
+        &TmpPairInfo {
+            pair_key,
+            pair_type: pair_type.clone(),
+        },
+    )?;
 
     let sub_msg: Vec<SubMsg> = vec![SubMsg {
         id: INSTANTIATE_PAIR_REPLY_ID,
@@ -377,6 +891,101 @@ pub fn execute_create_pair(
         ]))
 }
 
+/// ## Description
+/// Creates a new pool of `pair_type` with more than two assets specified in `asset_infos`. Returns a [`ContractError`] on failure or
+/// returns the address of the pool contract if the transaction was successful.
+///
+/// ## Params
+/// * **deps** is an object of type [`DepsMut`].
+///
+/// * **env** is an object of type [`Env`].
+///
+/// * **pair_type** is an object of type [`PairType`]. This is the pair type of the newly created pool.
+///
+/// * **asset_infos** is a vector with items of type [`AssetInfo`]. These are the assets for which we create a pool.
+///
+/// * **init_params** is an [`Option`] type. These are packed params used for custom pair types that need extra data to be instantiated.
+pub fn execute_create_multi_pair(
+    deps: DepsMut,
+    env: Env,
+    pair_type: PairType,
+    asset_infos: Vec<AssetInfo>,
+    init_params: Option<Binary>,
+) -> Result<Response, ContractError> {
+    if asset_infos.len() < MIN_ASSETS_NUM || asset_infos.len() > MAX_ASSETS_NUM {
+        return Err(ContractError::InvalidNumberOfAssets {});
+    }
+
+    for (i, asset_info) in asset_infos.iter().enumerate() {
+        asset_info.check(deps.api)?;
+
+        if asset_infos[i + 1..].contains(asset_info) {
+            return Err(ContractError::DoublingAssets {});
+        }
+    }
+
+    let config = CONFIG.load(deps.storage)?;
+
+    let pair_key = multi_pair_key(&asset_infos);
+    if MULTI_PAIRS
+        .may_load(deps.storage, (&pair_key, pair_type.to_string().as_bytes()))?
+        .is_some()
+    {
+        return Err(ContractError::PairWasCreated {});
+    }
+
+    // Get pair type from config
+    let pair_config = PAIR_CONFIGS
+        .load(deps.storage, pair_type.to_string())
+        .map_err(|_| ContractError::PairConfigNotFound {})?;
+
+    // Check if pair config is disabled
+    if pair_config.is_disabled {
+        return Err(ContractError::PairConfigDisabled {});
+    }
+
+    TMP_PAIR_INFO.save(
+        deps.storage,
+        &TmpPairInfo {
+            pair_key,
+            pair_type: pair_type.clone(),
+        },
+    )?;
+
+    let sub_msg: Vec<SubMsg> = vec![SubMsg {
+        id: INSTANTIATE_MULTI_PAIR_REPLY_ID,
+        msg: WasmMsg::Instantiate {
+            admin: Some(config.owner.to_string()),
+            code_id: pair_config.code_id,
+            msg: to_binary(&MultiPairInstantiateMsg {
+                asset_infos: asset_infos.clone(),
+                token_code_id: config.token_code_id,
+                factory_addr: env.contract.address.to_string(),
+                init_params,
+            })?,
+            funds: vec![],
+            label: "Astroport pair".to_string(),
+        }
+        .into(),
+        gas_limit: None,
+        reply_on: ReplyOn::Success,
+    }];
+
+    Ok(Response::new()
+        .add_submessages(sub_msg)
+        .add_attributes(vec![
+            attr("action", "create_multi_pair"),
+            attr(
+                "pair",
+                asset_infos
+                    .iter()
+                    .map(|asset_info| asset_info.to_string())
+                    .collect::<Vec<String>>()
+                    .join("-"),
+            ),
+        ]))
+}
+
 /// # Description
 /// The entry point to the contract for processing replies from submessages.
 /// # Params
@@ -387,8 +996,17 @@ pub fn execute_create_pair(
 /// * **msg** is an object of type [`Reply`].
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn reply(deps: DepsMut, _env: Env, msg: Reply) -> Result<Response, ContractError> {
+    let pairs = match msg.id {
+        INSTANTIATE_MULTI_PAIR_REPLY_ID => MULTI_PAIRS,
+        _ => PAIRS,
+    };
+
     let tmp = TMP_PAIR_INFO.load(deps.storage)?;
-    if PAIRS.may_load(deps.storage, &tmp.pair_key)?.is_some() {
+    let pair_type = tmp.pair_type.to_string();
+    if pairs
+        .may_load(deps.storage, (&tmp.pair_key, pair_type.as_bytes()))?
+        .is_some()
+    {
         return Err(ContractError::PairWasRegistered {});
     }
 
@@ -400,7 +1018,11 @@ pub fn reply(deps: DepsMut, _env: Env, msg: Reply) -> Result<Response, ContractE
 
     let pair_contract = addr_validate_to_lower(deps.api, res.get_contract_address())?;
 
-    PAIRS.save(deps.storage, &tmp.pair_key, &pair_contract)?;
+    pairs.save(
+        deps.storage,
+        (&tmp.pair_key, pair_type.as_bytes()),
+        &pair_contract,
+    )?;
 
     Ok(Response::new().add_attributes(vec![
         attr("action", "register"),
@@ -419,12 +1041,16 @@ pub fn reply(deps: DepsMut, _env: Env, msg: Reply) -> Result<Response, ContractE
 ///
 /// * **asset_infos** is an array with two items of type [`AssetInfo`]. These are the asets for which we deregister the pair.
 ///
+/// * **pair_type** is an [`Option`] field of type [`PairType`]. This is the type of the pair to deregister.
+/// It can be omitted if only one pool exists for the assets.
+///
 /// ## Executor
 /// Only the owner can execute this.
 pub fn deregister(
     deps: DepsMut,
     info: MessageInfo,
     asset_infos: [AssetInfo; 2],
+    pair_type: Option<PairType>,
 ) -> Result<Response, ContractError> {
     asset_infos[0].check(deps.api)?;
     asset_infos[1].check(deps.api)?;
@@ -435,21 +1061,17 @@ pub fn deregister(
         return Err(ContractError::Unauthorized {});
     }
 
-    let pair_addr: Addr = PAIRS.load(deps.storage, &pair_key(&asset_infos))?;
-    PAIRS.remove(deps.storage, &pair_key(&asset_infos));
+    let pair_addr = remove_pool(deps.storage, PAIRS, &pair_key(&asset_infos), pair_type)?;
 
     let mut messages: Vec<CosmosMsg> = vec![];
     if let Some(generator) = config.generator_address {
         let pair_info = query_pair_info(deps.as_ref(), &pair_addr)?;
 
         // sets the allocation point to zero for the lp_token
-        messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
-            contract_addr: generator.to_string(),
-            msg: to_binary(&DeactivatePool {
-                lp_token: pair_info.liquidity_token.to_string(),
-            })?,
-            funds: vec![],
-        }));
+        // (concentrated liquidity pairs have no LP token, so there is nothing to deactivate)
+        if pair_info.liquidity_token != Addr::unchecked("") {
+            messages.push(deactivate_pool_msg(&generator, &pair_info.liquidity_token)?);
+        }
     }
 
     Ok(Response::new().add_messages(messages).add_attributes(vec![
@@ -458,6 +1080,110 @@ pub fn deregister(
     ]))
 }
 
+/// ## Description
+/// Removes a pool with more than two assets from the factory. Returns a [`ContractError`] on failure,
+/// otherwise returns a [`Response`] with the specified attributes if the operation was successful.
+/// ## Params
+/// * **deps** is an object of type [`DepsMut`].
+///
+/// * **info** is an object of type [`MessageInfo`].
+///
+/// * **asset_infos** is a vector with items of type [`AssetInfo`]. These are the assets traded in the pool.
+///
+/// * **pair_type** is an [`Option`] field of type [`PairType`]. This is the type of the pool to deregister.
+/// It can be omitted if only one pool exists for the assets.
+///
+/// ## Executor
+/// Only the owner can execute this.
+pub fn deregister_multi_pair(
+    deps: DepsMut,
+    info: MessageInfo,
+    asset_infos: Vec<AssetInfo>,
+    pair_type: Option<PairType>,
+) -> Result<Response, ContractError> {
+    for asset_info in &asset_infos {
+        asset_info.check(deps.api)?;
+    }
+
+    let config = CONFIG.load(deps.storage)?;
+
+    if info.sender != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let pair_addr = remove_pool(
+        deps.storage,
+        MULTI_PAIRS,
+        &multi_pair_key(&asset_infos),
+        pair_type,
+    )?;
+
+    let mut messages: Vec<CosmosMsg> = vec![];
+    if let Some(generator) = config.generator_address {
+        let pair_info = query_multi_pair_info(deps.as_ref(), &pair_addr)?;
+        messages.push(deactivate_pool_msg(&generator, &pair_info.liquidity_token)?);
+    }
+
+    Ok(Response::new().add_messages(messages).add_attributes(vec![
+        attr("action", "deregister_multi_pair"),
+        attr("pair_contract_addr", pair_addr),
+    ]))
+}
+
+/// ## Description
+/// Removes a pool and its fee override and trading state from the factory. Returns the address of the removed pool.
+/// ## Params
+/// * **storage** is an object of type [`Storage`].
+///
+/// * **pairs** is the map to remove the pool from, either [`PAIRS`] or [`MULTI_PAIRS`].
+///
+/// * **pair_key** is the asset key of the pool (see [`pair_key`] and [`multi_pair_key`]).
+///
+/// * **pair_type** is an [`Option`] field of type [`PairType`]. It can be omitted if only one pool exists for the assets.
+fn remove_pool(
+    storage: &mut dyn Storage,
+    pairs: Map<(&[u8], &[u8]), Addr>,
+    pair_key: &[u8],
+    pair_type: Option<PairType>,
+) -> Result<Addr, ContractError> {
+    let pair_type = match pair_type {
+        Some(pair_type) => pair_type.to_string().into_bytes(),
+        None => {
+            let mut pair_types = pairs
+                .prefix(pair_key)
+                .keys(storage, None, None, Order::Ascending);
+            match (pair_types.next(), pair_types.next()) {
+                (Some(pair_type), None) => pair_type,
+                (None, _) => return Err(StdError::not_found("Pair").into()),
+                _ => return Err(ContractError::PairTypeNotSpecified {}),
+            }
+        }
+    };
+
+    let pair_addr = pairs.load(storage, (pair_key, &pair_type))?;
+    pairs.remove(storage, (pair_key, &pair_type));
+    PAIR_FEE_OVERRIDES.remove(storage, &pair_addr);
+    PAIR_STATUSES.remove(storage, &pair_addr);
+
+    Ok(pair_addr)
+}
+
+/// ## Description
+/// Returns a message that sets the Generator allocation points of an LP token to zero.
+/// ## Params
+/// * **generator** is an object of type [`Addr`]. This is the Generator contract address.
+///
+/// * **lp_token** is an object of type [`Addr`]. This is the LP token of the deregistered pool.
+fn deactivate_pool_msg(generator: &Addr, lp_token: &Addr) -> StdResult<CosmosMsg> {
+    Ok(CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: generator.to_string(),
+        msg: to_binary(&DeactivatePool {
+            lp_token: lp_token.to_string(),
+        })?,
+        funds: vec![],
+    }))
+}
+
 /// ## Description
 /// Exposes all the queries available in the contract.
 /// ## Params
@@ -470,21 +1196,52 @@ pub fn deregister(
 /// ## Queries
 /// * **QueryMsg::Config {}** Returns general contract parameters using a custom [`ConfigResponse`] structure.
 ///
-/// * **QueryMsg::Pair { asset_infos }** Returns a [`PairInfo`] object with information about a specific Astroport pair.
+/// * **QueryMsg::Pair { asset_infos, pair_type }** Returns a [`PairInfo`] object with information about a specific Astroport pair.
+/// If the pair type is not specified, the pool is picked by pair type in a fixed priority order (xyk, stable, stable multi,
+/// weighted, concentrated, custom and finally LBP), regardless of which pool was registered first.
+///
+/// * **QueryMsg::PairsByAssets { asset_infos }** Returns an array that contains items of type [`PairInfo`].
+/// This returns information about all pools registered for the specified assets.
+///
+/// * **QueryMsg::MultiPair { asset_infos, pair_type }** Returns a [`MultiPairInfo`] object with information about
+/// a specific pool with more than two assets. If the pair type is not specified, the pool is picked in the same
+/// priority order as for two-asset pairs.
 ///
 /// * **QueryMsg::Pairs { start_after, limit }** Returns an array that contains items of type [`PairInfo`].
 /// This returns information about multiple Astroport pairs
 ///
-/// * **QueryMsg::FeeInfo { pair_type }** Returns the fee structure (total and maker fees) for a specific pair type.
+/// * **QueryMsg::FeeInfo { pair_type, pair_addr }** Returns the fee structure (total and maker fees) for a specific pair type.
+/// If `pair_addr` is specified and the pair has a fee override, the override is returned instead.
+///
+/// * **QueryMsg::PairFeeOverride { pair_addr }** Returns the fee override of a specific pair (if any).
+///
+/// * **QueryMsg::PairStatus { pair_addr }** Returns the trading state of a specific pair.
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
     match msg {
         QueryMsg::Config {} => to_binary(&query_config(deps)?),
-        QueryMsg::Pair { asset_infos } => to_binary(&query_pair(deps, asset_infos)?),
+        QueryMsg::Pair {
+            asset_infos,
+            pair_type,
+        } => to_binary(&query_pair(deps, asset_infos, pair_type)?),
+        QueryMsg::PairsByAssets { asset_infos } => {
+            to_binary(&query_pairs_by_assets(deps, asset_infos)?)
+        }
+        QueryMsg::MultiPair {
+            asset_infos,
+            pair_type,
+        } => to_binary(&query_multi_pair(deps, asset_infos, pair_type)?),
         QueryMsg::Pairs { start_after, limit } => {
             to_binary(&query_pairs(deps, start_after, limit)?)
         }
-        QueryMsg::FeeInfo { pair_type } => to_binary(&query_fee_info(deps, pair_type)?),
+        QueryMsg::FeeInfo {
+            pair_type,
+            pair_addr,
+        } => to_binary(&query_fee_info(deps, pair_type, pair_addr)?),
+        QueryMsg::PairFeeOverride { pair_addr } => {
+            to_binary(&query_pair_fee_override(deps, pair_addr)?)
+        }
+        QueryMsg::PairStatus { pair_addr } => to_binary(&query_pair_status(deps, pair_addr)?),
     }
 }
 
@@ -508,6 +1265,7 @@ pub fn query_config(deps: Deps) -> StdResult<ConfigResponse> {
         fee_address: config.fee_address,
         generator_address: config.generator_address,
         whitelist_code_id: config.whitelist_code_id,
+        guardian: config.guardian,
     };
 
     Ok(resp)
@@ -519,11 +1277,75 @@ pub fn query_config(deps: Deps) -> StdResult<ConfigResponse> {
 /// * **deps** is an object of type [`Deps`].
 ///
 /// * **asset_infos** is an array with two items of type [`AssetInfo`]. These are the assets traded in the pair.
-pub fn query_pair(deps: Deps, asset_infos: [AssetInfo; 2]) -> StdResult<PairInfo> {
-    let pair_addr = PAIRS.load(deps.storage, &pair_key(&asset_infos))?;
+///
+/// * **pair_type** is an [`Option`] field of type [`PairType`]. If it is not specified, the pool is picked by pair
+/// type priority: xyk, stable, stable multi, weighted, concentrated, custom and finally LBP pools.
+pub fn query_pair(
+    deps: Deps,
+    asset_infos: [AssetInfo; 2],
+    pair_type: Option<PairType>,
+) -> StdResult<PairInfo> {
+    let pair_key = pair_key(&asset_infos);
+
+    // A pair registered before v1.3.0 may not be migrated yet. It is the only pair for its assets
+    if let Some(pair_addr) = migration::PAIRSV120.may_load(deps.storage, &pair_key)? {
+        let pair_info = query_pair_info(deps, &pair_addr)?;
+        if pair_type
+            .as_ref()
+            .map_or(true, |t| *t == pair_info.pair_type)
+        {
+            return Ok(pair_info);
+        }
+    }
+
+    let pair_addr = match pair_type {
+        Some(pair_type) => {
+            PAIRS.load(deps.storage, (&pair_key, pair_type.to_string().as_bytes()))?
+        }
+        None => read_default_pair(deps, PAIRS, &pair_key)?,
+    };
     query_pair_info(deps, &pair_addr)
 }
 
+/// ## Description
+/// Returns an array with the data of all pools registered for the assets in `asset_infos`.
+/// ## Params
+/// * **deps** is an object of type [`Deps`].
+///
+/// * **asset_infos** is an array with two items of type [`AssetInfo`]. These are the assets traded in the pools.
+pub fn query_pairs_by_assets(deps: Deps, asset_infos: [AssetInfo; 2]) -> StdResult<PairsResponse> {
+    let pairs = read_pairs_by_assets(deps, &asset_infos)?
+        .iter()
+        .map(|pair_addr| query_pair_info(deps, pair_addr))
+        .collect::<StdResult<Vec<PairInfo>>>()?;
+
+    Ok(PairsResponse { pairs })
+}
+
+/// ## Description
+/// Returns the data of a pool with more than two assets using the assets in `asset_infos` as input.
+/// ## Params
+/// * **deps** is an object of type [`Deps`].
+///
+/// * **asset_infos** is a vector with items of type [`AssetInfo`]. These are the assets traded in the pool.
+///
+/// * **pair_type** is an [`Option`] field of type [`PairType`]. If it is not specified, the pool is picked by pair
+/// type priority: xyk, stable, stable multi, weighted, concentrated, custom and finally LBP pools.
+pub fn query_multi_pair(
+    deps: Deps,
+    asset_infos: Vec<AssetInfo>,
+    pair_type: Option<PairType>,
+) -> StdResult<MultiPairInfo> {
+    let pair_key = multi_pair_key(&asset_infos);
+    let pair_addr = match pair_type {
+        Some(pair_type) => {
+            MULTI_PAIRS.load(deps.storage, (&pair_key, pair_type.to_string().as_bytes()))?
+        }
+        None => read_default_pair(deps, MULTI_PAIRS, &pair_key)?,
+    };
+    query_multi_pair_info(deps, &pair_addr)
+}
+
 /// ## Description
 /// Returns an array with pair data that contains items of type [`PairInfo`]. Querying starts at `start_after` and returns `limit` pairs.
 /// ## Params
@@ -548,21 +1370,65 @@ pub fn query_pairs(
 
 /// ## Description
 /// Returns the fee setup for a specific pair type using a [`FeeInfoResponse`] struct.
+/// If `pair_addr` is specified and the pair has a fee override, the override is returned instead.
 /// ## Params
 /// * **deps** is an object of type [`Deps`].
 ///
 /// * **pair_type** is a [`PairType`] struct that returns the fee information (total and maker fees) for a specific pair type.
-pub fn query_fee_info(deps: Deps, pair_type: PairType) -> StdResult<FeeInfoResponse> {
+///
+/// * **pair_addr** is an [`Option`] field of type [`String`]. This is the pair for which to look up a fee override.
+pub fn query_fee_info(
+    deps: Deps,
+    pair_type: PairType,
+    pair_addr: Option<String>,
+) -> StdResult<FeeInfoResponse> {
     let config = CONFIG.load(deps.storage)?;
     let pair_config = PAIR_CONFIGS.load(deps.storage, pair_type.to_string())?;
 
+    if let Some(pair_addr) = pair_addr {
+        let pair_addr = addr_validate_to_lower(deps.api, &pair_addr)?;
+        if let Some(fee_override) = PAIR_FEE_OVERRIDES.may_load(deps.storage, &pair_addr)? {
+            return Ok(FeeInfoResponse {
+                fee_address: config.fee_address,
+                total_fee_bps: fee_override.total_fee_bps,
+                maker_fee_bps: fee_override.maker_fee_bps,
+                maker_fee_as_lp: pair_config.maker_fee_as_lp,
+            });
+        }
+    }
+
     Ok(FeeInfoResponse {
         fee_address: config.fee_address,
         total_fee_bps: pair_config.total_fee_bps,
         maker_fee_bps: pair_config.maker_fee_bps,
+        maker_fee_as_lp: pair_config.maker_fee_as_lp,
     })
 }
 
+/// ## Description
+/// Returns the fee override of a specific pair in an [`Option`] of type [`FeeOverride`].
+/// ## Params
+/// * **deps** is an object of type [`Deps`].
+///
+/// * **pair_addr** is an object of type [`String`]. This is the pair for which we return the fee override.
+pub fn query_pair_fee_override(deps: Deps, pair_addr: String) -> StdResult<Option<FeeOverride>> {
+    let pair_addr = addr_validate_to_lower(deps.api, &pair_addr)?;
+    PAIR_FEE_OVERRIDES.may_load(deps.storage, &pair_addr)
+}
+
+/// ## Description
+/// Returns the trading state of a specific pair in an object of type [`PairStatus`].
+/// ## Params
+/// * **deps** is an object of type [`Deps`].
+///
+/// * **pair_addr** is an object of type [`String`]. This is the pair for which we return the state.
+pub fn query_pair_status(deps: Deps, pair_addr: String) -> StdResult<PairStatus> {
+    let pair_addr = addr_validate_to_lower(deps.api, &pair_addr)?;
+    Ok(PAIR_STATUSES
+        .may_load(deps.storage, &pair_addr)?
+        .unwrap_or_default())
+}
+
 /// ## Description
 /// Used for contract migration. Returns a default object of type [`Response`].
 /// ## Params
@@ -572,7 +1438,7 @@ pub fn query_fee_info(deps: Deps, pair_type: PairType) -> StdResult<FeeInfoRespo
 ///
 /// * **_msg** is an object of type [`MigrateMsg`].
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn migrate(deps: DepsMut, _env: Env, msg: MigrateMsg) -> Result<Response, ContractError> {
+pub fn migrate(mut deps: DepsMut, _env: Env, msg: MigrateMsg) -> Result<Response, ContractError> {
     let contract_version = get_contract_version(deps.storage)?;
 
     match contract_version.contract.as_ref() {
@@ -588,13 +1454,26 @@ pub fn migrate(deps: DepsMut, _env: Env, msg: MigrateMsg) -> Result<Response, Co
                     generator_address: config_v100.generator_address,
                     owner: config_v100.owner,
                     token_code_id: config_v100.token_code_id,
+                    guardian: None,
                 };
 
                 CONFIG.save(deps.storage, &new_config)?;
 
-                migrate_pair_configs_to_v120(deps.storage)?
+                migrate_pair_configs_to_v120(deps.storage)?;
+                migrate_pairs_to_v130(deps.branch(), None)?;
+            }
+            "1.1.0" => {
+                migrate_pair_configs_to_v120(deps.storage)?;
+                migrate_pairs_to_v130(deps.branch(), None)?;
+            }
+            "1.2.0" => {
+                migrate_pairs_to_v130(deps.branch(), None)?;
+                migrate_pair_configs_to_v140(deps.storage)?
+            }
+            "1.3.0" => {
+                migrate_pair_configs_to_v140(deps.storage)?;
+                migrate_multi_pairs_to_v140(deps.branch())?
             }
-            "1.1.0" => migrate_pair_configs_to_v120(deps.storage)?,
             _ => return Err(ContractError::MigrationError {}),
         },
         _ => return Err(ContractError::MigrationError {}),
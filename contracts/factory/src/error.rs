@@ -1,3 +1,4 @@
+use astroport::pair_stable_multi::{MAX_ASSETS_NUM, MIN_ASSETS_NUM};
 use cosmwasm_std::StdError;
 use thiserror::Error;
 
@@ -17,12 +18,24 @@ pub enum ContractError {
     #[error("Pair was already registered")]
     PairWasRegistered {},
 
+    #[error("Several pools exist for the assets, the pair type must be specified")]
+    PairTypeNotSpecified {},
+
     #[error("Duplicate of pair configs")]
     PairConfigDuplicate {},
 
     #[error("Fee bps in pair config must be smaller than or equal to 10,000")]
     PairConfigInvalidFeeBps {},
 
+    #[error("Only XYK pairs can collect the Maker fee in LP tokens")]
+    PairConfigMakerFeeAsLpNotSupported {},
+
+    #[error("Fee bps in fee override must be smaller than or equal to 10,000")]
+    FeeOverrideInvalidFeeBps {},
+
+    #[error("Pair is not registered in the factory")]
+    PairNotRegistered {},
+
     #[error("Pair config not found")]
     PairConfigNotFound {},
 
@@ -32,6 +45,16 @@ pub enum ContractError {
     #[error("Doubling assets in asset infos")]
     DoublingAssets {},
 
+    #[error(
+        "A multi-asset pool must contain from {} to {} assets",
+        MIN_ASSETS_NUM,
+        MAX_ASSETS_NUM
+    )]
+    InvalidNumberOfAssets {},
+
     #[error("Contract can't be migrated!")]
     MigrationError {},
+
+    #[error("Pairs registered before v1.3.0 must be migrated first")]
+    PairsMigrationPending {},
 }
@@ -1,6 +1,7 @@
-use crate::state::PAIR_CONFIGS;
+use crate::querier::{query_multi_pair_info, query_pair_info};
+use crate::state::{multi_pair_key, DEFAULT_LIMIT, MAX_LIMIT, MULTI_PAIRS, PAIRS, PAIR_CONFIGS};
 use astroport::factory::{PairConfig, PairType};
-use cosmwasm_std::{Addr, StdError, Storage};
+use cosmwasm_std::{Addr, DepsMut, Order, StdError, StdResult, Storage};
 use cw_storage_plus::{Item, Map};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
@@ -60,9 +61,130 @@ pub fn migrate_pair_configs_to_v120(storage: &mut dyn Storage) -> Result<(), Std
             maker_fee_bps: pair_configs_v110.maker_fee_bps,
             is_disabled: pair_configs_v110.is_disabled.unwrap_or(false),
             is_generator_disabled: false,
+            maker_fee_as_lp: false,
         };
         PAIR_CONFIGS.save(storage, key, &pair_config)?;
     }
 
     Ok(())
 }
+
+/// Pairs were stored by their asset key only before v1.3.0
+pub const PAIRSV120: Map<&[u8], Addr> = Map::new("pair_info");
+
+/// ## Description
+/// Moves the next batch of registered pairs to the [`PAIRS`] map where the key also contains the pair type.
+/// The pair type is read from each pair contract, so the pairs are moved in batches to keep the gas used by
+/// a transaction bounded. Returns the number of moved pairs and whether all pairs were moved.
+/// ## Params
+/// `deps` is an object of type [`DepsMut`].
+///
+/// `limit` is the number of pairs to move. It is an [`Option`].
+pub fn migrate_pairs_to_v130(deps: DepsMut, limit: Option<u32>) -> StdResult<(usize, bool)> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT) as usize;
+
+    // One more pair is read to find out whether any pairs are left after the batch
+    let mut pairs = PAIRSV120
+        .range(deps.storage, None, None, Order::Ascending)
+        .take(limit + 1)
+        .collect::<StdResult<Vec<(Vec<u8>, Addr)>>>()?;
+    let finished = pairs.len() <= limit;
+    pairs.truncate(limit);
+
+    for (pair_key, pair_addr) in &pairs {
+        let pair_info = query_pair_info(deps.as_ref(), pair_addr)?;
+        PAIRS.save(
+            deps.storage,
+            (pair_key, pair_info.pair_type.to_string().as_bytes()),
+            pair_addr,
+        )?;
+        PAIRSV120.remove(deps.storage, pair_key);
+    }
+
+    Ok((pairs.len(), finished))
+}
+
+/// ## Description
+/// Returns true if some pairs registered before v1.3.0 still have to be moved to the [`PAIRS`] map.
+/// ## Params
+/// `storage` is an object of type [`Storage`].
+pub fn pairs_migration_pending(storage: &dyn Storage) -> bool {
+    PAIRSV120
+        .keys(storage, None, None, Order::Ascending)
+        .next()
+        .is_some()
+}
+
+/// This structure describes a pair's configuration before the Maker fee could be collected in LP tokens.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PairConfigV130 {
+    /// Pair contract code ID that's used to create new pairs of this type
+    pub code_id: u64,
+    /// The pair type (e.g XYK, stable)
+    pub pair_type: PairType,
+    /// The total amount of fees charged for the swap
+    pub total_fee_bps: u16,
+    /// The amount of fees that go to the Maker contract
+    pub maker_fee_bps: u16,
+    /// Whether a pair type is disabled or not
+    pub is_disabled: bool,
+    /// Whether pairs of this type can get an ASTRO generator or not
+    pub is_generator_disabled: bool,
+}
+
+pub const PAIR_CONFIGSV130: Map<String, PairConfigV130> = Map::new("pair_configs");
+
+/// ## Description
+/// Adds the Maker fee mode to the stored pair configs. Existing pair types keep sending the Maker fee on every swap.
+pub fn migrate_pair_configs_to_v140(storage: &mut dyn Storage) -> StdResult<()> {
+    let pair_configs = PAIR_CONFIGSV130
+        .range(storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<(Vec<u8>, PairConfigV130)>>>()?;
+
+    for (key, pair_config_v130) in pair_configs {
+        let pair_config = PairConfig {
+            code_id: pair_config_v130.code_id,
+            pair_type: pair_config_v130.pair_type,
+            total_fee_bps: pair_config_v130.total_fee_bps,
+            maker_fee_bps: pair_config_v130.maker_fee_bps,
+            is_disabled: pair_config_v130.is_disabled,
+            is_generator_disabled: pair_config_v130.is_generator_disabled,
+            maker_fee_as_lp: false,
+        };
+        PAIR_CONFIGS.save(storage, String::from_utf8(key)?, &pair_config)?;
+    }
+
+    Ok(())
+}
+
+/// ## Description
+/// Moves the registered pools with more than two assets to their length-prefixed asset keys (see [`multi_pair_key`]).
+/// The old keys can't be split back into assets, so the assets are read from each pool contract.
+pub fn migrate_multi_pairs_to_v140(deps: DepsMut) -> StdResult<()> {
+    let pools = MULTI_PAIRS
+        .range(deps.storage, None, None, Order::Ascending)
+        .map(|item| {
+            let (raw_key, pair_addr) = item?;
+            // Raw keys consist of the length-prefixed asset key and the pair type
+            let len = u16::from_be_bytes([raw_key[0], raw_key[1]]) as usize;
+            let (asset_key, pair_type) = raw_key[2..].split_at(len);
+            Ok((asset_key.to_vec(), pair_type.to_vec(), pair_addr))
+        })
+        .collect::<StdResult<Vec<(Vec<u8>, Vec<u8>, Addr)>>>()?;
+
+    // All old keys are removed first, so no pool is removed after it was saved under its new key
+    for (asset_key, pair_type, _) in &pools {
+        MULTI_PAIRS.remove(deps.storage, (asset_key, pair_type));
+    }
+
+    for (_, pair_type, pair_addr) in &pools {
+        let pair_info = query_multi_pair_info(deps.as_ref(), pair_addr)?;
+        MULTI_PAIRS.save(
+            deps.storage,
+            (&multi_pair_key(&pair_info.asset_infos), pair_type),
+            pair_addr,
+        )?;
+    }
+
+    Ok(())
+}
@@ -1,4 +1,4 @@
-use astroport::asset::PairInfo;
+use astroport::asset::{MultiPairInfo, PairInfo};
 use astroport::pair::QueryMsg;
 use cosmwasm_std::testing::{MockApi, MockQuerier, MockStorage, MOCK_CONTRACT_ADDR};
 use cosmwasm_std::{
@@ -30,12 +30,14 @@ pub struct WasmMockQuerier {
 #[derive(Clone, Default)]
 pub struct AstroportPairQuerier {
     pairs: HashMap<String, PairInfo>,
+    multi_pairs: HashMap<String, MultiPairInfo>,
 }
 
 impl AstroportPairQuerier {
     pub fn new(pairs: &[(&String, &PairInfo)]) -> Self {
         AstroportPairQuerier {
             pairs: pairs_to_map(pairs),
+            multi_pairs: HashMap::new(),
         }
     }
 }
@@ -70,6 +72,10 @@ impl WasmMockQuerier {
             QueryRequest::Wasm(WasmQuery::Smart {contract_addr, msg})// => {
                 => match from_binary(&msg).unwrap() {
                     QueryMsg::Pair {} => {
+                        if let Some(multi_pair_info) = self.astroport_pair_querier.multi_pairs.get(contract_addr) {
+                            return SystemResult::Ok(to_binary(multi_pair_info).into());
+                        }
+
                        let pair_info: PairInfo =
                         match self.astroport_pair_querier.pairs.get(contract_addr) {
                             Some(v) => v.clone(),
@@ -101,4 +107,13 @@ impl WasmMockQuerier {
     pub fn with_astroport_pairs(&mut self, pairs: &[(&String, &PairInfo)]) {
         self.astroport_pair_querier = AstroportPairQuerier::new(pairs);
     }
+
+    // Configure the Astroport pools with more than two assets
+    pub fn with_astroport_multi_pairs(&mut self, pairs: &[(&String, &MultiPairInfo)]) {
+        for (addr, pair) in pairs {
+            self.astroport_pair_querier
+                .multi_pairs
+                .insert(addr.to_string(), (*pair).clone());
+        }
+    }
 }
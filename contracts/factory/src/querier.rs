@@ -1,5 +1,6 @@
-use astroport::asset::PairInfo;
+use astroport::asset::{MultiPairInfo, PairInfo};
 use astroport::pair::QueryMsg;
+use astroport::pair_stable_multi::QueryMsg as MultiPairQueryMsg;
 use cosmwasm_std::{to_binary, Addr, Deps, QueryRequest, StdResult, WasmQuery};
 
 /// ## Description
@@ -12,3 +13,14 @@ pub fn query_pair_info(deps: Deps, pair_contract: &Addr) -> StdResult<PairInfo>
         msg: to_binary(&QueryMsg::Pair {})?,
     }))
 }
+
+/// ## Description
+/// Returns information about a pool with more than two assets (using the [`MultiPairInfo`] struct).
+/// ## Params
+/// `pair_contract` is a param of type [`Addr`]. This is the pool for which to retrieve information.
+pub fn query_multi_pair_info(deps: Deps, pair_contract: &Addr) -> StdResult<MultiPairInfo> {
+    deps.querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
+        contract_addr: pair_contract.to_string(),
+        msg: to_binary(&MultiPairQueryMsg::Pair {})?,
+    }))
+}
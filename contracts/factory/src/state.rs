@@ -1,13 +1,13 @@
-use cw_storage_plus::{Bound, Item, Map};
+use cw_storage_plus::{Bound, Item, Map, PrimaryKey};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use cosmwasm_std::{Addr, Deps, Order};
+use cosmwasm_std::{Addr, Deps, Order, StdError, StdResult};
 
 use astroport::asset::AssetInfo;
 
 use astroport::common::OwnershipProposal;
-use astroport::factory::PairConfig;
+use astroport::factory::{FeeOverride, PairConfig, PairStatus, PairType};
 
 /// ## Description
 /// This structure holds the main contract parameters.
@@ -23,6 +23,8 @@ pub struct Config {
     pub fee_address: Option<Addr>,
     /// CW1 whitelist contract code id used to store 3rd party generator staking rewards
     pub whitelist_code_id: u64,
+    /// Address allowed to halt pairs in an incident
+    pub guardian: Option<Addr>,
 }
 
 /// ## Description
@@ -30,6 +32,7 @@ pub struct Config {
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct TmpPairInfo {
     pub pair_key: Vec<u8>,
+    pub pair_type: PairType,
 }
 
 /// Saves a pair's key
@@ -38,32 +41,156 @@ pub const TMP_PAIR_INFO: Item<TmpPairInfo> = Item::new("tmp_pair_info");
 /// Saves factory settings
 pub const CONFIG: Item<Config> = Item::new("config");
 
-/// Saves created pairs (from olders to latest)
-pub const PAIRS: Map<&[u8], Addr> = Map::new("pair_info");
+/// Saves created pairs. The key consists of the pair's asset key (see [`pair_key`]) and its pair type
+pub const PAIRS: Map<(&[u8], &[u8]), Addr> = Map::new("pairs");
+
+/// Saves created pools with more than two assets. The key consists of the pool's asset key (see [`multi_pair_key`]) and its pair type
+pub const MULTI_PAIRS: Map<(&[u8], &[u8]), Addr> = Map::new("multi_pairs");
 
 /// ## Description
-/// Calculates a pair key from the specified parameters in the `asset_infos` variable.
+/// Calculates a pair's asset key from the specified parameters in the `asset_infos` variable.
+/// All pools for the same assets share this key and are told apart by their pair type.
 /// ## Params
-/// `asset_infos` is an array with two items of type [`AssetInfo`].
-pub fn pair_key(asset_infos: &[AssetInfo; 2]) -> Vec<u8> {
+/// `asset_infos` is a slice of [`AssetInfo`] items. The order of the assets doesn't matter.
+pub fn pair_key(asset_infos: &[AssetInfo]) -> Vec<u8> {
+    let mut asset_infos = asset_infos.to_vec();
+    asset_infos.sort_by(|a, b| a.as_bytes().cmp(b.as_bytes()));
+
+    asset_infos
+        .iter()
+        .map(|asset_info| asset_info.as_bytes())
+        .collect::<Vec<&[u8]>>()
+        .concat()
+}
+
+/// ## Description
+/// Calculates the asset key of a pool with more than two assets. Unlike in [`pair_key`], every asset is
+/// prefixed with its length, so different asset lists never share a key.
+/// ## Params
+/// `asset_infos` is a slice of [`AssetInfo`] items. The order of the assets doesn't matter.
+pub fn multi_pair_key(asset_infos: &[AssetInfo]) -> Vec<u8> {
     let mut asset_infos = asset_infos.to_vec();
     asset_infos.sort_by(|a, b| a.as_bytes().cmp(b.as_bytes()));
 
-    [asset_infos[0].as_bytes(), asset_infos[1].as_bytes()].concat()
+    asset_infos
+        .iter()
+        .flat_map(|asset_info| {
+            let asset_key = asset_info.as_bytes();
+            [&(asset_key.len() as u16).to_be_bytes(), asset_key].concat()
+        })
+        .collect()
 }
 
 /// Saves pair type configurations
 pub const PAIR_CONFIGS: Map<String, PairConfig> = Map::new("pair_configs");
 
+/// Saves fee overrides for specific pairs
+pub const PAIR_FEE_OVERRIDES: Map<&Addr, FeeOverride> = Map::new("pair_fee_overrides");
+
+/// Saves the trading states of halted pairs. Pairs without a state are active
+pub const PAIR_STATUSES: Map<&Addr, PairStatus> = Map::new("pair_statuses");
+
+/// ## Description
+/// This structure describes the pairs that still have to refresh the factory parameters they cache.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PendingRefresh {
+    /// The names of the pair types to refresh
+    pub pair_types: Vec<String>,
+    /// The raw [`PAIRS`] key of the last refreshed pair, or the raw [`MULTI_PAIRS`] key if `multi_pairs` is set
+    pub last_key: Option<Vec<u8>>,
+    /// Whether all pairs in [`PAIRS`] were refreshed and the pools with more than two assets are refreshed now
+    #[serde(default)]
+    pub multi_pairs: bool,
+}
+
+/// Saves the pairs that are waiting for a refresh of their cached factory parameters
+pub const PENDING_REFRESH: Item<PendingRefresh> = Item::new("pending_refresh");
+
 /// ## Pagination settings
 /// The maximum limit for reading pairs from [`PAIRS`]
-const MAX_LIMIT: u32 = 30;
+pub const MAX_LIMIT: u32 = 30;
 /// The default limit for reading pairs from [`PAIRS`]
-const DEFAULT_LIMIT: u32 = 10;
+pub const DEFAULT_LIMIT: u32 = 10;
+
+/// ## Description
+/// Returns the addresses of all pools registered for the specified assets ordered by pair type.
+/// ## Params
+/// `deps` is an object of type [`Deps`].
+///
+/// `asset_infos` is an array with two items of type [`AssetInfo`].
+pub fn read_pairs_by_assets(deps: Deps, asset_infos: &[AssetInfo; 2]) -> StdResult<Vec<Addr>> {
+    PAIRS
+        .prefix(&pair_key(asset_infos))
+        .range(deps.storage, None, None, Order::Ascending)
+        .map(|item| item.map(|(_, pair_addr)| pair_addr))
+        .collect()
+}
+
+/// ## Description
+/// Returns the rank of a pair type when a pool is picked for a pair query that doesn't specify the pair type.
+/// Pools with a lower rank are picked first: xyk, then stable, stable multi, weighted and concentrated pools.
+/// Custom pools come after these. Liquidity bootstrapping pools are only picked if no other pool exists for
+/// the assets because their price is deliberately skewed.
+/// ## Params
+/// `pair_type` is the pair type part of a [`PAIRS`] or [`MULTI_PAIRS`] key.
+fn default_pair_type_rank(pair_type: &[u8]) -> usize {
+    let priority = [
+        PairType::Xyk {},
+        PairType::Stable {},
+        PairType::StableMulti {},
+        PairType::Weighted {},
+        PairType::Concentrated {},
+    ];
+
+    match priority
+        .iter()
+        .position(|t| t.to_string().as_bytes() == pair_type)
+    {
+        Some(rank) => rank,
+        None if PairType::Lbp {}.to_string().as_bytes() == pair_type => priority.len() + 1,
+        None => priority.len(),
+    }
+}
+
+/// ## Description
+/// Returns the address of the pool that is picked for the assets when a pair query doesn't specify the pair type.
+/// Pools are picked by pair type rank (see [`default_pair_type_rank`]), and by pair type key if the rank is the same.
+/// ## Params
+/// `deps` is an object of type [`Deps`].
+///
+/// `pairs` is the map to read the pools from, either [`PAIRS`] or [`MULTI_PAIRS`].
+///
+/// `pair_key` is the asset key of the pools (see [`pair_key`] and [`multi_pair_key`]).
+pub fn read_default_pair(
+    deps: Deps,
+    pairs: Map<(&[u8], &[u8]), Addr>,
+    pair_key: &[u8],
+) -> StdResult<Addr> {
+    let mut default_pair: Option<(usize, Addr)> = None;
+    for item in pairs
+        .prefix(pair_key)
+        .range(deps.storage, None, None, Order::Ascending)
+    {
+        let (pair_type, pair_addr) = item?;
+        let rank = default_pair_type_rank(&pair_type);
+        // Pools are read in pair type key order, so only a lower rank replaces the current pick
+        if default_pair
+            .as_ref()
+            .map_or(true, |(default_rank, _)| rank < *default_rank)
+        {
+            default_pair = Some((rank, pair_addr));
+        }
+    }
+
+    default_pair
+        .map(|(_, pair_addr)| pair_addr)
+        .ok_or_else(|| StdError::not_found("Pair"))
+}
 
 /// ## Description
 /// Reads pairs from the [`PAIRS`] vector according to the `start_after` and `limit` variables.
 /// Otherwise, it returns the default number of pairs, starting from the oldest one.
+/// A page is never split between pools of the same assets, so it may contain more than `limit` pairs.
 /// ## Params
 /// `start_after` is the pair from which the function starts to fetch results. It is an [`Option`].
 ///
@@ -76,32 +203,95 @@ pub fn read_pairs(
     let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
     let start = calc_range_start(start_after).map(Bound::exclusive);
 
-    PAIRS
-        .range(deps.storage, start, None, Order::Ascending)
-        .take(limit)
-        .map(|item| {
-            let (_, pair_addr) = item.unwrap();
-            pair_addr
-        })
-        .collect()
+    let mut pairs = vec![];
+    let mut last_asset_key: Vec<u8> = vec![];
+    for item in PAIRS.range(deps.storage, start, None, Order::Ascending) {
+        let (key, pair_addr) = item.unwrap();
+        let asset_key = asset_key_from_raw(&key).to_vec();
+        if pairs.len() >= limit && asset_key != last_asset_key {
+            break;
+        }
+
+        last_asset_key = asset_key;
+        pairs.push(pair_addr);
+    }
+
+    pairs
+}
+
+/// ## Description
+/// Reads the next batch of pairs of the pending pair types from [`PAIRS`] and then from [`MULTI_PAIRS`].
+/// Returns the pairs and the pairs that are left after the batch, or [`None`] if no pairs are left.
+/// ## Params
+/// `deps` is an object of type [`Deps`].
+///
+/// `pending` is an object of type [`PendingRefresh`]. These are the pairs that have to be refreshed.
+///
+/// `limit` is the number of pairs to read. It is an [`Option`].
+pub fn read_pairs_to_refresh(
+    deps: Deps,
+    pending: &PendingRefresh,
+    limit: Option<u32>,
+) -> StdResult<(Vec<Addr>, Option<PendingRefresh>)> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT) as usize;
+
+    let mut pairs = vec![];
+    for (multi_pairs, map) in [(false, &PAIRS), (true, &MULTI_PAIRS)] {
+        if pending.multi_pairs && !multi_pairs {
+            continue;
+        }
+
+        let mut last_key = if pending.multi_pairs == multi_pairs {
+            pending.last_key.clone()
+        } else {
+            None
+        };
+        let start = last_key.clone().map(Bound::exclusive);
+        for item in map.range(deps.storage, start, None, Order::Ascending) {
+            let (key, pair_addr) = item?;
+            let pair_type = String::from_utf8_lossy(&key[asset_key_from_raw(&key).len()..]);
+            if !pending.pair_types.iter().any(|t| *t == pair_type) {
+                continue;
+            }
+
+            // Another pair is left, so the batch stops at the last pair read
+            if pairs.len() >= limit {
+                return Ok((
+                    pairs,
+                    Some(PendingRefresh {
+                        pair_types: pending.pair_types.clone(),
+                        last_key,
+                        multi_pairs,
+                    }),
+                ));
+            }
+
+            last_key = Some(key);
+            pairs.push(pair_addr);
+        }
+    }
+
+    Ok((pairs, None))
+}
+
+/// ## Description
+/// Returns the asset key part of a raw [`PAIRS`] or [`MULTI_PAIRS`] key.
+/// ## Params
+/// `raw_key` is the raw key of a [`PAIRS`] item (a length-prefixed asset key followed by the pair type).
+fn asset_key_from_raw(raw_key: &[u8]) -> &[u8] {
+    let len = u16::from_be_bytes([raw_key[0], raw_key[1]]) as usize;
+    &raw_key[..2 + len]
 }
 
 /// ## Description
-/// Calculates the key of a pair from which to start reading data.
+/// Calculates the key of a pair from which to start reading data. All pools for the `start_after` assets are skipped.
 /// ## Params
 /// `start_after` is an [`Option`] type that accepts two [`AssetInfo`] elements.
 /// It is the token pair which we use to determine the start index for a range when returning data for multiple pairs
 fn calc_range_start(start_after: Option<[AssetInfo; 2]>) -> Option<Vec<u8>> {
     start_after.map(|asset_infos| {
-        let mut asset_infos = asset_infos.to_vec();
-        asset_infos.sort_by(|a, b| a.as_bytes().cmp(b.as_bytes()));
-
-        let mut v = [asset_infos[0].as_bytes(), asset_infos[1].as_bytes()]
-            .concat()
-            .as_slice()
-            .to_vec();
-        v.push(1);
-        v
+        // Pair type names are ASCII strings, so they are all smaller than 0xFF
+        (pair_key(&asset_infos).as_slice(), [u8::MAX].as_ref()).joined_key()
     })
 }
 
@@ -1,23 +1,27 @@
 use cosmwasm_std::{
-    attr, from_binary, to_binary, Addr, ContractResult, Reply, ReplyOn, SubMsg,
-    SubMsgExecutionResponse, WasmMsg,
+    attr, from_binary, to_binary, Addr, ContractResult, CosmosMsg, Deps, Reply, ReplyOn, Response,
+    StdError, SubMsg, SubMsgExecutionResponse, WasmMsg,
 };
 
+use crate::migration::{migrate_multi_pairs_to_v140, PAIRSV120};
 use crate::mock_querier::mock_dependencies;
-use crate::state::CONFIG;
+use crate::state::{multi_pair_key, pair_key, CONFIG, MULTI_PAIRS, PAIRS};
 use crate::{
     contract::{execute, instantiate, query},
     error::ContractError,
 };
 
-use astroport::asset::{AssetInfo, PairInfo};
+use astroport::asset::{AssetInfo, MultiPairInfo, PairInfo};
 use astroport::factory::{
-    ConfigResponse, ExecuteMsg, InstantiateMsg, PairConfig, PairType, PairsResponse, QueryMsg,
+    ConfigResponse, ExecuteMsg, FeeInfoResponse, FeeOverride, InstantiateMsg, PairConfig,
+    PairStatus, PairType, PairsResponse, QueryMsg,
 };
 
 use crate::contract::reply;
 use crate::response::MsgInstantiateContractResponse;
-use astroport::pair::InstantiateMsg as PairInstantiateMsg;
+use astroport::generator::ExecuteMsg::DeactivatePool;
+use astroport::pair::{ExecuteMsg as PairExecuteMsg, InstantiateMsg as PairInstantiateMsg};
+use astroport::pair_stable_multi::InstantiateMsg as MultiPairInstantiateMsg;
 use cosmwasm_std::testing::{mock_env, mock_info, MOCK_CONTRACT_ADDR};
 use protobuf::Message;
 
@@ -42,6 +46,7 @@ fn proper_initialization() {
                 maker_fee_bps: 10,
                 is_disabled: false,
                 is_generator_disabled: false,
+                maker_fee_as_lp: false,
             },
             PairConfig {
                 code_id: 325u64,
@@ -50,6 +55,7 @@ fn proper_initialization() {
                 maker_fee_bps: 10,
                 is_disabled: false,
                 is_generator_disabled: false,
+                maker_fee_as_lp: false,
             },
         ],
         token_code_id: 123u64,
@@ -73,6 +79,7 @@ fn proper_initialization() {
             maker_fee_bps: 10,
             is_disabled: false,
             is_generator_disabled: false,
+            maker_fee_as_lp: false,
         }],
         token_code_id: 123u64,
         fee_address: None,
@@ -98,6 +105,7 @@ fn proper_initialization() {
                 maker_fee_bps: 10,
                 is_disabled: false,
                 is_generator_disabled: false,
+                maker_fee_as_lp: false,
             },
             PairConfig {
                 code_id: 123u64,
@@ -106,6 +114,7 @@ fn proper_initialization() {
                 maker_fee_bps: 10,
                 is_disabled: false,
                 is_generator_disabled: false,
+                maker_fee_as_lp: false,
             },
         ],
         token_code_id: 123u64,
@@ -139,6 +148,7 @@ fn update_config() {
         maker_fee_bps: 166,
         is_disabled: false,
         is_generator_disabled: false,
+        maker_fee_as_lp: false,
     }];
 
     let msg = InstantiateMsg {
@@ -286,6 +296,7 @@ fn update_pair_config() {
         maker_fee_bps: 10,
         is_disabled: false,
         is_generator_disabled: false,
+        maker_fee_as_lp: false,
     }];
 
     let msg = InstantiateMsg {
@@ -316,6 +327,7 @@ fn update_pair_config() {
         maker_fee_bps: 2,
         is_disabled: false,
         is_generator_disabled: false,
+        maker_fee_as_lp: false,
     };
 
     // Unauthorized err
@@ -339,12 +351,30 @@ fn update_pair_config() {
             maker_fee_bps: 10_001,
             is_disabled: false,
             is_generator_disabled: false,
+            maker_fee_as_lp: false,
         },
     };
 
     let res = execute(deps.as_mut(), env.clone(), info, msg).unwrap_err();
     assert_eq!(res, ContractError::PairConfigInvalidFeeBps {});
 
+    // Only XYK pairs can collect the Maker fee in LP tokens
+    let info = mock_info(owner.clone(), &[]);
+    let msg = ExecuteMsg::UpdatePairConfig {
+        config: PairConfig {
+            code_id: 325u64,
+            pair_type: PairType::Stable {},
+            total_fee_bps: 5,
+            maker_fee_bps: 5000,
+            is_disabled: false,
+            is_generator_disabled: false,
+            maker_fee_as_lp: true,
+        },
+    };
+
+    let res = execute(deps.as_mut(), env.clone(), info, msg).unwrap_err();
+    assert_eq!(res, ContractError::PairConfigMakerFeeAsLpNotSupported {});
+
     let info = mock_info(owner.clone(), &[]);
     let msg = ExecuteMsg::UpdatePairConfig {
         config: pair_config.clone(),
@@ -366,6 +396,7 @@ fn update_pair_config() {
         maker_fee_bps: 20,
         is_disabled: false,
         is_generator_disabled: false,
+        maker_fee_as_lp: false,
     };
 
     let info = mock_info(owner.clone(), &[]);
@@ -395,6 +426,7 @@ fn create_pair() {
         maker_fee_bps: 10,
         is_disabled: false,
         is_generator_disabled: false,
+        maker_fee_as_lp: false,
     };
 
     let msg = InstantiateMsg {
@@ -495,6 +527,7 @@ fn register() {
             maker_fee_bps: 10,
             is_disabled: false,
             is_generator_disabled: false,
+            maker_fee_as_lp: false,
         }],
         token_code_id: 123u64,
         fee_address: None,
@@ -563,6 +596,7 @@ fn register() {
         env.clone(),
         QueryMsg::Pair {
             asset_infos: asset_infos.clone(),
+            pair_type: None,
         },
     )
     .unwrap();
@@ -702,6 +736,7 @@ fn register() {
         info,
         ExecuteMsg::Deregister {
             asset_infos: asset_infos_2.clone(),
+            pair_type: None,
         },
     )
     .unwrap_err();
@@ -717,6 +752,7 @@ fn register() {
         info,
         ExecuteMsg::Deregister {
             asset_infos: asset_infos_2.clone(),
+            pair_type: None,
         },
     )
     .unwrap();
@@ -740,3 +776,1609 @@ fn register() {
         },]
     );
 }
+
+#[test]
+fn multiple_pools_per_assets() {
+    let mut deps = mock_dependencies(&[]);
+    let owner = "owner0000";
+
+    let msg = InstantiateMsg {
+        pair_configs: vec![
+            PairConfig {
+                code_id: 123u64,
+                pair_type: PairType::Xyk {},
+                total_fee_bps: 30,
+                maker_fee_bps: 3333,
+                is_disabled: false,
+                is_generator_disabled: false,
+                maker_fee_as_lp: false,
+            },
+            PairConfig {
+                code_id: 325u64,
+                pair_type: PairType::Stable {},
+                total_fee_bps: 5,
+                maker_fee_bps: 5000,
+                is_disabled: false,
+                is_generator_disabled: false,
+                maker_fee_as_lp: false,
+            },
+        ],
+        token_code_id: 123u64,
+        fee_address: None,
+        generator_address: None,
+        owner: owner.to_string(),
+        whitelist_code_id: 234u64,
+    };
+
+    let env = mock_env();
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    let asset_infos = [
+        AssetInfo::Token {
+            contract_addr: Addr::unchecked("asset0000"),
+        },
+        AssetInfo::Token {
+            contract_addr: Addr::unchecked("asset0001"),
+        },
+    ];
+
+    let xyk_addr = "pair0000".to_string();
+    let xyk_info = PairInfo {
+        asset_infos: asset_infos.clone(),
+        contract_addr: Addr::unchecked("pair0000"),
+        liquidity_token: Addr::unchecked("liquidity0000"),
+        pair_type: PairType::Xyk {},
+    };
+    let stable_addr = "pair0001".to_string();
+    let stable_info = PairInfo {
+        asset_infos: asset_infos.clone(),
+        contract_addr: Addr::unchecked("pair0001"),
+        liquidity_token: Addr::unchecked("liquidity0001"),
+        pair_type: PairType::Stable {},
+    };
+    deps.querier
+        .with_astroport_pairs(&[(&xyk_addr, &xyk_info), (&stable_addr, &stable_info)]);
+
+    for (pair_type, pair_addr) in [
+        (PairType::Xyk {}, xyk_addr.clone()),
+        (PairType::Stable {}, stable_addr.clone()),
+    ] {
+        let msg = ExecuteMsg::CreatePair {
+            pair_type,
+            asset_infos: asset_infos.clone(),
+            init_params: None,
+        };
+        let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+        let data = MsgInstantiateContractResponse {
+            contract_address: pair_addr,
+            data: vec![],
+            unknown_fields: Default::default(),
+            cached_size: Default::default(),
+        }
+        .write_to_bytes()
+        .unwrap();
+
+        let reply_msg = Reply {
+            id: 1,
+            result: ContractResult::Ok(SubMsgExecutionResponse {
+                events: vec![],
+                data: Some(data.into()),
+            }),
+        };
+        let _res = reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
+    }
+
+    // A pool of the same type cannot be created twice
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        info,
+        ExecuteMsg::CreatePair {
+            pair_type: PairType::Stable {},
+            asset_infos: asset_infos.clone(),
+            init_params: None,
+        },
+    )
+    .unwrap_err();
+    assert_eq!(res, ContractError::PairWasCreated {});
+
+    let res: PairInfo = from_binary(
+        &query(
+            deps.as_ref(),
+            env.clone(),
+            QueryMsg::Pair {
+                asset_infos: asset_infos.clone(),
+                pair_type: Some(PairType::Stable {}),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(res, stable_info);
+
+    // The xyk pool is preferred if the pair type is not specified
+    let res: PairInfo = from_binary(
+        &query(
+            deps.as_ref(),
+            env.clone(),
+            QueryMsg::Pair {
+                asset_infos: asset_infos.clone(),
+                pair_type: None,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(res, xyk_info);
+
+    let res: PairsResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            env.clone(),
+            QueryMsg::PairsByAssets {
+                asset_infos: asset_infos.clone(),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(res.pairs, vec![stable_info.clone(), xyk_info.clone()]);
+
+    // Both pools are returned on the same page even if the limit is smaller
+    let res: PairsResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            env.clone(),
+            QueryMsg::Pairs {
+                start_after: None,
+                limit: Some(1),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(res.pairs, vec![stable_info.clone(), xyk_info.clone()]);
+
+    let res: PairsResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            env.clone(),
+            QueryMsg::Pairs {
+                start_after: Some(asset_infos.clone()),
+                limit: None,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(res.pairs, vec![]);
+
+    // The pair type must be specified when several pools exist
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(owner, &[]),
+        ExecuteMsg::Deregister {
+            asset_infos: asset_infos.clone(),
+            pair_type: None,
+        },
+    )
+    .unwrap_err();
+    assert_eq!(res, ContractError::PairTypeNotSpecified {});
+
+    let _res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(owner, &[]),
+        ExecuteMsg::Deregister {
+            asset_infos: asset_infos.clone(),
+            pair_type: Some(PairType::Stable {}),
+        },
+    )
+    .unwrap();
+
+    let res: PairsResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            env.clone(),
+            QueryMsg::PairsByAssets {
+                asset_infos: asset_infos.clone(),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(res.pairs, vec![xyk_info.clone()]);
+
+    // The remaining pool can be deregistered without the pair type
+    let _res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(owner, &[]),
+        ExecuteMsg::Deregister {
+            asset_infos: asset_infos.clone(),
+            pair_type: None,
+        },
+    )
+    .unwrap();
+
+    let res = query(
+        deps.as_ref(),
+        env,
+        QueryMsg::Pair {
+            asset_infos,
+            pair_type: None,
+        },
+    );
+    assert!(res.is_err());
+}
+
+#[test]
+fn pair_query_without_pair_type() {
+    let mut deps = mock_dependencies(&[]);
+
+    let asset_infos = [
+        AssetInfo::Token {
+            contract_addr: Addr::unchecked("asset0000"),
+        },
+        AssetInfo::Token {
+            contract_addr: Addr::unchecked("asset0001"),
+        },
+    ];
+    let pair_info = |addr: &str, pair_type: PairType| PairInfo {
+        asset_infos: asset_infos.clone(),
+        contract_addr: Addr::unchecked(addr),
+        liquidity_token: Addr::unchecked("liquidity0000"),
+        pair_type,
+    };
+    let xyk_info = pair_info("xyk0000", PairType::Xyk {});
+    let stable_info = pair_info("stable0000", PairType::Stable {});
+    let weighted_info = pair_info("weighted0000", PairType::Weighted {});
+    let concentrated_info = pair_info("concentrated0000", PairType::Concentrated {});
+    let custom_info = pair_info("custom0000", PairType::Custom("aaa".to_string()));
+    let lbp_info = pair_info("lbp0000", PairType::Lbp {});
+    let infos = [
+        &lbp_info,
+        &custom_info,
+        &concentrated_info,
+        &weighted_info,
+        &stable_info,
+        &xyk_info,
+    ];
+    let addrs: Vec<String> = infos
+        .iter()
+        .map(|info| info.contract_addr.to_string())
+        .collect();
+    deps.querier.with_astroport_pairs(
+        &addrs
+            .iter()
+            .zip(infos)
+            .collect::<Vec<(&String, &PairInfo)>>(),
+    );
+
+    let query_pair = |deps: Deps| -> PairInfo {
+        from_binary(
+            &query(
+                deps,
+                mock_env(),
+                QueryMsg::Pair {
+                    asset_infos: asset_infos.clone(),
+                    pair_type: None,
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap()
+    };
+
+    // Register the pools from the lowest to the highest priority. The pair type keys are not in priority
+    // order (for example, `concentrated` and `lbp` come before `xyk`), so each new pool must still replace
+    // the previous pick
+    for info in infos {
+        PAIRS
+            .save(
+                deps.as_mut().storage,
+                (
+                    &pair_key(&asset_infos),
+                    info.pair_type.to_string().as_bytes(),
+                ),
+                &info.contract_addr,
+            )
+            .unwrap();
+
+        assert_eq!(query_pair(deps.as_ref()), *info);
+    }
+}
+
+#[test]
+fn pair_fee_override() {
+    let mut deps = mock_dependencies(&[]);
+    let owner = "owner0000";
+
+    let msg = InstantiateMsg {
+        pair_configs: vec![PairConfig {
+            code_id: 123u64,
+            pair_type: PairType::Xyk {},
+            total_fee_bps: 30,
+            maker_fee_bps: 3333,
+            is_disabled: false,
+            is_generator_disabled: false,
+            maker_fee_as_lp: false,
+        }],
+        token_code_id: 123u64,
+        fee_address: Some(String::from("maker")),
+        generator_address: None,
+        owner: owner.to_string(),
+        whitelist_code_id: 234u64,
+    };
+
+    let env = mock_env();
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    let asset_infos = [
+        AssetInfo::Token {
+            contract_addr: Addr::unchecked("asset0000"),
+        },
+        AssetInfo::Token {
+            contract_addr: Addr::unchecked("asset0001"),
+        },
+    ];
+
+    let msg = ExecuteMsg::CreatePair {
+        pair_type: PairType::Xyk {},
+        asset_infos: asset_infos.clone(),
+        init_params: None,
+    };
+    let _res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+    let pair0_addr = "pair0000".to_string();
+    let pair0_info = PairInfo {
+        asset_infos: asset_infos.clone(),
+        contract_addr: Addr::unchecked("pair0000"),
+        liquidity_token: Addr::unchecked("liquidity0000"),
+        pair_type: PairType::Xyk {},
+    };
+    let not_registered_addr = "pair0001".to_string();
+    let not_registered_info = PairInfo {
+        contract_addr: Addr::unchecked("pair0001"),
+        ..pair0_info.clone()
+    };
+    deps.querier.with_astroport_pairs(&[
+        (&pair0_addr, &pair0_info),
+        (&not_registered_addr, &not_registered_info),
+    ]);
+
+    let data = MsgInstantiateContractResponse {
+        contract_address: pair0_addr.clone(),
+        data: vec![],
+        unknown_fields: Default::default(),
+        cached_size: Default::default(),
+    }
+    .write_to_bytes()
+    .unwrap();
+
+    let reply_msg = Reply {
+        id: 1,
+        result: ContractResult::Ok(SubMsgExecutionResponse {
+            events: vec![],
+            data: Some(data.into()),
+        }),
+    };
+    let _res = reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
+
+    // Only the owner can set a fee override
+    let msg = ExecuteMsg::SetPairFeeOverride {
+        pair_addr: pair0_addr.clone(),
+        total_fee_bps: 100,
+        maker_fee_bps: 5000,
+    };
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("addr0000", &[]),
+        msg.clone(),
+    )
+    .unwrap_err();
+    assert_eq!(res, ContractError::Unauthorized {});
+
+    // Fee bps are validated
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(owner, &[]),
+        ExecuteMsg::SetPairFeeOverride {
+            pair_addr: pair0_addr.clone(),
+            total_fee_bps: 10_001,
+            maker_fee_bps: 5000,
+        },
+    )
+    .unwrap_err();
+    assert_eq!(res, ContractError::FeeOverrideInvalidFeeBps {});
+
+    // Only registered pairs can have a fee override
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(owner, &[]),
+        ExecuteMsg::SetPairFeeOverride {
+            pair_addr: not_registered_addr.clone(),
+            total_fee_bps: 100,
+            maker_fee_bps: 5000,
+        },
+    )
+    .unwrap_err();
+    assert_eq!(res, ContractError::PairNotRegistered {});
+
+    let res = execute(deps.as_mut(), env.clone(), mock_info(owner, &[]), msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "set_pair_fee_override"),
+            attr("pair_contract_addr", "pair0000"),
+            attr("total_fee_bps", "100"),
+            attr("maker_fee_bps", "5000"),
+        ]
+    );
+    // The pair is told to refresh the fees it caches
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(WasmMsg::Execute {
+            contract_addr: pair0_addr.clone(),
+            msg: to_binary(&PairExecuteMsg::RefreshFactoryParams {}).unwrap(),
+            funds: vec![],
+        })]
+    );
+
+    let res: Option<FeeOverride> = from_binary(
+        &query(
+            deps.as_ref(),
+            env.clone(),
+            QueryMsg::PairFeeOverride {
+                pair_addr: pair0_addr.clone(),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        res,
+        Some(FeeOverride {
+            total_fee_bps: 100,
+            maker_fee_bps: 5000,
+        })
+    );
+
+    // The override is used for the pair, other pairs of the same type keep the pair type fees
+    let res: FeeInfoResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            env.clone(),
+            QueryMsg::FeeInfo {
+                pair_type: PairType::Xyk {},
+                pair_addr: Some(pair0_addr.clone()),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        res,
+        FeeInfoResponse {
+            fee_address: Some(Addr::unchecked("maker")),
+            total_fee_bps: 100,
+            maker_fee_bps: 5000,
+            maker_fee_as_lp: false,
+        }
+    );
+
+    let res: FeeInfoResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            env.clone(),
+            QueryMsg::FeeInfo {
+                pair_type: PairType::Xyk {},
+                pair_addr: Some(not_registered_addr),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        res,
+        FeeInfoResponse {
+            fee_address: Some(Addr::unchecked("maker")),
+            total_fee_bps: 30,
+            maker_fee_bps: 3333,
+            maker_fee_as_lp: false,
+        }
+    );
+
+    // Remove the override
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("addr0000", &[]),
+        ExecuteMsg::RemovePairFeeOverride {
+            pair_addr: pair0_addr.clone(),
+        },
+    )
+    .unwrap_err();
+    assert_eq!(res, ContractError::Unauthorized {});
+
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(owner, &[]),
+        ExecuteMsg::RemovePairFeeOverride {
+            pair_addr: pair0_addr.clone(),
+        },
+    )
+    .unwrap();
+    assert_eq!(res.messages.len(), 1);
+
+    let res: FeeInfoResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            env,
+            QueryMsg::FeeInfo {
+                pair_type: PairType::Xyk {},
+                pair_addr: Some(pair0_addr),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(res.total_fee_bps, 30);
+    assert_eq!(res.maker_fee_bps, 3333);
+}
+
+#[test]
+fn refresh_pairs() {
+    let mut deps = mock_dependencies(&[]);
+    let owner = "owner0000";
+
+    let pair_config = |pair_type: PairType| PairConfig {
+        code_id: 123u64,
+        pair_type,
+        total_fee_bps: 30,
+        maker_fee_bps: 3333,
+        is_disabled: false,
+        is_generator_disabled: false,
+        maker_fee_as_lp: false,
+    };
+    let msg = InstantiateMsg {
+        pair_configs: vec![
+            pair_config(PairType::Xyk {}),
+            pair_config(PairType::Stable {}),
+            pair_config(PairType::StableMulti {}),
+            pair_config(PairType::Custom("custom".to_string())),
+        ],
+        token_code_id: 123u64,
+        fee_address: Some(String::from("maker")),
+        generator_address: None,
+        owner: owner.to_string(),
+        whitelist_code_id: 234u64,
+    };
+    instantiate(deps.as_mut(), mock_env(), mock_info("addr0000", &[]), msg).unwrap();
+
+    // Register an xyk and a custom pool for 12 asset pairs
+    for i in 0..12 {
+        let asset_infos = [
+            AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            AssetInfo::Token {
+                contract_addr: Addr::unchecked(format!("asset{:04}", i)),
+            },
+        ];
+        for pair_type in [PairType::Xyk {}, PairType::Custom("custom".to_string())] {
+            PAIRS
+                .save(
+                    deps.as_mut().storage,
+                    (&pair_key(&asset_infos), pair_type.to_string().as_bytes()),
+                    &Addr::unchecked(format!("{}{:04}", pair_type, i)),
+                )
+                .unwrap();
+        }
+    }
+
+    // And a stable pool for 2 sets of three assets
+    for i in 0..2 {
+        let asset_infos = [
+            AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            AssetInfo::NativeToken {
+                denom: "uluna".to_string(),
+            },
+            AssetInfo::Token {
+                contract_addr: Addr::unchecked(format!("asset{:04}", i)),
+            },
+        ];
+        MULTI_PAIRS
+            .save(
+                deps.as_mut().storage,
+                (
+                    &multi_pair_key(&asset_infos),
+                    PairType::StableMulti {}.to_string().as_bytes(),
+                ),
+                &Addr::unchecked(format!("stable_multi{:04}", i)),
+            )
+            .unwrap();
+    }
+
+    let refreshed_pairs = |res: &Response| -> Vec<String> {
+        res.messages
+            .iter()
+            .map(|msg| match &msg.msg {
+                CosmosMsg::Wasm(WasmMsg::Execute {
+                    contract_addr, msg, ..
+                }) => {
+                    assert_eq!(
+                        from_binary::<PairExecuteMsg>(msg).unwrap(),
+                        PairExecuteMsg::RefreshFactoryParams {}
+                    );
+                    contract_addr.clone()
+                }
+                _ => panic!("Unexpected message"),
+            })
+            .collect()
+    };
+
+    // Custom pairs don't cache the factory parameters
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(owner, &[]),
+        ExecuteMsg::UpdatePairConfig {
+            config: pair_config(PairType::Custom("custom".to_string())),
+        },
+    )
+    .unwrap();
+    assert!(res.messages.is_empty());
+
+    // The first batch of pairs is refreshed right away
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(owner, &[]),
+        ExecuteMsg::UpdatePairConfig {
+            config: pair_config(PairType::Xyk {}),
+        },
+    )
+    .unwrap();
+    assert_eq!(refreshed_pairs(&res).len(), 10);
+    assert!(refreshed_pairs(&res)
+        .iter()
+        .all(|addr| addr.starts_with("xyk")));
+    assert_eq!(res.attributes[1], attr("refresh_finished", "false"));
+
+    // Anyone can refresh the rest of the pairs
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        ExecuteMsg::RefreshPairs { limit: None },
+    )
+    .unwrap();
+    assert_eq!(refreshed_pairs(&res), vec!["xyk0010", "xyk0011"]);
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "refresh_pairs"),
+            attr("pairs_refreshed", "2"),
+            attr("refresh_finished", "true"),
+        ]
+    );
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        ExecuteMsg::RefreshPairs { limit: None },
+    )
+    .unwrap();
+    assert!(res.messages.is_empty());
+
+    // Fee and Generator address updates refresh all pairs that cache the factory parameters
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(owner, &[]),
+        ExecuteMsg::UpdateConfig {
+            token_code_id: None,
+            fee_address: Some(String::from("new_maker")),
+            generator_address: None,
+            whitelist_code_id: None,
+        },
+    )
+    .unwrap();
+    assert_eq!(refreshed_pairs(&res).len(), 10);
+
+    // Other config updates don't
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(owner, &[]),
+        ExecuteMsg::UpdateConfig {
+            token_code_id: Some(124u64),
+            fee_address: None,
+            generator_address: None,
+            whitelist_code_id: None,
+        },
+    )
+    .unwrap();
+    assert!(res.messages.is_empty());
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        ExecuteMsg::RefreshPairs { limit: Some(1) },
+    )
+    .unwrap();
+    assert_eq!(refreshed_pairs(&res), vec!["xyk0010"]);
+    assert_eq!(res.attributes[2], attr("refresh_finished", "false"));
+
+    // The pools with more than two assets are refreshed after the rest
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        ExecuteMsg::RefreshPairs { limit: Some(2) },
+    )
+    .unwrap();
+    assert_eq!(refreshed_pairs(&res), vec!["xyk0011", "stable_multi0000"]);
+    assert_eq!(res.attributes[2], attr("refresh_finished", "false"));
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        ExecuteMsg::RefreshPairs { limit: Some(5) },
+    )
+    .unwrap();
+    assert_eq!(refreshed_pairs(&res), vec!["stable_multi0001"]);
+    assert_eq!(res.attributes[2], attr("refresh_finished", "true"));
+}
+
+#[test]
+fn pair_status_and_guardian() {
+    let mut deps = mock_dependencies(&[]);
+    let owner = "owner0000";
+
+    let pair_config = |pair_type: PairType| PairConfig {
+        code_id: 123u64,
+        pair_type,
+        total_fee_bps: 30,
+        maker_fee_bps: 3333,
+        is_disabled: false,
+        is_generator_disabled: false,
+        maker_fee_as_lp: false,
+    };
+    let msg = InstantiateMsg {
+        pair_configs: vec![
+            pair_config(PairType::Xyk {}),
+            pair_config(PairType::Weighted {}),
+        ],
+        token_code_id: 123u64,
+        fee_address: None,
+        generator_address: None,
+        owner: owner.to_string(),
+        whitelist_code_id: 234u64,
+    };
+    instantiate(deps.as_mut(), mock_env(), mock_info("addr0000", &[]), msg).unwrap();
+
+    let asset_infos = [
+        AssetInfo::NativeToken {
+            denom: "uusd".to_string(),
+        },
+        AssetInfo::Token {
+            contract_addr: Addr::unchecked("asset0000"),
+        },
+    ];
+    let pair_info = |addr: &str, pair_type: PairType| PairInfo {
+        asset_infos: asset_infos.clone(),
+        contract_addr: Addr::unchecked(addr),
+        liquidity_token: Addr::unchecked("liquidity0000"),
+        pair_type,
+    };
+    let xyk_addr = "xyk0000".to_string();
+    let weighted_addr = "weighted0000".to_string();
+    let xyk_info = pair_info(&xyk_addr, PairType::Xyk {});
+    let weighted_info = pair_info(&weighted_addr, PairType::Weighted {});
+    let custom_addr = "custom0000".to_string();
+    let custom_info = pair_info(&custom_addr, PairType::Custom("custom".to_string()));
+    deps.querier.with_astroport_pairs(&[
+        (&xyk_addr, &xyk_info),
+        (&weighted_addr, &weighted_info),
+        (&custom_addr, &custom_info),
+    ]);
+    for info in [&xyk_info, &weighted_info, &custom_info] {
+        PAIRS
+            .save(
+                deps.as_mut().storage,
+                (
+                    &pair_key(&asset_infos),
+                    info.pair_type.to_string().as_bytes(),
+                ),
+                &info.contract_addr,
+            )
+            .unwrap();
+    }
+
+    let multi_addr = "multi0000".to_string();
+    let multi_info = MultiPairInfo {
+        asset_infos: vec![
+            asset_infos[0].clone(),
+            asset_infos[1].clone(),
+            AssetInfo::Token {
+                contract_addr: Addr::unchecked("asset0001"),
+            },
+        ],
+        contract_addr: Addr::unchecked(&multi_addr),
+        liquidity_token: Addr::unchecked("liquidity0001"),
+        pair_type: PairType::StableMulti {},
+    };
+    deps.querier
+        .with_astroport_multi_pairs(&[(&multi_addr, &multi_info)]);
+    MULTI_PAIRS
+        .save(
+            deps.as_mut().storage,
+            (
+                &multi_pair_key(&multi_info.asset_infos),
+                multi_info.pair_type.to_string().as_bytes(),
+            ),
+            &multi_info.contract_addr,
+        )
+        .unwrap();
+
+    let set_status = |pair_addr: &str, status: PairStatus| ExecuteMsg::SetPairStatus {
+        pair_addr: pair_addr.to_string(),
+        status,
+    };
+    let query_status = |deps: Deps, pair_addr: &str| -> PairStatus {
+        from_binary(
+            &query(
+                deps,
+                mock_env(),
+                QueryMsg::PairStatus {
+                    pair_addr: pair_addr.to_string(),
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap()
+    };
+
+    // Pairs are active by default
+    assert_eq!(query_status(deps.as_ref(), &xyk_addr), PairStatus::Active);
+
+    // Only the owner can set the guardian
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        ExecuteMsg::SetGuardian {
+            guardian: Some(String::from("guardian0000")),
+        },
+    )
+    .unwrap_err();
+    assert_eq!(res, ContractError::Unauthorized {});
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(owner, &[]),
+        ExecuteMsg::SetGuardian {
+            guardian: Some(String::from("guardian0000")),
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "set_guardian"),
+            attr("guardian", "guardian0000"),
+        ]
+    );
+    let config: ConfigResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap()).unwrap();
+    assert_eq!(config.guardian, Some(Addr::unchecked("guardian0000")));
+
+    // Random addresses can't halt a pair
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        set_status(&xyk_addr, PairStatus::SwapsPaused),
+    )
+    .unwrap_err();
+    assert_eq!(res, ContractError::Unauthorized {});
+
+    // Only registered pairs can be halted
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("guardian0000", &[]),
+        set_status("pair0001", PairStatus::SwapsPaused),
+    )
+    .unwrap_err();
+    assert_eq!(res, ContractError::PairNotRegistered {});
+
+    // The state of custom pairs is stored for them to query, but they are not told to refresh
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("guardian0000", &[]),
+        set_status(&custom_addr, PairStatus::SwapsPaused),
+    )
+    .unwrap();
+    assert!(res.messages.is_empty());
+    assert_eq!(
+        query_status(deps.as_ref(), &custom_addr),
+        PairStatus::SwapsPaused
+    );
+
+    // Weighted and multi-asset stable pairs cache the parameters too and are told to refresh
+    for pair_addr in [&weighted_addr, &multi_addr] {
+        let res = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("guardian0000", &[]),
+            set_status(pair_addr, PairStatus::SwapsPaused),
+        )
+        .unwrap();
+        assert_eq!(
+            res.messages,
+            vec![SubMsg::new(WasmMsg::Execute {
+                contract_addr: pair_addr.to_string(),
+                msg: to_binary(&PairExecuteMsg::RefreshFactoryParams {}).unwrap(),
+                funds: vec![],
+            })]
+        );
+        assert_eq!(
+            query_status(deps.as_ref(), pair_addr),
+            PairStatus::SwapsPaused
+        );
+    }
+
+    // The guardian halts the pair and the pair is told to refresh its cached parameters
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("guardian0000", &[]),
+        set_status(&xyk_addr, PairStatus::WithdrawOnly),
+    )
+    .unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "set_pair_status"),
+            attr("pair_contract_addr", xyk_addr.clone()),
+            attr("status", "withdraw_only"),
+        ]
+    );
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(WasmMsg::Execute {
+            contract_addr: xyk_addr.clone(),
+            msg: to_binary(&PairExecuteMsg::RefreshFactoryParams {}).unwrap(),
+            funds: vec![],
+        })]
+    );
+    assert_eq!(
+        query_status(deps.as_ref(), &xyk_addr),
+        PairStatus::WithdrawOnly
+    );
+
+    // The guardian can't resume the pair
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("guardian0000", &[]),
+        set_status(&xyk_addr, PairStatus::Active),
+    )
+    .unwrap_err();
+    assert_eq!(res, ContractError::Unauthorized {});
+
+    // The owner can
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(owner, &[]),
+        set_status(&xyk_addr, PairStatus::Active),
+    )
+    .unwrap();
+    assert_eq!(query_status(deps.as_ref(), &xyk_addr), PairStatus::Active);
+
+    // The guardian can't lift the restrictions of a withdraw-only pair
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(owner, &[]),
+        set_status(&xyk_addr, PairStatus::WithdrawOnly),
+    )
+    .unwrap();
+    for status in [PairStatus::SwapsPaused, PairStatus::DepositsPaused] {
+        let res = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("guardian0000", &[]),
+            set_status(&xyk_addr, status),
+        )
+        .unwrap();
+        assert_eq!(res.attributes[2], attr("status", "withdraw_only"));
+        assert_eq!(
+            query_status(deps.as_ref(), &xyk_addr),
+            PairStatus::WithdrawOnly
+        );
+    }
+
+    // Pausing deposits in a pair with paused swaps makes it withdraw-only
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(owner, &[]),
+        set_status(&xyk_addr, PairStatus::SwapsPaused),
+    )
+    .unwrap();
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("guardian0000", &[]),
+        set_status(&xyk_addr, PairStatus::DepositsPaused),
+    )
+    .unwrap();
+    assert_eq!(
+        query_status(deps.as_ref(), &xyk_addr),
+        PairStatus::WithdrawOnly
+    );
+
+    // Once the guardian is removed it can't halt pairs anymore
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(owner, &[]),
+        ExecuteMsg::SetGuardian { guardian: None },
+    )
+    .unwrap();
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("guardian0000", &[]),
+        set_status(&xyk_addr, PairStatus::SwapsPaused),
+    )
+    .unwrap_err();
+    assert_eq!(res, ContractError::Unauthorized {});
+}
+
+#[test]
+fn create_multi_pair() {
+    let mut deps = mock_dependencies(&[]);
+
+    let msg = InstantiateMsg {
+        pair_configs: vec![PairConfig {
+            code_id: 421u64,
+            pair_type: PairType::StableMulti {},
+            total_fee_bps: 5,
+            maker_fee_bps: 5000,
+            is_disabled: false,
+            is_generator_disabled: false,
+            maker_fee_as_lp: false,
+        }],
+        token_code_id: 123u64,
+        fee_address: None,
+        generator_address: None,
+        owner: "owner0000".to_string(),
+        whitelist_code_id: 234u64,
+    };
+
+    let env = mock_env();
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    let asset_infos = vec![
+        AssetInfo::Token {
+            contract_addr: Addr::unchecked("asset0000"),
+        },
+        AssetInfo::Token {
+            contract_addr: Addr::unchecked("asset0001"),
+        },
+        AssetInfo::NativeToken {
+            denom: "uusd".to_string(),
+        },
+    ];
+
+    // At least three assets are required
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        info.clone(),
+        ExecuteMsg::CreateMultiPair {
+            pair_type: PairType::StableMulti {},
+            asset_infos: asset_infos[..2].to_vec(),
+            init_params: None,
+        },
+    )
+    .unwrap_err();
+    assert_eq!(res, ContractError::InvalidNumberOfAssets {});
+
+    let mut doubling_assets = asset_infos.clone();
+    doubling_assets[2] = doubling_assets[0].clone();
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        info.clone(),
+        ExecuteMsg::CreateMultiPair {
+            pair_type: PairType::StableMulti {},
+            asset_infos: doubling_assets,
+            init_params: None,
+        },
+    )
+    .unwrap_err();
+    assert_eq!(res, ContractError::DoublingAssets {});
+
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        info.clone(),
+        ExecuteMsg::CreateMultiPair {
+            pair_type: PairType::StableMulti {},
+            asset_infos: asset_infos.clone(),
+            init_params: None,
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "create_multi_pair"),
+            attr("pair", "asset0000-asset0001-uusd")
+        ]
+    );
+    assert_eq!(
+        res.messages,
+        vec![SubMsg {
+            msg: WasmMsg::Instantiate {
+                msg: to_binary(&MultiPairInstantiateMsg {
+                    factory_addr: String::from(MOCK_CONTRACT_ADDR),
+                    asset_infos: asset_infos.clone(),
+                    token_code_id: 123u64,
+                    init_params: None,
+                })
+                .unwrap(),
+                code_id: 421u64,
+                funds: vec![],
+                admin: Some("owner0000".to_string()),
+                label: String::from("Astroport pair"),
+            }
+            .into(),
+            id: 2,
+            gas_limit: None,
+            reply_on: ReplyOn::Success
+        }]
+    );
+
+    let pair_info = MultiPairInfo {
+        asset_infos: asset_infos.clone(),
+        contract_addr: Addr::unchecked("pair0000"),
+        liquidity_token: Addr::unchecked("liquidity0000"),
+        pair_type: PairType::StableMulti {},
+    };
+    deps.querier
+        .with_astroport_multi_pairs(&[(&"pair0000".to_string(), &pair_info)]);
+
+    let data = MsgInstantiateContractResponse {
+        contract_address: String::from("pair0000"),
+        data: vec![],
+        unknown_fields: Default::default(),
+        cached_size: Default::default(),
+    }
+    .write_to_bytes()
+    .unwrap();
+
+    let reply_msg = Reply {
+        id: 2,
+        result: ContractResult::Ok(SubMsgExecutionResponse {
+            events: vec![],
+            data: Some(data.into()),
+        }),
+    };
+    let _res = reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
+
+    // The pool can be found using the assets in any order
+    let mut reversed_assets = asset_infos.clone();
+    reversed_assets.reverse();
+    for pair_type in [None, Some(PairType::StableMulti {})] {
+        let res: MultiPairInfo = from_binary(
+            &query(
+                deps.as_ref(),
+                env.clone(),
+                QueryMsg::MultiPair {
+                    asset_infos: reversed_assets.clone(),
+                    pair_type,
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(res, pair_info);
+    }
+
+    // Multi-asset pools are not listed among the two-asset pairs
+    let res: PairsResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            env.clone(),
+            QueryMsg::Pairs {
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(res.pairs, vec![]);
+
+    // A pool of the same type cannot be created twice
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        info.clone(),
+        ExecuteMsg::CreateMultiPair {
+            pair_type: PairType::StableMulti {},
+            asset_infos,
+            init_params: None,
+        },
+    )
+    .unwrap_err();
+    assert_eq!(res, ContractError::PairWasCreated {});
+
+    // Different assets with the same concatenated bytes don't collide with the existing pool
+    let res = execute(
+        deps.as_mut(),
+        env,
+        info,
+        ExecuteMsg::CreateMultiPair {
+            pair_type: PairType::StableMulti {},
+            asset_infos: vec![
+                AssetInfo::Token {
+                    contract_addr: Addr::unchecked("asset0000"),
+                },
+                AssetInfo::Token {
+                    contract_addr: Addr::unchecked("asset0001uu"),
+                },
+                AssetInfo::NativeToken {
+                    denom: "sd".to_string(),
+                },
+            ],
+            init_params: None,
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "create_multi_pair"),
+            attr("pair", "asset0000-asset0001uu-sd")
+        ]
+    );
+}
+
+#[test]
+fn migrate_multi_pairs_from_v130() {
+    let mut deps = mock_dependencies(&[]);
+
+    let asset_infos = vec![
+        AssetInfo::Token {
+            contract_addr: Addr::unchecked("asset0000"),
+        },
+        AssetInfo::Token {
+            contract_addr: Addr::unchecked("asset0001"),
+        },
+        AssetInfo::NativeToken {
+            denom: "uusd".to_string(),
+        },
+    ];
+    let pair_info = MultiPairInfo {
+        asset_infos: asset_infos.clone(),
+        contract_addr: Addr::unchecked("pair0000"),
+        liquidity_token: Addr::unchecked("liquidity0000"),
+        pair_type: PairType::StableMulti {},
+    };
+    deps.querier
+        .with_astroport_multi_pairs(&[(&"pair0000".to_string(), &pair_info)]);
+
+    // Before v1.4.0 multi-asset pools were keyed by their concatenated assets
+    let old_key = pair_key(&asset_infos);
+    MULTI_PAIRS
+        .save(
+            deps.as_mut().storage,
+            (&old_key, b"stable_multi"),
+            &Addr::unchecked("pair0000"),
+        )
+        .unwrap();
+
+    migrate_multi_pairs_to_v140(deps.as_mut()).unwrap();
+
+    assert_eq!(
+        MULTI_PAIRS
+            .may_load(deps.as_ref().storage, (&old_key, b"stable_multi"))
+            .unwrap(),
+        None
+    );
+    assert_eq!(
+        MULTI_PAIRS
+            .load(
+                deps.as_ref().storage,
+                (&multi_pair_key(&asset_infos), b"stable_multi")
+            )
+            .unwrap(),
+        Addr::unchecked("pair0000")
+    );
+}
+
+#[test]
+fn deregister_multi_pair() {
+    let mut deps = mock_dependencies(&[]);
+    let owner = "owner0000";
+
+    let msg = InstantiateMsg {
+        pair_configs: vec![PairConfig {
+            code_id: 421u64,
+            pair_type: PairType::StableMulti {},
+            total_fee_bps: 5,
+            maker_fee_bps: 5000,
+            is_disabled: false,
+            is_generator_disabled: false,
+            maker_fee_as_lp: false,
+        }],
+        token_code_id: 123u64,
+        fee_address: None,
+        generator_address: Some(String::from("generator")),
+        owner: owner.to_string(),
+        whitelist_code_id: 234u64,
+    };
+
+    let env = mock_env();
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    let asset_infos = vec![
+        AssetInfo::Token {
+            contract_addr: Addr::unchecked("asset0000"),
+        },
+        AssetInfo::Token {
+            contract_addr: Addr::unchecked("asset0001"),
+        },
+        AssetInfo::NativeToken {
+            denom: "uusd".to_string(),
+        },
+    ];
+
+    let _res = execute(
+        deps.as_mut(),
+        env.clone(),
+        info,
+        ExecuteMsg::CreateMultiPair {
+            pair_type: PairType::StableMulti {},
+            asset_infos: asset_infos.clone(),
+            init_params: None,
+        },
+    )
+    .unwrap();
+
+    let pair_info = MultiPairInfo {
+        asset_infos: asset_infos.clone(),
+        contract_addr: Addr::unchecked("pair0000"),
+        liquidity_token: Addr::unchecked("liquidity0000"),
+        pair_type: PairType::StableMulti {},
+    };
+    deps.querier
+        .with_astroport_multi_pairs(&[(&"pair0000".to_string(), &pair_info)]);
+
+    let data = MsgInstantiateContractResponse {
+        contract_address: String::from("pair0000"),
+        data: vec![],
+        unknown_fields: Default::default(),
+        cached_size: Default::default(),
+    }
+    .write_to_bytes()
+    .unwrap();
+
+    let reply_msg = Reply {
+        id: 2,
+        result: ContractResult::Ok(SubMsgExecutionResponse {
+            events: vec![],
+            data: Some(data.into()),
+        }),
+    };
+    let _res = reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
+
+    let mut reversed_assets = asset_infos.clone();
+    reversed_assets.reverse();
+
+    // Only the owner can deregister a pool
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("addr0000", &[]),
+        ExecuteMsg::DeregisterMultiPair {
+            asset_infos: reversed_assets.clone(),
+            pair_type: None,
+        },
+    )
+    .unwrap_err();
+    assert_eq!(res, ContractError::Unauthorized {});
+
+    // The pool is found using the assets in any order, and its LP token is deactivated in the Generator
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(owner, &[]),
+        ExecuteMsg::DeregisterMultiPair {
+            asset_infos: reversed_assets.clone(),
+            pair_type: None,
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "deregister_multi_pair"),
+            attr("pair_contract_addr", "pair0000")
+        ]
+    );
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(WasmMsg::Execute {
+            contract_addr: String::from("generator"),
+            msg: to_binary(&DeactivatePool {
+                lp_token: String::from("liquidity0000"),
+            })
+            .unwrap(),
+            funds: vec![],
+        })]
+    );
+
+    let res = query(
+        deps.as_ref(),
+        env.clone(),
+        QueryMsg::MultiPair {
+            asset_infos: asset_infos.clone(),
+            pair_type: None,
+        },
+    );
+    assert!(res.is_err());
+
+    // A deregistered pool cannot be deregistered again
+    let res = execute(
+        deps.as_mut(),
+        env,
+        mock_info(owner, &[]),
+        ExecuteMsg::DeregisterMultiPair {
+            asset_infos,
+            pair_type: Some(PairType::StableMulti {}),
+        },
+    )
+    .unwrap_err();
+    assert!(matches!(res, ContractError::Std(StdError::NotFound { .. })));
+}
+
+#[test]
+fn migrate_pairs_in_batches() {
+    let mut deps = mock_dependencies(&[]);
+
+    // Three pairs were registered before v1.3.0 by their assets only
+    let pairs = (0..3)
+        .map(|i| PairInfo {
+            asset_infos: [
+                AssetInfo::NativeToken {
+                    denom: "uusd".to_string(),
+                },
+                AssetInfo::Token {
+                    contract_addr: Addr::unchecked(format!("asset{:04}", i)),
+                },
+            ],
+            contract_addr: Addr::unchecked(format!("pair{:04}", i)),
+            liquidity_token: Addr::unchecked(format!("liquidity{:04}", i)),
+            pair_type: PairType::Xyk {},
+        })
+        .collect::<Vec<_>>();
+    let addrs = pairs
+        .iter()
+        .map(|pair| pair.contract_addr.to_string())
+        .collect::<Vec<_>>();
+    deps.querier.with_astroport_pairs(
+        &addrs
+            .iter()
+            .zip(pairs.iter())
+            .collect::<Vec<(&String, &PairInfo)>>(),
+    );
+    for pair in &pairs {
+        PAIRSV120
+            .save(
+                deps.as_mut().storage,
+                &pair_key(&pair.asset_infos),
+                &pair.contract_addr,
+            )
+            .unwrap();
+    }
+
+    // Other messages are rejected until all pairs are migrated
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        ExecuteMsg::RefreshPairs { limit: None },
+    )
+    .unwrap_err();
+    assert_eq!(res, ContractError::PairsMigrationPending {});
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        ExecuteMsg::MigratePairs { limit: Some(2) },
+    )
+    .unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "migrate_pairs"),
+            attr("pairs_migrated", "2"),
+            attr("migration_finished", "false"),
+        ]
+    );
+
+    // Both migrated pairs and pairs waiting for the migration can be queried
+    for pair in &pairs {
+        for pair_type in [None, Some(PairType::Xyk {})] {
+            let res: PairInfo = from_binary(
+                &query(
+                    deps.as_ref(),
+                    mock_env(),
+                    QueryMsg::Pair {
+                        asset_infos: pair.asset_infos.clone(),
+                        pair_type,
+                    },
+                )
+                .unwrap(),
+            )
+            .unwrap();
+            assert_eq!(&res, pair);
+        }
+    }
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        ExecuteMsg::MigratePairs { limit: None },
+    )
+    .unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "migrate_pairs"),
+            attr("pairs_migrated", "1"),
+            attr("migration_finished", "true"),
+        ]
+    );
+
+    for pair in &pairs {
+        assert_eq!(
+            PAIRS
+                .load(
+                    deps.as_ref().storage,
+                    (&pair_key(&pair.asset_infos), b"xyk")
+                )
+                .unwrap(),
+            pair.contract_addr
+        );
+    }
+}
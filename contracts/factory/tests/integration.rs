@@ -77,6 +77,7 @@ fn proper_initialization() {
         maker_fee_bps: 10,
         is_disabled: false,
         is_generator_disabled: false,
+        maker_fee_as_lp: false,
     }];
 
     let msg = InstantiateMsg {
@@ -185,6 +186,7 @@ fn instantiate_contract(app: &mut TerraApp, owner: &Addr, token_code_id: u64) ->
         maker_fee_bps: 10,
         is_disabled: false,
         is_generator_disabled: false,
+        maker_fee_as_lp: false,
     }];
 
     let msg = InstantiateMsg {
@@ -299,6 +301,7 @@ fn create_pair() {
             factory_instance.clone(),
             &QueryMsg::Pair {
                 asset_infos: asset_infos.clone(),
+                pair_type: None,
             },
         )
         .unwrap();
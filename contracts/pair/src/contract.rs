@@ -1,25 +1,36 @@
 use crate::error::ContractError;
-use crate::state::{Config, CONFIG};
+use crate::state::{
+    Config, FlashLoanState, ACCRUE_PROTOCOL_FEES, CONFIG, FACTORY_PARAMS, FLASH_LOAN,
+    PRICE_CUMULATIVE_LAST_U256, PROTOCOL_FEES, RESERVES, ROOT_K_LAST,
+};
 
 use cosmwasm_bignumber::{Decimal256, Uint256};
 use cosmwasm_std::{
     attr, entry_point, from_binary, to_binary, Addr, Binary, Coin, CosmosMsg, Decimal, Deps,
-    DepsMut, Env, MessageInfo, Reply, ReplyOn, Response, StdError, StdResult, SubMsg, Uint128,
-    WasmMsg,
+    DepsMut, Env, MessageInfo, QuerierWrapper, Reply, ReplyOn, Response, StdError, StdResult,
+    Storage, SubMsg, Timestamp, Uint128, Uint256 as StdUint256, WasmMsg,
 };
 
 use crate::response::MsgInstantiateContractResponse;
 use astroport::asset::{addr_validate_to_lower, format_lp_token_name, Asset, AssetInfo, PairInfo};
 use astroport::factory::PairType;
 use astroport::generator::Cw20HookMsg as GeneratorHookMsg;
-use astroport::pair::{ConfigResponse, DEFAULT_SLIPPAGE, MAX_ALLOWED_SLIPPAGE};
+use astroport::observation::{
+    observe, set_observations_size, write_observation, MAX_OBSERVATIONS_SIZE,
+};
+use astroport::pair::{ConfigResponse, DEFAULT_SLIPPAGE, MAX_ALLOWED_SLIPPAGE, MINIMUM_LIQUIDITY};
 use astroport::pair::{
-    CumulativePricesResponse, Cw20HookMsg, ExecuteMsg, InstantiateMsg, MigrateMsg, PoolResponse,
-    QueryMsg, ReverseSimulationResponse, SimulationResponse, TWAP_PRECISION,
+    CumulativePricesResponse, Cw20HookMsg, DepthResponse, ExecuteMsg, FlashLoanReceiverMsg,
+    InstantiateMsg, MigrateMsg, Observation, ObserveResponse, PoolResponse, PriceImpactResponse,
+    ProtocolFeesResponse, QueryMsg, ReverseSimulationResponse, SimulateProvideResponse,
+    SimulationResponse, SpotPriceResponse, XykPoolUpdateParams, TWAP_PRECISION,
+    TWAP_PRECISION_U256,
 };
-use astroport::querier::{query_factory_config, query_fee_info, query_supply};
-use astroport::{token::InstantiateMsg as TokenInstantiateMsg, U256};
-use cw2::set_contract_version;
+use astroport::querier::{
+    query_factory_config, query_factory_params, query_supply, query_token_precision, FeeInfo,
+};
+use astroport::{token::InstantiateMsg as TokenInstantiateMsg, Uint256WrappingOps, U256};
+use cw2::{get_contract_version, set_contract_version};
 use cw20::{Cw20ExecuteMsg, Cw20ReceiveMsg, MinterResponse};
 use protobuf::Message;
 use std::str::FromStr;
@@ -73,8 +84,18 @@ pub fn instantiate(
     };
 
     CONFIG.save(deps.storage, &config)?;
+    FACTORY_PARAMS.save(
+        deps.storage,
+        &query_factory_params(
+            &deps.querier,
+            config.factory_addr.clone(),
+            env.contract.address.clone(),
+            config.pair_info.pair_type.clone(),
+        )?,
+    )?;
+    RESERVES.save(deps.storage, &[Uint128::zero(), Uint128::zero()])?;
 
-    let token_name = format_lp_token_name(msg.asset_infos, &deps.querier)?;
+    let token_name = format_lp_token_name(&msg.asset_infos, &deps.querier)?;
 
     // Create the LP token contract
     let sub_msg: Vec<SubMsg> = vec![SubMsg {
@@ -145,7 +166,8 @@ pub fn reply(deps: DepsMut, _env: Env, msg: Reply) -> Result<Response, ContractE
 /// * **msg** is an object of type [`ExecuteMsg`].
 ///
 /// ## Queries
-/// * **ExecuteMsg::UpdateConfig { params: Binary }** Not supported.
+/// * **ExecuteMsg::UpdateConfig { params: Binary }** Updates the pair configuration with the specified
+/// [`XykPoolUpdateParams`].
 ///
 /// * **ExecuteMsg::Receive(msg)** Receives a message of type [`Cw20ReceiveMsg`] and processes
 /// it depending on the received template.
@@ -155,14 +177,47 @@ pub fn reply(deps: DepsMut, _env: Env, msg: Reply) -> Result<Response, ContractE
 ///             slippage_tolerance,
 ///             auto_stake,
 ///             receiver,
+///             min_lp_to_receive,
+///             deadline,
 ///         }** Provides liquidity in the pair with the specified input parameters.
 ///
+/// * **ExecuteMsg::ProvideSingleSided {
+///             asset,
+///             min_lp_to_receive,
+///             auto_stake,
+///             receiver,
+///             deadline,
+///         }** Provides liquidity in the pair using only one of the pool assets.
+///
 /// * **ExecuteMsg::Swap {
 ///             offer_asset,
 ///             belief_price,
 ///             max_spread,
 ///             to,
+///             min_return,
+///             deadline,
 ///         }** Performs a swap operation with the specified parameters.
+///
+/// * **ExecuteMsg::SwapExactOut {
+///             ask_asset,
+///             max_offer,
+///             to,
+///             deadline,
+///         }** Performs a swap that returns exactly the specified ask asset.
+///
+/// * **ExecuteMsg::FlashLoan { asset, receiver, msg }** Lends an asset from the pool to a receiver contract.
+///
+/// * **ExecuteMsg::AssertFlashLoanRepaid {}** Checks that the flash loan in progress was repaid.
+///
+/// * **ExecuteMsg::Sync {}** Sets the tracked pool reserves to the current balances of the pair.
+///
+/// * **ExecuteMsg::Skim { to }** Sends the balances of the pair that exceed the tracked pool reserves to a recipient.
+///
+/// * **ExecuteMsg::RefreshFactoryParams {}** Updates the fee and Generator information cached from the factory.
+///
+/// * **ExecuteMsg::SetProtocolFeeAccrual { enabled }** Enables or disables the accrual of the Maker fees in the pair.
+///
+/// * **ExecuteMsg::ClaimProtocolFees {}** Sends the Maker fees accrued in the pair to the fee address.
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn execute(
     deps: DepsMut,
@@ -170,14 +225,26 @@ pub fn execute(
     info: MessageInfo,
     msg: ExecuteMsg,
 ) -> Result<Response, ContractError> {
+    // The pool balances are not final until a flash loan is repaid. The flash loan fee is fixed
+    // when the loan is taken, so the cached factory parameters can still be refreshed
+    if !matches!(
+        msg,
+        ExecuteMsg::AssertFlashLoanRepaid {} | ExecuteMsg::RefreshFactoryParams {}
+    ) && FLASH_LOAN.may_load(deps.storage)?.is_some()
+    {
+        return Err(ContractError::FlashLoanInProgress {});
+    }
+
     match msg {
-        ExecuteMsg::UpdateConfig { .. } => Err(ContractError::NonSupported {}),
+        ExecuteMsg::UpdateConfig { params } => update_config(deps, info, params),
         ExecuteMsg::Receive(msg) => receive_cw20(deps, env, info, msg),
         ExecuteMsg::ProvideLiquidity {
             assets,
             slippage_tolerance,
             auto_stake,
             receiver,
+            min_lp_to_receive,
+            deadline,
         } => provide_liquidity(
             deps,
             env,
@@ -186,12 +253,32 @@ pub fn execute(
             slippage_tolerance,
             auto_stake,
             receiver,
+            min_lp_to_receive,
+            deadline,
+        ),
+        ExecuteMsg::ProvideSingleSided {
+            asset,
+            min_lp_to_receive,
+            auto_stake,
+            receiver,
+            deadline,
+        } => provide_single_sided(
+            deps,
+            env,
+            info,
+            asset,
+            min_lp_to_receive,
+            auto_stake,
+            receiver,
+            deadline,
         ),
         ExecuteMsg::Swap {
             offer_asset,
             belief_price,
             max_spread,
             to,
+            min_return,
+            deadline,
         } => {
             offer_asset.info.check(deps.api)?;
             if !offer_asset.is_native_token() {
@@ -213,8 +300,49 @@ pub fn execute(
                 belief_price,
                 max_spread,
                 to_addr,
+                min_return,
+                deadline,
+            )
+        }
+        ExecuteMsg::SwapExactOut {
+            ask_asset,
+            max_offer,
+            to,
+            deadline,
+        } => {
+            ask_asset.info.check(deps.api)?;
+
+            let to_addr = if let Some(to_addr) = to {
+                Some(addr_validate_to_lower(deps.api, &to_addr)?)
+            } else {
+                None
+            };
+
+            swap_exact_out(
+                deps,
+                env,
+                info.clone(),
+                info.sender,
+                ask_asset,
+                max_offer,
+                false,
+                to_addr,
+                deadline,
             )
         }
+        ExecuteMsg::FlashLoan {
+            asset,
+            receiver,
+            msg,
+        } => flash_loan(deps, env, info, asset, receiver, msg),
+        ExecuteMsg::AssertFlashLoanRepaid {} => assert_flash_loan_repaid(deps, env, info),
+        ExecuteMsg::Sync {} => sync(deps, env),
+        ExecuteMsg::Skim { to } => skim(deps, env, info, to),
+        ExecuteMsg::RefreshFactoryParams {} => refresh_factory_params(deps, env),
+        ExecuteMsg::SetProtocolFeeAccrual { enabled } => {
+            set_protocol_fee_accrual(deps, info, enabled)
+        }
+        ExecuteMsg::ClaimProtocolFees {} => claim_protocol_fees(deps, info),
     }
 }
 
@@ -242,6 +370,8 @@ pub fn receive_cw20(
             belief_price,
             max_spread,
             to,
+            min_return,
+            deadline,
         }) => {
             // Only asset contract can execute this message
             let mut authorized: bool = false;
@@ -277,15 +407,49 @@ pub fn receive_cw20(
                 belief_price,
                 max_spread,
                 to_addr,
+                min_return,
+                deadline,
+            )
+        }
+        Ok(Cw20HookMsg::SwapExactOut {
+            ask_asset,
+            to,
+            deadline,
+        }) => {
+            let to_addr = if let Some(to_addr) = to {
+                Some(addr_validate_to_lower(deps.api, to_addr.as_str())?)
+            } else {
+                None
+            };
+
+            swap_exact_out(
+                deps,
+                env,
+                info,
+                Addr::unchecked(cw20_msg.sender),
+                ask_asset,
+                cw20_msg.amount,
+                true,
+                to_addr,
+                deadline,
             )
         }
-        Ok(Cw20HookMsg::WithdrawLiquidity {}) => withdraw_liquidity(
+        Ok(Cw20HookMsg::WithdrawLiquidity {
+            min_assets_out,
+            deadline,
+        }) => withdraw_liquidity(
             deps,
             env,
             info,
             Addr::unchecked(cw20_msg.sender),
             cw20_msg.amount,
+            min_assets_out,
+            deadline,
         ),
+        Ok(Cw20HookMsg::WithdrawLiquidityOneCoin { .. })
+        | Ok(Cw20HookMsg::WithdrawLiquidityImbalanced { .. }) => {
+            Err(ContractError::NonSupported {})
+        }
         Err(err) => Err(ContractError::Std(err)),
     }
 }
@@ -311,7 +475,14 @@ pub fn receive_cw20(
 ///
 /// * **receiver** is an [`Option`] field of type [`String`]. This is the receiver of the LP tokens.
 /// If no custom receiver is specified, the pair will mint LP tokens for the function caller.
+///
+/// * **min_lp_to_receive** is an [`Option`] field of type [`Uint128`]. This is the minimum amount of
+/// LP tokens that must be minted, otherwise the transaction fails.
+///
+/// * **deadline** is an [`Option`] field of type [`u64`]. This is the block time (in seconds) after which
+/// the transaction fails.
 // NOTE - the address that wants to provide liquidity should approve the pair contract to pull its relevant tokens.
+#[allow(clippy::too_many_arguments)]
 pub fn provide_liquidity(
     deps: DepsMut,
     env: Env,
@@ -320,7 +491,12 @@ pub fn provide_liquidity(
     slippage_tolerance: Option<Decimal>,
     auto_stake: Option<bool>,
     receiver: Option<String>,
+    min_lp_to_receive: Option<Uint128>,
+    deadline: Option<u64>,
 ) -> Result<Response, ContractError> {
+    assert_deposits_allowed(deps.storage)?;
+    assert_deadline(&env, deadline)?;
+
     assets[0].info.check(deps.api)?;
     assets[1].info.check(deps.api)?;
 
@@ -330,28 +506,11 @@ pub fn provide_liquidity(
     }
 
     let mut config: Config = CONFIG.load(deps.storage)?;
-    let mut pools: [Asset; 2] = config
-        .pair_info
-        .query_pools(&deps.querier, env.contract.address.clone())?;
-    let deposits: [Uint128; 2] = [
-        assets
-            .iter()
-            .find(|a| a.info.equal(&pools[0].info))
-            .map(|a| a.amount)
-            .expect("Wrong asset info is given"),
-        assets
-            .iter()
-            .find(|a| a.info.equal(&pools[1].info))
-            .map(|a| a.amount)
-            .expect("Wrong asset info is given"),
-    ];
-
-    if deposits[0].is_zero() || deposits[1].is_zero() {
-        return Err(ContractError::InvalidZeroAmount {});
-    }
+    let pools = query_reserves(deps.storage, &config)?;
+    let deposits = get_deposits(&assets, &pools)?;
 
     let mut messages: Vec<CosmosMsg> = vec![];
-    for (i, pool) in pools.iter_mut().enumerate() {
+    for (i, pool) in pools.iter().enumerate() {
         // If the asset is a token contract, then we need to execute a TransferFrom msg to receive assets
         if let AssetInfo::Token { contract_addr, .. } = &pool.info {
             messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
@@ -363,14 +522,114 @@ pub fn provide_liquidity(
                 })?,
                 funds: vec![],
             }));
-        } else {
-            // If the asset is native token, the pool balance is already increased
-            // To calculate the total amount of deposits properly, we should subtract the user deposit from the pool
-            pool.amount = pool.amount.checked_sub(deposits[i])?;
         }
     }
 
     let total_share = query_supply(&deps.querier, config.pair_info.liquidity_token.clone())?;
+
+    // The Maker fee accrued since the last liquidity event is minted before the share is calculated
+    let (maker_fee_share, maker_fee_messages) =
+        mint_maker_fee_lp(deps.as_ref(), env.clone(), &config, &pools, total_share)?;
+    messages.extend(maker_fee_messages);
+    let total_share = total_share.checked_add(maker_fee_share)?;
+
+    let share = calculate_share(&deposits, &pools, total_share, slippage_tolerance)?;
+
+    if total_share.is_zero() {
+        // Lock the minimum liquidity in the pair forever, so the share price can't be inflated
+        messages.extend(mint_liquidity_token_message(
+            deps.as_ref(),
+            &config,
+            env.clone(),
+            env.contract.address.clone(),
+            MINIMUM_LIQUIDITY,
+            false,
+        )?);
+    }
+
+    if let Some(min_lp_to_receive) = min_lp_to_receive {
+        if share < min_lp_to_receive {
+            return Err(ContractError::MinLpToReceiveAssertion {});
+        }
+    }
+
+    // Mint LP tokens for the sender or for the receiver (if set)
+    let receiver = receiver.unwrap_or_else(|| info.sender.to_string());
+    messages.extend(mint_liquidity_token_message(
+        deps.as_ref(),
+        &config,
+        env.clone(),
+        addr_validate_to_lower(deps.api, receiver.as_str())?,
+        share,
+        auto_stake,
+    )?);
+
+    // Accumulate prices for the assets in the pool
+    update_cumulative_prices(
+        deps.storage,
+        &env,
+        &mut config,
+        pools[0].amount,
+        pools[1].amount,
+    )?;
+
+    let reserves = [
+        pools[0].amount.checked_add(deposits[0])?,
+        pools[1].amount.checked_add(deposits[1])?,
+    ];
+    RESERVES.save(deps.storage, &reserves)?;
+    update_root_k_last(deps.storage, reserves)?;
+
+    Ok(Response::new().add_messages(messages).add_attributes(vec![
+        attr("action", "provide_liquidity"),
+        attr("sender", info.sender.as_str()),
+        attr("receiver", receiver.as_str()),
+        attr("assets", format!("{}, {}", assets[0], assets[1])),
+        attr("share", share.to_string()),
+    ]))
+}
+
+/// ## Description
+/// Returns the deposit amounts in the order of the pool assets. Both pool assets must be deposited.
+/// ## Params
+/// * **assets** is an array with two objects of type [`Asset`]. These are the assets to deposit.
+///
+/// * **pools** are an array of [`Asset`] type items. These are the assets available in the pool.
+fn get_deposits(assets: &[Asset; 2], pools: &[Asset; 2]) -> Result<[Uint128; 2], ContractError> {
+    let deposit = |pool: &Asset| {
+        assets
+            .iter()
+            .find(|a| a.info.equal(&pool.info))
+            .map(|a| a.amount)
+            .ok_or(ContractError::AssetMismatch {})
+    };
+    let deposits = [deposit(&pools[0])?, deposit(&pools[1])?];
+
+    if deposits[0].is_zero() || deposits[1].is_zero() {
+        return Err(ContractError::InvalidZeroAmount {});
+    }
+
+    Ok(deposits)
+}
+
+/// ## Description
+/// Returns the amount of LP tokens minted for a deposit. The first deposit mints the geometric mean of the
+/// deposited amounts minus the [`MINIMUM_LIQUIDITY`] that is locked in the pair.
+/// ## Params
+/// * **deposits** is an array with two objects of type [`Uint128`]. These are the deposits in the order of the pool assets.
+///
+/// * **pools** are an array of [`Asset`] type items. These are the assets available in the pool.
+///
+/// * **total_share** is an object of type [`Uint128`]. This is the total amount of LP tokens including the Maker fee.
+///
+/// * **slippage_tolerance** is an [`Option`] field of type [`Decimal`]. It is used to specify how much
+/// the pool price can move until the provide liquidity transaction goes through.
+fn calculate_share(
+    deposits: &[Uint128; 2],
+    pools: &[Asset; 2],
+    total_share: Uint128,
+    slippage_tolerance: Option<Decimal>,
+) -> Result<Uint128, ContractError> {
     let share = if total_share.is_zero() {
         // Initial share = collateral amount
         Uint128::new(
@@ -378,9 +637,11 @@ pub fn provide_liquidity(
                 .integer_sqrt()
                 .as_u128(),
         )
+        .checked_sub(MINIMUM_LIQUIDITY)
+        .map_err(|_| ContractError::MinimumLiquidityAmountError {})?
     } else {
         // Assert slippage tolerance
-        assert_slippage_tolerance(slippage_tolerance, &deposits, &pools)?;
+        assert_slippage_tolerance(slippage_tolerance, deposits, pools)?;
 
         // min(1, 2)
         // 1. sqrt(deposit_0 * exchange_rate_0_to_1 * deposit_0) * (total_share / sqrt(pool_0 * pool_1))
@@ -393,6 +654,149 @@ pub fn provide_liquidity(
         )
     };
 
+    if share.is_zero() {
+        return Err(ContractError::InvalidZeroAmount {});
+    }
+
+    Ok(share)
+}
+
+/// ## Description
+/// Provides liquidity in the pair using only one of the pool assets. The optimal portion of the
+/// provided asset is swapped internally (the swap output stays in the pool) so that the remaining
+/// amount and the swap output are deposited in the current pool ratio.
+/// Returns a [`ContractError`] on failure, otherwise returns a [`Response`] with the specified
+/// attributes if the operation was successful.
+/// ## Params
+/// * **deps** is an object of type [`DepsMut`].
+///
+/// * **env** is an object of type [`Env`].
+///
+/// * **info** is an object of type [`MessageInfo`].
+///
+/// * **asset** is an object of type [`Asset`]. This is the asset to provide.
+///
+/// * **min_lp_to_receive** is an [`Option`] field of type [`Uint128`]. This is the minimum amount of
+/// LP tokens that must be minted, otherwise the transaction fails.
+///
+/// * **auto_stake** is an [`Option`] field of type [`bool`]. Determines whether the LP tokens minted after
+/// liquidity provision are automatically staked in the Generator contract on behalf of the LP token receiver.
+///
+/// * **receiver** is an [`Option`] field of type [`String`]. This is the receiver of the LP tokens.
+/// If no custom receiver is specified, the pair will mint LP tokens for the function caller.
+///
+/// * **deadline** is an [`Option`] field of type [`u64`]. This is the block time (in seconds) after which
+/// the transaction fails.
+// NOTE - the address that wants to provide liquidity should approve the pair contract to pull its relevant tokens.
+#[allow(clippy::too_many_arguments)]
+pub fn provide_single_sided(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    asset: Asset,
+    min_lp_to_receive: Option<Uint128>,
+    auto_stake: Option<bool>,
+    receiver: Option<String>,
+    deadline: Option<u64>,
+) -> Result<Response, ContractError> {
+    assert_deposits_allowed(deps.storage)?;
+    assert_deadline(&env, deadline)?;
+    asset.info.check(deps.api)?;
+    asset.assert_sent_native_token_balance(&info)?;
+
+    if asset.amount.is_zero() {
+        return Err(ContractError::InvalidZeroAmount {});
+    }
+
+    let auto_stake = auto_stake.unwrap_or(false);
+    let mut config: Config = CONFIG.load(deps.storage)?;
+    let pools = query_reserves(deps.storage, &config)?;
+
+    let offer_index = if asset.info.equal(&pools[0].info) {
+        0
+    } else if asset.info.equal(&pools[1].info) {
+        1
+    } else {
+        return Err(ContractError::AssetMismatch {});
+    };
+    let ask_index = 1 - offer_index;
+
+    let mut messages: Vec<CosmosMsg> = vec![];
+    if let AssetInfo::Token { contract_addr, .. } = &asset.info {
+        messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: contract_addr.to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::TransferFrom {
+                owner: info.sender.to_string(),
+                recipient: env.contract.address.to_string(),
+                amount: asset.amount,
+            })?,
+            funds: vec![],
+        }));
+    }
+
+    let total_share = query_supply(&deps.querier, config.pair_info.liquidity_token.clone())?;
+    if total_share.is_zero() || pools[0].amount.is_zero() || pools[1].amount.is_zero() {
+        return Err(ContractError::SingleSidedEmptyPool {});
+    }
+
+    // The Maker fee accrued since the last liquidity event is minted before the share is calculated
+    let (maker_fee_share, maker_fee_messages) =
+        mint_maker_fee_lp(deps.as_ref(), env.clone(), &config, &pools, total_share)?;
+    messages.extend(maker_fee_messages);
+    let total_share = total_share.checked_add(maker_fee_share)?;
+
+    // Get the fee info cached from the factory
+    let fee_info = FACTORY_PARAMS.load(deps.storage)?.fee_info;
+
+    let offer_pool = pools[offer_index].amount;
+    let ask_pool = pools[ask_index].amount;
+
+    // Swap the optimal part of the deposit; the swap output is kept in the pool as a deposit
+    let swap_amount =
+        compute_single_sided_swap_amount(offer_pool, asset.amount, fee_info.total_fee_rate)?;
+    let (return_amount, _, commission_amount) =
+        compute_swap(offer_pool, ask_pool, swap_amount, fee_info.total_fee_rate)?;
+
+    let mut maker_fee_amount = Uint128::zero();
+    if let Some(fee_address) = swap_fee_address(&fee_info) {
+        if let Some(f) = calculate_maker_fee(
+            pools[ask_index].info.clone(),
+            commission_amount,
+            fee_info.maker_fee_rate,
+        ) {
+            maker_fee_amount = f.amount;
+            messages.extend(pay_maker_fee(
+                deps.storage,
+                &deps.querier,
+                &config,
+                f,
+                fee_address,
+            )?);
+        }
+    }
+
+    let offer_pool_after = offer_pool.checked_add(swap_amount)?;
+    let ask_pool_after = ask_pool
+        .checked_sub(return_amount)?
+        .checked_sub(maker_fee_amount)?;
+    let share = std::cmp::min(
+        asset
+            .amount
+            .checked_sub(swap_amount)?
+            .multiply_ratio(total_share, offer_pool_after),
+        return_amount.multiply_ratio(total_share, ask_pool_after),
+    );
+
+    if share.is_zero() {
+        return Err(ContractError::InvalidZeroAmount {});
+    }
+
+    if let Some(min_lp_to_receive) = min_lp_to_receive {
+        if share < min_lp_to_receive {
+            return Err(ContractError::MinLpToReceiveAssertion {});
+        }
+    }
+
     // Mint LP tokens for the sender or for the receiver (if set)
     let receiver = receiver.unwrap_or_else(|| info.sender.to_string());
     messages.extend(mint_liquidity_token_message(
@@ -405,20 +809,30 @@ pub fn provide_liquidity(
     )?);
 
     // Accumulate prices for the assets in the pool
-    if let Some((price0_cumulative_new, price1_cumulative_new, block_time)) =
-        accumulate_prices(env, &config, pools[0].amount, pools[1].amount)?
-    {
-        config.price0_cumulative_last = price0_cumulative_new;
-        config.price1_cumulative_last = price1_cumulative_new;
-        config.block_time_last = block_time;
-        CONFIG.save(deps.storage, &config)?;
-    }
+    update_cumulative_prices(
+        deps.storage,
+        &env,
+        &mut config,
+        pools[0].amount,
+        pools[1].amount,
+    )?;
+
+    // The whole deposit stays in the pool, only the Maker fee leaves it
+    let mut reserves = [pools[0].amount, pools[1].amount];
+    reserves[offer_index] = offer_pool.checked_add(asset.amount)?;
+    reserves[ask_index] = ask_pool.checked_sub(maker_fee_amount)?;
+    RESERVES.save(deps.storage, &reserves)?;
+    update_root_k_last(deps.storage, reserves)?;
 
     Ok(Response::new().add_messages(messages).add_attributes(vec![
-        attr("action", "provide_liquidity"),
+        attr("action", "provide_single_sided"),
         attr("sender", info.sender.as_str()),
         attr("receiver", receiver.as_str()),
-        attr("assets", format!("{}, {}", assets[0], assets[1])),
+        attr("asset", asset.to_string()),
+        attr("swap_amount", swap_amount.to_string()),
+        attr("return_amount", return_amount.to_string()),
+        attr("commission_amount", commission_amount.to_string()),
+        attr("maker_fee_amount", maker_fee_amount.to_string()),
         attr("share", share.to_string()),
     ]))
 }
@@ -461,8 +875,7 @@ fn mint_liquidity_token_message(
     }
 
     // Mint for the pair contract and stake into the Generator contract
-    let generator =
-        query_factory_config(&deps.querier, config.clone().factory_addr)?.generator_address;
+    let generator = FACTORY_PARAMS.load(deps.storage)?.generator_address;
 
     if generator.is_none() {
         return Err(ContractError::AutoStakeError {});
@@ -502,13 +915,23 @@ fn mint_liquidity_token_message(
 /// * **sender** is an object of type [`Addr`]. This is the address that will receive assets back from the pair contract.
 ///
 /// * **amount** is an object of type [`Uint128`]. This is the amount of LP tokens to burn.
+///
+/// * **min_assets_out** is an [`Option`] field of type [`Vec<Asset>`]. These are the minimum amounts of assets
+/// to receive, otherwise the transaction fails.
+///
+/// * **deadline** is an [`Option`] field of type [`u64`]. This is the block time (in seconds) after which
+/// the transaction fails.
 pub fn withdraw_liquidity(
     deps: DepsMut,
     env: Env,
     info: MessageInfo,
     sender: Addr,
     amount: Uint128,
+    min_assets_out: Option<Vec<Asset>>,
+    deadline: Option<u64>,
 ) -> Result<Response, ContractError> {
+    assert_deadline(&env, deadline)?;
+
     let mut config: Config = CONFIG.load(deps.storage).unwrap();
 
     if info.sender != config.pair_info.liquidity_token {
@@ -516,20 +939,33 @@ pub fn withdraw_liquidity(
     }
 
     let (pools, total_share) = pool_info(deps.as_ref(), config.clone())?;
+
+    // The Maker fee accrued since the last liquidity event is minted before the refund is calculated
+    let (maker_fee_share, maker_fee_messages) =
+        mint_maker_fee_lp(deps.as_ref(), env.clone(), &config, &pools, total_share)?;
+    let total_share = total_share.checked_add(maker_fee_share)?;
+
     let refund_assets = get_share_in_assets(&pools, amount, total_share);
+    assert_min_assets_out(&refund_assets, min_assets_out)?;
 
     // Accumulate prices for the pair assets
-    if let Some((price0_cumulative_new, price1_cumulative_new, block_time)) =
-        accumulate_prices(env, &config, pools[0].amount, pools[1].amount)?
-    {
-        config.price0_cumulative_last = price0_cumulative_new;
-        config.price1_cumulative_last = price1_cumulative_new;
-        config.block_time_last = block_time;
-        CONFIG.save(deps.storage, &config)?;
-    }
+    update_cumulative_prices(
+        deps.storage,
+        &env,
+        &mut config,
+        pools[0].amount,
+        pools[1].amount,
+    )?;
+
+    let reserves = [
+        pools[0].amount.checked_sub(refund_assets[0].amount)?,
+        pools[1].amount.checked_sub(refund_assets[1].amount)?,
+    ];
+    RESERVES.save(deps.storage, &reserves)?;
+    update_root_k_last(deps.storage, reserves)?;
 
     // Update the pool info
-    let messages: Vec<CosmosMsg> = vec![
+    let mut messages: Vec<CosmosMsg> = vec![
         refund_assets[0]
             .clone()
             .into_msg(&deps.querier, sender.clone())?,
@@ -542,6 +978,7 @@ pub fn withdraw_liquidity(
             funds: vec![],
         }),
     ];
+    messages.extend(maker_fee_messages);
 
     let attributes = vec![
         attr("action", "withdraw_liquidity"),
@@ -605,6 +1042,10 @@ pub fn get_share_in_assets(
 /// * **max_spread** is an object of type [`Option<Decimal>`]. Sets the maximum spread of the swap operation.
 ///
 /// * **to** is an object of type [`Option<Addr>`]. Sets the recipient of the swap operation.
+///
+/// * **min_return** is an object of type [`Option<Uint128>`]. This is the minimum amount of ask assets to receive.
+///
+/// * **deadline** is an object of type [`Option<u64>`]. This is the block time (in seconds) after which the swap fails.
 /// NOTE - the address that wants to swap should approve the pair contract to pull the offer token.
 #[allow(clippy::too_many_arguments)]
 pub fn swap(
@@ -616,25 +1057,17 @@ pub fn swap(
     belief_price: Option<Decimal>,
     max_spread: Option<Decimal>,
     to: Option<Addr>,
+    min_return: Option<Uint128>,
+    deadline: Option<u64>,
 ) -> Result<Response, ContractError> {
+    assert_swaps_allowed(deps.storage)?;
+    assert_deadline(&env, deadline)?;
+
     offer_asset.assert_sent_native_token_balance(&info)?;
 
     let mut config: Config = CONFIG.load(deps.storage)?;
 
-    // If the asset balance is already increased, we should subtract the user deposit from the pool amount
-    let pools: Vec<Asset> = config
-        .pair_info
-        .query_pools(&deps.querier, env.clone().contract.address)?
-        .iter()
-        .map(|p| {
-            let mut p = p.clone();
-            if p.info.equal(&offer_asset.info) {
-                p.amount = p.amount.checked_sub(offer_asset.amount).unwrap();
-            }
-
-            p
-        })
-        .collect();
+    let pools = query_reserves(deps.storage, &config)?;
 
     let offer_pool: Asset;
     let ask_pool: Asset;
@@ -649,12 +1082,8 @@ pub fn swap(
         return Err(ContractError::AssetMismatch {});
     }
 
-    // Get fee info from the factory
-    let fee_info = query_fee_info(
-        &deps.querier,
-        config.factory_addr.clone(),
-        config.pair_info.pair_type.clone(),
-    )?;
+    // Get the fee info cached from the factory
+    let fee_info = FACTORY_PARAMS.load(deps.storage)?.fee_info;
 
     let offer_amount = offer_asset.amount;
     let (return_amount, spread_amount, commission_amount) = compute_swap(
@@ -673,6 +1102,13 @@ pub fn swap(
         spread_amount,
     )?;
 
+    // Check the minimum return amount (if it was specified)
+    if let Some(min_return) = min_return {
+        if return_amount < min_return {
+            return Err(ContractError::MinReturnAssertion {});
+        }
+    }
+
     // Compute the tax for the receiving asset (if it is a native one)
     let return_asset = Asset {
         info: ask_pool.info.clone(),
@@ -686,26 +1122,39 @@ pub fn swap(
 
     // Compute the Maker fee
     let mut maker_fee_amount = Uint128::new(0);
-    if let Some(fee_address) = fee_info.fee_address {
+    if let Some(fee_address) = swap_fee_address(&fee_info) {
         if let Some(f) = calculate_maker_fee(
             ask_pool.info.clone(),
             commission_amount,
             fee_info.maker_fee_rate,
         ) {
-            messages.push(f.clone().into_msg(&deps.querier, fee_address)?);
             maker_fee_amount = f.amount;
+            messages.extend(pay_maker_fee(
+                deps.storage,
+                &deps.querier,
+                &config,
+                f,
+                fee_address,
+            )?);
         }
     }
 
     // Accumulate prices for the assets in the pool
-    if let Some((price0_cumulative_new, price1_cumulative_new, block_time)) =
-        accumulate_prices(env, &config, pools[0].amount, pools[1].amount)?
-    {
-        config.price0_cumulative_last = price0_cumulative_new;
-        config.price1_cumulative_last = price1_cumulative_new;
-        config.block_time_last = block_time;
-        CONFIG.save(deps.storage, &config)?;
-    }
+    update_cumulative_prices(
+        deps.storage,
+        &env,
+        &mut config,
+        pools[0].amount,
+        pools[1].amount,
+    )?;
+
+    save_reserves_after_swap(
+        deps.storage,
+        &pools,
+        &offer_pool.info,
+        offer_amount,
+        return_amount.checked_add(maker_fee_amount)?,
+    )?;
 
     Ok(Response::new()
         .add_messages(
@@ -727,49 +1176,660 @@ pub fn swap(
 }
 
 /// ## Description
-/// Accumulate token prices for the assets in the pool.
-/// Note that this function shifts **block_time** when any of the token prices is zero in order to not
-/// fill an accumulator with a null price for that period.
+/// Performs a swap that returns exactly the specified ask asset. The offer amount is calculated
+/// the same way as in a reverse simulation and must not exceed `max_offer`. Native offer assets and
+/// token offer assets sent via [`Cw20HookMsg::SwapExactOut`] are received in advance, so the excess is
+/// sent back to the sender, otherwise only the required amount of offer tokens is pulled from the sender.
+/// Returns an [`ContractError`] on failure, otherwise returns the [`Response`] with the specified attributes if the operation was successful.
 /// ## Params
-/// * **env** is an object of type [`Env`].
+/// * **deps** is an object of type [`DepsMut`].
+///
+/// * **env** is an object of type [`Env`].
+///
+/// * **info** is an object of type [`MessageInfo`].
+///
+/// * **sender** is an object of type [`Addr`]. This is the sender of the swap operation.
+///
+/// * **ask_asset** is an object of type [`Asset`]. This is the asset and the exact amount to receive from the swap.
+///
+/// * **max_offer** is an object of type [`Uint128`]. This is the maximum amount of offer assets to pay for the ask asset.
+///
+/// * **offer_received** is an object of type [`bool`]. Determines whether the offer tokens were already sent to the pair.
+///
+/// * **to** is an object of type [`Option<Addr>`]. Sets the recipient of the swap operation.
+///
+/// * **deadline** is an [`Option`] field of type [`u64`]. This is the block time (in seconds) after which
+/// the transaction fails.
+/// NOTE - the address that wants to swap should approve the pair contract to pull the offer token.
+#[allow(clippy::too_many_arguments)]
+pub fn swap_exact_out(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    sender: Addr,
+    ask_asset: Asset,
+    max_offer: Uint128,
+    offer_received: bool,
+    to: Option<Addr>,
+    deadline: Option<u64>,
+) -> Result<Response, ContractError> {
+    assert_swaps_allowed(deps.storage)?;
+    assert_deadline(&env, deadline)?;
+    if ask_asset.amount.is_zero() {
+        return Err(ContractError::InvalidZeroAmount {});
+    }
+
+    let mut config: Config = CONFIG.load(deps.storage)?;
+    let pools = query_reserves(deps.storage, &config)?;
+
+    let (offer_index, ask_index) = if ask_asset.info.equal(&pools[0].info) {
+        (1, 0)
+    } else if ask_asset.info.equal(&pools[1].info) {
+        (0, 1)
+    } else {
+        return Err(ContractError::AssetMismatch {});
+    };
+
+    let offer_info = pools[offer_index].info.clone();
+    let offer_received = match &offer_info {
+        AssetInfo::NativeToken { .. } => {
+            Asset {
+                info: offer_info.clone(),
+                amount: max_offer,
+            }
+            .assert_sent_native_token_balance(&info)?;
+            true
+        }
+        AssetInfo::Token { contract_addr } => {
+            // Only the offer token contract can send the offer assets in advance
+            if offer_received && contract_addr != &info.sender {
+                return Err(ContractError::Unauthorized {});
+            }
+            offer_received
+        }
+    };
+
+    let offer_pool = pools[offer_index].clone();
+    let ask_pool = pools[ask_index].clone();
+
+    // Get the fee info cached from the factory
+    let fee_info = FACTORY_PARAMS.load(deps.storage)?.fee_info;
+
+    let (offer_amount, spread_amount, commission_amount) = compute_offer_amount(
+        offer_pool.amount,
+        ask_pool.amount,
+        ask_asset.amount,
+        fee_info.total_fee_rate,
+    )?;
+
+    if offer_amount > max_offer {
+        return Err(ContractError::MaxOfferAssertion {});
+    }
+
+    let mut messages: Vec<CosmosMsg> = vec![];
+    if !offer_received {
+        messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: offer_info.to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::TransferFrom {
+                owner: sender.to_string(),
+                recipient: env.contract.address.to_string(),
+                amount: offer_amount,
+            })?,
+            funds: vec![],
+        }));
+    }
+
+    let tax_amount = ask_asset.compute_tax(&deps.querier)?;
+    let receiver = to.unwrap_or_else(|| sender.clone());
+    messages.push(
+        ask_asset
+            .clone()
+            .into_msg(&deps.querier, receiver.clone())?,
+    );
+
+    // Send back the offer assets that were not used in the swap
+    let refund_amount = if offer_received {
+        max_offer - offer_amount
+    } else {
+        Uint128::zero()
+    };
+    if !refund_amount.is_zero() {
+        let refund_asset = Asset {
+            info: offer_info.clone(),
+            amount: refund_amount,
+        };
+        messages.push(refund_asset.into_msg(&deps.querier, sender.clone())?);
+    }
+
+    // Compute the Maker fee
+    let mut maker_fee_amount = Uint128::new(0);
+    if let Some(fee_address) = swap_fee_address(&fee_info) {
+        if let Some(f) = calculate_maker_fee(
+            ask_pool.info.clone(),
+            commission_amount,
+            fee_info.maker_fee_rate,
+        ) {
+            maker_fee_amount = f.amount;
+            messages.extend(pay_maker_fee(
+                deps.storage,
+                &deps.querier,
+                &config,
+                f,
+                fee_address,
+            )?);
+        }
+    }
+
+    // Accumulate prices for the assets in the pool
+    update_cumulative_prices(
+        deps.storage,
+        &env,
+        &mut config,
+        pools[0].amount,
+        pools[1].amount,
+    )?;
+
+    save_reserves_after_swap(
+        deps.storage,
+        &pools,
+        &offer_pool.info,
+        offer_amount,
+        ask_asset.amount.checked_add(maker_fee_amount)?,
+    )?;
+
+    Ok(Response::new()
+        .add_messages(
+            // 1. pull offer tokens from the sender (if they were not sent in advance)
+            // 2. send ask assets from the contract to a user
+            // 3. send back the unused offer assets
+            // 4. send inactive commission fees to the Maker ontract
+            messages,
+        )
+        .add_attribute("action", "swap")
+        .add_attribute("sender", sender.as_str())
+        .add_attribute("receiver", receiver.as_str())
+        .add_attribute("offer_asset", offer_info.to_string())
+        .add_attribute("ask_asset", ask_pool.info.to_string())
+        .add_attribute("offer_amount", offer_amount.to_string())
+        .add_attribute("return_amount", ask_asset.amount.to_string())
+        .add_attribute("refund_amount", refund_amount.to_string())
+        .add_attribute("tax_amount", tax_amount.to_string())
+        .add_attribute("spread_amount", spread_amount.to_string())
+        .add_attribute("commission_amount", commission_amount.to_string())
+        .add_attribute("maker_fee_amount", maker_fee_amount.to_string()))
+}
+
+/// ## Description
+/// Sends an asset from the pool to a receiver contract and calls it back with a [`FlashLoanReceiverMsg`].
+/// The repayment is checked by the [`ExecuteMsg::AssertFlashLoanRepaid`] callback that is executed after the receiver.
+/// Returns a [`ContractError`] on failure, otherwise returns a [`Response`] with the specified
+/// attributes if the operation was successful.
+/// ## Params
+/// * **deps** is an object of type [`DepsMut`].
+///
+/// * **env** is an object of type [`Env`].
+///
+/// * **info** is an object of type [`MessageInfo`].
+///
+/// * **asset** is an object of type [`Asset`]. This is the asset to borrow.
+///
+/// * **receiver** is an [`Option`] field of type [`String`]. This is the contract that receives the borrowed asset
+/// and the callback. If no custom receiver is specified, the sender is called back.
+///
+/// * **msg** is an object of type [`Binary`]. This is the message that is passed to the receiver in the callback.
+pub fn flash_loan(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    asset: Asset,
+    receiver: Option<String>,
+    msg: Binary,
+) -> Result<Response, ContractError> {
+    assert_swaps_allowed(deps.storage)?;
+    asset.info.check(deps.api)?;
+    if asset.amount.is_zero() {
+        return Err(ContractError::InvalidZeroAmount {});
+    }
+
+    let mut config: Config = CONFIG.load(deps.storage)?;
+    let pools = query_reserves(deps.storage, &config)?;
+
+    let borrowed_pool = pools
+        .iter()
+        .find(|p| p.info.equal(&asset.info))
+        .ok_or(ContractError::AssetMismatch {})?;
+    if pools.iter().any(|p| p.amount.is_zero()) || asset.amount >= borrowed_pool.amount {
+        return Err(ContractError::FlashLoanAmountExceedsPool {});
+    }
+
+    // Get the fee info cached from the factory
+    let fee_info = FACTORY_PARAMS.load(deps.storage)?.fee_info;
+
+    // The fee is split between the LPs and the Maker the same way as a swap commission
+    let commission_amount = asset.amount * fee_info.total_fee_rate;
+    let fee_address = swap_fee_address(&fee_info);
+    let mut maker_fee_amount = Uint128::zero();
+    if fee_address.is_some() {
+        if let Some(f) = calculate_maker_fee(
+            asset.info.clone(),
+            commission_amount,
+            fee_info.maker_fee_rate,
+        ) {
+            maker_fee_amount = f.amount;
+        }
+    }
+
+    // Assets that were sent to the pair without a sync must not be usable to repay the loan
+    let balances = query_pool_balances(deps.as_ref(), &config, env.contract.address.clone())?;
+    let unsynced_amounts = [
+        balances[0].amount.checked_sub(pools[0].amount)?,
+        balances[1].amount.checked_sub(pools[1].amount)?,
+    ];
+
+    // Accumulate prices for the assets in the pool
+    update_cumulative_prices(
+        deps.storage,
+        &env,
+        &mut config,
+        pools[0].amount,
+        pools[1].amount,
+    )?;
+
+    FLASH_LOAN.save(
+        deps.storage,
+        &FlashLoanState {
+            pools: pools.clone(),
+            unsynced_amounts,
+            asset: asset.clone(),
+            commission_amount,
+            maker_fee_amount,
+            fee_address,
+        },
+    )?;
+
+    let receiver = addr_validate_to_lower(
+        deps.api,
+        &receiver.unwrap_or_else(|| info.sender.to_string()),
+    )?;
+
+    let messages: Vec<CosmosMsg> = vec![
+        asset.clone().into_msg(&deps.querier, receiver.clone())?,
+        CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: receiver.to_string(),
+            msg: to_binary(&FlashLoanReceiverMsg::FlashLoanCallback {
+                asset: asset.clone(),
+                fee: commission_amount,
+                msg,
+            })?,
+            funds: vec![],
+        }),
+        CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: env.contract.address.to_string(),
+            msg: to_binary(&ExecuteMsg::AssertFlashLoanRepaid {})?,
+            funds: vec![],
+        }),
+    ];
+
+    Ok(Response::new()
+        .add_messages(
+            // 1. send the borrowed asset to the receiver
+            // 2. call the receiver back
+            // 3. check that the loan was repaid
+            messages,
+        )
+        .add_attribute("action", "flash_loan")
+        .add_attribute("sender", info.sender.as_str())
+        .add_attribute("receiver", receiver.as_str())
+        .add_attribute("asset", asset.to_string())
+        .add_attribute("commission_amount", commission_amount.to_string())
+        .add_attribute("maker_fee_amount", maker_fee_amount.to_string()))
+}
+
+/// ## Description
+/// Checks that the flash loan in progress was repaid: the pool invariant after the loan (without the Maker fee)
+/// must be at least the invariant before the loan with the LP part of the fee added to the borrowed asset.
+/// Assets that were sent to the pair without a sync before the loan don't count towards the repayment.
+/// Pays the Maker fee and unlocks the pair. Returns a [`ContractError`] on failure, otherwise returns
+/// a [`Response`] with the specified attributes if the operation was successful.
+/// ## Params
+/// * **deps** is an object of type [`DepsMut`].
+///
+/// * **env** is an object of type [`Env`].
+///
+/// * **info** is an object of type [`MessageInfo`].
+pub fn assert_flash_loan_repaid(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    // Callback functions can only be called by this contract
+    if info.sender != env.contract.address {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let flash_loan = FLASH_LOAN.load(deps.storage)?;
+    FLASH_LOAN.remove(deps.storage);
+
+    let config: Config = CONFIG.load(deps.storage)?;
+    let mut pools = query_pool_balances(deps.as_ref(), &config, env.contract.address)?;
+    let mut expected_pools = flash_loan.pools;
+
+    // Assets that were held on top of the reserves before the loan are not a repayment and stay unsynced
+    for (pool, unsynced_amount) in pools.iter_mut().zip(flash_loan.unsynced_amounts.iter()) {
+        pool.amount = pool
+            .amount
+            .checked_sub(*unsynced_amount)
+            .map_err(|_| ContractError::FlashLoanNotRepaid {})?;
+    }
+
+    let lp_fee_amount = flash_loan
+        .commission_amount
+        .checked_sub(flash_loan.maker_fee_amount)?;
+    for (pool, expected_pool) in pools.iter_mut().zip(expected_pools.iter_mut()) {
+        if pool.info.equal(&flash_loan.asset.info) {
+            // The Maker fee leaves the pool, so it doesn't count towards the invariant
+            pool.amount = pool
+                .amount
+                .checked_sub(flash_loan.maker_fee_amount)
+                .map_err(|_| ContractError::FlashLoanNotRepaid {})?;
+            expected_pool.amount = expected_pool.amount.checked_add(lp_fee_amount)?;
+        }
+    }
+
+    if pools[0].amount.full_mul(pools[1].amount)
+        < expected_pools[0].amount.full_mul(expected_pools[1].amount)
+    {
+        return Err(ContractError::FlashLoanNotRepaid {});
+    }
+
+    // Anything repaid on top of the loan stays in the pool
+    RESERVES.save(deps.storage, &[pools[0].amount, pools[1].amount])?;
+
+    let mut messages: Vec<CosmosMsg> = vec![];
+    if let Some(fee_address) = flash_loan.fee_address {
+        if !flash_loan.maker_fee_amount.is_zero() {
+            let maker_fee = Asset {
+                info: flash_loan.asset.info,
+                amount: flash_loan.maker_fee_amount,
+            };
+            messages.extend(pay_maker_fee(
+                deps.storage,
+                &deps.querier,
+                &config,
+                maker_fee,
+                fee_address,
+            )?);
+        }
+    }
+
+    Ok(Response::new()
+        .add_messages(messages)
+        .add_attribute("action", "assert_flash_loan_repaid")
+        .add_attribute("maker_fee_amount", flash_loan.maker_fee_amount.to_string()))
+}
+
+/// ## Description
+/// Sets the tracked pool reserves to the current balances of the pair (without the accrued protocol fees),
+/// so that assets sent to the pair directly are added to the pool. Cumulative prices are accumulated using the reserves before the sync.
+/// Returns a [`ContractError`] on failure, otherwise returns a [`Response`] with the specified
+/// attributes if the operation was successful.
+/// ## Params
+/// * **deps** is an object of type [`DepsMut`].
+///
+/// * **env** is an object of type [`Env`].
+pub fn sync(deps: DepsMut, env: Env) -> Result<Response, ContractError> {
+    let mut config: Config = CONFIG.load(deps.storage)?;
+    let reserves = query_reserves(deps.storage, &config)?;
+
+    // Accumulate prices for the assets in the pool
+    update_cumulative_prices(
+        deps.storage,
+        &env,
+        &mut config,
+        reserves[0].amount,
+        reserves[1].amount,
+    )?;
+
+    let pools = query_pool_balances(deps.as_ref(), &config, env.contract.address)?;
+    RESERVES.save(deps.storage, &[pools[0].amount, pools[1].amount])?;
+
+    Ok(Response::new()
+        .add_attribute("action", "sync")
+        .add_attribute("reserves", format!("{}, {}", pools[0], pools[1])))
+}
+
+/// ## Description
+/// Sends the balances of the pair that exceed the tracked pool reserves and the accrued protocol fees
+/// to a recipient. Returns a [`ContractError`] on failure, otherwise returns a [`Response`] with the specified
+/// attributes if the operation was successful.
+/// ## Params
+/// * **deps** is an object of type [`DepsMut`].
+///
+/// * **env** is an object of type [`Env`].
+///
+/// * **info** is an object of type [`MessageInfo`].
+///
+/// * **to** is an [`Option`] field of type [`String`]. This is the recipient of the excess assets.
+/// If no custom recipient is specified, the assets are sent to the function caller.
+pub fn skim(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    to: Option<String>,
+) -> Result<Response, ContractError> {
+    let config: Config = CONFIG.load(deps.storage)?;
+    let reserves = query_reserves(deps.storage, &config)?;
+    let pools = query_pool_balances(deps.as_ref(), &config, env.contract.address)?;
+
+    let to = addr_validate_to_lower(deps.api, &to.unwrap_or_else(|| info.sender.to_string()))?;
+
+    let mut messages: Vec<CosmosMsg> = vec![];
+    let mut excess_assets: Vec<Asset> = vec![];
+    for (pool, reserve) in pools.iter().zip(reserves.iter()) {
+        let excess = Asset {
+            info: pool.info.clone(),
+            amount: pool.amount.saturating_sub(reserve.amount),
+        };
+        if !excess.amount.is_zero() {
+            messages.push(excess.clone().into_msg(&deps.querier, to.clone())?);
+        }
+        excess_assets.push(excess);
+    }
+
+    Ok(Response::new()
+        .add_messages(messages)
+        .add_attribute("action", "skim")
+        .add_attribute("receiver", to.as_str())
+        .add_attribute(
+            "assets",
+            format!("{}, {}", excess_assets[0], excess_assets[1]),
+        ))
+}
+
+/// ## Description
+/// Accumulate token prices for the assets in the pool.
+/// Note that this function shifts **block_time** when any of the token prices is zero in order to not
+/// fill an accumulator with a null price for that period.
+/// ## Params
+/// * **env** is an object of type [`Env`].
+///
+/// * **config** is an object of type [`Config`].
+///
+/// * **x** is an object of type [`Uint128`]. This is the balance of asset\[\0] in the pool.
+///
+/// * **y** is an object of type [`Uint128`]. This is the balance of asset\[\1] in the pool.
+pub fn accumulate_prices(
+    env: Env,
+    config: &Config,
+    x: Uint128,
+    y: Uint128,
+) -> StdResult<Option<(Uint128, Uint128, u64)>> {
+    let block_time = env.block.time.seconds();
+    if block_time <= config.block_time_last {
+        return Ok(None);
+    }
+
+    // We have to shift block_time when any price is zero in order to not fill an accumulator with a null price for that period
+    let time_elapsed = Uint128::from(block_time - config.block_time_last);
+
+    let mut pcl0 = config.price0_cumulative_last;
+    let mut pcl1 = config.price1_cumulative_last;
+
+    if !x.is_zero() && !y.is_zero() {
+        let price_precision = Uint128::from(10u128.pow(TWAP_PRECISION.into()));
+        pcl0 = config.price0_cumulative_last.wrapping_add(
+            time_elapsed
+                .checked_mul(price_precision)?
+                .multiply_ratio(y, x),
+        );
+        pcl1 = config.price1_cumulative_last.wrapping_add(
+            time_elapsed
+                .checked_mul(price_precision)?
+                .multiply_ratio(x, y),
+        );
+    };
+
+    Ok(Some((pcl0, pcl1, block_time)))
+}
+
+/// ## Description
+/// Accumulate 256-bit token prices for the assets in the pool with [`TWAP_PRECISION_U256`] decimals,
+/// so that tiny prices and prices of assets with very different decimals don't lose their digits.
+/// Returns [`None`] if the prices were already accumulated in the current block.
+/// ## Params
+/// * **env** is an object of type [`Env`].
 ///
 /// * **config** is an object of type [`Config`].
 ///
+/// * **prices_cumulative_last** is an array with two objects of type [`StdUint256`].
+/// These are the last cumulative prices of the pool assets.
+///
+/// * **x** is an object of type [`Uint128`]. This is the balance of asset\[\0] in the pool.
+///
+/// * **y** is an object of type [`Uint128`]. This is the balance of asset\[\1] in the pool.
+pub fn accumulate_prices_u256(
+    env: Env,
+    config: &Config,
+    prices_cumulative_last: [StdUint256; 2],
+    x: Uint128,
+    y: Uint128,
+) -> StdResult<Option<[StdUint256; 2]>> {
+    let block_time = env.block.time.seconds();
+    if block_time <= config.block_time_last {
+        return Ok(None);
+    }
+
+    let mut prices_cumulative = prices_cumulative_last;
+
+    if !x.is_zero() && !y.is_zero() {
+        let price_precision = StdUint256::from(10u128.pow(TWAP_PRECISION_U256.into()));
+        let time_elapsed =
+            StdUint256::from(block_time - config.block_time_last).checked_mul(price_precision)?;
+        prices_cumulative[0] = prices_cumulative[0].wrapping_add(time_elapsed.multiply_ratio(y, x));
+        prices_cumulative[1] = prices_cumulative[1].wrapping_add(time_elapsed.multiply_ratio(x, y));
+    }
+
+    Ok(Some(prices_cumulative))
+}
+
+/// ## Description
+/// Accumulates the prices for the assets in the pool and saves both the 256-bit cumulative prices and
+/// the config with the updated cumulative prices.
+/// ## Params
+/// * **storage** is an object of type [`Storage`].
+///
+/// * **env** is an object of type [`Env`].
+///
+/// * **config** is an object of type [`Config`]. It is updated in place.
+///
 /// * **x** is an object of type [`Uint128`]. This is the balance of asset\[\0] in the pool.
 ///
 /// * **y** is an object of type [`Uint128`]. This is the balance of asset\[\1] in the pool.
-pub fn accumulate_prices(
-    env: Env,
-    config: &Config,
+pub fn update_cumulative_prices(
+    storage: &mut dyn Storage,
+    env: &Env,
+    config: &mut Config,
     x: Uint128,
     y: Uint128,
-) -> StdResult<Option<(Uint128, Uint128, u64)>> {
-    let block_time = env.block.time.seconds();
-    if block_time <= config.block_time_last {
-        return Ok(None);
+) -> StdResult<()> {
+    // The 256-bit prices are accumulated first as both use the block time of the last update
+    if let Some(prices_cumulative) = accumulate_prices_u256(
+        env.clone(),
+        config,
+        load_cumulative_prices_u256(storage)?,
+        x,
+        y,
+    )? {
+        PRICE_CUMULATIVE_LAST_U256.save(storage, &prices_cumulative)?;
+        // Prices are accumulated at most once per block time, so at most one observation is written per block
+        write_observation(
+            storage,
+            Observation {
+                timestamp: env.block.time.seconds(),
+                price0_cumulative: prices_cumulative[0],
+                price1_cumulative: prices_cumulative[1],
+            },
+        )?;
     }
 
-    // We have to shift block_time when any price is zero in order to not fill an accumulator with a null price for that period
-    let time_elapsed = Uint128::from(block_time - config.block_time_last);
+    if let Some((price0_cumulative_new, price1_cumulative_new, block_time)) =
+        accumulate_prices(env.clone(), config, x, y)?
+    {
+        config.price0_cumulative_last = price0_cumulative_new;
+        config.price1_cumulative_last = price1_cumulative_new;
+        config.block_time_last = block_time;
+        CONFIG.save(storage, config)?;
+    }
 
-    let mut pcl0 = config.price0_cumulative_last;
-    let mut pcl1 = config.price1_cumulative_last;
+    Ok(())
+}
 
-    if !x.is_zero() && !y.is_zero() {
-        let price_precision = Uint128::from(10u128.pow(TWAP_PRECISION.into()));
-        pcl0 = config.price0_cumulative_last.wrapping_add(
-            time_elapsed
-                .checked_mul(price_precision)?
-                .multiply_ratio(y, x),
-        );
-        pcl1 = config.price1_cumulative_last.wrapping_add(
-            time_elapsed
-                .checked_mul(price_precision)?
-                .multiply_ratio(x, y),
-        );
-    };
+/// ## Description
+/// Returns the last 256-bit cumulative prices of the pool assets. They are zero until the first
+/// accumulation after the pair is instantiated or migrated.
+/// ## Params
+/// * **storage** is an object of type [`Storage`].
+fn load_cumulative_prices_u256(storage: &dyn Storage) -> StdResult<[StdUint256; 2]> {
+    Ok(PRICE_CUMULATIVE_LAST_U256
+        .may_load(storage)?
+        .unwrap_or_default())
+}
 
-    Ok(Some((pcl0, pcl1, block_time)))
+/// ## Description
+/// Updates the pair configuration with the specified parameters in the `params` binary. Only the factory
+/// owner can execute this.
+/// ## Params
+/// * **deps** is an object of type [`DepsMut`].
+///
+/// * **info** is an object of type [`MessageInfo`].
+///
+/// * **params** is an object of type [`Binary`]. These are the new parameters for the pair.
+pub fn update_config(
+    deps: DepsMut,
+    info: MessageInfo,
+    params: Binary,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    let factory_config = query_factory_config(&deps.querier, config.factory_addr)?;
+
+    if info.sender != factory_config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    match from_binary::<XykPoolUpdateParams>(&params)? {
+        XykPoolUpdateParams::SetObservationsSize { size } => {
+            if size == 0 || size > MAX_OBSERVATIONS_SIZE {
+                return Err(ContractError::InvalidObservationsSize {});
+            }
+
+            set_observations_size(deps.storage, size)?;
+
+            Ok(Response::new().add_attributes(vec![
+                attr("action", "set_observations_size"),
+                attr("size", size.to_string()),
+            ]))
+        }
+    }
 }
 
 /// ## Description
@@ -797,6 +1857,135 @@ pub fn calculate_maker_fee(
     })
 }
 
+/// ## Description
+/// Pays the Maker fee. If the accrual of the Maker fees is enabled, the fee stays in the pair and is
+/// recorded in the accrued protocol fees, otherwise returns a message that sends the fee to the fee address.
+/// ## Params
+/// * **storage** is an object of type [`Storage`].
+///
+/// * **querier** is an object of type [`QuerierWrapper`].
+///
+/// * **config** is an object of type [`Config`].
+///
+/// * **maker_fee** is an object of type [`Asset`]. This is the Maker fee to pay.
+///
+/// * **fee_address** is an object of type [`Addr`]. This is the address that receives the Maker fees.
+pub fn pay_maker_fee(
+    storage: &mut dyn Storage,
+    querier: &QuerierWrapper,
+    config: &Config,
+    maker_fee: Asset,
+    fee_address: Addr,
+) -> StdResult<Option<CosmosMsg>> {
+    if !ACCRUE_PROTOCOL_FEES.may_load(storage)?.unwrap_or_default() {
+        return Ok(Some(maker_fee.into_msg(querier, fee_address)?));
+    }
+
+    let mut protocol_fees = load_protocol_fees(storage)?;
+    for (protocol_fee, asset_info) in protocol_fees
+        .iter_mut()
+        .zip(config.pair_info.asset_infos.iter())
+    {
+        if asset_info.equal(&maker_fee.info) {
+            *protocol_fee = protocol_fee.checked_add(maker_fee.amount)?;
+        }
+    }
+    PROTOCOL_FEES.save(storage, &protocol_fees)?;
+
+    Ok(None)
+}
+
+/// ## Description
+/// Returns the address that receives the Maker fee of every swap. Returns [`None`] if there is no fee address
+/// or if the Maker fee is collected by minting LP tokens instead.
+/// ## Params
+/// * **fee_info** is an object of type [`FeeInfo`].
+fn swap_fee_address(fee_info: &FeeInfo) -> Option<Addr> {
+    if fee_info.maker_fee_as_lp {
+        None
+    } else {
+        fee_info.fee_address.clone()
+    }
+}
+
+/// ## Description
+/// Mints the Maker fee in LP tokens for the fee address if the Maker fee is collected in LP tokens.
+/// Like `_mintFee` in Uniswap v2, the Maker gets its share of the swap fees accrued since the last liquidity
+/// event, which is measured by the growth of sqrt(k) of the pool. Returns the amount of minted LP tokens
+/// together with the mint messages.
+/// ## Params
+/// * **deps** is an object of type [`Deps`].
+///
+/// * **env** is an object of type [`Env`].
+///
+/// * **config** is an object of type [`Config`].
+///
+/// * **pools** is an array with two objects of type [`Asset`]. These are the pool reserves before the liquidity event.
+///
+/// * **total_share** is an object of type [`Uint128`]. This is the total amount of LP tokens.
+fn mint_maker_fee_lp(
+    deps: Deps,
+    env: Env,
+    config: &Config,
+    pools: &[Asset; 2],
+    total_share: Uint128,
+) -> Result<(Uint128, Vec<CosmosMsg>), ContractError> {
+    let fee_info = FACTORY_PARAMS.load(deps.storage)?.fee_info;
+    let root_k_last = ROOT_K_LAST.may_load(deps.storage)?.unwrap_or_default();
+    let fee_address = match fee_info.fee_address {
+        Some(fee_address) if fee_info.maker_fee_as_lp && !root_k_last.is_zero() => fee_address,
+        _ => return Ok((Uint128::zero(), vec![])),
+    };
+
+    let root_k = compute_root_k(pools[0].amount, pools[1].amount);
+    if root_k <= root_k_last {
+        return Ok((Uint128::zero(), vec![]));
+    }
+
+    // The Maker owns the maker_fee_rate part of the growth after the mint:
+    // share / (total_share + share) == fee_growth / root_k
+    let fee_growth = (root_k - root_k_last) * fee_info.maker_fee_rate;
+    let share = total_share.multiply_ratio(fee_growth, root_k - fee_growth);
+    if share.is_zero() {
+        return Ok((Uint128::zero(), vec![]));
+    }
+
+    let messages = mint_liquidity_token_message(deps, config, env, fee_address, share, false)?;
+
+    Ok((share, messages))
+}
+
+/// ## Description
+/// Saves sqrt(k) of the pool reserves after a liquidity event if the Maker fee is collected in LP tokens,
+/// otherwise stops tracking it.
+/// ## Params
+/// * **storage** is an object of type [`Storage`].
+///
+/// * **reserves** is an array with two objects of type [`Uint128`]. These are the pool reserves after the liquidity event.
+fn update_root_k_last(storage: &mut dyn Storage, reserves: [Uint128; 2]) -> StdResult<()> {
+    let fee_info = FACTORY_PARAMS.load(storage)?.fee_info;
+    if fee_info.maker_fee_as_lp && fee_info.fee_address.is_some() {
+        ROOT_K_LAST.save(storage, &compute_root_k(reserves[0], reserves[1]))
+    } else {
+        ROOT_K_LAST.remove(storage);
+        Ok(())
+    }
+}
+
+/// ## Description
+/// Returns sqrt(k) of a constant product pool.
+/// ## Params
+/// * **x** is an object of type [`Uint128`]. This is the amount of asset\[\0] in the pool.
+///
+/// * **y** is an object of type [`Uint128`]. This is the amount of asset\[\1] in the pool.
+fn compute_root_k(x: Uint128, y: Uint128) -> Uint128 {
+    Uint128::new(
+        (U256::from(x.u128()) * U256::from(y.u128()))
+            .integer_sqrt()
+            .as_u128(),
+    )
+}
+
 /// ## Description
 /// Exposes all the queries available in the contract.
 /// ## Params
@@ -824,6 +2013,30 @@ pub fn calculate_maker_fee(
 /// pool using a [`CumulativePricesResponse`] object.
 ///
 /// * **QueryMsg::Config {}** Returns the configuration for the pair contract using a [`ConfigResponse`] object.
+///
+/// * **QueryMsg::ProtocolFees {}** Returns the Maker fees accrued in the pair using a [`ProtocolFeesResponse`] object.
+///
+/// * **QueryMsg::Observe { seconds_ago }** Returns the cumulative prices at the specified number of seconds ago
+/// using an [`ObserveResponse`] object.
+///
+/// * **QueryMsg::SpotPrice { asset_info }** Returns the spot price of an asset using a [`SpotPriceResponse`] object.
+///
+/// * **QueryMsg::PriceImpact { offer_asset }** Returns the price impact of a swap using a [`PriceImpactResponse`] object.
+///
+/// * **QueryMsg::Depth { asset_info, price_change }** Returns the amount of an asset that can be swapped before
+/// its price drops by the specified percentage using a [`DepthResponse`] object.
+///
+/// * **QueryMsg::SimulateProvide { assets, slippage_tolerance }** Returns the amount of LP tokens minted
+/// for a deposit using a [`SimulateProvideResponse`] object.
+///
+/// * **QueryMsg::SimulateWithdraw { lp_amount }** Returns the amounts of assets received for burning
+/// LP tokens in a [`Vec<Asset>`] object.
+///
+/// * **QueryMsg::SimulateWithdrawOneCoin { .. }** and **QueryMsg::SimulateWithdrawImbalanced { .. }** Not supported.
+///
+/// * **QueryMsg::VirtualPrice {}**, **QueryMsg::CurrentD {}** and **QueryMsg::AmpHistory { .. }** Not supported.
+///
+/// * **QueryMsg::PairStatus {}** Returns the trading state of the pair set in the factory using a [`astroport::factory::PairStatus`] object.
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
     match msg {
@@ -836,6 +2049,32 @@ pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
         }
         QueryMsg::CumulativePrices {} => to_binary(&query_cumulative_prices(deps, env)?),
         QueryMsg::Config {} => to_binary(&query_config(deps)?),
+        QueryMsg::ProtocolFees {} => to_binary(&query_protocol_fees(deps)?),
+        QueryMsg::Observe { seconds_ago } => to_binary(&query_observe(deps, env, seconds_ago)?),
+        QueryMsg::SpotPrice { asset_info } => to_binary(&query_spot_price(deps, asset_info)?),
+        QueryMsg::PriceImpact { offer_asset } => to_binary(&query_price_impact(deps, offer_asset)?),
+        QueryMsg::Depth {
+            asset_info,
+            price_change,
+        } => to_binary(&query_depth(deps, asset_info, price_change)?),
+        QueryMsg::SimulateProvide {
+            assets,
+            slippage_tolerance,
+        } => to_binary(&query_simulate_provide(
+            deps,
+            env,
+            assets,
+            slippage_tolerance,
+        )?),
+        QueryMsg::SimulateWithdraw { lp_amount } => {
+            to_binary(&query_simulate_withdraw(deps, env, lp_amount)?)
+        }
+        QueryMsg::PairStatus {} => to_binary(&FACTORY_PARAMS.load(deps.storage)?.pair_status),
+        QueryMsg::SimulateWithdrawOneCoin { .. }
+        | QueryMsg::SimulateWithdrawImbalanced { .. }
+        | QueryMsg::VirtualPrice {}
+        | QueryMsg::CurrentD {}
+        | QueryMsg::AmpHistory { .. } => Err(StdError::generic_err("Operation non supported")),
     }
 }
 
@@ -888,9 +2127,7 @@ pub fn query_share(deps: Deps, amount: Uint128) -> StdResult<Vec<Asset>> {
 /// * **offer_asset** is an object of type [`Asset`]. This is the asset to swap as well as an amount of the said asset.
 pub fn query_simulation(deps: Deps, offer_asset: Asset) -> StdResult<SimulationResponse> {
     let config: Config = CONFIG.load(deps.storage)?;
-    let contract_addr = config.pair_info.contract_addr.clone();
-
-    let pools: [Asset; 2] = config.pair_info.query_pools(&deps.querier, contract_addr)?;
+    let pools = query_reserves(deps.storage, &config)?;
 
     let offer_pool: Asset;
     let ask_pool: Asset;
@@ -906,12 +2143,8 @@ pub fn query_simulation(deps: Deps, offer_asset: Asset) -> StdResult<SimulationR
         ));
     }
 
-    // Get fee info from the factory contract
-    let fee_info = query_fee_info(
-        &deps.querier,
-        config.factory_addr,
-        config.pair_info.pair_type,
-    )?;
+    // Get the fee info cached from the factory
+    let fee_info = FACTORY_PARAMS.load(deps.storage)?.fee_info;
 
     let (return_amount, spread_amount, commission_amount) = compute_swap(
         offer_pool.amount,
@@ -939,9 +2172,7 @@ pub fn query_reverse_simulation(
     ask_asset: Asset,
 ) -> StdResult<ReverseSimulationResponse> {
     let config: Config = CONFIG.load(deps.storage)?;
-    let contract_addr = config.pair_info.contract_addr.clone();
-
-    let pools: [Asset; 2] = config.pair_info.query_pools(&deps.querier, contract_addr)?;
+    let pools = query_reserves(deps.storage, &config)?;
 
     let offer_pool: Asset;
     let ask_pool: Asset;
@@ -957,55 +2188,297 @@ pub fn query_reverse_simulation(
         ));
     }
 
-    // Get fee info from factory
-    let fee_info = query_fee_info(
-        &deps.querier,
-        config.factory_addr,
-        config.pair_info.pair_type,
-    )?;
+    // Get the fee info cached from the factory
+    let fee_info = FACTORY_PARAMS.load(deps.storage)?.fee_info;
+
+    let (offer_amount, spread_amount, commission_amount) = compute_offer_amount(
+        offer_pool.amount,
+        ask_pool.amount,
+        ask_asset.amount,
+        fee_info.total_fee_rate,
+    )?;
+
+    Ok(ReverseSimulationResponse {
+        offer_amount,
+        spread_amount,
+        commission_amount,
+    })
+}
+
+/// ## Description
+/// Returns information about cumulative prices for the assets in the pool using a [`CumulativePricesResponse`] object.
+/// ## Params
+/// * **deps** is an object of type [`Deps`].
+///
+/// * **env** is an object of type [`Env`].
+pub fn query_cumulative_prices(deps: Deps, env: Env) -> StdResult<CumulativePricesResponse> {
+    let config: Config = CONFIG.load(deps.storage)?;
+    let (assets, total_share) = pool_info(deps, config.clone())?;
+
+    let mut price0_cumulative_last = config.price0_cumulative_last;
+    let mut price1_cumulative_last = config.price1_cumulative_last;
+    let mut prices_cumulative_u256 = load_cumulative_prices_u256(deps.storage)?;
+
+    if let Some(prices_cumulative_new) = accumulate_prices_u256(
+        env.clone(),
+        &config,
+        prices_cumulative_u256,
+        assets[0].amount,
+        assets[1].amount,
+    )? {
+        prices_cumulative_u256 = prices_cumulative_new;
+    }
+
+    if let Some((price0_cumulative_new, price1_cumulative_new, _)) =
+        accumulate_prices(env, &config, assets[0].amount, assets[1].amount)?
+    {
+        price0_cumulative_last = price0_cumulative_new;
+        price1_cumulative_last = price1_cumulative_new;
+    }
+
+    let resp = CumulativePricesResponse {
+        assets,
+        total_share,
+        price0_cumulative_last,
+        price1_cumulative_last,
+        price0_cumulative_last_u256: Some(prices_cumulative_u256[0]),
+        price1_cumulative_last_u256: Some(prices_cumulative_u256[1]),
+    };
+
+    Ok(resp)
+}
+
+/// ## Description
+/// Returns the price of one unit of an asset in the other pool asset in a [`SpotPriceResponse`] object.
+/// ## Params
+/// * **deps** is an object of type [`Deps`].
+///
+/// * **asset_info** is an object of type [`AssetInfo`]. This is the asset to return the price for.
+pub fn query_spot_price(deps: Deps, asset_info: AssetInfo) -> StdResult<SpotPriceResponse> {
+    let config: Config = CONFIG.load(deps.storage)?;
+    let (offer_pool, ask_pool) = select_pools(&asset_info, query_reserves(deps.storage, &config)?)?;
+
+    Ok(SpotPriceResponse {
+        price: compute_price(
+            offer_pool.amount,
+            query_token_precision(&deps.querier, offer_pool.info)?,
+            ask_pool.amount,
+            query_token_precision(&deps.querier, ask_pool.info)?,
+        )?
+        .into(),
+    })
+}
+
+/// ## Description
+/// Returns the spot price, the execution price and the price impact of a swap in a [`PriceImpactResponse`] object.
+/// ## Params
+/// * **deps** is an object of type [`Deps`].
+///
+/// * **offer_asset** is an object of type [`Asset`]. This is the asset to swap as well as an amount of the said asset.
+pub fn query_price_impact(deps: Deps, offer_asset: Asset) -> StdResult<PriceImpactResponse> {
+    let config: Config = CONFIG.load(deps.storage)?;
+    let (offer_pool, ask_pool) =
+        select_pools(&offer_asset.info, query_reserves(deps.storage, &config)?)?;
+    let offer_precision = query_token_precision(&deps.querier, offer_pool.info)?;
+    let ask_precision = query_token_precision(&deps.querier, ask_pool.info)?;
+
+    let spot_price = compute_price(
+        offer_pool.amount,
+        offer_precision,
+        ask_pool.amount,
+        ask_precision,
+    )?;
+
+    // Get the fee info cached from the factory
+    let fee_info = FACTORY_PARAMS.load(deps.storage)?.fee_info;
+
+    let (return_amount, _, commission_amount) = compute_swap(
+        offer_pool.amount,
+        ask_pool.amount,
+        offer_asset.amount,
+        fee_info.total_fee_rate,
+    )?;
+    let execution_price = compute_price(
+        offer_asset.amount,
+        offer_precision,
+        return_amount + commission_amount,
+        ask_precision,
+    )?;
+
+    Ok(PriceImpactResponse {
+        spot_price: spot_price.into(),
+        execution_price: execution_price.into(),
+        price_impact: compute_price_impact(spot_price, execution_price).into(),
+    })
+}
+
+/// ## Description
+/// Returns the amount of an asset that can be swapped before its spot price drops by the specified
+/// percentage in a [`DepthResponse`] object. The commission is not taken into account.
+/// ## Params
+/// * **deps** is an object of type [`Deps`].
+///
+/// * **asset_info** is an object of type [`AssetInfo`]. This is the asset to swap.
+///
+/// * **price_change** is an object of type [`Decimal`]. This is the relative price change.
+pub fn query_depth(
+    deps: Deps,
+    asset_info: AssetInfo,
+    price_change: Decimal,
+) -> StdResult<DepthResponse> {
+    if price_change.is_zero() || price_change >= Decimal::one() {
+        return Err(StdError::generic_err(
+            "The price change must be between 0 and 1",
+        ));
+    }
+
+    let config: Config = CONFIG.load(deps.storage)?;
+    let (offer_pool, ask_pool) = select_pools(&asset_info, query_reserves(deps.storage, &config)?)?;
+    if offer_pool.amount.is_zero() || ask_pool.amount.is_zero() {
+        return Err(StdError::generic_err("The pool has no liquidity"));
+    }
+
+    let offer_amount = compute_depth(offer_pool.amount, price_change)?;
+
+    // Get the fee info cached from the factory
+    let fee_info = FACTORY_PARAMS.load(deps.storage)?.fee_info;
+
+    let (return_amount, _, _) = compute_swap(
+        offer_pool.amount,
+        ask_pool.amount,
+        offer_amount,
+        fee_info.total_fee_rate,
+    )?;
+
+    Ok(DepthResponse {
+        offer_amount,
+        return_amount,
+    })
+}
+
+/// ## Description
+/// Returns the amount of LP tokens minted for a deposit in an object of type [`SimulateProvideResponse`].
+/// The share is calculated in the same way as in [`provide_liquidity`], including the Maker fee minted beforehand.
+/// ## Params
+/// * **deps** is an object of type [`Deps`].
+///
+/// * **env** is an object of type [`Env`].
+///
+/// * **assets** is an array with two objects of type [`Asset`]. These are the assets to deposit.
+///
+/// * **slippage_tolerance** is an [`Option`] field of type [`Decimal`]. It is used to specify how much
+/// the pool price can move until the provide liquidity transaction goes through.
+pub fn query_simulate_provide(
+    deps: Deps,
+    env: Env,
+    assets: [Asset; 2],
+    slippage_tolerance: Option<Decimal>,
+) -> StdResult<SimulateProvideResponse> {
+    let config: Config = CONFIG.load(deps.storage)?;
+    let (pools, total_share) = pool_info(deps, config.clone())?;
+    let deposits = get_deposits(&assets, &pools)?;
+
+    let (maker_fee_share, _) = mint_maker_fee_lp(deps, env, &config, &pools, total_share)?;
+    let total_share = total_share.checked_add(maker_fee_share)?;
 
-    let (offer_amount, spread_amount, commission_amount) = compute_offer_amount(
-        offer_pool.amount,
-        ask_pool.amount,
-        ask_asset.amount,
-        fee_info.total_fee_rate,
-    )?;
+    let share = calculate_share(&deposits, &pools, total_share, slippage_tolerance)?;
 
-    Ok(ReverseSimulationResponse {
-        offer_amount,
-        spread_amount,
-        commission_amount,
+    Ok(SimulateProvideResponse {
+        share,
+        fees: pools
+            .iter()
+            .map(|pool| Asset {
+                info: pool.info.clone(),
+                amount: Uint128::zero(),
+            })
+            .collect(),
     })
 }
 
 /// ## Description
-/// Returns information about cumulative prices for the assets in the pool using a [`CumulativePricesResponse`] object.
+/// Returns the amounts of assets received for burning LP tokens in a [`Vec<Asset>`] object.
+/// The amounts are calculated in the same way as in [`withdraw_liquidity`].
 /// ## Params
 /// * **deps** is an object of type [`Deps`].
 ///
 /// * **env** is an object of type [`Env`].
-pub fn query_cumulative_prices(deps: Deps, env: Env) -> StdResult<CumulativePricesResponse> {
+///
+/// * **lp_amount** is an object of type [`Uint128`]. This is the amount of LP tokens to burn.
+pub fn query_simulate_withdraw(deps: Deps, env: Env, lp_amount: Uint128) -> StdResult<Vec<Asset>> {
     let config: Config = CONFIG.load(deps.storage)?;
-    let (assets, total_share) = pool_info(deps, config.clone())?;
+    let (pools, total_share) = pool_info(deps, config.clone())?;
 
-    let mut price0_cumulative_last = config.price0_cumulative_last;
-    let mut price1_cumulative_last = config.price1_cumulative_last;
+    let (maker_fee_share, _) = mint_maker_fee_lp(deps, env, &config, &pools, total_share)?;
+    let total_share = total_share.checked_add(maker_fee_share)?;
 
-    if let Some((price0_cumulative_new, price1_cumulative_new, _)) =
-        accumulate_prices(env, &config, assets[0].amount, assets[1].amount)?
-    {
-        price0_cumulative_last = price0_cumulative_new;
-        price1_cumulative_last = price1_cumulative_new;
+    Ok(get_share_in_assets(&pools, lp_amount, total_share))
+}
+
+/// ## Description
+/// Returns the offer and the ask pools for the specified offer asset.
+/// ## Params
+/// * **offer_asset_info** is an object of type [`AssetInfo`]. This is the asset to swap.
+///
+/// * **pools** are an array of [`Asset`] type items. These are the assets available in the pool.
+fn select_pools(offer_asset_info: &AssetInfo, pools: [Asset; 2]) -> StdResult<(Asset, Asset)> {
+    let [pool0, pool1] = pools;
+    if offer_asset_info.equal(&pool0.info) {
+        Ok((pool0, pool1))
+    } else if offer_asset_info.equal(&pool1.info) {
+        Ok((pool1, pool0))
+    } else {
+        Err(StdError::generic_err(
+            "Given offer asset does not belong in the pair",
+        ))
     }
+}
 
-    let resp = CumulativePricesResponse {
-        assets,
-        total_share,
-        price0_cumulative_last,
-        price1_cumulative_last,
+/// ## Description
+/// Returns the 256-bit cumulative prices at the specified number of seconds ago in an [`ObserveResponse`] object.
+/// Cumulative prices after the last price accumulation are extrapolated using the current reserves and cumulative
+/// prices between two stored observations are interpolated linearly.
+/// ## Params
+/// * **deps** is an object of type [`Deps`].
+///
+/// * **env** is an object of type [`Env`].
+///
+/// * **seconds_ago** is a [`Vec`] of type [`u64`]. These are the observation times relative to the current block time.
+pub fn query_observe(deps: Deps, env: Env, seconds_ago: Vec<u64>) -> StdResult<ObserveResponse> {
+    let config: Config = CONFIG.load(deps.storage)?;
+    let reserves = RESERVES.load(deps.storage)?;
+    let prices_cumulative_last = load_cumulative_prices_u256(deps.storage)?;
+    let last = Observation {
+        timestamp: config.block_time_last,
+        price0_cumulative: prices_cumulative_last[0],
+        price1_cumulative: prices_cumulative_last[1],
     };
 
-    Ok(resp)
+    let observations = observe(
+        deps.storage,
+        env.block.time.seconds(),
+        last,
+        seconds_ago,
+        |target| {
+            let mut env = env.clone();
+            env.block.time = Timestamp::from_seconds(target);
+            let prices_cumulative = accumulate_prices_u256(
+                env,
+                &config,
+                prices_cumulative_last,
+                reserves[0],
+                reserves[1],
+            )?
+            .unwrap_or(prices_cumulative_last);
+
+            Ok(Observation {
+                timestamp: target,
+                price0_cumulative: prices_cumulative[0],
+                price1_cumulative: prices_cumulative[1],
+            })
+        },
+    )?;
+
+    Ok(ObserveResponse { observations })
 }
 
 /// ## Description
@@ -1076,6 +2549,112 @@ pub fn compute_swap(
     ))
 }
 
+/// ## Description
+/// Returns the price of the offer asset in the ask asset for the specified amounts adjusted for the token precisions.
+/// ## Params
+/// * **offer_amount** is an object of type [`Uint128`]. This is the amount of offer assets.
+///
+/// * **offer_precision** is an object of type [`u8`]. This is the token precision used for the offer amount.
+///
+/// * **ask_amount** is an object of type [`Uint128`]. This is the amount of ask assets.
+///
+/// * **ask_precision** is an object of type [`u8`]. This is the token precision used for the ask amount.
+pub fn compute_price(
+    offer_amount: Uint128,
+    offer_precision: u8,
+    ask_amount: Uint128,
+    ask_precision: u8,
+) -> StdResult<Decimal256> {
+    if offer_amount.is_zero() || ask_amount.is_zero() {
+        return Err(StdError::generic_err(
+            "The price is undefined for zero amounts",
+        ));
+    }
+
+    Ok(Decimal256::from_ratio(
+        Uint256::from(ask_amount) * Uint256::from(10u64.pow(offer_precision.into())),
+        Uint256::from(offer_amount) * Uint256::from(10u64.pow(ask_precision.into())),
+    ))
+}
+
+/// ## Description
+/// Returns the relative difference between the spot price and the execution price of a swap.
+/// ## Params
+/// * **spot_price** is an object of type [`Decimal256`]. This is the spot price before the swap.
+///
+/// * **execution_price** is an object of type [`Decimal256`]. This is the average price of the swap.
+fn compute_price_impact(spot_price: Decimal256, execution_price: Decimal256) -> Decimal256 {
+    if execution_price >= spot_price {
+        Decimal256::zero()
+    } else {
+        Decimal256::one() - execution_price / spot_price
+    }
+}
+
+/// ## Description
+/// Returns the amount of offer assets that moves the spot price of the offer asset down by `price_change`.
+/// The spot price is `ask_pool / offer_pool` and the pool invariant stays constant, so the price drops by
+/// `price_change` when the offer pool grows to `offer_pool / sqrt(1 - price_change)`.
+/// ## Params
+/// * **offer_pool** is an object of type [`Uint128`]. This is the total amount of offer assets in the pool.
+///
+/// * **price_change** is an object of type [`Decimal`]. This is the relative price change.
+pub fn compute_depth(offer_pool: Uint128, price_change: Decimal) -> StdResult<Uint128> {
+    let precision = Uint128::new(10u128.pow(18));
+    let price_ratio = U256::from((precision * (Decimal::one() - price_change)).u128());
+    let offer_pool_u256 = U256::from(offer_pool.u128());
+
+    let new_offer_pool = offer_pool_u256
+        .checked_mul(offer_pool_u256)
+        .and_then(|pool_sq| pool_sq.checked_mul(U256::from(precision.u128())))
+        .and_then(|pool_sq| pool_sq.checked_div(price_ratio))
+        .ok_or_else(|| StdError::generic_err("Overflow while computing the depth"))?
+        .integer_sqrt();
+
+    u128::try_from(new_offer_pool - offer_pool_u256)
+        .map(Uint128::new)
+        .map_err(|_| StdError::generic_err("Overflow while computing the depth"))
+}
+
+/// ## Description
+/// Returns the part of a single-sided deposit that has to be swapped so that the remaining amount and
+/// the swap output match the pool ratio after the swap.
+/// The commission is charged on the swap output, so the swapped amount `s` is the positive root of
+/// `s^2 + (offer_pool * (2 - f) - amount * f) * s - amount * offer_pool = 0`.
+/// ## Params
+/// * **offer_pool** is an object of type [`Uint128`]. This is the total amount of offer assets in the pool.
+///
+/// * **amount** is an object of type [`Uint128`]. This is the amount of offer assets to deposit.
+///
+/// * **commission_rate** is an object of type [`Decimal`]. This is the total amount of fees charged for the swap.
+pub fn compute_single_sided_swap_amount(
+    offer_pool: Uint128,
+    amount: Uint128,
+    commission_rate: Decimal,
+) -> StdResult<Uint128> {
+    let pool_fee = U256::from((offer_pool * commission_rate).u128());
+    let amount_fee = U256::from((amount * commission_rate).u128());
+    let offer_pool = U256::from(offer_pool.u128());
+    let amount = U256::from(amount.u128());
+
+    let b_positive = offer_pool * U256::from(2u8) - pool_fee;
+    let discriminant = |b: U256| -> StdResult<U256> {
+        b.checked_mul(b)
+            .and_then(|b2| b2.checked_add(U256::from(4u8) * amount * offer_pool))
+            .ok_or_else(|| StdError::generic_err("Overflow while computing the swap amount"))
+    };
+
+    let swap_amount = if b_positive >= amount_fee {
+        let b = b_positive - amount_fee;
+        (discriminant(b)?.integer_sqrt() - b) / U256::from(2u8)
+    } else {
+        let b = amount_fee - b_positive;
+        (discriminant(b)?.integer_sqrt() + b) / U256::from(2u8)
+    };
+
+    Ok(Uint128::new(std::cmp::min(swap_amount, amount).as_u128()))
+}
+
 /// ## Description
 /// Returns an amount of offer assets for a specified amount of ask assets.
 /// ## Params
@@ -1161,6 +2740,71 @@ pub fn assert_max_spread(
     Ok(())
 }
 
+/// ## Description
+/// Returns a [`ContractError`] on failure if the current block time is past the specified deadline.
+/// ## Params
+/// * **env** is an object of type [`Env`].
+///
+/// * **deadline** is an [`Option`] field of type [`u64`]. This is the block time (in seconds) after which
+/// the operation fails.
+pub fn assert_deadline(env: &Env, deadline: Option<u64>) -> Result<(), ContractError> {
+    if let Some(deadline) = deadline {
+        if env.block.time.seconds() > deadline {
+            return Err(ContractError::DeadlineExpired {});
+        }
+    }
+
+    Ok(())
+}
+
+/// ## Description
+/// Returns a [`ContractError`] if the factory paused swaps in the pair.
+/// ## Params
+/// * **storage** is a reference to an object implementing [`Storage`].
+fn assert_swaps_allowed(storage: &dyn Storage) -> Result<(), ContractError> {
+    if !FACTORY_PARAMS.load(storage)?.pair_status.swaps_allowed() {
+        return Err(ContractError::SwapsPaused {});
+    }
+
+    Ok(())
+}
+
+/// ## Description
+/// Returns a [`ContractError`] if the factory paused deposits in the pair.
+/// ## Params
+/// * **storage** is a reference to an object implementing [`Storage`].
+fn assert_deposits_allowed(storage: &dyn Storage) -> Result<(), ContractError> {
+    if !FACTORY_PARAMS.load(storage)?.pair_status.deposits_allowed() {
+        return Err(ContractError::DepositsPaused {});
+    }
+
+    Ok(())
+}
+
+/// ## Description
+/// Returns a [`ContractError`] on failure if the amount of any withdrawn asset is less than the specified minimum.
+/// ## Params
+/// * **refund_assets** are an array of [`Asset`] type items. These are the assets withdrawn from the pool.
+///
+/// * **min_assets_out** is an [`Option`] field of type [`Vec<Asset>`]. These are the minimum amounts of
+/// assets to receive.
+fn assert_min_assets_out(
+    refund_assets: &[Asset],
+    min_assets_out: Option<Vec<Asset>>,
+) -> Result<(), ContractError> {
+    for min_asset in min_assets_out.unwrap_or_default() {
+        let refund_asset = refund_assets
+            .iter()
+            .find(|a| a.info.equal(&min_asset.info))
+            .ok_or(ContractError::AssetMismatch {})?;
+        if refund_asset.amount < min_asset.amount {
+            return Err(ContractError::MinAssetsOutAssertion {});
+        }
+    }
+
+    Ok(())
+}
+
 /// ## Description
 /// This is an internal function that enforces slippage tolerance for swaps.
 /// Returns a [`ContractError`] on failure, otherwise returns [`Ok`].
@@ -1201,27 +2845,319 @@ fn assert_slippage_tolerance(
 }
 
 /// ## Description
-/// Used for the contract migration. Returns a default object of type [`Response`].
+/// Enables or disables the accrual of the Maker fees in the pair. When enabled, the Maker fees stay in the pair
+/// and can be claimed by the fee address with [`ExecuteMsg::ClaimProtocolFees`]. Fees accrued before the accrual
+/// is disabled can still be claimed. Returns a [`ContractError`] on failure, otherwise returns a [`Response`]
+/// with the specified attributes if the operation was successful.
 /// ## Params
-/// * **_deps** is an object of type [`DepsMut`].
+/// * **deps** is an object of type [`DepsMut`].
 ///
-/// * **_env** is an object of type [`Env`].
+/// * **info** is an object of type [`MessageInfo`].
+///
+/// * **enabled** is an object of type [`bool`]. Sets whether the Maker fees are accrued in the pair.
+///
+/// ## Executor
+/// Only the factory owner can execute this.
+pub fn set_protocol_fee_accrual(
+    deps: DepsMut,
+    info: MessageInfo,
+    enabled: bool,
+) -> Result<Response, ContractError> {
+    let config: Config = CONFIG.load(deps.storage)?;
+    let factory_config = query_factory_config(&deps.querier, config.factory_addr)?;
+
+    if info.sender != factory_config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    ACCRUE_PROTOCOL_FEES.save(deps.storage, &enabled)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "set_protocol_fee_accrual")
+        .add_attribute("enabled", enabled.to_string()))
+}
+
+/// ## Description
+/// Sends the Maker fees accrued in the pair to the fee address. Returns a [`ContractError`] on failure,
+/// otherwise returns a [`Response`] with the specified attributes if the operation was successful.
+/// ## Params
+/// * **deps** is an object of type [`DepsMut`].
+///
+/// * **info** is an object of type [`MessageInfo`].
+///
+/// ## Executor
+/// Only the fee address can execute this.
+pub fn claim_protocol_fees(deps: DepsMut, info: MessageInfo) -> Result<Response, ContractError> {
+    // Get the fee info cached from the factory
+    let fee_info = FACTORY_PARAMS.load(deps.storage)?.fee_info;
+
+    if fee_info.fee_address != Some(info.sender.clone()) {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let config: Config = CONFIG.load(deps.storage)?;
+    let protocol_fees = load_protocol_fees(deps.storage)?;
+    PROTOCOL_FEES.save(deps.storage, &[Uint128::zero(), Uint128::zero()])?;
+
+    let mut messages: Vec<CosmosMsg> = vec![];
+    let mut claimed_assets: Vec<Asset> = vec![];
+    for (asset_info, amount) in config
+        .pair_info
+        .asset_infos
+        .iter()
+        .zip(protocol_fees.iter())
+    {
+        let claimed = Asset {
+            info: asset_info.clone(),
+            amount: *amount,
+        };
+        if !claimed.amount.is_zero() {
+            messages.push(
+                claimed
+                    .clone()
+                    .into_msg(&deps.querier, info.sender.clone())?,
+            );
+        }
+        claimed_assets.push(claimed);
+    }
+
+    Ok(Response::new()
+        .add_messages(messages)
+        .add_attribute("action", "claim_protocol_fees")
+        .add_attribute("receiver", info.sender.as_str())
+        .add_attribute(
+            "assets",
+            format!("{}, {}", claimed_assets[0], claimed_assets[1]),
+        ))
+}
+
+/// ## Description
+/// Updates the fee and Generator information that the pair caches from the factory.
+/// The factory pushes this message to its pairs when their parameters change, but anyone can execute it.
+/// The Maker fee accrued in LP tokens under the cached parameters is minted before they are replaced.
+/// ## Params
+/// * **deps** is an object of type [`DepsMut`].
+///
+/// * **env** is an object of type [`Env`].
+pub fn refresh_factory_params(deps: DepsMut, env: Env) -> Result<Response, ContractError> {
+    let config: Config = CONFIG.load(deps.storage)?;
+
+    // Otherwise the growth of the pool since the last liquidity event would be lost
+    // when the Maker fee stops being collected in LP tokens or its rate changes
+    let (pools, total_share) = pool_info(deps.as_ref(), config.clone())?;
+    let (maker_fee_share, messages) =
+        mint_maker_fee_lp(deps.as_ref(), env.clone(), &config, &pools, total_share)?;
+
+    let factory_params = query_factory_params(
+        &deps.querier,
+        config.factory_addr,
+        env.contract.address,
+        config.pair_info.pair_type,
+    )?;
+    FACTORY_PARAMS.save(deps.storage, &factory_params)?;
+
+    // The growth of the pool is measured from now on if the Maker fee starts being collected in LP tokens
+    // or if the accrued Maker fee was just minted
+    let fee_info = &factory_params.fee_info;
+    if !fee_info.maker_fee_as_lp
+        || fee_info.fee_address.is_none()
+        || ROOT_K_LAST.may_load(deps.storage)?.is_none()
+        || !maker_fee_share.is_zero()
+    {
+        let reserves = RESERVES.load(deps.storage)?;
+        update_root_k_last(deps.storage, reserves)?;
+    }
+
+    let fee_address = factory_params
+        .fee_info
+        .fee_address
+        .map(|addr| addr.to_string())
+        .unwrap_or_default();
+    let generator_address = factory_params
+        .generator_address
+        .map(|addr| addr.to_string())
+        .unwrap_or_default();
+
+    Ok(Response::new().add_messages(messages).add_attributes(vec![
+        attr("action", "refresh_factory_params"),
+        attr(
+            "total_fee_rate",
+            factory_params.fee_info.total_fee_rate.to_string(),
+        ),
+        attr(
+            "maker_fee_rate",
+            factory_params.fee_info.maker_fee_rate.to_string(),
+        ),
+        attr("fee_address", fee_address),
+        attr("generator_address", generator_address),
+        attr("maker_fee_share", maker_fee_share),
+        attr("pair_status", factory_params.pair_status.to_string()),
+    ]))
+}
+
+/// ## Description
+/// Used for the contract migration. Pairs that were instantiated before the pool reserves were tracked
+/// start tracking them from the current balances and cache the factory parameters.
+/// Returns a [`ContractError`] if the stored contract version can't be migrated.
+/// ## Params
+/// * **deps** is an object of type [`DepsMut`].
+///
+/// * **env** is an object of type [`Env`].
 ///
 /// * **_msg** is an object of type [`MigrateMsg`].
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn migrate(_deps: DepsMut, _env: Env, _msg: MigrateMsg) -> StdResult<Response> {
-    Ok(Response::default())
+pub fn migrate(deps: DepsMut, env: Env, _msg: MigrateMsg) -> Result<Response, ContractError> {
+    let contract_version = get_contract_version(deps.storage)?;
+
+    match contract_version.contract.as_ref() {
+        "astroport-pair" => match contract_version.version.as_ref() {
+            "1.0.0" => {
+                let config: Config = CONFIG.load(deps.storage)?;
+                let pools = config
+                    .pair_info
+                    .query_pools(&deps.querier, env.contract.address.clone())?;
+                RESERVES.save(deps.storage, &[pools[0].amount, pools[1].amount])?;
+                FACTORY_PARAMS.save(
+                    deps.storage,
+                    &query_factory_params(
+                        &deps.querier,
+                        config.factory_addr,
+                        env.contract.address,
+                        config.pair_info.pair_type,
+                    )?,
+                )?;
+            }
+            _ => return Err(ContractError::MigrationError {}),
+        },
+        _ => return Err(ContractError::MigrationError {}),
+    }
+
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+
+    Ok(Response::new()
+        .add_attribute("previous_contract_name", &contract_version.contract)
+        .add_attribute("previous_contract_version", &contract_version.version)
+        .add_attribute("new_contract_name", CONTRACT_NAME)
+        .add_attribute("new_contract_version", CONTRACT_VERSION))
+}
+
+/// ## Description
+/// Returns the Maker fees accrued in the pair using a [`ProtocolFeesResponse`] object.
+/// ## Params
+/// * **deps** is an object of type [`Deps`].
+pub fn query_protocol_fees(deps: Deps) -> StdResult<ProtocolFeesResponse> {
+    let config: Config = CONFIG.load(deps.storage)?;
+    let protocol_fees = load_protocol_fees(deps.storage)?;
+
+    Ok(ProtocolFeesResponse {
+        accrual_enabled: ACCRUE_PROTOCOL_FEES
+            .may_load(deps.storage)?
+            .unwrap_or_default(),
+        assets: [
+            Asset {
+                info: config.pair_info.asset_infos[0].clone(),
+                amount: protocol_fees[0],
+            },
+            Asset {
+                info: config.pair_info.asset_infos[1].clone(),
+                amount: protocol_fees[1],
+            },
+        ],
+    })
+}
+
+/// ## Description
+/// Returns the Maker fees accrued in the pair in the order of the pair asset infos.
+/// ## Params
+/// * **storage** is an object of type [`Storage`].
+pub fn load_protocol_fees(storage: &dyn Storage) -> StdResult<[Uint128; 2]> {
+    Ok(PROTOCOL_FEES.may_load(storage)?.unwrap_or_default())
+}
+
+/// ## Description
+/// Returns the balances of the pool assets held by the pair without the accrued Maker fees.
+/// ## Params
+/// * **deps** is an object of type [`Deps`].
+///
+/// * **config** is an object of type [`Config`].
+///
+/// * **contract_addr** is an object of type [`Addr`]. This is the address of the pair.
+pub fn query_pool_balances(
+    deps: Deps,
+    config: &Config,
+    contract_addr: Addr,
+) -> StdResult<[Asset; 2]> {
+    let mut pools = config.pair_info.query_pools(&deps.querier, contract_addr)?;
+    let protocol_fees = load_protocol_fees(deps.storage)?;
+    for (pool, protocol_fee) in pools.iter_mut().zip(protocol_fees.iter()) {
+        pool.amount = pool.amount.checked_sub(*protocol_fee)?;
+    }
+
+    Ok(pools)
+}
+
+/// ## Description
+/// Returns the tracked reserves of the pool assets in the order of the pair asset infos.
+/// ## Params
+/// * **storage** is an object of type [`Storage`].
+///
+/// * **config** is an object of type [`Config`].
+pub fn query_reserves(storage: &dyn Storage, config: &Config) -> StdResult<[Asset; 2]> {
+    let reserves = RESERVES.load(storage)?;
+
+    Ok([
+        Asset {
+            info: config.pair_info.asset_infos[0].clone(),
+            amount: reserves[0],
+        },
+        Asset {
+            info: config.pair_info.asset_infos[1].clone(),
+            amount: reserves[1],
+        },
+    ])
+}
+
+/// ## Description
+/// Updates the tracked pool reserves after a swap: the offer amount is added to the offer pool and
+/// the amount that left the pool is subtracted from the ask pool.
+/// ## Params
+/// * **storage** is an object of type [`Storage`].
+///
+/// * **pools** are the tracked reserves before the swap.
+///
+/// * **offer_info** is an object of type [`AssetInfo`]. This is the offer asset of the swap.
+///
+/// * **offer_amount** is an object of type [`Uint128`]. This is the amount of offer assets added to the pool.
+///
+/// * **ask_amount** is an object of type [`Uint128`]. This is the amount of ask assets that left the pool,
+/// including the Maker fee.
+fn save_reserves_after_swap(
+    storage: &mut dyn Storage,
+    pools: &[Asset; 2],
+    offer_info: &AssetInfo,
+    offer_amount: Uint128,
+    ask_amount: Uint128,
+) -> StdResult<()> {
+    let mut reserves = [pools[0].amount, pools[1].amount];
+    for (reserve, pool) in reserves.iter_mut().zip(pools.iter()) {
+        if pool.info.equal(offer_info) {
+            *reserve = reserve.checked_add(offer_amount)?;
+        } else {
+            *reserve = reserve.checked_sub(ask_amount)?;
+        }
+    }
+
+    RESERVES.save(storage, &reserves)
 }
 
 /// ## Description
-/// Returns the total amount of assets in the pool as well as the total amount of LP tokens currently minted.
+/// Returns the tracked reserves of the pool assets as well as the total amount of LP tokens currently minted.
 /// ## Params
 /// * **deps** is an object of type [`Deps`].
 ///
 /// * **config** is an object of type [`Config`].
 pub fn pool_info(deps: Deps, config: Config) -> StdResult<([Asset; 2], Uint128)> {
-    let contract_addr = config.pair_info.contract_addr.clone();
-    let pools: [Asset; 2] = config.pair_info.query_pools(&deps.querier, contract_addr)?;
+    let pools = query_reserves(deps.storage, &config)?;
     let total_share: Uint128 = query_supply(&deps.querier, config.pair_info.liquidity_token)?;
 
     Ok((pools, total_share))
@@ -1,3 +1,5 @@
+use astroport::observation::MAX_OBSERVATIONS_SIZE;
+use astroport::pair::MINIMUM_LIQUIDITY;
 use cosmwasm_std::{OverflowError, StdError};
 use thiserror::Error;
 
@@ -14,6 +16,9 @@ pub enum ContractError {
     #[error("Operation non supported")]
     NonSupported {},
 
+    #[error("Contract can't be migrated!")]
+    MigrationError {},
+
     #[error("Event of zero transfer")]
     InvalidZeroAmount {},
 
@@ -37,6 +42,45 @@ pub enum ContractError {
 
     #[error("Generator address is not set in factory. Cannot auto-stake")]
     AutoStakeError {},
+
+    #[error("Single-sided liquidity cannot be provided to an empty pool")]
+    SingleSidedEmptyPool {},
+
+    #[error("Minted LP amount is less than min_lp_to_receive")]
+    MinLpToReceiveAssertion {},
+
+    #[error("Offer amount exceeds max_offer")]
+    MaxOfferAssertion {},
+
+    #[error("Flash loan amount exceeds the available liquidity")]
+    FlashLoanAmountExceedsPool {},
+
+    #[error("The pair is locked until the flash loan is repaid")]
+    FlashLoanInProgress {},
+
+    #[error("Flash loan is not repaid")]
+    FlashLoanNotRepaid {},
+
+    #[error("Transaction deadline has expired")]
+    DeadlineExpired {},
+
+    #[error("Swaps are paused in the pair")]
+    SwapsPaused {},
+
+    #[error("Deposits are paused in the pair")]
+    DepositsPaused {},
+
+    #[error("Return amount is less than min_return")]
+    MinReturnAssertion {},
+
+    #[error("Withdrawn amount is less than min_assets_out")]
+    MinAssetsOutAssertion {},
+
+    #[error("Initial liquidity must be more than {}", MINIMUM_LIQUIDITY)]
+    MinimumLiquidityAmountError {},
+
+    #[error("Observations size must be between 1 and {}", MAX_OBSERVATIONS_SIZE)]
+    InvalidObservationsSize {},
 }
 
 impl From<OverflowError> for ContractError {
@@ -44,3 +88,12 @@ impl From<OverflowError> for ContractError {
         StdError::from(o).into()
     }
 }
+
+impl From<ContractError> for StdError {
+    fn from(err: ContractError) -> Self {
+        match err {
+            ContractError::Std(err) => err,
+            err => StdError::generic_err(err.to_string()),
+        }
+    }
+}
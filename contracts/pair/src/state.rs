@@ -1,5 +1,6 @@
-use astroport::asset::PairInfo;
-use cosmwasm_std::{Addr, Uint128};
+use astroport::asset::{Asset, PairInfo};
+use astroport::querier::FactoryParams;
+use cosmwasm_std::{Addr, Uint128, Uint256};
 use cw_storage_plus::Item;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
@@ -23,3 +24,54 @@ pub struct Config {
 /// ## Description
 /// Stores the config struct at the given key
 pub const CONFIG: Item<Config> = Item::new("config");
+
+/// ## Description
+/// This structure stores the state of a flash loan until it's repaid.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct FlashLoanState {
+    /// The pool balances before the flash loan
+    pub pools: [Asset; 2],
+    /// The amounts held by the pair on top of the reserves before the flash loan (assets sent to the pair
+    /// without a sync). They don't count towards the repayment
+    pub unsynced_amounts: [Uint128; 2],
+    /// The borrowed asset
+    pub asset: Asset,
+    /// The total flash loan fee in the borrowed asset
+    pub commission_amount: Uint128,
+    /// The part of the fee that goes to the Maker contract
+    pub maker_fee_amount: Uint128,
+    /// The address that receives the Maker fee
+    pub fee_address: Option<Addr>,
+}
+
+/// ## Description
+/// Stores the flash loan that is in progress
+pub const FLASH_LOAN: Item<FlashLoanState> = Item::new("flash_loan");
+
+/// ## Description
+/// Stores the tracked pool reserves in the order of the pair asset infos. Assets sent to the pair
+/// directly are not counted until they are synced
+pub const RESERVES: Item<[Uint128; 2]> = Item::new("reserves");
+
+/// ## Description
+/// Stores the fee and Generator information cached from the factory
+pub const FACTORY_PARAMS: Item<FactoryParams> = Item::new("factory_params");
+
+/// ## Description
+/// Stores whether the Maker fees are accrued in the pair instead of being sent on every swap
+pub const ACCRUE_PROTOCOL_FEES: Item<bool> = Item::new("accrue_protocol_fees");
+
+/// ## Description
+/// Stores the Maker fees accrued in the pair in the order of the pair asset infos. They are not part of
+/// the pool reserves
+pub const PROTOCOL_FEES: Item<[Uint128; 2]> = Item::new("protocol_fees");
+
+/// ## Description
+/// Stores sqrt(k) of the pool reserves after the last liquidity event. It is used to mint the Maker fee
+/// in LP tokens and is only tracked while the Maker fee is collected in LP tokens
+pub const ROOT_K_LAST: Item<Uint128> = Item::new("root_k_last");
+
+/// ## Description
+/// Stores the 256-bit cumulative prices of the pool assets (in the order of the pair asset infos)
+/// with 18 decimals
+pub const PRICE_CUMULATIVE_LAST_U256: Item<[Uint256; 2]> = Item::new("price_cumulative_last_u256");
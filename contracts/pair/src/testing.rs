@@ -1,26 +1,33 @@
 use crate::contract::reply;
 use crate::contract::{
-    accumulate_prices, assert_max_spread, compute_swap, execute, instantiate, query_pair_info,
-    query_pool, query_reverse_simulation, query_share, query_simulation,
+    accumulate_prices, accumulate_prices_u256, assert_max_spread, compute_depth, compute_price,
+    compute_single_sided_swap_amount, compute_swap, execute, instantiate, migrate, query,
+    query_pair_info, query_pool, query_protocol_fees, query_reverse_simulation, query_share,
+    query_simulation, update_cumulative_prices,
 };
 use crate::error::ContractError;
 use crate::mock_querier::mock_dependencies;
 use crate::response::MsgInstantiateContractResponse;
-use crate::state::Config;
+use crate::state::{Config, CONFIG, FACTORY_PARAMS, RESERVES, ROOT_K_LAST};
 use astroport::asset::{Asset, AssetInfo, PairInfo};
-use astroport::factory::PairType;
+use astroport::factory::{PairStatus, PairType};
+use astroport::querier::{FactoryParams, FeeInfo};
 
 use astroport::pair::{
-    Cw20HookMsg, ExecuteMsg, InstantiateMsg, PoolResponse, ReverseSimulationResponse,
-    SimulationResponse, TWAP_PRECISION,
+    Cw20HookMsg, DepthResponse, ExecuteMsg, FlashLoanReceiverMsg, InstantiateMsg, MigrateMsg,
+    ObserveResponse, PoolResponse, PriceImpactResponse, QueryMsg, ReverseSimulationResponse,
+    SimulateProvideResponse, SimulationResponse, SpotPriceResponse, XykPoolUpdateParams,
+    MINIMUM_LIQUIDITY, TWAP_PRECISION, TWAP_PRECISION_U256,
 };
 use astroport::token::InstantiateMsg as TokenInstantiateMsg;
+use astroport::{Uint256WrappingOps, U256};
 use cosmwasm_std::testing::{mock_env, mock_info, MOCK_CONTRACT_ADDR};
 use cosmwasm_std::{
-    attr, to_binary, Addr, BankMsg, BlockInfo, Coin, ContractResult, CosmosMsg, Decimal, DepsMut,
-    Env, Reply, ReplyOn, Response, StdError, SubMsg, SubMsgExecutionResponse, Timestamp, Uint128,
-    WasmMsg,
+    attr, from_binary, to_binary, Addr, BankMsg, BlockInfo, Coin, ContractResult, CosmosMsg,
+    Decimal, Deps, DepsMut, Env, Reply, ReplyOn, Response, StdError, SubMsg,
+    SubMsgExecutionResponse, Timestamp, Uint128, Uint256 as StdUint256, WasmMsg,
 };
+use cw2::{get_contract_version, set_contract_version};
 use cw20::{Cw20ExecuteMsg, Cw20ReceiveMsg, MinterResponse};
 use proptest::prelude::*;
 use protobuf::Message;
@@ -46,6 +53,10 @@ fn store_liquidity_token(deps: DepsMut, msg_id: u64, contract_addr: String) {
     let _res = reply(deps, mock_env(), reply_msg.clone()).unwrap();
 }
 
+fn store_reserves(deps: DepsMut, reserves: [Uint128; 2]) {
+    RESERVES.save(deps.storage, &reserves).unwrap();
+}
+
 #[test]
 fn proper_initialization() {
     let mut deps = mock_dependencies(&[]);
@@ -179,6 +190,8 @@ fn provide_liquidity() {
         slippage_tolerance: None,
         auto_stake: None,
         receiver: None,
+        min_lp_to_receive: None,
+        deadline: None,
     };
 
     let env = mock_env();
@@ -191,7 +204,8 @@ fn provide_liquidity() {
     );
     let res = execute(deps.as_mut(), env.clone().clone(), info, msg).unwrap();
     let transfer_from_msg = res.messages.get(0).expect("no message");
-    let mint_msg = res.messages.get(1).expect("no message");
+    let mint_min_liquidity_msg = res.messages.get(1).expect("no message");
+    let mint_msg = res.messages.get(2).expect("no message");
     assert_eq!(
         transfer_from_msg,
         &SubMsg {
@@ -211,6 +225,24 @@ fn provide_liquidity() {
             reply_on: ReplyOn::Never
         }
     );
+    assert_eq!(
+        mint_min_liquidity_msg,
+        &SubMsg {
+            msg: WasmMsg::Execute {
+                contract_addr: String::from("liquidity0000"),
+                msg: to_binary(&Cw20ExecuteMsg::Mint {
+                    recipient: String::from(MOCK_CONTRACT_ADDR),
+                    amount: MINIMUM_LIQUIDITY,
+                })
+                .unwrap(),
+                funds: vec![],
+            }
+            .into(),
+            id: 0,
+            gas_limit: None,
+            reply_on: ReplyOn::Never,
+        }
+    );
     assert_eq!(
         mint_msg,
         &SubMsg {
@@ -218,7 +250,7 @@ fn provide_liquidity() {
                 contract_addr: String::from("liquidity0000"),
                 msg: to_binary(&Cw20ExecuteMsg::Mint {
                     recipient: String::from("addr0000"),
-                    amount: Uint128::from(100_000000000000000000u128),
+                    amount: Uint128::from(100_000000000000000000u128) - MINIMUM_LIQUIDITY,
                 })
                 .unwrap(),
                 funds: vec![],
@@ -256,6 +288,13 @@ fn provide_liquidity() {
             )],
         ),
     ]);
+    store_reserves(
+        deps.as_mut(),
+        [
+            Uint128::new(200_000000000000000000),
+            Uint128::new(200_000000000000000000),
+        ],
+    );
 
     let msg = ExecuteMsg::ProvideLiquidity {
         assets: [
@@ -275,6 +314,8 @@ fn provide_liquidity() {
         slippage_tolerance: Some(Decimal::percent(50)),
         auto_stake: None,
         receiver: None,
+        min_lp_to_receive: None,
+        deadline: None,
     };
 
     let env = mock_env_with_block_time(env.block.time.seconds() + 1000);
@@ -347,6 +388,8 @@ fn provide_liquidity() {
         slippage_tolerance: None,
         auto_stake: None,
         receiver: None,
+        min_lp_to_receive: None,
+        deadline: None,
     };
 
     let env = mock_env();
@@ -391,6 +434,13 @@ fn provide_liquidity() {
             )],
         ),
     ]);
+    store_reserves(
+        deps.as_mut(),
+        [
+            Uint128::new(100_000000000000000000),
+            Uint128::new(100_000000000000000000),
+        ],
+    );
 
     // Failed because the price is under slippage_tolerance
     let msg = ExecuteMsg::ProvideLiquidity {
@@ -411,6 +461,8 @@ fn provide_liquidity() {
         slippage_tolerance: Some(Decimal::percent(1)),
         auto_stake: None,
         receiver: None,
+        min_lp_to_receive: None,
+        deadline: None,
     };
 
     let env = mock_env_with_block_time(env.block.time.seconds() + 1000);
@@ -452,6 +504,8 @@ fn provide_liquidity() {
         slippage_tolerance: Some(Decimal::percent(1)),
         auto_stake: None,
         receiver: None,
+        min_lp_to_receive: None,
+        deadline: None,
     };
 
     let env = mock_env_with_block_time(env.block.time.seconds() + 1000);
@@ -493,6 +547,8 @@ fn provide_liquidity() {
         slippage_tolerance: Some(Decimal::percent(1)),
         auto_stake: None,
         receiver: None,
+        min_lp_to_receive: None,
+        deadline: None,
     };
 
     let env = mock_env_with_block_time(env.block.time.seconds() + 1000);
@@ -513,6 +569,13 @@ fn provide_liquidity() {
             amount: Uint128::new(100_000000000000000000 + 99_000000000000000000 /* user deposit must be pre-applied */),
         }],
     )]);
+    store_reserves(
+        deps.as_mut(),
+        [
+            Uint128::new(100_000000000000000000),
+            Uint128::new(100_000000000000000000),
+        ],
+    );
 
     // Successfully provides liquidity
     let msg = ExecuteMsg::ProvideLiquidity {
@@ -533,6 +596,8 @@ fn provide_liquidity() {
         slippage_tolerance: Some(Decimal::percent(1)),
         auto_stake: None,
         receiver: None,
+        min_lp_to_receive: None,
+        deadline: None,
     };
 
     let env = mock_env_with_block_time(env.block.time.seconds() + 1000);
@@ -590,11 +655,19 @@ fn withdraw_liquidity() {
 
     // Store liquidity token
     store_liquidity_token(deps.as_mut(), 1, "liquidity0000".to_string());
+    store_reserves(
+        deps.as_mut(),
+        [Uint128::new(100u128), Uint128::new(100u128)],
+    );
 
     // Withdraw liquidity
     let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
         sender: String::from("addr0000"),
-        msg: to_binary(&Cw20HookMsg::WithdrawLiquidity {}).unwrap(),
+        msg: to_binary(&Cw20HookMsg::WithdrawLiquidity {
+            min_assets_out: None,
+            deadline: None,
+        })
+        .unwrap(),
         amount: Uint128::new(100u128),
     });
 
@@ -667,6 +740,188 @@ fn withdraw_liquidity() {
     );
 }
 
+#[test]
+fn provide_single_sided() {
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::new(
+            1_000_000_000u128 + 100_000_000u128, /* user deposit must be pre-applied */
+        ),
+    }]);
+
+    deps.querier.with_token_balances(&[
+        (
+            &String::from("liquidity0000"),
+            &[(
+                &String::from(MOCK_CONTRACT_ADDR),
+                &Uint128::new(1_000_000_000u128),
+            )],
+        ),
+        (
+            &String::from("asset0000"),
+            &[(
+                &String::from(MOCK_CONTRACT_ADDR),
+                &Uint128::new(1_000_000_000u128),
+            )],
+        ),
+    ]);
+
+    let msg = InstantiateMsg {
+        asset_infos: [
+            AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            AssetInfo::Token {
+                contract_addr: Addr::unchecked("asset0000"),
+            },
+        ],
+        token_code_id: 10u64,
+        factory_addr: String::from("factory"),
+        init_params: None,
+    };
+
+    let env = mock_env();
+    let info = mock_info("addr0000", &[]);
+    // We can just call .unwrap() to assert this was a success
+    let _res = instantiate(deps.as_mut(), env, info, msg).unwrap();
+
+    // Store liquidity token
+    store_liquidity_token(deps.as_mut(), 1, "liquidity0000".to_string());
+    store_reserves(
+        deps.as_mut(),
+        [
+            Uint128::new(1_000_000_000u128),
+            Uint128::new(1_000_000_000u128),
+        ],
+    );
+
+    let offer_asset = Asset {
+        info: AssetInfo::NativeToken {
+            denom: "uusd".to_string(),
+        },
+        amount: Uint128::new(100_000_000u128),
+    };
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::new(100_000_000u128),
+        }],
+    );
+
+    // The deadline is enforced
+    let msg = ExecuteMsg::ProvideSingleSided {
+        asset: offer_asset.clone(),
+        min_lp_to_receive: None,
+        auto_stake: None,
+        receiver: None,
+        deadline: Some(mock_env().block.time.seconds() - 1),
+    };
+    let res = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap_err();
+    assert_eq!(res, ContractError::DeadlineExpired {});
+
+    // The slippage guard is enforced
+    let msg = ExecuteMsg::ProvideSingleSided {
+        asset: offer_asset.clone(),
+        min_lp_to_receive: Some(Uint128::new(48_731_949u128)),
+        auto_stake: None,
+        receiver: None,
+        deadline: None,
+    };
+    let res = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap_err();
+    assert_eq!(res, ContractError::MinLpToReceiveAssertion {});
+
+    let msg = ExecuteMsg::ProvideSingleSided {
+        asset: offer_asset,
+        min_lp_to_receive: Some(Uint128::new(48_731_948u128)),
+        auto_stake: None,
+        receiver: None,
+        deadline: None,
+    };
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // 48885753 uusd are swapped for 46467498 asset0000 (after the 139821 commission),
+    // the rest is deposited together with the swap output
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::new(WasmMsg::Execute {
+                contract_addr: String::from("asset0000"),
+                msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                    recipient: String::from("fee_address"),
+                    amount: Uint128::new(23210u128),
+                })
+                .unwrap(),
+                funds: vec![],
+            }),
+            SubMsg::new(WasmMsg::Execute {
+                contract_addr: String::from("liquidity0000"),
+                msg: to_binary(&Cw20ExecuteMsg::Mint {
+                    recipient: String::from("addr0000"),
+                    amount: Uint128::new(48_731_948u128),
+                })
+                .unwrap(),
+                funds: vec![],
+            }),
+        ]
+    );
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "provide_single_sided"),
+            attr("sender", "addr0000"),
+            attr("receiver", "addr0000"),
+            attr("asset", "100000000uusd"),
+            attr("swap_amount", "48885753"),
+            attr("return_amount", "46467498"),
+            attr("commission_amount", "139821"),
+            attr("maker_fee_amount", "23210"),
+            attr("share", "48731948"),
+        ]
+    );
+
+    // Single-sided liquidity can't be provided to an empty pool
+    deps.querier.with_token_balances(&[
+        (
+            &String::from("liquidity0000"),
+            &[(&String::from(MOCK_CONTRACT_ADDR), &Uint128::zero())],
+        ),
+        (
+            &String::from("asset0000"),
+            &[(&String::from(MOCK_CONTRACT_ADDR), &Uint128::zero())],
+        ),
+    ]);
+    store_reserves(deps.as_mut(), [Uint128::zero(), Uint128::zero()]);
+    let msg = ExecuteMsg::ProvideSingleSided {
+        asset: Asset {
+            info: AssetInfo::Token {
+                contract_addr: Addr::unchecked("asset0000"),
+            },
+            amount: Uint128::new(100_000_000u128),
+        },
+        min_lp_to_receive: None,
+        auto_stake: None,
+        receiver: None,
+        deadline: None,
+    };
+    let res = execute(deps.as_mut(), mock_env(), mock_info("addr0000", &[]), msg).unwrap_err();
+    assert_eq!(res, ContractError::SingleSidedEmptyPool {});
+}
+
+#[test]
+fn compute_single_sided_swap_amount_without_fee() {
+    // Without fees the swap amount is sqrt(pool * (pool + amount)) - pool
+    assert_eq!(
+        compute_single_sided_swap_amount(
+            Uint128::new(1_000_000_000u128),
+            Uint128::new(3_000_000_000u128),
+            Decimal::zero()
+        )
+        .unwrap(),
+        Uint128::new(1_000_000_000u128)
+    );
+}
+
 #[test]
 fn try_native_to_token() {
     let total_share = Uint128::new(30000000000u128);
@@ -716,6 +971,7 @@ fn try_native_to_token() {
 
     // Store liquidity token
     store_liquidity_token(deps.as_mut(), 1, "liquidity0000".to_string());
+    store_reserves(deps.as_mut(), [collateral_pool_amount, asset_pool_amount]);
 
     // Normal swap
     let msg = ExecuteMsg::Swap {
@@ -728,6 +984,8 @@ fn try_native_to_token() {
         belief_price: None,
         max_spread: Some(Decimal::percent(50)),
         to: None,
+        min_return: None,
+        deadline: None,
     };
     let env = mock_env_with_block_time(1000);
     let info = mock_info(
@@ -764,6 +1022,7 @@ fn try_native_to_token() {
             amount: collateral_pool_amount, /* user deposit must be pre-applied */
         }],
     )]);
+    store_reserves(deps.as_mut(), [collateral_pool_amount, asset_pool_amount]);
 
     let simulation_res: SimulationResponse = query_simulation(
         deps.as_ref(),
@@ -897,6 +1156,7 @@ fn try_token_to_native() {
 
     // Store liquidity token
     store_liquidity_token(deps.as_mut(), 1, "liquidity0000".to_string());
+    store_reserves(deps.as_mut(), [collateral_pool_amount, asset_pool_amount]);
 
     // Unauthorized access; can not execute swap directy for token swap
     let msg = ExecuteMsg::Swap {
@@ -909,6 +1169,8 @@ fn try_token_to_native() {
         belief_price: None,
         max_spread: None,
         to: None,
+        min_return: None,
+        deadline: None,
     };
     let env = mock_env_with_block_time(1000);
     let info = mock_info("addr0000", &[]);
@@ -923,6 +1185,8 @@ fn try_token_to_native() {
             belief_price: None,
             max_spread: Some(Decimal::percent(50)),
             to: None,
+            min_return: None,
+            deadline: None,
         })
         .unwrap(),
     });
@@ -964,6 +1228,7 @@ fn try_token_to_native() {
             &[(&String::from(MOCK_CONTRACT_ADDR), &(asset_pool_amount))],
         ),
     ]);
+    store_reserves(deps.as_mut(), [collateral_pool_amount, asset_pool_amount]);
 
     let simulation_res: SimulationResponse = query_simulation(
         deps.as_ref(),
@@ -1054,6 +1319,8 @@ fn try_token_to_native() {
             belief_price: None,
             max_spread: None,
             to: None,
+            min_return: None,
+            deadline: None,
         })
         .unwrap(),
     });
@@ -1064,91 +1331,24 @@ fn try_token_to_native() {
 }
 
 #[test]
-fn test_max_spread() {
-    assert_max_spread(
-        Some(Decimal::from_ratio(1200u128, 1u128)),
-        Some(Decimal::percent(1)),
-        Uint128::from(1200000000u128),
-        Uint128::from(989999u128),
-        Uint128::zero(),
-    )
-    .unwrap_err();
-
-    assert_max_spread(
-        Some(Decimal::from_ratio(1200u128, 1u128)),
-        Some(Decimal::percent(1)),
-        Uint128::from(1200000000u128),
-        Uint128::from(990000u128),
-        Uint128::zero(),
-    )
-    .unwrap();
-
-    assert_max_spread(
-        None,
-        Some(Decimal::percent(1)),
-        Uint128::zero(),
-        Uint128::from(989999u128),
-        Uint128::from(10001u128),
-    )
-    .unwrap_err();
-
-    assert_max_spread(
-        None,
-        Some(Decimal::percent(1)),
-        Uint128::zero(),
-        Uint128::from(990000u128),
-        Uint128::from(10000u128),
-    )
-    .unwrap();
-}
-
-#[test]
-fn test_deduct() {
-    let mut deps = mock_dependencies(&[]);
-
-    let tax_rate = Decimal::percent(2);
-    let tax_cap = Uint128::from(1_000_000u128);
-    deps.querier.with_tax(
-        Decimal::percent(2),
-        &[(&"uusd".to_string(), &Uint128::from(1000000u128))],
-    );
-
-    let amount = Uint128::new(1000_000_000u128);
-    let expected_after_amount = std::cmp::max(
-        amount.checked_sub(amount * tax_rate).unwrap(),
-        amount.checked_sub(tax_cap).unwrap(),
-    );
-
-    let after_amount = (Asset {
-        info: AssetInfo::NativeToken {
-            denom: "uusd".to_string(),
-        },
-        amount,
-    })
-    .deduct_tax(&deps.as_ref().querier)
-    .unwrap();
-
-    assert_eq!(expected_after_amount, after_amount.amount);
-}
+fn flash_loan() {
+    let total_share = Uint128::new(20000000000u128);
+    let asset_pool_amount = Uint128::new(30000000000u128);
+    let collateral_pool_amount = Uint128::new(20000000000u128);
+    let loan_amount = Uint128::new(1000000000u128);
 
-#[test]
-fn test_query_pool() {
-    let total_share_amount = Uint128::from(111u128);
-    let asset_0_amount = Uint128::from(222u128);
-    let asset_1_amount = Uint128::from(333u128);
     let mut deps = mock_dependencies(&[Coin {
         denom: "uusd".to_string(),
-        amount: asset_0_amount,
+        amount: collateral_pool_amount,
     }]);
-
     deps.querier.with_token_balances(&[
         (
-            &String::from("asset0000"),
-            &[(&String::from(MOCK_CONTRACT_ADDR), &asset_1_amount)],
+            &String::from("liquidity0000"),
+            &[(&String::from(MOCK_CONTRACT_ADDR), &total_share)],
         ),
         (
-            &String::from("liquidity0000"),
-            &[(&String::from(MOCK_CONTRACT_ADDR), &total_share_amount)],
+            &String::from("asset0000"),
+            &[(&String::from(MOCK_CONTRACT_ADDR), &asset_pool_amount)],
         ),
     ]);
 
@@ -1168,16 +1368,1787 @@ fn test_query_pool() {
 
     let env = mock_env();
     let info = mock_info("addr0000", &[]);
-    // We can just call .unwrap() to assert this was a success
-    let _res = instantiate(deps.as_mut(), env, info, msg).unwrap();
-
-    // Store liquidity token
+    instantiate(deps.as_mut(), env, info, msg).unwrap();
     store_liquidity_token(deps.as_mut(), 1, "liquidity0000".to_string());
+    store_reserves(deps.as_mut(), [collateral_pool_amount, asset_pool_amount]);
 
-    let res: PoolResponse = query_pool(deps.as_ref()).unwrap();
+    let loan_asset = |amount: Uint128| Asset {
+        info: AssetInfo::Token {
+            contract_addr: Addr::unchecked("asset0000"),
+        },
+        amount,
+    };
+    let flash_loan_msg = |amount: Uint128| ExecuteMsg::FlashLoan {
+        asset: loan_asset(amount),
+        receiver: None,
+        msg: to_binary(&"arbitrage").unwrap(),
+    };
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        flash_loan_msg(Uint128::zero()),
+    )
+    .unwrap_err();
+    assert_eq!(res, ContractError::InvalidZeroAmount {});
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        flash_loan_msg(asset_pool_amount),
+    )
+    .unwrap_err();
+    assert_eq!(res, ContractError::FlashLoanAmountExceedsPool {});
 
+    // The fee is 0.3% of the loan and the Maker gets 16.6% of the fee
+    let expected_commission_amount = Uint128::new(3000000u128);
+    let expected_maker_fee_amount = Uint128::new(498000u128);
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        flash_loan_msg(loan_amount),
+    )
+    .unwrap();
     assert_eq!(
-        res.assets,
+        res.messages,
+        vec![
+            SubMsg::new(WasmMsg::Execute {
+                contract_addr: String::from("asset0000"),
+                msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                    recipient: String::from("addr0000"),
+                    amount: loan_amount,
+                })
+                .unwrap(),
+                funds: vec![],
+            }),
+            SubMsg::new(WasmMsg::Execute {
+                contract_addr: String::from("addr0000"),
+                msg: to_binary(&FlashLoanReceiverMsg::FlashLoanCallback {
+                    asset: loan_asset(loan_amount),
+                    fee: expected_commission_amount,
+                    msg: to_binary(&"arbitrage").unwrap(),
+                })
+                .unwrap(),
+                funds: vec![],
+            }),
+            SubMsg::new(WasmMsg::Execute {
+                contract_addr: String::from(MOCK_CONTRACT_ADDR),
+                msg: to_binary(&ExecuteMsg::AssertFlashLoanRepaid {}).unwrap(),
+                funds: vec![],
+            }),
+        ]
+    );
+
+    // The pair is locked until the loan is repaid
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        flash_loan_msg(loan_amount),
+    )
+    .unwrap_err();
+    assert_eq!(res, ContractError::FlashLoanInProgress {});
+
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: String::from("addr0000"),
+        amount: loan_amount,
+        msg: to_binary(&Cw20HookMsg::WithdrawLiquidity {
+            min_assets_out: None,
+            deadline: None,
+        })
+        .unwrap(),
+    });
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("liquidity0000", &[]),
+        msg,
+    )
+    .unwrap_err();
+    assert_eq!(res, ContractError::FlashLoanInProgress {});
+
+    // Only the pair can check the repayment
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        ExecuteMsg::AssertFlashLoanRepaid {},
+    )
+    .unwrap_err();
+    assert_eq!(res, ContractError::Unauthorized {});
+
+    // The loan is repaid without the fee
+    deps.querier.with_token_balances(&[(
+        &String::from("asset0000"),
+        &[(
+            &String::from(MOCK_CONTRACT_ADDR),
+            &(asset_pool_amount + expected_commission_amount - Uint128::new(1)),
+        )],
+    )]);
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(MOCK_CONTRACT_ADDR, &[]),
+        ExecuteMsg::AssertFlashLoanRepaid {},
+    )
+    .unwrap_err();
+    assert_eq!(res, ContractError::FlashLoanNotRepaid {});
+
+    // The loan is repaid with the fee and the Maker fee is sent out
+    deps.querier.with_token_balances(&[(
+        &String::from("asset0000"),
+        &[(&String::from(MOCK_CONTRACT_ADDR), &asset_pool_amount)],
+    )]);
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        flash_loan_msg(loan_amount),
+    )
+    .unwrap();
+    deps.querier.with_token_balances(&[(
+        &String::from("asset0000"),
+        &[(
+            &String::from(MOCK_CONTRACT_ADDR),
+            &(asset_pool_amount + expected_commission_amount),
+        )],
+    )]);
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(MOCK_CONTRACT_ADDR, &[]),
+        ExecuteMsg::AssertFlashLoanRepaid {},
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(WasmMsg::Execute {
+            contract_addr: String::from("asset0000"),
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: String::from("fee_address"),
+                amount: expected_maker_fee_amount,
+            })
+            .unwrap(),
+            funds: vec![],
+        })]
+    );
+
+    // The loan can be repaid with the other asset as long as the invariant plus the LP fee is restored:
+    // 20691736017 = ceil(20000000000 * (30000000000 + 2502000) / (30000000000 - 1000000000 - 498000))
+    deps.querier.with_token_balances(&[(
+        &String::from("asset0000"),
+        &[(&String::from(MOCK_CONTRACT_ADDR), &asset_pool_amount)],
+    )]);
+    store_reserves(deps.as_mut(), [collateral_pool_amount, asset_pool_amount]);
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        flash_loan_msg(loan_amount),
+    )
+    .unwrap();
+    deps.querier.with_token_balances(&[(
+        &String::from("asset0000"),
+        &[(
+            &String::from(MOCK_CONTRACT_ADDR),
+            &(asset_pool_amount - loan_amount),
+        )],
+    )]);
+    deps.querier.with_balance(&[(
+        &String::from(MOCK_CONTRACT_ADDR),
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::new(20691736017u128),
+        }],
+    )]);
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(MOCK_CONTRACT_ADDR, &[]),
+        ExecuteMsg::AssertFlashLoanRepaid {},
+    )
+    .unwrap();
+
+    // An unsynced donation that was sent to the pair before the loan doesn't count towards the repayment
+    let donation_amount = expected_commission_amount;
+    deps.querier.with_token_balances(&[(
+        &String::from("asset0000"),
+        &[(
+            &String::from(MOCK_CONTRACT_ADDR),
+            &(asset_pool_amount + donation_amount),
+        )],
+    )]);
+    deps.querier.with_balance(&[(
+        &String::from(MOCK_CONTRACT_ADDR),
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: collateral_pool_amount,
+        }],
+    )]);
+    store_reserves(deps.as_mut(), [collateral_pool_amount, asset_pool_amount]);
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        flash_loan_msg(loan_amount),
+    )
+    .unwrap();
+
+    // Only the loan itself is repaid, the donation would cover the fee
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(MOCK_CONTRACT_ADDR, &[]),
+        ExecuteMsg::AssertFlashLoanRepaid {},
+    )
+    .unwrap_err();
+    assert_eq!(res, ContractError::FlashLoanNotRepaid {});
+
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        flash_loan_msg(loan_amount),
+    )
+    .unwrap();
+    deps.querier.with_token_balances(&[(
+        &String::from("asset0000"),
+        &[(
+            &String::from(MOCK_CONTRACT_ADDR),
+            &(asset_pool_amount + donation_amount + expected_commission_amount),
+        )],
+    )]);
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(MOCK_CONTRACT_ADDR, &[]),
+        ExecuteMsg::AssertFlashLoanRepaid {},
+    )
+    .unwrap();
+
+    // Only the LP part of the fee is added to the reserves, the donation is still unsynced
+    let reserves = RESERVES.load(deps.as_ref().storage).unwrap();
+    assert_eq!(
+        reserves,
+        [
+            collateral_pool_amount,
+            asset_pool_amount + expected_commission_amount - expected_maker_fee_amount
+        ]
+    );
+}
+
+#[test]
+fn swap_exact_out() {
+    let total_share = Uint128::new(20000000000u128);
+    let asset_pool_amount = Uint128::new(30000000000u128);
+    let collateral_pool_amount = Uint128::new(20000000000u128);
+    let ask_amount = Uint128::new(1000000000u128);
+    let max_offer = Uint128::new(800000000u128);
+
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: collateral_pool_amount + max_offer,
+    }]);
+    deps.querier.with_token_balances(&[
+        (
+            &String::from("liquidity0000"),
+            &[(&String::from(MOCK_CONTRACT_ADDR), &total_share)],
+        ),
+        (
+            &String::from("asset0000"),
+            &[(&String::from(MOCK_CONTRACT_ADDR), &asset_pool_amount)],
+        ),
+    ]);
+
+    let msg = InstantiateMsg {
+        asset_infos: [
+            AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            AssetInfo::Token {
+                contract_addr: Addr::unchecked("asset0000"),
+            },
+        ],
+        token_code_id: 10u64,
+        factory_addr: String::from("factory"),
+        init_params: None,
+    };
+
+    let env = mock_env();
+    let info = mock_info("addr0000", &[]);
+    instantiate(deps.as_mut(), env, info, msg).unwrap();
+    store_liquidity_token(deps.as_mut(), 1, "liquidity0000".to_string());
+    store_reserves(deps.as_mut(), [collateral_pool_amount, asset_pool_amount]);
+
+    // Buy exactly 1000000000 asset0000 for uusd, the excess uusd is sent back
+    // 691802144 = 20000000000 * 30000000000 / (30000000000 - 1000000000 / 0.997) - 20000000000
+    let expected_offer_amount = Uint128::new(691802144u128);
+    let expected_commission_amount = Uint128::new(3009027u128);
+    let expected_maker_fee_amount = Uint128::new(499498u128);
+    let expected_spread_amount = Uint128::new(34694189u128);
+
+    let msg = ExecuteMsg::SwapExactOut {
+        ask_asset: Asset {
+            info: AssetInfo::Token {
+                contract_addr: Addr::unchecked("asset0000"),
+            },
+            amount: ask_amount,
+        },
+        max_offer,
+        to: None,
+        deadline: None,
+    };
+    let env = mock_env_with_block_time(1000);
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: max_offer,
+        }],
+    );
+    let res = execute(deps.as_mut(), env, info, msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::new(WasmMsg::Execute {
+                contract_addr: String::from("asset0000"),
+                msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                    recipient: String::from("addr0000"),
+                    amount: ask_amount,
+                })
+                .unwrap(),
+                funds: vec![],
+            }),
+            SubMsg::new(BankMsg::Send {
+                to_address: String::from("addr0000"),
+                amount: vec![Coin {
+                    denom: "uusd".to_string(),
+                    amount: max_offer - expected_offer_amount,
+                }],
+            }),
+            SubMsg::new(WasmMsg::Execute {
+                contract_addr: String::from("asset0000"),
+                msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                    recipient: String::from("fee_address"),
+                    amount: expected_maker_fee_amount,
+                })
+                .unwrap(),
+                funds: vec![],
+            }),
+        ]
+    );
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "swap"),
+            attr("sender", "addr0000"),
+            attr("receiver", "addr0000"),
+            attr("offer_asset", "uusd"),
+            attr("ask_asset", "asset0000"),
+            attr("offer_amount", expected_offer_amount.to_string()),
+            attr("return_amount", ask_amount.to_string()),
+            attr(
+                "refund_amount",
+                (max_offer - expected_offer_amount).to_string()
+            ),
+            attr("tax_amount", "0"),
+            attr("spread_amount", expected_spread_amount.to_string()),
+            attr("commission_amount", expected_commission_amount.to_string()),
+            attr("maker_fee_amount", expected_maker_fee_amount.to_string()),
+        ]
+    );
+
+    // The offer amount is the same as in the reverse simulation
+    deps.querier.with_balance(&[(
+        &String::from(MOCK_CONTRACT_ADDR),
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: collateral_pool_amount,
+        }],
+    )]);
+    store_reserves(deps.as_mut(), [collateral_pool_amount, asset_pool_amount]);
+    let reverse_simulation_res: ReverseSimulationResponse = query_reverse_simulation(
+        deps.as_ref(),
+        Asset {
+            info: AssetInfo::Token {
+                contract_addr: Addr::unchecked("asset0000"),
+            },
+            amount: ask_amount,
+        },
+    )
+    .unwrap();
+    assert_eq!(reverse_simulation_res.offer_amount, expected_offer_amount);
+
+    // Buy exactly 1000000000 uusd for asset0000
+    // 1583949313 = 30000000000 * 20000000000 / (20000000000 - 1000000000 / 0.997) - 30000000000
+    let expected_offer_amount = Uint128::new(1583949313u128);
+    let expected_spread_amount = Uint128::new(52957181u128);
+
+    // The deadline is enforced
+    let msg = ExecuteMsg::SwapExactOut {
+        ask_asset: Asset {
+            info: AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            amount: ask_amount,
+        },
+        max_offer: Uint128::new(2000000000u128),
+        to: None,
+        deadline: Some(999),
+    };
+    let res = execute(
+        deps.as_mut(),
+        mock_env_with_block_time(1000),
+        mock_info("addr0000", &[]),
+        msg,
+    )
+    .unwrap_err();
+    assert_eq!(res, ContractError::DeadlineExpired {});
+
+    // The required offer amount exceeds max_offer
+    let msg = ExecuteMsg::SwapExactOut {
+        ask_asset: Asset {
+            info: AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            amount: ask_amount,
+        },
+        max_offer: expected_offer_amount - Uint128::new(1u128),
+        to: None,
+        deadline: None,
+    };
+    let env = mock_env_with_block_time(1000);
+    let info = mock_info("addr0000", &[]);
+    let res = execute(deps.as_mut(), env, info, msg).unwrap_err();
+    assert_eq!(res, ContractError::MaxOfferAssertion {});
+
+    // Only the required amount of offer tokens is pulled from the sender
+    let msg = ExecuteMsg::SwapExactOut {
+        ask_asset: Asset {
+            info: AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            amount: ask_amount,
+        },
+        max_offer: Uint128::new(2000000000u128),
+        to: Some(String::from("addr0001")),
+        deadline: None,
+    };
+    let env = mock_env_with_block_time(1000);
+    let info = mock_info("addr0000", &[]);
+    let res = execute(deps.as_mut(), env, info, msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::new(WasmMsg::Execute {
+                contract_addr: String::from("asset0000"),
+                msg: to_binary(&Cw20ExecuteMsg::TransferFrom {
+                    owner: String::from("addr0000"),
+                    recipient: String::from(MOCK_CONTRACT_ADDR),
+                    amount: expected_offer_amount,
+                })
+                .unwrap(),
+                funds: vec![],
+            }),
+            SubMsg::new(BankMsg::Send {
+                to_address: String::from("addr0001"),
+                amount: vec![Coin {
+                    denom: "uusd".to_string(),
+                    amount: ask_amount,
+                }],
+            }),
+            SubMsg::new(BankMsg::Send {
+                to_address: String::from("fee_address"),
+                amount: vec![Coin {
+                    denom: "uusd".to_string(),
+                    amount: expected_maker_fee_amount,
+                }],
+            }),
+        ]
+    );
+
+    // The tokens sent via the hook are the max offer amount and the excess is sent back
+    let max_offer = Uint128::new(2000000000u128);
+    deps.querier.with_token_balances(&[
+        (
+            &String::from("liquidity0000"),
+            &[(&String::from(MOCK_CONTRACT_ADDR), &total_share)],
+        ),
+        (
+            &String::from("asset0000"),
+            &[(
+                &String::from(MOCK_CONTRACT_ADDR),
+                &(asset_pool_amount + max_offer),
+            )],
+        ),
+    ]);
+    store_reserves(deps.as_mut(), [collateral_pool_amount, asset_pool_amount]);
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: String::from("addr0000"),
+        amount: max_offer,
+        msg: to_binary(&Cw20HookMsg::SwapExactOut {
+            ask_asset: Asset {
+                info: AssetInfo::NativeToken {
+                    denom: "uusd".to_string(),
+                },
+                amount: ask_amount,
+            },
+            to: None,
+            deadline: None,
+        })
+        .unwrap(),
+    });
+
+    // Only the offer token contract can send the hook message
+    let env = mock_env_with_block_time(1000);
+    let info = mock_info("liquidity0000", &[]);
+    let res = execute(deps.as_mut(), env, info, msg.clone()).unwrap_err();
+    assert_eq!(res, ContractError::Unauthorized {});
+
+    let env = mock_env_with_block_time(1000);
+    let info = mock_info("asset0000", &[]);
+    let res = execute(deps.as_mut(), env, info, msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::new(BankMsg::Send {
+                to_address: String::from("addr0000"),
+                amount: vec![Coin {
+                    denom: "uusd".to_string(),
+                    amount: ask_amount,
+                }],
+            }),
+            SubMsg::new(WasmMsg::Execute {
+                contract_addr: String::from("asset0000"),
+                msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                    recipient: String::from("addr0000"),
+                    amount: max_offer - expected_offer_amount,
+                })
+                .unwrap(),
+                funds: vec![],
+            }),
+            SubMsg::new(BankMsg::Send {
+                to_address: String::from("fee_address"),
+                amount: vec![Coin {
+                    denom: "uusd".to_string(),
+                    amount: expected_maker_fee_amount,
+                }],
+            }),
+        ]
+    );
+    assert_eq!(
+        res.attributes[9],
+        attr("spread_amount", expected_spread_amount.to_string())
+    );
+}
+
+#[test]
+fn execution_guards() {
+    let total_share = Uint128::new(20000000000u128);
+    let asset_pool_amount = Uint128::new(30000000000u128);
+    let collateral_pool_amount = Uint128::new(20000000000u128);
+    let offer_amount = Uint128::new(1000000000u128);
+
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: collateral_pool_amount + offer_amount,
+    }]);
+    deps.querier.with_token_balances(&[
+        (
+            &String::from("liquidity0000"),
+            &[(&String::from(MOCK_CONTRACT_ADDR), &total_share)],
+        ),
+        (
+            &String::from("asset0000"),
+            &[(&String::from(MOCK_CONTRACT_ADDR), &asset_pool_amount)],
+        ),
+    ]);
+
+    let msg = InstantiateMsg {
+        asset_infos: [
+            AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            AssetInfo::Token {
+                contract_addr: Addr::unchecked("asset0000"),
+            },
+        ],
+        token_code_id: 10u64,
+        factory_addr: String::from("factory"),
+        init_params: None,
+    };
+
+    let env = mock_env();
+    let info = mock_info("addr0000", &[]);
+    instantiate(deps.as_mut(), env, info, msg).unwrap();
+    store_liquidity_token(deps.as_mut(), 1, "liquidity0000".to_string());
+    store_reserves(deps.as_mut(), [collateral_pool_amount, asset_pool_amount]);
+
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: offer_amount,
+        }],
+    );
+    let swap_msg = |min_return: Option<Uint128>, deadline: Option<u64>| ExecuteMsg::Swap {
+        offer_asset: Asset {
+            info: AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            amount: offer_amount,
+        },
+        belief_price: None,
+        max_spread: Some(Decimal::percent(50)),
+        to: None,
+        min_return,
+        deadline,
+    };
+
+    // The deadline is in the past
+    let res = execute(
+        deps.as_mut(),
+        mock_env_with_block_time(1000),
+        info.clone(),
+        swap_msg(None, Some(999)),
+    )
+    .unwrap_err();
+    assert_eq!(res, ContractError::DeadlineExpired {});
+
+    // 1424285714 = (30000000000 - 20000000000 * 30000000000 / 21000000000) * 0.997
+    let res = execute(
+        deps.as_mut(),
+        mock_env_with_block_time(1000),
+        info.clone(),
+        swap_msg(Some(Uint128::new(1424285715u128)), None),
+    )
+    .unwrap_err();
+    assert_eq!(res, ContractError::MinReturnAssertion {});
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env_with_block_time(1000),
+        info.clone(),
+        swap_msg(Some(Uint128::new(1424285714u128)), Some(1000)),
+    )
+    .unwrap();
+    assert_eq!(res.attributes[6], attr("return_amount", "1424285714"));
+
+    store_reserves(deps.as_mut(), [collateral_pool_amount, asset_pool_amount]);
+
+    // Provide 1000000000 uusd and 1500000000 asset0000 for 1000000000 LP tokens
+    let provide_msg =
+        |min_lp_to_receive: Option<Uint128>, deadline: Option<u64>| ExecuteMsg::ProvideLiquidity {
+            assets: [
+                Asset {
+                    info: AssetInfo::NativeToken {
+                        denom: "uusd".to_string(),
+                    },
+                    amount: offer_amount,
+                },
+                Asset {
+                    info: AssetInfo::Token {
+                        contract_addr: Addr::unchecked("asset0000"),
+                    },
+                    amount: Uint128::new(1500000000u128),
+                },
+            ],
+            slippage_tolerance: None,
+            auto_stake: None,
+            receiver: None,
+            min_lp_to_receive,
+            deadline,
+        };
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env_with_block_time(1000),
+        info.clone(),
+        provide_msg(None, Some(999)),
+    )
+    .unwrap_err();
+    assert_eq!(res, ContractError::DeadlineExpired {});
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env_with_block_time(1000),
+        info.clone(),
+        provide_msg(Some(Uint128::new(1000000001u128)), None),
+    )
+    .unwrap_err();
+    assert_eq!(res, ContractError::MinLpToReceiveAssertion {});
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env_with_block_time(1000),
+        info,
+        provide_msg(Some(Uint128::new(1000000000u128)), None),
+    )
+    .unwrap();
+    assert_eq!(res.attributes[4], attr("share", "1000000000"));
+
+    // Withdraw 1000000000 LP tokens for 1000000000 uusd and 1500000000 asset0000
+    deps.querier.with_balance(&[(
+        &String::from(MOCK_CONTRACT_ADDR),
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: collateral_pool_amount,
+        }],
+    )]);
+    store_reserves(deps.as_mut(), [collateral_pool_amount, asset_pool_amount]);
+    let withdraw_msg = |min_assets_out: Option<Vec<Asset>>, deadline: Option<u64>| {
+        ExecuteMsg::Receive(Cw20ReceiveMsg {
+            sender: String::from("addr0000"),
+            msg: to_binary(&Cw20HookMsg::WithdrawLiquidity {
+                min_assets_out,
+                deadline,
+            })
+            .unwrap(),
+            amount: Uint128::new(1000000000u128),
+        })
+    };
+    let uusd_out = |amount: u128| Asset {
+        info: AssetInfo::NativeToken {
+            denom: "uusd".to_string(),
+        },
+        amount: Uint128::new(amount),
+    };
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env_with_block_time(1000),
+        mock_info("liquidity0000", &[]),
+        withdraw_msg(None, Some(999)),
+    )
+    .unwrap_err();
+    assert_eq!(res, ContractError::DeadlineExpired {});
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env_with_block_time(1000),
+        mock_info("liquidity0000", &[]),
+        withdraw_msg(Some(vec![uusd_out(1000000001u128)]), None),
+    )
+    .unwrap_err();
+    assert_eq!(res, ContractError::MinAssetsOutAssertion {});
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env_with_block_time(1000),
+        mock_info("liquidity0000", &[]),
+        withdraw_msg(
+            Some(vec![Asset {
+                info: AssetInfo::Token {
+                    contract_addr: Addr::unchecked("asset0001"),
+                },
+                amount: Uint128::new(1u128),
+            }]),
+            None,
+        ),
+    )
+    .unwrap_err();
+    assert_eq!(res, ContractError::AssetMismatch {});
+
+    execute(
+        deps.as_mut(),
+        mock_env_with_block_time(1000),
+        mock_info("liquidity0000", &[]),
+        withdraw_msg(Some(vec![uusd_out(1000000000u128)]), Some(1000)),
+    )
+    .unwrap();
+}
+
+#[test]
+fn migrate_from_v100() {
+    let asset_pool_amount = Uint128::new(30000000000u128);
+    let collateral_pool_amount = Uint128::new(20000000000u128);
+
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: collateral_pool_amount,
+    }]);
+    deps.querier.with_token_balances(&[(
+        &String::from("asset0000"),
+        &[(&String::from(MOCK_CONTRACT_ADDR), &asset_pool_amount)],
+    )]);
+
+    let msg = InstantiateMsg {
+        asset_infos: [
+            AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            AssetInfo::Token {
+                contract_addr: Addr::unchecked("asset0000"),
+            },
+        ],
+        token_code_id: 10u64,
+        factory_addr: String::from("factory"),
+        init_params: None,
+    };
+    instantiate(deps.as_mut(), mock_env(), mock_info("addr0000", &[]), msg).unwrap();
+
+    // The current version cannot be migrated again
+    let res = migrate(deps.as_mut(), mock_env(), MigrateMsg {}).unwrap_err();
+    assert_eq!(res, ContractError::MigrationError {});
+
+    // A v1.0.0 pair doesn't track its reserves and doesn't cache the factory parameters
+    set_contract_version(deps.as_mut().storage, "astroport-pair", "1.0.0").unwrap();
+    RESERVES.remove(deps.as_mut().storage);
+    FACTORY_PARAMS.remove(deps.as_mut().storage);
+
+    let res = migrate(deps.as_mut(), mock_env(), MigrateMsg {}).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("previous_contract_name", "astroport-pair"),
+            attr("previous_contract_version", "1.0.0"),
+            attr("new_contract_name", "astroport-pair"),
+            attr("new_contract_version", env!("CARGO_PKG_VERSION")),
+        ]
+    );
+    assert_eq!(
+        RESERVES.load(deps.as_ref().storage).unwrap(),
+        [collateral_pool_amount, asset_pool_amount]
+    );
+    assert!(FACTORY_PARAMS.load(deps.as_ref().storage).is_ok());
+    assert_eq!(
+        get_contract_version(deps.as_ref().storage).unwrap().version,
+        env!("CARGO_PKG_VERSION")
+    );
+}
+
+#[test]
+fn sync_and_skim() {
+    let total_share = Uint128::new(20000000000u128);
+    let asset_pool_amount = Uint128::new(30000000000u128);
+    let collateral_pool_amount = Uint128::new(20000000000u128);
+    let donation_amount = Uint128::new(1000000000u128);
+
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: collateral_pool_amount,
+    }]);
+    deps.querier.with_token_balances(&[
+        (
+            &String::from("liquidity0000"),
+            &[(&String::from(MOCK_CONTRACT_ADDR), &total_share)],
+        ),
+        (
+            &String::from("asset0000"),
+            &[(&String::from(MOCK_CONTRACT_ADDR), &asset_pool_amount)],
+        ),
+    ]);
+
+    let msg = InstantiateMsg {
+        asset_infos: [
+            AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            AssetInfo::Token {
+                contract_addr: Addr::unchecked("asset0000"),
+            },
+        ],
+        token_code_id: 10u64,
+        factory_addr: String::from("factory"),
+        init_params: None,
+    };
+
+    let env = mock_env();
+    let info = mock_info("addr0000", &[]);
+    instantiate(deps.as_mut(), env, info, msg).unwrap();
+    store_liquidity_token(deps.as_mut(), 1, "liquidity0000".to_string());
+
+    // The reserves are tracked from zero, so the balances of the pair are not counted
+    let res: PoolResponse = query_pool(deps.as_ref()).unwrap();
+    assert_eq!(res.assets[0].amount, Uint128::zero());
+    assert_eq!(res.assets[1].amount, Uint128::zero());
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        ExecuteMsg::Sync {},
+    )
+    .unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "sync"),
+            attr("reserves", "20000000000uusd, 30000000000asset0000"),
+        ]
+    );
+
+    let offer_asset = Asset {
+        info: AssetInfo::NativeToken {
+            denom: "uusd".to_string(),
+        },
+        amount: Uint128::new(1000000000u128),
+    };
+    let simulation_res = query_simulation(deps.as_ref(), offer_asset.clone()).unwrap();
+
+    // A direct transfer to the pair doesn't move the price
+    deps.querier.with_token_balances(&[
+        (
+            &String::from("liquidity0000"),
+            &[(&String::from(MOCK_CONTRACT_ADDR), &total_share)],
+        ),
+        (
+            &String::from("asset0000"),
+            &[(
+                &String::from(MOCK_CONTRACT_ADDR),
+                &(asset_pool_amount + donation_amount),
+            )],
+        ),
+    ]);
+    assert_eq!(
+        query_simulation(deps.as_ref(), offer_asset.clone()).unwrap(),
+        simulation_res
+    );
+    let res: PoolResponse = query_pool(deps.as_ref()).unwrap();
+    assert_eq!(res.assets[1].amount, asset_pool_amount);
+
+    // The excess balance is sent to the recipient
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        ExecuteMsg::Skim {
+            to: Some(String::from("addr0001")),
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(WasmMsg::Execute {
+            contract_addr: String::from("asset0000"),
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: String::from("addr0001"),
+                amount: donation_amount,
+            })
+            .unwrap(),
+            funds: vec![],
+        })]
+    );
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "skim"),
+            attr("receiver", "addr0001"),
+            attr("assets", "0uusd, 1000000000asset0000"),
+        ]
+    );
+
+    // Syncing adds the excess balance to the pool instead
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        ExecuteMsg::Sync {},
+    )
+    .unwrap();
+    let res: PoolResponse = query_pool(deps.as_ref()).unwrap();
+    assert_eq!(res.assets[0].amount, collateral_pool_amount);
+    assert_eq!(res.assets[1].amount, asset_pool_amount + donation_amount);
+    assert_ne!(
+        query_simulation(deps.as_ref(), offer_asset).unwrap(),
+        simulation_res
+    );
+
+    // Nothing is left to skim
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        ExecuteMsg::Skim { to: None },
+    )
+    .unwrap();
+    assert!(res.messages.is_empty());
+}
+
+#[test]
+fn cached_factory_params() {
+    let total_share = Uint128::new(30000000000u128);
+    let asset_pool_amount = Uint128::new(20000000000u128);
+    let collateral_pool_amount = Uint128::new(30000000000u128);
+    let offer_amount = Uint128::new(1500000000u128);
+
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: collateral_pool_amount + offer_amount,
+    }]);
+    deps.querier.with_token_balances(&[
+        (
+            &String::from("liquidity0000"),
+            &[(&String::from(MOCK_CONTRACT_ADDR), &total_share)],
+        ),
+        (
+            &String::from("asset0000"),
+            &[(&String::from(MOCK_CONTRACT_ADDR), &asset_pool_amount)],
+        ),
+    ]);
+
+    let msg = InstantiateMsg {
+        asset_infos: [
+            AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            AssetInfo::Token {
+                contract_addr: Addr::unchecked("asset0000"),
+            },
+        ],
+        token_code_id: 10u64,
+        factory_addr: String::from("factory"),
+        init_params: None,
+    };
+
+    let env = mock_env();
+    let info = mock_info("addr0000", &[]);
+    instantiate(deps.as_mut(), env, info, msg).unwrap();
+    store_liquidity_token(deps.as_mut(), 1, "liquidity0000".to_string());
+    store_reserves(deps.as_mut(), [collateral_pool_amount, asset_pool_amount]);
+
+    // The fee and Generator information is cached at instantiation
+    let expected_params = FactoryParams {
+        fee_info: FeeInfo {
+            fee_address: Some(Addr::unchecked("fee_address")),
+            total_fee_rate: Decimal::from_ratio(3u128, 1000u128),
+            maker_fee_rate: Decimal::from_ratio(166u128, 1000u128),
+            maker_fee_as_lp: false,
+        },
+        generator_address: Some(Addr::unchecked("generator")),
+        pair_status: PairStatus::Active,
+    };
+    assert_eq!(
+        FACTORY_PARAMS.load(deps.as_ref().storage).unwrap(),
+        expected_params
+    );
+
+    // Benchmark a swap. It used to query the factory for the fee info, but now the fees are read
+    // from the cache and the reserves from storage, so the swap doesn't make any queries at all
+    let (queries_before, factory_queries_before) = deps.querier.query_counts();
+    let msg = ExecuteMsg::Swap {
+        offer_asset: Asset {
+            info: AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            amount: offer_amount,
+        },
+        belief_price: None,
+        max_spread: Some(Decimal::percent(50)),
+        to: None,
+        min_return: None,
+        deadline: None,
+    };
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: offer_amount,
+        }],
+    );
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    let (queries_after, factory_queries_after) = deps.querier.query_counts();
+    assert_eq!(factory_queries_after, factory_queries_before);
+    assert_eq!(queries_after, queries_before);
+
+    // Anyone can refresh the cached parameters
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("anyone", &[]),
+        ExecuteMsg::RefreshFactoryParams {},
+    )
+    .unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "refresh_factory_params"),
+            attr("total_fee_rate", "0.003"),
+            attr("maker_fee_rate", "0.166"),
+            attr("fee_address", "fee_address"),
+            attr("generator_address", "generator"),
+            attr("maker_fee_share", "0"),
+            attr("pair_status", "active"),
+        ]
+    );
+    assert_eq!(deps.querier.query_counts().1, factory_queries_after + 3);
+    assert_eq!(
+        FACTORY_PARAMS.load(deps.as_ref().storage).unwrap(),
+        expected_params
+    );
+}
+
+#[test]
+fn pair_status_circuit_breaker() {
+    let total_share = Uint128::new(30000000000u128);
+    let asset_pool_amount = Uint128::new(20000000000u128);
+    let collateral_pool_amount = Uint128::new(30000000000u128);
+    let offer_amount = Uint128::new(1500000000u128);
+
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: collateral_pool_amount + offer_amount,
+    }]);
+    deps.querier.with_token_balances(&[
+        (
+            &String::from("liquidity0000"),
+            &[(&String::from(MOCK_CONTRACT_ADDR), &total_share)],
+        ),
+        (
+            &String::from("asset0000"),
+            &[(&String::from(MOCK_CONTRACT_ADDR), &asset_pool_amount)],
+        ),
+    ]);
+
+    let msg = InstantiateMsg {
+        asset_infos: [
+            AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            AssetInfo::Token {
+                contract_addr: Addr::unchecked("asset0000"),
+            },
+        ],
+        token_code_id: 10u64,
+        factory_addr: String::from("factory"),
+        init_params: None,
+    };
+    instantiate(deps.as_mut(), mock_env(), mock_info("addr0000", &[]), msg).unwrap();
+    store_liquidity_token(deps.as_mut(), 1, "liquidity0000".to_string());
+    store_reserves(deps.as_mut(), [collateral_pool_amount, asset_pool_amount]);
+
+    let swap = |deps: DepsMut| {
+        let msg = ExecuteMsg::Swap {
+            offer_asset: Asset {
+                info: AssetInfo::NativeToken {
+                    denom: "uusd".to_string(),
+                },
+                amount: offer_amount,
+            },
+            belief_price: None,
+            max_spread: Some(Decimal::percent(50)),
+            to: None,
+            min_return: None,
+            deadline: None,
+        };
+        let info = mock_info(
+            "addr0000",
+            &[Coin {
+                denom: "uusd".to_string(),
+                amount: offer_amount,
+            }],
+        );
+        execute(deps, mock_env(), info, msg)
+    };
+    let provide = |deps: DepsMut| {
+        let msg = ExecuteMsg::ProvideLiquidity {
+            assets: [
+                Asset {
+                    info: AssetInfo::NativeToken {
+                        denom: "uusd".to_string(),
+                    },
+                    amount: Uint128::new(300u128),
+                },
+                Asset {
+                    info: AssetInfo::Token {
+                        contract_addr: Addr::unchecked("asset0000"),
+                    },
+                    amount: Uint128::new(200u128),
+                },
+            ],
+            slippage_tolerance: Some(Decimal::percent(50)),
+            auto_stake: None,
+            receiver: None,
+            min_lp_to_receive: None,
+            deadline: None,
+        };
+        let info = mock_info(
+            "addr0000",
+            &[Coin {
+                denom: "uusd".to_string(),
+                amount: Uint128::new(300u128),
+            }],
+        );
+        execute(deps, mock_env(), info, msg)
+    };
+    let withdraw = |deps: DepsMut| {
+        let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+            sender: String::from("addr0000"),
+            msg: to_binary(&Cw20HookMsg::WithdrawLiquidity {
+                min_assets_out: None,
+                deadline: None,
+            })
+            .unwrap(),
+            amount: Uint128::new(100u128),
+        });
+        execute(deps, mock_env(), mock_info("liquidity0000", &[]), msg)
+    };
+    let refresh = |deps: DepsMut| {
+        execute(
+            deps,
+            mock_env(),
+            mock_info("anyone", &[]),
+            ExecuteMsg::RefreshFactoryParams {},
+        )
+        .unwrap()
+    };
+    let query_status = |deps: Deps| -> PairStatus {
+        from_binary(&query(deps, mock_env(), QueryMsg::PairStatus {}).unwrap()).unwrap()
+    };
+
+    // The pair is active by default
+    assert_eq!(query_status(deps.as_ref()), PairStatus::Active);
+
+    // The factory pauses swaps. The pair picks the status up once its cached parameters are refreshed
+    deps.querier.with_pair_status(PairStatus::SwapsPaused);
+    assert_eq!(query_status(deps.as_ref()), PairStatus::Active);
+    let res = refresh(deps.as_mut());
+    assert_eq!(
+        res.attributes.last().unwrap(),
+        &attr("pair_status", "swaps_paused")
+    );
+    assert_eq!(query_status(deps.as_ref()), PairStatus::SwapsPaused);
+    assert_eq!(
+        swap(deps.as_mut()).unwrap_err(),
+        ContractError::SwapsPaused {}
+    );
+    provide(deps.as_mut()).unwrap();
+
+    // Pausing deposits allows swaps again
+    deps.querier.with_pair_status(PairStatus::DepositsPaused);
+    refresh(deps.as_mut());
+    assert_eq!(
+        provide(deps.as_mut()).unwrap_err(),
+        ContractError::DepositsPaused {}
+    );
+    swap(deps.as_mut()).unwrap();
+
+    // In withdraw-only mode LPs can still exit the pool
+    deps.querier.with_pair_status(PairStatus::WithdrawOnly);
+    refresh(deps.as_mut());
+    assert_eq!(query_status(deps.as_ref()), PairStatus::WithdrawOnly);
+    assert_eq!(
+        swap(deps.as_mut()).unwrap_err(),
+        ContractError::SwapsPaused {}
+    );
+    assert_eq!(
+        provide(deps.as_mut()).unwrap_err(),
+        ContractError::DepositsPaused {}
+    );
+    withdraw(deps.as_mut()).unwrap();
+
+    // Resuming the pair lifts every restriction
+    deps.querier.with_pair_status(PairStatus::Active);
+    refresh(deps.as_mut());
+    swap(deps.as_mut()).unwrap();
+    provide(deps.as_mut()).unwrap();
+}
+
+#[test]
+fn protocol_fee_accrual() {
+    let total_share = Uint128::new(30000000000u128);
+    let asset_pool_amount = Uint128::new(20000000000u128);
+    let collateral_pool_amount = Uint128::new(30000000000u128);
+    let offer_amount = Uint128::new(1500000000u128);
+
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: collateral_pool_amount + offer_amount,
+    }]);
+    deps.querier.with_token_balances(&[
+        (
+            &String::from("liquidity0000"),
+            &[(&String::from(MOCK_CONTRACT_ADDR), &total_share)],
+        ),
+        (
+            &String::from("asset0000"),
+            &[(&String::from(MOCK_CONTRACT_ADDR), &asset_pool_amount)],
+        ),
+    ]);
+
+    let msg = InstantiateMsg {
+        asset_infos: [
+            AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            AssetInfo::Token {
+                contract_addr: Addr::unchecked("asset0000"),
+            },
+        ],
+        token_code_id: 10u64,
+        factory_addr: String::from("factory"),
+        init_params: None,
+    };
+
+    let env = mock_env();
+    let info = mock_info("addr0000", &[]);
+    instantiate(deps.as_mut(), env, info, msg).unwrap();
+    store_liquidity_token(deps.as_mut(), 1, "liquidity0000".to_string());
+    store_reserves(deps.as_mut(), [collateral_pool_amount, asset_pool_amount]);
+
+    // Only the factory owner can enable the accrual
+    let msg = ExecuteMsg::SetProtocolFeeAccrual { enabled: true };
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        msg.clone(),
+    );
+    assert_eq!(res.unwrap_err(), ContractError::Unauthorized {});
+    let res = execute(deps.as_mut(), mock_env(), mock_info("owner", &[]), msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "set_protocol_fee_accrual"),
+            attr("enabled", "true"),
+        ]
+    );
+
+    // The Maker fee stays in the pair instead of being sent to the fee address
+    let msg = ExecuteMsg::Swap {
+        offer_asset: Asset {
+            info: AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            amount: offer_amount,
+        },
+        belief_price: None,
+        max_spread: Some(Decimal::percent(50)),
+        to: None,
+        min_return: None,
+        deadline: None,
+    };
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: offer_amount,
+        }],
+    );
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(res.messages.len(), 1);
+    let attr_amount = |key: &str| -> Uint128 {
+        let value = &res.attributes.iter().find(|a| a.key == key).unwrap().value;
+        Uint128::new(value.parse().unwrap())
+    };
+    let return_amount = attr_amount("return_amount");
+    let maker_fee_amount = attr_amount("maker_fee_amount");
+    assert!(!maker_fee_amount.is_zero());
+
+    let protocol_fees = query_protocol_fees(deps.as_ref()).unwrap();
+    assert!(protocol_fees.accrual_enabled);
+    assert_eq!(protocol_fees.assets[0].amount, Uint128::zero());
+    assert_eq!(protocol_fees.assets[1].amount, maker_fee_amount);
+
+    // The accrued fees are not a part of the pool
+    let asset_balance = asset_pool_amount - return_amount;
+    deps.querier.with_token_balances(&[
+        (
+            &String::from("liquidity0000"),
+            &[(&String::from(MOCK_CONTRACT_ADDR), &total_share)],
+        ),
+        (
+            &String::from("asset0000"),
+            &[(&String::from(MOCK_CONTRACT_ADDR), &asset_balance)],
+        ),
+    ]);
+    let res: PoolResponse = query_pool(deps.as_ref()).unwrap();
+    assert_eq!(res.assets[1].amount, asset_balance - maker_fee_amount);
+
+    // Skimming and syncing don't touch the accrued fees
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        ExecuteMsg::Skim { to: None },
+    )
+    .unwrap();
+    assert!(res.messages.is_empty());
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        ExecuteMsg::Sync {},
+    )
+    .unwrap();
+    let res: PoolResponse = query_pool(deps.as_ref()).unwrap();
+    assert_eq!(res.assets[1].amount, asset_balance - maker_fee_amount);
+
+    // Only the fee address can claim the accrued fees
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        ExecuteMsg::ClaimProtocolFees {},
+    );
+    assert_eq!(res.unwrap_err(), ContractError::Unauthorized {});
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("fee_address", &[]),
+        ExecuteMsg::ClaimProtocolFees {},
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(WasmMsg::Execute {
+            contract_addr: String::from("asset0000"),
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: String::from("fee_address"),
+                amount: maker_fee_amount,
+            })
+            .unwrap(),
+            funds: vec![],
+        })]
+    );
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "claim_protocol_fees"),
+            attr("receiver", "fee_address"),
+            attr(
+                "assets",
+                format!("0uusd, {}asset0000", maker_fee_amount).as_str()
+            ),
+        ]
+    );
+    let protocol_fees = query_protocol_fees(deps.as_ref()).unwrap();
+    assert_eq!(protocol_fees.assets[1].amount, Uint128::zero());
+}
+
+#[test]
+fn maker_fee_as_lp() {
+    let total_share = Uint128::new(20000000000u128);
+    let asset_pool_amount = Uint128::new(20000000000u128);
+    let collateral_pool_amount = Uint128::new(30000000000u128);
+    let offer_amount = Uint128::new(1500000000u128);
+    let withdraw_amount = Uint128::new(1000000000u128);
+
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: collateral_pool_amount + offer_amount,
+    }]);
+    deps.querier.with_token_balances(&[
+        (
+            &String::from("liquidity0000"),
+            &[(&String::from("addr0000"), &total_share)],
+        ),
+        (
+            &String::from("asset0000"),
+            &[(&String::from(MOCK_CONTRACT_ADDR), &asset_pool_amount)],
+        ),
+    ]);
+    deps.querier.with_maker_fee_as_lp(true);
+
+    let msg = InstantiateMsg {
+        asset_infos: [
+            AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            AssetInfo::Token {
+                contract_addr: Addr::unchecked("asset0000"),
+            },
+        ],
+        token_code_id: 10u64,
+        factory_addr: String::from("factory"),
+        init_params: None,
+    };
+
+    let env = mock_env();
+    let info = mock_info("addr0000", &[]);
+    instantiate(deps.as_mut(), env, info, msg).unwrap();
+    store_liquidity_token(deps.as_mut(), 1, "liquidity0000".to_string());
+    store_reserves(deps.as_mut(), [collateral_pool_amount, asset_pool_amount]);
+
+    let root_k = |x: Uint128, y: Uint128| {
+        Uint128::new(
+            (U256::from(x.u128()) * U256::from(y.u128()))
+                .integer_sqrt()
+                .as_u128(),
+        )
+    };
+
+    // The growth of the pool is measured from the refresh
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("anyone", &[]),
+        ExecuteMsg::RefreshFactoryParams {},
+    )
+    .unwrap();
+    let root_k_last = root_k(collateral_pool_amount, asset_pool_amount);
+    assert_eq!(
+        ROOT_K_LAST.load(deps.as_ref().storage).unwrap(),
+        root_k_last
+    );
+
+    // The whole commission stays in the pool
+    let msg = ExecuteMsg::Swap {
+        offer_asset: Asset {
+            info: AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            amount: offer_amount,
+        },
+        belief_price: None,
+        max_spread: Some(Decimal::percent(50)),
+        to: None,
+        min_return: None,
+        deadline: None,
+    };
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: offer_amount,
+        }],
+    );
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(res.messages.len(), 1);
+    assert!(res.attributes.contains(&attr("maker_fee_amount", "0")));
+    let reserves = RESERVES.load(deps.as_ref().storage).unwrap();
+    assert_eq!(reserves[0], collateral_pool_amount + offer_amount);
+
+    // The Maker share of the sqrt(k) growth is minted on the next liquidity event
+    let root_k_before = root_k(reserves[0], reserves[1]);
+    let fee_growth = (root_k_before - root_k_last) * Decimal::from_ratio(166u128, 1000u128);
+    let maker_fee_share = total_share.multiply_ratio(fee_growth, root_k_before - fee_growth);
+    assert!(!maker_fee_share.is_zero());
+
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: String::from("addr0000"),
+        msg: to_binary(&Cw20HookMsg::WithdrawLiquidity {
+            min_assets_out: None,
+            deadline: None,
+        })
+        .unwrap(),
+        amount: withdraw_amount,
+    });
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("liquidity0000", &[]),
+        msg,
+    )
+    .unwrap();
+    assert!(res.messages.contains(&SubMsg::new(WasmMsg::Execute {
+        contract_addr: String::from("liquidity0000"),
+        msg: to_binary(&Cw20ExecuteMsg::Mint {
+            recipient: String::from("fee_address"),
+            amount: maker_fee_share,
+        })
+        .unwrap(),
+        funds: vec![],
+    })));
+
+    // The refund is calculated with the minted Maker fee included in the total share
+    let reserves_after = RESERVES.load(deps.as_ref().storage).unwrap();
+    assert_eq!(
+        reserves_after[1],
+        reserves[1] - reserves[1].multiply_ratio(withdraw_amount, total_share + maker_fee_share)
+    );
+    assert_eq!(
+        ROOT_K_LAST.load(deps.as_ref().storage).unwrap(),
+        root_k(reserves_after[0], reserves_after[1])
+    );
+
+    // The Maker share accrued before the Maker fee stops being collected in LP tokens is minted on the refresh
+    let msg = ExecuteMsg::Swap {
+        offer_asset: Asset {
+            info: AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            amount: offer_amount,
+        },
+        belief_price: None,
+        max_spread: Some(Decimal::percent(50)),
+        to: None,
+        min_return: None,
+        deadline: None,
+    };
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: offer_amount,
+        }],
+    );
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let reserves = RESERVES.load(deps.as_ref().storage).unwrap();
+    let root_k_last = root_k(reserves_after[0], reserves_after[1]);
+    let root_k_before = root_k(reserves[0], reserves[1]);
+    let fee_growth = (root_k_before - root_k_last) * Decimal::from_ratio(166u128, 1000u128);
+    let maker_fee_share = total_share.multiply_ratio(fee_growth, root_k_before - fee_growth);
+    assert!(!maker_fee_share.is_zero());
+
+    deps.querier.with_maker_fee_as_lp(false);
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("anyone", &[]),
+        ExecuteMsg::RefreshFactoryParams {},
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(WasmMsg::Execute {
+            contract_addr: String::from("liquidity0000"),
+            msg: to_binary(&Cw20ExecuteMsg::Mint {
+                recipient: String::from("fee_address"),
+                amount: maker_fee_share,
+            })
+            .unwrap(),
+            funds: vec![],
+        })]
+    );
+    assert!(res
+        .attributes
+        .contains(&attr("maker_fee_share", maker_fee_share.to_string())));
+    assert!(ROOT_K_LAST
+        .may_load(deps.as_ref().storage)
+        .unwrap()
+        .is_none());
+
+    // Nothing is minted once the Maker fee isn't collected in LP tokens
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("anyone", &[]),
+        ExecuteMsg::RefreshFactoryParams {},
+    )
+    .unwrap();
+    assert!(res.messages.is_empty());
+}
+
+#[test]
+fn test_max_spread() {
+    assert_max_spread(
+        Some(Decimal::from_ratio(1200u128, 1u128)),
+        Some(Decimal::percent(1)),
+        Uint128::from(1200000000u128),
+        Uint128::from(989999u128),
+        Uint128::zero(),
+    )
+    .unwrap_err();
+
+    assert_max_spread(
+        Some(Decimal::from_ratio(1200u128, 1u128)),
+        Some(Decimal::percent(1)),
+        Uint128::from(1200000000u128),
+        Uint128::from(990000u128),
+        Uint128::zero(),
+    )
+    .unwrap();
+
+    assert_max_spread(
+        None,
+        Some(Decimal::percent(1)),
+        Uint128::zero(),
+        Uint128::from(989999u128),
+        Uint128::from(10001u128),
+    )
+    .unwrap_err();
+
+    assert_max_spread(
+        None,
+        Some(Decimal::percent(1)),
+        Uint128::zero(),
+        Uint128::from(990000u128),
+        Uint128::from(10000u128),
+    )
+    .unwrap();
+}
+
+#[test]
+fn test_deduct() {
+    let mut deps = mock_dependencies(&[]);
+
+    let tax_rate = Decimal::percent(2);
+    let tax_cap = Uint128::from(1_000_000u128);
+    deps.querier.with_tax(
+        Decimal::percent(2),
+        &[(&"uusd".to_string(), &Uint128::from(1000000u128))],
+    );
+
+    let amount = Uint128::new(1000_000_000u128);
+    let expected_after_amount = std::cmp::max(
+        amount.checked_sub(amount * tax_rate).unwrap(),
+        amount.checked_sub(tax_cap).unwrap(),
+    );
+
+    let after_amount = (Asset {
+        info: AssetInfo::NativeToken {
+            denom: "uusd".to_string(),
+        },
+        amount,
+    })
+    .deduct_tax(&deps.as_ref().querier)
+    .unwrap();
+
+    assert_eq!(expected_after_amount, after_amount.amount);
+}
+
+#[test]
+fn test_query_pool() {
+    let total_share_amount = Uint128::from(111u128);
+    let asset_0_amount = Uint128::from(222u128);
+    let asset_1_amount = Uint128::from(333u128);
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: asset_0_amount,
+    }]);
+
+    deps.querier.with_token_balances(&[
+        (
+            &String::from("asset0000"),
+            &[(&String::from(MOCK_CONTRACT_ADDR), &asset_1_amount)],
+        ),
+        (
+            &String::from("liquidity0000"),
+            &[(&String::from(MOCK_CONTRACT_ADDR), &total_share_amount)],
+        ),
+    ]);
+
+    let msg = InstantiateMsg {
+        asset_infos: [
+            AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            AssetInfo::Token {
+                contract_addr: Addr::unchecked("asset0000"),
+            },
+        ],
+        token_code_id: 10u64,
+        factory_addr: String::from("factory"),
+        init_params: None,
+    };
+
+    let env = mock_env();
+    let info = mock_info("addr0000", &[]);
+    // We can just call .unwrap() to assert this was a success
+    let _res = instantiate(deps.as_mut(), env, info, msg).unwrap();
+
+    // Store liquidity token
+    store_liquidity_token(deps.as_mut(), 1, "liquidity0000".to_string());
+
+    // Start tracking the pool balances
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        ExecuteMsg::Sync {},
+    )
+    .unwrap();
+
+    let res: PoolResponse = query_pool(deps.as_ref()).unwrap();
+
+    assert_eq!(
+        res.assets,
         [
             Asset {
                 info: AssetInfo::NativeToken {
@@ -1239,6 +3210,15 @@ fn test_query_share() {
     // Store liquidity token
     store_liquidity_token(deps.as_mut(), 1, "liquidity0000".to_string());
 
+    // Start tracking the pool balances
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        ExecuteMsg::Sync {},
+    )
+    .unwrap();
+
     let res = query_share(deps.as_ref(), Uint128::new(250)).unwrap();
 
     assert_eq!(res[0].amount, Uint128::new(125));
@@ -1363,6 +3343,605 @@ fn test_accumulate_prices() {
     }
 }
 
+#[test]
+fn test_accumulate_prices_u256() {
+    let config = Config {
+        pair_info: PairInfo {
+            asset_infos: [
+                AssetInfo::NativeToken {
+                    denom: "uusd".to_string(),
+                },
+                AssetInfo::Token {
+                    contract_addr: Addr::unchecked("asset0000"),
+                },
+            ],
+            contract_addr: Addr::unchecked("pair"),
+            liquidity_token: Addr::unchecked("lp_token"),
+            pair_type: PairType::Xyk {},
+        },
+        factory_addr: Addr::unchecked("factory"),
+        block_time_last: 1000,
+        price0_cumulative_last: Uint128::zero(),
+        price1_cumulative_last: Uint128::zero(),
+    };
+    let price_precision = StdUint256::from(10u128.pow(TWAP_PRECISION_U256.into()));
+
+    // Same block time, no changes
+    let res = accumulate_prices_u256(
+        mock_env_with_block_time(1000),
+        &config,
+        [StdUint256::zero(); 2],
+        Uint128::new(250),
+        Uint128::new(500),
+    )
+    .unwrap();
+    assert_eq!(res, None);
+
+    // The price of asset 0 is too small for the legacy accumulator, but not for the 256-bit one
+    let x = Uint128::new(1_000_000_000_000_000);
+    let y = Uint128::new(1_000_000);
+    let legacy = accumulate_prices(mock_env_with_block_time(1500), &config, x, y)
+        .unwrap()
+        .unwrap();
+    assert_eq!(legacy.0, Uint128::zero());
+    let res = accumulate_prices_u256(
+        mock_env_with_block_time(1500),
+        &config,
+        [StdUint256::zero(); 2],
+        x,
+        y,
+    )
+    .unwrap()
+    .unwrap();
+    assert_eq!(
+        res[0],
+        StdUint256::from(500u64) * price_precision / StdUint256::from(1_000_000_000u64)
+    );
+    assert_eq!(
+        res[1],
+        StdUint256::from(500u64) * price_precision * StdUint256::from(1_000_000_000u64)
+    );
+
+    // The accumulators wrap around on overflow
+    let max = StdUint256::from_be_bytes([u8::MAX; 32]);
+    let res = accumulate_prices_u256(
+        mock_env_with_block_time(1500),
+        &config,
+        [max; 2],
+        Uint128::new(250),
+        Uint128::new(500),
+    )
+    .unwrap()
+    .unwrap();
+    assert_eq!(
+        res[0],
+        StdUint256::from(1000u64) * price_precision - StdUint256::from(1u64)
+    );
+    assert_eq!(
+        res[1],
+        StdUint256::from(250u64) * price_precision - StdUint256::from(1u64)
+    );
+    assert_eq!(
+        res[0],
+        max.wrapping_add(StdUint256::from(1000u64) * price_precision)
+    );
+}
+
+#[test]
+fn observe_cumulative_prices() {
+    let mut deps = mock_dependencies(&[]);
+    let mut config = Config {
+        pair_info: PairInfo {
+            asset_infos: [
+                AssetInfo::NativeToken {
+                    denom: "uusd".to_string(),
+                },
+                AssetInfo::Token {
+                    contract_addr: Addr::unchecked("asset0000"),
+                },
+            ],
+            contract_addr: Addr::unchecked("pair"),
+            liquidity_token: Addr::unchecked("lp_token"),
+            pair_type: PairType::Xyk {},
+        },
+        factory_addr: Addr::unchecked("factory"),
+        block_time_last: 0,
+        price0_cumulative_last: Uint128::zero(),
+        price1_cumulative_last: Uint128::zero(),
+    };
+    CONFIG.save(deps.as_mut().storage, &config).unwrap();
+
+    // Observations are written at 1000, 2000 and 3000 with the price of asset 0 equal to 2 between them
+    for (block_time, x, y) in [(1000, 0, 0), (2000, 100, 200), (3000, 100, 200)] {
+        update_cumulative_prices(
+            deps.as_mut().storage,
+            &mock_env_with_block_time(block_time),
+            &mut config,
+            Uint128::new(x),
+            Uint128::new(y),
+        )
+        .unwrap();
+    }
+    // The price of asset 0 is 0.5 after the last observation
+    RESERVES
+        .save(
+            deps.as_mut().storage,
+            &[Uint128::new(200), Uint128::new(100)],
+        )
+        .unwrap();
+
+    let price_precision = StdUint256::from(10u128.pow(TWAP_PRECISION_U256.into()));
+    let env = mock_env_with_block_time(4000);
+    let res: ObserveResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            env.clone(),
+            QueryMsg::Observe {
+                seconds_ago: vec![0, 500, 1500, 2500, 3000],
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    let prices: Vec<(u64, StdUint256, StdUint256)> = res
+        .observations
+        .into_iter()
+        .map(|o| (o.timestamp, o.price0_cumulative, o.price1_cumulative))
+        .collect();
+    let expected: Vec<(u64, StdUint256, StdUint256)> = [
+        (4000, 4500, 3000), // Extrapolated using the current reserves
+        (3500, 4250, 2000),
+        (2500, 3000, 750), // Interpolated between the observations
+        (1500, 1000, 250),
+        (1000, 0, 0),
+    ]
+    .iter()
+    .map(|&(time, price0, price1)| {
+        (
+            time,
+            StdUint256::from(price0 as u64) * price_precision,
+            StdUint256::from(price1 as u64) * price_precision,
+        )
+    })
+    .collect();
+    assert_eq!(prices, expected);
+
+    let err = query(
+        deps.as_ref(),
+        env.clone(),
+        QueryMsg::Observe {
+            seconds_ago: vec![3001],
+        },
+    )
+    .unwrap_err();
+    assert_eq!(
+        err,
+        StdError::generic_err("The observation time is before the oldest stored observation")
+    );
+
+    // Only the factory owner can change the number of stored observations
+    let msg = ExecuteMsg::UpdateConfig {
+        params: to_binary(&XykPoolUpdateParams::SetObservationsSize { size: 1 }).unwrap(),
+    };
+    let err = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("addr0000", &[]),
+        msg.clone(),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    let err = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("owner", &[]),
+        ExecuteMsg::UpdateConfig {
+            params: to_binary(&XykPoolUpdateParams::SetObservationsSize { size: 0 }).unwrap(),
+        },
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::InvalidObservationsSize {});
+
+    execute(deps.as_mut(), env.clone(), mock_info("owner", &[]), msg).unwrap();
+
+    // Only the latest observation is kept
+    let err = query(
+        deps.as_ref(),
+        env.clone(),
+        QueryMsg::Observe {
+            seconds_ago: vec![1500],
+        },
+    )
+    .unwrap_err();
+    assert_eq!(
+        err,
+        StdError::generic_err("The observation time is before the oldest stored observation")
+    );
+    let res: ObserveResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            env,
+            QueryMsg::Observe {
+                seconds_ago: vec![1000],
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        res.observations[0].price0_cumulative,
+        StdUint256::from(4000u64) * price_precision
+    );
+}
+
+#[test]
+fn spot_price_impact_and_depth() {
+    let mut deps = mock_dependencies(&[]);
+    let uusd = AssetInfo::NativeToken {
+        denom: "uusd".to_string(),
+    };
+    let uluna = AssetInfo::NativeToken {
+        denom: "uluna".to_string(),
+    };
+    let config = Config {
+        pair_info: PairInfo {
+            asset_infos: [uusd.clone(), uluna.clone()],
+            contract_addr: Addr::unchecked("pair"),
+            liquidity_token: Addr::unchecked("lp_token"),
+            pair_type: PairType::Xyk {},
+        },
+        factory_addr: Addr::unchecked("factory"),
+        block_time_last: 0,
+        price0_cumulative_last: Uint128::zero(),
+        price1_cumulative_last: Uint128::zero(),
+    };
+    CONFIG.save(deps.as_mut().storage, &config).unwrap();
+    RESERVES
+        .save(
+            deps.as_mut().storage,
+            &[Uint128::new(1_000_000), Uint128::new(2_000_000)],
+        )
+        .unwrap();
+    FACTORY_PARAMS
+        .save(
+            deps.as_mut().storage,
+            &FactoryParams {
+                fee_info: FeeInfo {
+                    fee_address: None,
+                    total_fee_rate: Decimal::from_ratio(3u128, 1000u128),
+                    maker_fee_rate: Decimal::zero(),
+                    maker_fee_as_lp: false,
+                },
+                generator_address: None,
+                pair_status: PairStatus::Active,
+            },
+        )
+        .unwrap();
+    let env = mock_env();
+
+    let res: SpotPriceResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            env.clone(),
+            QueryMsg::SpotPrice {
+                asset_info: uusd.clone(),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(res.price, Decimal::from_ratio(2u128, 1u128));
+    let res: SpotPriceResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            env.clone(),
+            QueryMsg::SpotPrice {
+                asset_info: uluna.clone(),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(res.price, Decimal::from_ratio(1u128, 2u128));
+
+    // Swapping the whole offer pool returns half of the ask pool before the commission
+    let res: PriceImpactResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            env.clone(),
+            QueryMsg::PriceImpact {
+                offer_asset: Asset {
+                    info: uusd.clone(),
+                    amount: Uint128::new(1_000_000),
+                },
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        res,
+        PriceImpactResponse {
+            spot_price: Decimal::from_ratio(2u128, 1u128),
+            execution_price: Decimal::one(),
+            price_impact: Decimal::from_ratio(1u128, 2u128),
+        }
+    );
+
+    // The price drops by 19% when the offer pool grows by 1/0.9
+    let res: DepthResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            env.clone(),
+            QueryMsg::Depth {
+                asset_info: uusd.clone(),
+                price_change: Decimal::percent(19),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(res.offer_amount, Uint128::new(111_111));
+    let simulation: SimulationResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            env.clone(),
+            QueryMsg::Simulation {
+                offer_asset: Asset {
+                    info: uusd.clone(),
+                    amount: res.offer_amount,
+                },
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(res.return_amount, simulation.return_amount);
+
+    for price_change in [Decimal::zero(), Decimal::one()] {
+        let err = query(
+            deps.as_ref(),
+            env.clone(),
+            QueryMsg::Depth {
+                asset_info: uusd.clone(),
+                price_change,
+            },
+        )
+        .unwrap_err();
+        assert_eq!(
+            err,
+            StdError::generic_err("The price change must be between 0 and 1")
+        );
+    }
+
+    // Prices are adjusted for the token decimals
+    assert_eq!(
+        Decimal::from(
+            compute_price(
+                Uint128::new(1_000_000),
+                6,
+                Uint128::new(2 * 10u128.pow(18)),
+                18
+            )
+            .unwrap()
+        ),
+        Decimal::from_ratio(2u128, 1u128)
+    );
+    assert_eq!(
+        compute_depth(Uint128::new(1_000_000_000), Decimal::percent(75)).unwrap(),
+        Uint128::new(1_000_000_000)
+    );
+}
+
+#[test]
+fn simulate_provide_and_withdraw() {
+    let total_share = Uint128::new(20000000000u128);
+    let asset_pool_amount = Uint128::new(20000000000u128);
+    let collateral_pool_amount = Uint128::new(30000000000u128);
+    let offer_amount = Uint128::new(1500000000u128);
+    let provide_amount = Uint128::new(1000000000u128);
+    let withdraw_amount = Uint128::new(1000000000u128);
+
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: collateral_pool_amount + offer_amount,
+    }]);
+    deps.querier.with_token_balances(&[
+        (
+            &String::from("liquidity0000"),
+            &[(&String::from("addr0000"), &total_share)],
+        ),
+        (
+            &String::from("asset0000"),
+            &[(&String::from(MOCK_CONTRACT_ADDR), &asset_pool_amount)],
+        ),
+    ]);
+    deps.querier.with_maker_fee_as_lp(true);
+
+    let uusd = AssetInfo::NativeToken {
+        denom: "uusd".to_string(),
+    };
+    let asset0000 = AssetInfo::Token {
+        contract_addr: Addr::unchecked("asset0000"),
+    };
+    let msg = InstantiateMsg {
+        asset_infos: [uusd.clone(), asset0000.clone()],
+        token_code_id: 10u64,
+        factory_addr: String::from("factory"),
+        init_params: None,
+    };
+    instantiate(deps.as_mut(), mock_env(), mock_info("addr0000", &[]), msg).unwrap();
+    store_liquidity_token(deps.as_mut(), 1, "liquidity0000".to_string());
+    store_reserves(deps.as_mut(), [collateral_pool_amount, asset_pool_amount]);
+
+    // Grow the pool, so the Maker fee is minted on the next liquidity event
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("anyone", &[]),
+        ExecuteMsg::RefreshFactoryParams {},
+    )
+    .unwrap();
+    let msg = ExecuteMsg::Swap {
+        offer_asset: Asset {
+            info: uusd.clone(),
+            amount: offer_amount,
+        },
+        belief_price: None,
+        max_spread: Some(Decimal::percent(50)),
+        to: None,
+        min_return: None,
+        deadline: None,
+    };
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: offer_amount,
+        }],
+    );
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    let reserves = RESERVES.load(deps.as_ref().storage).unwrap();
+
+    // Both pool assets must be deposited
+    let assets = [
+        Asset {
+            info: uusd.clone(),
+            amount: reserves[0].multiply_ratio(provide_amount, asset_pool_amount),
+        },
+        Asset {
+            info: asset0000.clone(),
+            amount: reserves[1].multiply_ratio(provide_amount, asset_pool_amount),
+        },
+    ];
+    let err = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::SimulateProvide {
+            assets: [
+                assets[0].clone(),
+                Asset {
+                    info: asset0000.clone(),
+                    amount: Uint128::zero(),
+                },
+            ],
+            slippage_tolerance: None,
+        },
+    )
+    .unwrap_err();
+    assert_eq!(
+        err,
+        StdError::generic_err(ContractError::InvalidZeroAmount {}.to_string())
+    );
+
+    // The slippage tolerance is checked the same way as in the provide
+    let err = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::SimulateProvide {
+            assets: [
+                assets[0].clone(),
+                Asset {
+                    info: asset0000.clone(),
+                    amount: assets[0].amount,
+                },
+            ],
+            slippage_tolerance: Some(Decimal::percent(1)),
+        },
+    )
+    .unwrap_err();
+    assert_eq!(
+        err,
+        StdError::generic_err(ContractError::MaxSlippageAssertion {}.to_string())
+    );
+
+    let simulation: SimulateProvideResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::SimulateProvide {
+                assets: assets.clone(),
+                slippage_tolerance: None,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert!(simulation.fees.iter().all(|fee| fee.amount.is_zero()));
+
+    let withdraw_simulation: Vec<Asset> = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::SimulateWithdraw {
+                lp_amount: withdraw_amount,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+
+    // The simulated share matches the share minted by the provide
+    let msg = ExecuteMsg::ProvideLiquidity {
+        assets: assets.clone(),
+        slippage_tolerance: None,
+        auto_stake: None,
+        receiver: None,
+        min_lp_to_receive: None,
+        deadline: None,
+    };
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: assets[0].amount,
+        }],
+    );
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert!(res
+        .attributes
+        .contains(&attr("share", simulation.share.to_string())));
+
+    // The Maker fee is already minted, so withdraw from the original pool state
+    store_reserves(deps.as_mut(), reserves);
+    ROOT_K_LAST
+        .save(
+            deps.as_mut().storage,
+            &Uint128::new(
+                (U256::from(collateral_pool_amount.u128()) * U256::from(asset_pool_amount.u128()))
+                    .integer_sqrt()
+                    .as_u128(),
+            ),
+        )
+        .unwrap();
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: String::from("addr0000"),
+        msg: to_binary(&Cw20HookMsg::WithdrawLiquidity {
+            min_assets_out: None,
+            deadline: None,
+        })
+        .unwrap(),
+        amount: withdraw_amount,
+    });
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("liquidity0000", &[]),
+        msg,
+    )
+    .unwrap();
+    let reserves_after = RESERVES.load(deps.as_ref().storage).unwrap();
+    assert_eq!(
+        withdraw_simulation[0].amount,
+        reserves[0] - reserves_after[0]
+    );
+    assert_eq!(
+        withdraw_simulation[1].amount,
+        reserves[1] - reserves_after[1]
+    );
+}
+
 fn mock_env_with_block_time(time: u64) -> Env {
     let mut env = mock_env();
     env.block = BlockInfo {
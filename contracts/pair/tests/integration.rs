@@ -4,11 +4,15 @@ use astroport::factory::{
     QueryMsg as FactoryQueryMsg,
 };
 use astroport::pair::{
-    CumulativePricesResponse, Cw20HookMsg, ExecuteMsg, InstantiateMsg, QueryMsg, TWAP_PRECISION,
+    CumulativePricesResponse, Cw20HookMsg, ExecuteMsg, FlashLoanReceiverMsg, InstantiateMsg,
+    PoolResponse, QueryMsg, MINIMUM_LIQUIDITY, TWAP_PRECISION,
 };
 use astroport::token::InstantiateMsg as TokenInstantiateMsg;
 use cosmwasm_std::testing::{mock_env, MockApi, MockStorage};
-use cosmwasm_std::{attr, to_binary, Addr, Coin, Decimal, QueryRequest, Uint128, WasmQuery};
+use cosmwasm_std::{
+    attr, from_binary, to_binary, Addr, Binary, Coin, Decimal, Deps, DepsMut, Empty, Env,
+    MessageInfo, QueryRequest, Response, StdResult, Uint128, WasmQuery,
+};
 use cw20::{BalanceResponse, Cw20Coin, Cw20ExecuteMsg, Cw20QueryMsg, MinterResponse};
 use terra_multi_test::{AppBuilder, BankKeeper, ContractWrapper, Executor, TerraApp, TerraMock};
 
@@ -70,6 +74,35 @@ fn instantiate_pair(mut router: &mut TerraApp, owner: &Addr) -> Addr {
     let token_contract_code_id = store_token_code(&mut router);
 
     let pair_contract_code_id = store_pair_code(&mut router);
+    let factory_code_id = store_factory_code(&mut router);
+
+    // The pair caches its fees from the factory at instantiation
+    let init_msg = FactoryInstantiateMsg {
+        fee_address: None,
+        pair_configs: vec![PairConfig {
+            code_id: pair_contract_code_id,
+            maker_fee_bps: 0,
+            pair_type: PairType::Xyk {},
+            total_fee_bps: 0,
+            is_disabled: false,
+            is_generator_disabled: false,
+            maker_fee_as_lp: false,
+        }],
+        token_code_id: token_contract_code_id,
+        generator_address: None,
+        owner: owner.to_string(),
+        whitelist_code_id: 234u64,
+    };
+    let factory = router
+        .instantiate_contract(
+            factory_code_id,
+            owner.clone(),
+            &init_msg,
+            &[],
+            "FACTORY",
+            None,
+        )
+        .unwrap();
 
     let msg = InstantiateMsg {
         asset_infos: [
@@ -81,7 +114,7 @@ fn instantiate_pair(mut router: &mut TerraApp, owner: &Addr) -> Addr {
             },
         ],
         token_code_id: token_contract_code_id,
-        factory_addr: String::from("factory"),
+        factory_addr: factory.to_string(),
         init_params: None,
     };
 
@@ -100,8 +133,8 @@ fn instantiate_pair(mut router: &mut TerraApp, owner: &Addr) -> Addr {
         .wrap()
         .query_wasm_smart(pair.clone(), &QueryMsg::Pair {})
         .unwrap();
-    assert_eq!("contract #0", res.contract_addr);
-    assert_eq!("contract #1", res.liquidity_token);
+    assert_eq!("contract #1", res.contract_addr);
+    assert_eq!("contract #2", res.liquidity_token);
 
     pair
 }
@@ -119,11 +152,11 @@ fn test_provide_and_withdraw_liquidity() {
             vec![
                 Coin {
                     denom: "uusd".to_string(),
-                    amount: Uint128::new(233u128),
+                    amount: Uint128::new(233_000u128),
                 },
                 Coin {
                     denom: "uluna".to_string(),
-                    amount: Uint128::new(200u128),
+                    amount: Uint128::new(200_000u128),
                 },
             ],
         )
@@ -157,18 +190,19 @@ fn test_provide_and_withdraw_liquidity() {
             vec![
                 Coin {
                     denom: "uusd".to_string(),
-                    amount: Uint128::new(100u128),
+                    amount: Uint128::new(100_000u128),
                 },
                 Coin {
                     denom: "uluna".to_string(),
-                    amount: Uint128::new(100u128),
+                    amount: Uint128::new(100_000u128),
                 },
             ],
         )
         .unwrap();
 
     // Provide liquidity
-    let (msg, coins) = provide_liquidity_msg(Uint128::new(100), Uint128::new(100), None, None);
+    let (msg, coins) =
+        provide_liquidity_msg(Uint128::new(100_000), Uint128::new(100_000), None, None);
     let res = router
         .execute_contract(alice_address.clone(), pair_instance.clone(), &msg, &coins)
         .unwrap();
@@ -180,20 +214,33 @@ fn test_provide_and_withdraw_liquidity() {
     assert_eq!(res.events[1].attributes[3], attr("receiver", "alice"),);
     assert_eq!(
         res.events[1].attributes[4],
-        attr("assets", "100uusd, 100uluna")
+        attr("assets", "100000uusd, 100000uluna")
     );
     assert_eq!(
         res.events[1].attributes[5],
-        attr("share", 100u128.to_string())
+        attr("share", 99_000u128.to_string())
     );
+    // The minimum liquidity is locked in the pair
     assert_eq!(res.events[3].attributes[1], attr("action", "mint"));
-    assert_eq!(res.events[3].attributes[2], attr("to", "alice"));
-    assert_eq!(res.events[3].attributes[3], attr("amount", 100.to_string()));
+    assert_eq!(
+        res.events[3].attributes[2],
+        attr("to", pair_instance.as_str())
+    );
+    assert_eq!(
+        res.events[3].attributes[3],
+        attr("amount", MINIMUM_LIQUIDITY.to_string())
+    );
+    assert_eq!(res.events[5].attributes[1], attr("action", "mint"));
+    assert_eq!(res.events[5].attributes[2], attr("to", "alice"));
+    assert_eq!(
+        res.events[5].attributes[3],
+        attr("amount", 99_000.to_string())
+    );
 
     // Provide liquidity for receiver
     let (msg, coins) = provide_liquidity_msg(
-        Uint128::new(100),
-        Uint128::new(100),
+        Uint128::new(100_000),
+        Uint128::new(100_000),
         Some("bob".to_string()),
         None,
     );
@@ -208,15 +255,18 @@ fn test_provide_and_withdraw_liquidity() {
     assert_eq!(res.events[1].attributes[3], attr("receiver", "bob"),);
     assert_eq!(
         res.events[1].attributes[4],
-        attr("assets", "100uusd, 100uluna")
+        attr("assets", "100000uusd, 100000uluna")
     );
     assert_eq!(
         res.events[1].attributes[5],
-        attr("share", 50u128.to_string())
+        attr("share", 100_000u128.to_string())
     );
     assert_eq!(res.events[3].attributes[1], attr("action", "mint"));
     assert_eq!(res.events[3].attributes[2], attr("to", "bob"));
-    assert_eq!(res.events[3].attributes[3], attr("amount", 50.to_string()));
+    assert_eq!(
+        res.events[3].attributes[3],
+        attr("amount", 100_000.to_string())
+    );
 }
 
 fn provide_liquidity_msg(
@@ -243,6 +293,8 @@ fn provide_liquidity_msg(
         slippage_tolerance: Option::from(slippage_tolerance),
         auto_stake: None,
         receiver,
+        min_lp_to_receive: None,
+        deadline: None,
     };
 
     let coins = [
@@ -259,6 +311,174 @@ fn provide_liquidity_msg(
     (msg, coins)
 }
 
+#[test]
+fn provide_liquidity_locks_minimum_liquidity() {
+    let owner = Addr::unchecked("owner");
+    let alice_address = Addr::unchecked("alice");
+    let mut router = mock_app();
+
+    router
+        .init_bank_balance(
+            &alice_address,
+            vec![
+                Coin {
+                    denom: "uusd".to_string(),
+                    amount: Uint128::new(10_000u128),
+                },
+                Coin {
+                    denom: "uluna".to_string(),
+                    amount: Uint128::new(10_000u128),
+                },
+            ],
+        )
+        .unwrap();
+
+    let pair_instance = instantiate_pair(&mut router, &owner);
+
+    // The first deposit must mint more LP tokens than the locked amount
+    let (msg, coins) = provide_liquidity_msg(Uint128::new(999), Uint128::new(999), None, None);
+    let err = router
+        .execute_contract(alice_address.clone(), pair_instance.clone(), &msg, &coins)
+        .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        format!("Initial liquidity must be more than {}", MINIMUM_LIQUIDITY)
+    );
+
+    let (msg, coins) = provide_liquidity_msg(Uint128::new(1000), Uint128::new(1000), None, None);
+    let err = router
+        .execute_contract(alice_address.clone(), pair_instance.clone(), &msg, &coins)
+        .unwrap_err();
+    assert_eq!(err.to_string(), "Event of zero transfer");
+
+    let (msg, coins) = provide_liquidity_msg(Uint128::new(2000), Uint128::new(2000), None, None);
+    router
+        .execute_contract(alice_address.clone(), pair_instance.clone(), &msg, &coins)
+        .unwrap();
+
+    let pair_info: PairInfo = router
+        .wrap()
+        .query_wasm_smart(&pair_instance, &QueryMsg::Pair {})
+        .unwrap();
+    let query_lp_balance = |router: &TerraApp, address: &Addr| -> Uint128 {
+        let res: BalanceResponse = router
+            .wrap()
+            .query_wasm_smart(
+                &pair_info.liquidity_token,
+                &Cw20QueryMsg::Balance {
+                    address: address.to_string(),
+                },
+            )
+            .unwrap();
+        res.balance
+    };
+    assert_eq!(
+        query_lp_balance(&router, &alice_address),
+        Uint128::new(1000)
+    );
+    assert_eq!(query_lp_balance(&router, &pair_instance), MINIMUM_LIQUIDITY);
+
+    // Withdraw all the LP tokens of the provider. The locked liquidity stays in the pool
+    let msg = Cw20ExecuteMsg::Send {
+        contract: pair_instance.to_string(),
+        amount: Uint128::new(1000),
+        msg: to_binary(&Cw20HookMsg::WithdrawLiquidity {
+            min_assets_out: None,
+            deadline: None,
+        })
+        .unwrap(),
+    };
+    router
+        .execute_contract(
+            alice_address.clone(),
+            pair_info.liquidity_token.clone(),
+            &msg,
+            &[],
+        )
+        .unwrap();
+
+    let pool: PoolResponse = router
+        .wrap()
+        .query_wasm_smart(&pair_instance, &QueryMsg::Pool {})
+        .unwrap();
+    assert_eq!(pool.total_share, MINIMUM_LIQUIDITY);
+    assert_eq!(pool.assets[0].amount, Uint128::new(1000));
+    assert_eq!(pool.assets[1].amount, Uint128::new(1000));
+}
+
+#[test]
+fn provide_liquidity_rejects_zero_share() {
+    let owner = Addr::unchecked("owner");
+    let attacker = Addr::unchecked("attacker");
+    let victim = Addr::unchecked("victim");
+    let mut router = mock_app();
+
+    for (address, amount) in [(&attacker, 2_000u128), (&victim, 5_000u128)] {
+        router
+            .init_bank_balance(
+                address,
+                vec![
+                    Coin {
+                        denom: "uusd".to_string(),
+                        amount: Uint128::new(amount),
+                    },
+                    Coin {
+                        denom: "uluna".to_string(),
+                        amount: Uint128::new(amount),
+                    },
+                ],
+            )
+            .unwrap();
+    }
+
+    let pair_instance = instantiate_pair(&mut router, &owner);
+
+    // The attacker provides the initial liquidity and inflates the share price with a donation
+    let (msg, coins) = provide_liquidity_msg(Uint128::new(2_000), Uint128::new(2_000), None, None);
+    router
+        .execute_contract(attacker.clone(), pair_instance.clone(), &msg, &coins)
+        .unwrap();
+
+    router
+        .init_bank_balance(
+            &pair_instance,
+            vec![
+                Coin {
+                    denom: "uusd".to_string(),
+                    amount: Uint128::new(10_002_000u128),
+                },
+                Coin {
+                    denom: "uluna".to_string(),
+                    amount: Uint128::new(10_002_000u128),
+                },
+            ],
+        )
+        .unwrap();
+    router
+        .execute_contract(
+            attacker.clone(),
+            pair_instance.clone(),
+            &ExecuteMsg::Sync {},
+            &[],
+        )
+        .unwrap();
+
+    // The victim's deposit is worth less than one LP token, so it is rejected instead of being
+    // absorbed by the existing shares
+    let (msg, coins) = provide_liquidity_msg(Uint128::new(5_000), Uint128::new(5_000), None, None);
+    let err = router
+        .execute_contract(victim.clone(), pair_instance.clone(), &msg, &coins)
+        .unwrap_err();
+    assert_eq!(err.to_string(), "Event of zero transfer");
+
+    let pool: PoolResponse = router
+        .wrap()
+        .query_wasm_smart(&pair_instance, &QueryMsg::Pool {})
+        .unwrap();
+    assert_eq!(pool.total_share, Uint128::new(2_000));
+    assert_eq!(pool.assets[0].amount, Uint128::new(10_002_000));
+}
+
 #[test]
 fn test_compatibility_of_tokens_with_different_precision() {
     let mut app = mock_app();
@@ -338,6 +558,7 @@ fn test_compatibility_of_tokens_with_different_precision() {
             total_fee_bps: 0,
             is_disabled: false,
             is_generator_disabled: false,
+            maker_fee_as_lp: false,
         }],
         token_code_id,
         generator_address: Some(String::from("generator")),
@@ -381,6 +602,7 @@ fn test_compatibility_of_tokens_with_different_precision() {
                 contract_addr: token_y_instance.clone(),
             },
         ],
+        pair_type: Some(PairType::Xyk {}),
     };
 
     let res: PairInfo = app
@@ -426,6 +648,8 @@ fn test_compatibility_of_tokens_with_different_precision() {
         slippage_tolerance: None,
         auto_stake: None,
         receiver: None,
+        min_lp_to_receive: None,
+        deadline: None,
     };
 
     app.execute_contract(owner.clone(), pair_instance.clone(), &msg, &[])
@@ -439,6 +663,8 @@ fn test_compatibility_of_tokens_with_different_precision() {
             belief_price: None,
             max_spread: None,
             to: Some(user.to_string()),
+            min_return: None,
+            deadline: None,
         })
         .unwrap(),
         amount: x_offer,
@@ -575,3 +801,235 @@ fn create_pair_with_same_assets() {
 
     assert_eq!(resp.to_string(), "Doubling assets in asset infos")
 }
+
+// A flash loan receiver that repays the loan with the fee if the callback message is `true`
+fn receiver_instantiate(
+    _deps: DepsMut,
+    _env: Env,
+    _info: MessageInfo,
+    _msg: Empty,
+) -> StdResult<Response> {
+    Ok(Response::new())
+}
+
+fn receiver_execute(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    msg: FlashLoanReceiverMsg,
+) -> StdResult<Response> {
+    let FlashLoanReceiverMsg::FlashLoanCallback { asset, fee, msg } = msg;
+    let repay: bool = from_binary(&msg)?;
+    if !repay {
+        return Ok(Response::new());
+    }
+
+    let repayment = Asset {
+        info: asset.info,
+        amount: asset.amount + fee,
+    };
+
+    Ok(Response::new().add_message(repayment.into_msg(&deps.querier, info.sender)?))
+}
+
+fn receiver_query(_deps: Deps, _env: Env, _msg: Empty) -> StdResult<Binary> {
+    to_binary(&Empty {})
+}
+
+#[test]
+fn flash_loan() {
+    let mut app = mock_app();
+    let owner = Addr::unchecked(OWNER);
+    let maker = Addr::unchecked("maker");
+
+    let token_code_id = store_token_code(&mut app);
+    let pool_amount = Uint128::new(1_000_000_000);
+    let loan_amount = Uint128::new(1_000_000);
+    // The fee is 0.3% of the loan and the Maker gets 16.6% of the fee
+    let fee_amount = Uint128::new(3_000);
+    let maker_fee_amount = Uint128::new(498);
+
+    let mut tokens = vec![];
+    for token_name in ["Xtoken", "Ytoken"] {
+        let init_msg = TokenInstantiateMsg {
+            name: token_name.to_string(),
+            symbol: token_name.to_string(),
+            decimals: 6,
+            initial_balances: vec![Cw20Coin {
+                address: OWNER.to_string(),
+                amount: pool_amount + fee_amount,
+            }],
+            mint: Some(MinterResponse {
+                minter: String::from(OWNER),
+                cap: None,
+            }),
+        };
+
+        tokens.push(
+            app.instantiate_contract(
+                token_code_id,
+                owner.clone(),
+                &init_msg,
+                &[],
+                token_name,
+                None,
+            )
+            .unwrap(),
+        );
+    }
+    let asset_infos = [
+        AssetInfo::Token {
+            contract_addr: tokens[0].clone(),
+        },
+        AssetInfo::Token {
+            contract_addr: tokens[1].clone(),
+        },
+    ];
+
+    let pair_code_id = store_pair_code(&mut app);
+    let factory_code_id = store_factory_code(&mut app);
+    let init_msg = FactoryInstantiateMsg {
+        fee_address: Some(maker.to_string()),
+        pair_configs: vec![PairConfig {
+            code_id: pair_code_id,
+            maker_fee_bps: 1660,
+            pair_type: PairType::Xyk {},
+            total_fee_bps: 30,
+            is_disabled: false,
+            is_generator_disabled: false,
+            maker_fee_as_lp: false,
+        }],
+        token_code_id,
+        generator_address: Some(String::from("generator")),
+        owner: owner.to_string(),
+        whitelist_code_id: 234u64,
+    };
+    let factory_instance = app
+        .instantiate_contract(
+            factory_code_id,
+            owner.clone(),
+            &init_msg,
+            &[],
+            "FACTORY",
+            None,
+        )
+        .unwrap();
+
+    let msg = FactoryExecuteMsg::CreatePair {
+        asset_infos: asset_infos.clone(),
+        pair_type: PairType::Xyk {},
+        init_params: None,
+    };
+    app.execute_contract(owner.clone(), factory_instance.clone(), &msg, &[])
+        .unwrap();
+
+    let res: PairInfo = app
+        .wrap()
+        .query_wasm_smart(
+            &factory_instance,
+            &FactoryQueryMsg::Pair {
+                asset_infos: asset_infos.clone(),
+                pair_type: Some(PairType::Xyk {}),
+            },
+        )
+        .unwrap();
+    let pair_instance = res.contract_addr;
+
+    for token in &tokens {
+        let msg = Cw20ExecuteMsg::IncreaseAllowance {
+            spender: pair_instance.to_string(),
+            expires: None,
+            amount: pool_amount,
+        };
+        app.execute_contract(owner.clone(), token.clone(), &msg, &[])
+            .unwrap();
+    }
+
+    let msg = ExecuteMsg::ProvideLiquidity {
+        assets: [
+            Asset {
+                info: asset_infos[0].clone(),
+                amount: pool_amount,
+            },
+            Asset {
+                info: asset_infos[1].clone(),
+                amount: pool_amount,
+            },
+        ],
+        slippage_tolerance: None,
+        auto_stake: None,
+        receiver: None,
+        min_lp_to_receive: None,
+        deadline: None,
+    };
+    app.execute_contract(owner.clone(), pair_instance.clone(), &msg, &[])
+        .unwrap();
+
+    // The receiver has enough tokens to pay the fee
+    let receiver_code_id = app.store_code(Box::new(ContractWrapper::new_with_empty(
+        receiver_execute,
+        receiver_instantiate,
+        receiver_query,
+    )));
+    let receiver = app
+        .instantiate_contract(
+            receiver_code_id,
+            owner.clone(),
+            &Empty {},
+            &[],
+            "RECEIVER",
+            None,
+        )
+        .unwrap();
+    let msg = Cw20ExecuteMsg::Transfer {
+        recipient: receiver.to_string(),
+        amount: fee_amount,
+    };
+    app.execute_contract(owner.clone(), tokens[0].clone(), &msg, &[])
+        .unwrap();
+
+    let flash_loan_msg = |repay: bool| ExecuteMsg::FlashLoan {
+        asset: Asset {
+            info: asset_infos[0].clone(),
+            amount: loan_amount,
+        },
+        receiver: Some(receiver.to_string()),
+        msg: to_binary(&repay).unwrap(),
+    };
+
+    // The whole transaction fails if the loan is not repaid
+    app.execute_contract(
+        owner.clone(),
+        pair_instance.clone(),
+        &flash_loan_msg(false),
+        &[],
+    )
+    .unwrap_err();
+
+    app.execute_contract(
+        owner.clone(),
+        pair_instance.clone(),
+        &flash_loan_msg(true),
+        &[],
+    )
+    .unwrap();
+
+    let query_balance = |token: &Addr, address: &Addr| -> Uint128 {
+        let res: BalanceResponse = app
+            .wrap()
+            .query_wasm_smart(
+                token,
+                &Cw20QueryMsg::Balance {
+                    address: address.to_string(),
+                },
+            )
+            .unwrap();
+        res.balance
+    };
+    assert_eq!(query_balance(&tokens[0], &receiver), Uint128::zero());
+    assert_eq!(query_balance(&tokens[0], &maker), maker_fee_amount);
+    assert_eq!(
+        query_balance(&tokens[0], &pair_instance),
+        pool_amount + fee_amount - maker_fee_amount
+    );
+}
@@ -0,0 +1,2148 @@
+use crate::error::ContractError;
+use crate::math::{
+    compute_swap_step, decimal_from_sqrt_price, get_amounts_for_liquidity,
+    get_liquidity_for_amounts, get_sqrt_price_at_tick, get_tick_at_sqrt_price, max_sqrt_price,
+    min_sqrt_price, mul_div, q128, q96, sqrt_price_from_decimal, wrapping_add, wrapping_sub,
+};
+use crate::state::{
+    position_id_from_key, tick_from_key, tick_key, Config, PoolState, Position, TickInfo, CONFIG,
+    FACTORY_PARAMS, LAST_POSITION_ID, OWNER_POSITIONS, POOL, POSITIONS, TICKS,
+};
+
+use cosmwasm_bignumber::Decimal256;
+use cosmwasm_std::{
+    attr, entry_point, from_binary, to_binary, Addr, Binary, CosmosMsg, Decimal, Deps, DepsMut,
+    Env, MessageInfo, Order, QuerierWrapper, Response, StdError, StdResult, Storage, Uint128,
+    Uint256, WasmMsg,
+};
+
+use astroport::asset::{addr_validate_to_lower, Asset, AssetInfo, PairInfo};
+use astroport::factory::PairType;
+use astroport::pair::{
+    ConfigResponse, CumulativePricesResponse, InstantiateMsg, MigrateMsg, PoolResponse,
+    ReverseSimulationResponse, SimulationResponse, DEFAULT_SLIPPAGE, MAX_ALLOWED_SLIPPAGE,
+    TWAP_PRECISION,
+};
+use astroport::pair_concentrated::{
+    ConcentratedPoolConfig, ConcentratedPoolParams, Cw20HookMsg, ExecuteMsg, PositionResponse,
+    PositionsResponse, QueryMsg, SimulateProvideResponse, MAX_TICK, MAX_TICK_SPACING, MIN_TICK,
+};
+use astroport::querier::query_factory_params;
+use cw2::set_contract_version;
+use cw20::{Cw20ExecuteMsg, Cw20ReceiveMsg};
+use cw_storage_plus::{Bound, U64Key};
+use std::convert::TryFrom;
+use std::str::FromStr;
+use std::vec;
+
+/// Contract name that is used for migration.
+const CONTRACT_NAME: &str = "astroport-pair-concentrated";
+/// Contract version that is used for migration.
+const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// The default amount of positions returned by the `positions_by_owner` query
+const DEFAULT_LIMIT: u32 = 10;
+/// The maximum amount of positions returned by the `positions_by_owner` query
+const MAX_LIMIT: u32 = 30;
+
+/// ## Description
+/// Creates a new contract with the specified parameters in the [`InstantiateMsg`].
+/// The pair doesn't create an LP token because liquidity positions are stored in the contract.
+/// Returns the [`Response`] with the specified attributes if the operation was successful, or a [`ContractError`] if
+/// the contract was not created.
+/// ## Params
+/// * **deps** is an object of type [`DepsMut`].
+///
+/// * **env** is an object of type [`Env`].
+///
+/// * **_info** is an object of type [`MessageInfo`].
+/// * **msg** is a message of type [`InstantiateMsg`] which contains the basic settings for creating a contract.
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn instantiate(
+    deps: DepsMut,
+    env: Env,
+    _info: MessageInfo,
+    msg: InstantiateMsg,
+) -> Result<Response, ContractError> {
+    msg.asset_infos[0].check(deps.api)?;
+    msg.asset_infos[1].check(deps.api)?;
+
+    if msg.asset_infos[0] == msg.asset_infos[1] {
+        return Err(ContractError::DoublingAssets {});
+    }
+
+    if msg.init_params.is_none() {
+        return Err(ContractError::InitParamsNotFound {});
+    }
+
+    let params: ConcentratedPoolParams = from_binary(&msg.init_params.unwrap())?;
+
+    if params.tick_spacing == 0 || params.tick_spacing > MAX_TICK_SPACING {
+        return Err(ContractError::InvalidTickSpacing {});
+    }
+
+    let sqrt_price = sqrt_price_from_decimal(params.initial_price)
+        .filter(|p| *p >= min_sqrt_price() && *p < max_sqrt_price())
+        .ok_or(ContractError::InvalidInitialPrice {})?;
+    let tick = get_tick_at_sqrt_price(sqrt_price).ok_or(ContractError::InvalidInitialPrice {})?;
+
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+
+    let config = Config {
+        pair_info: PairInfo {
+            contract_addr: env.contract.address.clone(),
+            liquidity_token: Addr::unchecked(""),
+            asset_infos: msg.asset_infos,
+            pair_type: PairType::Concentrated {},
+        },
+        factory_addr: addr_validate_to_lower(deps.api, msg.factory_addr.as_str())?,
+        tick_spacing: params.tick_spacing,
+        block_time_last: 0,
+        price0_cumulative_last: Uint128::zero(),
+        price1_cumulative_last: Uint128::zero(),
+    };
+
+    CONFIG.save(deps.storage, &config)?;
+    FACTORY_PARAMS.save(
+        deps.storage,
+        &query_factory_params(
+            &deps.querier,
+            config.factory_addr.clone(),
+            env.contract.address.clone(),
+            config.pair_info.pair_type.clone(),
+        )?,
+    )?;
+    POOL.save(
+        deps.storage,
+        &PoolState {
+            sqrt_price,
+            tick,
+            liquidity: Uint128::zero(),
+            fee_growth_global: [Uint256::zero(), Uint256::zero()],
+        },
+    )?;
+    LAST_POSITION_ID.save(deps.storage, &0)?;
+
+    Ok(Response::new())
+}
+
+/// ## Description
+/// Exposes all the execute functions available in the contract.
+/// ## Params
+/// * **deps** is an object of type [`Deps`].
+///
+/// * **env** is an object of type [`Env`].
+///
+/// * **info** is an object of type [`MessageInfo`].
+///
+/// * **msg** is an object of type [`ExecuteMsg`].
+///
+/// ## Queries
+/// * **ExecuteMsg::Receive(msg)** Receives a message of type [`Cw20ReceiveMsg`] and processes
+/// it depending on the received template.
+///
+/// * **ExecuteMsg::Swap {
+///             offer_asset,
+///             belief_price,
+///             max_spread,
+///             to,
+///             min_return,
+///             deadline,
+///         }** Performs a swap operation with the specified parameters.
+///
+/// * **ExecuteMsg::CreatePosition {
+///             lower_tick,
+///             upper_tick,
+///             assets,
+///             receiver,
+///             min_lp_to_receive,
+///             deadline,
+///         }** Creates a new liquidity position in the specified price range.
+///
+/// * **ExecuteMsg::IncreaseLiquidity {
+///             position_id,
+///             assets,
+///             min_lp_to_receive,
+///             deadline,
+///         }** Adds liquidity to a position.
+///
+/// * **ExecuteMsg::DecreaseLiquidity {
+///             position_id,
+///             liquidity,
+///             min_assets_out,
+///             deadline,
+///         }** Removes liquidity from a position.
+///
+/// * **ExecuteMsg::CollectFees { position_id }** Sends the fees earned by a position to its owner.
+///
+/// * **ExecuteMsg::TransferPosition { position_id, recipient }** Changes the owner of a position.
+///
+/// * **ExecuteMsg::RefreshFactoryParams {}** Updates the fee and Generator information cached from the factory.
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn execute(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: ExecuteMsg,
+) -> Result<Response, ContractError> {
+    match msg {
+        ExecuteMsg::Receive(msg) => receive_cw20(deps, env, info, msg),
+        ExecuteMsg::Swap {
+            offer_asset,
+            belief_price,
+            max_spread,
+            to,
+            min_return,
+            deadline,
+        } => {
+            offer_asset.info.check(deps.api)?;
+            if !offer_asset.is_native_token() {
+                return Err(ContractError::Unauthorized {});
+            }
+
+            let to_addr = if let Some(to_addr) = to {
+                Some(addr_validate_to_lower(deps.api, &to_addr)?)
+            } else {
+                None
+            };
+
+            swap(
+                deps,
+                env,
+                info.clone(),
+                info.sender,
+                offer_asset,
+                belief_price,
+                max_spread,
+                to_addr,
+                min_return,
+                deadline,
+            )
+        }
+        ExecuteMsg::CreatePosition {
+            lower_tick,
+            upper_tick,
+            assets,
+            receiver,
+            min_lp_to_receive,
+            deadline,
+        } => create_position(
+            deps,
+            env,
+            info,
+            lower_tick,
+            upper_tick,
+            assets,
+            receiver,
+            min_lp_to_receive,
+            deadline,
+        ),
+        ExecuteMsg::IncreaseLiquidity {
+            position_id,
+            assets,
+            min_lp_to_receive,
+            deadline,
+        } => increase_liquidity(
+            deps,
+            env,
+            info,
+            position_id,
+            assets,
+            min_lp_to_receive,
+            deadline,
+        ),
+        ExecuteMsg::DecreaseLiquidity {
+            position_id,
+            liquidity,
+            min_assets_out,
+            deadline,
+        } => decrease_liquidity(
+            deps,
+            env,
+            info,
+            position_id,
+            liquidity,
+            min_assets_out,
+            deadline,
+        ),
+        ExecuteMsg::CollectFees { position_id } => collect_fees(deps, info, position_id),
+        ExecuteMsg::TransferPosition {
+            position_id,
+            recipient,
+        } => transfer_position(deps, info, position_id, recipient),
+        ExecuteMsg::RefreshFactoryParams {} => refresh_factory_params(deps, env),
+    }
+}
+
+/// ## Description
+/// Receives a message of type [`Cw20ReceiveMsg`] and processes it depending on the received template.
+/// If the template is not found in the received message, then an [`ContractError`] is returned,
+/// otherwise it returns the [`Response`] with the specified attributes if the operation was successful.
+/// ## Params
+/// * **deps** is an object of type [`DepsMut`].
+///
+/// * **env** is an object of type [`Env`].
+///
+/// * **info** is an object of type [`MessageInfo`].
+///
+/// * **cw20_msg** is an object of type [`Cw20ReceiveMsg`]. This is the CW20 message that has to be processed.
+pub fn receive_cw20(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    cw20_msg: Cw20ReceiveMsg,
+) -> Result<Response, ContractError> {
+    let contract_addr = info.sender.clone();
+    match from_binary(&cw20_msg.msg) {
+        Ok(Cw20HookMsg::Swap {
+            belief_price,
+            max_spread,
+            to,
+            min_return,
+            deadline,
+        }) => {
+            // Only asset contract can execute this message
+            let mut authorized: bool = false;
+            let config: Config = CONFIG.load(deps.storage)?;
+
+            for pool in config.pair_info.asset_infos {
+                if let AssetInfo::Token { contract_addr, .. } = &pool {
+                    if contract_addr == &info.sender {
+                        authorized = true;
+                    }
+                }
+            }
+
+            if !authorized {
+                return Err(ContractError::Unauthorized {});
+            }
+
+            let to_addr = if let Some(to_addr) = to {
+                Some(addr_validate_to_lower(deps.api, to_addr.as_str())?)
+            } else {
+                None
+            };
+
+            swap(
+                deps,
+                env,
+                info,
+                Addr::unchecked(cw20_msg.sender),
+                Asset {
+                    info: AssetInfo::Token { contract_addr },
+                    amount: cw20_msg.amount,
+                },
+                belief_price,
+                max_spread,
+                to_addr,
+                min_return,
+                deadline,
+            )
+        }
+        Err(err) => Err(ContractError::Std(err)),
+    }
+}
+
+/// ## Description
+/// Creates a new liquidity position in the price range between `lower_tick` and `upper_tick`.
+/// The position gets the maximum liquidity that can be provided with the specified assets at the current price.
+/// Unused native assets are sent back and unused token assets are not pulled from the sender.
+/// Returns a [`ContractError`] on failure, otherwise returns a [`Response`] with the specified
+/// attributes if the operation was successful.
+/// ## Params
+/// * **deps** is an object of type [`DepsMut`].
+///
+/// * **env** is an object of type [`Env`].
+///
+/// * **info** is an object of type [`MessageInfo`].
+///
+/// * **lower_tick** is an object of type [`i32`]. This is the lower tick of the position's price range.
+///
+/// * **upper_tick** is an object of type [`i32`]. This is the upper tick of the position's price range.
+///
+/// * **assets** is an array with two objects of type [`Asset`]. These are the maximum amounts of assets to deposit.
+///
+/// * **receiver** is an [`Option`] field of type [`String`]. This is the owner of the new position.
+/// If no custom receiver is specified, the position is owned by the sender.
+///
+/// * **min_lp_to_receive** is an [`Option`] field of type [`Uint128`]. This is the minimum amount of
+/// liquidity that must be added to the position, otherwise the transaction fails.
+///
+/// * **deadline** is an [`Option`] field of type [`u64`]. This is the block time (in seconds) after which
+/// the transaction fails.
+// NOTE - the address that provides liquidity should approve the pair contract to pull its relevant tokens.
+#[allow(clippy::too_many_arguments)]
+pub fn create_position(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    lower_tick: i32,
+    upper_tick: i32,
+    assets: [Asset; 2],
+    receiver: Option<String>,
+    min_lp_to_receive: Option<Uint128>,
+    deadline: Option<u64>,
+) -> Result<Response, ContractError> {
+    assert_deadline(&env, deadline)?;
+    assert_deposits_allowed(deps.storage)?;
+    let config: Config = CONFIG.load(deps.storage)?;
+    assert_tick_range(&config, lower_tick, upper_tick)?;
+
+    let max_amounts = deposit_amounts(deps.api, &config, &info, &assets)?;
+
+    let owner = if let Some(receiver) = receiver {
+        addr_validate_to_lower(deps.api, &receiver)?
+    } else {
+        info.sender.clone()
+    };
+
+    let position_id = LAST_POSITION_ID.load(deps.storage)? + 1;
+    LAST_POSITION_ID.save(deps.storage, &position_id)?;
+
+    let mut pool: PoolState = POOL.load(deps.storage)?;
+    let mut position = Position {
+        owner: owner.clone(),
+        lower_tick,
+        upper_tick,
+        liquidity: Uint128::zero(),
+        fee_growth_inside_last: [Uint256::zero(), Uint256::zero()],
+        tokens_owed: [Uint128::zero(), Uint128::zero()],
+    };
+
+    let (liquidity, amounts) = add_liquidity(deps.storage, &mut pool, &mut position, max_amounts)?;
+    assert_min_lp_to_receive(liquidity, min_lp_to_receive)?;
+
+    POSITIONS.save(deps.storage, U64Key::new(position_id), &position)?;
+    OWNER_POSITIONS.save(deps.storage, (&owner, U64Key::new(position_id)), &true)?;
+    POOL.save(deps.storage, &pool)?;
+
+    let messages = deposit_messages(&deps.querier, &env, &info, &config, amounts, max_amounts)?;
+
+    Ok(Response::new().add_messages(messages).add_attributes(vec![
+        attr("action", "create_position"),
+        attr("sender", info.sender.as_str()),
+        attr("owner", owner.as_str()),
+        attr("position_id", position_id.to_string()),
+        attr("lower_tick", lower_tick.to_string()),
+        attr("upper_tick", upper_tick.to_string()),
+        attr("liquidity", liquidity.to_string()),
+        attr(
+            "assets",
+            format!(
+                "{}{}, {}{}",
+                amounts[0],
+                config.pair_info.asset_infos[0],
+                amounts[1],
+                config.pair_info.asset_infos[1]
+            ),
+        ),
+    ]))
+}
+
+/// ## Description
+/// Adds liquidity to an existing position. Only the position owner can execute this.
+/// Unused native assets are sent back and unused token assets are not pulled from the sender.
+/// Returns a [`ContractError`] on failure, otherwise returns a [`Response`] with the specified
+/// attributes if the operation was successful.
+/// ## Params
+/// * **deps** is an object of type [`DepsMut`].
+///
+/// * **env** is an object of type [`Env`].
+///
+/// * **info** is an object of type [`MessageInfo`].
+///
+/// * **position_id** is an object of type [`u64`]. This is the position identifier.
+///
+/// * **assets** is an array with two objects of type [`Asset`]. These are the maximum amounts of assets to deposit.
+///
+/// * **min_lp_to_receive** is an [`Option`] field of type [`Uint128`]. This is the minimum amount of
+/// liquidity that must be added to the position, otherwise the transaction fails.
+///
+/// * **deadline** is an [`Option`] field of type [`u64`]. This is the block time (in seconds) after which
+/// the transaction fails.
+pub fn increase_liquidity(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    position_id: u64,
+    assets: [Asset; 2],
+    min_lp_to_receive: Option<Uint128>,
+    deadline: Option<u64>,
+) -> Result<Response, ContractError> {
+    assert_deadline(&env, deadline)?;
+    assert_deposits_allowed(deps.storage)?;
+    let config: Config = CONFIG.load(deps.storage)?;
+    let mut position = POSITIONS.load(deps.storage, U64Key::new(position_id))?;
+
+    if info.sender != position.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let max_amounts = deposit_amounts(deps.api, &config, &info, &assets)?;
+
+    let mut pool: PoolState = POOL.load(deps.storage)?;
+    let (liquidity, amounts) = add_liquidity(deps.storage, &mut pool, &mut position, max_amounts)?;
+    assert_min_lp_to_receive(liquidity, min_lp_to_receive)?;
+
+    POSITIONS.save(deps.storage, U64Key::new(position_id), &position)?;
+    POOL.save(deps.storage, &pool)?;
+
+    let messages = deposit_messages(&deps.querier, &env, &info, &config, amounts, max_amounts)?;
+
+    Ok(Response::new().add_messages(messages).add_attributes(vec![
+        attr("action", "increase_liquidity"),
+        attr("sender", info.sender.as_str()),
+        attr("position_id", position_id.to_string()),
+        attr("liquidity", liquidity.to_string()),
+        attr(
+            "assets",
+            format!(
+                "{}{}, {}{}",
+                amounts[0],
+                config.pair_info.asset_infos[0],
+                amounts[1],
+                config.pair_info.asset_infos[1]
+            ),
+        ),
+    ]))
+}
+
+/// ## Description
+/// Removes liquidity from a position and sends the withdrawn assets together with all fees earned
+/// by the position to the position owner. The position is removed when all of its liquidity is withdrawn.
+/// Returns a [`ContractError`] on failure, otherwise returns a [`Response`] with the specified
+/// attributes if the operation was successful.
+/// ## Params
+/// * **deps** is an object of type [`DepsMut`].
+///
+/// * **env** is an object of type [`Env`].
+///
+/// * **info** is an object of type [`MessageInfo`].
+///
+/// * **position_id** is an object of type [`u64`]. This is the position identifier.
+///
+/// * **liquidity** is an object of type [`Uint128`]. This is the amount of liquidity to remove.
+///
+/// * **min_assets_out** is an [`Option`] field of type [`Vec<Asset>`]. These are the minimum amounts of assets
+/// to withdraw (the collected fees excluded), otherwise the transaction fails.
+///
+/// * **deadline** is an [`Option`] field of type [`u64`]. This is the block time (in seconds) after which
+/// the transaction fails.
+pub fn decrease_liquidity(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    position_id: u64,
+    liquidity: Uint128,
+    min_assets_out: Option<Vec<Asset>>,
+    deadline: Option<u64>,
+) -> Result<Response, ContractError> {
+    assert_deadline(&env, deadline)?;
+
+    let config: Config = CONFIG.load(deps.storage)?;
+    let mut position = POSITIONS.load(deps.storage, U64Key::new(position_id))?;
+
+    if info.sender != position.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let mut pool: PoolState = POOL.load(deps.storage)?;
+    let amounts = get_withdrawn_amounts(&pool, &position, liquidity)?;
+    assert_min_assets_out(&config, amounts, min_assets_out)?;
+
+    update_position(deps.storage, &mut pool, &mut position, liquidity, false)?;
+
+    let fees = position.tokens_owed;
+    position.tokens_owed = [Uint128::zero(), Uint128::zero()];
+
+    if position.liquidity.is_zero() {
+        POSITIONS.remove(deps.storage, U64Key::new(position_id));
+        OWNER_POSITIONS.remove(deps.storage, (&position.owner, U64Key::new(position_id)));
+    } else {
+        POSITIONS.save(deps.storage, U64Key::new(position_id), &position)?;
+    }
+    POOL.save(deps.storage, &pool)?;
+
+    let messages = payout_messages(
+        &deps.querier,
+        &config,
+        [amounts[0] + fees[0], amounts[1] + fees[1]],
+        &position.owner,
+    )?;
+
+    Ok(Response::new().add_messages(messages).add_attributes(vec![
+        attr("action", "decrease_liquidity"),
+        attr("sender", info.sender.as_str()),
+        attr("position_id", position_id.to_string()),
+        attr("liquidity", liquidity.to_string()),
+        attr(
+            "withdrawn_assets",
+            format!(
+                "{}{}, {}{}",
+                amounts[0],
+                config.pair_info.asset_infos[0],
+                amounts[1],
+                config.pair_info.asset_infos[1]
+            ),
+        ),
+        attr(
+            "fees",
+            format!(
+                "{}{}, {}{}",
+                fees[0], config.pair_info.asset_infos[0], fees[1], config.pair_info.asset_infos[1]
+            ),
+        ),
+    ]))
+}
+
+/// ## Description
+/// Sends all fees earned by a position to the position owner. Only the position owner can execute this.
+/// Returns a [`ContractError`] on failure, otherwise returns a [`Response`] with the specified
+/// attributes if the operation was successful.
+/// ## Params
+/// * **deps** is an object of type [`DepsMut`].
+///
+/// * **info** is an object of type [`MessageInfo`].
+///
+/// * **position_id** is an object of type [`u64`]. This is the position identifier.
+pub fn collect_fees(
+    deps: DepsMut,
+    info: MessageInfo,
+    position_id: u64,
+) -> Result<Response, ContractError> {
+    let config: Config = CONFIG.load(deps.storage)?;
+    let mut position = POSITIONS.load(deps.storage, U64Key::new(position_id))?;
+
+    if info.sender != position.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    // Update the fees owed to the position without changing its liquidity
+    let mut pool: PoolState = POOL.load(deps.storage)?;
+    update_position(
+        deps.storage,
+        &mut pool,
+        &mut position,
+        Uint128::zero(),
+        true,
+    )?;
+
+    let fees = position.tokens_owed;
+    position.tokens_owed = [Uint128::zero(), Uint128::zero()];
+    POSITIONS.save(deps.storage, U64Key::new(position_id), &position)?;
+
+    let messages = payout_messages(&deps.querier, &config, fees, &position.owner)?;
+
+    Ok(Response::new().add_messages(messages).add_attributes(vec![
+        attr("action", "collect_fees"),
+        attr("sender", info.sender.as_str()),
+        attr("position_id", position_id.to_string()),
+        attr(
+            "fees",
+            format!(
+                "{}{}, {}{}",
+                fees[0], config.pair_info.asset_infos[0], fees[1], config.pair_info.asset_infos[1]
+            ),
+        ),
+    ]))
+}
+
+/// ## Description
+/// Changes the owner of a position. Only the position owner can execute this.
+/// Returns a [`ContractError`] on failure, otherwise returns a [`Response`] with the specified
+/// attributes if the operation was successful.
+/// ## Params
+/// * **deps** is an object of type [`DepsMut`].
+///
+/// * **info** is an object of type [`MessageInfo`].
+///
+/// * **position_id** is an object of type [`u64`]. This is the position identifier.
+///
+/// * **recipient** is an object of type [`String`]. This is the new owner of the position.
+pub fn transfer_position(
+    deps: DepsMut,
+    info: MessageInfo,
+    position_id: u64,
+    recipient: String,
+) -> Result<Response, ContractError> {
+    let mut position = POSITIONS.load(deps.storage, U64Key::new(position_id))?;
+
+    if info.sender != position.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let recipient = addr_validate_to_lower(deps.api, &recipient)?;
+
+    OWNER_POSITIONS.remove(deps.storage, (&position.owner, U64Key::new(position_id)));
+    OWNER_POSITIONS.save(deps.storage, (&recipient, U64Key::new(position_id)), &true)?;
+
+    position.owner = recipient.clone();
+    POSITIONS.save(deps.storage, U64Key::new(position_id), &position)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "transfer_position"),
+        attr("sender", info.sender.as_str()),
+        attr("recipient", recipient.as_str()),
+        attr("position_id", position_id.to_string()),
+    ]))
+}
+
+/// ## Description
+/// Returns the maximum amounts of assets to deposit in the same order as the pool assets.
+/// The native assets must be sent together with the message.
+/// ## Params
+/// * **api** is an object of type [`cosmwasm_std::Api`].
+///
+/// * **config** is an object of type [`Config`].
+///
+/// * **info** is an object of type [`MessageInfo`].
+///
+/// * **assets** is an array with two objects of type [`Asset`]. These are the assets to deposit.
+fn deposit_amounts(
+    api: &dyn cosmwasm_std::Api,
+    config: &Config,
+    info: &MessageInfo,
+    assets: &[Asset; 2],
+) -> Result<[Uint128; 2], ContractError> {
+    assets[0].info.check(api)?;
+    assets[1].info.check(api)?;
+
+    for asset in assets.iter() {
+        asset.assert_sent_native_token_balance(info)?;
+    }
+
+    get_deposits(config, assets)
+}
+
+/// ## Description
+/// Returns the amounts of assets to deposit in the same order as the pool assets.
+/// ## Params
+/// * **config** is an object of type [`Config`].
+///
+/// * **assets** is an array with two objects of type [`Asset`]. These are the assets to deposit.
+fn get_deposits(config: &Config, assets: &[Asset; 2]) -> Result<[Uint128; 2], ContractError> {
+    let mut amounts = [Uint128::zero(), Uint128::zero()];
+    for (i, asset_info) in config.pair_info.asset_infos.iter().enumerate() {
+        amounts[i] = assets
+            .iter()
+            .find(|a| a.info.equal(asset_info))
+            .map(|a| a.amount)
+            .ok_or(ContractError::AssetMismatch {})?;
+    }
+
+    Ok(amounts)
+}
+
+/// ## Description
+/// Returns a [`ContractError`] if the price range of a new position is invalid.
+/// ## Params
+/// * **config** is an object of type [`Config`].
+///
+/// * **lower_tick** is an object of type [`i32`]. This is the lower tick of the position's price range.
+///
+/// * **upper_tick** is an object of type [`i32`]. This is the upper tick of the position's price range.
+fn assert_tick_range(
+    config: &Config,
+    lower_tick: i32,
+    upper_tick: i32,
+) -> Result<(), ContractError> {
+    if lower_tick >= upper_tick
+        || lower_tick < MIN_TICK
+        || upper_tick > MAX_TICK
+        || lower_tick % config.tick_spacing as i32 != 0
+        || upper_tick % config.tick_spacing as i32 != 0
+    {
+        return Err(ContractError::InvalidTickRange {});
+    }
+
+    Ok(())
+}
+
+/// ## Description
+/// Returns the messages that pull the deposited token assets from the sender and send back the unused native assets.
+/// ## Params
+/// * **querier** is an object of type [`QuerierWrapper`].
+///
+/// * **env** is an object of type [`Env`].
+///
+/// * **info** is an object of type [`MessageInfo`].
+///
+/// * **config** is an object of type [`Config`].
+///
+/// * **amounts** is an array with two objects of type [`Uint128`]. These are the deposited amounts.
+///
+/// * **max_amounts** is an array with two objects of type [`Uint128`]. These are the amounts specified by the sender.
+fn deposit_messages(
+    querier: &QuerierWrapper,
+    env: &Env,
+    info: &MessageInfo,
+    config: &Config,
+    amounts: [Uint128; 2],
+    max_amounts: [Uint128; 2],
+) -> Result<Vec<CosmosMsg>, ContractError> {
+    let mut messages: Vec<CosmosMsg> = vec![];
+    for (i, asset_info) in config.pair_info.asset_infos.iter().enumerate() {
+        if let AssetInfo::Token { contract_addr } = asset_info {
+            // If the asset is a token contract, then we need to execute a TransferFrom msg to receive assets
+            if !amounts[i].is_zero() {
+                messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
+                    contract_addr: contract_addr.to_string(),
+                    msg: to_binary(&Cw20ExecuteMsg::TransferFrom {
+                        owner: info.sender.to_string(),
+                        recipient: env.contract.address.to_string(),
+                        amount: amounts[i],
+                    })?,
+                    funds: vec![],
+                }));
+            }
+        } else {
+            // The native assets are already sent, so the unused part is sent back
+            let refund_amount = max_amounts[i].checked_sub(amounts[i])?;
+            if !refund_amount.is_zero() {
+                let refund = Asset {
+                    info: asset_info.clone(),
+                    amount: refund_amount,
+                };
+                messages.push(refund.into_msg(querier, info.sender.clone())?);
+            }
+        }
+    }
+
+    Ok(messages)
+}
+
+/// ## Description
+/// Returns the messages that send the specified amounts of pool assets to a receiver.
+/// ## Params
+/// * **querier** is an object of type [`QuerierWrapper`].
+///
+/// * **config** is an object of type [`Config`].
+///
+/// * **amounts** is an array with two objects of type [`Uint128`]. These are the amounts to send.
+///
+/// * **receiver** is an object of type [`Addr`]. This is the receiver of the assets.
+fn payout_messages(
+    querier: &QuerierWrapper,
+    config: &Config,
+    amounts: [Uint128; 2],
+    receiver: &Addr,
+) -> StdResult<Vec<CosmosMsg>> {
+    let mut messages: Vec<CosmosMsg> = vec![];
+    for (i, asset_info) in config.pair_info.asset_infos.iter().enumerate() {
+        if !amounts[i].is_zero() {
+            let asset = Asset {
+                info: asset_info.clone(),
+                amount: amounts[i],
+            };
+            messages.push(asset.into_msg(querier, receiver.clone())?);
+        }
+    }
+
+    Ok(messages)
+}
+
+/// ## Description
+/// Adds the maximum liquidity that can be provided with the specified amounts to a position.
+/// Returns the added liquidity and the amounts of assets that must be deposited for it.
+/// ## Params
+/// * **storage** is an object of type [`Storage`].
+///
+/// * **pool** is an object of type [`PoolState`]. This is the current pool state.
+///
+/// * **position** is an object of type [`Position`]. This is the position to update.
+///
+/// * **max_amounts** is an array with two objects of type [`Uint128`]. These are the available amounts of assets.
+fn add_liquidity(
+    storage: &mut dyn Storage,
+    pool: &mut PoolState,
+    position: &mut Position,
+    max_amounts: [Uint128; 2],
+) -> Result<(Uint128, [Uint128; 2]), ContractError> {
+    // No MINIMUM_LIQUIDITY is locked: liquidity is held in non-fungible positions, not LP shares that could be inflated
+    let (liquidity, amounts) =
+        get_liquidity_for_deposit(pool, position.lower_tick, position.upper_tick, max_amounts)?;
+
+    update_position(storage, pool, position, liquidity, true)?;
+
+    Ok((liquidity, amounts))
+}
+
+/// ## Description
+/// Returns the maximum liquidity that can be provided with the specified amounts in a price range
+/// together with the amounts of assets that must be deposited for it.
+/// ## Params
+/// * **pool** is an object of type [`PoolState`]. This is the current pool state.
+///
+/// * **lower_tick** is an object of type [`i32`]. This is the lower tick of the price range.
+///
+/// * **upper_tick** is an object of type [`i32`]. This is the upper tick of the price range.
+///
+/// * **max_amounts** is an array with two objects of type [`Uint128`]. These are the available amounts of assets.
+fn get_liquidity_for_deposit(
+    pool: &PoolState,
+    lower_tick: i32,
+    upper_tick: i32,
+    max_amounts: [Uint128; 2],
+) -> Result<(Uint128, [Uint128; 2]), ContractError> {
+    let sqrt_price_lower =
+        get_sqrt_price_at_tick(lower_tick).ok_or(ContractError::MathOverflow {})?;
+    let sqrt_price_upper =
+        get_sqrt_price_at_tick(upper_tick).ok_or(ContractError::MathOverflow {})?;
+
+    let liquidity = get_liquidity_for_amounts(
+        pool.sqrt_price,
+        sqrt_price_lower,
+        sqrt_price_upper,
+        max_amounts,
+    )
+    .ok_or(ContractError::MathOverflow {})?;
+
+    if liquidity.is_zero() {
+        return Err(ContractError::InvalidZeroAmount {});
+    }
+
+    // The deposited amounts are rounded up in favor of the pool
+    let amounts = get_amounts_for_liquidity(
+        pool.sqrt_price,
+        sqrt_price_lower,
+        sqrt_price_upper,
+        liquidity,
+        true,
+    )
+    .ok_or(ContractError::MathOverflow {})?;
+
+    Ok((liquidity, amounts))
+}
+
+/// ## Description
+/// Returns the amounts of assets withdrawn for removing liquidity from a position.
+/// The fees earned by the position are not included.
+/// ## Params
+/// * **pool** is an object of type [`PoolState`]. This is the current pool state.
+///
+/// * **position** is an object of type [`Position`]. This is the position to withdraw from.
+///
+/// * **liquidity** is an object of type [`Uint128`]. This is the amount of liquidity to remove.
+fn get_withdrawn_amounts(
+    pool: &PoolState,
+    position: &Position,
+    liquidity: Uint128,
+) -> Result<[Uint128; 2], ContractError> {
+    if liquidity.is_zero() {
+        return Err(ContractError::InvalidZeroAmount {});
+    }
+
+    if liquidity > position.liquidity {
+        return Err(ContractError::InsufficientPositionLiquidity {});
+    }
+
+    get_amounts_for_liquidity(
+        pool.sqrt_price,
+        get_sqrt_price_at_tick(position.lower_tick).ok_or(ContractError::MathOverflow {})?,
+        get_sqrt_price_at_tick(position.upper_tick).ok_or(ContractError::MathOverflow {})?,
+        liquidity,
+        false,
+    )
+    .ok_or(ContractError::MathOverflow {})
+}
+
+/// ## Description
+/// Adds liquidity to or removes liquidity from a position. The position ticks, the fees owed to the position
+/// and the liquidity in range are updated accordingly.
+/// ## Params
+/// * **storage** is an object of type [`Storage`].
+///
+/// * **pool** is an object of type [`PoolState`]. This is the current pool state.
+///
+/// * **position** is an object of type [`Position`]. This is the position to update.
+///
+/// * **liquidity_delta** is an object of type [`Uint128`]. This is the amount of liquidity to add or remove.
+///
+/// * **add** is an object of type [`bool`]. Whether the liquidity is added or removed.
+fn update_position(
+    storage: &mut dyn Storage,
+    pool: &mut PoolState,
+    position: &mut Position,
+    liquidity_delta: Uint128,
+    add: bool,
+) -> Result<(), ContractError> {
+    let mut lower = TICKS
+        .may_load(storage, tick_key(position.lower_tick))?
+        .unwrap_or_default();
+    let mut upper = TICKS
+        .may_load(storage, tick_key(position.upper_tick))?
+        .unwrap_or_default();
+
+    // By convention, all fees are assumed to be earned below a tick when it's initialized
+    for (tick, tick_info) in [
+        (position.lower_tick, &mut lower),
+        (position.upper_tick, &mut upper),
+    ] {
+        if tick_info.liquidity_gross().is_zero() && tick <= pool.tick {
+            tick_info.fee_growth_outside = pool.fee_growth_global;
+        }
+    }
+
+    if add {
+        lower.liquidity_lower = lower.liquidity_lower.checked_add(liquidity_delta)?;
+        upper.liquidity_upper = upper.liquidity_upper.checked_add(liquidity_delta)?;
+    } else {
+        lower.liquidity_lower = lower.liquidity_lower.checked_sub(liquidity_delta)?;
+        upper.liquidity_upper = upper.liquidity_upper.checked_sub(liquidity_delta)?;
+    }
+
+    let fee_growth_inside = get_fee_growth_inside(pool, position, &lower, &upper);
+    position.tokens_owed = get_owed_fees(position, fee_growth_inside)?;
+    position.fee_growth_inside_last = fee_growth_inside;
+
+    if add {
+        position.liquidity = position.liquidity.checked_add(liquidity_delta)?;
+    } else {
+        position.liquidity = position.liquidity.checked_sub(liquidity_delta)?;
+    }
+
+    for (tick, tick_info) in [(position.lower_tick, lower), (position.upper_tick, upper)] {
+        if tick_info.liquidity_gross().is_zero() {
+            TICKS.remove(storage, tick_key(tick));
+        } else {
+            TICKS.save(storage, tick_key(tick), &tick_info)?;
+        }
+    }
+
+    // Only the liquidity of the positions that include the current price is in range
+    if position.lower_tick <= pool.tick && pool.tick < position.upper_tick {
+        if add {
+            pool.liquidity = pool.liquidity.checked_add(liquidity_delta)?;
+        } else {
+            pool.liquidity = pool.liquidity.checked_sub(liquidity_delta)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// ## Description
+/// Returns the fee growth per unit of liquidity inside the price range of a position for every asset.
+/// ## Params
+/// * **pool** is an object of type [`PoolState`]. This is the current pool state.
+///
+/// * **position** is an object of type [`Position`].
+///
+/// * **lower** is an object of type [`TickInfo`]. This is the lower tick of the position.
+///
+/// * **upper** is an object of type [`TickInfo`]. This is the upper tick of the position.
+fn get_fee_growth_inside(
+    pool: &PoolState,
+    position: &Position,
+    lower: &TickInfo,
+    upper: &TickInfo,
+) -> [Uint256; 2] {
+    let mut fee_growth_inside = [Uint256::zero(), Uint256::zero()];
+    for (i, fee_growth) in fee_growth_inside.iter_mut().enumerate() {
+        let global = pool.fee_growth_global[i];
+        let below = if pool.tick >= position.lower_tick {
+            lower.fee_growth_outside[i]
+        } else {
+            wrapping_sub(global, lower.fee_growth_outside[i])
+        };
+        let above = if pool.tick < position.upper_tick {
+            upper.fee_growth_outside[i]
+        } else {
+            wrapping_sub(global, upper.fee_growth_outside[i])
+        };
+
+        *fee_growth = wrapping_sub(wrapping_sub(global, below), above);
+    }
+
+    fee_growth_inside
+}
+
+/// ## Description
+/// Returns the total fees owed to a position given the current fee growth inside its price range.
+/// ## Params
+/// * **position** is an object of type [`Position`].
+///
+/// * **fee_growth_inside** is an array with two objects of type [`Uint256`]. This is the current fee growth inside the position's price range.
+fn get_owed_fees(
+    position: &Position,
+    fee_growth_inside: [Uint256; 2],
+) -> Result<[Uint128; 2], ContractError> {
+    let mut owed = position.tokens_owed;
+    for (i, tokens_owed) in owed.iter_mut().enumerate() {
+        let fees = mul_div(
+            position.liquidity.into(),
+            wrapping_sub(fee_growth_inside[i], position.fee_growth_inside_last[i]),
+            q128(),
+        )
+        .and_then(|fees| Uint128::try_from(fees).ok())
+        .ok_or(ContractError::MathOverflow {})?;
+        *tokens_owed = tokens_owed.checked_add(fees)?;
+    }
+
+    Ok(owed)
+}
+
+/// ## Description
+/// Performs an swap operation with the specified parameters. The swap crosses the initialized ticks
+/// and uses the liquidity that is in range at every step.
+/// The trader must approve the pool contract to transfer offer assets from their wallet.
+/// Returns an [`ContractError`] on failure, otherwise returns the [`Response`] with the specified attributes if the operation was successful.
+/// ## Params
+/// * **deps** is an object of type [`DepsMut`].
+///
+/// * **env** is an object of type [`Env`].
+///
+/// * **info** is an object of type [`MessageInfo`].
+///
+/// * **sender** is an object of type [`Addr`]. This is the sender of the swap operation.
+///
+/// * **offer_asset** is an object of type [`Asset`]. Proposed asset for swapping.
+///
+/// * **belief_price** is an object of type [`Option<Decimal>`]. Used to calculate the maximum swap spread.
+///
+/// * **max_spread** is an object of type [`Option<Decimal>`]. Sets the maximum spread of the swap operation.
+///
+/// * **to** is an object of type [`Option<Addr>`]. Sets the recipient of the swap operation.
+///
+/// * **min_return** is an object of type [`Option<Uint128>`]. This is the minimum amount of ask assets to receive.
+///
+/// * **deadline** is an object of type [`Option<u64>`]. This is the block time (in seconds) after which the swap fails.
+/// NOTE - the address that wants to swap should approve the pair contract to pull the offer token.
+#[allow(clippy::too_many_arguments)]
+pub fn swap(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    sender: Addr,
+    offer_asset: Asset,
+    belief_price: Option<Decimal>,
+    max_spread: Option<Decimal>,
+    to: Option<Addr>,
+    min_return: Option<Uint128>,
+    deadline: Option<u64>,
+) -> Result<Response, ContractError> {
+    assert_deadline(&env, deadline)?;
+    assert_swaps_allowed(deps.storage)?;
+    offer_asset.assert_sent_native_token_balance(&info)?;
+
+    let mut config: Config = CONFIG.load(deps.storage)?;
+    let pool: PoolState = POOL.load(deps.storage)?;
+
+    let (offer_index, ask_index) = if offer_asset.info.equal(&config.pair_info.asset_infos[0]) {
+        (0, 1)
+    } else if offer_asset.info.equal(&config.pair_info.asset_infos[1]) {
+        (1, 0)
+    } else {
+        return Err(ContractError::AssetMismatch {});
+    };
+    let ask_info = config.pair_info.asset_infos[ask_index].clone();
+
+    // Get the fee info cached from the factory
+    let fee_info = FACTORY_PARAMS.load(deps.storage)?.fee_info;
+
+    // The Maker fee is left to the liquidity providers if there is no fee address
+    let maker_fee_rate = if fee_info.fee_address.is_some() {
+        fee_info.maker_fee_rate
+    } else {
+        Decimal::zero()
+    };
+
+    let offer_amount = offer_asset.amount;
+    let result = compute_swap(
+        deps.storage,
+        &pool,
+        offer_index == 0,
+        offer_amount,
+        true,
+        fee_info.total_fee_rate,
+        maker_fee_rate,
+    )?;
+
+    let commission_amount = result.commission_amount;
+    let return_amount = result.amount_out.checked_sub(commission_amount)?;
+    let spread_amount = spot_amount(pool.sqrt_price, offer_amount, offer_index == 0)?
+        .saturating_sub(result.amount_out);
+
+    // Check the max spread limit (if it was specified)
+    assert_max_spread(
+        belief_price,
+        max_spread,
+        offer_amount,
+        return_amount + commission_amount,
+        spread_amount,
+    )?;
+
+    // Check the minimum return amount (if it was specified)
+    if let Some(min_return) = min_return {
+        if return_amount < min_return {
+            return Err(ContractError::MinReturnAssertion {});
+        }
+    }
+
+    // Compute the tax for the receiving asset (if it is a native one)
+    let return_asset = Asset {
+        info: ask_info.clone(),
+        amount: return_amount,
+    };
+
+    let tax_amount = return_asset.compute_tax(&deps.querier)?;
+    let receiver = to.unwrap_or_else(|| sender.clone());
+    let mut messages: Vec<CosmosMsg> =
+        vec![return_asset.into_msg(&deps.querier, receiver.clone())?];
+
+    let maker_fee_amount = result.maker_fee_amount;
+    if let Some(fee_address) = fee_info.fee_address {
+        if !maker_fee_amount.is_zero() {
+            let maker_fee = Asset {
+                info: ask_info.clone(),
+                amount: maker_fee_amount,
+            };
+            messages.push(maker_fee.into_msg(&deps.querier, fee_address)?);
+        }
+    }
+
+    // Accumulate prices for the assets in the pool
+    if let Some((price0_cumulative_new, price1_cumulative_new, block_time)) =
+        accumulate_prices(env, &config, pool.sqrt_price)?
+    {
+        config.price0_cumulative_last = price0_cumulative_new;
+        config.price1_cumulative_last = price1_cumulative_new;
+        config.block_time_last = block_time;
+        CONFIG.save(deps.storage, &config)?;
+    }
+
+    for (tick, tick_info) in result.crossed_ticks.iter() {
+        TICKS.save(deps.storage, tick_key(*tick), tick_info)?;
+    }
+    POOL.save(deps.storage, &result.pool)?;
+
+    Ok(Response::new()
+        .add_messages(
+            // 1. send collateral tokens from the contract to a user
+            // 2. send inactive commission fees to the Maker ontract
+            messages,
+        )
+        .add_attribute("action", "swap")
+        .add_attribute("sender", sender.as_str())
+        .add_attribute("receiver", receiver.as_str())
+        .add_attribute("offer_asset", offer_asset.info.to_string())
+        .add_attribute("ask_asset", ask_info.to_string())
+        .add_attribute("offer_amount", offer_amount.to_string())
+        .add_attribute("return_amount", return_amount.to_string())
+        .add_attribute("tax_amount", tax_amount.to_string())
+        .add_attribute("spread_amount", spread_amount.to_string())
+        .add_attribute("commission_amount", commission_amount.to_string())
+        .add_attribute("maker_fee_amount", maker_fee_amount.to_string()))
+}
+
+/// ## Description
+/// This structure describes the result of a swap computed by [`compute_swap`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct SwapResult {
+    /// The pool state after the swap
+    pub pool: PoolState,
+    /// The ticks crossed during the swap with their updated state
+    pub crossed_ticks: Vec<(i32, TickInfo)>,
+    /// The amount of offer assets taken by the swap
+    pub amount_in: Uint128,
+    /// The amount of ask assets returned by the swap before the commission is deducted
+    pub amount_out: Uint128,
+    /// The total commission charged in ask assets
+    pub commission_amount: Uint128,
+    /// The part of the commission that goes to the Maker contract
+    pub maker_fee_amount: Uint128,
+}
+
+/// ## Description
+/// Computes a swap without changing the contract state. The price moves from one initialized tick to
+/// the next one until the specified amount is swapped. The commission is charged in ask assets and the part
+/// that doesn't go to the Maker contract is distributed to the liquidity that is in range at every step.
+/// Returns a [`ContractError::InsufficientLiquidity`] if the pool cannot swap the whole amount.
+/// ## Params
+/// * **storage** is an object of type [`Storage`].
+///
+/// * **pool** is an object of type [`PoolState`]. This is the current pool state.
+///
+/// * **zero_for_one** is an object of type [`bool`]. Whether asset 0 is swapped to asset 1 or vice versa.
+///
+/// * **amount** is an object of type [`Uint128`]. This is the offer amount if `exact_input` is true,
+/// otherwise this is the ask amount before the commission is deducted.
+///
+/// * **exact_input** is an object of type [`bool`]. Whether `amount` is an offer or an ask amount.
+///
+/// * **commission_rate** is an object of type [`Decimal`]. This is the total amount of fees charged for the swap.
+///
+/// * **maker_commission_rate** is an object of type [`Decimal`]. This is the percentage of fees that go to the Maker contract.
+pub fn compute_swap(
+    storage: &dyn Storage,
+    pool: &PoolState,
+    zero_for_one: bool,
+    amount: Uint128,
+    exact_input: bool,
+    commission_rate: Decimal,
+    maker_commission_rate: Decimal,
+) -> Result<SwapResult, ContractError> {
+    if amount.is_zero() {
+        return Err(ContractError::InvalidZeroAmount {});
+    }
+
+    let ask_index = if zero_for_one { 1 } else { 0 };
+    let mut state = pool.clone();
+    let mut crossed_ticks = vec![];
+    let mut amount_in = Uint128::zero();
+    let mut amount_out = Uint128::zero();
+    let mut total_commission_amount = Uint128::zero();
+    let mut total_maker_fee_amount = Uint128::zero();
+    let mut amount_remaining = Uint256::from(amount);
+
+    while !amount_remaining.is_zero() {
+        // If there is no initialized tick in the swap direction, there is no liquidity left either
+        let (next_tick, mut next_tick_info) =
+            next_initialized_tick(storage, state.tick, zero_for_one)?
+                .ok_or(ContractError::InsufficientLiquidity {})?;
+        let sqrt_price_target =
+            get_sqrt_price_at_tick(next_tick).ok_or(ContractError::MathOverflow {})?;
+
+        let step = compute_swap_step(
+            state.sqrt_price,
+            sqrt_price_target,
+            state.liquidity,
+            amount_remaining,
+            exact_input,
+        )
+        .ok_or(ContractError::MathOverflow {})?;
+
+        let step_amount_in =
+            Uint128::try_from(step.amount_in).map_err(|_| ContractError::MathOverflow {})?;
+        let step_amount_out =
+            Uint128::try_from(step.amount_out).map_err(|_| ContractError::MathOverflow {})?;
+        amount_remaining = amount_remaining.checked_sub(if exact_input {
+            step.amount_in
+        } else {
+            step.amount_out
+        })?;
+        amount_in = amount_in.checked_add(step_amount_in)?;
+        amount_out = amount_out.checked_add(step_amount_out)?;
+
+        // The commission (minus the Maker fee) is distributed to the liquidity that is in range
+        let commission_amount = step_amount_out * commission_rate;
+        let maker_fee_amount = commission_amount * maker_commission_rate;
+        total_commission_amount += commission_amount;
+        total_maker_fee_amount += maker_fee_amount;
+        if !state.liquidity.is_zero() {
+            let fee_growth = mul_div(
+                (commission_amount - maker_fee_amount).into(),
+                q128(),
+                state.liquidity.into(),
+            )
+            .ok_or(ContractError::MathOverflow {})?;
+            state.fee_growth_global[ask_index] =
+                wrapping_add(state.fee_growth_global[ask_index], fee_growth);
+        }
+
+        state.sqrt_price = step.sqrt_price_next;
+        if step.sqrt_price_next != sqrt_price_target {
+            // The whole amount is swapped before the next tick
+            state.tick =
+                get_tick_at_sqrt_price(state.sqrt_price).ok_or(ContractError::MathOverflow {})?;
+            break;
+        }
+
+        // Cross the tick: flip the fee growth outside and update the liquidity in range
+        for (i, fee_growth_outside) in next_tick_info.fee_growth_outside.iter_mut().enumerate() {
+            *fee_growth_outside = wrapping_sub(state.fee_growth_global[i], *fee_growth_outside);
+        }
+
+        if zero_for_one {
+            state.liquidity = state
+                .liquidity
+                .checked_sub(next_tick_info.liquidity_lower)?
+                .checked_add(next_tick_info.liquidity_upper)?;
+            state.tick = next_tick - 1;
+        } else {
+            state.liquidity = state
+                .liquidity
+                .checked_add(next_tick_info.liquidity_lower)?
+                .checked_sub(next_tick_info.liquidity_upper)?;
+            state.tick = next_tick;
+        }
+
+        crossed_ticks.push((next_tick, next_tick_info));
+    }
+
+    Ok(SwapResult {
+        pool: state,
+        crossed_ticks,
+        amount_in,
+        amount_out,
+        commission_amount: total_commission_amount,
+        maker_fee_amount: total_maker_fee_amount,
+    })
+}
+
+/// ## Description
+/// Returns the next initialized tick in the swap direction together with its state.
+/// When swapping asset 0 to asset 1, the current tick is included in the search.
+/// ## Params
+/// * **storage** is an object of type [`Storage`].
+///
+/// * **tick** is an object of type [`i32`]. This is the current tick.
+///
+/// * **zero_for_one** is an object of type [`bool`]. Whether the price moves down or up.
+fn next_initialized_tick(
+    storage: &dyn Storage,
+    tick: i32,
+    zero_for_one: bool,
+) -> StdResult<Option<(i32, TickInfo)>> {
+    let item = if zero_for_one {
+        if tick < MIN_TICK {
+            return Ok(None);
+        }
+
+        TICKS
+            .range(
+                storage,
+                None,
+                Some(Bound::inclusive(tick_key(tick))),
+                Order::Descending,
+            )
+            .next()
+    } else {
+        TICKS
+            .range(
+                storage,
+                Some(Bound::exclusive(tick_key(tick))),
+                None,
+                Order::Ascending,
+            )
+            .next()
+    };
+
+    item.map(|item| item.map(|(key, tick_info)| (tick_from_key(&key), tick_info)))
+        .transpose()
+}
+
+/// ## Description
+/// Returns the amount of ask assets that would be received for an offer amount at the current price.
+/// ## Params
+/// * **sqrt_price** is an object of type [`Uint256`]. This is the current square root price.
+///
+/// * **offer_amount** is an object of type [`Uint128`]. This is the amount of offer assets.
+///
+/// * **zero_for_one** is an object of type [`bool`]. Whether asset 0 is swapped to asset 1 or vice versa.
+fn spot_amount(
+    sqrt_price: Uint256,
+    offer_amount: Uint128,
+    zero_for_one: bool,
+) -> Result<Uint128, ContractError> {
+    let amount = if zero_for_one {
+        mul_div(offer_amount.into(), sqrt_price, q96())
+            .and_then(|amount| mul_div(amount, sqrt_price, q96()))
+    } else {
+        mul_div(offer_amount.into(), q96(), sqrt_price)
+            .and_then(|amount| mul_div(amount, q96(), sqrt_price))
+    }
+    .ok_or(ContractError::MathOverflow {})?;
+
+    Ok(Uint128::try_from(amount).unwrap_or(Uint128::MAX))
+}
+
+/// ## Description
+/// Accumulate token prices for the assets in the pool. The prices are derived from the current square root price.
+/// ## Params
+/// * **env** is an object of type [`Env`].
+///
+/// * **config** is an object of type [`Config`].
+///
+/// * **sqrt_price** is an object of type [`Uint256`]. This is the current square root price.
+pub fn accumulate_prices(
+    env: Env,
+    config: &Config,
+    sqrt_price: Uint256,
+) -> StdResult<Option<(Uint128, Uint128, u64)>> {
+    let block_time = env.block.time.seconds();
+    if block_time <= config.block_time_last {
+        return Ok(None);
+    }
+
+    let time_elapsed = Uint128::from(block_time - config.block_time_last);
+    let price_precision = Uint128::from(10u128.pow(TWAP_PRECISION.into()));
+    let scaled_time = Uint256::from(time_elapsed.checked_mul(price_precision)?);
+
+    // price0 = sqrt_price^2 / 2^192 and price1 = 2^192 / sqrt_price^2
+    let price0 = mul_div(scaled_time, sqrt_price, q96())
+        .and_then(|price| mul_div(price, sqrt_price, q96()))
+        .and_then(|price| Uint128::try_from(price).ok());
+    let price1 = mul_div(scaled_time, q96(), sqrt_price)
+        .and_then(|price| mul_div(price, q96(), sqrt_price))
+        .and_then(|price| Uint128::try_from(price).ok());
+
+    match (price0, price1) {
+        (Some(price0), Some(price1)) => Ok(Some((
+            config.price0_cumulative_last.wrapping_add(price0),
+            config.price1_cumulative_last.wrapping_add(price1),
+            block_time,
+        ))),
+        _ => Err(StdError::generic_err("Price accumulator overflow")),
+    }
+}
+
+/// ## Description
+/// Exposes all the queries available in the contract.
+/// ## Params
+/// * **deps** is an object of type [`Deps`].
+///
+/// * **env** is an object of type [`Env`].
+///
+/// * **msg** is an object of type [`QueryMsg`].
+///
+/// ## Queries
+/// * **QueryMsg::Pair {}** Returns information about the pair in an object of type [`PairInfo`].
+///
+/// * **QueryMsg::Pool {}** Returns the amounts of assets in the pair contract as well as the liquidity
+/// that is currently in range using an object of type [`PoolResponse`].
+///
+/// * **QueryMsg::Config {}** Returns the configuration for the pair contract using a [`ConfigResponse`] object.
+///
+/// * **QueryMsg::Simulation { offer_asset }** Returns the result of a swap simulation using a [`SimulationResponse`] object.
+///
+/// * **QueryMsg::ReverseSimulation { ask_asset }** Returns the result of a reverse swap simulation  using
+/// a [`ReverseSimulationResponse`] object.
+///
+/// * **QueryMsg::CumulativePrices {}** Returns information about cumulative prices for the assets in the
+/// pool using a [`CumulativePricesResponse`] object.
+///
+/// * **QueryMsg::SimulateProvide {
+///             lower_tick,
+///             upper_tick,
+///             assets,
+///         }** Returns the liquidity added to a position for a deposit using a [`SimulateProvideResponse`] object.
+///
+/// * **QueryMsg::SimulateWithdraw {
+///             position_id,
+///             liquidity,
+///         }** Returns the assets withdrawn for removing liquidity from a position in a vector
+/// that contains objects of type [`Asset`].
+///
+/// * **QueryMsg::Position { position_id }** Returns information about a position using a [`PositionResponse`] object.
+///
+/// * **QueryMsg::PositionsByOwner {
+///             owner,
+///             start_after,
+///             limit,
+///         }** Returns the positions of an owner using a [`PositionsResponse`] object.
+///
+/// * **QueryMsg::PairStatus {}** Returns the trading state of the pair set in the factory using a [`astroport::factory::PairStatus`] object.
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
+    match msg {
+        QueryMsg::Pair {} => to_binary(&query_pair_info(deps)?),
+        QueryMsg::Pool {} => to_binary(&query_pool(deps)?),
+        QueryMsg::Config {} => to_binary(&query_config(deps)?),
+        QueryMsg::Simulation { offer_asset } => to_binary(&query_simulation(deps, offer_asset)?),
+        QueryMsg::ReverseSimulation { ask_asset } => {
+            to_binary(&query_reverse_simulation(deps, ask_asset)?)
+        }
+        QueryMsg::CumulativePrices {} => to_binary(&query_cumulative_prices(deps, env)?),
+        QueryMsg::SimulateProvide {
+            lower_tick,
+            upper_tick,
+            assets,
+        } => to_binary(&query_simulate_provide(
+            deps, lower_tick, upper_tick, assets,
+        )?),
+        QueryMsg::SimulateWithdraw {
+            position_id,
+            liquidity,
+        } => to_binary(&query_simulate_withdraw(deps, position_id, liquidity)?),
+        QueryMsg::Position { position_id } => to_binary(&query_position(deps, position_id)?),
+        QueryMsg::PositionsByOwner {
+            owner,
+            start_after,
+            limit,
+        } => to_binary(&query_positions_by_owner(deps, owner, start_after, limit)?),
+        QueryMsg::PairStatus {} => to_binary(&FACTORY_PARAMS.load(deps.storage)?.pair_status),
+    }
+}
+
+/// ## Description
+/// Returns information about the pair contract in an object of type [`PairInfo`].
+/// ## Params
+/// * **deps** is an object of type [`Deps`].
+pub fn query_pair_info(deps: Deps) -> StdResult<PairInfo> {
+    let config: Config = CONFIG.load(deps.storage)?;
+    Ok(config.pair_info)
+}
+
+/// ## Description
+/// Returns the amounts of assets in the pair contract as well as the liquidity that is
+/// currently in range in an object of type [`PoolResponse`].
+/// ## Params
+/// * **deps** is an object of type [`Deps`].
+pub fn query_pool(deps: Deps) -> StdResult<PoolResponse> {
+    let config: Config = CONFIG.load(deps.storage)?;
+    let (assets, total_share) = pool_info(deps, config)?;
+
+    let resp = PoolResponse {
+        assets,
+        total_share,
+    };
+
+    Ok(resp)
+}
+
+/// ## Description
+/// Returns information about a swap simulation in a [`SimulationResponse`] object.
+/// ## Params
+/// * **deps** is an object of type [`Deps`].
+///
+/// * **offer_asset** is an object of type [`Asset`]. This is the asset to swap as well as an amount of the said asset.
+pub fn query_simulation(deps: Deps, offer_asset: Asset) -> StdResult<SimulationResponse> {
+    let config: Config = CONFIG.load(deps.storage)?;
+    let pool: PoolState = POOL.load(deps.storage)?;
+
+    let zero_for_one = if offer_asset.info.equal(&config.pair_info.asset_infos[0]) {
+        true
+    } else if offer_asset.info.equal(&config.pair_info.asset_infos[1]) {
+        false
+    } else {
+        return Err(StdError::generic_err(
+            "Given offer asset does not belong in the pair",
+        ));
+    };
+
+    // Get the fee info cached from the factory
+    let fee_info = FACTORY_PARAMS.load(deps.storage)?.fee_info;
+
+    let result = compute_swap(
+        deps.storage,
+        &pool,
+        zero_for_one,
+        offer_asset.amount,
+        true,
+        fee_info.total_fee_rate,
+        fee_info.maker_fee_rate,
+    )
+    .map_err(|e| StdError::generic_err(e.to_string()))?;
+
+    let spread_amount = spot_amount(pool.sqrt_price, offer_asset.amount, zero_for_one)
+        .map_err(|e| StdError::generic_err(e.to_string()))?
+        .saturating_sub(result.amount_out);
+
+    Ok(SimulationResponse {
+        return_amount: result.amount_out.checked_sub(result.commission_amount)?,
+        spread_amount,
+        commission_amount: result.commission_amount,
+    })
+}
+
+/// ## Description
+/// Returns information about a reverse swap simulation in a [`ReverseSimulationResponse`] object.
+/// ## Params
+/// * **deps** is an object of type [`Deps`].
+///
+/// * **ask_asset** is an object of type [`Asset`]. This is the asset to swap to as well as the desired
+/// amount of ask assets to receive from the swap.
+pub fn query_reverse_simulation(
+    deps: Deps,
+    ask_asset: Asset,
+) -> StdResult<ReverseSimulationResponse> {
+    let config: Config = CONFIG.load(deps.storage)?;
+    let pool: PoolState = POOL.load(deps.storage)?;
+
+    let zero_for_one = if ask_asset.info.equal(&config.pair_info.asset_infos[1]) {
+        true
+    } else if ask_asset.info.equal(&config.pair_info.asset_infos[0]) {
+        false
+    } else {
+        return Err(StdError::generic_err(
+            "Given ask asset doesn't belong to pairs",
+        ));
+    };
+
+    // Get the fee info cached from the factory
+    let fee_info = FACTORY_PARAMS.load(deps.storage)?.fee_info;
+
+    let one_minus_commission = Decimal256::one() - Decimal256::from(fee_info.total_fee_rate);
+    let inv_one_minus_commission: Decimal = (Decimal256::one() / one_minus_commission).into();
+    let before_commission_deduction = ask_asset.amount * inv_one_minus_commission;
+
+    let result = compute_swap(
+        deps.storage,
+        &pool,
+        zero_for_one,
+        before_commission_deduction,
+        false,
+        fee_info.total_fee_rate,
+        fee_info.maker_fee_rate,
+    )
+    .map_err(|e| StdError::generic_err(e.to_string()))?;
+
+    let spread_amount = spot_amount(pool.sqrt_price, result.amount_in, zero_for_one)
+        .map_err(|e| StdError::generic_err(e.to_string()))?
+        .saturating_sub(result.amount_out);
+
+    Ok(ReverseSimulationResponse {
+        offer_amount: result.amount_in,
+        spread_amount,
+        commission_amount: result.commission_amount,
+    })
+}
+
+/// ## Description
+/// Returns information about cumulative prices for the assets in the pool using a [`CumulativePricesResponse`] object.
+/// ## Params
+/// * **deps** is an object of type [`Deps`].
+///
+/// * **env** is an object of type [`Env`].
+pub fn query_cumulative_prices(deps: Deps, env: Env) -> StdResult<CumulativePricesResponse> {
+    let config: Config = CONFIG.load(deps.storage)?;
+    let pool: PoolState = POOL.load(deps.storage)?;
+    let (assets, total_share) = pool_info(deps, config.clone())?;
+
+    let mut price0_cumulative_last = config.price0_cumulative_last;
+    let mut price1_cumulative_last = config.price1_cumulative_last;
+
+    if let Some((price0_cumulative_new, price1_cumulative_new, _)) =
+        accumulate_prices(env, &config, pool.sqrt_price)?
+    {
+        price0_cumulative_last = price0_cumulative_new;
+        price1_cumulative_last = price1_cumulative_new;
+    }
+
+    let resp = CumulativePricesResponse {
+        assets,
+        total_share,
+        price0_cumulative_last,
+        price1_cumulative_last,
+        price0_cumulative_last_u256: None,
+        price1_cumulative_last_u256: None,
+    };
+
+    Ok(resp)
+}
+
+/// ## Description
+/// Returns the liquidity added to a position for a deposit in a [`SimulateProvideResponse`] object.
+/// The liquidity and the deposited amounts are calculated in the same way as in [`create_position`]
+/// and [`increase_liquidity`].
+/// ## Params
+/// * **deps** is an object of type [`Deps`].
+///
+/// * **lower_tick** is an object of type [`i32`]. This is the lower tick of the position's price range.
+///
+/// * **upper_tick** is an object of type [`i32`]. This is the upper tick of the position's price range.
+///
+/// * **assets** is an array with two objects of type [`Asset`]. These are the maximum amounts of assets to deposit.
+pub fn query_simulate_provide(
+    deps: Deps,
+    lower_tick: i32,
+    upper_tick: i32,
+    assets: [Asset; 2],
+) -> StdResult<SimulateProvideResponse> {
+    let config: Config = CONFIG.load(deps.storage)?;
+    let pool: PoolState = POOL.load(deps.storage)?;
+    assert_tick_range(&config, lower_tick, upper_tick)?;
+
+    let max_amounts = get_deposits(&config, &assets)?;
+    let (liquidity, amounts) =
+        get_liquidity_for_deposit(&pool, lower_tick, upper_tick, max_amounts)?;
+
+    let asset_infos = &config.pair_info.asset_infos;
+    Ok(SimulateProvideResponse {
+        liquidity,
+        assets: [
+            Asset {
+                info: asset_infos[0].clone(),
+                amount: amounts[0],
+            },
+            Asset {
+                info: asset_infos[1].clone(),
+                amount: amounts[1],
+            },
+        ],
+    })
+}
+
+/// ## Description
+/// Returns the assets withdrawn for removing liquidity from a position in a [`Vec<Asset>`] object.
+/// The amounts are calculated in the same way as in [`decrease_liquidity`]. The fees earned by the position
+/// are not included, they are returned by the `position` query.
+/// ## Params
+/// * **deps** is an object of type [`Deps`].
+///
+/// * **position_id** is an object of type [`u64`]. This is the position identifier.
+///
+/// * **liquidity** is an object of type [`Uint128`]. This is the amount of liquidity to remove.
+pub fn query_simulate_withdraw(
+    deps: Deps,
+    position_id: u64,
+    liquidity: Uint128,
+) -> StdResult<Vec<Asset>> {
+    let config: Config = CONFIG.load(deps.storage)?;
+    let pool: PoolState = POOL.load(deps.storage)?;
+    let position = POSITIONS.load(deps.storage, U64Key::new(position_id))?;
+
+    let amounts = get_withdrawn_amounts(&pool, &position, liquidity)?;
+
+    Ok(config
+        .pair_info
+        .asset_infos
+        .iter()
+        .zip(amounts.iter())
+        .map(|(info, amount)| Asset {
+            info: info.clone(),
+            amount: *amount,
+        })
+        .collect())
+}
+
+/// ## Description
+/// Returns the pair contract configuration in a [`ConfigResponse`] object.
+/// The current pool state is returned in a [`ConcentratedPoolConfig`] object serialized in `params`.
+/// ## Params
+/// * **deps** is an object of type [`Deps`].
+pub fn query_config(deps: Deps) -> StdResult<ConfigResponse> {
+    let config: Config = CONFIG.load(deps.storage)?;
+    let pool: PoolState = POOL.load(deps.storage)?;
+
+    Ok(ConfigResponse {
+        block_time_last: config.block_time_last,
+        params: Some(to_binary(&ConcentratedPoolConfig {
+            tick_spacing: config.tick_spacing,
+            price: decimal_from_sqrt_price(pool.sqrt_price)
+                .ok_or_else(|| StdError::generic_err("The price doesn't fit into a decimal"))?,
+            sqrt_price: pool.sqrt_price,
+            tick: pool.tick,
+            liquidity: pool.liquidity,
+        })?),
+    })
+}
+
+/// ## Description
+/// Returns information about a position in a [`PositionResponse`] object.
+/// ## Params
+/// * **deps** is an object of type [`Deps`].
+///
+/// * **position_id** is an object of type [`u64`]. This is the position identifier.
+pub fn query_position(deps: Deps, position_id: u64) -> StdResult<PositionResponse> {
+    let config: Config = CONFIG.load(deps.storage)?;
+    let pool: PoolState = POOL.load(deps.storage)?;
+
+    position_response(deps, &config, &pool, position_id)
+}
+
+/// ## Description
+/// Returns the positions of an owner in a [`PositionsResponse`] object. The positions are sorted by their identifiers.
+/// ## Params
+/// * **deps** is an object of type [`Deps`].
+///
+/// * **owner** is an object of type [`String`]. This is the position owner.
+///
+/// * **start_after** is an [`Option`] field of type [`u64`]. This is the position identifier from which the function starts to fetch results.
+///
+/// * **limit** is an [`Option`] field of type [`u32`]. This is the number of items to retreive.
+pub fn query_positions_by_owner(
+    deps: Deps,
+    owner: String,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<PositionsResponse> {
+    let config: Config = CONFIG.load(deps.storage)?;
+    let pool: PoolState = POOL.load(deps.storage)?;
+    let owner = addr_validate_to_lower(deps.api, &owner)?;
+
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(|id| Bound::exclusive(U64Key::new(id)));
+
+    let positions = OWNER_POSITIONS
+        .prefix(&owner)
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| {
+            let (key, _) = item?;
+            position_response(deps, &config, &pool, position_id_from_key(&key))
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+
+    Ok(PositionsResponse { positions })
+}
+
+/// ## Description
+/// Returns the amounts of assets and the uncollected fees of a position in a [`PositionResponse`] object.
+/// ## Params
+/// * **deps** is an object of type [`Deps`].
+///
+/// * **config** is an object of type [`Config`].
+///
+/// * **pool** is an object of type [`PoolState`]. This is the current pool state.
+///
+/// * **position_id** is an object of type [`u64`]. This is the position identifier.
+fn position_response(
+    deps: Deps,
+    config: &Config,
+    pool: &PoolState,
+    position_id: u64,
+) -> StdResult<PositionResponse> {
+    let position = POSITIONS.load(deps.storage, U64Key::new(position_id))?;
+    let lower = TICKS.load(deps.storage, tick_key(position.lower_tick))?;
+    let upper = TICKS.load(deps.storage, tick_key(position.upper_tick))?;
+
+    let fee_growth_inside = get_fee_growth_inside(pool, &position, &lower, &upper);
+    let fees = get_owed_fees(&position, fee_growth_inside)
+        .map_err(|e| StdError::generic_err(e.to_string()))?;
+    let amounts = get_sqrt_price_at_tick(position.lower_tick)
+        .zip(get_sqrt_price_at_tick(position.upper_tick))
+        .and_then(|(sqrt_price_lower, sqrt_price_upper)| {
+            get_amounts_for_liquidity(
+                pool.sqrt_price,
+                sqrt_price_lower,
+                sqrt_price_upper,
+                position.liquidity,
+                false,
+            )
+        })
+        .ok_or_else(|| StdError::generic_err(ContractError::MathOverflow {}.to_string()))?;
+
+    let asset_infos = &config.pair_info.asset_infos;
+    Ok(PositionResponse {
+        position_id,
+        owner: position.owner,
+        lower_tick: position.lower_tick,
+        upper_tick: position.upper_tick,
+        liquidity: position.liquidity,
+        assets: [
+            Asset {
+                info: asset_infos[0].clone(),
+                amount: amounts[0],
+            },
+            Asset {
+                info: asset_infos[1].clone(),
+                amount: amounts[1],
+            },
+        ],
+        fees: [
+            Asset {
+                info: asset_infos[0].clone(),
+                amount: fees[0],
+            },
+            Asset {
+                info: asset_infos[1].clone(),
+                amount: fees[1],
+            },
+        ],
+    })
+}
+
+/// ## Description
+/// Returns a [`ContractError`] if the factory paused swaps in the pair.
+/// ## Params
+/// * **storage** is a reference to an object implementing [`Storage`].
+fn assert_swaps_allowed(storage: &dyn Storage) -> Result<(), ContractError> {
+    if !FACTORY_PARAMS.load(storage)?.pair_status.swaps_allowed() {
+        return Err(ContractError::SwapsPaused {});
+    }
+
+    Ok(())
+}
+
+/// ## Description
+/// Returns a [`ContractError`] if the factory paused deposits in the pair.
+/// ## Params
+/// * **storage** is a reference to an object implementing [`Storage`].
+fn assert_deposits_allowed(storage: &dyn Storage) -> Result<(), ContractError> {
+    if !FACTORY_PARAMS.load(storage)?.pair_status.deposits_allowed() {
+        return Err(ContractError::DepositsPaused {});
+    }
+
+    Ok(())
+}
+
+/// ## Description
+/// Returns a [`ContractError`] on failure.
+/// If `belief_price` and `max_spread` are both specified, we compute a new spread,
+/// otherwise we just use the swap spread to check `max_spread`.
+/// ## Params
+/// * **belief_price** is an object of type [`Option<Decimal>`]. This is the belief price used in the swap.
+///
+/// * **max_spread** is an object of type [`Option<Decimal>`]. This is the
+/// max spread allowed so that the swap can be executed successfuly.
+///
+/// * **offer_amount** is an object of type [`Uint128`]. This is the amount of assets to swap.
+///
+/// * **return_amount** is an object of type [`Uint128`]. This is the amount of assets to receive from the swap.
+///
+/// * **spread_amount** is an object of type [`Uint128`]. This is the spread used in the swap.
+pub fn assert_max_spread(
+    belief_price: Option<Decimal>,
+    max_spread: Option<Decimal>,
+    offer_amount: Uint128,
+    return_amount: Uint128,
+    spread_amount: Uint128,
+) -> Result<(), ContractError> {
+    let default_spread = Decimal::from_str(DEFAULT_SLIPPAGE)?;
+    let max_allowed_spread = Decimal::from_str(MAX_ALLOWED_SLIPPAGE)?;
+
+    let max_spread = max_spread.unwrap_or(default_spread);
+    if max_spread.gt(&max_allowed_spread) {
+        return Err(ContractError::AllowedSpreadAssertion {});
+    }
+
+    if let Some(belief_price) = belief_price {
+        let expected_return =
+            offer_amount * Decimal::from(Decimal256::one() / Decimal256::from(belief_price));
+        let spread_amount = expected_return
+            .checked_sub(return_amount)
+            .unwrap_or_else(|_| Uint128::zero());
+
+        if return_amount < expected_return
+            && Decimal::from_ratio(spread_amount, expected_return) > max_spread
+        {
+            return Err(ContractError::MaxSpreadAssertion {});
+        }
+    } else if Decimal::from_ratio(spread_amount, return_amount + spread_amount) > max_spread {
+        return Err(ContractError::MaxSpreadAssertion {});
+    }
+
+    Ok(())
+}
+
+/// ## Description
+/// Returns a [`ContractError`] on failure if the current block time is past the specified deadline.
+/// ## Params
+/// * **env** is an object of type [`Env`].
+///
+/// * **deadline** is an [`Option`] field of type [`u64`]. This is the block time (in seconds) after which
+/// the operation fails.
+pub fn assert_deadline(env: &Env, deadline: Option<u64>) -> Result<(), ContractError> {
+    if let Some(deadline) = deadline {
+        if env.block.time.seconds() > deadline {
+            return Err(ContractError::DeadlineExpired {});
+        }
+    }
+
+    Ok(())
+}
+
+/// ## Description
+/// Returns a [`ContractError`] on failure if less than the specified minimum liquidity was added to a position.
+/// ## Params
+/// * **liquidity** is an object of type [`Uint128`]. This is the liquidity added to the position.
+///
+/// * **min_lp_to_receive** is an [`Option`] field of type [`Uint128`]. This is the minimum amount of
+/// liquidity to add.
+fn assert_min_lp_to_receive(
+    liquidity: Uint128,
+    min_lp_to_receive: Option<Uint128>,
+) -> Result<(), ContractError> {
+    if let Some(min_lp_to_receive) = min_lp_to_receive {
+        if liquidity < min_lp_to_receive {
+            return Err(ContractError::MinLpToReceiveAssertion {});
+        }
+    }
+
+    Ok(())
+}
+
+/// ## Description
+/// Returns a [`ContractError`] on failure if the amount of any withdrawn asset is less than the specified minimum.
+/// ## Params
+/// * **config** is an object of type [`Config`].
+///
+/// * **amounts** is an array with two items of type [`Uint128`]. These are the amounts withdrawn from the
+/// position in the order of the pool assets.
+///
+/// * **min_assets_out** is an [`Option`] field of type [`Vec<Asset>`]. These are the minimum amounts of
+/// assets to receive.
+fn assert_min_assets_out(
+    config: &Config,
+    amounts: [Uint128; 2],
+    min_assets_out: Option<Vec<Asset>>,
+) -> Result<(), ContractError> {
+    for min_asset in min_assets_out.unwrap_or_default() {
+        let amount = config
+            .pair_info
+            .asset_infos
+            .iter()
+            .zip(amounts.iter())
+            .find(|(info, _)| info.equal(&min_asset.info))
+            .map(|(_, amount)| *amount)
+            .ok_or(ContractError::AssetMismatch {})?;
+        if amount < min_asset.amount {
+            return Err(ContractError::MinAssetsOutAssertion {});
+        }
+    }
+
+    Ok(())
+}
+
+/// ## Description
+/// Updates the fee and Generator information that the pair caches from the factory.
+/// The factory pushes this message to its pairs when their parameters change, but anyone can execute it.
+/// ## Params
+/// * **deps** is an object of type [`DepsMut`].
+///
+/// * **env** is an object of type [`Env`].
+pub fn refresh_factory_params(deps: DepsMut, env: Env) -> Result<Response, ContractError> {
+    let config: Config = CONFIG.load(deps.storage)?;
+    let factory_params = query_factory_params(
+        &deps.querier,
+        config.factory_addr,
+        env.contract.address,
+        config.pair_info.pair_type,
+    )?;
+    FACTORY_PARAMS.save(deps.storage, &factory_params)?;
+
+    let fee_address = factory_params
+        .fee_info
+        .fee_address
+        .map(|addr| addr.to_string())
+        .unwrap_or_default();
+    let generator_address = factory_params
+        .generator_address
+        .map(|addr| addr.to_string())
+        .unwrap_or_default();
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "refresh_factory_params"),
+        attr(
+            "total_fee_rate",
+            factory_params.fee_info.total_fee_rate.to_string(),
+        ),
+        attr(
+            "maker_fee_rate",
+            factory_params.fee_info.maker_fee_rate.to_string(),
+        ),
+        attr("fee_address", fee_address),
+        attr("generator_address", generator_address),
+        attr("pair_status", factory_params.pair_status.to_string()),
+    ]))
+}
+
+/// ## Description
+/// Used for the contract migration. Returns a default object of type [`Response`].
+/// ## Params
+/// * **_deps** is an object of type [`DepsMut`].
+///
+/// * **_env** is an object of type [`Env`].
+///
+/// * **_msg** is an object of type [`MigrateMsg`].
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn migrate(_deps: DepsMut, _env: Env, _msg: MigrateMsg) -> StdResult<Response> {
+    Ok(Response::default())
+}
+
+/// ## Description
+/// Returns the total amount of assets in the pool as well as the liquidity that is currently in range.
+/// The pool doesn't mint LP tokens, so the liquidity in range is returned as the total share.
+/// ## Params
+/// * **deps** is an object of type [`Deps`].
+///
+/// * **config** is an object of type [`Config`].
+pub fn pool_info(deps: Deps, config: Config) -> StdResult<([Asset; 2], Uint128)> {
+    let contract_addr = config.pair_info.contract_addr.clone();
+    let pools: [Asset; 2] = config.pair_info.query_pools(&deps.querier, contract_addr)?;
+    let pool: PoolState = POOL.load(deps.storage)?;
+
+    Ok((pools, pool.liquidity))
+}
@@ -0,0 +1,85 @@
+use astroport::pair_concentrated::MAX_TICK_SPACING;
+use cosmwasm_std::{OverflowError, StdError};
+use thiserror::Error;
+
+/// ## Description
+/// This enum describes concentrated liquidity pair contract errors!
+#[derive(Error, Debug, PartialEq)]
+pub enum ContractError {
+    #[error("{0}")]
+    Std(#[from] StdError),
+
+    #[error("Unauthorized")]
+    Unauthorized {},
+
+    #[error("Event of zero transfer")]
+    InvalidZeroAmount {},
+
+    #[error("Operation exceeds max spread limit")]
+    MaxSpreadAssertion {},
+
+    #[error("Provided spread amount exceeds allowed limit")]
+    AllowedSpreadAssertion {},
+
+    #[error("Doubling assets in asset infos")]
+    DoublingAssets {},
+
+    #[error("Asset mismatch between the requested and the stored asset in contract")]
+    AssetMismatch {},
+
+    #[error("You need to provide init params")]
+    InitParamsNotFound {},
+
+    #[error("Tick spacing must be between 1 and {}", MAX_TICK_SPACING)]
+    InvalidTickSpacing {},
+
+    #[error("The initial price is outside of the supported price range")]
+    InvalidInitialPrice {},
+
+    #[error(
+        "The lower tick must be below the upper tick and both ticks must be multiples of the tick spacing within the supported range"
+    )]
+    InvalidTickRange {},
+
+    #[error("The position doesn't have enough liquidity")]
+    InsufficientPositionLiquidity {},
+
+    #[error("There is not enough liquidity in the pool to complete the swap")]
+    InsufficientLiquidity {},
+
+    #[error("Concentrated liquidity math overflow")]
+    MathOverflow {},
+
+    #[error("Swaps are paused in the pair")]
+    SwapsPaused {},
+
+    #[error("Deposits are paused in the pair")]
+    DepositsPaused {},
+
+    #[error("Transaction deadline has expired")]
+    DeadlineExpired {},
+
+    #[error("Return amount is less than min_return")]
+    MinReturnAssertion {},
+
+    #[error("Added liquidity is less than min_lp_to_receive")]
+    MinLpToReceiveAssertion {},
+
+    #[error("Withdrawn amount is less than min_assets_out")]
+    MinAssetsOutAssertion {},
+}
+
+impl From<OverflowError> for ContractError {
+    fn from(o: OverflowError) -> Self {
+        StdError::from(o).into()
+    }
+}
+
+impl From<ContractError> for StdError {
+    fn from(err: ContractError) -> Self {
+        match err {
+            ContractError::Std(err) => err,
+            err => StdError::generic_err(err.to_string()),
+        }
+    }
+}
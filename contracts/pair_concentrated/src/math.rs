@@ -0,0 +1,550 @@
+use std::convert::TryFrom;
+
+use astroport::pair_concentrated::{MAX_TICK, MIN_TICK};
+use cosmwasm_std::{Decimal, Uint128, Uint256};
+
+/// The fixed point precision of decimal prices (18 decimals)
+const DECIMAL_FRACTIONAL: u128 = 1_000_000_000_000_000_000;
+
+/// The factors used in [`get_sqrt_price_at_tick`]: 2^128 / sqrt(1.0001^(2^i)) for every bit `i` of the tick
+const TICK_FACTORS: [u128; 19] = [
+    0xfff97272373d413259a46990580e213a,
+    0xfff2e50f5f656932ef12357cf3c7fdcc,
+    0xffe5caca7e10e4e61c3624eaa0941cd0,
+    0xffcb9843d60f6159c9db58835c926644,
+    0xff973b41fa98c081472e6896dfb254c0,
+    0xff2ea16466c96a3843ec78b326b52861,
+    0xfe5dee046a99a2a811c461f1969c3053,
+    0xfcbe86c7900a88aedcffc83b479aa3a4,
+    0xf987a7253ac413176f2b074cf7815e54,
+    0xf3392b0822b70005940c7a398e4b70f3,
+    0xe7159475a2c29b7443b29c7fa6e889d9,
+    0xd097f3bdfd2022b8845ad8f792aa5825,
+    0xa9f746462d870fdf8a65dc1f90e061e5,
+    0x70d869a156d2a1b890bb3df62baf32f7,
+    0x31be135f97d08fd981231505542fcfa6,
+    0x9aa508b5b7a84e1c677de54f3e99bc9,
+    0x5d6af8dedb81196699c329225ee604,
+    0x2216e584f5fa1ea926041bedfe98,
+    0x48a170391f7dc42444e8fa2,
+];
+
+/// ## Description
+/// Returns 2^96, the scale of Q64.96 fixed point numbers used for square root prices.
+pub fn q96() -> Uint256 {
+    Uint256::from(1u128 << 96)
+}
+
+/// ## Description
+/// Returns 2^128, the scale of Q128.128 fixed point numbers used for fee growth.
+pub fn q128() -> Uint256 {
+    Uint256::from(1u8) << 128
+}
+
+/// ## Description
+/// Returns the square root price at [`MIN_TICK`].
+pub fn min_sqrt_price() -> Uint256 {
+    Uint256::from(4_295_128_739u128)
+}
+
+/// ## Description
+/// Returns the square root price at [`MAX_TICK`].
+pub fn max_sqrt_price() -> Uint256 {
+    // 1461446703485210103287273052203988822378723970342
+    (Uint256::from(0xfffd8963efd1fc6a506488495d951d52u128) << 32) + Uint256::from(0x63988d26u128)
+}
+
+/// ## Description
+/// Calculates `a * b / denominator` with a 512 bit intermediate value, rounding down.
+/// Returns [`None`] if the denominator is zero or the result doesn't fit into 256 bits.
+pub fn mul_div(a: Uint256, b: Uint256, denominator: Uint256) -> Option<Uint256> {
+    let result = a.full_mul(b).checked_div(denominator.into()).ok()?;
+    Uint256::try_from(result).ok()
+}
+
+/// ## Description
+/// Calculates `a * b / denominator` with a 512 bit intermediate value, rounding up.
+/// Returns [`None`] if the denominator is zero or the result doesn't fit into 256 bits.
+pub fn mul_div_rounding_up(a: Uint256, b: Uint256, denominator: Uint256) -> Option<Uint256> {
+    let result = mul_div(a, b, denominator)?;
+    if a.full_mul(b)
+        .checked_rem(denominator.into())
+        .ok()?
+        .is_zero()
+    {
+        Some(result)
+    } else {
+        result.checked_add(Uint256::from(1u8)).ok()
+    }
+}
+
+/// ## Description
+/// Calculates `a / b`, rounding up. Returns [`None`] if `b` is zero.
+fn div_rounding_up(a: Uint256, b: Uint256) -> Option<Uint256> {
+    let result = a.checked_div(b).ok()?;
+    if a.checked_rem(b).ok()?.is_zero() {
+        Some(result)
+    } else {
+        result.checked_add(Uint256::from(1u8)).ok()
+    }
+}
+
+/// ## Description
+/// Adds two numbers modulo 2^256. Used for the fee growth accumulators which are allowed to overflow.
+pub fn wrapping_add(a: Uint256, b: Uint256) -> Uint256 {
+    match a.checked_add(b) {
+        Ok(sum) => sum,
+        // a + b - 2^256 = b - (2^256 - a) = b - (MAX - a) - 1
+        Err(_) => b - (Uint256::MAX - a) - Uint256::from(1u8),
+    }
+}
+
+/// ## Description
+/// Subtracts two numbers modulo 2^256. Used for the fee growth accumulators which are allowed to overflow.
+pub fn wrapping_sub(a: Uint256, b: Uint256) -> Uint256 {
+    match a.checked_sub(b) {
+        Ok(difference) => difference,
+        // a - b + 2^256 = MAX - (b - a) + 1
+        Err(_) => Uint256::MAX - (b - a) + Uint256::from(1u8),
+    }
+}
+
+/// ## Description
+/// Returns the square root of 1.0001^tick as a Q64.96 number.
+/// Returns [`None`] if the tick is outside of the [`MIN_TICK`]..=[`MAX_TICK`] range.
+/// ## Params
+/// * **tick** is an object of type [`i32`].
+pub fn get_sqrt_price_at_tick(tick: i32) -> Option<Uint256> {
+    if !(MIN_TICK..=MAX_TICK).contains(&tick) {
+        return None;
+    }
+
+    let abs_tick = tick.unsigned_abs();
+    let mut ratio = if abs_tick & 0x1 != 0 {
+        Uint256::from(0xfffcb933bd6fad37aa2d162d1a594001u128)
+    } else {
+        q128()
+    };
+
+    for (i, factor) in TICK_FACTORS.iter().enumerate() {
+        if abs_tick & (0x2 << i) != 0 {
+            ratio = ratio.checked_mul(Uint256::from(*factor)).ok()? >> 128;
+        }
+    }
+
+    if tick > 0 {
+        ratio = Uint256::MAX.checked_div(ratio).ok()?;
+    }
+
+    // Convert the Q128.128 ratio to Q64.96, rounding up
+    let sqrt_price = ratio >> 32;
+    if (ratio - (sqrt_price << 32)).is_zero() {
+        Some(sqrt_price)
+    } else {
+        sqrt_price.checked_add(Uint256::from(1u8)).ok()
+    }
+}
+
+/// ## Description
+/// Returns the greatest tick whose square root price is less than or equal to `sqrt_price`.
+/// Returns [`None`] if the price is outside of the supported price range.
+/// ## Params
+/// * **sqrt_price** is an object of type [`Uint256`]. This is a square root price as a Q64.96 number.
+pub fn get_tick_at_sqrt_price(sqrt_price: Uint256) -> Option<i32> {
+    if sqrt_price < min_sqrt_price() || sqrt_price >= max_sqrt_price() {
+        return None;
+    }
+
+    // Binary search for the greatest tick with a price below or equal to the given one
+    let mut low = MIN_TICK;
+    let mut high = MAX_TICK;
+    while low < high {
+        let mid = low + (high - low + 1) / 2;
+        if get_sqrt_price_at_tick(mid)? <= sqrt_price {
+            low = mid;
+        } else {
+            high = mid - 1;
+        }
+    }
+
+    Some(low)
+}
+
+/// ## Description
+/// Converts a decimal price into a square root price as a Q64.96 number.
+/// ## Params
+/// * **price** is an object of type [`Decimal`]. This is the price of asset 0 in terms of asset 1.
+pub fn sqrt_price_from_decimal(price: Decimal) -> Option<Uint256> {
+    // sqrt(price * 2^128) * 2^32 = sqrt(price) * 2^96
+    let scaled = mul_div(
+        Uint256::from(Uint128::new(DECIMAL_FRACTIONAL) * price),
+        q128(),
+        Uint256::from(DECIMAL_FRACTIONAL),
+    )?;
+    isqrt(scaled).checked_shl(32).ok()
+}
+
+/// ## Description
+/// Converts a square root price into a decimal price.
+/// Returns [`None`] if the price doesn't fit into a [`Decimal`].
+/// ## Params
+/// * **sqrt_price** is an object of type [`Uint256`]. This is a square root price as a Q64.96 number.
+pub fn decimal_from_sqrt_price(sqrt_price: Uint256) -> Option<Decimal> {
+    let price = mul_div(
+        mul_div(sqrt_price, sqrt_price, q96())?,
+        Uint256::from(DECIMAL_FRACTIONAL),
+        q96(),
+    )?;
+    Some(Decimal::from_ratio(
+        Uint128::try_from(price).ok()?,
+        DECIMAL_FRACTIONAL,
+    ))
+}
+
+/// ## Description
+/// Returns the integer square root of a number.
+fn isqrt(value: Uint256) -> Uint256 {
+    if value.is_zero() {
+        return value;
+    }
+
+    // Newton's method starting from ceil(value / 2)
+    let mut z = value;
+    let mut x = value - value / Uint256::from(2u8);
+    while x < z {
+        z = x;
+        x = (value / x + x) / Uint256::from(2u8);
+    }
+
+    z
+}
+
+/// ## Description
+/// Returns the amount of asset 0 between two prices for the given liquidity.
+///
+/// * **Equation**
+///
+/// amount0 = liquidity * (sqrt_price_b - sqrt_price_a) / (sqrt_price_a * sqrt_price_b)
+///
+/// ## Params
+/// * **sqrt_price_a** is an object of type [`Uint256`]. This is the lower square root price.
+///
+/// * **sqrt_price_b** is an object of type [`Uint256`]. This is the upper square root price.
+///
+/// * **liquidity** is an object of type [`Uint128`].
+///
+/// * **round_up** is an object of type [`bool`]. Whether the amount is rounded up or down.
+pub fn get_amount0_delta(
+    sqrt_price_a: Uint256,
+    sqrt_price_b: Uint256,
+    liquidity: Uint128,
+    round_up: bool,
+) -> Option<Uint256> {
+    let (sqrt_price_a, sqrt_price_b) = sort_prices(sqrt_price_a, sqrt_price_b);
+    if sqrt_price_a.is_zero() {
+        return None;
+    }
+
+    let numerator1 = Uint256::from(liquidity) << 96;
+    let numerator2 = sqrt_price_b - sqrt_price_a;
+
+    if round_up {
+        div_rounding_up(
+            mul_div_rounding_up(numerator1, numerator2, sqrt_price_b)?,
+            sqrt_price_a,
+        )
+    } else {
+        mul_div(numerator1, numerator2, sqrt_price_b)?
+            .checked_div(sqrt_price_a)
+            .ok()
+    }
+}
+
+/// ## Description
+/// Returns the amount of asset 1 between two prices for the given liquidity.
+///
+/// * **Equation**
+///
+/// amount1 = liquidity * (sqrt_price_b - sqrt_price_a)
+///
+/// ## Params
+/// * **sqrt_price_a** is an object of type [`Uint256`]. This is the lower square root price.
+///
+/// * **sqrt_price_b** is an object of type [`Uint256`]. This is the upper square root price.
+///
+/// * **liquidity** is an object of type [`Uint128`].
+///
+/// * **round_up** is an object of type [`bool`]. Whether the amount is rounded up or down.
+pub fn get_amount1_delta(
+    sqrt_price_a: Uint256,
+    sqrt_price_b: Uint256,
+    liquidity: Uint128,
+    round_up: bool,
+) -> Option<Uint256> {
+    let (sqrt_price_a, sqrt_price_b) = sort_prices(sqrt_price_a, sqrt_price_b);
+
+    if round_up {
+        mul_div_rounding_up(liquidity.into(), sqrt_price_b - sqrt_price_a, q96())
+    } else {
+        mul_div(liquidity.into(), sqrt_price_b - sqrt_price_a, q96())
+    }
+}
+
+/// ## Description
+/// Returns the square root price after adding or removing an amount of asset 0, rounding up.
+fn get_next_sqrt_price_from_amount0(
+    sqrt_price: Uint256,
+    liquidity: Uint128,
+    amount: Uint256,
+    add: bool,
+) -> Option<Uint256> {
+    if amount.is_zero() {
+        return Some(sqrt_price);
+    }
+
+    let numerator1 = Uint256::from(liquidity) << 96;
+    if add {
+        // numerator1 * sqrt_price / (numerator1 + amount * sqrt_price)
+        if let Ok(product) = amount.checked_mul(sqrt_price) {
+            if let Ok(denominator) = numerator1.checked_add(product) {
+                return mul_div_rounding_up(numerator1, sqrt_price, denominator);
+            }
+        }
+
+        div_rounding_up(
+            numerator1,
+            numerator1
+                .checked_div(sqrt_price)
+                .ok()?
+                .checked_add(amount)
+                .ok()?,
+        )
+    } else {
+        let product = amount.checked_mul(sqrt_price).ok()?;
+        let denominator = numerator1.checked_sub(product).ok()?;
+        if denominator.is_zero() {
+            return None;
+        }
+
+        mul_div_rounding_up(numerator1, sqrt_price, denominator)
+    }
+}
+
+/// ## Description
+/// Returns the square root price after adding or removing an amount of asset 1, rounding down.
+fn get_next_sqrt_price_from_amount1(
+    sqrt_price: Uint256,
+    liquidity: Uint128,
+    amount: Uint256,
+    add: bool,
+) -> Option<Uint256> {
+    if add {
+        let quotient = mul_div(amount, q96(), liquidity.into())?;
+        sqrt_price.checked_add(quotient).ok()
+    } else {
+        let quotient = mul_div_rounding_up(amount, q96(), liquidity.into())?;
+        sqrt_price
+            .checked_sub(quotient)
+            .ok()
+            .filter(|price| !price.is_zero())
+    }
+}
+
+/// ## Description
+/// The result of a swap within a single liquidity range.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SwapStep {
+    /// The square root price after the step
+    pub sqrt_price_next: Uint256,
+    /// The amount of offer assets taken in the step
+    pub amount_in: Uint256,
+    /// The amount of ask assets returned in the step
+    pub amount_out: Uint256,
+}
+
+/// ## Description
+/// Computes a swap within a single liquidity range. The price moves from `sqrt_price` towards
+/// `sqrt_price_target` until the remaining amount is used up or the target is reached.
+/// The direction of the swap is defined by the target: asset 0 is swapped to asset 1 if the target is below the current price.
+/// ## Params
+/// * **sqrt_price** is an object of type [`Uint256`]. This is the current square root price.
+///
+/// * **sqrt_price_target** is an object of type [`Uint256`]. This is the square root price that cannot be exceeded in the step.
+///
+/// * **liquidity** is an object of type [`Uint128`]. This is the liquidity that is in range.
+///
+/// * **amount_remaining** is an object of type [`Uint256`]. This is the amount of offer assets
+/// (or ask assets if `exact_input` is false) that is left to swap.
+///
+/// * **exact_input** is an object of type [`bool`]. Whether `amount_remaining` is an offer or an ask amount.
+pub fn compute_swap_step(
+    sqrt_price: Uint256,
+    sqrt_price_target: Uint256,
+    liquidity: Uint128,
+    amount_remaining: Uint256,
+    exact_input: bool,
+) -> Option<SwapStep> {
+    let zero_for_one = sqrt_price >= sqrt_price_target;
+
+    let sqrt_price_next = if exact_input {
+        let max_in = if zero_for_one {
+            get_amount0_delta(sqrt_price_target, sqrt_price, liquidity, true)?
+        } else {
+            get_amount1_delta(sqrt_price, sqrt_price_target, liquidity, true)?
+        };
+
+        if amount_remaining >= max_in {
+            sqrt_price_target
+        } else if zero_for_one {
+            get_next_sqrt_price_from_amount0(sqrt_price, liquidity, amount_remaining, true)?
+        } else {
+            get_next_sqrt_price_from_amount1(sqrt_price, liquidity, amount_remaining, true)?
+        }
+    } else {
+        let max_out = if zero_for_one {
+            get_amount1_delta(sqrt_price_target, sqrt_price, liquidity, false)?
+        } else {
+            get_amount0_delta(sqrt_price, sqrt_price_target, liquidity, false)?
+        };
+
+        if amount_remaining >= max_out {
+            sqrt_price_target
+        } else if zero_for_one {
+            get_next_sqrt_price_from_amount1(sqrt_price, liquidity, amount_remaining, false)?
+        } else {
+            get_next_sqrt_price_from_amount0(sqrt_price, liquidity, amount_remaining, false)?
+        }
+    };
+
+    let (mut amount_in, mut amount_out) = if zero_for_one {
+        (
+            get_amount0_delta(sqrt_price_next, sqrt_price, liquidity, true)?,
+            get_amount1_delta(sqrt_price_next, sqrt_price, liquidity, false)?,
+        )
+    } else {
+        (
+            get_amount1_delta(sqrt_price, sqrt_price_next, liquidity, true)?,
+            get_amount0_delta(sqrt_price, sqrt_price_next, liquidity, false)?,
+        )
+    };
+
+    if exact_input && sqrt_price_next != sqrt_price_target {
+        // The target is not reached so the whole remaining amount is taken, the rounding dust stays in the pool
+        amount_in = amount_remaining;
+    }
+    if !exact_input && amount_out > amount_remaining {
+        amount_out = amount_remaining;
+    }
+
+    Some(SwapStep {
+        sqrt_price_next,
+        amount_in,
+        amount_out,
+    })
+}
+
+/// ## Description
+/// Returns the maximum liquidity that can be provided in a price range with the given amounts of assets.
+/// ## Params
+/// * **sqrt_price** is an object of type [`Uint256`]. This is the current square root price.
+///
+/// * **sqrt_price_a** is an object of type [`Uint256`]. This is the square root price at the lower tick.
+///
+/// * **sqrt_price_b** is an object of type [`Uint256`]. This is the square root price at the upper tick.
+///
+/// * **amounts** is an array with two objects of type [`Uint128`]. These are the available amounts of assets.
+pub fn get_liquidity_for_amounts(
+    sqrt_price: Uint256,
+    sqrt_price_a: Uint256,
+    sqrt_price_b: Uint256,
+    amounts: [Uint128; 2],
+) -> Option<Uint128> {
+    let (sqrt_price_a, sqrt_price_b) = sort_prices(sqrt_price_a, sqrt_price_b);
+
+    let liquidity = if sqrt_price <= sqrt_price_a {
+        get_liquidity_for_amount0(sqrt_price_a, sqrt_price_b, amounts[0])?
+    } else if sqrt_price < sqrt_price_b {
+        get_liquidity_for_amount0(sqrt_price, sqrt_price_b, amounts[0])?.min(
+            get_liquidity_for_amount1(sqrt_price_a, sqrt_price, amounts[1])?,
+        )
+    } else {
+        get_liquidity_for_amount1(sqrt_price_a, sqrt_price_b, amounts[1])?
+    };
+
+    Uint128::try_from(liquidity).ok()
+}
+
+/// ## Description
+/// Returns the amounts of assets that correspond to an amount of liquidity in a price range.
+/// ## Params
+/// * **sqrt_price** is an object of type [`Uint256`]. This is the current square root price.
+///
+/// * **sqrt_price_a** is an object of type [`Uint256`]. This is the square root price at the lower tick.
+///
+/// * **sqrt_price_b** is an object of type [`Uint256`]. This is the square root price at the upper tick.
+///
+/// * **liquidity** is an object of type [`Uint128`].
+///
+/// * **round_up** is an object of type [`bool`]. Whether the amounts are rounded up (deposits) or down (withdrawals).
+pub fn get_amounts_for_liquidity(
+    sqrt_price: Uint256,
+    sqrt_price_a: Uint256,
+    sqrt_price_b: Uint256,
+    liquidity: Uint128,
+    round_up: bool,
+) -> Option<[Uint128; 2]> {
+    let (sqrt_price_a, sqrt_price_b) = sort_prices(sqrt_price_a, sqrt_price_b);
+
+    let (amount0, amount1) = if sqrt_price <= sqrt_price_a {
+        (
+            get_amount0_delta(sqrt_price_a, sqrt_price_b, liquidity, round_up)?,
+            Uint256::zero(),
+        )
+    } else if sqrt_price < sqrt_price_b {
+        (
+            get_amount0_delta(sqrt_price, sqrt_price_b, liquidity, round_up)?,
+            get_amount1_delta(sqrt_price_a, sqrt_price, liquidity, round_up)?,
+        )
+    } else {
+        (
+            Uint256::zero(),
+            get_amount1_delta(sqrt_price_a, sqrt_price_b, liquidity, round_up)?,
+        )
+    };
+
+    Some([
+        Uint128::try_from(amount0).ok()?,
+        Uint128::try_from(amount1).ok()?,
+    ])
+}
+
+/// ## Description
+/// Returns the liquidity for an amount of asset 0 between two square root prices.
+fn get_liquidity_for_amount0(
+    sqrt_price_a: Uint256,
+    sqrt_price_b: Uint256,
+    amount0: Uint128,
+) -> Option<Uint256> {
+    let intermediate = mul_div(sqrt_price_a, sqrt_price_b, q96())?;
+    mul_div(amount0.into(), intermediate, sqrt_price_b - sqrt_price_a)
+}
+
+/// ## Description
+/// Returns the liquidity for an amount of asset 1 between two square root prices.
+fn get_liquidity_for_amount1(
+    sqrt_price_a: Uint256,
+    sqrt_price_b: Uint256,
+    amount1: Uint128,
+) -> Option<Uint256> {
+    mul_div(amount1.into(), q96(), sqrt_price_b - sqrt_price_a)
+}
+
+/// ## Description
+/// Returns the two prices in ascending order.
+fn sort_prices(a: Uint256, b: Uint256) -> (Uint256, Uint256) {
+    if a > b {
+        (b, a)
+    } else {
+        (a, b)
+    }
+}
@@ -0,0 +1,134 @@
+use astroport::asset::PairInfo;
+use astroport::pair_concentrated::MIN_TICK;
+use astroport::querier::FactoryParams;
+use cosmwasm_std::{Addr, Uint128, Uint256};
+use cw_storage_plus::{Item, Map, U32Key, U64Key};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// ## Description
+/// This structure stores the main config parameters for a concentrated liquidity pair contract.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Config {
+    /// General pair information (e.g pair type)
+    pub pair_info: PairInfo,
+    /// The factory contract address
+    pub factory_addr: Addr,
+    /// Position ticks must be multiples of the tick spacing
+    pub tick_spacing: u32,
+    /// The last timestamp when the pair contract update the asset cumulative prices
+    pub block_time_last: u64,
+    /// The last cumulative price for asset 0
+    pub price0_cumulative_last: Uint128,
+    /// The last cumulative price for asset 1
+    pub price1_cumulative_last: Uint128,
+}
+
+/// ## Description
+/// This structure stores the current state of the pool.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PoolState {
+    /// The square root of the current price as a Q64.96 number
+    pub sqrt_price: Uint256,
+    /// The greatest tick whose price is less than or equal to the current price
+    pub tick: i32,
+    /// The liquidity that is currently in range
+    pub liquidity: Uint128,
+    /// The fees earned per unit of liquidity for every asset as Q128.128 numbers. These values may overflow
+    pub fee_growth_global: [Uint256; 2],
+}
+
+/// ## Description
+/// This structure stores the state of an initialized tick.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, JsonSchema)]
+pub struct TickInfo {
+    /// The liquidity of the positions that use this tick as the lower tick
+    pub liquidity_lower: Uint128,
+    /// The liquidity of the positions that use this tick as the upper tick
+    pub liquidity_upper: Uint128,
+    /// The fee growth on the other side of this tick relative to the current tick for every asset
+    pub fee_growth_outside: [Uint256; 2],
+}
+
+impl TickInfo {
+    /// ## Description
+    /// Returns the total liquidity that references this tick. The tick can be removed when it's zero.
+    pub fn liquidity_gross(&self) -> Uint128 {
+        self.liquidity_lower + self.liquidity_upper
+    }
+}
+
+/// ## Description
+/// This structure stores a liquidity position.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Position {
+    /// The position owner
+    pub owner: Addr,
+    /// The lower tick of the position's price range
+    pub lower_tick: i32,
+    /// The upper tick of the position's price range
+    pub upper_tick: i32,
+    /// The position liquidity
+    pub liquidity: Uint128,
+    /// The fee growth inside the position's price range as of the last position update
+    pub fee_growth_inside_last: [Uint256; 2],
+    /// The fees owed to the position owner as of the last position update
+    pub tokens_owed: [Uint128; 2],
+}
+
+/// ## Description
+/// Stores the config struct at the given key
+pub const CONFIG: Item<Config> = Item::new("config");
+
+/// ## Description
+/// Stores the current pool state
+pub const POOL: Item<PoolState> = Item::new("pool");
+
+/// ## Description
+/// Stores the initialized ticks. The keys are produced by [`tick_key`] so that the ticks are ordered by their values
+pub const TICKS: Map<U32Key, TickInfo> = Map::new("ticks");
+
+/// ## Description
+/// Stores the positions by their identifiers
+pub const POSITIONS: Map<U64Key, Position> = Map::new("positions");
+
+/// ## Description
+/// Stores the position identifiers of every owner
+pub const OWNER_POSITIONS: Map<(&Addr, U64Key), bool> = Map::new("owner_positions");
+
+/// ## Description
+/// Stores the identifier of the last created position
+pub const LAST_POSITION_ID: Item<u64> = Item::new("last_position_id");
+
+/// ## Description
+/// Returns the storage key of a tick. Ticks are shifted by [`MIN_TICK`] so that their big endian
+/// representation keeps the order of the tick values.
+/// ## Params
+/// * **tick** is an object of type [`i32`].
+pub fn tick_key(tick: i32) -> U32Key {
+    U32Key::new((tick - MIN_TICK) as u32)
+}
+
+/// ## Description
+/// Returns the tick that is stored at a raw [`TICKS`] key.
+/// ## Params
+/// * **raw_key** is an object of type [`&[u8]`].
+pub fn tick_from_key(raw_key: &[u8]) -> i32 {
+    let mut bytes = [0u8; 4];
+    bytes.copy_from_slice(raw_key);
+    u32::from_be_bytes(bytes) as i32 + MIN_TICK
+}
+
+/// ## Description
+/// Returns the position identifier that is stored at a raw [`OWNER_POSITIONS`] key (without the owner prefix).
+/// ## Params
+/// * **raw_key** is an object of type [`&[u8]`].
+pub fn position_id_from_key(raw_key: &[u8]) -> u64 {
+    let mut bytes = [0u8; 8];
+    bytes.copy_from_slice(raw_key);
+    u64::from_be_bytes(bytes)
+}
+
+/// ## Description
+/// Stores the fee and Generator information cached from the factory
+pub const FACTORY_PARAMS: Item<FactoryParams> = Item::new("factory_params");
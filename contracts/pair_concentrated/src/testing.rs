@@ -0,0 +1,856 @@
+use crate::contract::{
+    execute, instantiate, query, query_config, query_pair_info, query_position,
+    query_positions_by_owner, query_reverse_simulation, query_simulate_provide,
+    query_simulate_withdraw, query_simulation,
+};
+use crate::error::ContractError;
+use crate::math::{
+    decimal_from_sqrt_price, get_sqrt_price_at_tick, get_tick_at_sqrt_price, max_sqrt_price,
+    min_sqrt_price, q96, sqrt_price_from_decimal, wrapping_add, wrapping_sub,
+};
+use crate::mock_querier::mock_dependencies;
+use astroport::asset::{Asset, AssetInfo, PairInfo};
+use astroport::factory::{PairStatus, PairType};
+
+use astroport::pair::InstantiateMsg;
+use astroport::pair_concentrated::{
+    ConcentratedPoolConfig, ConcentratedPoolParams, ExecuteMsg, QueryMsg, MAX_TICK, MIN_TICK,
+};
+use cosmwasm_std::testing::{mock_env, mock_info, MOCK_CONTRACT_ADDR};
+use cosmwasm_std::{
+    from_binary, to_binary, Addr, BankMsg, Coin, CosmosMsg, Decimal, DepsMut, Response, StdError,
+    SubMsg, Uint128, Uint256, WasmMsg,
+};
+use cw20::Cw20ExecuteMsg;
+use std::str::FromStr;
+
+fn pool_params() -> ConcentratedPoolParams {
+    ConcentratedPoolParams {
+        tick_spacing: 60,
+        initial_price: Decimal::one(),
+    }
+}
+
+fn instantiate_msg(params: &ConcentratedPoolParams) -> InstantiateMsg {
+    InstantiateMsg {
+        factory_addr: String::from("factory"),
+        asset_infos: [
+            AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            AssetInfo::Token {
+                contract_addr: Addr::unchecked("asset0000"),
+            },
+        ],
+        token_code_id: 10u64,
+        init_params: Some(to_binary(params).unwrap()),
+    }
+}
+
+fn assets(amount0: u128, amount1: u128) -> [Asset; 2] {
+    [
+        Asset {
+            info: AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            amount: Uint128::new(amount0),
+        },
+        Asset {
+            info: AssetInfo::Token {
+                contract_addr: Addr::unchecked("asset0000"),
+            },
+            amount: Uint128::new(amount1),
+        },
+    ]
+}
+
+fn create_position(
+    deps: DepsMut,
+    sender: &str,
+    lower_tick: i32,
+    upper_tick: i32,
+    amount0: u128,
+    amount1: u128,
+) -> Result<Response, ContractError> {
+    let msg = ExecuteMsg::CreatePosition {
+        lower_tick,
+        upper_tick,
+        assets: assets(amount0, amount1),
+        receiver: None,
+        min_lp_to_receive: None,
+        deadline: None,
+    };
+    let info = mock_info(
+        sender,
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::new(amount0),
+        }],
+    );
+
+    execute(deps, mock_env(), info, msg)
+}
+
+fn swap_msg(amount: u128) -> ExecuteMsg {
+    ExecuteMsg::Swap {
+        offer_asset: assets(amount, 0)[0].clone(),
+        belief_price: None,
+        max_spread: Some(Decimal::percent(50)),
+        to: None,
+        min_return: None,
+        deadline: None,
+    }
+}
+
+#[test]
+fn tick_math() {
+    assert_eq!(get_sqrt_price_at_tick(0).unwrap(), q96());
+    assert_eq!(get_sqrt_price_at_tick(MIN_TICK).unwrap(), min_sqrt_price());
+    assert_eq!(
+        get_sqrt_price_at_tick(MAX_TICK).unwrap(),
+        Uint256::from_str("1461446703485210103287273052203988822378723970342").unwrap()
+    );
+    assert_eq!(get_sqrt_price_at_tick(MAX_TICK).unwrap(), max_sqrt_price());
+    assert_eq!(get_sqrt_price_at_tick(MIN_TICK - 1), None);
+    assert_eq!(get_sqrt_price_at_tick(MAX_TICK + 1), None);
+
+    for tick in [MIN_TICK, -100_000, -60, -1, 0, 1, 60, 100_000, MAX_TICK - 1] {
+        let sqrt_price = get_sqrt_price_at_tick(tick).unwrap();
+        assert_eq!(get_tick_at_sqrt_price(sqrt_price).unwrap(), tick);
+        // The prices between two ticks belong to the lower tick
+        assert_eq!(
+            get_tick_at_sqrt_price(sqrt_price + Uint256::from(1u8)).unwrap(),
+            tick
+        );
+    }
+    assert_eq!(get_tick_at_sqrt_price(max_sqrt_price()), None);
+
+    // price = 1.0001^tick
+    assert_eq!(sqrt_price_from_decimal(Decimal::one()).unwrap(), q96());
+    let sqrt_price = sqrt_price_from_decimal(Decimal::from_str("4").unwrap()).unwrap();
+    assert_eq!(sqrt_price, q96() * Uint256::from(2u8));
+    assert_eq!(get_tick_at_sqrt_price(sqrt_price).unwrap(), 13_863);
+    assert_eq!(
+        decimal_from_sqrt_price(sqrt_price).unwrap(),
+        Decimal::from_str("4").unwrap()
+    );
+
+    // The fee growth accumulators wrap around
+    let one = Uint256::from(1u8);
+    assert_eq!(wrapping_add(Uint256::MAX, one + one), one);
+    assert_eq!(wrapping_sub(one, one + one), Uint256::MAX);
+    assert_eq!(
+        wrapping_sub(wrapping_add(Uint256::MAX, one), Uint256::MAX),
+        one
+    );
+}
+
+#[test]
+fn proper_initialization() {
+    let mut deps = mock_dependencies(&[]);
+
+    // Init params are required
+    let mut msg = instantiate_msg(&pool_params());
+    msg.init_params = None;
+    let res = instantiate(deps.as_mut(), mock_env(), mock_info("addr0000", &[]), msg).unwrap_err();
+    assert_eq!(res, ContractError::InitParamsNotFound {});
+
+    let mut params = pool_params();
+    params.tick_spacing = 0;
+    let msg = instantiate_msg(&params);
+    let res = instantiate(deps.as_mut(), mock_env(), mock_info("addr0000", &[]), msg).unwrap_err();
+    assert_eq!(res, ContractError::InvalidTickSpacing {});
+
+    let mut params = pool_params();
+    params.initial_price = Decimal::zero();
+    let msg = instantiate_msg(&params);
+    let res = instantiate(deps.as_mut(), mock_env(), mock_info("addr0000", &[]), msg).unwrap_err();
+    assert_eq!(res, ContractError::InvalidInitialPrice {});
+
+    // No LP token is created for the pair
+    let msg = instantiate_msg(&pool_params());
+    let res = instantiate(deps.as_mut(), mock_env(), mock_info("addr0000", &[]), msg).unwrap();
+    assert_eq!(res.messages.len(), 0);
+
+    let pair_info: PairInfo = query_pair_info(deps.as_ref()).unwrap();
+    assert_eq!(pair_info.liquidity_token, Addr::unchecked(""));
+    assert_eq!(pair_info.pair_type, PairType::Concentrated {});
+
+    let config = query_config(deps.as_ref()).unwrap();
+    let params: ConcentratedPoolConfig = from_binary(&config.params.unwrap()).unwrap();
+    assert_eq!(
+        params,
+        ConcentratedPoolConfig {
+            tick_spacing: 60,
+            price: Decimal::one(),
+            sqrt_price: q96(),
+            tick: 0,
+            liquidity: Uint128::zero(),
+        }
+    );
+}
+
+#[test]
+fn provide_and_withdraw_liquidity() {
+    let mut deps = mock_dependencies(&[]);
+    let msg = instantiate_msg(&pool_params());
+    instantiate(deps.as_mut(), mock_env(), mock_info("addr0000", &[]), msg).unwrap();
+
+    // The ticks must be multiples of the tick spacing
+    let res = create_position(deps.as_mut(), "addr0000", -600, 630, 100, 100).unwrap_err();
+    assert_eq!(res, ContractError::InvalidTickRange {});
+    let res = create_position(deps.as_mut(), "addr0000", 600, -600, 100, 100).unwrap_err();
+    assert_eq!(res, ContractError::InvalidTickRange {});
+
+    // The position is limited by the token amount, so the unused native assets are sent back
+    let res = create_position(deps.as_mut(), "addr0000", -600, 600, 2_000_000, 1_000_000).unwrap();
+    let position = query_position(deps.as_ref(), 1).unwrap();
+    assert_eq!(position.owner, Addr::unchecked("addr0000"));
+    assert_eq!(position.lower_tick, -600);
+    assert_eq!(position.upper_tick, 600);
+
+    // The range is symmetric around the current price, so the deposited amounts are equal.
+    // Deposits are rounded up while the position value is rounded down
+    let deposit = position.assets[1].amount;
+    assert!(deposit <= Uint128::new(1_000_000) && deposit > Uint128::new(999_990));
+    assert_eq!(position.assets[0].amount, deposit);
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+                to_address: String::from("addr0000"),
+                amount: vec![Coin {
+                    denom: "uusd".to_string(),
+                    amount: Uint128::new(1_000_000),
+                }],
+            })),
+            SubMsg::new(WasmMsg::Execute {
+                contract_addr: String::from("asset0000"),
+                msg: to_binary(&Cw20ExecuteMsg::TransferFrom {
+                    owner: String::from("addr0000"),
+                    recipient: String::from(MOCK_CONTRACT_ADDR),
+                    amount: Uint128::new(1_000_000),
+                })
+                .unwrap(),
+                funds: vec![],
+            }),
+        ]
+    );
+
+    // Liquidity in range is the position liquidity
+    let config = query_config(deps.as_ref()).unwrap();
+    let params: ConcentratedPoolConfig = from_binary(&config.params.unwrap()).unwrap();
+    assert_eq!(params.liquidity, position.liquidity);
+
+    // A position above the current price only needs asset 0, so no tokens are pulled from the sender
+    let res = create_position(deps.as_mut(), "addr0001", 600, 1200, 1_000_000, 1_000_000).unwrap();
+    assert!(res.messages.is_empty());
+    let position2 = query_position(deps.as_ref(), 2).unwrap();
+    assert_eq!(position2.assets[0].amount, Uint128::new(999_999));
+    assert_eq!(position2.assets[1].amount, Uint128::zero());
+    let params: ConcentratedPoolConfig =
+        from_binary(&query_config(deps.as_ref()).unwrap().params.unwrap()).unwrap();
+    assert_eq!(params.liquidity, position.liquidity);
+
+    // Only the owner can change a position
+    let msg = ExecuteMsg::DecreaseLiquidity {
+        position_id: 1,
+        liquidity: position.liquidity,
+        min_assets_out: None,
+        deadline: None,
+    };
+    let res = execute(deps.as_mut(), mock_env(), mock_info("addr0001", &[]), msg).unwrap_err();
+    assert_eq!(res, ContractError::Unauthorized {});
+
+    let msg = ExecuteMsg::DecreaseLiquidity {
+        position_id: 1,
+        liquidity: position.liquidity + Uint128::new(1),
+        min_assets_out: None,
+        deadline: None,
+    };
+    let res = execute(deps.as_mut(), mock_env(), mock_info("addr0000", &[]), msg).unwrap_err();
+    assert_eq!(res, ContractError::InsufficientPositionLiquidity {});
+
+    // The position is transferred and the new owner withdraws everything (the withdrawn amounts are rounded down
+    // like the position value, so the pool keeps the rounding dust)
+    let msg = ExecuteMsg::TransferPosition {
+        position_id: 1,
+        recipient: String::from("addr0001"),
+    };
+    execute(deps.as_mut(), mock_env(), mock_info("addr0000", &[]), msg).unwrap();
+    let positions = query_positions_by_owner(deps.as_ref(), String::from("addr0001"), None, None)
+        .unwrap()
+        .positions;
+    assert_eq!(
+        positions.iter().map(|p| p.position_id).collect::<Vec<_>>(),
+        vec![1, 2]
+    );
+    let positions =
+        query_positions_by_owner(deps.as_ref(), String::from("addr0001"), Some(1), None)
+            .unwrap()
+            .positions;
+    assert_eq!(positions, vec![position2]);
+    assert!(
+        query_positions_by_owner(deps.as_ref(), String::from("addr0000"), None, None)
+            .unwrap()
+            .positions
+            .is_empty()
+    );
+
+    let msg = ExecuteMsg::DecreaseLiquidity {
+        position_id: 1,
+        liquidity: position.liquidity,
+        min_assets_out: None,
+        deadline: None,
+    };
+    let res = execute(deps.as_mut(), mock_env(), mock_info("addr0001", &[]), msg).unwrap();
+    let withdrawn = deposit;
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+                to_address: String::from("addr0001"),
+                amount: vec![Coin {
+                    denom: "uusd".to_string(),
+                    amount: withdrawn,
+                }],
+            })),
+            SubMsg::new(WasmMsg::Execute {
+                contract_addr: String::from("asset0000"),
+                msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                    recipient: String::from("addr0001"),
+                    amount: withdrawn,
+                })
+                .unwrap(),
+                funds: vec![],
+            }),
+        ]
+    );
+
+    // The position is removed together with its liquidity
+    query_position(deps.as_ref(), 1).unwrap_err();
+    let params: ConcentratedPoolConfig =
+        from_binary(&query_config(deps.as_ref()).unwrap().params.unwrap()).unwrap();
+    assert_eq!(params.liquidity, Uint128::zero());
+}
+
+#[test]
+fn simulate_provide_and_withdraw() {
+    let mut deps = mock_dependencies(&[]);
+    let msg = instantiate_msg(&pool_params());
+    instantiate(deps.as_mut(), mock_env(), mock_info("addr0000", &[]), msg).unwrap();
+
+    // The price range is validated in the same way as in create_position
+    let res = query_simulate_provide(deps.as_ref(), -600, 630, assets(100, 100)).unwrap_err();
+    assert_eq!(
+        res,
+        StdError::generic_err(ContractError::InvalidTickRange {}.to_string())
+    );
+
+    // The simulation returns the liquidity and the deposited amounts of the position
+    let simulation =
+        query_simulate_provide(deps.as_ref(), -600, 600, assets(2_000_000, 1_000_000)).unwrap();
+    let res = create_position(deps.as_mut(), "addr0000", -600, 600, 2_000_000, 1_000_000).unwrap();
+    let position = query_position(deps.as_ref(), 1).unwrap();
+    assert_eq!(simulation.liquidity, position.liquidity);
+    assert_eq!(simulation.assets[1].amount, Uint128::new(1_000_000));
+    assert_eq!(
+        res.messages[0],
+        SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+            to_address: String::from("addr0000"),
+            amount: vec![Coin {
+                denom: "uusd".to_string(),
+                amount: Uint128::new(2_000_000) - simulation.assets[0].amount,
+            }],
+        }))
+    );
+
+    // The withdrawal simulation matches the assets sent by decrease_liquidity
+    let liquidity = position.liquidity.multiply_ratio(1u128, 2u128);
+    let simulation = query_simulate_withdraw(deps.as_ref(), 1, liquidity).unwrap();
+    let msg = ExecuteMsg::DecreaseLiquidity {
+        position_id: 1,
+        liquidity,
+        min_assets_out: None,
+        deadline: None,
+    };
+    let res = execute(deps.as_mut(), mock_env(), mock_info("addr0000", &[]), msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+                to_address: String::from("addr0000"),
+                amount: vec![Coin {
+                    denom: "uusd".to_string(),
+                    amount: simulation[0].amount,
+                }],
+            })),
+            SubMsg::new(WasmMsg::Execute {
+                contract_addr: String::from("asset0000"),
+                msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                    recipient: String::from("addr0000"),
+                    amount: simulation[1].amount,
+                })
+                .unwrap(),
+                funds: vec![],
+            }),
+        ]
+    );
+
+    // The position liquidity can't be exceeded
+    let res = query_simulate_withdraw(deps.as_ref(), 1, position.liquidity).unwrap_err();
+    assert_eq!(
+        res,
+        StdError::generic_err(ContractError::InsufficientPositionLiquidity {}.to_string())
+    );
+}
+
+#[test]
+fn swap_within_range() {
+    let mut deps = mock_dependencies(&[]);
+    let msg = instantiate_msg(&pool_params());
+    instantiate(deps.as_mut(), mock_env(), mock_info("addr0000", &[]), msg).unwrap();
+    create_position(deps.as_mut(), "addr0000", -600, 600, 1_000_000, 1_000_000).unwrap();
+    let liquidity = query_position(deps.as_ref(), 1).unwrap().liquidity;
+
+    // At price 1 the swap follows the x * y = L^2 curve: return = L * offer / (L + offer)
+    let offer_amount = 10_000u128;
+    let ask_amount = liquidity.u128() * offer_amount / (liquidity.u128() + offer_amount);
+    let commission_amount = ask_amount * 3 / 1000;
+
+    let simulation = query_simulation(deps.as_ref(), assets(offer_amount, 0)[0].clone()).unwrap();
+    assert!(simulation.return_amount.u128() + commission_amount + 1 >= ask_amount);
+    assert!(simulation.return_amount.u128() + commission_amount <= ask_amount);
+    assert_eq!(simulation.commission_amount.u128(), commission_amount);
+    assert_eq!(
+        simulation.spread_amount.u128(),
+        offer_amount - simulation.return_amount.u128() - commission_amount
+    );
+
+    // The reverse simulation asks for the same amount
+    let reverse_simulation = query_reverse_simulation(
+        deps.as_ref(),
+        Asset {
+            info: AssetInfo::Token {
+                contract_addr: Addr::unchecked("asset0000"),
+            },
+            amount: simulation.return_amount,
+        },
+    )
+    .unwrap();
+    assert!(reverse_simulation.offer_amount.u128() <= offer_amount + 3);
+    assert!(reverse_simulation.offer_amount.u128() + 3 >= offer_amount);
+
+    let info = mock_info(
+        "addr0001",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::new(offer_amount),
+        }],
+    );
+    let res = execute(deps.as_mut(), mock_env(), info, swap_msg(offer_amount)).unwrap();
+    let maker_fee_amount = simulation.commission_amount * Decimal::permille(166);
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::new(WasmMsg::Execute {
+                contract_addr: String::from("asset0000"),
+                msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                    recipient: String::from("addr0001"),
+                    amount: simulation.return_amount,
+                })
+                .unwrap(),
+                funds: vec![],
+            }),
+            SubMsg::new(WasmMsg::Execute {
+                contract_addr: String::from("asset0000"),
+                msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                    recipient: String::from("fee_address"),
+                    amount: maker_fee_amount,
+                })
+                .unwrap(),
+                funds: vec![],
+            }),
+        ]
+    );
+
+    // The price moved down but no tick was crossed
+    let params: ConcentratedPoolConfig =
+        from_binary(&query_config(deps.as_ref()).unwrap().params.unwrap()).unwrap();
+    assert!(params.price < Decimal::one());
+    assert!(params.tick < 0 && params.tick > -600);
+    assert_eq!(params.liquidity, liquidity);
+
+    // The rest of the commission is earned by the position
+    let position = query_position(deps.as_ref(), 1).unwrap();
+    let lp_fee = simulation.commission_amount - maker_fee_amount;
+    assert_eq!(position.fees[0].amount, Uint128::zero());
+    assert!(
+        position.fees[1].amount <= lp_fee && position.fees[1].amount + Uint128::new(1) >= lp_fee
+    );
+}
+
+#[test]
+fn swap_crosses_ticks() {
+    let mut deps = mock_dependencies(&[]);
+    let msg = instantiate_msg(&pool_params());
+    instantiate(deps.as_mut(), mock_env(), mock_info("addr0000", &[]), msg).unwrap();
+
+    create_position(deps.as_mut(), "addr0000", -60, 60, 100_000, 100_000).unwrap();
+    create_position(deps.as_mut(), "addr0001", -1200, 1200, 1_000_000, 1_000_000).unwrap();
+    let narrow = query_position(deps.as_ref(), 1).unwrap();
+    let wide = query_position(deps.as_ref(), 2).unwrap();
+
+    let params: ConcentratedPoolConfig =
+        from_binary(&query_config(deps.as_ref()).unwrap().params.unwrap()).unwrap();
+    assert_eq!(params.liquidity, narrow.liquidity + wide.liquidity);
+
+    // The swap is too big for the liquidity in the pool
+    let offer_amount = 10_000_000u128;
+    let info = mock_info(
+        "addr0002",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::new(offer_amount),
+        }],
+    );
+    let res = execute(deps.as_mut(), mock_env(), info, swap_msg(offer_amount)).unwrap_err();
+    assert_eq!(res, ContractError::InsufficientLiquidity {});
+
+    // The swap uses up the narrow position and continues with the wide one
+    let offer_amount = 300_000u128;
+    let simulation = query_simulation(deps.as_ref(), assets(offer_amount, 0)[0].clone()).unwrap();
+    let info = mock_info(
+        "addr0002",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::new(offer_amount),
+        }],
+    );
+    let res = execute(deps.as_mut(), mock_env(), info, swap_msg(offer_amount)).unwrap();
+    assert_eq!(
+        res.messages[0],
+        SubMsg::new(WasmMsg::Execute {
+            contract_addr: String::from("asset0000"),
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: String::from("addr0002"),
+                amount: simulation.return_amount,
+            })
+            .unwrap(),
+            funds: vec![],
+        })
+    );
+
+    let params: ConcentratedPoolConfig =
+        from_binary(&query_config(deps.as_ref()).unwrap().params.unwrap()).unwrap();
+    assert!(params.tick < -60 && params.tick > -1200);
+    assert_eq!(params.liquidity, wide.liquidity);
+
+    // The narrow position is converted to asset 0
+    let narrow = query_position(deps.as_ref(), 1).unwrap();
+    assert_eq!(narrow.assets[1].amount, Uint128::zero());
+    assert!(narrow.assets[0].amount > Uint128::new(100_000));
+
+    // The fees are split between the positions that were in range
+    let maker_fee_amount = simulation.commission_amount * Decimal::permille(166);
+    let lp_fee = simulation.commission_amount - maker_fee_amount;
+    let wide = query_position(deps.as_ref(), 2).unwrap();
+    let fees = narrow.fees[1].amount + wide.fees[1].amount;
+    assert!(narrow.fees[1].amount > Uint128::zero());
+    assert!(wide.fees[1].amount > narrow.fees[1].amount);
+    assert!(fees <= lp_fee && fees + Uint128::new(2) >= lp_fee);
+
+    // Swapping back crosses the tick again and the narrow position earns fees in asset 0
+    let offer_amount = 600_000u128;
+    let msg = ExecuteMsg::Receive(cw20::Cw20ReceiveMsg {
+        sender: String::from("addr0002"),
+        amount: Uint128::new(offer_amount),
+        msg: to_binary(&astroport::pair_concentrated::Cw20HookMsg::Swap {
+            belief_price: None,
+            max_spread: Some(Decimal::percent(50)),
+            to: None,
+            min_return: None,
+            deadline: None,
+        })
+        .unwrap(),
+    });
+    execute(deps.as_mut(), mock_env(), mock_info("asset0000", &[]), msg).unwrap();
+
+    let params: ConcentratedPoolConfig =
+        from_binary(&query_config(deps.as_ref()).unwrap().params.unwrap()).unwrap();
+    assert!(params.tick >= 60);
+    assert_eq!(params.liquidity, wide.liquidity);
+    let narrow = query_position(deps.as_ref(), 1).unwrap();
+    assert!(narrow.fees[0].amount > Uint128::zero());
+    assert_eq!(narrow.assets[0].amount, Uint128::zero());
+
+    // The owner collects the fees
+    let fees = narrow.fees.clone();
+    let msg = ExecuteMsg::CollectFees { position_id: 1 };
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0001", &[]),
+        msg.clone(),
+    )
+    .unwrap_err();
+    assert_eq!(res, ContractError::Unauthorized {});
+    let res = execute(deps.as_mut(), mock_env(), mock_info("addr0000", &[]), msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+                to_address: String::from("addr0000"),
+                amount: vec![Coin {
+                    denom: "uusd".to_string(),
+                    amount: fees[0].amount,
+                }],
+            })),
+            SubMsg::new(WasmMsg::Execute {
+                contract_addr: String::from("asset0000"),
+                msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                    recipient: String::from("addr0000"),
+                    amount: fees[1].amount,
+                })
+                .unwrap(),
+                funds: vec![],
+            }),
+        ]
+    );
+
+    let narrow = query_position(deps.as_ref(), 1).unwrap();
+    assert_eq!(narrow.fees[0].amount, Uint128::zero());
+    assert_eq!(narrow.fees[1].amount, Uint128::zero());
+}
+
+#[test]
+fn pair_status_circuit_breaker() {
+    let mut deps = mock_dependencies(&[]);
+    let msg = instantiate_msg(&pool_params());
+    instantiate(deps.as_mut(), mock_env(), mock_info("addr0000", &[]), msg).unwrap();
+    create_position(deps.as_mut(), "addr0000", -600, 600, 1_000_000, 1_000_000).unwrap();
+
+    // The pair picks the factory state up once its cached parameters are refreshed
+    deps.querier.with_pair_status(PairStatus::WithdrawOnly);
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("anyone", &[]),
+        ExecuteMsg::RefreshFactoryParams {},
+    )
+    .unwrap();
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::PairStatus {}).unwrap();
+    assert_eq!(
+        from_binary::<PairStatus>(&res).unwrap(),
+        PairStatus::WithdrawOnly
+    );
+
+    let info = mock_info(
+        "addr0001",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::new(10_000),
+        }],
+    );
+    let res = execute(deps.as_mut(), mock_env(), info.clone(), swap_msg(10_000)).unwrap_err();
+    assert_eq!(res, ContractError::SwapsPaused {});
+    let res = create_position(deps.as_mut(), "addr0000", -600, 600, 100, 100).unwrap_err();
+    assert_eq!(res, ContractError::DepositsPaused {});
+
+    // Positions can still be closed while the pair is halted
+    let liquidity = query_position(deps.as_ref(), 1).unwrap().liquidity;
+    let msg = ExecuteMsg::DecreaseLiquidity {
+        position_id: 1,
+        liquidity,
+        min_assets_out: None,
+        deadline: None,
+    };
+    execute(deps.as_mut(), mock_env(), mock_info("addr0000", &[]), msg).unwrap();
+
+    deps.querier.with_pair_status(PairStatus::Active);
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("anyone", &[]),
+        ExecuteMsg::RefreshFactoryParams {},
+    )
+    .unwrap();
+    create_position(deps.as_mut(), "addr0000", -600, 600, 1_000_000, 1_000_000).unwrap();
+    execute(deps.as_mut(), mock_env(), info, swap_msg(10_000)).unwrap();
+}
+
+#[test]
+fn execution_guards() {
+    let new_pool = || {
+        let mut deps = mock_dependencies(&[]);
+        let msg = instantiate_msg(&pool_params());
+        instantiate(deps.as_mut(), mock_env(), mock_info("addr0000", &[]), msg).unwrap();
+        deps
+    };
+
+    let env = mock_env();
+    let expired = Some(env.block.time.seconds() - 1);
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::new(1_000_000),
+        }],
+    );
+
+    // A failed deposit leaves its storage writes behind in unit tests, so it's checked on a separate pool
+    let create_msg =
+        |min_lp_to_receive: Option<Uint128>, deadline: Option<u64>| ExecuteMsg::CreatePosition {
+            lower_tick: -600,
+            upper_tick: 600,
+            assets: assets(1_000_000, 1_000_000),
+            receiver: None,
+            min_lp_to_receive,
+            deadline,
+        };
+    let mut other_deps = new_pool();
+    let res = execute(
+        other_deps.as_mut(),
+        env.clone(),
+        info.clone(),
+        create_msg(None, expired),
+    )
+    .unwrap_err();
+    assert_eq!(res, ContractError::DeadlineExpired {});
+    let res = execute(
+        other_deps.as_mut(),
+        env.clone(),
+        info.clone(),
+        create_msg(Some(Uint128::MAX), None),
+    )
+    .unwrap_err();
+    assert_eq!(res, ContractError::MinLpToReceiveAssertion {});
+
+    let mut deps = new_pool();
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        info.clone(),
+        create_msg(None, Some(env.block.time.seconds())),
+    )
+    .unwrap();
+    let position = query_position(deps.as_ref(), 1).unwrap();
+
+    // The same deposit at the same price adds the same liquidity
+    let increase_msg =
+        |min_lp_to_receive: Option<Uint128>, deadline: Option<u64>| ExecuteMsg::IncreaseLiquidity {
+            position_id: 1,
+            assets: assets(1_000_000, 1_000_000),
+            min_lp_to_receive,
+            deadline,
+        };
+    let mut other_deps = new_pool();
+    execute(
+        other_deps.as_mut(),
+        env.clone(),
+        info.clone(),
+        create_msg(None, None),
+    )
+    .unwrap();
+    let res = execute(
+        other_deps.as_mut(),
+        env.clone(),
+        info.clone(),
+        increase_msg(Some(position.liquidity + Uint128::new(1)), None),
+    )
+    .unwrap_err();
+    assert_eq!(res, ContractError::MinLpToReceiveAssertion {});
+
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        info.clone(),
+        increase_msg(None, expired),
+    )
+    .unwrap_err();
+    assert_eq!(res, ContractError::DeadlineExpired {});
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        info,
+        increase_msg(Some(position.liquidity), None),
+    )
+    .unwrap();
+
+    let offer_amount = 10_000u128;
+    let simulation = query_simulation(deps.as_ref(), assets(offer_amount, 0)[0].clone()).unwrap();
+    let swap_msg = |min_return: Option<Uint128>, deadline: Option<u64>| ExecuteMsg::Swap {
+        offer_asset: assets(offer_amount, 0)[0].clone(),
+        belief_price: None,
+        max_spread: Some(Decimal::percent(50)),
+        to: None,
+        min_return,
+        deadline,
+    };
+    let info = mock_info(
+        "addr0001",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::new(offer_amount),
+        }],
+    );
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        info.clone(),
+        swap_msg(None, expired),
+    )
+    .unwrap_err();
+    assert_eq!(res, ContractError::DeadlineExpired {});
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        info.clone(),
+        swap_msg(Some(simulation.return_amount + Uint128::new(1)), None),
+    )
+    .unwrap_err();
+    assert_eq!(res, ContractError::MinReturnAssertion {});
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        info,
+        swap_msg(Some(simulation.return_amount), None),
+    )
+    .unwrap();
+
+    // The withdrawn amounts are checked without the collected fees
+    let position = query_position(deps.as_ref(), 1).unwrap();
+    let decrease_msg =
+        |min_assets_out: Option<Vec<Asset>>, deadline: Option<u64>| ExecuteMsg::DecreaseLiquidity {
+            position_id: 1,
+            liquidity: position.liquidity,
+            min_assets_out,
+            deadline,
+        };
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("addr0000", &[]),
+        decrease_msg(None, expired),
+    )
+    .unwrap_err();
+    assert_eq!(res, ContractError::DeadlineExpired {});
+
+    let mut min_assets_out = position.assets.to_vec();
+    min_assets_out[1].amount += Uint128::new(1);
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("addr0000", &[]),
+        decrease_msg(Some(min_assets_out), None),
+    )
+    .unwrap_err();
+    assert_eq!(res, ContractError::MinAssetsOutAssertion {});
+    execute(
+        deps.as_mut(),
+        env,
+        mock_info("addr0000", &[]),
+        decrease_msg(Some(position.assets.to_vec()), None),
+    )
+    .unwrap();
+}
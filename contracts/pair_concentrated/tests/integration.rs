@@ -0,0 +1,332 @@
+use astroport::asset::{Asset, AssetInfo, PairInfo};
+use astroport::factory::{
+    ExecuteMsg as FactoryExecuteMsg, InstantiateMsg as FactoryInstantiateMsg, PairConfig, PairType,
+    QueryMsg as FactoryQueryMsg,
+};
+use astroport::pair::{PoolResponse, ReverseSimulationResponse, SimulationResponse};
+use astroport::pair_concentrated::{
+    ConcentratedPoolParams, Cw20HookMsg, ExecuteMsg, PositionResponse, QueryMsg,
+};
+use astroport::token::InstantiateMsg as TokenInstantiateMsg;
+use cosmwasm_std::testing::{mock_env, MockApi, MockStorage};
+use cosmwasm_std::{to_binary, Addr, Decimal, Uint128};
+use cw20::{BalanceResponse, Cw20Coin, Cw20ExecuteMsg, Cw20QueryMsg, MinterResponse};
+use terra_multi_test::{AppBuilder, BankKeeper, ContractWrapper, Executor, TerraApp, TerraMock};
+
+const OWNER: &str = "owner";
+
+fn mock_app() -> TerraApp {
+    let env = mock_env();
+    let api = MockApi::default();
+    let bank = BankKeeper::new();
+    let storage = MockStorage::new();
+    let custom = TerraMock::luna_ust_case();
+
+    AppBuilder::new()
+        .with_api(api)
+        .with_block(env.block)
+        .with_bank(bank)
+        .with_storage(storage)
+        .with_custom(custom)
+        .build()
+}
+
+fn store_token_code(app: &mut TerraApp) -> u64 {
+    let astro_token_contract = Box::new(ContractWrapper::new_with_empty(
+        astroport_token::contract::execute,
+        astroport_token::contract::instantiate,
+        astroport_token::contract::query,
+    ));
+
+    app.store_code(astro_token_contract)
+}
+
+fn store_pair_code(app: &mut TerraApp) -> u64 {
+    let pair_contract = Box::new(ContractWrapper::new_with_empty(
+        astroport_pair_concentrated::contract::execute,
+        astroport_pair_concentrated::contract::instantiate,
+        astroport_pair_concentrated::contract::query,
+    ));
+
+    app.store_code(pair_contract)
+}
+
+fn store_factory_code(app: &mut TerraApp) -> u64 {
+    let factory_contract = Box::new(
+        ContractWrapper::new_with_empty(
+            astroport_factory::contract::execute,
+            astroport_factory::contract::instantiate,
+            astroport_factory::contract::query,
+        )
+        .with_reply_empty(astroport_factory::contract::reply),
+    );
+
+    app.store_code(factory_contract)
+}
+
+fn instantiate_token(app: &mut TerraApp, token_code_id: u64, name: &str, amount: Uint128) -> Addr {
+    let init_msg = TokenInstantiateMsg {
+        name: name.to_string(),
+        symbol: name.to_string(),
+        decimals: 6,
+        initial_balances: vec![Cw20Coin {
+            address: OWNER.to_string(),
+            amount,
+        }],
+        mint: Some(MinterResponse {
+            minter: String::from(OWNER),
+            cap: None,
+        }),
+    };
+
+    app.instantiate_contract(
+        token_code_id,
+        Addr::unchecked(OWNER),
+        &init_msg,
+        &[],
+        name,
+        None,
+    )
+    .unwrap()
+}
+
+fn instantiate_factory(app: &mut TerraApp, token_code_id: u64) -> Addr {
+    let pair_code_id = store_pair_code(app);
+    let factory_code_id = store_factory_code(app);
+
+    let init_msg = FactoryInstantiateMsg {
+        fee_address: None,
+        pair_configs: vec![PairConfig {
+            code_id: pair_code_id,
+            maker_fee_bps: 0,
+            pair_type: PairType::Concentrated {},
+            total_fee_bps: 30,
+            is_disabled: false,
+            is_generator_disabled: false,
+            maker_fee_as_lp: false,
+        }],
+        token_code_id,
+        generator_address: Some(String::from("generator")),
+        owner: OWNER.to_string(),
+        whitelist_code_id: 234u64,
+    };
+
+    app.instantiate_contract(
+        factory_code_id,
+        Addr::unchecked(OWNER),
+        &init_msg,
+        &[],
+        "FACTORY",
+        None,
+    )
+    .unwrap()
+}
+
+fn query_balance(app: &TerraApp, token: &Addr, address: &Addr) -> Uint128 {
+    let res: BalanceResponse = app
+        .wrap()
+        .query_wasm_smart(
+            token,
+            &Cw20QueryMsg::Balance {
+                address: address.to_string(),
+            },
+        )
+        .unwrap();
+
+    res.balance
+}
+
+#[test]
+fn create_position_swap_and_withdraw() {
+    let mut app = mock_app();
+    let owner = Addr::unchecked(OWNER);
+    let user = Addr::unchecked("user");
+
+    let token_code_id = store_token_code(&mut app);
+    let provide_amount = Uint128::new(1_000_000_000u128);
+    let offer_amount = Uint128::new(1_000_000u128);
+    let gov_token = instantiate_token(&mut app, token_code_id, "GOV", provide_amount);
+    let usd_token = instantiate_token(
+        &mut app,
+        token_code_id,
+        "USD",
+        provide_amount + offer_amount,
+    );
+    let factory = instantiate_factory(&mut app, token_code_id);
+
+    let asset_infos = [
+        AssetInfo::Token {
+            contract_addr: gov_token.clone(),
+        },
+        AssetInfo::Token {
+            contract_addr: usd_token.clone(),
+        },
+    ];
+
+    // Init params are required for concentrated liquidity pairs
+    let msg = FactoryExecuteMsg::CreatePair {
+        asset_infos: asset_infos.clone(),
+        pair_type: PairType::Concentrated {},
+        init_params: None,
+    };
+    app.execute_contract(owner.clone(), factory.clone(), &msg, &[])
+        .unwrap_err();
+
+    let msg = FactoryExecuteMsg::CreatePair {
+        asset_infos: asset_infos.clone(),
+        pair_type: PairType::Concentrated {},
+        init_params: Some(
+            to_binary(&ConcentratedPoolParams {
+                tick_spacing: 60,
+                initial_price: Decimal::one(),
+            })
+            .unwrap(),
+        ),
+    };
+    app.execute_contract(owner.clone(), factory.clone(), &msg, &[])
+        .unwrap();
+
+    let pair_info: PairInfo = app
+        .wrap()
+        .query_wasm_smart(
+            &factory,
+            &FactoryQueryMsg::Pair {
+                asset_infos: asset_infos.clone(),
+                pair_type: Some(PairType::Concentrated {}),
+            },
+        )
+        .unwrap();
+    assert_eq!(pair_info.pair_type, PairType::Concentrated {});
+    // No LP token is created for the pair
+    assert_eq!(pair_info.liquidity_token, Addr::unchecked(""));
+    let pair = pair_info.contract_addr;
+
+    for token in [&gov_token, &usd_token] {
+        let msg = Cw20ExecuteMsg::IncreaseAllowance {
+            spender: pair.to_string(),
+            expires: None,
+            amount: provide_amount,
+        };
+        app.execute_contract(owner.clone(), token.clone(), &msg, &[])
+            .unwrap();
+    }
+
+    // The range is symmetric around the current price, so both assets are deposited in full
+    let msg = ExecuteMsg::CreatePosition {
+        lower_tick: -600,
+        upper_tick: 600,
+        assets: [
+            Asset {
+                info: asset_infos[0].clone(),
+                amount: provide_amount,
+            },
+            Asset {
+                info: asset_infos[1].clone(),
+                amount: provide_amount,
+            },
+        ],
+        receiver: None,
+        min_lp_to_receive: None,
+        deadline: None,
+    };
+    app.execute_contract(owner.clone(), pair.clone(), &msg, &[])
+        .unwrap();
+    assert_eq!(query_balance(&app, &gov_token, &owner), Uint128::zero());
+    assert_eq!(query_balance(&app, &usd_token, &owner), offer_amount);
+
+    let position: PositionResponse = app
+        .wrap()
+        .query_wasm_smart(&pair, &QueryMsg::Position { position_id: 1 })
+        .unwrap();
+    assert_eq!(position.owner, owner);
+
+    let pool: PoolResponse = app
+        .wrap()
+        .query_wasm_smart(&pair, &QueryMsg::Pool {})
+        .unwrap();
+    assert_eq!(pool.assets[0].amount, provide_amount);
+    assert_eq!(pool.assets[1].amount, provide_amount);
+    assert_eq!(pool.total_share, position.liquidity);
+
+    let offer_asset = Asset {
+        info: asset_infos[1].clone(),
+        amount: offer_amount,
+    };
+    let simulation: SimulationResponse = app
+        .wrap()
+        .query_wasm_smart(
+            &pair,
+            &QueryMsg::Simulation {
+                offer_asset: offer_asset.clone(),
+            },
+        )
+        .unwrap();
+    // The liquidity is concentrated around the current price, so the spread is tiny
+    assert!(simulation.spread_amount < Uint128::new(100));
+
+    let reverse_simulation: ReverseSimulationResponse = app
+        .wrap()
+        .query_wasm_smart(
+            &pair,
+            &QueryMsg::ReverseSimulation {
+                ask_asset: Asset {
+                    info: asset_infos[0].clone(),
+                    amount: simulation.return_amount,
+                },
+            },
+        )
+        .unwrap();
+    assert!(reverse_simulation.offer_amount <= offer_amount);
+    assert!(reverse_simulation.offer_amount + Uint128::new(10) > offer_amount);
+
+    let msg = Cw20ExecuteMsg::Send {
+        contract: pair.to_string(),
+        msg: to_binary(&Cw20HookMsg::Swap {
+            belief_price: None,
+            max_spread: None,
+            to: Some(user.to_string()),
+            min_return: None,
+            deadline: None,
+        })
+        .unwrap(),
+        amount: offer_amount,
+    };
+    app.execute_contract(owner.clone(), usd_token.clone(), &msg, &[])
+        .unwrap();
+    assert_eq!(
+        query_balance(&app, &gov_token, &user),
+        simulation.return_amount
+    );
+
+    // The position earned the swap fee
+    let position: PositionResponse = app
+        .wrap()
+        .query_wasm_smart(&pair, &QueryMsg::Position { position_id: 1 })
+        .unwrap();
+    assert!(position.fees[0].amount > Uint128::zero());
+    assert!(position.fees[0].amount <= simulation.commission_amount);
+
+    // Only the owner can withdraw the liquidity
+    let msg = ExecuteMsg::DecreaseLiquidity {
+        position_id: 1,
+        liquidity: position.liquidity,
+        min_assets_out: None,
+        deadline: None,
+    };
+    app.execute_contract(user.clone(), pair.clone(), &msg, &[])
+        .unwrap_err();
+    app.execute_contract(owner.clone(), pair.clone(), &msg, &[])
+        .unwrap();
+
+    assert_eq!(
+        query_balance(&app, &gov_token, &owner),
+        position.assets[0].amount + position.fees[0].amount
+    );
+    assert_eq!(
+        query_balance(&app, &usd_token, &owner),
+        position.assets[1].amount + position.fees[1].amount
+    );
+    // Only the rounding dust stays in the pool
+    assert!(query_balance(&app, &gov_token, &pair) < Uint128::new(10));
+    assert!(query_balance(&app, &usd_token, &pair) < Uint128::new(10));
+}
@@ -0,0 +1,1549 @@
+use crate::error::ContractError;
+use crate::state::{Config, CONFIG, FACTORY_PARAMS, RESERVES};
+use astroport::weighted_math::{
+    calc_ask_amount, calc_offer_amount, calc_spot_price, MAX_IN_RATIO, MAX_OUT_RATIO, ONE,
+};
+
+use cosmwasm_bignumber::Decimal256;
+use cosmwasm_std::{
+    attr, entry_point, from_binary, to_binary, Addr, Binary, CosmosMsg, Decimal, Deps, DepsMut,
+    Env, MessageInfo, Reply, ReplyOn, Response, StdError, StdResult, Storage, SubMsg, Uint128,
+    WasmMsg,
+};
+
+use crate::response::MsgInstantiateContractResponse;
+use astroport::asset::{addr_validate_to_lower, format_lp_token_name, Asset, AssetInfo, PairInfo};
+use astroport::factory::PairType;
+use astroport::pair::{
+    ConfigResponse, CumulativePricesResponse, InstantiateMsg, MigrateMsg, PoolResponse, QueryMsg,
+    ReverseSimulationResponse, SimulateProvideResponse, SimulationResponse, DEFAULT_SLIPPAGE,
+    MAX_ALLOWED_SLIPPAGE, TWAP_PRECISION,
+};
+use astroport::pair_lbp::{
+    Cw20HookMsg, ExecuteMsg, LbpPoolConfig, LbpPoolParams, LbpPoolUpdateParams, MIN_WEIGHT,
+};
+use astroport::querier::{query_factory_params, query_supply};
+use astroport::token::InstantiateMsg as TokenInstantiateMsg;
+use cw2::set_contract_version;
+use cw20::{Cw20ExecuteMsg, Cw20ReceiveMsg, MinterResponse};
+use protobuf::Message;
+use std::str::FromStr;
+use std::vec;
+
+/// Contract name that is used for migration.
+const CONTRACT_NAME: &str = "astroport-pair-lbp";
+/// Contract version that is used for migration.
+const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
+/// A `reply` call code ID used for sub-messages.
+const INSTANTIATE_TOKEN_REPLY_ID: u64 = 1;
+
+/// ## Description
+/// Creates a new contract with the specified parameters in the [`InstantiateMsg`].
+/// Returns the [`Response`] with the specified attributes if the operation was successful, or a [`ContractError`] if
+/// the contract was not created.
+/// ## Params
+/// * **deps** is an object of type [`DepsMut`].
+///
+/// * **env** is an object of type [`Env`].
+///
+/// * **_info** is an object of type [`MessageInfo`].
+/// * **msg** is a message of type [`InstantiateMsg`] which contains the basic settings for creating a contract.
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn instantiate(
+    deps: DepsMut,
+    env: Env,
+    _info: MessageInfo,
+    msg: InstantiateMsg,
+) -> Result<Response, ContractError> {
+    msg.asset_infos[0].check(deps.api)?;
+    msg.asset_infos[1].check(deps.api)?;
+
+    if msg.asset_infos[0] == msg.asset_infos[1] {
+        return Err(ContractError::DoublingAssets {});
+    }
+
+    if msg.init_params.is_none() {
+        return Err(ContractError::InitParamsNotFound {});
+    }
+
+    let params: LbpPoolParams = from_binary(&msg.init_params.unwrap())?;
+    let min_weight = Decimal::percent(MIN_WEIGHT);
+    for weights in [params.start_weights, params.end_weights] {
+        if weights.iter().any(|w| *w < min_weight) || weights[0] + weights[1] != Decimal::one() {
+            return Err(ContractError::InvalidWeights {});
+        }
+    }
+
+    if params.start_time >= params.end_time || params.end_time <= env.block.time.seconds() {
+        return Err(ContractError::InvalidTimes {});
+    }
+
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+
+    let config = Config {
+        pair_info: PairInfo {
+            contract_addr: env.contract.address.clone(),
+            liquidity_token: Addr::unchecked(""),
+            asset_infos: msg.asset_infos.clone(),
+            pair_type: PairType::Lbp {},
+        },
+        factory_addr: addr_validate_to_lower(deps.api, msg.factory_addr.as_str())?,
+        owner: addr_validate_to_lower(deps.api, params.owner.as_str())?,
+        block_time_last: 0,
+        price0_cumulative_last: Uint128::zero(),
+        price1_cumulative_last: Uint128::zero(),
+        start_weights: params.start_weights,
+        end_weights: params.end_weights,
+        start_time: params.start_time,
+        end_time: params.end_time,
+        is_paused: false,
+    };
+
+    CONFIG.save(deps.storage, &config)?;
+    RESERVES.save(deps.storage, &[Uint128::zero(), Uint128::zero()])?;
+    FACTORY_PARAMS.save(
+        deps.storage,
+        &query_factory_params(
+            &deps.querier,
+            config.factory_addr.clone(),
+            env.contract.address.clone(),
+            config.pair_info.pair_type.clone(),
+        )?,
+    )?;
+
+    let token_name = format_lp_token_name(&msg.asset_infos, &deps.querier)?;
+
+    // Create the LP token contract
+    let sub_msg: Vec<SubMsg> = vec![SubMsg {
+        msg: WasmMsg::Instantiate {
+            code_id: msg.token_code_id,
+            msg: to_binary(&TokenInstantiateMsg {
+                name: token_name,
+                symbol: "uLP".to_string(),
+                decimals: 6,
+                initial_balances: vec![],
+                mint: Some(MinterResponse {
+                    minter: env.contract.address.to_string(),
+                    cap: None,
+                }),
+            })?,
+            funds: vec![],
+            admin: None,
+            label: String::from("Astroport LP token"),
+        }
+        .into(),
+        id: INSTANTIATE_TOKEN_REPLY_ID,
+        gas_limit: None,
+        reply_on: ReplyOn::Success,
+    }];
+
+    Ok(Response::new().add_submessages(sub_msg))
+}
+
+/// # Description
+/// The entry point to the contract for processing replies from submessages.
+/// # Params
+/// * **deps** is an object of type [`DepsMut`].
+///
+/// * **_env** is an object of type [`Env`].
+///
+/// * **msg** is an object of type [`Reply`].
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn reply(deps: DepsMut, _env: Env, msg: Reply) -> Result<Response, ContractError> {
+    let mut config: Config = CONFIG.load(deps.storage)?;
+
+    if config.pair_info.liquidity_token != Addr::unchecked("") {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let data = msg.result.unwrap().data.unwrap();
+    let res: MsgInstantiateContractResponse =
+        Message::parse_from_bytes(data.as_slice()).map_err(|_| {
+            StdError::parse_err("MsgInstantiateContractResponse", "failed to parse data")
+        })?;
+
+    config.pair_info.liquidity_token =
+        addr_validate_to_lower(deps.api, res.get_contract_address())?;
+
+    CONFIG.save(deps.storage, &config)?;
+
+    Ok(Response::new().add_attribute("liquidity_token_addr", config.pair_info.liquidity_token))
+}
+
+/// ## Description
+/// Exposes all the execute functions available in the contract.
+/// ## Params
+/// * **deps** is an object of type [`Deps`].
+///
+/// * **env** is an object of type [`Env`].
+///
+/// * **info** is an object of type [`MessageInfo`].
+///
+/// * **msg** is an object of type [`ExecuteMsg`].
+///
+/// ## Queries
+/// * **ExecuteMsg::UpdateConfig { params: Binary }** Pauses or resumes swaps using [`LbpPoolUpdateParams`].
+///
+/// * **ExecuteMsg::Receive(msg)** Receives a message of type [`Cw20ReceiveMsg`] and processes
+/// it depending on the received template.
+///
+/// * **ExecuteMsg::ProvideLiquidity { assets }** Deposits the owner's assets in the pool.
+///
+/// * **ExecuteMsg::Swap {
+///             offer_asset,
+///             belief_price,
+///             max_spread,
+///             to,
+///             min_return,
+///             deadline,
+///         }** Performs a swap operation with the specified parameters.
+///
+/// * **ExecuteMsg::WithdrawProceeds { receiver }** Withdraws all pool assets after the end time.
+///
+/// * **ExecuteMsg::RefreshFactoryParams {}** Updates the fee and Generator information cached from the factory.
+///
+/// * **ExecuteMsg::Sync {}** Sets the tracked pool reserves to the current balances of the pair.
+///
+/// * **ExecuteMsg::Skim { to }** Sends the balances of the pair that exceed the tracked pool reserves to a recipient.
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn execute(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: ExecuteMsg,
+) -> Result<Response, ContractError> {
+    match msg {
+        ExecuteMsg::UpdateConfig { params } => update_config(deps, info, params),
+        ExecuteMsg::Receive(msg) => receive_cw20(deps, env, info, msg),
+        ExecuteMsg::ProvideLiquidity { assets } => provide_liquidity(deps, env, info, assets),
+        ExecuteMsg::Swap {
+            offer_asset,
+            belief_price,
+            max_spread,
+            to,
+            min_return,
+            deadline,
+        } => {
+            offer_asset.info.check(deps.api)?;
+            if !offer_asset.is_native_token() {
+                return Err(ContractError::Unauthorized {});
+            }
+
+            let to_addr = if let Some(to_addr) = to {
+                Some(addr_validate_to_lower(deps.api, &to_addr)?)
+            } else {
+                None
+            };
+
+            swap(
+                deps,
+                env,
+                info.clone(),
+                info.sender,
+                offer_asset,
+                belief_price,
+                max_spread,
+                to_addr,
+                min_return,
+                deadline,
+            )
+        }
+        ExecuteMsg::WithdrawProceeds { receiver } => withdraw_proceeds(deps, env, info, receiver),
+        ExecuteMsg::RefreshFactoryParams {} => refresh_factory_params(deps, env),
+        ExecuteMsg::Sync {} => sync(deps, env),
+        ExecuteMsg::Skim { to } => skim(deps, env, info, to),
+    }
+}
+
+/// ## Description
+/// Receives a message of type [`Cw20ReceiveMsg`] and processes it depending on the received template.
+/// If the template is not found in the received message, then an [`ContractError`] is returned,
+/// otherwise it returns the [`Response`] with the specified attributes if the operation was successful.
+/// ## Params
+/// * **deps** is an object of type [`DepsMut`].
+///
+/// * **env** is an object of type [`Env`].
+///
+/// * **info** is an object of type [`MessageInfo`].
+///
+/// * **cw20_msg** is an object of type [`Cw20ReceiveMsg`]. This is the CW20 message that has to be processed.
+pub fn receive_cw20(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    cw20_msg: Cw20ReceiveMsg,
+) -> Result<Response, ContractError> {
+    let contract_addr = info.sender.clone();
+    match from_binary(&cw20_msg.msg) {
+        Ok(Cw20HookMsg::Swap {
+            belief_price,
+            max_spread,
+            to,
+            min_return,
+            deadline,
+        }) => {
+            // Only asset contract can execute this message
+            let mut authorized: bool = false;
+            let config: Config = CONFIG.load(deps.storage)?;
+
+            for pool in config.pair_info.asset_infos {
+                if let AssetInfo::Token { contract_addr, .. } = &pool {
+                    if contract_addr == &info.sender {
+                        authorized = true;
+                    }
+                }
+            }
+
+            if !authorized {
+                return Err(ContractError::Unauthorized {});
+            }
+
+            let to_addr = if let Some(to_addr) = to {
+                Some(addr_validate_to_lower(deps.api, to_addr.as_str())?)
+            } else {
+                None
+            };
+
+            swap(
+                deps,
+                env,
+                info,
+                Addr::unchecked(cw20_msg.sender),
+                Asset {
+                    info: AssetInfo::Token { contract_addr },
+                    amount: cw20_msg.amount,
+                },
+                belief_price,
+                max_spread,
+                to_addr,
+                min_return,
+                deadline,
+            )
+        }
+        Err(err) => Err(ContractError::Std(err)),
+    }
+}
+
+/// ## Description
+/// Deposits the owner's assets in the pool. No LP tokens are minted because the pool assets
+/// can only be withdrawn by the owner with [`withdraw_proceeds`].
+/// Returns a [`ContractError`] on failure, otherwise returns a [`Response`] with the specified
+/// attributes if the operation was successful.
+/// ## Params
+/// * **deps** is an object of type [`DepsMut`].
+///
+/// * **env** is an object of type [`Env`].
+///
+/// * **info** is an object of type [`MessageInfo`].
+///
+/// * **assets** is an array with two objects of type [`Asset`]. These are the assets available in the pool.
+// NOTE - the owner should approve the pair contract to pull its relevant tokens.
+pub fn provide_liquidity(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    assets: [Asset; 2],
+) -> Result<Response, ContractError> {
+    assert_deposits_allowed(deps.storage)?;
+    assets[0].info.check(deps.api)?;
+    assets[1].info.check(deps.api)?;
+
+    let mut config: Config = CONFIG.load(deps.storage)?;
+
+    if info.sender != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    if env.block.time.seconds() >= config.end_time {
+        return Err(ContractError::LiquidityProvisionEnded {});
+    }
+
+    for asset in assets.iter() {
+        asset.assert_sent_native_token_balance(&info)?;
+    }
+
+    // No MINIMUM_LIQUIDITY is locked: only the owner can deposit and no LP tokens are minted
+    let pools = query_reserves(deps.storage, &config)?;
+    let deposits = get_deposits(&assets, &pools)?;
+
+    let mut messages: Vec<CosmosMsg> = vec![];
+    for (i, pool) in pools.iter().enumerate() {
+        // If the asset is a token contract, then we need to execute a TransferFrom msg to receive assets
+        if let AssetInfo::Token { contract_addr, .. } = &pool.info {
+            messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: contract_addr.to_string(),
+                msg: to_binary(&Cw20ExecuteMsg::TransferFrom {
+                    owner: info.sender.to_string(),
+                    recipient: env.contract.address.to_string(),
+                    amount: deposits[i],
+                })?,
+                funds: vec![],
+            }));
+        }
+    }
+
+    // Accumulate prices for the assets in the pool
+    if let Some((price0_cumulative_new, price1_cumulative_new, block_time)) =
+        accumulate_prices(env, &config, pools[0].amount, pools[1].amount)?
+    {
+        config.price0_cumulative_last = price0_cumulative_new;
+        config.price1_cumulative_last = price1_cumulative_new;
+        config.block_time_last = block_time;
+        CONFIG.save(deps.storage, &config)?;
+    }
+
+    RESERVES.save(
+        deps.storage,
+        &[
+            pools[0].amount.checked_add(deposits[0])?,
+            pools[1].amount.checked_add(deposits[1])?,
+        ],
+    )?;
+
+    Ok(Response::new().add_messages(messages).add_attributes(vec![
+        attr("action", "provide_liquidity"),
+        attr("sender", info.sender.as_str()),
+        attr("assets", format!("{}, {}", assets[0], assets[1])),
+    ]))
+}
+
+/// ## Description
+/// Returns the deposit amounts in the order of the pool assets. Both pool assets must be deposited.
+/// ## Params
+/// * **assets** is an array with two objects of type [`Asset`]. These are the assets to deposit.
+///
+/// * **pools** are an array of [`Asset`] type items. These are the assets available in the pool.
+fn get_deposits(assets: &[Asset; 2], pools: &[Asset; 2]) -> Result<[Uint128; 2], ContractError> {
+    let deposit = |pool: &Asset| {
+        assets
+            .iter()
+            .find(|a| a.info.equal(&pool.info))
+            .map(|a| a.amount)
+            .ok_or(ContractError::AssetMismatch {})
+    };
+    let deposits = [deposit(&pools[0])?, deposit(&pools[1])?];
+
+    if deposits[0].is_zero() || deposits[1].is_zero() {
+        return Err(ContractError::InvalidZeroAmount {});
+    }
+
+    Ok(deposits)
+}
+
+/// ## Description
+/// Withdraws all pool assets after the end time. Only the pool owner can execute this.
+/// Returns a [`ContractError`] on failure, otherwise returns a [`Response`] with the specified
+/// attributes if the operation was successful.
+/// ## Params
+/// * **deps** is an object of type [`DepsMut`].
+///
+/// * **env** is an object of type [`Env`].
+///
+/// * **info** is an object of type [`MessageInfo`].
+///
+/// * **receiver** is an [`Option`] field of type [`String`]. This is the receiver of the pool assets.
+/// If no custom receiver is specified, the assets are sent to the owner.
+pub fn withdraw_proceeds(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    receiver: Option<String>,
+) -> Result<Response, ContractError> {
+    let config: Config = CONFIG.load(deps.storage)?;
+
+    if info.sender != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    if env.block.time.seconds() < config.end_time {
+        return Err(ContractError::WithdrawBeforeEnd {});
+    }
+
+    let receiver = if let Some(receiver) = receiver {
+        addr_validate_to_lower(deps.api, &receiver)?
+    } else {
+        info.sender
+    };
+
+    // All balances of the pair are withdrawn, including the assets that were not synced
+    let pools: [Asset; 2] = config
+        .pair_info
+        .query_pools(&deps.querier, env.contract.address)?;
+
+    let mut messages: Vec<CosmosMsg> = vec![];
+    for pool in pools.iter().filter(|p| !p.amount.is_zero()) {
+        messages.push(pool.clone().into_msg(&deps.querier, receiver.clone())?);
+    }
+
+    RESERVES.save(deps.storage, &[Uint128::zero(), Uint128::zero()])?;
+
+    Ok(Response::new().add_messages(messages).add_attributes(vec![
+        attr("action", "withdraw_proceeds"),
+        attr("receiver", receiver.as_str()),
+        attr("assets", format!("{}, {}", pools[0], pools[1])),
+    ]))
+}
+
+/// ## Description
+/// Performs an swap operation with the specified parameters using the current pool weights.
+/// The trader must approve the pool contract to transfer offer assets from their wallet.
+/// Returns an [`ContractError`] on failure, otherwise returns the [`Response`] with the specified attributes if the operation was successful.
+/// ## Params
+/// * **deps** is an object of type [`DepsMut`].
+///
+/// * **env** is an object of type [`Env`].
+///
+/// * **info** is an object of type [`MessageInfo`].
+///
+/// * **sender** is an object of type [`Addr`]. This is the sender of the swap operation.
+///
+/// * **offer_asset** is an object of type [`Asset`]. Proposed asset for swapping.
+///
+/// * **belief_price** is an object of type [`Option<Decimal>`]. Used to calculate the maximum swap spread.
+///
+/// * **max_spread** is an object of type [`Option<Decimal>`]. Sets the maximum spread of the swap operation.
+///
+/// * **to** is an object of type [`Option<Addr>`]. Sets the recipient of the swap operation.
+///
+/// * **min_return** is an object of type [`Option<Uint128>`]. This is the minimum amount of ask assets to receive.
+///
+/// * **deadline** is an object of type [`Option<u64>`]. This is the block time (in seconds) after which the swap fails.
+/// NOTE - the address that wants to swap should approve the pair contract to pull the offer token.
+#[allow(clippy::too_many_arguments)]
+pub fn swap(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    sender: Addr,
+    offer_asset: Asset,
+    belief_price: Option<Decimal>,
+    max_spread: Option<Decimal>,
+    to: Option<Addr>,
+    min_return: Option<Uint128>,
+    deadline: Option<u64>,
+) -> Result<Response, ContractError> {
+    assert_deadline(&env, deadline)?;
+    assert_swaps_allowed(deps.storage)?;
+    offer_asset.assert_sent_native_token_balance(&info)?;
+
+    let mut config: Config = CONFIG.load(deps.storage)?;
+
+    if config.is_paused {
+        return Err(ContractError::Paused {});
+    }
+
+    let block_time = env.block.time.seconds();
+    if block_time < config.start_time || block_time >= config.end_time {
+        return Err(ContractError::SwapsDisabled {});
+    }
+
+    let pools = query_reserves(deps.storage, &config)?;
+
+    let (offer_index, ask_index) = if offer_asset.info.equal(&pools[0].info) {
+        (0, 1)
+    } else if offer_asset.info.equal(&pools[1].info) {
+        (1, 0)
+    } else {
+        return Err(ContractError::AssetMismatch {});
+    };
+    let offer_pool = pools[offer_index].clone();
+    let ask_pool = pools[ask_index].clone();
+
+    // Get the fee info cached from the factory
+    let fee_info = FACTORY_PARAMS.load(deps.storage)?.fee_info;
+
+    let weights = compute_current_weights(&config, &env)?;
+    let offer_amount = offer_asset.amount;
+    let (return_amount, spread_amount, commission_amount) = compute_swap(
+        offer_pool.amount,
+        weights[offer_index],
+        ask_pool.amount,
+        weights[ask_index],
+        offer_amount,
+        fee_info.total_fee_rate,
+    )?;
+
+    // Check the max spread limit (if it was specified)
+    assert_max_spread(
+        belief_price,
+        max_spread,
+        offer_amount,
+        return_amount + commission_amount,
+        spread_amount,
+    )?;
+
+    // Check the minimum return amount (if it was specified)
+    if let Some(min_return) = min_return {
+        if return_amount < min_return {
+            return Err(ContractError::MinReturnAssertion {});
+        }
+    }
+
+    // Compute the tax for the receiving asset (if it is a native one)
+    let return_asset = Asset {
+        info: ask_pool.info.clone(),
+        amount: return_amount,
+    };
+
+    let tax_amount = return_asset.compute_tax(&deps.querier)?;
+    let receiver = to.unwrap_or_else(|| sender.clone());
+    let mut messages: Vec<CosmosMsg> =
+        vec![return_asset.into_msg(&deps.querier, receiver.clone())?];
+
+    // Compute the Maker fee
+    let mut maker_fee_amount = Uint128::new(0);
+    if let Some(fee_address) = fee_info.fee_address {
+        if let Some(f) = calculate_maker_fee(
+            ask_pool.info.clone(),
+            commission_amount,
+            fee_info.maker_fee_rate,
+        ) {
+            messages.push(f.clone().into_msg(&deps.querier, fee_address)?);
+            maker_fee_amount = f.amount;
+        }
+    }
+
+    // Accumulate prices for the assets in the pool
+    if let Some((price0_cumulative_new, price1_cumulative_new, block_time)) =
+        accumulate_prices(env, &config, pools[0].amount, pools[1].amount)?
+    {
+        config.price0_cumulative_last = price0_cumulative_new;
+        config.price1_cumulative_last = price1_cumulative_new;
+        config.block_time_last = block_time;
+        CONFIG.save(deps.storage, &config)?;
+    }
+
+    save_reserves_after_swap(
+        deps.storage,
+        &pools,
+        &offer_asset.info,
+        offer_amount,
+        return_amount + maker_fee_amount,
+    )?;
+
+    Ok(Response::new()
+        .add_messages(
+            // 1. send collateral tokens from the contract to a user
+            // 2. send inactive commission fees to the Maker ontract
+            messages,
+        )
+        .add_attribute("action", "swap")
+        .add_attribute("sender", sender.as_str())
+        .add_attribute("receiver", receiver.as_str())
+        .add_attribute("offer_asset", offer_asset.info.to_string())
+        .add_attribute("ask_asset", ask_pool.info.to_string())
+        .add_attribute("offer_amount", offer_amount.to_string())
+        .add_attribute("return_amount", return_amount.to_string())
+        .add_attribute("tax_amount", tax_amount.to_string())
+        .add_attribute("spread_amount", spread_amount.to_string())
+        .add_attribute("commission_amount", commission_amount.to_string())
+        .add_attribute("maker_fee_amount", maker_fee_amount.to_string()))
+}
+
+/// ## Description
+/// Sets the tracked pool reserves to the current balances of the pair, so that assets sent to the pair
+/// directly are added to the pool. Cumulative prices are accumulated using the reserves before the sync.
+/// Returns a [`ContractError`] on failure, otherwise returns a [`Response`] with the specified
+/// attributes if the operation was successful.
+/// ## Params
+/// * **deps** is an object of type [`DepsMut`].
+///
+/// * **env** is an object of type [`Env`].
+pub fn sync(deps: DepsMut, env: Env) -> Result<Response, ContractError> {
+    let mut config: Config = CONFIG.load(deps.storage)?;
+    let reserves = query_reserves(deps.storage, &config)?;
+
+    // Accumulate prices for the assets in the pool
+    if let Some((price0_cumulative_new, price1_cumulative_new, block_time)) =
+        accumulate_prices(env.clone(), &config, reserves[0].amount, reserves[1].amount)?
+    {
+        config.price0_cumulative_last = price0_cumulative_new;
+        config.price1_cumulative_last = price1_cumulative_new;
+        config.block_time_last = block_time;
+        CONFIG.save(deps.storage, &config)?;
+    }
+
+    let pools = config
+        .pair_info
+        .query_pools(&deps.querier, env.contract.address)?;
+    RESERVES.save(deps.storage, &[pools[0].amount, pools[1].amount])?;
+
+    Ok(Response::new()
+        .add_attribute("action", "sync")
+        .add_attribute("reserves", format!("{}, {}", pools[0], pools[1])))
+}
+
+/// ## Description
+/// Sends the balances of the pair that exceed the tracked pool reserves to a recipient.
+/// Returns a [`ContractError`] on failure, otherwise returns a [`Response`] with the specified
+/// attributes if the operation was successful.
+/// ## Params
+/// * **deps** is an object of type [`DepsMut`].
+///
+/// * **env** is an object of type [`Env`].
+///
+/// * **info** is an object of type [`MessageInfo`].
+///
+/// * **to** is an [`Option`] field of type [`String`]. This is the recipient of the excess assets.
+/// If no custom recipient is specified, the assets are sent to the function caller.
+pub fn skim(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    to: Option<String>,
+) -> Result<Response, ContractError> {
+    let config: Config = CONFIG.load(deps.storage)?;
+    let reserves = query_reserves(deps.storage, &config)?;
+    let pools = config
+        .pair_info
+        .query_pools(&deps.querier, env.contract.address)?;
+
+    let to = addr_validate_to_lower(deps.api, &to.unwrap_or_else(|| info.sender.to_string()))?;
+
+    let mut messages: Vec<CosmosMsg> = vec![];
+    let mut excess_assets: Vec<Asset> = vec![];
+    for (pool, reserve) in pools.iter().zip(reserves.iter()) {
+        let excess = Asset {
+            info: pool.info.clone(),
+            amount: pool.amount.saturating_sub(reserve.amount),
+        };
+        if !excess.amount.is_zero() {
+            messages.push(excess.clone().into_msg(&deps.querier, to.clone())?);
+        }
+        excess_assets.push(excess);
+    }
+
+    Ok(Response::new()
+        .add_messages(messages)
+        .add_attribute("action", "skim")
+        .add_attribute("receiver", to.as_str())
+        .add_attribute(
+            "assets",
+            format!("{}, {}", excess_assets[0], excess_assets[1]),
+        ))
+}
+
+/// ## Description
+/// Accumulate token prices for the assets in the pool. The prices are the weighted spot prices
+/// of the assets using the current pool weights.
+/// Note that this function shifts **block_time** when any of the token prices is zero in order to not
+/// fill an accumulator with a null price for that period.
+/// ## Params
+/// * **env** is an object of type [`Env`].
+///
+/// * **config** is an object of type [`Config`].
+///
+/// * **x** is an object of type [`Uint128`]. This is the balance of asset\[\0] in the pool.
+///
+/// * **y** is an object of type [`Uint128`]. This is the balance of asset\[\1] in the pool.
+pub fn accumulate_prices(
+    env: Env,
+    config: &Config,
+    x: Uint128,
+    y: Uint128,
+) -> StdResult<Option<(Uint128, Uint128, u64)>> {
+    let block_time = env.block.time.seconds();
+    if block_time <= config.block_time_last {
+        return Ok(None);
+    }
+
+    // We have to shift block_time when any price is zero in order to not fill an accumulator with a null price for that period
+    let time_elapsed = Uint128::from(block_time - config.block_time_last);
+
+    let mut pcl0 = config.price0_cumulative_last;
+    let mut pcl1 = config.price1_cumulative_last;
+
+    // Scale the pool balances by the asset weights: price0 = (y / w1) / (x / w0)
+    let weights = compute_current_weights(config, &env)?;
+    let x_weighted = x * weights[1];
+    let y_weighted = y * weights[0];
+
+    if !x_weighted.is_zero() && !y_weighted.is_zero() {
+        let price_precision = Uint128::from(10u128.pow(TWAP_PRECISION.into()));
+        pcl0 = config.price0_cumulative_last.wrapping_add(
+            time_elapsed
+                .checked_mul(price_precision)?
+                .multiply_ratio(y_weighted, x_weighted),
+        );
+        pcl1 = config.price1_cumulative_last.wrapping_add(
+            time_elapsed
+                .checked_mul(price_precision)?
+                .multiply_ratio(x_weighted, y_weighted),
+        );
+    };
+
+    Ok(Some((pcl0, pcl1, block_time)))
+}
+
+/// ## Description
+/// Calculates the amount of fees the Maker contract gets according to specified pair parameters.
+/// Returns a [`None`] if the Maker fee is zero, otherwise returns a [`Asset`] struct with the specified attributes.
+/// ## Params
+/// * **pool_info** is an object of type [`AssetInfo`]. Contains information about the pool asset for which the commission will be calculated.
+///
+/// * **commission_amount** is an object of type [`Env`]. This is the total amount of fees charged for a swap.
+///
+/// * **maker_commission_rate** is an object of type [`MessageInfo`]. This is the percentage of fees that go to the Maker contract.
+pub fn calculate_maker_fee(
+    pool_info: AssetInfo,
+    commission_amount: Uint128,
+    maker_commission_rate: Decimal,
+) -> Option<Asset> {
+    let maker_fee: Uint128 = commission_amount * maker_commission_rate;
+    if maker_fee.is_zero() {
+        return None;
+    }
+
+    Some(Asset {
+        info: pool_info,
+        amount: maker_fee,
+    })
+}
+
+/// ## Description
+/// Exposes all the queries available in the contract.
+/// ## Params
+/// * **deps** is an object of type [`Deps`].
+///
+/// * **env** is an object of type [`Env`].
+///
+/// * **msg** is an object of type [`QueryMsg`].
+///
+/// ## Queries
+/// * **QueryMsg::Pair {}** Returns information about the pair in an object of type [`PairInfo`].
+///
+/// * **QueryMsg::Pool {}** Returns information about the amount of assets in the pair contract as
+/// well as the amount of LP tokens issued using an object of type [`PoolResponse`].
+///
+/// * **QueryMsg::Share { amount }** Returns the amount of assets that could be withdrawn from the pool
+/// using a specific amount of LP tokens. The result is returned in a vector that contains objects of type [`Asset`].
+///
+/// * **QueryMsg::Simulation { offer_asset }** Returns the result of a swap simulation using a [`SimulationResponse`] object.
+///
+/// * **QueryMsg::ReverseSimulation { ask_asset }** Returns the result of a reverse swap simulation  using
+/// a [`ReverseSimulationResponse`] object.
+///
+/// * **QueryMsg::CumulativePrices {}** Returns information about cumulative prices for the assets in the
+/// pool using a [`CumulativePricesResponse`] object.
+///
+/// * **QueryMsg::Config {}** Returns the configuration for the pair contract using a [`ConfigResponse`] object.
+///
+/// * **QueryMsg::ProtocolFees {}** Not supported.
+///
+/// * **QueryMsg::Observe { .. }** Not supported.
+///
+/// * **QueryMsg::SpotPrice { .. }**, **QueryMsg::PriceImpact { .. }** and **QueryMsg::Depth { .. }** Not supported.
+///
+/// * **QueryMsg::SimulateProvide { assets, .. }** Returns the amount of LP tokens minted for a deposit
+/// in a [`SimulateProvideResponse`] object. The pool doesn't mint LP tokens, so the share is always zero.
+///
+/// * **QueryMsg::SimulateWithdraw { lp_amount }** Returns the amounts of assets received for burning LP tokens
+/// in a vector that contains objects of type [`Asset`]. The pool doesn't mint LP tokens, so the amounts are always zero.
+///
+/// * **QueryMsg::SimulateWithdrawOneCoin { .. }** and **QueryMsg::SimulateWithdrawImbalanced { .. }** Not supported.
+///
+/// * **QueryMsg::VirtualPrice {}**, **QueryMsg::CurrentD {}** and **QueryMsg::AmpHistory { .. }** Not supported.
+///
+/// * **QueryMsg::PairStatus {}** Returns the trading state of the pair set in the factory using a [`astroport::factory::PairStatus`] object.
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
+    match msg {
+        QueryMsg::Pair {} => to_binary(&query_pair_info(deps)?),
+        QueryMsg::Pool {} => to_binary(&query_pool(deps)?),
+        QueryMsg::Share { amount } => to_binary(&query_share(deps, amount)?),
+        QueryMsg::Simulation { offer_asset } => {
+            to_binary(&query_simulation(deps, env, offer_asset)?)
+        }
+        QueryMsg::ReverseSimulation { ask_asset } => {
+            to_binary(&query_reverse_simulation(deps, env, ask_asset)?)
+        }
+        QueryMsg::CumulativePrices {} => to_binary(&query_cumulative_prices(deps, env)?),
+        QueryMsg::Config {} => to_binary(&query_config(deps, env)?),
+        QueryMsg::SimulateProvide { assets, .. } => {
+            to_binary(&query_simulate_provide(deps, env, assets)?)
+        }
+        QueryMsg::SimulateWithdraw { lp_amount } => {
+            to_binary(&query_simulate_withdraw(deps, lp_amount)?)
+        }
+        QueryMsg::ProtocolFees {} => Err(StdError::generic_err("Operation non supported")),
+        QueryMsg::Observe { .. }
+        | QueryMsg::SpotPrice { .. }
+        | QueryMsg::PriceImpact { .. }
+        | QueryMsg::Depth { .. }
+        | QueryMsg::SimulateWithdrawOneCoin { .. }
+        | QueryMsg::SimulateWithdrawImbalanced { .. }
+        | QueryMsg::VirtualPrice {}
+        | QueryMsg::CurrentD {}
+        | QueryMsg::AmpHistory { .. } => Err(StdError::generic_err("Operation non supported")),
+        QueryMsg::PairStatus {} => to_binary(&FACTORY_PARAMS.load(deps.storage)?.pair_status),
+    }
+}
+
+/// ## Description
+/// Returns information about the pair contract in an object of type [`PairInfo`].
+/// ## Params
+/// * **deps** is an object of type [`Deps`].
+pub fn query_pair_info(deps: Deps) -> StdResult<PairInfo> {
+    let config: Config = CONFIG.load(deps.storage)?;
+    Ok(config.pair_info)
+}
+
+/// ## Description
+/// Returns the amounts of assets in the pair contract as well as the amount of LP
+/// tokens currently minted in an object of type [`PoolResponse`].
+/// ## Params
+/// * **deps** is an object of type [`Deps`].
+pub fn query_pool(deps: Deps) -> StdResult<PoolResponse> {
+    let config: Config = CONFIG.load(deps.storage)?;
+    let (assets, total_share) = pool_info(deps, config)?;
+
+    let resp = PoolResponse {
+        assets,
+        total_share,
+    };
+
+    Ok(resp)
+}
+
+/// ## Description
+/// Returns the amount of assets that could be withdrawn from the pool using a specific amount of LP tokens.
+/// The pool doesn't mint LP tokens, so the returned amounts are always zero.
+/// ## Params
+/// * **deps** is an object of type [`Deps`].
+///
+/// * **amount** is an object of type [`Uint128`]. This is the amount of LP tokens for which we calculate associated amounts of assets.
+pub fn query_share(deps: Deps, amount: Uint128) -> StdResult<Vec<Asset>> {
+    let config: Config = CONFIG.load(deps.storage)?;
+    let (pools, total_share) = pool_info(deps, config)?;
+
+    Ok(get_share_in_assets(&pools, amount, total_share))
+}
+
+/// ## Description
+/// Returns the amount of pool assets that correspond to an amount of LP tokens.
+/// ## Params
+/// * **pools** are an array of [`Asset`] type items. These are the assets in the pool.
+///
+/// * **amount** is an object of type [`Uint128`]. This is the amount of LP tokens to compute a corresponding amount of assets for.
+///
+/// * **total_share** is an object of type [`Uint128`]. This is the total amount of LP tokens currently minted.
+pub fn get_share_in_assets(
+    pools: &[Asset; 2],
+    amount: Uint128,
+    total_share: Uint128,
+) -> Vec<Asset> {
+    let mut share_ratio = Decimal::zero();
+    if !total_share.is_zero() {
+        share_ratio = Decimal::from_ratio(amount, total_share);
+    }
+
+    pools
+        .iter()
+        .map(|a| Asset {
+            info: a.info.clone(),
+            amount: a.amount * share_ratio,
+        })
+        .collect()
+}
+
+/// ## Description
+/// Returns the amount of LP tokens minted for a deposit in an object of type [`SimulateProvideResponse`].
+/// The deposit is validated in the same way as in [`provide_liquidity`]. The pool doesn't mint LP tokens
+/// or charge fees on deposits, so the share and the fees are always zero.
+/// ## Params
+/// * **deps** is an object of type [`Deps`].
+///
+/// * **env** is an object of type [`Env`].
+///
+/// * **assets** is an array with two objects of type [`Asset`]. These are the assets to deposit.
+pub fn query_simulate_provide(
+    deps: Deps,
+    env: Env,
+    assets: [Asset; 2],
+) -> StdResult<SimulateProvideResponse> {
+    let config: Config = CONFIG.load(deps.storage)?;
+
+    if env.block.time.seconds() >= config.end_time {
+        return Err(ContractError::LiquidityProvisionEnded {}.into());
+    }
+
+    let (pools, _) = pool_info(deps, config)?;
+    get_deposits(&assets, &pools)?;
+
+    Ok(SimulateProvideResponse {
+        share: Uint128::zero(),
+        fees: pools
+            .iter()
+            .map(|pool| Asset {
+                info: pool.info.clone(),
+                amount: Uint128::zero(),
+            })
+            .collect(),
+    })
+}
+
+/// ## Description
+/// Returns the amounts of assets received for burning LP tokens in a [`Vec<Asset>`] object.
+/// The amounts are calculated in the same way as in [`query_share`], so they are always zero.
+/// Pool assets can only be withdrawn by the owner with [`withdraw_proceeds`].
+/// ## Params
+/// * **deps** is an object of type [`Deps`].
+///
+/// * **lp_amount** is an object of type [`Uint128`]. This is the amount of LP tokens to burn.
+pub fn query_simulate_withdraw(deps: Deps, lp_amount: Uint128) -> StdResult<Vec<Asset>> {
+    let config: Config = CONFIG.load(deps.storage)?;
+    let (pools, total_share) = pool_info(deps, config)?;
+
+    Ok(get_share_in_assets(&pools, lp_amount, total_share))
+}
+
+/// ## Description
+/// Returns information about a swap simulation in a [`SimulationResponse`] object.
+/// ## Params
+/// * **deps** is an object of type [`Deps`].
+///
+/// * **env** is an object of type [`Env`].
+///
+/// * **offer_asset** is an object of type [`Asset`]. This is the asset to swap as well as an amount of the said asset.
+pub fn query_simulation(deps: Deps, env: Env, offer_asset: Asset) -> StdResult<SimulationResponse> {
+    let config: Config = CONFIG.load(deps.storage)?;
+    let pools = query_reserves(deps.storage, &config)?;
+
+    let (offer_index, ask_index) = if offer_asset.info.equal(&pools[0].info) {
+        (0, 1)
+    } else if offer_asset.info.equal(&pools[1].info) {
+        (1, 0)
+    } else {
+        return Err(StdError::generic_err(
+            "Given offer asset does not belong in the pair",
+        ));
+    };
+
+    // Get the fee info cached from the factory
+    let fee_info = FACTORY_PARAMS.load(deps.storage)?.fee_info;
+
+    let weights = compute_current_weights(&config, &env)?;
+    let (return_amount, spread_amount, commission_amount) = compute_swap(
+        pools[offer_index].amount,
+        weights[offer_index],
+        pools[ask_index].amount,
+        weights[ask_index],
+        offer_asset.amount,
+        fee_info.total_fee_rate,
+    )
+    .map_err(|e| StdError::generic_err(e.to_string()))?;
+
+    Ok(SimulationResponse {
+        return_amount,
+        spread_amount,
+        commission_amount,
+    })
+}
+
+/// ## Description
+/// Returns information about a reverse swap simulation in a [`ReverseSimulationResponse`] object.
+/// ## Params
+/// * **deps** is an object of type [`Deps`].
+///
+/// * **env** is an object of type [`Env`].
+///
+/// * **ask_asset** is an object of type [`Asset`]. This is the asset to swap to as well as the desired
+/// amount of ask assets to receive from the swap.
+pub fn query_reverse_simulation(
+    deps: Deps,
+    env: Env,
+    ask_asset: Asset,
+) -> StdResult<ReverseSimulationResponse> {
+    let config: Config = CONFIG.load(deps.storage)?;
+    let pools = query_reserves(deps.storage, &config)?;
+
+    let (offer_index, ask_index) = if ask_asset.info.equal(&pools[0].info) {
+        (1, 0)
+    } else if ask_asset.info.equal(&pools[1].info) {
+        (0, 1)
+    } else {
+        return Err(StdError::generic_err(
+            "Given ask asset doesn't belong to pairs",
+        ));
+    };
+
+    // Get the fee info cached from the factory
+    let fee_info = FACTORY_PARAMS.load(deps.storage)?.fee_info;
+
+    let weights = compute_current_weights(&config, &env)?;
+    let (offer_amount, spread_amount, commission_amount) = compute_offer_amount(
+        pools[offer_index].amount,
+        weights[offer_index],
+        pools[ask_index].amount,
+        weights[ask_index],
+        ask_asset.amount,
+        fee_info.total_fee_rate,
+    )
+    .map_err(|e| StdError::generic_err(e.to_string()))?;
+
+    Ok(ReverseSimulationResponse {
+        offer_amount,
+        spread_amount,
+        commission_amount,
+    })
+}
+
+/// ## Description
+/// Returns information about cumulative prices for the assets in the pool using a [`CumulativePricesResponse`] object.
+/// ## Params
+/// * **deps** is an object of type [`Deps`].
+///
+/// * **env** is an object of type [`Env`].
+pub fn query_cumulative_prices(deps: Deps, env: Env) -> StdResult<CumulativePricesResponse> {
+    let config: Config = CONFIG.load(deps.storage)?;
+    let (assets, total_share) = pool_info(deps, config.clone())?;
+
+    let mut price0_cumulative_last = config.price0_cumulative_last;
+    let mut price1_cumulative_last = config.price1_cumulative_last;
+
+    if let Some((price0_cumulative_new, price1_cumulative_new, _)) =
+        accumulate_prices(env, &config, assets[0].amount, assets[1].amount)?
+    {
+        price0_cumulative_last = price0_cumulative_new;
+        price1_cumulative_last = price1_cumulative_new;
+    }
+
+    let resp = CumulativePricesResponse {
+        assets,
+        total_share,
+        price0_cumulative_last,
+        price1_cumulative_last,
+        price0_cumulative_last_u256: None,
+        price1_cumulative_last_u256: None,
+    };
+
+    Ok(resp)
+}
+
+/// ## Description
+/// Returns the pair contract configuration in a [`ConfigResponse`] object.
+/// The weight schedule and the current weights are returned in a [`LbpPoolConfig`] object serialized in `params`.
+/// ## Params
+/// * **deps** is an object of type [`Deps`].
+///
+/// * **env** is an object of type [`Env`].
+pub fn query_config(deps: Deps, env: Env) -> StdResult<ConfigResponse> {
+    let config: Config = CONFIG.load(deps.storage)?;
+    Ok(ConfigResponse {
+        block_time_last: config.block_time_last,
+        params: Some(to_binary(&LbpPoolConfig {
+            owner: config.owner.clone(),
+            start_weights: config.start_weights,
+            end_weights: config.end_weights,
+            start_time: config.start_time,
+            end_time: config.end_time,
+            current_weights: compute_current_weights(&config, &env)?,
+            is_paused: config.is_paused,
+        })?),
+    })
+}
+
+/// ## Description
+/// Returns the result of a swap.
+/// ## Params
+/// * **offer_pool** is an object of type [`Uint128`]. This is the total amount of offer assets in the pool.
+///
+/// * **offer_weight** is an object of type [`Decimal`]. This is the normalized weight of the offer asset.
+///
+/// * **ask_pool** is an object of type [`Uint128`]. This is the total amount of ask assets in the pool.
+///
+/// * **ask_weight** is an object of type [`Decimal`]. This is the normalized weight of the ask asset.
+///
+/// * **offer_amount** is an object of type [`Uint128`]. This is the amount of offer assets to swap.
+///
+/// * **commission_rate** is an object of type [`Decimal`]. This is the total amount of fees charged for the swap.
+pub fn compute_swap(
+    offer_pool: Uint128,
+    offer_weight: Decimal,
+    ask_pool: Uint128,
+    ask_weight: Decimal,
+    offer_amount: Uint128,
+    commission_rate: Decimal,
+) -> Result<(Uint128, Uint128, Uint128), ContractError> {
+    if offer_amount > offer_pool.multiply_ratio(MAX_IN_RATIO, 100u64) {
+        return Err(ContractError::MaxInRatioAssertion {});
+    }
+
+    let return_amount = Uint128::new(
+        calc_ask_amount(
+            offer_pool.u128(),
+            to_fixed(offer_weight),
+            ask_pool.u128(),
+            to_fixed(ask_weight),
+            offer_amount.u128(),
+        )
+        .ok_or(ContractError::MathOverflow {})?,
+    );
+
+    // Calculate spread & commission
+    let spot_price = calc_spot_price(
+        offer_pool.u128(),
+        to_fixed(offer_weight),
+        ask_pool.u128(),
+        to_fixed(ask_weight),
+    )
+    .ok_or(ContractError::MathOverflow {})?;
+    let spread_amount = offer_amount
+        .multiply_ratio(spot_price, ONE)
+        .saturating_sub(return_amount);
+    let commission_amount: Uint128 = return_amount * commission_rate;
+
+    // The commision (minus the part that goes to the Maker contract) will be absorbed by the pool
+    let return_amount: Uint128 = return_amount.checked_sub(commission_amount)?;
+    Ok((return_amount, spread_amount, commission_amount))
+}
+
+/// ## Description
+/// Returns an amount of offer assets for a specified amount of ask assets.
+/// ## Params
+/// * **offer_pool** is an object of type [`Uint128`]. This is the total amount of offer assets in the pool.
+///
+/// * **offer_weight** is an object of type [`Decimal`]. This is the normalized weight of the offer asset.
+///
+/// * **ask_pool** is an object of type [`Uint128`]. This is the total amount of ask assets in the pool.
+///
+/// * **ask_weight** is an object of type [`Decimal`]. This is the normalized weight of the ask asset.
+///
+/// * **ask_amount** is an object of type [`Uint128`]. This is the amount of ask assets to swap to.
+///
+/// * **commission_rate** is an object of type [`Decimal`]. This is the total amount of fees charged for the swap.
+fn compute_offer_amount(
+    offer_pool: Uint128,
+    offer_weight: Decimal,
+    ask_pool: Uint128,
+    ask_weight: Decimal,
+    ask_amount: Uint128,
+    commission_rate: Decimal,
+) -> Result<(Uint128, Uint128, Uint128), ContractError> {
+    let one_minus_commission = ONE
+        .checked_sub(to_fixed(commission_rate))
+        .filter(|v| *v != 0)
+        .ok_or(ContractError::MathOverflow {})?;
+    let before_commission_deduction = ask_amount.multiply_ratio(ONE, one_minus_commission);
+
+    if before_commission_deduction > ask_pool.multiply_ratio(MAX_OUT_RATIO, 100u64) {
+        return Err(ContractError::MaxOutRatioAssertion {});
+    }
+
+    let offer_amount = Uint128::new(
+        calc_offer_amount(
+            offer_pool.u128(),
+            to_fixed(offer_weight),
+            ask_pool.u128(),
+            to_fixed(ask_weight),
+            before_commission_deduction.u128(),
+        )
+        .ok_or(ContractError::MathOverflow {})?,
+    );
+
+    let spot_price = calc_spot_price(
+        offer_pool.u128(),
+        to_fixed(offer_weight),
+        ask_pool.u128(),
+        to_fixed(ask_weight),
+    )
+    .ok_or(ContractError::MathOverflow {})?;
+    let spread_amount = offer_amount
+        .multiply_ratio(spot_price, ONE)
+        .saturating_sub(before_commission_deduction);
+    let commission_amount = before_commission_deduction * commission_rate;
+    Ok((offer_amount, spread_amount, commission_amount))
+}
+
+/// ## Description
+/// Converts a [`Decimal`] into a fixed point number with 18 decimals used in the weighted math.
+fn to_fixed(value: Decimal) -> u128 {
+    (Uint128::new(ONE) * value).u128()
+}
+
+/// ## Description
+/// Returns a [`ContractError`] if the factory paused swaps in the pair.
+/// ## Params
+/// * **storage** is a reference to an object implementing [`Storage`].
+fn assert_swaps_allowed(storage: &dyn Storage) -> Result<(), ContractError> {
+    if !FACTORY_PARAMS.load(storage)?.pair_status.swaps_allowed() {
+        return Err(ContractError::SwapsPaused {});
+    }
+
+    Ok(())
+}
+
+/// ## Description
+/// Returns a [`ContractError`] if the factory paused deposits in the pair.
+/// ## Params
+/// * **storage** is a reference to an object implementing [`Storage`].
+fn assert_deposits_allowed(storage: &dyn Storage) -> Result<(), ContractError> {
+    if !FACTORY_PARAMS.load(storage)?.pair_status.deposits_allowed() {
+        return Err(ContractError::DepositsPaused {});
+    }
+
+    Ok(())
+}
+
+/// ## Description
+/// Returns a [`ContractError`] on failure.
+/// If `belief_price` and `max_spread` are both specified, we compute a new spread,
+/// otherwise we just use the swap spread to check `max_spread`.
+/// ## Params
+/// * **belief_price** is an object of type [`Option<Decimal>`]. This is the belief price used in the swap.
+///
+/// * **max_spread** is an object of type [`Option<Decimal>`]. This is the
+/// max spread allowed so that the swap can be executed successfuly.
+///
+/// * **offer_amount** is an object of type [`Uint128`]. This is the amount of assets to swap.
+///
+/// * **return_amount** is an object of type [`Uint128`]. This is the amount of assets to receive from the swap.
+///
+/// * **spread_amount** is an object of type [`Uint128`]. This is the spread used in the swap.
+pub fn assert_max_spread(
+    belief_price: Option<Decimal>,
+    max_spread: Option<Decimal>,
+    offer_amount: Uint128,
+    return_amount: Uint128,
+    spread_amount: Uint128,
+) -> Result<(), ContractError> {
+    let default_spread = Decimal::from_str(DEFAULT_SLIPPAGE)?;
+    let max_allowed_spread = Decimal::from_str(MAX_ALLOWED_SLIPPAGE)?;
+
+    let max_spread = max_spread.unwrap_or(default_spread);
+    if max_spread.gt(&max_allowed_spread) {
+        return Err(ContractError::AllowedSpreadAssertion {});
+    }
+
+    if let Some(belief_price) = belief_price {
+        let expected_return =
+            offer_amount * Decimal::from(Decimal256::one() / Decimal256::from(belief_price));
+        let spread_amount = expected_return
+            .checked_sub(return_amount)
+            .unwrap_or_else(|_| Uint128::zero());
+
+        if return_amount < expected_return
+            && Decimal::from_ratio(spread_amount, expected_return) > max_spread
+        {
+            return Err(ContractError::MaxSpreadAssertion {});
+        }
+    } else if Decimal::from_ratio(spread_amount, return_amount + spread_amount) > max_spread {
+        return Err(ContractError::MaxSpreadAssertion {});
+    }
+
+    Ok(())
+}
+
+/// ## Description
+/// Returns a [`ContractError`] on failure if the current block time is past the specified deadline.
+/// ## Params
+/// * **env** is an object of type [`Env`].
+///
+/// * **deadline** is an [`Option`] field of type [`u64`]. This is the block time (in seconds) after which
+/// the operation fails.
+pub fn assert_deadline(env: &Env, deadline: Option<u64>) -> Result<(), ContractError> {
+    if let Some(deadline) = deadline {
+        if env.block.time.seconds() > deadline {
+            return Err(ContractError::DeadlineExpired {});
+        }
+    }
+
+    Ok(())
+}
+
+/// ## Description
+/// Updates the fee and Generator information that the pair caches from the factory.
+/// The factory pushes this message to its pairs when their parameters change, but anyone can execute it.
+/// ## Params
+/// * **deps** is an object of type [`DepsMut`].
+///
+/// * **env** is an object of type [`Env`].
+pub fn refresh_factory_params(deps: DepsMut, env: Env) -> Result<Response, ContractError> {
+    let config: Config = CONFIG.load(deps.storage)?;
+    let factory_params = query_factory_params(
+        &deps.querier,
+        config.factory_addr,
+        env.contract.address,
+        config.pair_info.pair_type,
+    )?;
+    FACTORY_PARAMS.save(deps.storage, &factory_params)?;
+
+    let fee_address = factory_params
+        .fee_info
+        .fee_address
+        .map(|addr| addr.to_string())
+        .unwrap_or_default();
+    let generator_address = factory_params
+        .generator_address
+        .map(|addr| addr.to_string())
+        .unwrap_or_default();
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "refresh_factory_params"),
+        attr(
+            "total_fee_rate",
+            factory_params.fee_info.total_fee_rate.to_string(),
+        ),
+        attr(
+            "maker_fee_rate",
+            factory_params.fee_info.maker_fee_rate.to_string(),
+        ),
+        attr("fee_address", fee_address),
+        attr("generator_address", generator_address),
+        attr("pair_status", factory_params.pair_status.to_string()),
+    ]))
+}
+
+/// ## Description
+/// Used for the contract migration. Returns a default object of type [`Response`].
+/// ## Params
+/// * **_deps** is an object of type [`DepsMut`].
+///
+/// * **_env** is an object of type [`Env`].
+///
+/// * **_msg** is an object of type [`MigrateMsg`].
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn migrate(_deps: DepsMut, _env: Env, _msg: MigrateMsg) -> StdResult<Response> {
+    Ok(Response::default())
+}
+
+/// ## Description
+/// Returns the tracked reserves of the pool assets in the order of the pair asset infos.
+/// ## Params
+/// * **storage** is an object of type [`Storage`].
+///
+/// * **config** is an object of type [`Config`].
+pub fn query_reserves(storage: &dyn Storage, config: &Config) -> StdResult<[Asset; 2]> {
+    let reserves = RESERVES.load(storage)?;
+
+    Ok([
+        Asset {
+            info: config.pair_info.asset_infos[0].clone(),
+            amount: reserves[0],
+        },
+        Asset {
+            info: config.pair_info.asset_infos[1].clone(),
+            amount: reserves[1],
+        },
+    ])
+}
+
+/// ## Description
+/// Updates the tracked pool reserves after a swap: the offer amount is added to the offer pool and
+/// the amount that left the pool is subtracted from the ask pool.
+/// ## Params
+/// * **storage** is an object of type [`Storage`].
+///
+/// * **pools** are the tracked reserves before the swap.
+///
+/// * **offer_info** is an object of type [`AssetInfo`]. This is the offer asset of the swap.
+///
+/// * **offer_amount** is an object of type [`Uint128`]. This is the amount of offer assets added to the pool.
+///
+/// * **ask_amount** is an object of type [`Uint128`]. This is the amount of ask assets that left the pool,
+/// including the Maker fee.
+fn save_reserves_after_swap(
+    storage: &mut dyn Storage,
+    pools: &[Asset; 2],
+    offer_info: &AssetInfo,
+    offer_amount: Uint128,
+    ask_amount: Uint128,
+) -> StdResult<()> {
+    let mut reserves = [pools[0].amount, pools[1].amount];
+    for (reserve, pool) in reserves.iter_mut().zip(pools.iter()) {
+        if pool.info.equal(offer_info) {
+            *reserve = reserve.checked_add(offer_amount)?;
+        } else {
+            *reserve = reserve.checked_sub(ask_amount)?;
+        }
+    }
+
+    RESERVES.save(storage, &reserves)
+}
+
+/// ## Description
+/// Returns the total amount of assets in the pool as well as the total amount of LP tokens currently minted.
+/// ## Params
+/// * **deps** is an object of type [`Deps`].
+///
+/// * **config** is an object of type [`Config`].
+pub fn pool_info(deps: Deps, config: Config) -> StdResult<([Asset; 2], Uint128)> {
+    let pools = query_reserves(deps.storage, &config)?;
+    let total_share: Uint128 = query_supply(&deps.querier, config.pair_info.liquidity_token)?;
+
+    Ok((pools, total_share))
+}
+
+/// ## Description
+/// Updates the pool configuration with the specified parameters in the `params` variable.
+/// Only the pool owner can execute this.
+/// Returns a [`ContractError`] as a failure, otherwise returns a [`Response`] with the specified
+/// attributes if the operation was successful
+/// ## Params
+/// * **deps** is an object of type [`DepsMut`].
+///
+/// * **info** is an object of type [`MessageInfo`].
+///
+/// * **params** is an object of type [`Binary`]. These are the the new parameter values.
+pub fn update_config(
+    deps: DepsMut,
+    info: MessageInfo,
+    params: Binary,
+) -> Result<Response, ContractError> {
+    let mut config = CONFIG.load(deps.storage)?;
+
+    if info.sender != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let action = match from_binary::<LbpPoolUpdateParams>(&params)? {
+        LbpPoolUpdateParams::Pause {} => {
+            config.is_paused = true;
+            "pause"
+        }
+        LbpPoolUpdateParams::Resume {} => {
+            config.is_paused = false;
+            "resume"
+        }
+    };
+
+    CONFIG.save(deps.storage, &config)?;
+
+    Ok(Response::new().add_attribute("action", action))
+}
+
+/// ## Description
+/// Compute the current pool weights. The weights move linearly from `start_weights` to
+/// `end_weights` between `start_time` and `end_time`.
+/// ## Params
+/// * **config** is an object of type [`Config`].
+///
+/// * **env** is an object of type [`Env`].
+pub fn compute_current_weights(config: &Config, env: &Env) -> StdResult<[Decimal; 2]> {
+    let block_time = env.block.time.seconds();
+
+    if block_time <= config.start_time {
+        Ok(config.start_weights)
+    } else if block_time < config.end_time {
+        let elapsed_time =
+            Uint128::from(block_time).checked_sub(Uint128::from(config.start_time))?;
+        let time_range =
+            Uint128::from(config.end_time).checked_sub(Uint128::from(config.start_time))?;
+        let start_weight = Uint128::new(ONE) * config.start_weights[0];
+        let end_weight = Uint128::new(ONE) * config.end_weights[0];
+
+        // Only the first weight is interpolated so that the weights always sum up to 1
+        let weight = if end_weight > start_weight {
+            let weight_range = end_weight - start_weight;
+            start_weight + (weight_range * elapsed_time).checked_div(time_range)?
+        } else {
+            let weight_range = start_weight - end_weight;
+            start_weight - (weight_range * elapsed_time).checked_div(time_range)?
+        };
+        let weight = Decimal::from_ratio(weight, ONE);
+
+        Ok([weight, Decimal::one() - weight])
+    } else {
+        Ok(config.end_weights)
+    }
+}
@@ -0,0 +1,91 @@
+use astroport::pair_lbp::MIN_WEIGHT;
+use cosmwasm_std::{OverflowError, StdError};
+use thiserror::Error;
+
+use astroport::weighted_math::{MAX_IN_RATIO, MAX_OUT_RATIO};
+
+/// ## Description
+/// This enum describes liquidity bootstrapping pair contract errors!
+#[derive(Error, Debug, PartialEq)]
+pub enum ContractError {
+    #[error("{0}")]
+    Std(#[from] StdError),
+
+    #[error("Unauthorized")]
+    Unauthorized {},
+
+    #[error("Event of zero transfer")]
+    InvalidZeroAmount {},
+
+    #[error("Operation exceeds max spread limit")]
+    MaxSpreadAssertion {},
+
+    #[error("Provided spread amount exceeds allowed limit")]
+    AllowedSpreadAssertion {},
+
+    #[error("Doubling assets in asset infos")]
+    DoublingAssets {},
+
+    #[error("Asset mismatch between the requested and the stored asset in contract")]
+    AssetMismatch {},
+
+    #[error("You need to provide init params")]
+    InitParamsNotFound {},
+
+    #[error(
+        "Weights must sum up to 1 and each weight must be at least {}%",
+        MIN_WEIGHT
+    )]
+    InvalidWeights {},
+
+    #[error("The start time must be before the end time and the end time must be in the future")]
+    InvalidTimes {},
+
+    #[error("Swaps are paused")]
+    Paused {},
+
+    #[error("Swaps are only allowed between the start time and the end time")]
+    SwapsDisabled {},
+
+    #[error("Liquidity cannot be provided after the end time")]
+    LiquidityProvisionEnded {},
+
+    #[error("Proceeds cannot be withdrawn before the end time")]
+    WithdrawBeforeEnd {},
+
+    #[error("The offer amount cannot exceed {}% of the offer pool", MAX_IN_RATIO)]
+    MaxInRatioAssertion {},
+
+    #[error("The ask amount cannot exceed {}% of the ask pool", MAX_OUT_RATIO)]
+    MaxOutRatioAssertion {},
+
+    #[error("Weighted math overflow")]
+    MathOverflow {},
+
+    #[error("Swaps are paused in the pair")]
+    SwapsPaused {},
+
+    #[error("Deposits are paused in the pair")]
+    DepositsPaused {},
+
+    #[error("Transaction deadline has expired")]
+    DeadlineExpired {},
+
+    #[error("Return amount is less than min_return")]
+    MinReturnAssertion {},
+}
+
+impl From<OverflowError> for ContractError {
+    fn from(o: OverflowError) -> Self {
+        StdError::from(o).into()
+    }
+}
+
+impl From<ContractError> for StdError {
+    fn from(err: ContractError) -> Self {
+        match err {
+            ContractError::Std(err) => err,
+            err => StdError::generic_err(err.to_string()),
+        }
+    }
+}
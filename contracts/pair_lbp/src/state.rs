@@ -0,0 +1,46 @@
+use astroport::asset::PairInfo;
+use astroport::querier::FactoryParams;
+use cosmwasm_std::{Addr, Decimal, Uint128};
+use cw_storage_plus::Item;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// ## Description
+/// This structure stores the main config parameters for a liquidity bootstrapping pair contract.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Config {
+    /// General pair information (e.g pair type)
+    pub pair_info: PairInfo,
+    /// The factory contract address
+    pub factory_addr: Addr,
+    /// The pool owner
+    pub owner: Addr,
+    /// The last timestamp when the pair contract update the asset cumulative prices
+    pub block_time_last: u64,
+    /// The last cumulative price for asset 0
+    pub price0_cumulative_last: Uint128,
+    /// The last cumulative price for asset 1
+    pub price1_cumulative_last: Uint128,
+    /// The normalized asset weights at `start_time`
+    pub start_weights: [Decimal; 2],
+    /// The normalized asset weights at `end_time`
+    pub end_weights: [Decimal; 2],
+    /// The timestamp when the weights start to change
+    pub start_time: u64,
+    /// The timestamp when the weights stop changing
+    pub end_time: u64,
+    /// Whether swaps are paused by the owner
+    pub is_paused: bool,
+}
+
+/// ## Description
+/// Stores the config struct at the given key
+pub const CONFIG: Item<Config> = Item::new("config");
+
+/// Stores the tracked pool reserves in the order of the pair asset infos. Assets sent to the pair
+/// directly are not counted until they are synced
+pub const RESERVES: Item<[Uint128; 2]> = Item::new("reserves");
+
+/// ## Description
+/// Stores the fee and Generator information cached from the factory
+pub const FACTORY_PARAMS: Item<FactoryParams> = Item::new("factory_params");
@@ -0,0 +1,875 @@
+use crate::contract::{
+    compute_current_weights, execute, instantiate, query, query_config, query_pair_info,
+    query_pool, query_simulate_provide, query_simulate_withdraw, query_simulation, reply,
+};
+use crate::error::ContractError;
+use crate::mock_querier::mock_dependencies;
+use crate::response::MsgInstantiateContractResponse;
+use crate::state::{CONFIG, RESERVES};
+use astroport::asset::{Asset, AssetInfo, PairInfo};
+use astroport::factory::{PairStatus, PairType};
+use astroport::weighted_math::{calc_ask_amount, ONE};
+
+use astroport::pair::{InstantiateMsg, QueryMsg, SimulateProvideResponse};
+use astroport::pair_lbp::{ExecuteMsg, LbpPoolConfig, LbpPoolParams, LbpPoolUpdateParams};
+use cosmwasm_std::testing::{mock_env, mock_info, MOCK_CONTRACT_ADDR};
+use cosmwasm_std::{
+    attr, from_binary, to_binary, Addr, BankMsg, Coin, ContractResult, CosmosMsg, Decimal, DepsMut,
+    Env, Reply, StdError, SubMsg, SubMsgExecutionResponse, Uint128, WasmMsg,
+};
+use cw20::Cw20ExecuteMsg;
+use protobuf::Message;
+
+/// The duration of the weight schedule used in the tests
+const DURATION: u64 = 1_000;
+
+fn store_liquidity_token(deps: DepsMut, msg_id: u64, contract_addr: String) {
+    let data = MsgInstantiateContractResponse {
+        contract_address: contract_addr,
+        data: vec![],
+        unknown_fields: Default::default(),
+        cached_size: Default::default(),
+    }
+    .write_to_bytes()
+    .unwrap();
+
+    let reply_msg = Reply {
+        id: msg_id,
+        result: ContractResult::Ok(SubMsgExecutionResponse {
+            events: vec![],
+            data: Some(data.into()),
+        }),
+    };
+
+    let _res = reply(deps, mock_env(), reply_msg.clone()).unwrap();
+}
+
+fn store_reserves(deps: DepsMut, reserves: [u128; 2]) {
+    RESERVES
+        .save(
+            deps.storage,
+            &[Uint128::new(reserves[0]), Uint128::new(reserves[1])],
+        )
+        .unwrap();
+}
+
+fn env_at(elapsed: u64) -> Env {
+    let mut env = mock_env();
+    env.block.time = env.block.time.plus_seconds(elapsed);
+    env
+}
+
+fn lbp_params() -> LbpPoolParams {
+    let start_time = mock_env().block.time.seconds();
+    LbpPoolParams {
+        owner: String::from("owner"),
+        start_weights: [Decimal::percent(10), Decimal::percent(90)],
+        end_weights: [Decimal::percent(50), Decimal::percent(50)],
+        start_time,
+        end_time: start_time + DURATION,
+    }
+}
+
+fn instantiate_msg(params: &LbpPoolParams) -> InstantiateMsg {
+    InstantiateMsg {
+        factory_addr: String::from("factory"),
+        asset_infos: [
+            AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            AssetInfo::Token {
+                contract_addr: Addr::unchecked("asset0000"),
+            },
+        ],
+        token_code_id: 10u64,
+        init_params: Some(to_binary(params).unwrap()),
+    }
+}
+
+#[test]
+fn proper_initialization() {
+    let mut deps = mock_dependencies(&[]);
+
+    deps.querier.with_token_balances(&[(
+        &String::from("asset0000"),
+        &[(&String::from(MOCK_CONTRACT_ADDR), &Uint128::new(123u128))],
+    )]);
+
+    // Init params are required
+    let mut msg = instantiate_msg(&lbp_params());
+    msg.init_params = None;
+    let res = instantiate(deps.as_mut(), mock_env(), mock_info("addr0000", &[]), msg).unwrap_err();
+    assert_eq!(res, ContractError::InitParamsNotFound {});
+
+    // Both the start and the end weights must be valid
+    let mut params = lbp_params();
+    params.start_weights = [Decimal::percent(10), Decimal::percent(80)];
+    let msg = instantiate_msg(&params);
+    let res = instantiate(deps.as_mut(), mock_env(), mock_info("addr0000", &[]), msg).unwrap_err();
+    assert_eq!(res, ContractError::InvalidWeights {});
+
+    let mut params = lbp_params();
+    params.end_weights = [Decimal::one(), Decimal::zero()];
+    let msg = instantiate_msg(&params);
+    let res = instantiate(deps.as_mut(), mock_env(), mock_info("addr0000", &[]), msg).unwrap_err();
+    assert_eq!(res, ContractError::InvalidWeights {});
+
+    // The start time must be before the end time
+    let mut params = lbp_params();
+    params.start_time = params.end_time;
+    let msg = instantiate_msg(&params);
+    let res = instantiate(deps.as_mut(), mock_env(), mock_info("addr0000", &[]), msg).unwrap_err();
+    assert_eq!(res, ContractError::InvalidTimes {});
+
+    // The end time must be in the future
+    let msg = instantiate_msg(&lbp_params());
+    let res = instantiate(
+        deps.as_mut(),
+        env_at(DURATION),
+        mock_info("addr0000", &[]),
+        msg,
+    )
+    .unwrap_err();
+    assert_eq!(res, ContractError::InvalidTimes {});
+
+    let msg = instantiate_msg(&lbp_params());
+    let res = instantiate(deps.as_mut(), mock_env(), mock_info("addr0000", &[]), msg).unwrap();
+    assert_eq!(res.messages.len(), 1);
+
+    // Store liquidity token
+    store_liquidity_token(deps.as_mut(), 1, "liquidity0000".to_string());
+
+    let pair_info: PairInfo = query_pair_info(deps.as_ref()).unwrap();
+    assert_eq!(Addr::unchecked("liquidity0000"), pair_info.liquidity_token);
+    assert_eq!(pair_info.pair_type, PairType::Lbp {});
+
+    let config = query_config(deps.as_ref(), env_at(DURATION / 2)).unwrap();
+    let params: LbpPoolConfig = from_binary(&config.params.unwrap()).unwrap();
+    assert_eq!(params.owner, Addr::unchecked("owner"));
+    assert_eq!(
+        params.current_weights,
+        [Decimal::percent(30), Decimal::percent(70)]
+    );
+    assert!(!params.is_paused);
+}
+
+#[test]
+fn weights_move_linearly() {
+    let mut deps = mock_dependencies(&[]);
+
+    deps.querier.with_token_balances(&[(
+        &String::from("asset0000"),
+        &[(&String::from(MOCK_CONTRACT_ADDR), &Uint128::new(123u128))],
+    )]);
+
+    let mut params = lbp_params();
+    params.start_time += 100;
+    params.end_time += 100;
+    let msg = instantiate_msg(&params);
+    instantiate(deps.as_mut(), mock_env(), mock_info("addr0000", &[]), msg).unwrap();
+    let config = CONFIG.load(deps.as_ref().storage).unwrap();
+
+    // The start weights are used before the start time
+    assert_eq!(
+        compute_current_weights(&config, &mock_env()).unwrap(),
+        [Decimal::percent(10), Decimal::percent(90)]
+    );
+
+    for (elapsed, weights) in [
+        (0, [Decimal::percent(10), Decimal::percent(90)]),
+        (250, [Decimal::percent(20), Decimal::percent(80)]),
+        (
+            333,
+            [
+                Decimal::from_ratio(23_320u128, 100_000u128),
+                Decimal::from_ratio(76_680u128, 100_000u128),
+            ],
+        ),
+        (750, [Decimal::percent(40), Decimal::percent(60)]),
+        (DURATION, [Decimal::percent(50), Decimal::percent(50)]),
+        (DURATION * 2, [Decimal::percent(50), Decimal::percent(50)]),
+    ] {
+        let current_weights = compute_current_weights(&config, &env_at(100 + elapsed)).unwrap();
+        assert_eq!(current_weights, weights);
+        assert_eq!(current_weights[0] + current_weights[1], Decimal::one());
+    }
+
+    // Decreasing weights are interpolated the same way
+    let mut config = config;
+    config.start_weights = [Decimal::percent(90), Decimal::percent(10)];
+    config.end_weights = [Decimal::percent(30), Decimal::percent(70)];
+    assert_eq!(
+        compute_current_weights(&config, &env_at(100 + DURATION / 2)).unwrap(),
+        [Decimal::percent(60), Decimal::percent(40)]
+    );
+}
+
+#[test]
+fn swap_uses_current_weights() {
+    let offer_amount = Uint128::new(10_000_000u128);
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::new(1_000_000_000u128) + offer_amount, /* user deposit must be pre-applied */
+    }]);
+
+    deps.querier.with_token_balances(&[(
+        &String::from("asset0000"),
+        &[(
+            &String::from(MOCK_CONTRACT_ADDR),
+            &Uint128::new(9_000_000_000u128),
+        )],
+    )]);
+
+    let msg = instantiate_msg(&lbp_params());
+    instantiate(deps.as_mut(), mock_env(), mock_info("addr0000", &[]), msg).unwrap();
+    store_liquidity_token(deps.as_mut(), 1, "liquidity0000".to_string());
+    store_reserves(deps.as_mut(), [1_000_000_000u128, 9_000_000_000u128]);
+
+    let swap_msg = |min_return: Option<Uint128>, deadline: Option<u64>| ExecuteMsg::Swap {
+        offer_asset: Asset {
+            info: AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            amount: offer_amount,
+        },
+        belief_price: None,
+        max_spread: Some(Decimal::percent(5)),
+        to: None,
+        min_return,
+        deadline,
+    };
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: offer_amount,
+        }],
+    );
+
+    // Swaps are not allowed after the end time
+    let res = execute(
+        deps.as_mut(),
+        env_at(DURATION),
+        info.clone(),
+        swap_msg(None, None),
+    )
+    .unwrap_err();
+    assert_eq!(res, ContractError::SwapsDisabled {});
+
+    let expected_ask_amount = calc_ask_amount(
+        1_000_000_000u128,
+        ONE / 5,
+        9_000_000_000u128,
+        ONE * 4 / 5,
+        offer_amount.u128(),
+    )
+    .unwrap();
+    // The swap at the start weights would return a lot less
+    let start_ask_amount = calc_ask_amount(
+        1_000_000_000u128,
+        ONE / 10,
+        9_000_000_000u128,
+        ONE * 9 / 10,
+        offer_amount.u128(),
+    )
+    .unwrap();
+    assert!(expected_ask_amount > start_ask_amount * 2);
+
+    let expected_commission_amount = expected_ask_amount * 3 / 1000;
+    let expected_return_amount = expected_ask_amount - expected_commission_amount;
+
+    // The execution guards are checked against the current weights
+    let env = env_at(DURATION / 4);
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        info.clone(),
+        swap_msg(None, Some(env.block.time.seconds() - 1)),
+    )
+    .unwrap_err();
+    assert_eq!(res, ContractError::DeadlineExpired {});
+
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        info.clone(),
+        swap_msg(Some(Uint128::new(expected_return_amount + 1)), None),
+    )
+    .unwrap_err();
+    assert_eq!(res, ContractError::MinReturnAssertion {});
+
+    // A quarter into the schedule the weights are 20/80
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        info,
+        swap_msg(
+            Some(Uint128::new(expected_return_amount)),
+            Some(env.block.time.seconds()),
+        ),
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages[0],
+        SubMsg::new(WasmMsg::Execute {
+            contract_addr: String::from("asset0000"),
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: String::from("addr0000"),
+                amount: Uint128::new(expected_return_amount),
+            })
+            .unwrap(),
+            funds: vec![],
+        })
+    );
+    assert_eq!(
+        res.attributes[6],
+        attr("return_amount", expected_return_amount.to_string())
+    );
+
+    // The simulation uses the same weights (the reserves are reset to the ones before the swap)
+    store_reserves(deps.as_mut(), [1_000_000_000u128, 9_000_000_000u128]);
+    let simulation = query_simulation(
+        deps.as_ref(),
+        env_at(DURATION / 4),
+        Asset {
+            info: AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            amount: offer_amount,
+        },
+    )
+    .unwrap();
+    assert_eq!(simulation.return_amount.u128(), expected_return_amount);
+}
+
+#[test]
+fn owner_actions() {
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::new(1_000_000_000u128),
+    }]);
+
+    deps.querier.with_token_balances(&[(
+        &String::from("asset0000"),
+        &[(
+            &String::from(MOCK_CONTRACT_ADDR),
+            &Uint128::new(9_000_000_000u128),
+        )],
+    )]);
+
+    let msg = instantiate_msg(&lbp_params());
+    instantiate(deps.as_mut(), mock_env(), mock_info("addr0000", &[]), msg).unwrap();
+    store_liquidity_token(deps.as_mut(), 1, "liquidity0000".to_string());
+
+    // Only the owner can provide liquidity
+    let assets = [
+        Asset {
+            info: AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            amount: Uint128::new(1_000_000_000u128),
+        },
+        Asset {
+            info: AssetInfo::Token {
+                contract_addr: Addr::unchecked("asset0000"),
+            },
+            amount: Uint128::new(9_000_000_000u128),
+        },
+    ];
+    let funds = [Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::new(1_000_000_000u128),
+    }];
+    let msg = ExecuteMsg::ProvideLiquidity {
+        assets: assets.clone(),
+    };
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &funds),
+        msg.clone(),
+    )
+    .unwrap_err();
+    assert_eq!(res, ContractError::Unauthorized {});
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("owner", &funds),
+        msg.clone(),
+    )
+    .unwrap();
+    // No LP tokens are minted for the deposit
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(WasmMsg::Execute {
+            contract_addr: String::from("asset0000"),
+            msg: to_binary(&Cw20ExecuteMsg::TransferFrom {
+                owner: String::from("owner"),
+                recipient: String::from(MOCK_CONTRACT_ADDR),
+                amount: Uint128::new(9_000_000_000u128),
+            })
+            .unwrap(),
+            funds: vec![],
+        })]
+    );
+
+    // Liquidity cannot be provided after the end time
+    let res = execute(
+        deps.as_mut(),
+        env_at(DURATION),
+        mock_info("owner", &funds),
+        msg,
+    )
+    .unwrap_err();
+    assert_eq!(res, ContractError::LiquidityProvisionEnded {});
+
+    // Only the owner can pause swaps
+    let pause_msg = ExecuteMsg::UpdateConfig {
+        params: to_binary(&LbpPoolUpdateParams::Pause {}).unwrap(),
+    };
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        pause_msg.clone(),
+    )
+    .unwrap_err();
+    assert_eq!(res, ContractError::Unauthorized {});
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("owner", &[]),
+        pause_msg,
+    )
+    .unwrap();
+    assert_eq!(res.attributes, vec![attr("action", "pause")]);
+
+    let config = query_config(deps.as_ref(), mock_env()).unwrap();
+    let params: LbpPoolConfig = from_binary(&config.params.unwrap()).unwrap();
+    assert!(params.is_paused);
+
+    let swap_msg = ExecuteMsg::Swap {
+        offer_asset: Asset {
+            info: AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            amount: Uint128::new(1_000u128),
+        },
+        belief_price: None,
+        max_spread: None,
+        to: None,
+        min_return: None,
+        deadline: None,
+    };
+    let swap_funds = [Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::new(1_000u128),
+    }];
+    let res = execute(
+        deps.as_mut(),
+        env_at(1),
+        mock_info("addr0000", &swap_funds),
+        swap_msg.clone(),
+    )
+    .unwrap_err();
+    assert_eq!(res, ContractError::Paused {});
+
+    let resume_msg = ExecuteMsg::UpdateConfig {
+        params: to_binary(&LbpPoolUpdateParams::Resume {}).unwrap(),
+    };
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("owner", &[]),
+        resume_msg,
+    )
+    .unwrap();
+    execute(
+        deps.as_mut(),
+        env_at(1),
+        mock_info("addr0000", &swap_funds),
+        swap_msg,
+    )
+    .unwrap();
+
+    // The proceeds can be withdrawn only by the owner after the end time
+    let withdraw_msg = ExecuteMsg::WithdrawProceeds {
+        receiver: Some(String::from("treasury")),
+    };
+    let res = execute(
+        deps.as_mut(),
+        env_at(DURATION - 1),
+        mock_info("owner", &[]),
+        withdraw_msg.clone(),
+    )
+    .unwrap_err();
+    assert_eq!(res, ContractError::WithdrawBeforeEnd {});
+
+    let res = execute(
+        deps.as_mut(),
+        env_at(DURATION),
+        mock_info("addr0000", &[]),
+        withdraw_msg.clone(),
+    )
+    .unwrap_err();
+    assert_eq!(res, ContractError::Unauthorized {});
+
+    let res = execute(
+        deps.as_mut(),
+        env_at(DURATION),
+        mock_info("owner", &[]),
+        withdraw_msg,
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+                to_address: String::from("treasury"),
+                amount: vec![Coin {
+                    denom: "uusd".to_string(),
+                    amount: Uint128::new(1_000_000_000u128),
+                }],
+            })),
+            SubMsg::new(WasmMsg::Execute {
+                contract_addr: String::from("asset0000"),
+                msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                    recipient: String::from("treasury"),
+                    amount: Uint128::new(9_000_000_000u128),
+                })
+                .unwrap(),
+                funds: vec![],
+            }),
+        ]
+    );
+}
+
+#[test]
+fn simulate_provide_and_withdraw() {
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::new(1_000_000_000u128),
+    }]);
+
+    deps.querier.with_token_balances(&[
+        (&String::from("liquidity0000"), &[]),
+        (
+            &String::from("asset0000"),
+            &[(
+                &String::from(MOCK_CONTRACT_ADDR),
+                &Uint128::new(9_000_000_000u128),
+            )],
+        ),
+    ]);
+
+    let msg = instantiate_msg(&lbp_params());
+    instantiate(deps.as_mut(), mock_env(), mock_info("addr0000", &[]), msg).unwrap();
+    store_liquidity_token(deps.as_mut(), 1, "liquidity0000".to_string());
+
+    let uusd = AssetInfo::NativeToken {
+        denom: "uusd".to_string(),
+    };
+    let asset0000 = AssetInfo::Token {
+        contract_addr: Addr::unchecked("asset0000"),
+    };
+    let simulate_provide =
+        |env: Env, assets: [Asset; 2]| query_simulate_provide(deps.as_ref(), env, assets);
+    let assets = [
+        Asset {
+            info: uusd.clone(),
+            amount: Uint128::new(1_000_000u128),
+        },
+        Asset {
+            info: asset0000.clone(),
+            amount: Uint128::new(9_000_000u128),
+        },
+    ];
+
+    // No LP tokens are minted and no fees are charged for the deposit
+    let res = simulate_provide(mock_env(), assets.clone()).unwrap();
+    assert_eq!(
+        res,
+        SimulateProvideResponse {
+            share: Uint128::zero(),
+            fees: vec![
+                Asset {
+                    info: uusd.clone(),
+                    amount: Uint128::zero(),
+                },
+                Asset {
+                    info: asset0000.clone(),
+                    amount: Uint128::zero(),
+                },
+            ],
+        }
+    );
+
+    // The deposit is validated in the same way as in provide_liquidity
+    let mut zero_assets = assets.clone();
+    zero_assets[1].amount = Uint128::zero();
+    assert_eq!(
+        simulate_provide(mock_env(), zero_assets).unwrap_err(),
+        StdError::generic_err(ContractError::InvalidZeroAmount {}.to_string())
+    );
+    let mut wrong_assets = assets.clone();
+    wrong_assets[1].info = AssetInfo::NativeToken {
+        denom: "uluna".to_string(),
+    };
+    assert_eq!(
+        simulate_provide(mock_env(), wrong_assets).unwrap_err(),
+        StdError::generic_err(ContractError::AssetMismatch {}.to_string())
+    );
+    assert_eq!(
+        simulate_provide(env_at(DURATION), assets).unwrap_err(),
+        StdError::generic_err(ContractError::LiquidityProvisionEnded {}.to_string())
+    );
+
+    // Pool assets can only be withdrawn as proceeds, so burning LP tokens returns nothing
+    let res = query_simulate_withdraw(deps.as_ref(), Uint128::new(100u128)).unwrap();
+    assert_eq!(
+        res,
+        vec![
+            Asset {
+                info: uusd,
+                amount: Uint128::zero(),
+            },
+            Asset {
+                info: asset0000,
+                amount: Uint128::zero(),
+            },
+        ]
+    );
+}
+
+#[test]
+fn sync_and_skim() {
+    let asset_pool_amount = Uint128::new(9_000_000_000u128);
+    let donation_amount = Uint128::new(1_000_000u128);
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::new(1_000_000_000u128),
+    }]);
+
+    deps.querier.with_token_balances(&[
+        (&String::from("liquidity0000"), &[]),
+        (
+            &String::from("asset0000"),
+            &[(&String::from(MOCK_CONTRACT_ADDR), &asset_pool_amount)],
+        ),
+    ]);
+
+    let msg = instantiate_msg(&lbp_params());
+    instantiate(deps.as_mut(), mock_env(), mock_info("addr0000", &[]), msg).unwrap();
+    store_liquidity_token(deps.as_mut(), 1, "liquidity0000".to_string());
+
+    // The reserves are tracked from zero, so the balances of the pair are not counted
+    let res = query_pool(deps.as_ref()).unwrap();
+    assert_eq!(res.assets[0].amount, Uint128::zero());
+    assert_eq!(res.assets[1].amount, Uint128::zero());
+
+    let res = execute(
+        deps.as_mut(),
+        env_at(DURATION / 4),
+        mock_info("addr0000", &[]),
+        ExecuteMsg::Sync {},
+    )
+    .unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "sync"),
+            attr("reserves", "1000000000uusd, 9000000000asset0000"),
+        ]
+    );
+
+    let offer_asset = Asset {
+        info: AssetInfo::NativeToken {
+            denom: "uusd".to_string(),
+        },
+        amount: Uint128::new(10_000_000u128),
+    };
+    let simulation =
+        query_simulation(deps.as_ref(), env_at(DURATION / 4), offer_asset.clone()).unwrap();
+
+    // A direct transfer to the pair doesn't move the price
+    deps.querier.with_token_balances(&[
+        (&String::from("liquidity0000"), &[]),
+        (
+            &String::from("asset0000"),
+            &[(
+                &String::from(MOCK_CONTRACT_ADDR),
+                &(asset_pool_amount + donation_amount),
+            )],
+        ),
+    ]);
+    assert_eq!(
+        query_simulation(deps.as_ref(), env_at(DURATION / 4), offer_asset.clone()).unwrap(),
+        simulation
+    );
+
+    // The excess balance is sent to the recipient
+    let res = execute(
+        deps.as_mut(),
+        env_at(DURATION / 4),
+        mock_info("addr0000", &[]),
+        ExecuteMsg::Skim {
+            to: Some(String::from("addr0001")),
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(WasmMsg::Execute {
+            contract_addr: String::from("asset0000"),
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: String::from("addr0001"),
+                amount: donation_amount,
+            })
+            .unwrap(),
+            funds: vec![],
+        })]
+    );
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "skim"),
+            attr("receiver", "addr0001"),
+            attr("assets", "0uusd, 1000000asset0000"),
+        ]
+    );
+
+    // Syncing adds the excess balance to the pool instead
+    execute(
+        deps.as_mut(),
+        env_at(DURATION / 4),
+        mock_info("addr0000", &[]),
+        ExecuteMsg::Sync {},
+    )
+    .unwrap();
+    let res = query_pool(deps.as_ref()).unwrap();
+    assert_eq!(res.assets[1].amount, asset_pool_amount + donation_amount);
+    assert_ne!(
+        query_simulation(deps.as_ref(), env_at(DURATION / 4), offer_asset).unwrap(),
+        simulation
+    );
+
+    // Nothing is left to skim
+    let res = execute(
+        deps.as_mut(),
+        env_at(DURATION / 4),
+        mock_info("addr0000", &[]),
+        ExecuteMsg::Skim { to: None },
+    )
+    .unwrap();
+    assert!(res.messages.is_empty());
+}
+
+#[test]
+fn pair_status_circuit_breaker() {
+    let offer_amount = Uint128::new(10_000_000u128);
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::new(1_000_000_000u128) + offer_amount, /* user deposit must be pre-applied */
+    }]);
+
+    deps.querier.with_token_balances(&[(
+        &String::from("asset0000"),
+        &[(
+            &String::from(MOCK_CONTRACT_ADDR),
+            &Uint128::new(9_000_000_000u128),
+        )],
+    )]);
+
+    let msg = instantiate_msg(&lbp_params());
+    instantiate(deps.as_mut(), mock_env(), mock_info("addr0000", &[]), msg).unwrap();
+    store_liquidity_token(deps.as_mut(), 1, "liquidity0000".to_string());
+    store_reserves(deps.as_mut(), [1_000_000_000u128, 9_000_000_000u128]);
+
+    let swap_msg = ExecuteMsg::Swap {
+        offer_asset: Asset {
+            info: AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            amount: offer_amount,
+        },
+        belief_price: None,
+        max_spread: Some(Decimal::percent(5)),
+        to: None,
+        min_return: None,
+        deadline: None,
+    };
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: offer_amount,
+        }],
+    );
+    let provide_msg = ExecuteMsg::ProvideLiquidity {
+        assets: [
+            Asset {
+                info: AssetInfo::NativeToken {
+                    denom: "uusd".to_string(),
+                },
+                amount: offer_amount,
+            },
+            Asset {
+                info: AssetInfo::Token {
+                    contract_addr: Addr::unchecked("asset0000"),
+                },
+                amount: offer_amount,
+            },
+        ],
+    };
+    let owner_info = mock_info(
+        "owner",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: offer_amount,
+        }],
+    );
+
+    // The pair picks the factory state up once its cached parameters are refreshed
+    deps.querier.with_pair_status(PairStatus::WithdrawOnly);
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("anyone", &[]),
+        ExecuteMsg::RefreshFactoryParams {},
+    )
+    .unwrap();
+    let status: PairStatus =
+        from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::PairStatus {}).unwrap()).unwrap();
+    assert_eq!(status, PairStatus::WithdrawOnly);
+
+    let res = execute(
+        deps.as_mut(),
+        env_at(DURATION / 4),
+        info.clone(),
+        swap_msg.clone(),
+    )
+    .unwrap_err();
+    assert_eq!(res, ContractError::SwapsPaused {});
+    let res = execute(
+        deps.as_mut(),
+        env_at(DURATION / 4),
+        owner_info.clone(),
+        provide_msg.clone(),
+    )
+    .unwrap_err();
+    assert_eq!(res, ContractError::DepositsPaused {});
+
+    // Once the pair is resumed, swaps and deposits go through again
+    deps.querier.with_pair_status(PairStatus::Active);
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("anyone", &[]),
+        ExecuteMsg::RefreshFactoryParams {},
+    )
+    .unwrap();
+    execute(deps.as_mut(), env_at(DURATION / 4), info, swap_msg).unwrap();
+    execute(deps.as_mut(), env_at(DURATION / 4), owner_info, provide_msg).unwrap();
+}
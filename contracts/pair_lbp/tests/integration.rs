@@ -0,0 +1,332 @@
+use astroport::asset::{Asset, AssetInfo, PairInfo};
+use astroport::factory::{
+    ExecuteMsg as FactoryExecuteMsg, InstantiateMsg as FactoryInstantiateMsg, PairConfig, PairType,
+    QueryMsg as FactoryQueryMsg,
+};
+use astroport::pair::{PoolResponse, QueryMsg, SimulationResponse};
+use astroport::pair_lbp::{Cw20HookMsg, ExecuteMsg, LbpPoolParams, LbpPoolUpdateParams};
+use astroport::token::InstantiateMsg as TokenInstantiateMsg;
+use cosmwasm_std::testing::{mock_env, MockApi, MockStorage};
+use cosmwasm_std::{to_binary, Addr, Decimal, Uint128};
+use cw20::{BalanceResponse, Cw20Coin, Cw20ExecuteMsg, Cw20QueryMsg, MinterResponse};
+use terra_multi_test::{AppBuilder, BankKeeper, ContractWrapper, Executor, TerraApp, TerraMock};
+
+const OWNER: &str = "owner";
+/// The duration of the weight schedule used in the tests
+const DURATION: u64 = 86_400;
+
+fn mock_app() -> TerraApp {
+    let env = mock_env();
+    let api = MockApi::default();
+    let bank = BankKeeper::new();
+    let storage = MockStorage::new();
+    let custom = TerraMock::luna_ust_case();
+
+    AppBuilder::new()
+        .with_api(api)
+        .with_block(env.block)
+        .with_bank(bank)
+        .with_storage(storage)
+        .with_custom(custom)
+        .build()
+}
+
+fn store_token_code(app: &mut TerraApp) -> u64 {
+    let astro_token_contract = Box::new(ContractWrapper::new_with_empty(
+        astroport_token::contract::execute,
+        astroport_token::contract::instantiate,
+        astroport_token::contract::query,
+    ));
+
+    app.store_code(astro_token_contract)
+}
+
+fn store_pair_code(app: &mut TerraApp) -> u64 {
+    let pair_contract = Box::new(
+        ContractWrapper::new_with_empty(
+            astroport_pair_lbp::contract::execute,
+            astroport_pair_lbp::contract::instantiate,
+            astroport_pair_lbp::contract::query,
+        )
+        .with_reply_empty(astroport_pair_lbp::contract::reply),
+    );
+
+    app.store_code(pair_contract)
+}
+
+fn store_factory_code(app: &mut TerraApp) -> u64 {
+    let factory_contract = Box::new(
+        ContractWrapper::new_with_empty(
+            astroport_factory::contract::execute,
+            astroport_factory::contract::instantiate,
+            astroport_factory::contract::query,
+        )
+        .with_reply_empty(astroport_factory::contract::reply),
+    );
+
+    app.store_code(factory_contract)
+}
+
+fn instantiate_token(app: &mut TerraApp, token_code_id: u64, name: &str, amount: Uint128) -> Addr {
+    let init_msg = TokenInstantiateMsg {
+        name: name.to_string(),
+        symbol: name.to_string(),
+        decimals: 6,
+        initial_balances: vec![Cw20Coin {
+            address: OWNER.to_string(),
+            amount,
+        }],
+        mint: Some(MinterResponse {
+            minter: String::from(OWNER),
+            cap: None,
+        }),
+    };
+
+    app.instantiate_contract(
+        token_code_id,
+        Addr::unchecked(OWNER),
+        &init_msg,
+        &[],
+        name,
+        None,
+    )
+    .unwrap()
+}
+
+fn instantiate_factory(app: &mut TerraApp, token_code_id: u64) -> Addr {
+    let pair_code_id = store_pair_code(app);
+    let factory_code_id = store_factory_code(app);
+
+    let init_msg = FactoryInstantiateMsg {
+        fee_address: None,
+        pair_configs: vec![PairConfig {
+            code_id: pair_code_id,
+            maker_fee_bps: 0,
+            pair_type: PairType::Lbp {},
+            total_fee_bps: 30,
+            is_disabled: false,
+            is_generator_disabled: false,
+            maker_fee_as_lp: false,
+        }],
+        token_code_id,
+        generator_address: Some(String::from("generator")),
+        owner: OWNER.to_string(),
+        whitelist_code_id: 234u64,
+    };
+
+    app.instantiate_contract(
+        factory_code_id,
+        Addr::unchecked(OWNER),
+        &init_msg,
+        &[],
+        "FACTORY",
+        None,
+    )
+    .unwrap()
+}
+
+fn query_balance(app: &TerraApp, token: &Addr, address: &Addr) -> Uint128 {
+    let res: BalanceResponse = app
+        .wrap()
+        .query_wasm_smart(
+            token,
+            &Cw20QueryMsg::Balance {
+                address: address.to_string(),
+            },
+        )
+        .unwrap();
+
+    res.balance
+}
+
+#[test]
+fn create_provide_swap_and_withdraw_proceeds() {
+    let mut app = mock_app();
+    let owner = Addr::unchecked(OWNER);
+    let user = Addr::unchecked("user");
+    let treasury = Addr::unchecked("treasury");
+
+    let token_code_id = store_token_code(&mut app);
+    let gov_amount = Uint128::new(9_000_000_000_000u128);
+    let usd_amount = Uint128::new(1_000_000_000_000u128);
+    let offer_amount = Uint128::new(1_000_000_000u128);
+    let gov_token = instantiate_token(&mut app, token_code_id, "GOV", gov_amount);
+    let usd_token = instantiate_token(&mut app, token_code_id, "USD", usd_amount + offer_amount);
+    let factory = instantiate_factory(&mut app, token_code_id);
+
+    let asset_infos = [
+        AssetInfo::Token {
+            contract_addr: gov_token.clone(),
+        },
+        AssetInfo::Token {
+            contract_addr: usd_token.clone(),
+        },
+    ];
+
+    // Init params are required for LBP pairs
+    let msg = FactoryExecuteMsg::CreatePair {
+        asset_infos: asset_infos.clone(),
+        pair_type: PairType::Lbp {},
+        init_params: None,
+    };
+    app.execute_contract(owner.clone(), factory.clone(), &msg, &[])
+        .unwrap_err();
+
+    // The GOV weight goes down from 90% to 50% in a day
+    let start_time = app.block_info().time.seconds();
+    let msg = FactoryExecuteMsg::CreatePair {
+        asset_infos: asset_infos.clone(),
+        pair_type: PairType::Lbp {},
+        init_params: Some(
+            to_binary(&LbpPoolParams {
+                owner: OWNER.to_string(),
+                start_weights: [Decimal::percent(90), Decimal::percent(10)],
+                end_weights: [Decimal::percent(50), Decimal::percent(50)],
+                start_time,
+                end_time: start_time + DURATION,
+            })
+            .unwrap(),
+        ),
+    };
+    app.execute_contract(owner.clone(), factory.clone(), &msg, &[])
+        .unwrap();
+
+    let pair_info: PairInfo = app
+        .wrap()
+        .query_wasm_smart(
+            &factory,
+            &FactoryQueryMsg::Pair {
+                asset_infos: asset_infos.clone(),
+                pair_type: Some(PairType::Lbp {}),
+            },
+        )
+        .unwrap();
+    assert_eq!(pair_info.pair_type, PairType::Lbp {});
+    let pair = pair_info.contract_addr;
+
+    for (token, amount) in [(&gov_token, gov_amount), (&usd_token, usd_amount)] {
+        let msg = Cw20ExecuteMsg::IncreaseAllowance {
+            spender: pair.to_string(),
+            expires: None,
+            amount,
+        };
+        app.execute_contract(owner.clone(), token.clone(), &msg, &[])
+            .unwrap();
+    }
+
+    let msg = ExecuteMsg::ProvideLiquidity {
+        assets: [
+            Asset {
+                info: asset_infos[0].clone(),
+                amount: gov_amount,
+            },
+            Asset {
+                info: asset_infos[1].clone(),
+                amount: usd_amount,
+            },
+        ],
+    };
+    app.execute_contract(owner.clone(), pair.clone(), &msg, &[])
+        .unwrap();
+    // No LP tokens are minted
+    assert_eq!(
+        query_balance(&app, &pair_info.liquidity_token, &owner),
+        Uint128::zero()
+    );
+
+    let simulate = |app: &TerraApp| -> SimulationResponse {
+        app.wrap()
+            .query_wasm_smart(
+                &pair,
+                &QueryMsg::Simulation {
+                    offer_asset: Asset {
+                        info: asset_infos[1].clone(),
+                        amount: offer_amount,
+                    },
+                },
+            )
+            .unwrap()
+    };
+    let swap_msg = Cw20ExecuteMsg::Send {
+        contract: pair.to_string(),
+        msg: to_binary(&Cw20HookMsg::Swap {
+            belief_price: None,
+            max_spread: None,
+            to: Some(user.to_string()),
+            min_return: None,
+            deadline: None,
+        })
+        .unwrap(),
+        amount: offer_amount,
+    };
+
+    // 1 GOV is worth 1 USD at the start
+    let start_simulation = simulate(&app);
+    assert!(start_simulation.return_amount < offer_amount);
+
+    // GOV becomes cheaper as its weight goes down
+    app.update_block(|b| {
+        b.time = b.time.plus_seconds(DURATION / 2);
+    });
+    let simulation = simulate(&app);
+    assert!(simulation.return_amount > start_simulation.return_amount * Uint128::new(3));
+
+    // Only the owner can pause swaps
+    let pause_msg = ExecuteMsg::UpdateConfig {
+        params: to_binary(&LbpPoolUpdateParams::Pause {}).unwrap(),
+    };
+    app.execute_contract(user.clone(), pair.clone(), &pause_msg, &[])
+        .unwrap_err();
+    app.execute_contract(owner.clone(), pair.clone(), &pause_msg, &[])
+        .unwrap();
+    app.execute_contract(owner.clone(), usd_token.clone(), &swap_msg, &[])
+        .unwrap_err();
+
+    let resume_msg = ExecuteMsg::UpdateConfig {
+        params: to_binary(&LbpPoolUpdateParams::Resume {}).unwrap(),
+    };
+    app.execute_contract(owner.clone(), pair.clone(), &resume_msg, &[])
+        .unwrap();
+    app.execute_contract(owner.clone(), usd_token.clone(), &swap_msg, &[])
+        .unwrap();
+    assert_eq!(
+        query_balance(&app, &gov_token, &user),
+        simulation.return_amount
+    );
+
+    // The proceeds cannot be withdrawn before the end time
+    let withdraw_msg = ExecuteMsg::WithdrawProceeds {
+        receiver: Some(treasury.to_string()),
+    };
+    app.execute_contract(owner.clone(), pair.clone(), &withdraw_msg, &[])
+        .unwrap_err();
+
+    // Swaps are disabled after the end time
+    app.update_block(|b| {
+        b.time = b.time.plus_seconds(DURATION / 2);
+    });
+    app.execute_contract(owner.clone(), usd_token.clone(), &swap_msg, &[])
+        .unwrap_err();
+
+    let pool: PoolResponse = app
+        .wrap()
+        .query_wasm_smart(&pair, &QueryMsg::Pool {})
+        .unwrap();
+    assert_eq!(pool.assets[1].amount, usd_amount + offer_amount);
+
+    app.execute_contract(user.clone(), pair.clone(), &withdraw_msg, &[])
+        .unwrap_err();
+    app.execute_contract(owner.clone(), pair.clone(), &withdraw_msg, &[])
+        .unwrap();
+
+    assert_eq!(
+        query_balance(&app, &gov_token, &treasury),
+        pool.assets[0].amount
+    );
+    assert_eq!(
+        query_balance(&app, &usd_token, &treasury),
+        pool.assets[1].amount
+    );
+    assert_eq!(query_balance(&app, &gov_token, &pair), Uint128::zero());
+    assert_eq!(query_balance(&app, &usd_token, &pair), Uint128::zero());
+}
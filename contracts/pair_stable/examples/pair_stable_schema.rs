@@ -5,8 +5,8 @@ use cosmwasm_schema::{export_schema_with_title, remove_schemas, schema_for};
 
 use astroport::asset::PairInfo;
 use astroport::pair::{
-    CumulativePricesResponse, Cw20HookMsg, ExecuteMsg, InstantiateMsg, MigrateMsg, PoolResponse,
-    QueryMsg, ReverseSimulationResponse, SimulationResponse,
+    CumulativePricesResponse, Cw20HookMsg, ExecuteMsg, FlashLoanReceiverMsg, InstantiateMsg,
+    MigrateMsg, PoolResponse, QueryMsg, ReverseSimulationResponse, SimulationResponse,
 };
 
 fn main() {
@@ -18,6 +18,11 @@ fn main() {
     export_schema_with_title(&schema_for!(InstantiateMsg), &out_dir, "InstantiateMsg");
     export_schema_with_title(&schema_for!(ExecuteMsg), &out_dir, "ExecuteMsg");
     export_schema_with_title(&schema_for!(Cw20HookMsg), &out_dir, "Cw20HookMsg");
+    export_schema_with_title(
+        &schema_for!(FlashLoanReceiverMsg),
+        &out_dir,
+        "FlashLoanReceiverMsg",
+    );
     export_schema_with_title(&schema_for!(QueryMsg), &out_dir, "QueryMsg");
     export_schema_with_title(&schema_for!(PairInfo), &out_dir, "PairInfo");
     export_schema_with_title(&schema_for!(PoolResponse), &out_dir, "PoolResponse");
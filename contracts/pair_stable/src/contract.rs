@@ -1,15 +1,19 @@
 use crate::error::ContractError;
 use crate::math::{
-    calc_ask_amount, calc_offer_amount, compute_d, AMP_PRECISION, MAX_AMP, MAX_AMP_CHANGE,
-    MIN_AMP_CHANGING_TIME, N_COINS,
+    calc_ask_amount, calc_depth, calc_dynamic_fee_rate, calc_offer_amount, calc_spot_price,
+    calc_withdraw_imbalanced, calc_withdraw_one_coin, compute_d, AMP_PRECISION, MAX_AMP,
+    MAX_AMP_CHANGE, MAX_FEE_MULTIPLIER, MIN_AMP_CHANGING_TIME, N_COINS,
+};
+use crate::state::{
+    Config, FlashLoanState, ACCRUE_PROTOCOL_FEES, AMP_HISTORY, CONFIG, FACTORY_PARAMS,
+    FEE_MULTIPLIER, FLASH_LOAN, LAST_AMP_RAMP_ID, PROTOCOL_FEES, RESERVES,
 };
-use crate::state::{Config, CONFIG};
 
-use cosmwasm_bignumber::Decimal256;
+use cosmwasm_bignumber::{Decimal256, Uint256};
 use cosmwasm_std::{
     attr, entry_point, from_binary, to_binary, Addr, Binary, Coin, CosmosMsg, Decimal, Deps,
-    DepsMut, Env, MessageInfo, Reply, ReplyOn, Response, StdError, StdResult, SubMsg, Uint128,
-    WasmMsg,
+    DepsMut, Env, MessageInfo, Order, QuerierWrapper, Reply, ReplyOn, Response, StdError,
+    StdResult, Storage, SubMsg, Timestamp, Uint128, WasmMsg,
 };
 
 use crate::response::MsgInstantiateContractResponse;
@@ -17,21 +21,28 @@ use astroport::asset::{addr_validate_to_lower, format_lp_token_name, Asset, Asse
 use astroport::factory::PairType;
 
 use astroport::generator::Cw20HookMsg as GeneratorHookMsg;
+use astroport::observation::{
+    observe, set_observations_size, write_observation, MAX_OBSERVATIONS_SIZE,
+};
 use astroport::pair::{
     ConfigResponse, InstantiateMsg, StablePoolParams, StablePoolUpdateParams, DEFAULT_SLIPPAGE,
-    MAX_ALLOWED_SLIPPAGE, TWAP_PRECISION,
+    MAX_ALLOWED_SLIPPAGE, MINIMUM_LIQUIDITY, TWAP_PRECISION,
 };
 
 use astroport::pair::{
-    CumulativePricesResponse, Cw20HookMsg, ExecuteMsg, MigrateMsg, PoolResponse, QueryMsg,
-    ReverseSimulationResponse, SimulationResponse, StablePoolConfig,
+    AmpHistoryResponse, AmpRamp, CumulativePricesResponse, CurrentDResponse, Cw20HookMsg,
+    DepthResponse, ExecuteMsg, FlashLoanReceiverMsg, MigrateMsg, Observation, ObserveResponse,
+    PoolResponse, PriceImpactResponse, ProtocolFeesResponse, QueryMsg, ReverseSimulationResponse,
+    SimulateProvideResponse, SimulateWithdrawImbalancedResponse, SimulateWithdrawOneCoinResponse,
+    SimulationResponse, SpotPriceResponse, StablePoolConfig, VirtualPriceResponse,
 };
 use astroport::querier::{
-    query_factory_config, query_fee_info, query_supply, query_token_precision,
+    query_factory_config, query_factory_params, query_supply, query_token_precision,
 };
 use astroport::{token::InstantiateMsg as TokenInstantiateMsg, U256};
-use cw2::set_contract_version;
+use cw2::{get_contract_version, set_contract_version};
 use cw20::{Cw20ExecuteMsg, Cw20ReceiveMsg, MinterResponse};
+use cw_storage_plus::{Bound, U64Key};
 use protobuf::Message;
 use std::cmp::Ordering;
 use std::str::FromStr;
@@ -43,6 +54,10 @@ const CONTRACT_NAME: &str = "astroport-pair-stable";
 const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
 /// A `reply` call code ID of sub-message.
 const INSTANTIATE_TOKEN_REPLY_ID: u64 = 1;
+/// The default amount of amplification changes returned in a history query
+const DEFAULT_LIMIT: u32 = 10;
+/// The maximum amount of amplification changes returned in a history query
+const MAX_LIMIT: u32 = 30;
 
 /// ## Description
 /// Creates a new contract with the specified parameters in [`InstantiateMsg`].
@@ -100,8 +115,18 @@ pub fn instantiate(
     };
 
     CONFIG.save(deps.storage, &config)?;
+    FACTORY_PARAMS.save(
+        deps.storage,
+        &query_factory_params(
+            &deps.querier,
+            config.factory_addr.clone(),
+            env.contract.address.clone(),
+            config.pair_info.pair_type.clone(),
+        )?,
+    )?;
+    RESERVES.save(deps.storage, &[Uint128::zero(), Uint128::zero()])?;
 
-    let token_name = format_lp_token_name(msg.asset_infos, &deps.querier)?;
+    let token_name = format_lp_token_name(&msg.asset_infos, &deps.querier)?;
 
     // Create LP token
     let sub_msg: Vec<SubMsg> = vec![SubMsg {
@@ -183,14 +208,41 @@ pub fn reply(deps: DepsMut, _env: Env, msg: Reply) -> Result<Response, ContractE
 ///             slippage_tolerance,
 ///             auto_stake,
 ///             receiver,
+///             min_lp_to_receive,
+///             deadline,
 ///         }** Provides liquidity in the pair using the specified input parameters.
 ///
+/// * **ExecuteMsg::ProvideSingleSided { .. }** Not supported.
+///
 /// * **ExecuteMsg::Swap {
 ///             offer_asset,
 ///             belief_price,
 ///             max_spread,
 ///             to,
+///             min_return,
+///             deadline,
 ///         }** Performs an swap using the specified parameters.
+///
+/// * **ExecuteMsg::SwapExactOut {
+///             ask_asset,
+///             max_offer,
+///             to,
+///             deadline,
+///         }** Performs a swap that returns exactly the specified ask asset.
+///
+/// * **ExecuteMsg::FlashLoan { asset, receiver, msg }** Lends an asset from the pool to a receiver contract.
+///
+/// * **ExecuteMsg::AssertFlashLoanRepaid {}** Checks that the flash loan in progress was repaid.
+///
+/// * **ExecuteMsg::Sync {}** Sets the tracked pool reserves to the current balances of the pair.
+///
+/// * **ExecuteMsg::Skim { to }** Sends the balances of the pair that exceed the tracked pool reserves to a recipient.
+///
+/// * **ExecuteMsg::RefreshFactoryParams {}** Updates the fee and Generator information cached from the factory.
+///
+/// * **ExecuteMsg::SetProtocolFeeAccrual { enabled }** Enables or disables the accrual of the Maker fees in the pair.
+///
+/// * **ExecuteMsg::ClaimProtocolFees {}** Sends the Maker fees accrued in the pair to the fee address.
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn execute(
     deps: DepsMut,
@@ -198,6 +250,16 @@ pub fn execute(
     info: MessageInfo,
     msg: ExecuteMsg,
 ) -> Result<Response, ContractError> {
+    // The pool balances are not final until a flash loan is repaid. The flash loan fee is fixed
+    // when the loan is taken, so the cached factory parameters can still be refreshed
+    if !matches!(
+        msg,
+        ExecuteMsg::AssertFlashLoanRepaid {} | ExecuteMsg::RefreshFactoryParams {}
+    ) && FLASH_LOAN.may_load(deps.storage)?.is_some()
+    {
+        return Err(ContractError::FlashLoanInProgress {});
+    }
+
     match msg {
         ExecuteMsg::UpdateConfig { params } => update_config(deps, env, info, params),
         ExecuteMsg::Receive(msg) => receive_cw20(deps, env, info, msg),
@@ -206,6 +268,8 @@ pub fn execute(
             slippage_tolerance,
             auto_stake,
             receiver,
+            min_lp_to_receive,
+            deadline,
         } => provide_liquidity(
             deps,
             env,
@@ -214,12 +278,17 @@ pub fn execute(
             slippage_tolerance,
             auto_stake,
             receiver,
+            min_lp_to_receive,
+            deadline,
         ),
+        ExecuteMsg::ProvideSingleSided { .. } => Err(ContractError::NonSupported {}),
         ExecuteMsg::Swap {
             offer_asset,
             belief_price,
             max_spread,
             to,
+            min_return,
+            deadline,
         } => {
             offer_asset.info.check(deps.api)?;
             if !offer_asset.is_native_token() {
@@ -241,8 +310,49 @@ pub fn execute(
                 belief_price,
                 max_spread,
                 to_addr,
+                min_return,
+                deadline,
+            )
+        }
+        ExecuteMsg::SwapExactOut {
+            ask_asset,
+            max_offer,
+            to,
+            deadline,
+        } => {
+            ask_asset.info.check(deps.api)?;
+
+            let to_addr = if let Some(to_addr) = to {
+                Some(addr_validate_to_lower(deps.api, &to_addr)?)
+            } else {
+                None
+            };
+
+            swap_exact_out(
+                deps,
+                env,
+                info.clone(),
+                info.sender,
+                ask_asset,
+                max_offer,
+                false,
+                to_addr,
+                deadline,
             )
         }
+        ExecuteMsg::FlashLoan {
+            asset,
+            receiver,
+            msg,
+        } => flash_loan(deps, env, info, asset, receiver, msg),
+        ExecuteMsg::AssertFlashLoanRepaid {} => assert_flash_loan_repaid(deps, env, info),
+        ExecuteMsg::Sync {} => sync(deps, env),
+        ExecuteMsg::Skim { to } => skim(deps, env, info, to),
+        ExecuteMsg::RefreshFactoryParams {} => refresh_factory_params(deps, env),
+        ExecuteMsg::SetProtocolFeeAccrual { enabled } => {
+            set_protocol_fee_accrual(deps, info, enabled)
+        }
+        ExecuteMsg::ClaimProtocolFees {} => claim_protocol_fees(deps, info),
     }
 }
 
@@ -270,6 +380,8 @@ pub fn receive_cw20(
             belief_price,
             max_spread,
             to,
+            min_return,
+            deadline,
         }) => {
             // Only an asset (token) contract can execute this message
             let mut authorized: bool = false;
@@ -305,15 +417,70 @@ pub fn receive_cw20(
                 belief_price,
                 max_spread,
                 to_addr,
+                min_return,
+                deadline,
+            )
+        }
+        Ok(Cw20HookMsg::SwapExactOut {
+            ask_asset,
+            to,
+            deadline,
+        }) => {
+            let to_addr = if let Some(to_addr) = to {
+                Some(addr_validate_to_lower(deps.api, to_addr.as_str())?)
+            } else {
+                None
+            };
+
+            swap_exact_out(
+                deps,
+                env,
+                info,
+                Addr::unchecked(cw20_msg.sender),
+                ask_asset,
+                cw20_msg.amount,
+                true,
+                to_addr,
+                deadline,
             )
         }
-        Ok(Cw20HookMsg::WithdrawLiquidity {}) => withdraw_liquidity(
+        Ok(Cw20HookMsg::WithdrawLiquidity {
+            min_assets_out,
+            deadline,
+        }) => withdraw_liquidity(
+            deps,
+            env,
+            info,
+            Addr::unchecked(cw20_msg.sender),
+            cw20_msg.amount,
+            min_assets_out,
+            deadline,
+        ),
+        Ok(Cw20HookMsg::WithdrawLiquidityOneCoin {
+            ask_asset_info,
+            min_return,
+            deadline,
+        }) => withdraw_liquidity_one_coin(
             deps,
             env,
             info,
             Addr::unchecked(cw20_msg.sender),
             cw20_msg.amount,
+            ask_asset_info,
+            min_return,
+            deadline,
         ),
+        Ok(Cw20HookMsg::WithdrawLiquidityImbalanced { assets, deadline }) => {
+            withdraw_liquidity_imbalanced(
+                deps,
+                env,
+                info,
+                Addr::unchecked(cw20_msg.sender),
+                cw20_msg.amount,
+                assets,
+                deadline,
+            )
+        }
         Err(err) => Err(ContractError::Std(err)),
     }
 }
@@ -338,7 +505,14 @@ pub fn receive_cw20(
 ///
 /// * **receiver** is object of type [`Option<String>`]. This is the address that receives LP tokens.
 /// If this address isn't specified, the function will default to the caller.
+///
+/// * **min_lp_to_receive** is an [`Option`] field of type [`Uint128`]. This is the minimum amount of
+/// LP tokens that must be minted, otherwise the transaction fails.
+///
+/// * **deadline** is an [`Option`] field of type [`u64`]. This is the block time (in seconds) after which
+/// the transaction fails.
 /// NOTE - the address that wants to provide liquidity should approve the pair contract to pull its relevant tokens.
+#[allow(clippy::too_many_arguments)]
 pub fn provide_liquidity(
     deps: DepsMut,
     env: Env,
@@ -347,7 +521,12 @@ pub fn provide_liquidity(
     slippage_tolerance: Option<Decimal>,
     auto_stake: Option<bool>,
     receiver: Option<String>,
+    min_lp_to_receive: Option<Uint128>,
+    deadline: Option<u64>,
 ) -> Result<Response, ContractError> {
+    assert_deposits_allowed(deps.storage)?;
+    assert_deadline(&env, deadline)?;
+
     assets[0].info.check(deps.api)?;
     assets[1].info.check(deps.api)?;
 
@@ -357,28 +536,11 @@ pub fn provide_liquidity(
     }
 
     let mut config: Config = CONFIG.load(deps.storage)?;
-    let mut pools: [Asset; 2] = config
-        .pair_info
-        .query_pools(&deps.querier, env.contract.address.clone())?;
-    let deposits: [Uint128; 2] = [
-        assets
-            .iter()
-            .find(|a| a.info.equal(&pools[0].info))
-            .map(|a| a.amount)
-            .expect("Wrong asset info is given"),
-        assets
-            .iter()
-            .find(|a| a.info.equal(&pools[1].info))
-            .map(|a| a.amount)
-            .expect("Wrong asset info is given"),
-    ];
-
-    if deposits[0].is_zero() || deposits[1].is_zero() {
-        return Err(ContractError::InvalidZeroAmount {});
-    }
+    let pools = query_reserves(deps.storage, &config)?;
+    let deposits = get_deposits(&assets, &pools)?;
 
     let mut messages: Vec<CosmosMsg> = vec![];
-    for (i, pool) in pools.iter_mut().enumerate() {
+    for (i, pool) in pools.iter().enumerate() {
         // If the pool is a token contract, then we need to execute a TransferFrom msg to receive funds
         if let AssetInfo::Token { contract_addr, .. } = &pool.info {
             messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
@@ -390,75 +552,40 @@ pub fn provide_liquidity(
                 })?,
                 funds: vec![],
             }))
-        } else {
-            // If the asset is a native token, the pool balance already increased
-            // To calculate the pool balance properly, we should subtract the user deposit from the recorded pool token amount
-            pool.amount = pool.amount.checked_sub(deposits[i])?;
         }
     }
 
-    // Assert that slippage tolerance is respected
-    assert_slippage_tolerance(&slippage_tolerance, &deposits, &pools)?;
-
     let token_precision_0 = query_token_precision(&deps.querier, pools[0].info.clone())?;
     let token_precision_1 = query_token_precision(&deps.querier, pools[1].info.clone())?;
 
-    let greater_precision = token_precision_0.max(token_precision_1);
-
-    let deposit_amount_0 = adjust_precision(deposits[0], token_precision_0, greater_precision)?;
-    let deposit_amount_1 = adjust_precision(deposits[1], token_precision_1, greater_precision)?;
-
     let total_share = query_supply(&deps.querier, config.pair_info.liquidity_token.clone())?;
-    let share = if total_share.is_zero() {
-        let liquidity_token_precision = query_token_precision(
-            &deps.querier,
-            AssetInfo::Token {
-                contract_addr: config.pair_info.liquidity_token.clone(),
-            },
-        )?;
-
-        // Initial share = collateral amount
-        adjust_precision(
-            Uint128::new(
-                (U256::from(deposit_amount_0.u128()) * U256::from(deposit_amount_1.u128()))
-                    .integer_sqrt()
-                    .as_u128(),
-            ),
-            greater_precision,
-            liquidity_token_precision,
-        )?
-    } else {
-        let leverage = compute_current_amp(&config, &env)?
-            .checked_mul(u64::from(N_COINS))
-            .unwrap();
-
-        let mut pool_amount_0 =
-            adjust_precision(pools[0].amount, token_precision_0, greater_precision)?;
-        let mut pool_amount_1 =
-            adjust_precision(pools[1].amount, token_precision_1, greater_precision)?;
-
-        let d_before_addition_liquidity =
-            compute_d(leverage, pool_amount_0.u128(), pool_amount_1.u128()).unwrap();
-
-        pool_amount_0 = pool_amount_0.checked_add(deposit_amount_0)?;
-        pool_amount_1 = pool_amount_1.checked_add(deposit_amount_1)?;
+    let share = calculate_share(
+        deps.as_ref(),
+        &env,
+        &config,
+        &deposits,
+        &pools,
+        [token_precision_0, token_precision_1],
+        total_share,
+        slippage_tolerance,
+    )?;
 
-        let d_after_addition_liquidity =
-            compute_d(leverage, pool_amount_0.u128(), pool_amount_1.u128()).unwrap();
+    if total_share.is_zero() {
+        // Lock the minimum liquidity in the pair forever, so the share price can't be inflated
+        messages.extend(mint_liquidity_token_message(
+            deps.as_ref(),
+            &config,
+            env.clone(),
+            env.contract.address.clone(),
+            MINIMUM_LIQUIDITY,
+            false,
+        )?);
+    }
 
-        // d after adding liquidity may be less than or equal to d before adding liquidity because of rounding
-        if d_before_addition_liquidity >= d_after_addition_liquidity {
-            return Err(ContractError::LiquidityAmountTooSmall {});
+    if let Some(min_lp_to_receive) = min_lp_to_receive {
+        if share < min_lp_to_receive {
+            return Err(ContractError::MinLpToReceiveAssertion {});
         }
-
-        total_share.multiply_ratio(
-            d_after_addition_liquidity - d_before_addition_liquidity,
-            d_before_addition_liquidity,
-        )
-    };
-
-    if share.is_zero() {
-        return Err(ContractError::LiquidityAmountTooSmall {});
     }
 
     // Mint LP token for the caller (or for the receiver if it was set)
@@ -485,8 +612,17 @@ pub fn provide_liquidity(
         config.price1_cumulative_last = price1_cumulative_new;
         config.block_time_last = block_time;
         CONFIG.save(deps.storage, &config)?;
+        write_price_observation(deps.storage, &config)?;
     }
 
+    RESERVES.save(
+        deps.storage,
+        &[
+            pools[0].amount.checked_add(deposits[0])?,
+            pools[1].amount.checked_add(deposits[1])?,
+        ],
+    )?;
+
     Ok(Response::new().add_messages(messages).add_attributes(vec![
         attr("action", "provide_liquidity"),
         attr("sender", info.sender.as_str()),
@@ -533,8 +669,7 @@ fn mint_liquidity_token_message(
     }
 
     // Mint for the contract and stake into the Generator
-    let generator =
-        query_factory_config(&deps.querier, config.clone().factory_addr)?.generator_address;
+    let generator = FACTORY_PARAMS.load(deps.storage)?.generator_address;
 
     if generator.is_none() {
         return Err(ContractError::AutoStakeError {});
@@ -561,6 +696,127 @@ fn mint_liquidity_token_message(
     ])
 }
 
+/// ## Description
+/// Returns the deposit amounts in the order of the pool assets. Both pool assets must be deposited.
+/// ## Params
+/// * **assets** is an array with two objects of type [`Asset`]. These are the assets to deposit.
+///
+/// * **pools** are an array of [`Asset`] type items. These are the assets available in the pool.
+fn get_deposits(assets: &[Asset; 2], pools: &[Asset; 2]) -> Result<[Uint128; 2], ContractError> {
+    let deposit = |pool: &Asset| {
+        assets
+            .iter()
+            .find(|a| a.info.equal(&pool.info))
+            .map(|a| a.amount)
+            .ok_or(ContractError::AssetMismatch {})
+    };
+    let deposits = [deposit(&pools[0])?, deposit(&pools[1])?];
+
+    if deposits[0].is_zero() || deposits[1].is_zero() {
+        return Err(ContractError::InvalidZeroAmount {});
+    }
+
+    Ok(deposits)
+}
+
+/// ## Description
+/// Returns the amount of LP tokens minted for a deposit. The share is proportional to the growth of the
+/// stableswap invariant D. The first deposit mints the geometric mean of the deposited amounts minus
+/// the [`MINIMUM_LIQUIDITY`] that is locked in the pair.
+/// ## Params
+/// * **deps** is an object of type [`Deps`].
+///
+/// * **env** is an object of type [`Env`].
+///
+/// * **config** is an object of type [`Config`].
+///
+/// * **deposits** is an array with two objects of type [`Uint128`]. These are the deposits in the order of the pool assets.
+///
+/// * **pools** are an array of [`Asset`] type items. These are the assets available in the pool.
+///
+/// * **token_precisions** is an array with two objects of type [`u8`]. These are the precisions of the pool assets.
+///
+/// * **total_share** is an object of type [`Uint128`]. This is the total amount of LP tokens.
+///
+/// * **slippage_tolerance** is an [`Option`] field of type [`Decimal`]. It is used to specify how much
+/// the pool price can move until the provide liquidity transaction goes through.
+#[allow(clippy::too_many_arguments)]
+fn calculate_share(
+    deps: Deps,
+    env: &Env,
+    config: &Config,
+    deposits: &[Uint128; 2],
+    pools: &[Asset; 2],
+    token_precisions: [u8; 2],
+    total_share: Uint128,
+    slippage_tolerance: Option<Decimal>,
+) -> Result<Uint128, ContractError> {
+    // Assert that slippage tolerance is respected
+    assert_slippage_tolerance(&slippage_tolerance, deposits, pools)?;
+
+    let [token_precision_0, token_precision_1] = token_precisions;
+    let greater_precision = token_precision_0.max(token_precision_1);
+
+    let deposit_amount_0 = adjust_precision(deposits[0], token_precision_0, greater_precision)?;
+    let deposit_amount_1 = adjust_precision(deposits[1], token_precision_1, greater_precision)?;
+
+    let share = if total_share.is_zero() {
+        let liquidity_token_precision = query_token_precision(
+            &deps.querier,
+            AssetInfo::Token {
+                contract_addr: config.pair_info.liquidity_token.clone(),
+            },
+        )?;
+
+        // Initial share = collateral amount
+        adjust_precision(
+            Uint128::new(
+                (U256::from(deposit_amount_0.u128()) * U256::from(deposit_amount_1.u128()))
+                    .integer_sqrt()
+                    .as_u128(),
+            ),
+            greater_precision,
+            liquidity_token_precision,
+        )?
+        .checked_sub(MINIMUM_LIQUIDITY)
+        .map_err(|_| ContractError::MinimumLiquidityAmountError {})?
+    } else {
+        let leverage = compute_current_amp(config, env)?
+            .checked_mul(u64::from(N_COINS))
+            .unwrap();
+
+        let mut pool_amount_0 =
+            adjust_precision(pools[0].amount, token_precision_0, greater_precision)?;
+        let mut pool_amount_1 =
+            adjust_precision(pools[1].amount, token_precision_1, greater_precision)?;
+
+        let d_before_addition_liquidity =
+            compute_d(leverage, pool_amount_0.u128(), pool_amount_1.u128()).unwrap();
+
+        pool_amount_0 = pool_amount_0.checked_add(deposit_amount_0)?;
+        pool_amount_1 = pool_amount_1.checked_add(deposit_amount_1)?;
+
+        let d_after_addition_liquidity =
+            compute_d(leverage, pool_amount_0.u128(), pool_amount_1.u128()).unwrap();
+
+        // d after adding liquidity may be less than or equal to d before adding liquidity because of rounding
+        if d_before_addition_liquidity >= d_after_addition_liquidity {
+            return Err(ContractError::LiquidityAmountTooSmall {});
+        }
+
+        total_share.multiply_ratio(
+            d_after_addition_liquidity - d_before_addition_liquidity,
+            d_before_addition_liquidity,
+        )
+    };
+
+    if share.is_zero() {
+        return Err(ContractError::LiquidityAmountTooSmall {});
+    }
+
+    Ok(share)
+}
+
 /// ## Description
 /// Withdraw liquidity from the pool. Returns a [`ContractError`] on failure,
 /// otherwise returns a [`Response`] with the specified attributes if the operation was successful.
@@ -574,13 +830,23 @@ fn mint_liquidity_token_message(
 /// * **sender** is an object of type [`Addr`]. This is the address that will receive the withdrawn liquidity.
 ///
 /// * **amount** is an object of type [`Uint128`]. This is the amount of LP tokens to burn and withdraw liquidity with.
+///
+/// * **min_assets_out** is an [`Option`] field of type [`Vec<Asset>`]. These are the minimum amounts of assets
+/// to receive, otherwise the transaction fails.
+///
+/// * **deadline** is an [`Option`] field of type [`u64`]. This is the block time (in seconds) after which
+/// the transaction fails.
 pub fn withdraw_liquidity(
     deps: DepsMut,
     env: Env,
     info: MessageInfo,
     sender: Addr,
     amount: Uint128,
+    min_assets_out: Option<Vec<Asset>>,
+    deadline: Option<u64>,
 ) -> Result<Response, ContractError> {
+    assert_deadline(&env, deadline)?;
+
     let mut config: Config = CONFIG.load(deps.storage).unwrap();
 
     if info.sender != config.pair_info.liquidity_token {
@@ -589,6 +855,7 @@ pub fn withdraw_liquidity(
 
     let (pools, total_share) = pool_info(deps.as_ref(), config.clone())?;
     let refund_assets = get_share_in_assets(&pools, amount, total_share);
+    assert_min_assets_out(&refund_assets, min_assets_out)?;
 
     // Accumulate prices for the assets in the pool
     if let Some((price0_cumulative_new, price1_cumulative_new, block_time)) = accumulate_prices(
@@ -603,8 +870,17 @@ pub fn withdraw_liquidity(
         config.price1_cumulative_last = price1_cumulative_new;
         config.block_time_last = block_time;
         CONFIG.save(deps.storage, &config)?;
+        write_price_observation(deps.storage, &config)?;
     }
 
+    RESERVES.save(
+        deps.storage,
+        &[
+            pools[0].amount.checked_sub(refund_assets[0].amount)?,
+            pools[1].amount.checked_sub(refund_assets[1].amount)?,
+        ],
+    )?;
+
     let messages: Vec<CosmosMsg> = vec![
         refund_assets[0]
             .clone()
@@ -665,9 +941,9 @@ pub fn get_share_in_assets(
 }
 
 /// ## Description
-/// Performs a swap with the specified parameters.
-/// Returns a [`ContractError`] on failure, otherwise returns a [`Response`] with the
-/// specified attributes if the operation was successful.
+/// Withdraw liquidity from the pool in a single asset. The withdrawal is charged a fee on the part
+/// that differs from the current pool ratio. Returns a [`ContractError`] on failure,
+/// otherwise returns a [`Response`] with the specified attributes if the operation was successful.
 /// ## Params
 /// * **deps** is an object of type [`DepsMut`].
 ///
@@ -675,101 +951,677 @@ pub fn get_share_in_assets(
 ///
 /// * **info** is an object of type [`MessageInfo`].
 ///
-/// * **sender** is an object of type [`Addr`]. This is the default recipient of the swap operation.
+/// * **sender** is an object of type [`Addr`]. This is the address that will receive the withdrawn liquidity.
 ///
-/// * **offer_asset** is an object of type [`Asset`]. This is the asset to swap.
+/// * **amount** is an object of type [`Uint128`]. This is the amount of LP tokens to burn.
 ///
-/// * **belief_price** is an object of type [`Option<Decimal>`]. This is used to calculate the maximum spread.
+/// * **ask_asset_info** is an object of type [`AssetInfo`]. This is the asset to withdraw.
 ///
-/// * **max_spread** is an object of type [`Option<Decimal>`]. This is the maximum spread allowed for the swap.
+/// * **min_return** is an [`Option`] field of type [`Uint128`]. This is the minimum amount of the ask asset
+/// to receive, otherwise the transaction fails.
 ///
-/// * **to** is an object of type [`Option<Addr>`]. This is the address that receives ask tokens.
-/// NOTE - the address that wants to swap should approve the pair contract to pull the offer token.
+/// * **deadline** is an [`Option`] field of type [`u64`]. This is the block time (in seconds) after which
+/// the transaction fails.
 #[allow(clippy::too_many_arguments)]
-pub fn swap(
+pub fn withdraw_liquidity_one_coin(
     deps: DepsMut,
     env: Env,
     info: MessageInfo,
     sender: Addr,
-    offer_asset: Asset,
-    belief_price: Option<Decimal>,
-    max_spread: Option<Decimal>,
-    to: Option<Addr>,
+    amount: Uint128,
+    ask_asset_info: AssetInfo,
+    min_return: Option<Uint128>,
+    deadline: Option<u64>,
 ) -> Result<Response, ContractError> {
-    offer_asset.assert_sent_native_token_balance(&info)?;
+    assert_deadline(&env, deadline)?;
 
     let mut config: Config = CONFIG.load(deps.storage)?;
 
-    // If the asset balance already increased
-    // We should subtract the user deposit from the pool offer asset amount
-    let pools: Vec<Asset> = config
-        .pair_info
-        .query_pools(&deps.querier, env.clone().contract.address)?
-        .iter()
-        .map(|p| {
-            let mut p = p.clone();
-            if p.info.equal(&offer_asset.info) {
-                p.amount = p.amount.checked_sub(offer_asset.amount).unwrap();
-            }
+    if info.sender != config.pair_info.liquidity_token {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let (pools, total_share) = pool_info(deps.as_ref(), config.clone())?;
+    let (ask_index, return_amount, fee_amount) = compute_withdraw_one_coin(
+        deps.as_ref(),
+        &env,
+        &config,
+        &pools,
+        total_share,
+        amount,
+        &ask_asset_info,
+    )?;
+
+    if let Some(min_return) = min_return {
+        if return_amount < min_return {
+            return Err(ContractError::MinReturnAssertion {});
+        }
+    }
+
+    // Accumulate prices for the assets in the pool
+    if let Some((price0_cumulative_new, price1_cumulative_new, block_time)) = accumulate_prices(
+        env,
+        &config,
+        pools[0].amount,
+        query_token_precision(&deps.querier, pools[0].info.clone())?,
+        pools[1].amount,
+        query_token_precision(&deps.querier, pools[1].info.clone())?,
+    )? {
+        config.price0_cumulative_last = price0_cumulative_new;
+        config.price1_cumulative_last = price1_cumulative_new;
+        config.block_time_last = block_time;
+        CONFIG.save(deps.storage, &config)?;
+        write_price_observation(deps.storage, &config)?;
+    }
+
+    let mut reserves = [pools[0].amount, pools[1].amount];
+    reserves[ask_index] = reserves[ask_index].checked_sub(return_amount)?;
+    RESERVES.save(deps.storage, &reserves)?;
+
+    let return_asset = Asset {
+        info: pools[ask_index].info.clone(),
+        amount: return_amount,
+    };
+    let messages: Vec<CosmosMsg> = vec![
+        return_asset
+            .clone()
+            .into_msg(&deps.querier, sender.clone())?,
+        CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: config.pair_info.liquidity_token.to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Burn { amount })?,
+            funds: vec![],
+        }),
+    ];
+
+    Ok(Response::new().add_messages(messages).add_attributes(vec![
+        attr("action", "withdraw_liquidity_one_coin"),
+        attr("sender", sender.as_str()),
+        attr("withdrawn_share", amount.to_string()),
+        attr("return_asset", return_asset.to_string()),
+        attr("fee_amount", fee_amount.to_string()),
+    ]))
+}
+
+/// ## Description
+/// Withdraw exact amounts of assets from the pool. The withdrawal is charged a fee on the part that
+/// differs from the current pool ratio. The sent LP tokens are the maximum amount to burn and the excess
+/// is sent back to the sender. Returns a [`ContractError`] on failure, otherwise returns a [`Response`]
+/// with the specified attributes if the operation was successful.
+/// ## Params
+/// * **deps** is an object of type [`DepsMut`].
+///
+/// * **env** is an object of type [`Env`].
+///
+/// * **info** is an object of type [`MessageInfo`].
+///
+/// * **sender** is an object of type [`Addr`]. This is the address that will receive the withdrawn liquidity.
+///
+/// * **amount** is an object of type [`Uint128`]. This is the maximum amount of LP tokens to burn.
+///
+/// * **assets** is a vector with items of type [`Asset`]. These are the assets to withdraw.
+///
+/// * **deadline** is an [`Option`] field of type [`u64`]. This is the block time (in seconds) after which
+/// the transaction fails.
+pub fn withdraw_liquidity_imbalanced(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    sender: Addr,
+    amount: Uint128,
+    assets: Vec<Asset>,
+    deadline: Option<u64>,
+) -> Result<Response, ContractError> {
+    assert_deadline(&env, deadline)?;
+
+    let mut config: Config = CONFIG.load(deps.storage)?;
+
+    if info.sender != config.pair_info.liquidity_token {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let (pools, total_share) = pool_info(deps.as_ref(), config.clone())?;
+    let withdrawals = get_withdrawals(&assets, &pools)?;
+    let (lp_amount, fees) = compute_withdraw_imbalanced(
+        deps.as_ref(),
+        &env,
+        &config,
+        &pools,
+        total_share,
+        &withdrawals,
+    )?;
+
+    if lp_amount > amount {
+        return Err(ContractError::MaxLpToBurnAssertion {});
+    }
+
+    // Accumulate prices for the assets in the pool
+    if let Some((price0_cumulative_new, price1_cumulative_new, block_time)) = accumulate_prices(
+        env,
+        &config,
+        pools[0].amount,
+        query_token_precision(&deps.querier, pools[0].info.clone())?,
+        pools[1].amount,
+        query_token_precision(&deps.querier, pools[1].info.clone())?,
+    )? {
+        config.price0_cumulative_last = price0_cumulative_new;
+        config.price1_cumulative_last = price1_cumulative_new;
+        config.block_time_last = block_time;
+        CONFIG.save(deps.storage, &config)?;
+        write_price_observation(deps.storage, &config)?;
+    }
+
+    RESERVES.save(
+        deps.storage,
+        &[
+            pools[0].amount.checked_sub(withdrawals[0])?,
+            pools[1].amount.checked_sub(withdrawals[1])?,
+        ],
+    )?;
 
-            p
+    let refund_assets: Vec<Asset> = pools
+        .iter()
+        .zip(withdrawals.iter())
+        .map(|(pool, withdrawal)| Asset {
+            info: pool.info.clone(),
+            amount: *withdrawal,
         })
         .collect();
 
-    let offer_pool: Asset;
-    let ask_pool: Asset;
+    let mut messages = refund_assets
+        .iter()
+        .filter(|asset| !asset.amount.is_zero())
+        .map(|asset| asset.clone().into_msg(&deps.querier, sender.clone()))
+        .collect::<StdResult<Vec<CosmosMsg>>>()?;
+    messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: config.pair_info.liquidity_token.to_string(),
+        msg: to_binary(&Cw20ExecuteMsg::Burn { amount: lp_amount })?,
+        funds: vec![],
+    }));
+
+    // Send back the LP tokens that weren't burned
+    let lp_refund = amount - lp_amount;
+    if !lp_refund.is_zero() {
+        messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: config.pair_info.liquidity_token.to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: sender.to_string(),
+                amount: lp_refund,
+            })?,
+            funds: vec![],
+        }));
+    }
+
+    Ok(Response::new().add_messages(messages).add_attributes(vec![
+        attr("action", "withdraw_liquidity_imbalanced"),
+        attr("sender", sender.as_str()),
+        attr("withdrawn_share", lp_amount.to_string()),
+        attr(
+            "refund_assets",
+            format!("{}, {}", refund_assets[0], refund_assets[1]),
+        ),
+        attr("fees", format!("{}, {}", fees[0], fees[1])),
+    ]))
+}
+
+/// ## Description
+/// Returns the withdrawal amounts in the order of the pool assets. Pool assets that aren't withdrawn
+/// get a zero amount, but at least one of the amounts must be non-zero.
+/// ## Params
+/// * **assets** is a slice of [`Asset`] type items. These are the assets to withdraw.
+///
+/// * **pools** are an array of [`Asset`] type items. These are the assets available in the pool.
+fn get_withdrawals(assets: &[Asset], pools: &[Asset; 2]) -> Result<[Uint128; 2], ContractError> {
+    for (i, asset) in assets.iter().enumerate() {
+        if assets[i + 1..].iter().any(|a| a.info.equal(&asset.info)) {
+            return Err(ContractError::DoublingAssets {});
+        }
+        if !pools.iter().any(|pool| pool.info.equal(&asset.info)) {
+            return Err(ContractError::AssetMismatch {});
+        }
+    }
+
+    let withdrawal = |pool: &Asset| {
+        assets
+            .iter()
+            .find(|a| a.info.equal(&pool.info))
+            .map(|a| a.amount)
+            .unwrap_or_default()
+    };
+    let withdrawals = [withdrawal(&pools[0]), withdrawal(&pools[1])];
+
+    if withdrawals[0].is_zero() && withdrawals[1].is_zero() {
+        return Err(ContractError::InvalidZeroAmount {});
+    }
+
+    Ok(withdrawals)
+}
+
+/// ## Description
+/// Returns the index of the ask pool, the amount of the ask asset withdrawn for burning `lp_amount`
+/// LP tokens and the fee charged on the withdrawal.
+/// ## Params
+/// * **deps** is an object of type [`Deps`].
+///
+/// * **env** is an object of type [`Env`].
+///
+/// * **config** is an object of type [`Config`].
+///
+/// * **pools** are an array of [`Asset`] type items. These are the assets available in the pool.
+///
+/// * **total_share** is an object of type [`Uint128`]. This is the total amount of LP tokens.
+///
+/// * **lp_amount** is an object of type [`Uint128`]. This is the amount of LP tokens to burn.
+///
+/// * **ask_asset_info** is an object of type [`AssetInfo`]. This is the asset to withdraw.
+fn compute_withdraw_one_coin(
+    deps: Deps,
+    env: &Env,
+    config: &Config,
+    pools: &[Asset; 2],
+    total_share: Uint128,
+    lp_amount: Uint128,
+    ask_asset_info: &AssetInfo,
+) -> Result<(usize, Uint128, Uint128), ContractError> {
+    if lp_amount.is_zero() {
+        return Err(ContractError::InvalidZeroAmount {});
+    }
+
+    let ask_index = pools
+        .iter()
+        .position(|pool| pool.info.equal(ask_asset_info))
+        .ok_or(ContractError::AssetMismatch {})?;
+    let other_index = 1 - ask_index;
+
+    let ask_precision = query_token_precision(&deps.querier, pools[ask_index].info.clone())?;
+    let other_precision = query_token_precision(&deps.querier, pools[other_index].info.clone())?;
+    let greater_precision = ask_precision.max(other_precision);
+
+    let ask_pool = adjust_precision(pools[ask_index].amount, ask_precision, greater_precision)?;
+    let other_pool = adjust_precision(
+        pools[other_index].amount,
+        other_precision,
+        greater_precision,
+    )?;
+
+    let fee_info = FACTORY_PARAMS.load(deps.storage)?.fee_info;
+    let (return_amount, fee_amount) = calc_withdraw_one_coin(
+        ask_pool.u128(),
+        other_pool.u128(),
+        lp_amount.u128(),
+        total_share.u128(),
+        compute_current_amp(config, env)?,
+        fee_info.total_fee_rate,
+    )
+    .ok_or(ContractError::LiquidityAmountTooSmall {})?;
+
+    let return_amount = adjust_precision(
+        Uint128::new(return_amount),
+        greater_precision,
+        ask_precision,
+    )?;
+    if return_amount.is_zero() {
+        return Err(ContractError::InvalidZeroAmount {});
+    }
+
+    Ok((
+        ask_index,
+        return_amount,
+        adjust_precision(Uint128::new(fee_amount), greater_precision, ask_precision)?,
+    ))
+}
+
+/// ## Description
+/// Returns the amount of LP tokens burned for withdrawing exact amounts of assets and the fees charged
+/// on the withdrawal in the order of the pool assets.
+/// ## Params
+/// * **deps** is an object of type [`Deps`].
+///
+/// * **env** is an object of type [`Env`].
+///
+/// * **config** is an object of type [`Config`].
+///
+/// * **pools** are an array of [`Asset`] type items. These are the assets available in the pool.
+///
+/// * **total_share** is an object of type [`Uint128`]. This is the total amount of LP tokens.
+///
+/// * **withdrawals** is an array with two objects of type [`Uint128`]. These are the amounts to withdraw
+/// in the order of the pool assets.
+fn compute_withdraw_imbalanced(
+    deps: Deps,
+    env: &Env,
+    config: &Config,
+    pools: &[Asset; 2],
+    total_share: Uint128,
+    withdrawals: &[Uint128; 2],
+) -> Result<(Uint128, [Uint128; 2]), ContractError> {
+    let token_precision_0 = query_token_precision(&deps.querier, pools[0].info.clone())?;
+    let token_precision_1 = query_token_precision(&deps.querier, pools[1].info.clone())?;
+    let greater_precision = token_precision_0.max(token_precision_1);
+
+    let fee_info = FACTORY_PARAMS.load(deps.storage)?.fee_info;
+    let (lp_amount, fees) = calc_withdraw_imbalanced(
+        [
+            adjust_precision(pools[0].amount, token_precision_0, greater_precision)?.u128(),
+            adjust_precision(pools[1].amount, token_precision_1, greater_precision)?.u128(),
+        ],
+        [
+            adjust_precision(withdrawals[0], token_precision_0, greater_precision)?.u128(),
+            adjust_precision(withdrawals[1], token_precision_1, greater_precision)?.u128(),
+        ],
+        total_share.u128(),
+        compute_current_amp(config, env)?,
+        fee_info.total_fee_rate,
+    )
+    .ok_or(ContractError::LiquidityAmountTooSmall {})?;
+
+    Ok((
+        Uint128::new(lp_amount),
+        [
+            adjust_precision(Uint128::new(fees[0]), greater_precision, token_precision_0)?,
+            adjust_precision(Uint128::new(fees[1]), greater_precision, token_precision_1)?,
+        ],
+    ))
+}
+
+/// ## Description
+/// Performs a swap with the specified parameters.
+/// Returns a [`ContractError`] on failure, otherwise returns a [`Response`] with the
+/// specified attributes if the operation was successful.
+/// ## Params
+/// * **deps** is an object of type [`DepsMut`].
+///
+/// * **env** is an object of type [`Env`].
+///
+/// * **info** is an object of type [`MessageInfo`].
+///
+/// * **sender** is an object of type [`Addr`]. This is the default recipient of the swap operation.
+///
+/// * **offer_asset** is an object of type [`Asset`]. This is the asset to swap.
+///
+/// * **belief_price** is an object of type [`Option<Decimal>`]. This is used to calculate the maximum spread.
+///
+/// * **max_spread** is an object of type [`Option<Decimal>`]. This is the maximum spread allowed for the swap.
+///
+/// * **to** is an object of type [`Option<Addr>`]. This is the address that receives ask tokens.
+///
+/// * **min_return** is an object of type [`Option<Uint128>`]. This is the minimum amount of ask assets to receive.
+///
+/// * **deadline** is an object of type [`Option<u64>`]. This is the block time (in seconds) after which the swap fails.
+/// NOTE - the address that wants to swap should approve the pair contract to pull the offer token.
+#[allow(clippy::too_many_arguments)]
+pub fn swap(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    sender: Addr,
+    offer_asset: Asset,
+    belief_price: Option<Decimal>,
+    max_spread: Option<Decimal>,
+    to: Option<Addr>,
+    min_return: Option<Uint128>,
+    deadline: Option<u64>,
+) -> Result<Response, ContractError> {
+    assert_swaps_allowed(deps.storage)?;
+    assert_deadline(&env, deadline)?;
+
+    offer_asset.assert_sent_native_token_balance(&info)?;
+
+    let mut config: Config = CONFIG.load(deps.storage)?;
+
+    let pools = query_reserves(deps.storage, &config)?;
+
+    let offer_pool: Asset;
+    let ask_pool: Asset;
+
+    if offer_asset.info.equal(&pools[0].info) {
+        offer_pool = pools[0].clone();
+        ask_pool = pools[1].clone();
+    } else if offer_asset.info.equal(&pools[1].info) {
+        offer_pool = pools[1].clone();
+        ask_pool = pools[0].clone();
+    } else {
+        return Err(ContractError::AssetMismatch {});
+    }
+
+    // Get the fee info cached from the factory
+    let fee_info = FACTORY_PARAMS.load(deps.storage)?.fee_info;
+    let fee_multiplier = FEE_MULTIPLIER.may_load(deps.storage)?;
+
+    let offer_amount = offer_asset.amount;
+    let (return_amount, spread_amount, commission_amount) = compute_swap(
+        offer_pool.amount,
+        query_token_precision(&deps.querier, offer_pool.info)?,
+        ask_pool.amount,
+        query_token_precision(&deps.querier, ask_pool.info.clone())?,
+        offer_amount,
+        fee_info.total_fee_rate,
+        fee_multiplier,
+        compute_current_amp(&config, &env)?,
+    )?;
+
+    // Check the max spread limit (if it was specified)
+    assert_max_spread(
+        belief_price,
+        max_spread,
+        offer_amount,
+        return_amount + commission_amount,
+        spread_amount,
+    )?;
+
+    // Check the minimum return amount (if it was specified)
+    if let Some(min_return) = min_return {
+        if return_amount < min_return {
+            return Err(ContractError::MinReturnAssertion {});
+        }
+    }
+
+    // Compute the tax for the ask asset
+    let return_asset = Asset {
+        info: ask_pool.info.clone(),
+        amount: return_amount,
+    };
+
+    let tax_amount = return_asset.compute_tax(&deps.querier)?;
+
+    let receiver = to.unwrap_or_else(|| sender.clone());
+
+    let mut messages: Vec<CosmosMsg> =
+        vec![return_asset.into_msg(&deps.querier, receiver.clone())?];
+
+    // Maker fee
+    let mut maker_fee_amount = Uint128::new(0);
+    if let Some(fee_address) = fee_info.fee_address {
+        if let Some(f) = calculate_maker_fee(
+            ask_pool.info.clone(),
+            commission_amount,
+            fee_info.maker_fee_rate,
+        ) {
+            maker_fee_amount = f.amount;
+            messages.extend(pay_maker_fee(
+                deps.storage,
+                &deps.querier,
+                &config,
+                f,
+                fee_address,
+            )?);
+        }
+    }
+
+    // Accumulate prices for the assets in the pool
+    if let Some((price0_cumulative_new, price1_cumulative_new, block_time)) = accumulate_prices(
+        env,
+        &config,
+        pools[0].amount,
+        query_token_precision(&deps.querier, pools[0].info.clone())?,
+        pools[1].amount,
+        query_token_precision(&deps.querier, pools[1].info.clone())?,
+    )? {
+        config.price0_cumulative_last = price0_cumulative_new;
+        config.price1_cumulative_last = price1_cumulative_new;
+        config.block_time_last = block_time;
+        CONFIG.save(deps.storage, &config)?;
+        write_price_observation(deps.storage, &config)?;
+    }
+
+    save_reserves_after_swap(
+        deps.storage,
+        &pools,
+        &offer_asset.info,
+        offer_amount,
+        return_amount.checked_add(maker_fee_amount)?,
+    )?;
+
+    Ok(Response::new()
+        .add_messages(
+            // 1. send collateral token from the contract to a user
+            // 2. send inactive commission to collector
+            messages,
+        )
+        .add_attribute("action", "swap")
+        .add_attribute("sender", sender.as_str())
+        .add_attribute("receiver", receiver.as_str())
+        .add_attribute("offer_asset", offer_asset.info.to_string())
+        .add_attribute("ask_asset", ask_pool.info.to_string())
+        .add_attribute("offer_amount", offer_amount.to_string())
+        .add_attribute("return_amount", return_amount.to_string())
+        .add_attribute("tax_amount", tax_amount.to_string())
+        .add_attribute("spread_amount", spread_amount.to_string())
+        .add_attribute("commission_amount", commission_amount.to_string())
+        .add_attribute("maker_fee_amount", maker_fee_amount.to_string()))
+}
+
+/// ## Description
+/// Performs a swap that returns exactly the specified ask asset. The offer amount is calculated
+/// the same way as in a reverse simulation and must not exceed `max_offer`. Native offer assets and
+/// token offer assets sent via [`Cw20HookMsg::SwapExactOut`] are received in advance, so the excess is
+/// sent back to the sender, otherwise only the required amount of offer tokens is pulled from the sender.
+/// Returns an [`ContractError`] on failure, otherwise returns the [`Response`] with the specified attributes if the operation was successful.
+/// ## Params
+/// * **deps** is an object of type [`DepsMut`].
+///
+/// * **env** is an object of type [`Env`].
+///
+/// * **info** is an object of type [`MessageInfo`].
+///
+/// * **sender** is an object of type [`Addr`]. This is the sender of the swap operation.
+///
+/// * **ask_asset** is an object of type [`Asset`]. This is the asset and the exact amount to receive from the swap.
+///
+/// * **max_offer** is an object of type [`Uint128`]. This is the maximum amount of offer assets to pay for the ask asset.
+///
+/// * **offer_received** is an object of type [`bool`]. Determines whether the offer tokens were already sent to the pair.
+///
+/// * **to** is an object of type [`Option<Addr>`]. Sets the recipient of the swap operation.
+///
+/// * **deadline** is an [`Option`] field of type [`u64`]. This is the block time (in seconds) after which
+/// the transaction fails.
+/// NOTE - the address that wants to swap should approve the pair contract to pull the offer token.
+#[allow(clippy::too_many_arguments)]
+pub fn swap_exact_out(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    sender: Addr,
+    ask_asset: Asset,
+    max_offer: Uint128,
+    offer_received: bool,
+    to: Option<Addr>,
+    deadline: Option<u64>,
+) -> Result<Response, ContractError> {
+    assert_swaps_allowed(deps.storage)?;
+    assert_deadline(&env, deadline)?;
+    if ask_asset.amount.is_zero() {
+        return Err(ContractError::InvalidZeroAmount {});
+    }
+
+    let mut config: Config = CONFIG.load(deps.storage)?;
+    let pools = query_reserves(deps.storage, &config)?;
 
-    if offer_asset.info.equal(&pools[0].info) {
-        offer_pool = pools[0].clone();
-        ask_pool = pools[1].clone();
-    } else if offer_asset.info.equal(&pools[1].info) {
-        offer_pool = pools[1].clone();
-        ask_pool = pools[0].clone();
+    let (offer_index, ask_index) = if ask_asset.info.equal(&pools[0].info) {
+        (1, 0)
+    } else if ask_asset.info.equal(&pools[1].info) {
+        (0, 1)
     } else {
         return Err(ContractError::AssetMismatch {});
-    }
+    };
 
-    // Get fee info from the factory
-    let fee_info = query_fee_info(
-        &deps.querier,
-        config.factory_addr.clone(),
-        config.pair_info.pair_type.clone(),
-    )?;
+    let offer_info = pools[offer_index].info.clone();
+    let offer_received = match &offer_info {
+        AssetInfo::NativeToken { .. } => {
+            Asset {
+                info: offer_info.clone(),
+                amount: max_offer,
+            }
+            .assert_sent_native_token_balance(&info)?;
+            true
+        }
+        AssetInfo::Token { contract_addr } => {
+            // Only the offer token contract can send the offer assets in advance
+            if offer_received && contract_addr != &info.sender {
+                return Err(ContractError::Unauthorized {});
+            }
+            offer_received
+        }
+    };
 
-    let offer_amount = offer_asset.amount;
-    let (return_amount, spread_amount, commission_amount) = compute_swap(
+    let offer_pool = pools[offer_index].clone();
+    let ask_pool = pools[ask_index].clone();
+
+    // Get the fee info cached from the factory
+    let fee_info = FACTORY_PARAMS.load(deps.storage)?.fee_info;
+    let fee_multiplier = FEE_MULTIPLIER.may_load(deps.storage)?;
+
+    let (offer_amount, spread_amount, commission_amount) = compute_offer_amount(
         offer_pool.amount,
-        query_token_precision(&deps.querier, offer_pool.info)?,
+        query_token_precision(&deps.querier, offer_pool.info.clone())?,
         ask_pool.amount,
         query_token_precision(&deps.querier, ask_pool.info.clone())?,
-        offer_amount,
+        ask_asset.amount,
         fee_info.total_fee_rate,
+        fee_multiplier,
         compute_current_amp(&config, &env)?,
     )?;
 
-    // Check the max spread limit (if it was specified)
-    assert_max_spread(
-        belief_price,
-        max_spread,
-        offer_amount,
-        return_amount + commission_amount,
-        spread_amount,
-    )?;
-
-    // Compute the tax for the ask asset
-    let return_asset = Asset {
-        info: ask_pool.info.clone(),
-        amount: return_amount,
-    };
+    if offer_amount > max_offer {
+        return Err(ContractError::MaxOfferAssertion {});
+    }
 
-    let tax_amount = return_asset.compute_tax(&deps.querier)?;
+    let mut messages: Vec<CosmosMsg> = vec![];
+    if !offer_received {
+        messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: offer_info.to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::TransferFrom {
+                owner: sender.to_string(),
+                recipient: env.contract.address.to_string(),
+                amount: offer_amount,
+            })?,
+            funds: vec![],
+        }));
+    }
 
+    let tax_amount = ask_asset.compute_tax(&deps.querier)?;
     let receiver = to.unwrap_or_else(|| sender.clone());
+    messages.push(
+        ask_asset
+            .clone()
+            .into_msg(&deps.querier, receiver.clone())?,
+    );
 
-    let mut messages: Vec<CosmosMsg> =
-        vec![return_asset.into_msg(&deps.querier, receiver.clone())?];
+    // Send back the offer assets that were not used in the swap
+    let refund_amount = if offer_received {
+        max_offer - offer_amount
+    } else {
+        Uint128::zero()
+    };
+    if !refund_amount.is_zero() {
+        let refund_asset = Asset {
+            info: offer_info.clone(),
+            amount: refund_amount,
+        };
+        messages.push(refund_asset.into_msg(&deps.querier, sender.clone())?);
+    }
 
-    // Maker fee
+    // Compute the Maker fee
     let mut maker_fee_amount = Uint128::new(0);
     if let Some(fee_address) = fee_info.fee_address {
         if let Some(f) = calculate_maker_fee(
@@ -777,8 +1629,14 @@ pub fn swap(
             commission_amount,
             fee_info.maker_fee_rate,
         ) {
-            messages.push(f.clone().into_msg(&deps.querier, fee_address)?);
             maker_fee_amount = f.amount;
+            messages.extend(pay_maker_fee(
+                deps.storage,
+                &deps.querier,
+                &config,
+                f,
+                fee_address,
+            )?);
         }
     }
 
@@ -795,27 +1653,351 @@ pub fn swap(
         config.price1_cumulative_last = price1_cumulative_new;
         config.block_time_last = block_time;
         CONFIG.save(deps.storage, &config)?;
+        write_price_observation(deps.storage, &config)?;
     }
 
+    save_reserves_after_swap(
+        deps.storage,
+        &pools,
+        &offer_info,
+        offer_amount,
+        ask_asset.amount.checked_add(maker_fee_amount)?,
+    )?;
+
     Ok(Response::new()
         .add_messages(
-            // 1. send collateral token from the contract to a user
-            // 2. send inactive commission to collector
+            // 1. pull offer tokens from the sender (if they were not sent in advance)
+            // 2. send ask assets from the contract to a user
+            // 3. send back the unused offer assets
+            // 4. send inactive commission to collector
             messages,
         )
         .add_attribute("action", "swap")
         .add_attribute("sender", sender.as_str())
         .add_attribute("receiver", receiver.as_str())
-        .add_attribute("offer_asset", offer_asset.info.to_string())
+        .add_attribute("offer_asset", offer_info.to_string())
         .add_attribute("ask_asset", ask_pool.info.to_string())
         .add_attribute("offer_amount", offer_amount.to_string())
-        .add_attribute("return_amount", return_amount.to_string())
+        .add_attribute("return_amount", ask_asset.amount.to_string())
+        .add_attribute("refund_amount", refund_amount.to_string())
         .add_attribute("tax_amount", tax_amount.to_string())
         .add_attribute("spread_amount", spread_amount.to_string())
         .add_attribute("commission_amount", commission_amount.to_string())
         .add_attribute("maker_fee_amount", maker_fee_amount.to_string()))
 }
 
+/// ## Description
+/// Sends an asset from the pool to a receiver contract and calls it back with a [`FlashLoanReceiverMsg`].
+/// The repayment is checked by the [`ExecuteMsg::AssertFlashLoanRepaid`] callback that is executed after the receiver.
+/// Returns a [`ContractError`] on failure, otherwise returns a [`Response`] with the specified
+/// attributes if the operation was successful.
+/// ## Params
+/// * **deps** is an object of type [`DepsMut`].
+///
+/// * **env** is an object of type [`Env`].
+///
+/// * **info** is an object of type [`MessageInfo`].
+///
+/// * **asset** is an object of type [`Asset`]. This is the asset to borrow.
+///
+/// * **receiver** is an [`Option`] field of type [`String`]. This is the contract that receives the borrowed asset
+/// and the callback. If no custom receiver is specified, the sender is called back.
+///
+/// * **msg** is an object of type [`Binary`]. This is the message that is passed to the receiver in the callback.
+pub fn flash_loan(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    asset: Asset,
+    receiver: Option<String>,
+    msg: Binary,
+) -> Result<Response, ContractError> {
+    assert_swaps_allowed(deps.storage)?;
+    asset.info.check(deps.api)?;
+    if asset.amount.is_zero() {
+        return Err(ContractError::InvalidZeroAmount {});
+    }
+
+    let mut config: Config = CONFIG.load(deps.storage)?;
+    let pools = query_reserves(deps.storage, &config)?;
+
+    let borrowed_pool = pools
+        .iter()
+        .find(|p| p.info.equal(&asset.info))
+        .ok_or(ContractError::AssetMismatch {})?;
+    if pools.iter().any(|p| p.amount.is_zero()) || asset.amount >= borrowed_pool.amount {
+        return Err(ContractError::FlashLoanAmountExceedsPool {});
+    }
+
+    // Get the fee info cached from the factory
+    let fee_info = FACTORY_PARAMS.load(deps.storage)?.fee_info;
+
+    // The fee is split between the LPs and the Maker the same way as a swap commission
+    let commission_amount = asset.amount * fee_info.total_fee_rate;
+    let mut maker_fee_amount = Uint128::zero();
+    if fee_info.fee_address.is_some() {
+        if let Some(f) = calculate_maker_fee(
+            asset.info.clone(),
+            commission_amount,
+            fee_info.maker_fee_rate,
+        ) {
+            maker_fee_amount = f.amount;
+        }
+    }
+
+    // Assets that were sent to the pair without a sync must not be usable to repay the loan
+    let balances = query_pool_balances(deps.as_ref(), &config, env.contract.address.clone())?;
+    let unsynced_amounts = [
+        balances[0].amount.checked_sub(pools[0].amount)?,
+        balances[1].amount.checked_sub(pools[1].amount)?,
+    ];
+
+    // Accumulate prices for the assets in the pool
+    if let Some((price0_cumulative_new, price1_cumulative_new, block_time)) = accumulate_prices(
+        env.clone(),
+        &config,
+        pools[0].amount,
+        query_token_precision(&deps.querier, pools[0].info.clone())?,
+        pools[1].amount,
+        query_token_precision(&deps.querier, pools[1].info.clone())?,
+    )? {
+        config.price0_cumulative_last = price0_cumulative_new;
+        config.price1_cumulative_last = price1_cumulative_new;
+        config.block_time_last = block_time;
+        CONFIG.save(deps.storage, &config)?;
+        write_price_observation(deps.storage, &config)?;
+    }
+
+    FLASH_LOAN.save(
+        deps.storage,
+        &FlashLoanState {
+            pools: pools.clone(),
+            unsynced_amounts,
+            asset: asset.clone(),
+            commission_amount,
+            maker_fee_amount,
+            fee_address: fee_info.fee_address,
+        },
+    )?;
+
+    let receiver = addr_validate_to_lower(
+        deps.api,
+        &receiver.unwrap_or_else(|| info.sender.to_string()),
+    )?;
+
+    let messages: Vec<CosmosMsg> = vec![
+        asset.clone().into_msg(&deps.querier, receiver.clone())?,
+        CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: receiver.to_string(),
+            msg: to_binary(&FlashLoanReceiverMsg::FlashLoanCallback {
+                asset: asset.clone(),
+                fee: commission_amount,
+                msg,
+            })?,
+            funds: vec![],
+        }),
+        CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: env.contract.address.to_string(),
+            msg: to_binary(&ExecuteMsg::AssertFlashLoanRepaid {})?,
+            funds: vec![],
+        }),
+    ];
+
+    Ok(Response::new()
+        .add_messages(
+            // 1. send the borrowed asset to the receiver
+            // 2. call the receiver back
+            // 3. check that the loan was repaid
+            messages,
+        )
+        .add_attribute("action", "flash_loan")
+        .add_attribute("sender", info.sender.as_str())
+        .add_attribute("receiver", receiver.as_str())
+        .add_attribute("asset", asset.to_string())
+        .add_attribute("commission_amount", commission_amount.to_string())
+        .add_attribute("maker_fee_amount", maker_fee_amount.to_string()))
+}
+
+/// ## Description
+/// Checks that the flash loan in progress was repaid: the D invariant after the loan (without the Maker fee)
+/// must be at least the invariant before the loan with the LP part of the fee added to the borrowed asset.
+/// Pays the Maker fee and unlocks the pair. Returns a [`ContractError`] on failure, otherwise returns
+/// a [`Response`] with the specified attributes if the operation was successful.
+/// ## Params
+/// * **deps** is an object of type [`DepsMut`].
+///
+/// * **env** is an object of type [`Env`].
+///
+/// * **info** is an object of type [`MessageInfo`].
+pub fn assert_flash_loan_repaid(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    // Callback functions can only be called by this contract
+    if info.sender != env.contract.address {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let flash_loan = FLASH_LOAN.load(deps.storage)?;
+    FLASH_LOAN.remove(deps.storage);
+
+    let config: Config = CONFIG.load(deps.storage)?;
+    let mut pools = query_pool_balances(deps.as_ref(), &config, env.contract.address.clone())?;
+    let mut expected_pools = flash_loan.pools;
+
+    // Assets that were held on top of the reserves before the loan are not a repayment and stay unsynced
+    for (pool, unsynced_amount) in pools.iter_mut().zip(flash_loan.unsynced_amounts.iter()) {
+        pool.amount = pool
+            .amount
+            .checked_sub(*unsynced_amount)
+            .map_err(|_| ContractError::FlashLoanNotRepaid {})?;
+    }
+
+    let lp_fee_amount = flash_loan
+        .commission_amount
+        .checked_sub(flash_loan.maker_fee_amount)?;
+    for (pool, expected_pool) in pools.iter_mut().zip(expected_pools.iter_mut()) {
+        if pool.info.equal(&flash_loan.asset.info) {
+            // The Maker fee leaves the pool, so it doesn't count towards the invariant
+            pool.amount = pool
+                .amount
+                .checked_sub(flash_loan.maker_fee_amount)
+                .map_err(|_| ContractError::FlashLoanNotRepaid {})?;
+            expected_pool.amount = expected_pool.amount.checked_add(lp_fee_amount)?;
+        }
+    }
+
+    let token_precision_0 = query_token_precision(&deps.querier, pools[0].info.clone())?;
+    let token_precision_1 = query_token_precision(&deps.querier, pools[1].info.clone())?;
+    let greater_precision = token_precision_0.max(token_precision_1);
+    let leverage = compute_current_amp(&config, &env)?
+        .checked_mul(u64::from(N_COINS))
+        .unwrap();
+
+    let compute_pool_d = |pools: &[Asset; 2]| -> StdResult<u128> {
+        let amount_0 = adjust_precision(pools[0].amount, token_precision_0, greater_precision)?;
+        let amount_1 = adjust_precision(pools[1].amount, token_precision_1, greater_precision)?;
+
+        compute_d(leverage, amount_0.u128(), amount_1.u128())
+            .ok_or_else(|| StdError::generic_err("Failed to compute the pool invariant"))
+    };
+
+    if compute_pool_d(&pools)? < compute_pool_d(&expected_pools)? {
+        return Err(ContractError::FlashLoanNotRepaid {});
+    }
+
+    // Anything repaid on top of the loan stays in the pool
+    RESERVES.save(deps.storage, &[pools[0].amount, pools[1].amount])?;
+
+    let mut messages: Vec<CosmosMsg> = vec![];
+    if let Some(fee_address) = flash_loan.fee_address {
+        if !flash_loan.maker_fee_amount.is_zero() {
+            let maker_fee = Asset {
+                info: flash_loan.asset.info,
+                amount: flash_loan.maker_fee_amount,
+            };
+            messages.extend(pay_maker_fee(
+                deps.storage,
+                &deps.querier,
+                &config,
+                maker_fee,
+                fee_address,
+            )?);
+        }
+    }
+
+    Ok(Response::new()
+        .add_messages(messages)
+        .add_attribute("action", "assert_flash_loan_repaid")
+        .add_attribute("maker_fee_amount", flash_loan.maker_fee_amount.to_string()))
+}
+
+/// ## Description
+/// Sets the tracked pool reserves to the current balances of the pair (without the accrued protocol fees),
+/// so that assets sent to the pair directly are added to the pool. Cumulative prices are accumulated using the reserves before the sync.
+/// Returns a [`ContractError`] on failure, otherwise returns a [`Response`] with the specified
+/// attributes if the operation was successful.
+/// ## Params
+/// * **deps** is an object of type [`DepsMut`].
+///
+/// * **env** is an object of type [`Env`].
+pub fn sync(deps: DepsMut, env: Env) -> Result<Response, ContractError> {
+    let mut config: Config = CONFIG.load(deps.storage)?;
+    let reserves = query_reserves(deps.storage, &config)?;
+
+    // Accumulate prices for the assets in the pool
+    if let Some((price0_cumulative_new, price1_cumulative_new, block_time)) = accumulate_prices(
+        env.clone(),
+        &config,
+        reserves[0].amount,
+        query_token_precision(&deps.querier, reserves[0].info.clone())?,
+        reserves[1].amount,
+        query_token_precision(&deps.querier, reserves[1].info.clone())?,
+    )? {
+        config.price0_cumulative_last = price0_cumulative_new;
+        config.price1_cumulative_last = price1_cumulative_new;
+        config.block_time_last = block_time;
+        CONFIG.save(deps.storage, &config)?;
+        write_price_observation(deps.storage, &config)?;
+    }
+
+    let pools = query_pool_balances(deps.as_ref(), &config, env.contract.address)?;
+    RESERVES.save(deps.storage, &[pools[0].amount, pools[1].amount])?;
+
+    Ok(Response::new()
+        .add_attribute("action", "sync")
+        .add_attribute("reserves", format!("{}, {}", pools[0], pools[1])))
+}
+
+/// ## Description
+/// Sends the balances of the pair that exceed the tracked pool reserves and the accrued protocol fees
+/// to a recipient. Returns a [`ContractError`] on failure, otherwise returns a [`Response`] with the specified
+/// attributes if the operation was successful.
+/// ## Params
+/// * **deps** is an object of type [`DepsMut`].
+///
+/// * **env** is an object of type [`Env`].
+///
+/// * **info** is an object of type [`MessageInfo`].
+///
+/// * **to** is an [`Option`] field of type [`String`]. This is the recipient of the excess assets.
+/// If no custom recipient is specified, the assets are sent to the function caller.
+pub fn skim(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    to: Option<String>,
+) -> Result<Response, ContractError> {
+    let config: Config = CONFIG.load(deps.storage)?;
+    let reserves = query_reserves(deps.storage, &config)?;
+    let pools = query_pool_balances(deps.as_ref(), &config, env.contract.address)?;
+
+    let to = addr_validate_to_lower(deps.api, &to.unwrap_or_else(|| info.sender.to_string()))?;
+
+    let mut messages: Vec<CosmosMsg> = vec![];
+    let mut excess_assets: Vec<Asset> = vec![];
+    for (pool, reserve) in pools.iter().zip(reserves.iter()) {
+        let excess = Asset {
+            info: pool.info.clone(),
+            amount: pool.amount.saturating_sub(reserve.amount),
+        };
+        if !excess.amount.is_zero() {
+            messages.push(excess.clone().into_msg(&deps.querier, to.clone())?);
+        }
+        excess_assets.push(excess);
+    }
+
+    Ok(Response::new()
+        .add_messages(messages)
+        .add_attribute("action", "skim")
+        .add_attribute("receiver", to.as_str())
+        .add_attribute(
+            "assets",
+            format!("{}, {}", excess_assets[0], excess_assets[1]),
+        ))
+}
+
 /// ## Description
 /// Accumulate token prices for the assets in the pool.
 /// Note that this function shifts **block_time** when any of the token prices is zero in order to not
@@ -888,6 +2070,24 @@ pub fn accumulate_prices(
     Ok(Some((pcl0, pcl1, block_time)))
 }
 
+/// ## Description
+/// Stores an observation of the last cumulative prices of the pool assets. Prices are accumulated at most
+/// once per block time, so at most one observation is written per block.
+/// ## Params
+/// * **storage** is an object of type [`Storage`].
+///
+/// * **config** is an object of type [`Config`].
+pub fn write_price_observation(storage: &mut dyn Storage, config: &Config) -> StdResult<()> {
+    write_observation(
+        storage,
+        Observation {
+            timestamp: config.block_time_last,
+            price0_cumulative: config.price0_cumulative_last.into(),
+            price1_cumulative: config.price1_cumulative_last.into(),
+        },
+    )
+}
+
 /// ## Description
 /// Calculates the amount of fees the Maker contract gets according to specified pair parameters.
 /// Returns a [`None`] if the Maker fee is zero, otherwise returns a [`Asset`] struct with the specified attributes.
@@ -907,10 +2107,48 @@ pub fn calculate_maker_fee(
         return None;
     }
 
-    Some(Asset {
-        info: pool_info,
-        amount: maker_fee,
-    })
+    Some(Asset {
+        info: pool_info,
+        amount: maker_fee,
+    })
+}
+
+/// ## Description
+/// Pays the Maker fee. If the accrual of the Maker fees is enabled, the fee stays in the pair and is
+/// recorded in the accrued protocol fees, otherwise returns a message that sends the fee to the fee address.
+/// ## Params
+/// * **storage** is an object of type [`Storage`].
+///
+/// * **querier** is an object of type [`QuerierWrapper`].
+///
+/// * **config** is an object of type [`Config`].
+///
+/// * **maker_fee** is an object of type [`Asset`]. This is the Maker fee to pay.
+///
+/// * **fee_address** is an object of type [`Addr`]. This is the address that receives the Maker fees.
+pub fn pay_maker_fee(
+    storage: &mut dyn Storage,
+    querier: &QuerierWrapper,
+    config: &Config,
+    maker_fee: Asset,
+    fee_address: Addr,
+) -> StdResult<Option<CosmosMsg>> {
+    if !ACCRUE_PROTOCOL_FEES.may_load(storage)?.unwrap_or_default() {
+        return Ok(Some(maker_fee.into_msg(querier, fee_address)?));
+    }
+
+    let mut protocol_fees = load_protocol_fees(storage)?;
+    for (protocol_fee, asset_info) in protocol_fees
+        .iter_mut()
+        .zip(config.pair_info.asset_infos.iter())
+    {
+        if asset_info.equal(&maker_fee.info) {
+            *protocol_fee = protocol_fee.checked_add(maker_fee.amount)?;
+        }
+    }
+    PROTOCOL_FEES.save(storage, &protocol_fees)?;
+
+    Ok(None)
 }
 
 /// ## Description
@@ -940,6 +2178,40 @@ pub fn calculate_maker_fee(
 /// pool using a [`CumulativePricesResponse`] object.
 ///
 /// * **QueryMsg::Config {}** Returns the configuration for the pair contract using a [`ConfigResponse`] object.
+///
+/// * **QueryMsg::ProtocolFees {}** Returns the Maker fees accrued in the pair using a [`ProtocolFeesResponse`] object.
+///
+/// * **QueryMsg::Observe { seconds_ago }** Returns the cumulative prices at the specified number of seconds ago
+/// using an [`ObserveResponse`] object.
+///
+/// * **QueryMsg::SpotPrice { asset_info }** Returns the spot price of an asset using a [`SpotPriceResponse`] object.
+///
+/// * **QueryMsg::PriceImpact { offer_asset }** Returns the price impact of a swap using a [`PriceImpactResponse`] object.
+///
+/// * **QueryMsg::Depth { asset_info, price_change }** Returns the amount of an asset that can be swapped before
+/// its price drops by the specified percentage using a [`DepthResponse`] object.
+///
+/// * **QueryMsg::SimulateProvide { assets, slippage_tolerance }** Returns the amount of LP tokens minted
+/// for a deposit using a [`SimulateProvideResponse`] object.
+///
+/// * **QueryMsg::SimulateWithdraw { lp_amount }** Returns the amounts of assets received for burning
+/// LP tokens in a [`Vec<Asset>`] object.
+///
+/// * **QueryMsg::SimulateWithdrawOneCoin { lp_amount, ask_asset_info }** Returns the amount of an asset
+/// received for burning LP tokens using a [`SimulateWithdrawOneCoinResponse`] object.
+///
+/// * **QueryMsg::SimulateWithdrawImbalanced { assets }** Returns the amount of LP tokens burned for
+/// withdrawing exact amounts of assets using a [`SimulateWithdrawImbalancedResponse`] object.
+///
+/// * **QueryMsg::VirtualPrice {}** Returns the value of one LP token in the units of the pool invariant
+/// using a [`VirtualPriceResponse`] object.
+///
+/// * **QueryMsg::CurrentD {}** Returns the current pool invariant using a [`CurrentDResponse`] object.
+///
+/// * **QueryMsg::AmpHistory { start_after, limit }** Returns the amplification changes of the pool
+/// using an [`AmpHistoryResponse`] object.
+///
+/// * **QueryMsg::PairStatus {}** Returns the trading state of the pair set in the factory using a [`astroport::factory::PairStatus`] object.
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
     match msg {
@@ -954,6 +2226,46 @@ pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
         }
         QueryMsg::CumulativePrices {} => to_binary(&query_cumulative_prices(deps, env)?),
         QueryMsg::Config {} => to_binary(&query_config(deps, env)?),
+        QueryMsg::ProtocolFees {} => to_binary(&query_protocol_fees(deps)?),
+        QueryMsg::Observe { seconds_ago } => to_binary(&query_observe(deps, env, seconds_ago)?),
+        QueryMsg::SpotPrice { asset_info } => to_binary(&query_spot_price(deps, env, asset_info)?),
+        QueryMsg::PriceImpact { offer_asset } => {
+            to_binary(&query_price_impact(deps, env, offer_asset)?)
+        }
+        QueryMsg::Depth {
+            asset_info,
+            price_change,
+        } => to_binary(&query_depth(deps, env, asset_info, price_change)?),
+        QueryMsg::SimulateProvide {
+            assets,
+            slippage_tolerance,
+        } => to_binary(&query_simulate_provide(
+            deps,
+            env,
+            assets,
+            slippage_tolerance,
+        )?),
+        QueryMsg::SimulateWithdraw { lp_amount } => {
+            to_binary(&query_simulate_withdraw(deps, lp_amount)?)
+        }
+        QueryMsg::PairStatus {} => to_binary(&FACTORY_PARAMS.load(deps.storage)?.pair_status),
+        QueryMsg::SimulateWithdrawOneCoin {
+            lp_amount,
+            ask_asset_info,
+        } => to_binary(&query_simulate_withdraw_one_coin(
+            deps,
+            env,
+            lp_amount,
+            ask_asset_info,
+        )?),
+        QueryMsg::SimulateWithdrawImbalanced { assets } => {
+            to_binary(&query_simulate_withdraw_imbalanced(deps, env, assets)?)
+        }
+        QueryMsg::VirtualPrice {} => to_binary(&query_virtual_price(deps, env)?),
+        QueryMsg::CurrentD {} => to_binary(&query_current_d(deps, env)?),
+        QueryMsg::AmpHistory { start_after, limit } => {
+            to_binary(&query_amp_history(deps, start_after, limit)?)
+        }
     }
 }
 
@@ -1008,9 +2320,7 @@ pub fn query_share(deps: Deps, amount: Uint128) -> StdResult<[Asset; 2]> {
 /// * **offer_asset** is an object of type [`Asset`]. This is the asset to swap as well as an amount of the said asset.
 pub fn query_simulation(deps: Deps, env: Env, offer_asset: Asset) -> StdResult<SimulationResponse> {
     let config: Config = CONFIG.load(deps.storage)?;
-    let contract_addr = config.pair_info.contract_addr.clone();
-
-    let pools: [Asset; 2] = config.pair_info.query_pools(&deps.querier, contract_addr)?;
+    let pools = query_reserves(deps.storage, &config)?;
 
     let offer_pool: Asset;
     let ask_pool: Asset;
@@ -1026,12 +2336,9 @@ pub fn query_simulation(deps: Deps, env: Env, offer_asset: Asset) -> StdResult<S
         ));
     }
 
-    // Get fee info from factory
-    let fee_info = query_fee_info(
-        &deps.querier,
-        config.factory_addr.clone(),
-        config.pair_info.pair_type.clone(),
-    )?;
+    // Get the fee info cached from the factory
+    let fee_info = FACTORY_PARAMS.load(deps.storage)?.fee_info;
+    let fee_multiplier = FEE_MULTIPLIER.may_load(deps.storage)?;
 
     let (return_amount, spread_amount, commission_amount) = compute_swap(
         offer_pool.amount,
@@ -1040,6 +2347,7 @@ pub fn query_simulation(deps: Deps, env: Env, offer_asset: Asset) -> StdResult<S
         query_token_precision(&deps.querier, ask_pool.info)?,
         offer_asset.amount,
         fee_info.total_fee_rate,
+        fee_multiplier,
         compute_current_amp(&config, &env)?,
     )?;
 
@@ -1050,6 +2358,413 @@ pub fn query_simulation(deps: Deps, env: Env, offer_asset: Asset) -> StdResult<S
     })
 }
 
+/// ## Description
+/// Returns the price of one unit of an asset in the other pool asset in a [`SpotPriceResponse`] object.
+/// ## Params
+/// * **deps** is an object of type [`Deps`].
+///
+/// * **env** is an object of type [`Env`].
+///
+/// * **asset_info** is an object of type [`AssetInfo`]. This is the asset to return the price for.
+pub fn query_spot_price(
+    deps: Deps,
+    env: Env,
+    asset_info: AssetInfo,
+) -> StdResult<SpotPriceResponse> {
+    let config: Config = CONFIG.load(deps.storage)?;
+    let (offer_pool, ask_pool) = select_pools(&asset_info, query_reserves(deps.storage, &config)?)?;
+
+    Ok(SpotPriceResponse {
+        price: compute_spot_price(
+            offer_pool.amount,
+            query_token_precision(&deps.querier, offer_pool.info)?,
+            ask_pool.amount,
+            query_token_precision(&deps.querier, ask_pool.info)?,
+            compute_current_amp(&config, &env)?,
+        )?
+        .into(),
+    })
+}
+
+/// ## Description
+/// Returns the spot price, the execution price and the price impact of a swap in a [`PriceImpactResponse`] object.
+/// ## Params
+/// * **deps** is an object of type [`Deps`].
+///
+/// * **env** is an object of type [`Env`].
+///
+/// * **offer_asset** is an object of type [`Asset`]. This is the asset to swap as well as an amount of the said asset.
+pub fn query_price_impact(
+    deps: Deps,
+    env: Env,
+    offer_asset: Asset,
+) -> StdResult<PriceImpactResponse> {
+    let config: Config = CONFIG.load(deps.storage)?;
+    let (offer_pool, ask_pool) =
+        select_pools(&offer_asset.info, query_reserves(deps.storage, &config)?)?;
+    let offer_precision = query_token_precision(&deps.querier, offer_pool.info)?;
+    let ask_precision = query_token_precision(&deps.querier, ask_pool.info)?;
+    let amp = compute_current_amp(&config, &env)?;
+
+    let spot_price = compute_spot_price(
+        offer_pool.amount,
+        offer_precision,
+        ask_pool.amount,
+        ask_precision,
+        amp,
+    )?;
+
+    // Get the fee info cached from the factory
+    let fee_info = FACTORY_PARAMS.load(deps.storage)?.fee_info;
+    let fee_multiplier = FEE_MULTIPLIER.may_load(deps.storage)?;
+
+    let (return_amount, _, commission_amount) = compute_swap(
+        offer_pool.amount,
+        offer_precision,
+        ask_pool.amount,
+        ask_precision,
+        offer_asset.amount,
+        fee_info.total_fee_rate,
+        fee_multiplier,
+        amp,
+    )?;
+    let execution_price = compute_price(
+        offer_asset.amount,
+        offer_precision,
+        return_amount + commission_amount,
+        ask_precision,
+    )?;
+
+    Ok(PriceImpactResponse {
+        spot_price: spot_price.into(),
+        execution_price: execution_price.into(),
+        price_impact: compute_price_impact(spot_price, execution_price).into(),
+    })
+}
+
+/// ## Description
+/// Returns the amount of an asset that can be swapped before its spot price drops by the specified
+/// percentage in a [`DepthResponse`] object. The commission is not taken into account.
+/// ## Params
+/// * **deps** is an object of type [`Deps`].
+///
+/// * **env** is an object of type [`Env`].
+///
+/// * **asset_info** is an object of type [`AssetInfo`]. This is the asset to swap.
+///
+/// * **price_change** is an object of type [`Decimal`]. This is the relative price change.
+pub fn query_depth(
+    deps: Deps,
+    env: Env,
+    asset_info: AssetInfo,
+    price_change: Decimal,
+) -> StdResult<DepthResponse> {
+    if price_change.is_zero() || price_change >= Decimal::one() {
+        return Err(StdError::generic_err(
+            "The price change must be between 0 and 1",
+        ));
+    }
+
+    let config: Config = CONFIG.load(deps.storage)?;
+    let (offer_pool, ask_pool) = select_pools(&asset_info, query_reserves(deps.storage, &config)?)?;
+    let offer_precision = query_token_precision(&deps.querier, offer_pool.info)?;
+    let ask_precision = query_token_precision(&deps.querier, ask_pool.info)?;
+    let amp = compute_current_amp(&config, &env)?;
+
+    let greater_precision = offer_precision.max(ask_precision);
+    let offer_amount = calc_depth(
+        adjust_precision(offer_pool.amount, offer_precision, greater_precision)?.u128(),
+        adjust_precision(ask_pool.amount, ask_precision, greater_precision)?.u128(),
+        Decimal256::one() - Decimal256::from(price_change),
+        amp,
+    )
+    .ok_or_else(|| StdError::generic_err("Failed to compute the depth of the pool"))?;
+    let offer_amount = adjust_precision(
+        Uint128::new(offer_amount),
+        greater_precision,
+        offer_precision,
+    )?;
+
+    // Get the fee info cached from the factory
+    let fee_info = FACTORY_PARAMS.load(deps.storage)?.fee_info;
+    let fee_multiplier = FEE_MULTIPLIER.may_load(deps.storage)?;
+
+    let (return_amount, _, _) = compute_swap(
+        offer_pool.amount,
+        offer_precision,
+        ask_pool.amount,
+        ask_precision,
+        offer_amount,
+        fee_info.total_fee_rate,
+        fee_multiplier,
+        amp,
+    )?;
+
+    Ok(DepthResponse {
+        offer_amount,
+        return_amount,
+    })
+}
+
+/// ## Description
+/// Returns the amount of LP tokens minted for a deposit in an object of type [`SimulateProvideResponse`].
+/// The share is calculated in the same way as in [`provide_liquidity`]. The pool doesn't charge
+/// imbalance fees, so the returned fees are always zero.
+/// ## Params
+/// * **deps** is an object of type [`Deps`].
+///
+/// * **env** is an object of type [`Env`].
+///
+/// * **assets** is an array with two objects of type [`Asset`]. These are the assets to deposit.
+///
+/// * **slippage_tolerance** is an [`Option`] field of type [`Decimal`]. It is used to specify how much
+/// the pool price can move until the provide liquidity transaction goes through.
+pub fn query_simulate_provide(
+    deps: Deps,
+    env: Env,
+    assets: [Asset; 2],
+    slippage_tolerance: Option<Decimal>,
+) -> StdResult<SimulateProvideResponse> {
+    let config: Config = CONFIG.load(deps.storage)?;
+    let (pools, total_share) = pool_info(deps, config.clone())?;
+    let deposits = get_deposits(&assets, &pools)?;
+
+    let token_precisions = [
+        query_token_precision(&deps.querier, pools[0].info.clone())?,
+        query_token_precision(&deps.querier, pools[1].info.clone())?,
+    ];
+    let share = calculate_share(
+        deps,
+        &env,
+        &config,
+        &deposits,
+        &pools,
+        token_precisions,
+        total_share,
+        slippage_tolerance,
+    )?;
+
+    Ok(SimulateProvideResponse {
+        share,
+        fees: pools
+            .iter()
+            .map(|pool| Asset {
+                info: pool.info.clone(),
+                amount: Uint128::zero(),
+            })
+            .collect(),
+    })
+}
+
+/// ## Description
+/// Returns the amounts of assets received for burning LP tokens in a [`Vec<Asset>`] object.
+/// The amounts are calculated in the same way as in [`withdraw_liquidity`].
+/// ## Params
+/// * **deps** is an object of type [`Deps`].
+///
+/// * **lp_amount** is an object of type [`Uint128`]. This is the amount of LP tokens to burn.
+pub fn query_simulate_withdraw(deps: Deps, lp_amount: Uint128) -> StdResult<Vec<Asset>> {
+    let config: Config = CONFIG.load(deps.storage)?;
+    let (pools, total_share) = pool_info(deps, config)?;
+
+    Ok(get_share_in_assets(&pools, lp_amount, total_share).to_vec())
+}
+
+/// ## Description
+/// Returns the amount of an asset received for burning LP tokens in an object of type
+/// [`SimulateWithdrawOneCoinResponse`]. The amount is calculated in the same way as in
+/// [`withdraw_liquidity_one_coin`].
+/// ## Params
+/// * **deps** is an object of type [`Deps`].
+///
+/// * **env** is an object of type [`Env`].
+///
+/// * **lp_amount** is an object of type [`Uint128`]. This is the amount of LP tokens to burn.
+///
+/// * **ask_asset_info** is an object of type [`AssetInfo`]. This is the asset to withdraw.
+pub fn query_simulate_withdraw_one_coin(
+    deps: Deps,
+    env: Env,
+    lp_amount: Uint128,
+    ask_asset_info: AssetInfo,
+) -> StdResult<SimulateWithdrawOneCoinResponse> {
+    let config: Config = CONFIG.load(deps.storage)?;
+    let (pools, total_share) = pool_info(deps, config.clone())?;
+
+    let (_, return_amount, fee_amount) = compute_withdraw_one_coin(
+        deps,
+        &env,
+        &config,
+        &pools,
+        total_share,
+        lp_amount,
+        &ask_asset_info,
+    )?;
+
+    Ok(SimulateWithdrawOneCoinResponse {
+        return_amount,
+        fee_amount,
+    })
+}
+
+/// ## Description
+/// Returns the amount of LP tokens burned for withdrawing exact amounts of assets in an object of type
+/// [`SimulateWithdrawImbalancedResponse`]. The amount is calculated in the same way as in
+/// [`withdraw_liquidity_imbalanced`].
+/// ## Params
+/// * **deps** is an object of type [`Deps`].
+///
+/// * **env** is an object of type [`Env`].
+///
+/// * **assets** is a vector with items of type [`Asset`]. These are the assets to withdraw.
+pub fn query_simulate_withdraw_imbalanced(
+    deps: Deps,
+    env: Env,
+    assets: Vec<Asset>,
+) -> StdResult<SimulateWithdrawImbalancedResponse> {
+    let config: Config = CONFIG.load(deps.storage)?;
+    let (pools, total_share) = pool_info(deps, config.clone())?;
+    let withdrawals = get_withdrawals(&assets, &pools)?;
+
+    let (lp_amount, fees) =
+        compute_withdraw_imbalanced(deps, &env, &config, &pools, total_share, &withdrawals)?;
+
+    Ok(SimulateWithdrawImbalancedResponse {
+        lp_amount,
+        fees: pools
+            .iter()
+            .zip(fees)
+            .map(|(pool, fee)| Asset {
+                info: pool.info.clone(),
+                amount: fee,
+            })
+            .collect(),
+    })
+}
+
+/// ## Description
+/// Returns the value of one LP token in the units of the pool invariant D in an object of type
+/// [`VirtualPriceResponse`]. The invariant is adjusted to the precision of the LP token, so a balanced
+/// pool starts with a virtual price of one that only grows as fees are collected.
+/// ## Params
+/// * **deps** is an object of type [`Deps`].
+///
+/// * **env** is an object of type [`Env`].
+pub fn query_virtual_price(deps: Deps, env: Env) -> StdResult<VirtualPriceResponse> {
+    let config: Config = CONFIG.load(deps.storage)?;
+    let (pools, total_share) = pool_info(deps, config.clone())?;
+
+    if total_share.is_zero() {
+        return Err(StdError::generic_err("The pool has no liquidity"));
+    }
+
+    let d = compute_current_d(deps, &env, &config, &pools)?;
+
+    Ok(VirtualPriceResponse {
+        virtual_price: Decimal::from_ratio(d, total_share),
+    })
+}
+
+/// ## Description
+/// Returns the current pool invariant D in the precision of the LP token in an object of type
+/// [`CurrentDResponse`].
+/// ## Params
+/// * **deps** is an object of type [`Deps`].
+///
+/// * **env** is an object of type [`Env`].
+pub fn query_current_d(deps: Deps, env: Env) -> StdResult<CurrentDResponse> {
+    let config: Config = CONFIG.load(deps.storage)?;
+    let pools = query_reserves(deps.storage, &config)?;
+
+    Ok(CurrentDResponse {
+        d: compute_current_d(deps, &env, &config, &pools)?,
+    })
+}
+
+/// ## Description
+/// Returns the amplification changes of the pool sorted by their identifiers in an object of type
+/// [`AmpHistoryResponse`].
+/// ## Params
+/// * **deps** is an object of type [`Deps`].
+///
+/// * **start_after** is an [`Option`] field of type [`u64`]. This is the identifier of the change to start after.
+///
+/// * **limit** is an [`Option`] field of type [`u32`]. This is the maximum amount of changes to return.
+pub fn query_amp_history(
+    deps: Deps,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<AmpHistoryResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(|id| Bound::exclusive(U64Key::new(id)));
+
+    let history = AMP_HISTORY
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| item.map(|(_, ramp)| ramp))
+        .collect::<StdResult<Vec<AmpRamp>>>()?;
+
+    Ok(AmpHistoryResponse { history })
+}
+
+/// ## Description
+/// Computes the pool invariant D with the current amplification and returns it in the precision
+/// of the LP token.
+/// ## Params
+/// * **deps** is an object of type [`Deps`].
+///
+/// * **env** is a reference to an object of type [`Env`].
+///
+/// * **config** is a reference to an object of type [`Config`].
+///
+/// * **pools** are an array of [`Asset`] type items. These are the pool reserves.
+fn compute_current_d(
+    deps: Deps,
+    env: &Env,
+    config: &Config,
+    pools: &[Asset; 2],
+) -> StdResult<Uint128> {
+    let token_precision_0 = query_token_precision(&deps.querier, pools[0].info.clone())?;
+    let token_precision_1 = query_token_precision(&deps.querier, pools[1].info.clone())?;
+    let greater_precision = token_precision_0.max(token_precision_1);
+    let lp_precision = query_token_precision(
+        &deps.querier,
+        AssetInfo::Token {
+            contract_addr: config.pair_info.liquidity_token.clone(),
+        },
+    )?;
+
+    let amount_0 = adjust_precision(pools[0].amount, token_precision_0, greater_precision)?;
+    let amount_1 = adjust_precision(pools[1].amount, token_precision_1, greater_precision)?;
+    let leverage = compute_current_amp(config, env)?
+        .checked_mul(u64::from(N_COINS))
+        .unwrap();
+
+    let d = compute_d(leverage, amount_0.u128(), amount_1.u128())
+        .ok_or_else(|| StdError::generic_err("Failed to compute the pool invariant"))?;
+
+    adjust_precision(Uint128::new(d), greater_precision, lp_precision)
+}
+
+/// ## Description
+/// Returns the offer and the ask pools for the specified offer asset.
+/// ## Params
+/// * **offer_asset_info** is an object of type [`AssetInfo`]. This is the asset to swap.
+///
+/// * **pools** are an array of [`Asset`] type items. These are the assets available in the pool.
+fn select_pools(offer_asset_info: &AssetInfo, pools: [Asset; 2]) -> StdResult<(Asset, Asset)> {
+    let [pool0, pool1] = pools;
+    if offer_asset_info.equal(&pool0.info) {
+        Ok((pool0, pool1))
+    } else if offer_asset_info.equal(&pool1.info) {
+        Ok((pool1, pool0))
+    } else {
+        Err(StdError::generic_err(
+            "Given offer asset doesn't belong to pairs",
+        ))
+    }
+}
+
 /// ## Description
 /// Returns information about a reverse swap simulation in a [`ReverseSimulationResponse`] object.
 /// ## Params
@@ -1065,9 +2780,7 @@ pub fn query_reverse_simulation(
     ask_asset: Asset,
 ) -> StdResult<ReverseSimulationResponse> {
     let config: Config = CONFIG.load(deps.storage)?;
-    let contract_addr = config.pair_info.contract_addr.clone();
-
-    let pools: [Asset; 2] = config.pair_info.query_pools(&deps.querier, contract_addr)?;
+    let pools = query_reserves(deps.storage, &config)?;
 
     let offer_pool: Asset;
     let ask_pool: Asset;
@@ -1083,12 +2796,9 @@ pub fn query_reverse_simulation(
         ));
     }
 
-    // Get fee info from the factory
-    let fee_info = query_fee_info(
-        &deps.querier,
-        config.factory_addr.clone(),
-        config.pair_info.pair_type.clone(),
-    )?;
+    // Get the fee info cached from the factory
+    let fee_info = FACTORY_PARAMS.load(deps.storage)?.fee_info;
+    let fee_multiplier = FEE_MULTIPLIER.may_load(deps.storage)?;
 
     let (offer_amount, spread_amount, commission_amount) = compute_offer_amount(
         offer_pool.amount,
@@ -1097,6 +2807,7 @@ pub fn query_reverse_simulation(
         query_token_precision(&deps.querier, ask_pool.info)?,
         ask_asset.amount,
         fee_info.total_fee_rate,
+        fee_multiplier,
         compute_current_amp(&config, &env)?,
     )?;
 
@@ -1132,14 +2843,71 @@ pub fn query_cumulative_prices(deps: Deps, env: Env) -> StdResult<CumulativePric
         price1_cumulative_last = price1_cumulative_new;
     }
 
-    let resp = CumulativePricesResponse {
-        assets,
-        total_share,
-        price0_cumulative_last,
-        price1_cumulative_last,
+    let resp = CumulativePricesResponse {
+        assets,
+        total_share,
+        price0_cumulative_last,
+        price1_cumulative_last,
+        price0_cumulative_last_u256: None,
+        price1_cumulative_last_u256: None,
+    };
+
+    Ok(resp)
+}
+
+/// ## Description
+/// Returns the cumulative prices at the specified number of seconds ago in an [`ObserveResponse`] object.
+/// These are the same 128-bit accumulators with [`TWAP_PRECISION`] decimals as in the cumulative prices query.
+/// Cumulative prices after the last price accumulation are extrapolated using the current reserves and
+/// cumulative prices between two stored observations are interpolated linearly.
+/// ## Params
+/// * **deps** is an object of type [`Deps`].
+///
+/// * **env** is an object of type [`Env`].
+///
+/// * **seconds_ago** is a [`Vec`] of type [`u64`]. These are the observation times relative to the current block time.
+pub fn query_observe(deps: Deps, env: Env, seconds_ago: Vec<u64>) -> StdResult<ObserveResponse> {
+    let config: Config = CONFIG.load(deps.storage)?;
+    let pools = query_reserves(deps.storage, &config)?;
+    let token_precision_0 = query_token_precision(&deps.querier, pools[0].info.clone())?;
+    let token_precision_1 = query_token_precision(&deps.querier, pools[1].info.clone())?;
+
+    let last = Observation {
+        timestamp: config.block_time_last,
+        price0_cumulative: config.price0_cumulative_last.into(),
+        price1_cumulative: config.price1_cumulative_last.into(),
     };
 
-    Ok(resp)
+    let observations = observe(
+        deps.storage,
+        env.block.time.seconds(),
+        last.clone(),
+        seconds_ago,
+        |target| {
+            let mut env = env.clone();
+            env.block.time = Timestamp::from_seconds(target);
+            match accumulate_prices(
+                env,
+                &config,
+                pools[0].amount,
+                token_precision_0,
+                pools[1].amount,
+                token_precision_1,
+            )? {
+                Some((price0_cumulative, price1_cumulative, _)) => Ok(Observation {
+                    timestamp: target,
+                    price0_cumulative: price0_cumulative.into(),
+                    price1_cumulative: price1_cumulative.into(),
+                }),
+                None => Ok(Observation {
+                    timestamp: target,
+                    ..last.clone()
+                }),
+            }
+        },
+    )?;
+
+    Ok(ObserveResponse { observations })
 }
 
 /// ## Description
@@ -1152,6 +2920,7 @@ pub fn query_config(deps: Deps, env: Env) -> StdResult<ConfigResponse> {
         block_time_last: config.block_time_last,
         params: Some(to_binary(&StablePoolConfig {
             amp: Decimal::from_ratio(compute_current_amp(&config, &env)?, AMP_PRECISION),
+            fee_multiplier: FEE_MULTIPLIER.may_load(deps.storage)?,
         })?),
     })
 }
@@ -1185,7 +2954,11 @@ pub fn amount_of(coins: &[Coin], denom: String) -> Uint128 {
 ///
 /// * **commission_rate** is an object of type [`Decimal`]. This is the total amount of fees charged for the swap.
 ///
+/// * **fee_multiplier** is an [`Option`] field of type [`Decimal`]. This is the off-peg fee multiplier
+/// if the pool uses dynamic fees.
+///
 /// * **amp** is an object of type [`u64`]. This is the pool amplification used to calculate the swap result.
+#[allow(clippy::too_many_arguments)]
 fn compute_swap(
     offer_pool: Uint128,
     offer_precision: u8,
@@ -1193,6 +2966,7 @@ fn compute_swap(
     ask_precision: u8,
     offer_amount: Uint128,
     commission_rate: Decimal,
+    fee_multiplier: Option<Decimal>,
     amp: u64,
 ) -> StdResult<(Uint128, Uint128, Uint128)> {
     // offer => ask
@@ -1209,6 +2983,19 @@ fn compute_swap(
     // We assume the assets should stay in a 1:1 ratio, so the true exchange rate is 1. So any exchange rate <1 could be considered the spread
     let spread_amount = offer_amount.saturating_sub(return_amount);
 
+    let commission_rate = match fee_multiplier {
+        Some(fee_multiplier) => calc_dynamic_fee_rate(
+            offer_pool.u128(),
+            ask_pool.u128(),
+            (offer_pool + offer_amount).u128(),
+            (ask_pool - return_amount).u128(),
+            amp,
+            commission_rate,
+            fee_multiplier,
+        )
+        .ok_or_else(|| StdError::generic_err("Failed to calculate the dynamic fee rate"))?,
+        None => commission_rate,
+    };
     let commission_amount: Uint128 = return_amount * commission_rate;
 
     // The commission will be absorbed by the pool
@@ -1235,6 +3022,12 @@ fn compute_swap(
 /// * **ask_amount** is an object of type [`Uint128`]. This is the amount of ask assets to swap to.
 ///
 /// * **commission_rate** is an object of type [`Decimal`]. This is the total amount of fees charged for the swap.
+///
+/// * **fee_multiplier** is an [`Option`] field of type [`Decimal`]. This is the off-peg fee multiplier
+/// if the pool uses dynamic fees.
+///
+/// * **amp** is an object of type [`u64`]. This is the pool amplification used to calculate the swap result.
+#[allow(clippy::too_many_arguments)]
 fn compute_offer_amount(
     offer_pool: Uint128,
     offer_precision: u8,
@@ -1242,6 +3035,7 @@ fn compute_offer_amount(
     ask_precision: u8,
     ask_amount: Uint128,
     commission_rate: Decimal,
+    fee_multiplier: Option<Decimal>,
     amp: u64,
 ) -> StdResult<(Uint128, Uint128, Uint128)> {
     // ask => offer
@@ -1251,6 +3045,26 @@ fn compute_offer_amount(
     let ask_pool = adjust_precision(ask_pool, ask_precision, greater_precision)?;
     let ask_amount = adjust_precision(ask_amount, ask_precision, greater_precision)?;
 
+    // The dynamic fee rate is estimated from the pool balances after a swap without the commission
+    let commission_rate = match fee_multiplier {
+        Some(fee_multiplier) => {
+            let offer_amount =
+                calc_offer_amount(offer_pool.u128(), ask_pool.u128(), ask_amount.u128(), amp)
+                    .ok_or_else(|| StdError::generic_err("Failed to calculate the offer amount"))?;
+            calc_dynamic_fee_rate(
+                offer_pool.u128(),
+                ask_pool.u128(),
+                offer_pool.u128() + offer_amount,
+                (ask_pool - ask_amount).u128(),
+                amp,
+                commission_rate,
+                fee_multiplier,
+            )
+            .ok_or_else(|| StdError::generic_err("Failed to calculate the dynamic fee rate"))?
+        }
+        None => commission_rate,
+    };
+
     let one_minus_commission = Decimal256::one() - Decimal256::from(commission_rate);
     let inv_one_minus_commission: Decimal = (Decimal256::one() / one_minus_commission).into();
     let before_commission_deduction = ask_amount * inv_one_minus_commission;
@@ -1277,6 +3091,76 @@ fn compute_offer_amount(
     Ok((offer_amount, spread_amount, commission_amount))
 }
 
+/// ## Description
+/// Returns the spot price of the offer asset in the ask asset adjusted for the token precisions.
+/// ## Params
+/// * **offer_pool** is an object of type [`Uint128`]. This is the total amount of offer assets in the pool.
+///
+/// * **offer_precision** is an object of type [`u8`]. This is the token precision used for the offer amount.
+///
+/// * **ask_pool** is an object of type [`Uint128`]. This is the total amount of ask assets in the pool.
+///
+/// * **ask_precision** is an object of type [`u8`]. This is the token precision used for the ask amount.
+///
+/// * **amp** is an object of type [`u64`]. This is the pool amplification used to calculate the price.
+fn compute_spot_price(
+    offer_pool: Uint128,
+    offer_precision: u8,
+    ask_pool: Uint128,
+    ask_precision: u8,
+    amp: u64,
+) -> StdResult<Decimal256> {
+    // Both pools use the same precision, so the price of the smallest units is the price of whole tokens
+    let greater_precision = offer_precision.max(ask_precision);
+    let offer_pool = adjust_precision(offer_pool, offer_precision, greater_precision)?;
+    let ask_pool = adjust_precision(ask_pool, ask_precision, greater_precision)?;
+
+    calc_spot_price(offer_pool.u128(), ask_pool.u128(), amp)
+        .ok_or_else(|| StdError::generic_err("The pool has no liquidity"))
+}
+
+/// ## Description
+/// Returns the price of the offer asset in the ask asset for the specified amounts adjusted for the token precisions.
+/// ## Params
+/// * **offer_amount** is an object of type [`Uint128`]. This is the amount of offer assets.
+///
+/// * **offer_precision** is an object of type [`u8`]. This is the token precision used for the offer amount.
+///
+/// * **ask_amount** is an object of type [`Uint128`]. This is the amount of ask assets.
+///
+/// * **ask_precision** is an object of type [`u8`]. This is the token precision used for the ask amount.
+fn compute_price(
+    offer_amount: Uint128,
+    offer_precision: u8,
+    ask_amount: Uint128,
+    ask_precision: u8,
+) -> StdResult<Decimal256> {
+    if offer_amount.is_zero() || ask_amount.is_zero() {
+        return Err(StdError::generic_err(
+            "The price is undefined for zero amounts",
+        ));
+    }
+
+    Ok(Decimal256::from_ratio(
+        Uint256::from(ask_amount) * Uint256::from(10u64.pow(offer_precision.into())),
+        Uint256::from(offer_amount) * Uint256::from(10u64.pow(ask_precision.into())),
+    ))
+}
+
+/// ## Description
+/// Returns the relative difference between the spot price and the execution price of a swap.
+/// ## Params
+/// * **spot_price** is an object of type [`Decimal256`]. This is the spot price before the swap.
+///
+/// * **execution_price** is an object of type [`Decimal256`]. This is the average price of the swap.
+fn compute_price_impact(spot_price: Decimal256, execution_price: Decimal256) -> Decimal256 {
+    if execution_price >= spot_price {
+        Decimal256::zero()
+    } else {
+        Decimal256::one() - execution_price / spot_price
+    }
+}
+
 /// ## Description
 /// Return a value using a newly specified precision.
 /// ## Params
@@ -1350,6 +3234,71 @@ pub fn assert_max_spread(
     Ok(())
 }
 
+/// ## Description
+/// Returns a [`ContractError`] on failure if the current block time is past the specified deadline.
+/// ## Params
+/// * **env** is an object of type [`Env`].
+///
+/// * **deadline** is an [`Option`] field of type [`u64`]. This is the block time (in seconds) after which
+/// the operation fails.
+pub fn assert_deadline(env: &Env, deadline: Option<u64>) -> Result<(), ContractError> {
+    if let Some(deadline) = deadline {
+        if env.block.time.seconds() > deadline {
+            return Err(ContractError::DeadlineExpired {});
+        }
+    }
+
+    Ok(())
+}
+
+/// ## Description
+/// Returns a [`ContractError`] if the factory paused swaps in the pair.
+/// ## Params
+/// * **storage** is a reference to an object implementing [`Storage`].
+fn assert_swaps_allowed(storage: &dyn Storage) -> Result<(), ContractError> {
+    if !FACTORY_PARAMS.load(storage)?.pair_status.swaps_allowed() {
+        return Err(ContractError::SwapsPaused {});
+    }
+
+    Ok(())
+}
+
+/// ## Description
+/// Returns a [`ContractError`] if the factory paused deposits in the pair.
+/// ## Params
+/// * **storage** is a reference to an object implementing [`Storage`].
+fn assert_deposits_allowed(storage: &dyn Storage) -> Result<(), ContractError> {
+    if !FACTORY_PARAMS.load(storage)?.pair_status.deposits_allowed() {
+        return Err(ContractError::DepositsPaused {});
+    }
+
+    Ok(())
+}
+
+/// ## Description
+/// Returns a [`ContractError`] on failure if the amount of any withdrawn asset is less than the specified minimum.
+/// ## Params
+/// * **refund_assets** are an array of [`Asset`] type items. These are the assets withdrawn from the pool.
+///
+/// * **min_assets_out** is an [`Option`] field of type [`Vec<Asset>`]. These are the minimum amounts of
+/// assets to receive.
+fn assert_min_assets_out(
+    refund_assets: &[Asset],
+    min_assets_out: Option<Vec<Asset>>,
+) -> Result<(), ContractError> {
+    for min_asset in min_assets_out.unwrap_or_default() {
+        let refund_asset = refund_assets
+            .iter()
+            .find(|a| a.info.equal(&min_asset.info))
+            .ok_or(ContractError::AssetMismatch {})?;
+        if refund_asset.amount < min_asset.amount {
+            return Err(ContractError::MinAssetsOutAssertion {});
+        }
+    }
+
+    Ok(())
+}
+
 /// ## Description
 /// This is an internal function that enforces slippage tolerance for swaps.
 /// Returns a [`ContractError`] on failure, otherwise returns [`Ok`].
@@ -1369,16 +3318,288 @@ fn assert_slippage_tolerance(
 }
 
 /// ## Description
-/// Used for contract migration. Returns a default object of type [`Response`].
+/// Enables or disables the accrual of the Maker fees in the pair. When enabled, the Maker fees stay in the pair
+/// and can be claimed by the fee address with [`ExecuteMsg::ClaimProtocolFees`]. Fees accrued before the accrual
+/// is disabled can still be claimed. Returns a [`ContractError`] on failure, otherwise returns a [`Response`]
+/// with the specified attributes if the operation was successful.
 /// ## Params
-/// * **_deps** is an object of type [`DepsMut`].
+/// * **deps** is an object of type [`DepsMut`].
 ///
-/// * **_env** is an object of type [`Env`].
+/// * **info** is an object of type [`MessageInfo`].
+///
+/// * **enabled** is an object of type [`bool`]. Sets whether the Maker fees are accrued in the pair.
+///
+/// ## Executor
+/// Only the factory owner can execute this.
+pub fn set_protocol_fee_accrual(
+    deps: DepsMut,
+    info: MessageInfo,
+    enabled: bool,
+) -> Result<Response, ContractError> {
+    let config: Config = CONFIG.load(deps.storage)?;
+    let factory_config = query_factory_config(&deps.querier, config.factory_addr)?;
+
+    if info.sender != factory_config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    ACCRUE_PROTOCOL_FEES.save(deps.storage, &enabled)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "set_protocol_fee_accrual")
+        .add_attribute("enabled", enabled.to_string()))
+}
+
+/// ## Description
+/// Sends the Maker fees accrued in the pair to the fee address. Returns a [`ContractError`] on failure,
+/// otherwise returns a [`Response`] with the specified attributes if the operation was successful.
+/// ## Params
+/// * **deps** is an object of type [`DepsMut`].
+///
+/// * **info** is an object of type [`MessageInfo`].
+///
+/// ## Executor
+/// Only the fee address can execute this.
+pub fn claim_protocol_fees(deps: DepsMut, info: MessageInfo) -> Result<Response, ContractError> {
+    // Get the fee info cached from the factory
+    let fee_info = FACTORY_PARAMS.load(deps.storage)?.fee_info;
+
+    if fee_info.fee_address != Some(info.sender.clone()) {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let config: Config = CONFIG.load(deps.storage)?;
+    let protocol_fees = load_protocol_fees(deps.storage)?;
+    PROTOCOL_FEES.save(deps.storage, &[Uint128::zero(), Uint128::zero()])?;
+
+    let mut messages: Vec<CosmosMsg> = vec![];
+    let mut claimed_assets: Vec<Asset> = vec![];
+    for (asset_info, amount) in config
+        .pair_info
+        .asset_infos
+        .iter()
+        .zip(protocol_fees.iter())
+    {
+        let claimed = Asset {
+            info: asset_info.clone(),
+            amount: *amount,
+        };
+        if !claimed.amount.is_zero() {
+            messages.push(
+                claimed
+                    .clone()
+                    .into_msg(&deps.querier, info.sender.clone())?,
+            );
+        }
+        claimed_assets.push(claimed);
+    }
+
+    Ok(Response::new()
+        .add_messages(messages)
+        .add_attribute("action", "claim_protocol_fees")
+        .add_attribute("receiver", info.sender.as_str())
+        .add_attribute(
+            "assets",
+            format!("{}, {}", claimed_assets[0], claimed_assets[1]),
+        ))
+}
+
+/// ## Description
+/// Updates the fee and Generator information that the pair caches from the factory.
+/// The factory pushes this message to its pairs when their parameters change, but anyone can execute it.
+/// ## Params
+/// * **deps** is an object of type [`DepsMut`].
+///
+/// * **env** is an object of type [`Env`].
+pub fn refresh_factory_params(deps: DepsMut, env: Env) -> Result<Response, ContractError> {
+    let config: Config = CONFIG.load(deps.storage)?;
+    let factory_params = query_factory_params(
+        &deps.querier,
+        config.factory_addr,
+        env.contract.address,
+        config.pair_info.pair_type,
+    )?;
+    FACTORY_PARAMS.save(deps.storage, &factory_params)?;
+
+    let fee_address = factory_params
+        .fee_info
+        .fee_address
+        .map(|addr| addr.to_string())
+        .unwrap_or_default();
+    let generator_address = factory_params
+        .generator_address
+        .map(|addr| addr.to_string())
+        .unwrap_or_default();
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "refresh_factory_params"),
+        attr(
+            "total_fee_rate",
+            factory_params.fee_info.total_fee_rate.to_string(),
+        ),
+        attr(
+            "maker_fee_rate",
+            factory_params.fee_info.maker_fee_rate.to_string(),
+        ),
+        attr("fee_address", fee_address),
+        attr("generator_address", generator_address),
+        attr("pair_status", factory_params.pair_status.to_string()),
+    ]))
+}
+
+/// ## Description
+/// Used for contract migration. Pairs that were instantiated before the pool reserves were tracked
+/// start tracking them from the current balances and cache the factory parameters.
+/// Returns a [`ContractError`] if the stored contract version can't be migrated.
+/// ## Params
+/// * **deps** is an object of type [`DepsMut`].
+///
+/// * **env** is an object of type [`Env`].
 ///
 /// * **_msg** is an object of type [`MigrateMsg`].
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn migrate(_deps: DepsMut, _env: Env, _msg: MigrateMsg) -> StdResult<Response> {
-    Ok(Response::default())
+pub fn migrate(deps: DepsMut, env: Env, _msg: MigrateMsg) -> Result<Response, ContractError> {
+    let contract_version = get_contract_version(deps.storage)?;
+
+    match contract_version.contract.as_ref() {
+        "astroport-pair-stable" => match contract_version.version.as_ref() {
+            "1.0.0" => {
+                let config: Config = CONFIG.load(deps.storage)?;
+                let pools = config
+                    .pair_info
+                    .query_pools(&deps.querier, env.contract.address.clone())?;
+                RESERVES.save(deps.storage, &[pools[0].amount, pools[1].amount])?;
+                FACTORY_PARAMS.save(
+                    deps.storage,
+                    &query_factory_params(
+                        &deps.querier,
+                        config.factory_addr,
+                        env.contract.address,
+                        config.pair_info.pair_type,
+                    )?,
+                )?;
+            }
+            _ => return Err(ContractError::MigrationError {}),
+        },
+        _ => return Err(ContractError::MigrationError {}),
+    }
+
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+
+    Ok(Response::new()
+        .add_attribute("previous_contract_name", &contract_version.contract)
+        .add_attribute("previous_contract_version", &contract_version.version)
+        .add_attribute("new_contract_name", CONTRACT_NAME)
+        .add_attribute("new_contract_version", CONTRACT_VERSION))
+}
+
+/// ## Description
+/// Returns the Maker fees accrued in the pair using a [`ProtocolFeesResponse`] object.
+/// ## Params
+/// * **deps** is an object of type [`Deps`].
+pub fn query_protocol_fees(deps: Deps) -> StdResult<ProtocolFeesResponse> {
+    let config: Config = CONFIG.load(deps.storage)?;
+    let protocol_fees = load_protocol_fees(deps.storage)?;
+
+    Ok(ProtocolFeesResponse {
+        accrual_enabled: ACCRUE_PROTOCOL_FEES
+            .may_load(deps.storage)?
+            .unwrap_or_default(),
+        assets: [
+            Asset {
+                info: config.pair_info.asset_infos[0].clone(),
+                amount: protocol_fees[0],
+            },
+            Asset {
+                info: config.pair_info.asset_infos[1].clone(),
+                amount: protocol_fees[1],
+            },
+        ],
+    })
+}
+
+/// ## Description
+/// Returns the Maker fees accrued in the pair in the order of the pair asset infos.
+/// ## Params
+/// * **storage** is an object of type [`Storage`].
+pub fn load_protocol_fees(storage: &dyn Storage) -> StdResult<[Uint128; 2]> {
+    Ok(PROTOCOL_FEES.may_load(storage)?.unwrap_or_default())
+}
+
+/// ## Description
+/// Returns the balances of the pool assets held by the pair without the accrued Maker fees.
+/// ## Params
+/// * **deps** is an object of type [`Deps`].
+///
+/// * **config** is an object of type [`Config`].
+///
+/// * **contract_addr** is an object of type [`Addr`]. This is the address of the pair.
+pub fn query_pool_balances(
+    deps: Deps,
+    config: &Config,
+    contract_addr: Addr,
+) -> StdResult<[Asset; 2]> {
+    let mut pools = config.pair_info.query_pools(&deps.querier, contract_addr)?;
+    let protocol_fees = load_protocol_fees(deps.storage)?;
+    for (pool, protocol_fee) in pools.iter_mut().zip(protocol_fees.iter()) {
+        pool.amount = pool.amount.checked_sub(*protocol_fee)?;
+    }
+
+    Ok(pools)
+}
+
+/// ## Description
+/// Returns the tracked reserves of the pool assets in the order of the pair asset infos.
+/// ## Params
+/// * **storage** is an object of type [`Storage`].
+///
+/// * **config** is an object of type [`Config`].
+pub fn query_reserves(storage: &dyn Storage, config: &Config) -> StdResult<[Asset; 2]> {
+    let reserves = RESERVES.load(storage)?;
+
+    Ok([
+        Asset {
+            info: config.pair_info.asset_infos[0].clone(),
+            amount: reserves[0],
+        },
+        Asset {
+            info: config.pair_info.asset_infos[1].clone(),
+            amount: reserves[1],
+        },
+    ])
+}
+
+/// ## Description
+/// Updates the tracked pool reserves after a swap: the offer amount is added to the offer pool and
+/// the amount that left the pool is subtracted from the ask pool.
+/// ## Params
+/// * **storage** is an object of type [`Storage`].
+///
+/// * **pools** are the tracked reserves before the swap.
+///
+/// * **offer_info** is an object of type [`AssetInfo`]. This is the offer asset of the swap.
+///
+/// * **offer_amount** is an object of type [`Uint128`]. This is the amount of offer assets added to the pool.
+///
+/// * **ask_amount** is an object of type [`Uint128`]. This is the amount of ask assets that left the pool,
+/// including the Maker fee.
+fn save_reserves_after_swap(
+    storage: &mut dyn Storage,
+    pools: &[Asset; 2],
+    offer_info: &AssetInfo,
+    offer_amount: Uint128,
+    ask_amount: Uint128,
+) -> StdResult<()> {
+    let mut reserves = [pools[0].amount, pools[1].amount];
+    for (reserve, pool) in reserves.iter_mut().zip(pools.iter()) {
+        if pool.info.equal(offer_info) {
+            *reserve = reserve.checked_add(offer_amount)?;
+        } else {
+            *reserve = reserve.checked_sub(ask_amount)?;
+        }
+    }
+
+    RESERVES.save(storage, &reserves)
 }
 
 /// ## Description
@@ -1388,8 +3609,7 @@ pub fn migrate(_deps: DepsMut, _env: Env, _msg: MigrateMsg) -> StdResult<Respons
 ///
 /// * **config** is an object of type [`Config`].
 pub fn pool_info(deps: Deps, config: Config) -> StdResult<([Asset; 2], Uint128)> {
-    let contract_addr = config.pair_info.contract_addr.clone();
-    let pools: [Asset; 2] = config.pair_info.query_pools(&deps.querier, contract_addr)?;
+    let pools = query_reserves(deps.storage, &config)?;
     let total_share: Uint128 = query_supply(&deps.querier, config.pair_info.liquidity_token)?;
 
     Ok((pools, total_share))
@@ -1424,8 +3644,19 @@ pub fn update_config(
         StablePoolUpdateParams::StartChangingAmp {
             next_amp,
             next_amp_time,
-        } => start_changing_amp(config, deps, env, next_amp, next_amp_time)?,
-        StablePoolUpdateParams::StopChangingAmp {} => stop_changing_amp(config, deps, env)?,
+        } => start_changing_amp(config, deps, env, info.sender, next_amp, next_amp_time)?,
+        StablePoolUpdateParams::StopChangingAmp {} => {
+            stop_changing_amp(config, deps, env, info.sender)?
+        }
+        StablePoolUpdateParams::SetFeeMultiplier { fee_multiplier } => {
+            set_fee_multiplier(deps, fee_multiplier)?
+        }
+        StablePoolUpdateParams::SetObservationsSize { size } => {
+            if size == 0 || size > MAX_OBSERVATIONS_SIZE {
+                return Err(ContractError::InvalidObservationsSize {});
+            }
+            set_observations_size(deps.storage, size)?
+        }
     }
 
     Ok(Response::default())
@@ -1440,6 +3671,8 @@ pub fn update_config(
 ///
 /// * **env** is an object of type [`Env`].
 ///
+/// * **sender** is an object of type [`Addr`]. This is the address that changes AMP.
+///
 /// * **next_amp** is an object of type [`u64`]. This is the new value for AMP.
 ///
 /// * **next_amp_time** is an object of type [`u64`]. This is the end time when the pool amplification will be equal to `next_amp`.
@@ -1447,6 +3680,7 @@ fn start_changing_amp(
     mut config: Config,
     deps: DepsMut,
     env: Env,
+    sender: Addr,
     next_amp: u64,
     next_amp_time: u64,
 ) -> Result<(), ContractError> {
@@ -1478,6 +3712,7 @@ fn start_changing_amp(
     config.next_amp_time = next_amp_time;
 
     CONFIG.save(deps.storage, &config)?;
+    save_amp_ramp(deps.storage, sender, &config)?;
 
     Ok(())
 }
@@ -1490,7 +3725,9 @@ fn start_changing_amp(
 /// * **deps** is an object of type [`DepsMut`].
 ///
 /// * **env** is an object of type [`Env`].
-fn stop_changing_amp(mut config: Config, deps: DepsMut, env: Env) -> StdResult<()> {
+///
+/// * **sender** is an object of type [`Addr`]. This is the address that stops changing AMP.
+fn stop_changing_amp(mut config: Config, deps: DepsMut, env: Env, sender: Addr) -> StdResult<()> {
     let current_amp = compute_current_amp(&config, &env)?;
     let block_time = env.block.time.seconds();
 
@@ -1501,6 +3738,56 @@ fn stop_changing_amp(mut config: Config, deps: DepsMut, env: Env) -> StdResult<(
 
     // now (block_time < next_amp_time) is always False, so we return the saved AMP
     CONFIG.save(deps.storage, &config)?;
+    save_amp_ramp(deps.storage, sender, &config)?;
+
+    Ok(())
+}
+
+/// ## Description
+/// Records the amplification change that is described by the pool configuration in the history.
+/// ## Params
+/// * **storage** is a mutable reference to an object implementing [`Storage`].
+///
+/// * **sender** is an object of type [`Addr`]. This is the address that changed AMP.
+///
+/// * **config** is a reference to an object of type [`Config`]. This is the updated pool configuration.
+fn save_amp_ramp(storage: &mut dyn Storage, sender: Addr, config: &Config) -> StdResult<()> {
+    let id = LAST_AMP_RAMP_ID.may_load(storage)?.unwrap_or_default() + 1;
+    LAST_AMP_RAMP_ID.save(storage, &id)?;
+
+    AMP_HISTORY.save(
+        storage,
+        U64Key::new(id),
+        &AmpRamp {
+            id,
+            sender,
+            from_amp: Decimal::from_ratio(config.init_amp, AMP_PRECISION),
+            to_amp: Decimal::from_ratio(config.next_amp, AMP_PRECISION),
+            start_time: config.init_amp_time,
+            end_time: config.next_amp_time,
+        },
+    )
+}
+
+/// ## Description
+/// Enables dynamic fees with the specified off-peg fee multiplier or disables them if the multiplier
+/// is not set. Returns a [`ContractError`] on failure, otherwise returns [`Ok`].
+/// ## Params
+/// * **deps** is an object of type [`DepsMut`].
+///
+/// * **fee_multiplier** is an [`Option`] field of type [`Decimal`]. This is the new off-peg fee multiplier.
+fn set_fee_multiplier(deps: DepsMut, fee_multiplier: Option<Decimal>) -> Result<(), ContractError> {
+    match fee_multiplier {
+        Some(fee_multiplier) => {
+            if fee_multiplier < Decimal::one()
+                || fee_multiplier > Decimal::from_ratio(MAX_FEE_MULTIPLIER, 1u64)
+            {
+                return Err(ContractError::IncorrectFeeMultiplier {});
+            }
+            FEE_MULTIPLIER.save(deps.storage, &fee_multiplier)?;
+        }
+        None => FEE_MULTIPLIER.remove(deps.storage),
+    }
 
     Ok(())
 }
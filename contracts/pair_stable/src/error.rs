@@ -1,4 +1,6 @@
-use crate::math::{MAX_AMP, MAX_AMP_CHANGE, MIN_AMP_CHANGING_TIME};
+use crate::math::{MAX_AMP, MAX_AMP_CHANGE, MAX_FEE_MULTIPLIER, MIN_AMP_CHANGING_TIME};
+use astroport::observation::MAX_OBSERVATIONS_SIZE;
+use astroport::pair::MINIMUM_LIQUIDITY;
 use cosmwasm_std::{OverflowError, StdError};
 use thiserror::Error;
 
@@ -12,6 +14,12 @@ pub enum ContractError {
     #[error("Unauthorized")]
     Unauthorized {},
 
+    #[error("Operation non supported")]
+    NonSupported {},
+
+    #[error("Contract can't be migrated!")]
+    MigrationError {},
+
     #[error("Doubling assets in asset infos")]
     DoublingAssets {},
 
@@ -59,6 +67,51 @@ pub enum ContractError {
 
     #[error("Generator address is not set in factory. Cannot autostake")]
     AutoStakeError {},
+
+    #[error("Offer amount exceeds max_offer")]
+    MaxOfferAssertion {},
+
+    #[error("Flash loan amount exceeds the available liquidity")]
+    FlashLoanAmountExceedsPool {},
+
+    #[error("The pair is locked until the flash loan is repaid")]
+    FlashLoanInProgress {},
+
+    #[error("Flash loan is not repaid")]
+    FlashLoanNotRepaid {},
+
+    #[error("Transaction deadline has expired")]
+    DeadlineExpired {},
+
+    #[error("Swaps are paused in the pair")]
+    SwapsPaused {},
+
+    #[error("Deposits are paused in the pair")]
+    DepositsPaused {},
+
+    #[error("Return amount is less than min_return")]
+    MinReturnAssertion {},
+
+    #[error("Minted LP amount is less than min_lp_to_receive")]
+    MinLpToReceiveAssertion {},
+
+    #[error("Withdrawn amount is less than min_assets_out")]
+    MinAssetsOutAssertion {},
+
+    #[error("Initial liquidity must be more than {}", MINIMUM_LIQUIDITY)]
+    MinimumLiquidityAmountError {},
+
+    #[error("Observations size must be between 1 and {}", MAX_OBSERVATIONS_SIZE)]
+    InvalidObservationsSize {},
+
+    #[error("LP amount to burn exceeds the sent amount")]
+    MaxLpToBurnAssertion {},
+
+    #[error(
+        "Fee multiplier must be greater than or equal to 1 and less than or equal to {}",
+        MAX_FEE_MULTIPLIER
+    )]
+    IncorrectFeeMultiplier {},
 }
 
 impl From<OverflowError> for ContractError {
@@ -66,3 +119,12 @@ impl From<OverflowError> for ContractError {
         StdError::from(o).into()
     }
 }
+
+impl From<ContractError> for StdError {
+    fn from(err: ContractError) -> Self {
+        match err {
+            ContractError::Std(err) => err,
+            err => StdError::generic_err(err.to_string()),
+        }
+    }
+}
@@ -1,6 +1,8 @@
 use std::convert::TryFrom;
 
 use astroport::U256;
+use cosmwasm_bignumber::{Decimal256, Uint256};
+use cosmwasm_std::{Decimal, Uint128};
 
 const N_COINS_SQUARED: u8 = 4;
 const ITERATIONS: u8 = 32;
@@ -10,6 +12,9 @@ pub const MAX_AMP: u64 = 1_000_000;
 pub const MAX_AMP_CHANGE: u64 = 10;
 pub const MIN_AMP_CHANGING_TIME: u64 = 86400;
 pub const AMP_PRECISION: u64 = 100;
+pub const MAX_FEE_MULTIPLIER: u64 = 10;
+/// The dynamic fee rate never exceeds this percentage unless the base fee rate is already higher
+const MAX_DYNAMIC_FEE_PERCENT: u64 = 50;
 
 /// ## Description
 /// Calculates the ask amount (the amount of tokens swapped to).
@@ -65,6 +70,287 @@ pub fn calc_offer_amount(
     Some(amount_swapped)
 }
 
+/// ## Description
+/// Calculates the fee rate of a swap for the dynamic fee model. The fee rate grows from `fee_rate`
+/// for a balanced pool up to `fee_rate * fee_multiplier` as the post-swap balances diverge.
+///
+/// * **Equation**
+///
+/// balance = 4 * new_offer_pool * new_ask_pool / D**2
+///
+/// dynamic_fee_rate = fee_rate * fee_multiplier / ((fee_multiplier - 1) * balance + 1)
+///
+/// ## Params
+/// * **offer_pool** is an object of type [`u128`]. This is the amount of offer tokens currently in a stableswap pool.
+///
+/// * **ask_pool** is an object of type [`u128`]. This is the amount of ask tokens currently in a stableswap pool.
+///
+/// * **new_offer_pool** is an object of type [`u128`]. This is the amount of offer tokens in the pool after the swap.
+///
+/// * **new_ask_pool** is an object of type [`u128`]. This is the amount of ask tokens in the pool after the swap.
+///
+/// * **amp** is an object of type [`u64`]. This is the pool's amplification parameter.
+///
+/// * **fee_rate** is an object of type [`Decimal`]. This is the pool's base swap fee rate.
+///
+/// * **fee_multiplier** is an object of type [`Decimal`]. This is the maximum multiplier of the base fee rate.
+pub fn calc_dynamic_fee_rate(
+    offer_pool: u128,
+    ask_pool: u128,
+    new_offer_pool: u128,
+    new_ask_pool: u128,
+    amp: u64,
+    fee_rate: Decimal,
+    fee_multiplier: Decimal,
+) -> Option<Decimal> {
+    if fee_multiplier <= Decimal::one() {
+        return Some(fee_rate);
+    }
+
+    let leverage = amp.checked_mul(u64::from(N_COINS))?;
+    let d = compute_d(leverage, offer_pool, ask_pool)?;
+    if d == 0 {
+        return Some(fee_rate);
+    }
+
+    // The balance is 1 when the pool is pegged and goes down to 0 as the pool diverges
+    let balance = (Decimal256::from_ratio(new_offer_pool, d)
+        * Decimal256::from_ratio(new_ask_pool, d)
+        * Decimal256::from_uint256(u64::from(N_COINS_SQUARED)))
+    .min(Decimal256::one());
+
+    let fee_multiplier = Decimal256::from(fee_multiplier);
+    let dynamic_fee_rate: Decimal = (Decimal256::from(fee_rate) * fee_multiplier
+        / ((fee_multiplier - Decimal256::one()) * balance + Decimal256::one()))
+    .into();
+
+    Some(dynamic_fee_rate.min(fee_rate.max(Decimal::percent(MAX_DYNAMIC_FEE_PERCENT))))
+}
+
+/// ## Description
+/// Calculates the spot price of the offer asset in the ask asset (the amount of ask tokens returned for an
+/// infinitesimal amount of offer tokens).
+/// ## Params
+/// * **offer_pool** is an object of type [`u128`]. This is the amount of offer tokens currently in a stableswap pool.
+///
+/// * **ask_pool** is an object of type [`u128`]. This is the amount of ask tokens currently in a stableswap pool.
+///
+/// * **amp** is an object of type [`u64`]. This is the pool's amplification parameter.
+pub fn calc_spot_price(offer_pool: u128, ask_pool: u128, amp: u64) -> Option<Decimal256> {
+    let leverage = amp.checked_mul(u64::from(N_COINS))?;
+    let d = compute_d(leverage, offer_pool, ask_pool)?;
+
+    compute_spot_price(leverage, offer_pool, ask_pool, d)
+}
+
+/// ## Description
+/// Calculates the amount of offer tokens that can be swapped before the spot price of the offer asset drops
+/// to `price_ratio` of its current value. The amount is found with a binary search up to one millionth of it.
+/// ## Params
+/// * **offer_pool** is an object of type [`u128`]. This is the amount of offer tokens currently in a stableswap pool.
+///
+/// * **ask_pool** is an object of type [`u128`]. This is the amount of ask tokens currently in a stableswap pool.
+///
+/// * **price_ratio** is an object of type [`Decimal256`]. This is the ratio of the target and the current spot price.
+///
+/// * **amp** is an object of type [`u64`]. This is the pool's amplification parameter.
+pub fn calc_depth(
+    offer_pool: u128,
+    ask_pool: u128,
+    price_ratio: Decimal256,
+    amp: u64,
+) -> Option<u128> {
+    let leverage = amp.checked_mul(u64::from(N_COINS))?;
+    let d = compute_d(leverage, offer_pool, ask_pool)?;
+    let target_price = compute_spot_price(leverage, offer_pool, ask_pool, d)? * price_ratio;
+
+    let price_after_swap = |offer_amount: u128| -> Option<Decimal256> {
+        let new_offer_pool = offer_pool.checked_add(offer_amount)?;
+        match compute_new_balance(leverage, new_offer_pool, d)? {
+            0 => Some(Decimal256::zero()),
+            new_ask_pool => compute_spot_price(leverage, new_offer_pool, new_ask_pool, d),
+        }
+    };
+
+    let mut low = 0;
+    let mut high = offer_pool;
+    while price_after_swap(high)? > target_price {
+        low = high;
+        high = high.checked_mul(2)?;
+    }
+
+    while high - low > 1 && high - low > high / 1_000_000 {
+        let mid = low + (high - low) / 2;
+        if price_after_swap(mid)? > target_price {
+            low = mid;
+        } else {
+            high = mid;
+        }
+    }
+
+    Some(low)
+}
+
+/// ## Description
+/// Calculates the amount of ask tokens withdrawn for burning `lp_amount` LP tokens in a single asset
+/// together with the fee charged on the withdrawal. The fee is charged on the difference between the
+/// withdrawal and a proportional one, the same way a swap would be charged.
+/// ## Params
+/// * **ask_pool** is an object of type [`u128`]. This is the amount of ask tokens currently in a stableswap pool.
+///
+/// * **other_pool** is an object of type [`u128`]. This is the amount of the other tokens currently in a stableswap pool.
+///
+/// * **lp_amount** is an object of type [`u128`]. This is the amount of LP tokens to burn.
+///
+/// * **total_share** is an object of type [`u128`]. This is the total amount of LP tokens.
+///
+/// * **amp** is an object of type [`u64`]. This is the pool's amplification parameter.
+///
+/// * **fee_rate** is an object of type [`Decimal`]. This is the pool's swap fee rate.
+pub fn calc_withdraw_one_coin(
+    ask_pool: u128,
+    other_pool: u128,
+    lp_amount: u128,
+    total_share: u128,
+    amp: u64,
+    fee_rate: Decimal,
+) -> Option<(u128, u128)> {
+    let leverage = amp.checked_mul(u64::from(N_COINS))?;
+    let d_before = compute_d(leverage, ask_pool, other_pool)?;
+    let d_after = d_before.checked_sub(
+        Uint128::new(d_before)
+            .multiply_ratio(lp_amount, total_share)
+            .u128(),
+    )?;
+
+    let new_ask_pool = compute_new_balance(leverage, other_pool, d_after)?;
+    let amount_without_fee = ask_pool.checked_sub(new_ask_pool)?;
+
+    // The proportional withdrawal doesn't change the pool ratio, the rest is charged as a swap
+    let ask_difference = Uint128::new(ask_pool)
+        .multiply_ratio(d_after, d_before)
+        .u128()
+        .saturating_sub(new_ask_pool);
+    let other_difference = other_pool.checked_sub(
+        Uint128::new(other_pool)
+            .multiply_ratio(d_after, d_before)
+            .u128(),
+    )?;
+    let reduced_ask_pool = ask_pool.checked_sub(imbalance_fee(ask_difference, fee_rate))?;
+    let reduced_other_pool = other_pool.checked_sub(imbalance_fee(other_difference, fee_rate))?;
+
+    let new_reduced_ask_pool = compute_new_balance(leverage, reduced_other_pool, d_after)?;
+    // Withdraw less to account for rounding errors
+    let amount = reduced_ask_pool
+        .checked_sub(new_reduced_ask_pool)?
+        .saturating_sub(1);
+
+    Some((amount, amount_without_fee.saturating_sub(amount)))
+}
+
+/// ## Description
+/// Calculates the amount of LP tokens burned for withdrawing exact amounts of tokens together with
+/// the fees charged on the withdrawal. The fees are charged on the difference between the withdrawal
+/// and a proportional one, the same way a swap would be charged.
+/// ## Params
+/// * **pools** is an array with two objects of type [`u128`]. These are the amounts of tokens currently in a stableswap pool.
+///
+/// * **amounts** is an array with two objects of type [`u128`]. These are the amounts of tokens to withdraw.
+///
+/// * **total_share** is an object of type [`u128`]. This is the total amount of LP tokens.
+///
+/// * **amp** is an object of type [`u64`]. This is the pool's amplification parameter.
+///
+/// * **fee_rate** is an object of type [`Decimal`]. This is the pool's swap fee rate.
+pub fn calc_withdraw_imbalanced(
+    pools: [u128; 2],
+    amounts: [u128; 2],
+    total_share: u128,
+    amp: u64,
+    fee_rate: Decimal,
+) -> Option<(u128, [u128; 2])> {
+    let leverage = amp.checked_mul(u64::from(N_COINS))?;
+    let d_before = compute_d(leverage, pools[0], pools[1])?;
+    let new_pools = [
+        pools[0].checked_sub(amounts[0])?,
+        pools[1].checked_sub(amounts[1])?,
+    ];
+    let d_after = compute_d(leverage, new_pools[0], new_pools[1])?;
+
+    let mut fees = [0; 2];
+    let mut pools_after_fee = new_pools;
+    for i in 0..2 {
+        let ideal_pool = Uint128::new(pools[i])
+            .multiply_ratio(d_after, d_before)
+            .u128();
+        let difference = ideal_pool.max(new_pools[i]) - ideal_pool.min(new_pools[i]);
+        fees[i] = imbalance_fee(difference, fee_rate);
+        pools_after_fee[i] = new_pools[i].checked_sub(fees[i])?;
+    }
+
+    let d_after_fee = compute_d(leverage, pools_after_fee[0], pools_after_fee[1])?;
+    // Burn more to account for rounding errors
+    let lp_amount = Uint128::new(total_share)
+        .multiply_ratio(d_before.checked_sub(d_after_fee)?, d_before)
+        .u128()
+        .checked_add(1)?;
+
+    Some((lp_amount, fees))
+}
+
+/// ## Description
+/// Returns the fee charged on the part of a deposit or a withdrawal that doesn't match the pool ratio.
+///
+/// * **Equation**
+///
+/// fee = difference * fee_rate * n / (4 * (n - 1))
+///
+/// ## Params
+/// * **difference** is an object of type [`u128`]. This is the difference from the proportional amount.
+///
+/// * **fee_rate** is an object of type [`Decimal`]. This is the pool's swap fee rate.
+fn imbalance_fee(difference: u128, fee_rate: Decimal) -> u128 {
+    (Uint128::new(difference) * fee_rate)
+        .multiply_ratio(N_COINS, 4 * (N_COINS - 1))
+        .u128()
+}
+
+/// ## Description
+/// Calculates the spot price of the offer asset in the ask asset for the specified invariant.
+///
+/// * **Equation**
+///
+/// price = (A * n**n + D**(n+1) / (n**n * offer_pool**2 * ask_pool)) / (A * n**n + D**(n+1) / (n**n * offer_pool * ask_pool**2))
+///
+/// ## Params
+/// * **leverage** is an object of type [`u64`].
+///
+/// * **offer_pool** is an object of type [`u128`].
+///
+/// * **ask_pool** is an object of type [`u128`].
+///
+/// * **d** is an object of type [`u128`].
+fn compute_spot_price(
+    leverage: u64,
+    offer_pool: u128,
+    ask_pool: u128,
+    d: u128,
+) -> Option<Decimal256> {
+    if offer_pool == 0 || ask_pool == 0 {
+        return None;
+    }
+
+    let leverage = Decimal256::from_ratio(leverage, AMP_PRECISION);
+    let n_coins_squared = Decimal256::from_uint256(u64::from(N_COINS_SQUARED));
+    let d_offer = Decimal256::from_ratio(Uint256::from(d), Uint256::from(offer_pool));
+    let d_ask = Decimal256::from_ratio(Uint256::from(d), Uint256::from(ask_pool));
+
+    Some(
+        (leverage + d_offer * d_offer * d_ask / n_coins_squared)
+            / (leverage + d_offer * d_ask * d_ask / n_coins_squared),
+    )
+}
+
 /// ## Description
 /// Computes the stableswap invariant (D).
 ///
@@ -3,10 +3,11 @@ use cosmwasm_std::{
     from_binary, from_slice, to_binary, Addr, Coin, Decimal, OwnedDeps, Querier, QuerierResult,
     QueryRequest, SystemError, SystemResult, Uint128, WasmQuery,
 };
+use std::cell::Cell;
 use std::collections::HashMap;
 
-use astroport::factory::FeeInfoResponse;
-use astroport::factory::QueryMsg::FeeInfo;
+use astroport::factory::QueryMsg::{Config, FeeInfo, PairStatus as PairStatusQuery};
+use astroport::factory::{ConfigResponse, FeeInfoResponse, PairStatus};
 use cw20::{BalanceResponse, Cw20QueryMsg, TokenInfoResponse};
 use terra_cosmwasm::{TaxCapResponse, TaxRateResponse, TerraQuery, TerraQueryWrapper, TerraRoute};
 
@@ -29,6 +30,8 @@ pub struct WasmMockQuerier {
     base: MockQuerier<TerraQueryWrapper>,
     token_querier: TokenQuerier,
     tax_querier: TaxQuerier,
+    query_count: Cell<u64>,
+    factory_query_count: Cell<u64>,
 }
 
 #[derive(Clone, Default)]
@@ -96,6 +99,7 @@ impl Querier for WasmMockQuerier {
                 })
             }
         };
+        self.query_count.set(self.query_count.get() + 1);
         self.handle_query(&request)
     }
 }
@@ -130,15 +134,33 @@ impl WasmMockQuerier {
             }
             QueryRequest::Wasm(WasmQuery::Smart { contract_addr, msg }) => {
                 if contract_addr == "factory" {
+                    self.factory_query_count
+                        .set(self.factory_query_count.get() + 1);
                     match from_binary(&msg).unwrap() {
                         FeeInfo { .. } => SystemResult::Ok(
                             to_binary(&FeeInfoResponse {
                                 fee_address: Some(Addr::unchecked("fee_address")),
                                 total_fee_bps: 30,
                                 maker_fee_bps: 1660,
+                                maker_fee_as_lp: false,
                             })
                             .into(),
                         ),
+                        Config { .. } => SystemResult::Ok(
+                            to_binary(&ConfigResponse {
+                                owner: Addr::unchecked("owner"),
+                                pair_configs: vec![],
+                                token_code_id: 0,
+                                fee_address: Some(Addr::unchecked("fee_address")),
+                                generator_address: Some(Addr::unchecked("generator")),
+                                whitelist_code_id: 0,
+                                guardian: None,
+                            })
+                            .into(),
+                        ),
+                        PairStatusQuery { .. } => {
+                            SystemResult::Ok(to_binary(&PairStatus::Active).into())
+                        }
                         _ => panic!("DO NOT ENTER HERE"),
                     }
                 } else {
@@ -203,6 +225,8 @@ impl WasmMockQuerier {
             base,
             token_querier: TokenQuerier::default(),
             tax_querier: TaxQuerier::default(),
+            query_count: Cell::new(0),
+            factory_query_count: Cell::new(0),
         }
     }
 
@@ -216,6 +240,11 @@ impl WasmMockQuerier {
         self.tax_querier = TaxQuerier::new(rate, caps);
     }
 
+    // Returns the number of queries made so far and how many of them were sent to the factory
+    pub fn query_counts(&self) -> (u64, u64) {
+        (self.query_count.get(), self.factory_query_count.get())
+    }
+
     pub fn with_balance(&mut self, balances: &[(&String, &[Coin])]) {
         for (addr, balance) in balances {
             self.base.update_balance(addr.to_string(), balance.to_vec());
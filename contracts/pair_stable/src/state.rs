@@ -1,6 +1,8 @@
-use astroport::asset::PairInfo;
-use cosmwasm_std::{Addr, Uint128};
-use cw_storage_plus::Item;
+use astroport::asset::{Asset, PairInfo};
+use astroport::pair::AmpRamp;
+use astroport::querier::FactoryParams;
+use cosmwasm_std::{Addr, Decimal, Uint128};
+use cw_storage_plus::{Item, Map, U64Key};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
@@ -29,3 +31,52 @@ pub struct Config {
 }
 
 pub const CONFIG: Item<Config> = Item::new("config");
+
+/// ## Description
+/// This structure stores the state of a flash loan until it's repaid.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct FlashLoanState {
+    /// The pool balances before the flash loan
+    pub pools: [Asset; 2],
+    /// The amounts held by the pair on top of the reserves before the flash loan (assets sent to the pair
+    /// without a sync). They don't count towards the repayment
+    pub unsynced_amounts: [Uint128; 2],
+    /// The borrowed asset
+    pub asset: Asset,
+    /// The total flash loan fee in the borrowed asset
+    pub commission_amount: Uint128,
+    /// The part of the fee that goes to the Maker contract
+    pub maker_fee_amount: Uint128,
+    /// The address that receives the Maker fee
+    pub fee_address: Option<Addr>,
+}
+
+/// Stores the flash loan that is in progress
+pub const FLASH_LOAN: Item<FlashLoanState> = Item::new("flash_loan");
+
+/// Stores the tracked pool reserves in the order of the pair asset infos. Assets sent to the pair
+/// directly are not counted until they are synced
+pub const RESERVES: Item<[Uint128; 2]> = Item::new("reserves");
+
+/// Stores the off-peg fee multiplier of the dynamic fee model. The pool charges the flat factory fee
+/// if it's not set
+pub const FEE_MULTIPLIER: Item<Decimal> = Item::new("fee_multiplier");
+
+/// Stores the amplification changes of the pool by their identifiers
+pub const AMP_HISTORY: Map<U64Key, AmpRamp> = Map::new("amp_history");
+
+/// Stores the identifier of the last amplification change
+pub const LAST_AMP_RAMP_ID: Item<u64> = Item::new("last_amp_ramp_id");
+
+/// ## Description
+/// Stores the fee and Generator information cached from the factory
+pub const FACTORY_PARAMS: Item<FactoryParams> = Item::new("factory_params");
+
+/// ## Description
+/// Stores whether the Maker fees are accrued in the pair instead of being sent on every swap
+pub const ACCRUE_PROTOCOL_FEES: Item<bool> = Item::new("accrue_protocol_fees");
+
+/// ## Description
+/// Stores the Maker fees accrued in the pair in the order of the pair asset infos. They are not part of
+/// the pool reserves
+pub const PROTOCOL_FEES: Item<[Uint128; 2]> = Item::new("protocol_fees");
@@ -1,28 +1,40 @@
 use crate::contract::{
-    accumulate_prices, assert_max_spread, execute, instantiate, query_pair_info, query_pool,
-    query_share, query_simulation, reply,
+    accumulate_prices, assert_max_spread, execute, instantiate, migrate, query, query_amp_history,
+    query_config, query_current_d, query_depth, query_pair_info, query_pool, query_price_impact,
+    query_protocol_fees, query_reverse_simulation, query_share, query_simulate_provide,
+    query_simulate_withdraw, query_simulate_withdraw_imbalanced, query_simulate_withdraw_one_coin,
+    query_simulation, query_spot_price, query_virtual_price, reply,
 };
 use crate::error::ContractError;
-use crate::math::{calc_ask_amount, calc_offer_amount, AMP_PRECISION};
+use crate::math::{
+    calc_ask_amount, calc_depth, calc_dynamic_fee_rate, calc_offer_amount, calc_spot_price,
+    AMP_PRECISION, MAX_FEE_MULTIPLIER,
+};
 use crate::mock_querier::mock_dependencies;
 
 use crate::response::MsgInstantiateContractResponse;
-use crate::state::Config;
+use crate::state::{Config, FACTORY_PARAMS, RESERVES};
 use astroport::asset::{Asset, AssetInfo, PairInfo};
+use astroport::factory::PairStatus;
+use astroport::querier::{FactoryParams, FeeInfo};
 
 use astroport::pair::{
-    Cw20HookMsg, ExecuteMsg, InstantiateMsg, PoolResponse, SimulationResponse, StablePoolParams,
-    TWAP_PRECISION,
+    AmpRamp, CumulativePricesResponse, Cw20HookMsg, ExecuteMsg, FlashLoanReceiverMsg,
+    InstantiateMsg, MigrateMsg, ObserveResponse, PoolResponse, QueryMsg, SimulationResponse,
+    StablePoolConfig, StablePoolParams, StablePoolUpdateParams, MINIMUM_LIQUIDITY, TWAP_PRECISION,
 };
 use astroport::token::InstantiateMsg as TokenInstantiateMsg;
+use cosmwasm_bignumber::Decimal256;
 use cosmwasm_std::testing::{mock_env, mock_info, MOCK_CONTRACT_ADDR};
 use cosmwasm_std::{
-    attr, to_binary, Addr, BankMsg, BlockInfo, Coin, ContractResult, CosmosMsg, Decimal, DepsMut,
-    Env, Reply, ReplyOn, Response, StdError, SubMsg, SubMsgExecutionResponse, Timestamp, Uint128,
-    WasmMsg,
+    attr, from_binary, to_binary, Addr, BankMsg, BlockInfo, Coin, ContractResult, CosmosMsg,
+    Decimal, DepsMut, Env, Reply, ReplyOn, Response, StdError, SubMsg, SubMsgExecutionResponse,
+    Timestamp, Uint128, Uint256 as StdUint256, WasmMsg,
 };
+use cw2::{get_contract_version, set_contract_version};
 use cw20::{Cw20ExecuteMsg, Cw20ReceiveMsg, MinterResponse};
 use protobuf::Message;
+use std::str::FromStr;
 
 fn store_liquidity_token(deps: DepsMut, msg_id: u64, contract_addr: String) {
     let data = MsgInstantiateContractResponse {
@@ -45,6 +57,10 @@ fn store_liquidity_token(deps: DepsMut, msg_id: u64, contract_addr: String) {
     let _res = reply(deps, mock_env(), reply_msg.clone()).unwrap();
 }
 
+fn store_reserves(deps: DepsMut, reserves: [Uint128; 2]) {
+    RESERVES.save(deps.storage, &reserves).unwrap();
+}
+
 #[test]
 fn proper_initialization() {
     let mut deps = mock_dependencies(&[]);
@@ -178,6 +194,8 @@ fn provide_liquidity() {
         slippage_tolerance: None,
         auto_stake: None,
         receiver: None,
+        min_lp_to_receive: None,
+        deadline: None,
     };
 
     let env = mock_env();
@@ -190,7 +208,8 @@ fn provide_liquidity() {
     );
     let res = execute(deps.as_mut(), env.clone().clone(), info, msg).unwrap();
     let transfer_from_msg = res.messages.get(0).expect("no message");
-    let mint_msg = res.messages.get(1).expect("no message");
+    let mint_min_liquidity_msg = res.messages.get(1).expect("no message");
+    let mint_msg = res.messages.get(2).expect("no message");
     assert_eq!(
         transfer_from_msg,
         &SubMsg {
@@ -210,6 +229,24 @@ fn provide_liquidity() {
             reply_on: ReplyOn::Never
         }
     );
+    assert_eq!(
+        mint_min_liquidity_msg,
+        &SubMsg {
+            msg: WasmMsg::Execute {
+                contract_addr: String::from("liquidity0000"),
+                msg: to_binary(&Cw20ExecuteMsg::Mint {
+                    recipient: String::from(MOCK_CONTRACT_ADDR),
+                    amount: MINIMUM_LIQUIDITY,
+                })
+                .unwrap(),
+                funds: vec![],
+            }
+            .into(),
+            id: 0,
+            gas_limit: None,
+            reply_on: ReplyOn::Never,
+        }
+    );
     assert_eq!(
         mint_msg,
         &SubMsg {
@@ -217,7 +254,7 @@ fn provide_liquidity() {
                 contract_addr: String::from("liquidity0000"),
                 msg: to_binary(&Cw20ExecuteMsg::Mint {
                     recipient: String::from("addr0000"),
-                    amount: Uint128::from(100_000000000000000000u128),
+                    amount: Uint128::from(100_000000000000000000u128) - MINIMUM_LIQUIDITY,
                 })
                 .unwrap(),
                 funds: vec![],
@@ -254,6 +291,13 @@ fn provide_liquidity() {
             )],
         ),
     ]);
+    store_reserves(
+        deps.as_mut(),
+        [
+            Uint128::new(200_000000000000000000),
+            Uint128::new(200_000000000000000000),
+        ],
+    );
 
     let msg = ExecuteMsg::ProvideLiquidity {
         assets: [
@@ -273,6 +317,8 @@ fn provide_liquidity() {
         slippage_tolerance: None,
         auto_stake: None,
         receiver: None,
+        min_lp_to_receive: None,
+        deadline: None,
     };
 
     let env = mock_env_with_block_time(env.block.time.seconds() + 1000);
@@ -344,6 +390,8 @@ fn provide_liquidity() {
         slippage_tolerance: None,
         auto_stake: None,
         receiver: None,
+        min_lp_to_receive: None,
+        deadline: None,
     };
 
     let env = mock_env();
@@ -388,6 +436,13 @@ fn provide_liquidity() {
             )],
         ),
     ]);
+    store_reserves(
+        deps.as_mut(),
+        [
+            Uint128::new(100_000000000000000000),
+            Uint128::new(100_000000000000000000),
+        ],
+    );
 
     // Initialize token balances with a ratio of 1:1
     deps.querier.with_balance(&[(
@@ -426,6 +481,8 @@ fn provide_liquidity() {
         slippage_tolerance: Some(Decimal::percent(1)),
         auto_stake: None,
         receiver: None,
+        min_lp_to_receive: None,
+        deadline: None,
     };
 
     let env = mock_env_with_block_time(env.block.time.seconds() + 1000);
@@ -446,6 +503,13 @@ fn provide_liquidity() {
             amount: Uint128::new(100_000000000000000000 + 99_000000000000000000 /* user deposit must be pre-applied */),
         }],
     )]);
+    store_reserves(
+        deps.as_mut(),
+        [
+            Uint128::new(100_000000000000000000),
+            Uint128::new(100_000000000000000000),
+        ],
+    );
 
     // Successfully provide liquidity
     let msg = ExecuteMsg::ProvideLiquidity {
@@ -466,6 +530,8 @@ fn provide_liquidity() {
         slippage_tolerance: Some(Decimal::percent(1)),
         auto_stake: None,
         receiver: None,
+        min_lp_to_receive: None,
+        deadline: None,
     };
 
     let env = mock_env_with_block_time(env.block.time.seconds() + 1000);
@@ -522,11 +588,19 @@ fn withdraw_liquidity() {
 
     // Store the liquidity token
     store_liquidity_token(deps.as_mut(), 1, "liquidity0000".to_string());
+    store_reserves(
+        deps.as_mut(),
+        [Uint128::new(100u128), Uint128::new(100u128)],
+    );
 
     // Withdraw liquidity
     let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
         sender: String::from("addr0000"),
-        msg: to_binary(&Cw20HookMsg::WithdrawLiquidity {}).unwrap(),
+        msg: to_binary(&Cw20HookMsg::WithdrawLiquidity {
+            min_assets_out: None,
+            deadline: None,
+        })
+        .unwrap(),
         amount: Uint128::new(100u128),
     });
 
@@ -648,6 +722,7 @@ fn try_native_to_token() {
 
     // Store the liquidity token
     store_liquidity_token(deps.as_mut(), 1, "liquidity0000".to_string());
+    store_reserves(deps.as_mut(), [collateral_pool_amount, asset_pool_amount]);
 
     // Normal swap
     let msg = ExecuteMsg::Swap {
@@ -660,6 +735,8 @@ fn try_native_to_token() {
         belief_price: None,
         max_spread: Some(Decimal::percent(50)),
         to: None,
+        min_return: None,
+        deadline: None,
     };
     let env = mock_env_with_block_time(1000);
     let info = mock_info(
@@ -699,6 +776,7 @@ fn try_native_to_token() {
             amount: collateral_pool_amount, /* user deposit must be pre-applied */
         }],
     )]);
+    store_reserves(deps.as_mut(), [collateral_pool_amount, asset_pool_amount]);
 
     let simulation_res: SimulationResponse = query_simulation(
         deps.as_ref(),
@@ -802,6 +880,7 @@ fn try_token_to_native() {
 
     // Store the liquidity token
     store_liquidity_token(deps.as_mut(), 1, "liquidity0000".to_string());
+    store_reserves(deps.as_mut(), [collateral_pool_amount, asset_pool_amount]);
 
     // Unauthorized access; can not execute swap directy for token swap
     let msg = ExecuteMsg::Swap {
@@ -814,6 +893,8 @@ fn try_token_to_native() {
         belief_price: None,
         max_spread: None,
         to: None,
+        min_return: None,
+        deadline: None,
     };
     let env = mock_env_with_block_time(1000);
     let info = mock_info("addr0000", &[]);
@@ -828,6 +909,8 @@ fn try_token_to_native() {
             belief_price: None,
             max_spread: None,
             to: None,
+            min_return: None,
+            deadline: None,
         })
         .unwrap(),
     });
@@ -873,6 +956,7 @@ fn try_token_to_native() {
             &[(&String::from(MOCK_CONTRACT_ADDR), &(asset_pool_amount))],
         ),
     ]);
+    store_reserves(deps.as_mut(), [collateral_pool_amount, asset_pool_amount]);
 
     let simulation_res: SimulationResponse = query_simulation(
         deps.as_ref(),
@@ -933,6 +1017,8 @@ fn try_token_to_native() {
             belief_price: None,
             max_spread: None,
             to: None,
+            min_return: None,
+            deadline: None,
         })
         .unwrap(),
     });
@@ -943,91 +1029,24 @@ fn try_token_to_native() {
 }
 
 #[test]
-fn test_max_spread() {
-    assert_max_spread(
-        Some(Decimal::from_ratio(1200u128, 1u128)),
-        Some(Decimal::percent(1)),
-        Uint128::from(1200000000u128),
-        Uint128::from(989999u128),
-        Uint128::zero(),
-    )
-    .unwrap_err();
-
-    assert_max_spread(
-        Some(Decimal::from_ratio(1200u128, 1u128)),
-        Some(Decimal::percent(1)),
-        Uint128::from(1200000000u128),
-        Uint128::from(990000u128),
-        Uint128::zero(),
-    )
-    .unwrap();
-
-    assert_max_spread(
-        None,
-        Some(Decimal::percent(1)),
-        Uint128::zero(),
-        Uint128::from(989999u128),
-        Uint128::from(10001u128),
-    )
-    .unwrap_err();
-
-    assert_max_spread(
-        None,
-        Some(Decimal::percent(1)),
-        Uint128::zero(),
-        Uint128::from(990000u128),
-        Uint128::from(10000u128),
-    )
-    .unwrap();
-}
-
-#[test]
-fn test_deduct() {
-    let mut deps = mock_dependencies(&[]);
-
-    let tax_rate = Decimal::percent(2);
-    let tax_cap = Uint128::from(1_000_000u128);
-    deps.querier.with_tax(
-        Decimal::percent(2),
-        &[(&"uusd".to_string(), &Uint128::from(1000000u128))],
-    );
-
-    let amount = Uint128::new(1000_000_000u128);
-    let expected_after_amount = std::cmp::max(
-        amount.checked_sub(amount * tax_rate).unwrap(),
-        amount.checked_sub(tax_cap).unwrap(),
-    );
-
-    let after_amount = (Asset {
-        info: AssetInfo::NativeToken {
-            denom: "uusd".to_string(),
-        },
-        amount,
-    })
-    .deduct_tax(&deps.as_ref().querier)
-    .unwrap();
-
-    assert_eq!(expected_after_amount, after_amount.amount);
-}
+fn flash_loan() {
+    let total_share = Uint128::new(30000000000u128);
+    let asset_pool_amount = Uint128::new(20000000000u128);
+    let collateral_pool_amount = Uint128::new(30000000000u128);
+    let loan_amount = Uint128::new(1000000000u128);
 
-#[test]
-fn test_query_pool() {
-    let total_share_amount = Uint128::from(111u128);
-    let asset_0_amount = Uint128::from(222u128);
-    let asset_1_amount = Uint128::from(333u128);
     let mut deps = mock_dependencies(&[Coin {
         denom: "uusd".to_string(),
-        amount: asset_0_amount,
+        amount: collateral_pool_amount,
     }]);
-
     deps.querier.with_token_balances(&[
         (
-            &String::from("asset0000"),
-            &[(&String::from(MOCK_CONTRACT_ADDR), &asset_1_amount)],
+            &String::from("liquidity0000"),
+            &[(&String::from(MOCK_CONTRACT_ADDR), &total_share)],
         ),
         (
-            &String::from("liquidity0000"),
-            &[(&String::from(MOCK_CONTRACT_ADDR), &total_share_amount)],
+            &String::from("asset0000"),
+            &[(&String::from(MOCK_CONTRACT_ADDR), &asset_pool_amount)],
         ),
     ]);
 
@@ -1045,54 +1064,191 @@ fn test_query_pool() {
         init_params: Some(to_binary(&StablePoolParams { amp: 100 }).unwrap()),
     };
 
-    let env = mock_env();
+    let env = mock_env_with_block_time(100);
     let info = mock_info("addr0000", &[]);
-    // We can just call .unwrap() to assert this was a success
-    let _res = instantiate(deps.as_mut(), env, info, msg).unwrap();
-
-    // Store the liquidity token
+    instantiate(deps.as_mut(), env, info, msg).unwrap();
     store_liquidity_token(deps.as_mut(), 1, "liquidity0000".to_string());
+    store_reserves(deps.as_mut(), [collateral_pool_amount, asset_pool_amount]);
 
-    let res: PoolResponse = query_pool(deps.as_ref()).unwrap();
+    let loan_asset = Asset {
+        info: AssetInfo::Token {
+            contract_addr: Addr::unchecked("asset0000"),
+        },
+        amount: loan_amount,
+    };
+    let flash_loan_msg = ExecuteMsg::FlashLoan {
+        asset: loan_asset.clone(),
+        receiver: Some(String::from("receiver")),
+        msg: to_binary(&"arbitrage").unwrap(),
+    };
 
+    // The fee is 0.3% of the loan and the Maker gets 16.6% of the fee
+    let expected_commission_amount = Uint128::new(3000000u128);
+    let expected_maker_fee_amount = Uint128::new(498000u128);
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env_with_block_time(1000),
+        mock_info("addr0000", &[]),
+        flash_loan_msg.clone(),
+    )
+    .unwrap();
     assert_eq!(
-        res.assets,
-        [
-            Asset {
-                info: AssetInfo::NativeToken {
-                    denom: "uusd".to_string(),
-                },
-                amount: asset_0_amount
-            },
-            Asset {
-                info: AssetInfo::Token {
-                    contract_addr: Addr::unchecked("asset0000"),
-                },
-                amount: asset_1_amount
-            }
+        res.messages,
+        vec![
+            SubMsg::new(WasmMsg::Execute {
+                contract_addr: String::from("asset0000"),
+                msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                    recipient: String::from("receiver"),
+                    amount: loan_amount,
+                })
+                .unwrap(),
+                funds: vec![],
+            }),
+            SubMsg::new(WasmMsg::Execute {
+                contract_addr: String::from("receiver"),
+                msg: to_binary(&FlashLoanReceiverMsg::FlashLoanCallback {
+                    asset: loan_asset,
+                    fee: expected_commission_amount,
+                    msg: to_binary(&"arbitrage").unwrap(),
+                })
+                .unwrap(),
+                funds: vec![],
+            }),
+            SubMsg::new(WasmMsg::Execute {
+                contract_addr: String::from(MOCK_CONTRACT_ADDR),
+                msg: to_binary(&ExecuteMsg::AssertFlashLoanRepaid {}).unwrap(),
+                funds: vec![],
+            }),
         ]
     );
-    assert_eq!(res.total_share, total_share_amount);
+
+    // The pair is locked until the loan is repaid
+    let msg = ExecuteMsg::Swap {
+        offer_asset: Asset {
+            info: AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            amount: loan_amount,
+        },
+        belief_price: None,
+        max_spread: None,
+        to: None,
+        min_return: None,
+        deadline: None,
+    };
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: loan_amount,
+        }],
+    );
+    let res = execute(deps.as_mut(), mock_env_with_block_time(1000), info, msg).unwrap_err();
+    assert_eq!(res, ContractError::FlashLoanInProgress {});
+
+    // The loan is repaid without the fee
+    deps.querier.with_token_balances(&[(
+        &String::from("asset0000"),
+        &[(
+            &String::from(MOCK_CONTRACT_ADDR),
+            &(asset_pool_amount + expected_commission_amount - Uint128::new(1)),
+        )],
+    )]);
+    let res = execute(
+        deps.as_mut(),
+        mock_env_with_block_time(1000),
+        mock_info(MOCK_CONTRACT_ADDR, &[]),
+        ExecuteMsg::AssertFlashLoanRepaid {},
+    )
+    .unwrap_err();
+    assert_eq!(res, ContractError::FlashLoanNotRepaid {});
+
+    // The loan is repaid with the fee and the Maker fee is sent out
+    deps.querier.with_token_balances(&[(
+        &String::from("asset0000"),
+        &[(&String::from(MOCK_CONTRACT_ADDR), &asset_pool_amount)],
+    )]);
+    execute(
+        deps.as_mut(),
+        mock_env_with_block_time(1000),
+        mock_info("addr0000", &[]),
+        flash_loan_msg.clone(),
+    )
+    .unwrap();
+    deps.querier.with_token_balances(&[(
+        &String::from("asset0000"),
+        &[(
+            &String::from(MOCK_CONTRACT_ADDR),
+            &(asset_pool_amount + expected_commission_amount),
+        )],
+    )]);
+    let res = execute(
+        deps.as_mut(),
+        mock_env_with_block_time(1000),
+        mock_info(MOCK_CONTRACT_ADDR, &[]),
+        ExecuteMsg::AssertFlashLoanRepaid {},
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(WasmMsg::Execute {
+            contract_addr: String::from("asset0000"),
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: String::from("fee_address"),
+                amount: expected_maker_fee_amount,
+            })
+            .unwrap(),
+            funds: vec![],
+        })]
+    );
+
+    // An unsynced donation that was sent to the pair before the loan doesn't count towards the repayment
+    store_reserves(deps.as_mut(), [collateral_pool_amount, asset_pool_amount]);
+    deps.querier.with_token_balances(&[(
+        &String::from("asset0000"),
+        &[(
+            &String::from(MOCK_CONTRACT_ADDR),
+            &(asset_pool_amount + expected_commission_amount),
+        )],
+    )]);
+    execute(
+        deps.as_mut(),
+        mock_env_with_block_time(1000),
+        mock_info("addr0000", &[]),
+        flash_loan_msg,
+    )
+    .unwrap();
+    let res = execute(
+        deps.as_mut(),
+        mock_env_with_block_time(1000),
+        mock_info(MOCK_CONTRACT_ADDR, &[]),
+        ExecuteMsg::AssertFlashLoanRepaid {},
+    )
+    .unwrap_err();
+    assert_eq!(res, ContractError::FlashLoanNotRepaid {});
 }
 
 #[test]
-fn test_query_share() {
-    let total_share_amount = Uint128::from(500u128);
-    let asset_0_amount = Uint128::from(250u128);
-    let asset_1_amount = Uint128::from(1000u128);
+fn swap_exact_out() {
+    let total_share = Uint128::new(30000000000u128);
+    let asset_pool_amount = Uint128::new(20000000000u128);
+    let collateral_pool_amount = Uint128::new(30000000000u128);
+    let ask_amount = Uint128::new(1000000000u128);
+    let max_offer = Uint128::new(1100000000u128);
+
     let mut deps = mock_dependencies(&[Coin {
         denom: "uusd".to_string(),
-        amount: asset_0_amount,
+        amount: collateral_pool_amount + max_offer,
     }]);
-
     deps.querier.with_token_balances(&[
         (
-            &String::from("asset0000"),
-            &[(&String::from(MOCK_CONTRACT_ADDR), &asset_1_amount)],
+            &String::from("liquidity0000"),
+            &[(&String::from(MOCK_CONTRACT_ADDR), &total_share)],
         ),
         (
-            &String::from("liquidity0000"),
-            &[(&String::from(MOCK_CONTRACT_ADDR), &total_share_amount)],
+            &String::from("asset0000"),
+            &[(&String::from(MOCK_CONTRACT_ADDR), &asset_pool_amount)],
         ),
     ]);
 
@@ -1110,142 +1266,2048 @@ fn test_query_share() {
         init_params: Some(to_binary(&StablePoolParams { amp: 100 }).unwrap()),
     };
 
-    let env = mock_env();
+    let env = mock_env_with_block_time(100);
     let info = mock_info("addr0000", &[]);
-    // We can just call .unwrap() to assert this was a success
-    let _res = instantiate(deps.as_mut(), env, info, msg).unwrap();
-
-    // Store the liquidity token
+    instantiate(deps.as_mut(), env, info, msg).unwrap();
     store_liquidity_token(deps.as_mut(), 1, "liquidity0000".to_string());
+    store_reserves(deps.as_mut(), [collateral_pool_amount, asset_pool_amount]);
+
+    // 1003009027 = 1000000000 / 0.997 is the ask amount before the commission deduction
+    let before_commission_deduction = Uint128::new(1003009027u128);
+    let expected_offer_amount = Uint128::new(
+        calc_offer_amount(
+            collateral_pool_amount.u128(),
+            asset_pool_amount.u128(),
+            before_commission_deduction.u128(),
+            100 * AMP_PRECISION,
+        )
+        .unwrap(),
+    );
+    let expected_commission_amount = before_commission_deduction.multiply_ratio(3u128, 1000u128); // 0.3%
+    let expected_maker_fee_amount = expected_commission_amount.multiply_ratio(166u128, 1000u128);
 
-    let res = query_share(deps.as_ref(), Uint128::new(250)).unwrap();
-
-    assert_eq!(res[0].amount, Uint128::new(125));
-    assert_eq!(res[1].amount, Uint128::new(500));
-}
-
-#[test]
-fn test_accumulate_prices() {
-    struct Case {
-        block_time: u64,
-        block_time_last: u64,
-        last0: u128,
-        last1: u128,
-        x_amount: u128,
-        y_amount: u128,
-    }
+    let ask_asset = Asset {
+        info: AssetInfo::Token {
+            contract_addr: Addr::unchecked("asset0000"),
+        },
+        amount: ask_amount,
+    };
 
-    struct Result {
-        block_time_last: u64,
-        cumulative_price_x: u128,
-        cumulative_price_y: u128,
-        is_some: bool,
-    }
+    // The required offer amount exceeds max_offer
+    let msg = ExecuteMsg::SwapExactOut {
+        ask_asset: ask_asset.clone(),
+        max_offer: expected_offer_amount - Uint128::new(1u128),
+        to: None,
+        deadline: None,
+    };
+    let env = mock_env_with_block_time(1000);
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: expected_offer_amount - Uint128::new(1u128),
+        }],
+    );
+    deps.querier.with_balance(&[(
+        &String::from(MOCK_CONTRACT_ADDR),
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: collateral_pool_amount + expected_offer_amount - Uint128::new(1u128),
+        }],
+    )]);
+    let res = execute(deps.as_mut(), env, info, msg).unwrap_err();
+    assert_eq!(res, ContractError::MaxOfferAssertion {});
 
-    let price_precision = 10u128.pow(TWAP_PRECISION.into());
+    // Buy exactly 1000000000 asset0000 for uusd, the excess uusd is sent back
+    deps.querier.with_balance(&[(
+        &String::from(MOCK_CONTRACT_ADDR),
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: collateral_pool_amount + max_offer,
+        }],
+    )]);
+    let msg = ExecuteMsg::SwapExactOut {
+        ask_asset: ask_asset.clone(),
+        max_offer,
+        to: None,
+        deadline: None,
+    };
+    let env = mock_env_with_block_time(1000);
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: max_offer,
+        }],
+    );
+    let res = execute(deps.as_mut(), env, info, msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::new(WasmMsg::Execute {
+                contract_addr: String::from("asset0000"),
+                msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                    recipient: String::from("addr0000"),
+                    amount: ask_amount,
+                })
+                .unwrap(),
+                funds: vec![],
+            }),
+            SubMsg::new(BankMsg::Send {
+                to_address: String::from("addr0000"),
+                amount: vec![Coin {
+                    denom: "uusd".to_string(),
+                    amount: max_offer - expected_offer_amount,
+                }],
+            }),
+            SubMsg::new(WasmMsg::Execute {
+                contract_addr: String::from("asset0000"),
+                msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                    recipient: String::from("fee_address"),
+                    amount: expected_maker_fee_amount,
+                })
+                .unwrap(),
+                funds: vec![],
+            }),
+        ]
+    );
+    assert_eq!(
+        res.attributes[5],
+        attr("offer_amount", expected_offer_amount.to_string())
+    );
+    assert_eq!(
+        res.attributes[10],
+        attr("commission_amount", expected_commission_amount.to_string())
+    );
 
-    let test_cases: Vec<(Case, Result)> = vec![
+    // Buy exactly 1000000000 uusd for asset0000 sent via the hook
+    let expected_offer_amount = Uint128::new(
+        calc_offer_amount(
+            asset_pool_amount.u128(),
+            collateral_pool_amount.u128(),
+            before_commission_deduction.u128(),
+            100 * AMP_PRECISION,
+        )
+        .unwrap(),
+    );
+    deps.querier.with_balance(&[(
+        &String::from(MOCK_CONTRACT_ADDR),
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: collateral_pool_amount,
+        }],
+    )]);
+    deps.querier.with_token_balances(&[
         (
-            Case {
-                block_time: 1000,
-                block_time_last: 0,
-                last0: 0,
-                last1: 0,
-                x_amount: 250_000000,
-                y_amount: 500_000000,
-            },
-            Result {
-                block_time_last: 1000,
-                cumulative_price_x: 1008,
-                cumulative_price_y: 991,
-                is_some: true,
-            },
+            &String::from("liquidity0000"),
+            &[(&String::from(MOCK_CONTRACT_ADDR), &total_share)],
         ),
-        // Same block height, no changes
         (
-            Case {
-                block_time: 1000,
-                block_time_last: 1000,
-                last0: 1 * price_precision,
-                last1: 2 * price_precision,
-                x_amount: 250_000000,
-                y_amount: 500_000000,
-            },
-            Result {
-                block_time_last: 1000,
-                cumulative_price_x: 1,
-                cumulative_price_y: 2,
-                is_some: false,
+            &String::from("asset0000"),
+            &[(
+                &String::from(MOCK_CONTRACT_ADDR),
+                &(asset_pool_amount + max_offer),
+            )],
+        ),
+    ]);
+    store_reserves(deps.as_mut(), [collateral_pool_amount, asset_pool_amount]);
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: String::from("addr0000"),
+        amount: max_offer,
+        msg: to_binary(&Cw20HookMsg::SwapExactOut {
+            ask_asset: Asset {
+                info: AssetInfo::NativeToken {
+                    denom: "uusd".to_string(),
+                },
+                amount: ask_amount,
             },
+            to: Some(String::from("addr0001")),
+            deadline: None,
+        })
+        .unwrap(),
+    });
+    let env = mock_env_with_block_time(1000);
+    let info = mock_info("asset0000", &[]);
+    let res = execute(deps.as_mut(), env, info, msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::new(BankMsg::Send {
+                to_address: String::from("addr0001"),
+                amount: vec![Coin {
+                    denom: "uusd".to_string(),
+                    amount: ask_amount,
+                }],
+            }),
+            SubMsg::new(WasmMsg::Execute {
+                contract_addr: String::from("asset0000"),
+                msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                    recipient: String::from("addr0000"),
+                    amount: max_offer - expected_offer_amount,
+                })
+                .unwrap(),
+                funds: vec![],
+            }),
+            SubMsg::new(BankMsg::Send {
+                to_address: String::from("fee_address"),
+                amount: vec![Coin {
+                    denom: "uusd".to_string(),
+                    amount: expected_maker_fee_amount,
+                }],
+            }),
+        ]
+    );
+}
+
+#[test]
+fn execution_guards() {
+    let total_share = Uint128::new(20000000000u128);
+    let asset_pool_amount = Uint128::new(20000000000u128);
+    let collateral_pool_amount = Uint128::new(20000000000u128);
+    let offer_amount = Uint128::new(1000000000u128);
+
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: collateral_pool_amount,
+    }]);
+    deps.querier.with_token_balances(&[
+        (
+            &String::from("liquidity0000"),
+            &[(&String::from(MOCK_CONTRACT_ADDR), &total_share)],
         ),
         (
-            Case {
-                block_time: 1500,
-                block_time_last: 1000,
-                last0: 500 * price_precision,
-                last1: 2000 * price_precision,
-                x_amount: 250_000000,
-                y_amount: 500_000000,
+            &String::from("asset0000"),
+            &[(&String::from(MOCK_CONTRACT_ADDR), &asset_pool_amount)],
+        ),
+    ]);
+
+    let msg = InstantiateMsg {
+        asset_infos: [
+            AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
             },
-            Result {
-                block_time_last: 1500,
-                cumulative_price_x: 1004,
-                cumulative_price_y: 2495,
-                is_some: true,
+            AssetInfo::Token {
+                contract_addr: Addr::unchecked("asset0000"),
             },
-        ),
-    ];
+        ],
+        token_code_id: 10u64,
+        factory_addr: String::from("factory"),
+        init_params: Some(to_binary(&StablePoolParams { amp: 100 }).unwrap()),
+    };
 
-    for test_case in test_cases {
-        let (case, result) = test_case;
+    let env = mock_env_with_block_time(100);
+    let info = mock_info("addr0000", &[]);
+    instantiate(deps.as_mut(), env, info, msg).unwrap();
+    store_liquidity_token(deps.as_mut(), 1, "liquidity0000".to_string());
+    store_reserves(deps.as_mut(), [collateral_pool_amount, asset_pool_amount]);
 
-        let env = mock_env_with_block_time(case.block_time);
-        let config = accumulate_prices(
-            env.clone(),
-            &Config {
-                pair_info: PairInfo {
-                    asset_infos: [
-                        AssetInfo::NativeToken {
-                            denom: "uusd".to_string(),
-                        },
-                        AssetInfo::Token {
-                            contract_addr: Addr::unchecked("asset0000"),
-                        },
-                    ],
-                    contract_addr: Addr::unchecked("pair"),
-                    liquidity_token: Addr::unchecked("lp_token"),
-                    pair_type: PairType::Stable {},
-                },
-                factory_addr: Addr::unchecked("factory"),
-                block_time_last: case.block_time_last,
-                price0_cumulative_last: Uint128::new(case.last0),
-                price1_cumulative_last: Uint128::new(case.last1),
-                init_amp: 100 * AMP_PRECISION,
-                init_amp_time: env.block.time.seconds(),
-                next_amp: 100 * AMP_PRECISION,
-                next_amp_time: env.block.time.seconds(),
-            },
-            Uint128::new(case.x_amount),
-            6,
-            Uint128::new(case.y_amount),
-            6,
-        )
-        .unwrap();
+    let offer_asset = Asset {
+        info: AssetInfo::NativeToken {
+            denom: "uusd".to_string(),
+        },
+        amount: offer_amount,
+    };
+    let simulation_res = query_simulation(
+        deps.as_ref(),
+        mock_env_with_block_time(1000),
+        offer_asset.clone(),
+    )
+    .unwrap();
 
-        assert_eq!(result.is_some, config.is_some());
+    deps.querier.with_balance(&[(
+        &String::from(MOCK_CONTRACT_ADDR),
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: collateral_pool_amount + offer_amount,
+        }],
+    )]);
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: offer_amount,
+        }],
+    );
+    let swap_msg = |min_return: Option<Uint128>, deadline: Option<u64>| ExecuteMsg::Swap {
+        offer_asset: offer_asset.clone(),
+        belief_price: None,
+        max_spread: Some(Decimal::percent(50)),
+        to: None,
+        min_return,
+        deadline,
+    };
 
-        if let Some(config) = config {
-            assert_eq!(config.2, result.block_time_last);
-            assert_eq!(
-                config.0 / Uint128::from(price_precision),
-                Uint128::new(result.cumulative_price_x)
-            );
-            assert_eq!(
-                config.1 / Uint128::from(price_precision),
-                Uint128::new(result.cumulative_price_y)
-            );
-        }
+    // The deadline is in the past
+    let res = execute(
+        deps.as_mut(),
+        mock_env_with_block_time(1000),
+        info.clone(),
+        swap_msg(None, Some(999)),
+    )
+    .unwrap_err();
+    assert_eq!(res, ContractError::DeadlineExpired {});
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env_with_block_time(1000),
+        info.clone(),
+        swap_msg(
+            Some(simulation_res.return_amount + Uint128::new(1u128)),
+            None,
+        ),
+    )
+    .unwrap_err();
+    assert_eq!(res, ContractError::MinReturnAssertion {});
+
+    execute(
+        deps.as_mut(),
+        mock_env_with_block_time(1000),
+        info.clone(),
+        swap_msg(Some(simulation_res.return_amount), Some(1000)),
+    )
+    .unwrap();
+
+    store_reserves(deps.as_mut(), [collateral_pool_amount, asset_pool_amount]);
+
+    // Provide liquidity in the pool ratio
+    let provide_msg =
+        |min_lp_to_receive: Option<Uint128>, deadline: Option<u64>| ExecuteMsg::ProvideLiquidity {
+            assets: [
+                Asset {
+                    info: AssetInfo::NativeToken {
+                        denom: "uusd".to_string(),
+                    },
+                    amount: offer_amount,
+                },
+                Asset {
+                    info: AssetInfo::Token {
+                        contract_addr: Addr::unchecked("asset0000"),
+                    },
+                    amount: offer_amount,
+                },
+            ],
+            slippage_tolerance: None,
+            auto_stake: None,
+            receiver: None,
+            min_lp_to_receive,
+            deadline,
+        };
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env_with_block_time(1000),
+        info.clone(),
+        provide_msg(None, Some(999)),
+    )
+    .unwrap_err();
+    assert_eq!(res, ContractError::DeadlineExpired {});
+
+    // The pool is balanced, so the deposit doubling the pool size mints 1000000000 LP tokens
+    let res = execute(
+        deps.as_mut(),
+        mock_env_with_block_time(1000),
+        info.clone(),
+        provide_msg(Some(Uint128::new(1000000001u128)), None),
+    )
+    .unwrap_err();
+    assert_eq!(res, ContractError::MinLpToReceiveAssertion {});
+
+    execute(
+        deps.as_mut(),
+        mock_env_with_block_time(1000),
+        info,
+        provide_msg(Some(Uint128::new(1000000000u128)), None),
+    )
+    .unwrap();
+
+    // Withdraw 1000000000 LP tokens for 1000000000 uusd and 1000000000 asset0000
+    deps.querier.with_balance(&[(
+        &String::from(MOCK_CONTRACT_ADDR),
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: collateral_pool_amount,
+        }],
+    )]);
+    store_reserves(deps.as_mut(), [collateral_pool_amount, asset_pool_amount]);
+    let withdraw_msg = |min_assets_out: Option<Vec<Asset>>, deadline: Option<u64>| {
+        ExecuteMsg::Receive(Cw20ReceiveMsg {
+            sender: String::from("addr0000"),
+            msg: to_binary(&Cw20HookMsg::WithdrawLiquidity {
+                min_assets_out,
+                deadline,
+            })
+            .unwrap(),
+            amount: Uint128::new(1000000000u128),
+        })
+    };
+    let asset_out = |amount: u128| Asset {
+        info: AssetInfo::Token {
+            contract_addr: Addr::unchecked("asset0000"),
+        },
+        amount: Uint128::new(amount),
+    };
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env_with_block_time(1000),
+        mock_info("liquidity0000", &[]),
+        withdraw_msg(None, Some(999)),
+    )
+    .unwrap_err();
+    assert_eq!(res, ContractError::DeadlineExpired {});
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env_with_block_time(1000),
+        mock_info("liquidity0000", &[]),
+        withdraw_msg(Some(vec![asset_out(1000000001u128)]), None),
+    )
+    .unwrap_err();
+    assert_eq!(res, ContractError::MinAssetsOutAssertion {});
+
+    execute(
+        deps.as_mut(),
+        mock_env_with_block_time(1000),
+        mock_info("liquidity0000", &[]),
+        withdraw_msg(Some(vec![asset_out(1000000000u128)]), Some(1000)),
+    )
+    .unwrap();
+}
+
+#[test]
+fn migrate_from_v100() {
+    let asset_pool_amount = Uint128::new(30000000000u128);
+    let collateral_pool_amount = Uint128::new(20000000000u128);
+
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: collateral_pool_amount,
+    }]);
+    deps.querier.with_token_balances(&[(
+        &String::from("asset0000"),
+        &[(&String::from(MOCK_CONTRACT_ADDR), &asset_pool_amount)],
+    )]);
+
+    let msg = InstantiateMsg {
+        asset_infos: [
+            AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            AssetInfo::Token {
+                contract_addr: Addr::unchecked("asset0000"),
+            },
+        ],
+        token_code_id: 10u64,
+        factory_addr: String::from("factory"),
+        init_params: Some(to_binary(&StablePoolParams { amp: 100 }).unwrap()),
+    };
+    instantiate(deps.as_mut(), mock_env(), mock_info("addr0000", &[]), msg).unwrap();
+
+    // The current version cannot be migrated again
+    let res = migrate(deps.as_mut(), mock_env(), MigrateMsg {}).unwrap_err();
+    assert_eq!(res, ContractError::MigrationError {});
+
+    // A v1.0.0 pair doesn't track its reserves and doesn't cache the factory parameters
+    set_contract_version(deps.as_mut().storage, "astroport-pair-stable", "1.0.0").unwrap();
+    RESERVES.remove(deps.as_mut().storage);
+    FACTORY_PARAMS.remove(deps.as_mut().storage);
+
+    let res = migrate(deps.as_mut(), mock_env(), MigrateMsg {}).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("previous_contract_name", "astroport-pair-stable"),
+            attr("previous_contract_version", "1.0.0"),
+            attr("new_contract_name", "astroport-pair-stable"),
+            attr("new_contract_version", env!("CARGO_PKG_VERSION")),
+        ]
+    );
+    assert_eq!(
+        RESERVES.load(deps.as_ref().storage).unwrap(),
+        [collateral_pool_amount, asset_pool_amount]
+    );
+    assert!(FACTORY_PARAMS.load(deps.as_ref().storage).is_ok());
+    assert_eq!(
+        get_contract_version(deps.as_ref().storage).unwrap().version,
+        env!("CARGO_PKG_VERSION")
+    );
+}
+
+#[test]
+fn sync_and_skim() {
+    let total_share = Uint128::new(20000000000u128);
+    let asset_pool_amount = Uint128::new(30000000000u128);
+    let collateral_pool_amount = Uint128::new(20000000000u128);
+    let donation_amount = Uint128::new(1000000000u128);
+
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: collateral_pool_amount,
+    }]);
+    deps.querier.with_token_balances(&[
+        (
+            &String::from("liquidity0000"),
+            &[(&String::from(MOCK_CONTRACT_ADDR), &total_share)],
+        ),
+        (
+            &String::from("asset0000"),
+            &[(&String::from(MOCK_CONTRACT_ADDR), &asset_pool_amount)],
+        ),
+    ]);
+
+    let msg = InstantiateMsg {
+        asset_infos: [
+            AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            AssetInfo::Token {
+                contract_addr: Addr::unchecked("asset0000"),
+            },
+        ],
+        token_code_id: 10u64,
+        factory_addr: String::from("factory"),
+        init_params: Some(to_binary(&StablePoolParams { amp: 100 }).unwrap()),
+    };
+
+    let env = mock_env();
+    let info = mock_info("addr0000", &[]);
+    instantiate(deps.as_mut(), env, info, msg).unwrap();
+    store_liquidity_token(deps.as_mut(), 1, "liquidity0000".to_string());
+
+    // The reserves are tracked from zero, so the balances of the pair are not counted
+    let res: PoolResponse = query_pool(deps.as_ref()).unwrap();
+    assert_eq!(res.assets[0].amount, Uint128::zero());
+    assert_eq!(res.assets[1].amount, Uint128::zero());
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        ExecuteMsg::Sync {},
+    )
+    .unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "sync"),
+            attr("reserves", "20000000000uusd, 30000000000asset0000"),
+        ]
+    );
+
+    let offer_asset = Asset {
+        info: AssetInfo::NativeToken {
+            denom: "uusd".to_string(),
+        },
+        amount: Uint128::new(1000000000u128),
+    };
+    let simulation_res = query_simulation(deps.as_ref(), mock_env(), offer_asset.clone()).unwrap();
+
+    // A direct transfer to the pair doesn't move the price
+    deps.querier.with_token_balances(&[
+        (
+            &String::from("liquidity0000"),
+            &[(&String::from(MOCK_CONTRACT_ADDR), &total_share)],
+        ),
+        (
+            &String::from("asset0000"),
+            &[(
+                &String::from(MOCK_CONTRACT_ADDR),
+                &(asset_pool_amount + donation_amount),
+            )],
+        ),
+    ]);
+    assert_eq!(
+        query_simulation(deps.as_ref(), mock_env(), offer_asset.clone()).unwrap(),
+        simulation_res
+    );
+    let res: PoolResponse = query_pool(deps.as_ref()).unwrap();
+    assert_eq!(res.assets[1].amount, asset_pool_amount);
+
+    // The excess balance is sent to the recipient
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        ExecuteMsg::Skim {
+            to: Some(String::from("addr0001")),
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(WasmMsg::Execute {
+            contract_addr: String::from("asset0000"),
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: String::from("addr0001"),
+                amount: donation_amount,
+            })
+            .unwrap(),
+            funds: vec![],
+        })]
+    );
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "skim"),
+            attr("receiver", "addr0001"),
+            attr("assets", "0uusd, 1000000000asset0000"),
+        ]
+    );
+
+    // Syncing adds the excess balance to the pool instead
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        ExecuteMsg::Sync {},
+    )
+    .unwrap();
+    let res: PoolResponse = query_pool(deps.as_ref()).unwrap();
+    assert_eq!(res.assets[0].amount, collateral_pool_amount);
+    assert_eq!(res.assets[1].amount, asset_pool_amount + donation_amount);
+    assert_ne!(
+        query_simulation(deps.as_ref(), mock_env(), offer_asset).unwrap(),
+        simulation_res
+    );
+
+    // Nothing is left to skim
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        ExecuteMsg::Skim { to: None },
+    )
+    .unwrap();
+    assert!(res.messages.is_empty());
+}
+
+#[test]
+fn observe_cumulative_prices() {
+    let total_share = Uint128::new(20000000000u128);
+    let asset_pool_amount = Uint128::new(30000000000u128);
+    let collateral_pool_amount = Uint128::new(20000000000u128);
+
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: collateral_pool_amount,
+    }]);
+    deps.querier.with_token_balances(&[
+        (
+            &String::from("liquidity0000"),
+            &[(&String::from(MOCK_CONTRACT_ADDR), &total_share)],
+        ),
+        (
+            &String::from("asset0000"),
+            &[(&String::from(MOCK_CONTRACT_ADDR), &asset_pool_amount)],
+        ),
+    ]);
+
+    let msg = InstantiateMsg {
+        asset_infos: [
+            AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            AssetInfo::Token {
+                contract_addr: Addr::unchecked("asset0000"),
+            },
+        ],
+        token_code_id: 10u64,
+        factory_addr: String::from("factory"),
+        init_params: Some(to_binary(&StablePoolParams { amp: 100 }).unwrap()),
+    };
+
+    instantiate(
+        deps.as_mut(),
+        mock_env_with_block_time(1000),
+        mock_info("addr0000", &[]),
+        msg,
+    )
+    .unwrap();
+    store_liquidity_token(deps.as_mut(), 1, "liquidity0000".to_string());
+
+    // Observations are written at 1000 (with empty reserves) and at 2000 (with the synced reserves)
+    for block_time in [1000, 2000] {
+        execute(
+            deps.as_mut(),
+            mock_env_with_block_time(block_time),
+            mock_info("addr0000", &[]),
+            ExecuteMsg::Sync {},
+        )
+        .unwrap();
     }
+
+    let cumulative_prices = |block_time: u64| -> (StdUint256, StdUint256) {
+        let res: CumulativePricesResponse = from_binary(
+            &query(
+                deps.as_ref(),
+                mock_env_with_block_time(block_time),
+                QueryMsg::CumulativePrices {},
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        (
+            res.price0_cumulative_last.into(),
+            res.price1_cumulative_last.into(),
+        )
+    };
+    let (price0_at_2000, price1_at_2000) = cumulative_prices(2000);
+    assert!(!price0_at_2000.is_zero());
+
+    let env = mock_env_with_block_time(3000);
+    let res: ObserveResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            env.clone(),
+            QueryMsg::Observe {
+                seconds_ago: vec![0, 500, 1500, 2000],
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    let prices: Vec<(u64, StdUint256, StdUint256)> = res
+        .observations
+        .into_iter()
+        .map(|o| (o.timestamp, o.price0_cumulative, o.price1_cumulative))
+        .collect();
+    assert_eq!(
+        prices,
+        vec![
+            // Extrapolated using the current reserves
+            (3000, cumulative_prices(3000).0, cumulative_prices(3000).1),
+            (2500, cumulative_prices(2500).0, cumulative_prices(2500).1),
+            // Interpolated between the observations
+            (
+                1500,
+                price0_at_2000.multiply_ratio(1u64, 2u64),
+                price1_at_2000.multiply_ratio(1u64, 2u64)
+            ),
+            (1000, StdUint256::zero(), StdUint256::zero()),
+        ]
+    );
+
+    let err = query(
+        deps.as_ref(),
+        env.clone(),
+        QueryMsg::Observe {
+            seconds_ago: vec![2001],
+        },
+    )
+    .unwrap_err();
+    assert_eq!(
+        err,
+        StdError::generic_err("The observation time is before the oldest stored observation")
+    );
+
+    // The factory owner can reduce the number of stored observations
+    let err = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("owner", &[]),
+        ExecuteMsg::UpdateConfig {
+            params: to_binary(&StablePoolUpdateParams::SetObservationsSize { size: 0 }).unwrap(),
+        },
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::InvalidObservationsSize {});
+
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("owner", &[]),
+        ExecuteMsg::UpdateConfig {
+            params: to_binary(&StablePoolUpdateParams::SetObservationsSize { size: 1 }).unwrap(),
+        },
+    )
+    .unwrap();
+
+    // Only the latest observation is kept
+    let err = query(
+        deps.as_ref(),
+        env,
+        QueryMsg::Observe {
+            seconds_ago: vec![1500],
+        },
+    )
+    .unwrap_err();
+    assert_eq!(
+        err,
+        StdError::generic_err("The observation time is before the oldest stored observation")
+    );
+}
+
+#[test]
+fn cached_factory_params() {
+    let total_share = Uint128::new(30000000000u128);
+    let asset_pool_amount = Uint128::new(20000000000u128);
+    let collateral_pool_amount = Uint128::new(30000000000u128);
+    let offer_amount = Uint128::new(1500000000u128);
+
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: collateral_pool_amount + offer_amount,
+    }]);
+    deps.querier.with_token_balances(&[
+        (
+            &String::from("liquidity0000"),
+            &[(&String::from(MOCK_CONTRACT_ADDR), &total_share)],
+        ),
+        (
+            &String::from("asset0000"),
+            &[(&String::from(MOCK_CONTRACT_ADDR), &asset_pool_amount)],
+        ),
+    ]);
+
+    let msg = InstantiateMsg {
+        asset_infos: [
+            AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            AssetInfo::Token {
+                contract_addr: Addr::unchecked("asset0000"),
+            },
+        ],
+        token_code_id: 10u64,
+        factory_addr: String::from("factory"),
+        init_params: Some(to_binary(&StablePoolParams { amp: 100 }).unwrap()),
+    };
+
+    let env = mock_env();
+    let info = mock_info("addr0000", &[]);
+    instantiate(deps.as_mut(), env, info, msg).unwrap();
+    store_liquidity_token(deps.as_mut(), 1, "liquidity0000".to_string());
+    store_reserves(deps.as_mut(), [collateral_pool_amount, asset_pool_amount]);
+
+    // The fee and Generator information is cached at instantiation
+    let expected_params = FactoryParams {
+        fee_info: FeeInfo {
+            fee_address: Some(Addr::unchecked("fee_address")),
+            total_fee_rate: Decimal::from_ratio(3u128, 1000u128),
+            maker_fee_rate: Decimal::from_ratio(166u128, 1000u128),
+            maker_fee_as_lp: false,
+        },
+        generator_address: Some(Addr::unchecked("generator")),
+        pair_status: PairStatus::Active,
+    };
+    assert_eq!(
+        FACTORY_PARAMS.load(deps.as_ref().storage).unwrap(),
+        expected_params
+    );
+
+    // Benchmark a swap. It used to query the factory for the fee info, but now the fees are read
+    // from the cache, so the swap only queries the precisions of the two assets
+    let (queries_before, factory_queries_before) = deps.querier.query_counts();
+    let msg = ExecuteMsg::Swap {
+        offer_asset: Asset {
+            info: AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            amount: offer_amount,
+        },
+        belief_price: None,
+        max_spread: Some(Decimal::percent(50)),
+        to: None,
+        min_return: None,
+        deadline: None,
+    };
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: offer_amount,
+        }],
+    );
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    let (queries_after, factory_queries_after) = deps.querier.query_counts();
+    assert_eq!(factory_queries_after, factory_queries_before);
+    assert_eq!(queries_after, queries_before + 2);
+
+    // Anyone can refresh the cached parameters
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("anyone", &[]),
+        ExecuteMsg::RefreshFactoryParams {},
+    )
+    .unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "refresh_factory_params"),
+            attr("total_fee_rate", "0.003"),
+            attr("maker_fee_rate", "0.166"),
+            attr("fee_address", "fee_address"),
+            attr("generator_address", "generator"),
+            attr("pair_status", "active"),
+        ]
+    );
+    assert_eq!(deps.querier.query_counts().1, factory_queries_after + 3);
+    assert_eq!(
+        FACTORY_PARAMS.load(deps.as_ref().storage).unwrap(),
+        expected_params
+    );
+}
+
+#[test]
+fn protocol_fee_accrual() {
+    let total_share = Uint128::new(30000000000u128);
+    let asset_pool_amount = Uint128::new(20000000000u128);
+    let collateral_pool_amount = Uint128::new(30000000000u128);
+    let offer_amount = Uint128::new(1500000000u128);
+
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: collateral_pool_amount + offer_amount,
+    }]);
+    deps.querier.with_token_balances(&[
+        (
+            &String::from("liquidity0000"),
+            &[(&String::from(MOCK_CONTRACT_ADDR), &total_share)],
+        ),
+        (
+            &String::from("asset0000"),
+            &[(&String::from(MOCK_CONTRACT_ADDR), &asset_pool_amount)],
+        ),
+    ]);
+
+    let msg = InstantiateMsg {
+        asset_infos: [
+            AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            AssetInfo::Token {
+                contract_addr: Addr::unchecked("asset0000"),
+            },
+        ],
+        token_code_id: 10u64,
+        factory_addr: String::from("factory"),
+        init_params: Some(to_binary(&StablePoolParams { amp: 100 }).unwrap()),
+    };
+
+    let env = mock_env();
+    let info = mock_info("addr0000", &[]);
+    instantiate(deps.as_mut(), env, info, msg).unwrap();
+    store_liquidity_token(deps.as_mut(), 1, "liquidity0000".to_string());
+    store_reserves(deps.as_mut(), [collateral_pool_amount, asset_pool_amount]);
+
+    // Only the factory owner can enable the accrual
+    let msg = ExecuteMsg::SetProtocolFeeAccrual { enabled: true };
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        msg.clone(),
+    );
+    assert_eq!(res.unwrap_err(), ContractError::Unauthorized {});
+    let res = execute(deps.as_mut(), mock_env(), mock_info("owner", &[]), msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "set_protocol_fee_accrual"),
+            attr("enabled", "true"),
+        ]
+    );
+
+    // The Maker fee stays in the pair instead of being sent to the fee address
+    let msg = ExecuteMsg::Swap {
+        offer_asset: Asset {
+            info: AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            amount: offer_amount,
+        },
+        belief_price: None,
+        max_spread: Some(Decimal::percent(50)),
+        to: None,
+        min_return: None,
+        deadline: None,
+    };
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: offer_amount,
+        }],
+    );
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(res.messages.len(), 1);
+    let attr_amount = |key: &str| -> Uint128 {
+        let value = &res.attributes.iter().find(|a| a.key == key).unwrap().value;
+        Uint128::new(value.parse().unwrap())
+    };
+    let return_amount = attr_amount("return_amount");
+    let maker_fee_amount = attr_amount("maker_fee_amount");
+    assert!(!maker_fee_amount.is_zero());
+
+    let protocol_fees = query_protocol_fees(deps.as_ref()).unwrap();
+    assert!(protocol_fees.accrual_enabled);
+    assert_eq!(protocol_fees.assets[0].amount, Uint128::zero());
+    assert_eq!(protocol_fees.assets[1].amount, maker_fee_amount);
+
+    // The accrued fees are not a part of the pool
+    let asset_balance = asset_pool_amount - return_amount;
+    deps.querier.with_token_balances(&[
+        (
+            &String::from("liquidity0000"),
+            &[(&String::from(MOCK_CONTRACT_ADDR), &total_share)],
+        ),
+        (
+            &String::from("asset0000"),
+            &[(&String::from(MOCK_CONTRACT_ADDR), &asset_balance)],
+        ),
+    ]);
+    let res: PoolResponse = query_pool(deps.as_ref()).unwrap();
+    assert_eq!(res.assets[1].amount, asset_balance - maker_fee_amount);
+
+    // Skimming and syncing don't touch the accrued fees
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        ExecuteMsg::Skim { to: None },
+    )
+    .unwrap();
+    assert!(res.messages.is_empty());
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        ExecuteMsg::Sync {},
+    )
+    .unwrap();
+    let res: PoolResponse = query_pool(deps.as_ref()).unwrap();
+    assert_eq!(res.assets[1].amount, asset_balance - maker_fee_amount);
+
+    // Only the fee address can claim the accrued fees
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        ExecuteMsg::ClaimProtocolFees {},
+    );
+    assert_eq!(res.unwrap_err(), ContractError::Unauthorized {});
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("fee_address", &[]),
+        ExecuteMsg::ClaimProtocolFees {},
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(WasmMsg::Execute {
+            contract_addr: String::from("asset0000"),
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: String::from("fee_address"),
+                amount: maker_fee_amount,
+            })
+            .unwrap(),
+            funds: vec![],
+        })]
+    );
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "claim_protocol_fees"),
+            attr("receiver", "fee_address"),
+            attr(
+                "assets",
+                format!("0uusd, {}asset0000", maker_fee_amount).as_str()
+            ),
+        ]
+    );
+    let protocol_fees = query_protocol_fees(deps.as_ref()).unwrap();
+    assert_eq!(protocol_fees.assets[1].amount, Uint128::zero());
+}
+
+#[test]
+fn test_max_spread() {
+    assert_max_spread(
+        Some(Decimal::from_ratio(1200u128, 1u128)),
+        Some(Decimal::percent(1)),
+        Uint128::from(1200000000u128),
+        Uint128::from(989999u128),
+        Uint128::zero(),
+    )
+    .unwrap_err();
+
+    assert_max_spread(
+        Some(Decimal::from_ratio(1200u128, 1u128)),
+        Some(Decimal::percent(1)),
+        Uint128::from(1200000000u128),
+        Uint128::from(990000u128),
+        Uint128::zero(),
+    )
+    .unwrap();
+
+    assert_max_spread(
+        None,
+        Some(Decimal::percent(1)),
+        Uint128::zero(),
+        Uint128::from(989999u128),
+        Uint128::from(10001u128),
+    )
+    .unwrap_err();
+
+    assert_max_spread(
+        None,
+        Some(Decimal::percent(1)),
+        Uint128::zero(),
+        Uint128::from(990000u128),
+        Uint128::from(10000u128),
+    )
+    .unwrap();
+}
+
+#[test]
+fn test_deduct() {
+    let mut deps = mock_dependencies(&[]);
+
+    let tax_rate = Decimal::percent(2);
+    let tax_cap = Uint128::from(1_000_000u128);
+    deps.querier.with_tax(
+        Decimal::percent(2),
+        &[(&"uusd".to_string(), &Uint128::from(1000000u128))],
+    );
+
+    let amount = Uint128::new(1000_000_000u128);
+    let expected_after_amount = std::cmp::max(
+        amount.checked_sub(amount * tax_rate).unwrap(),
+        amount.checked_sub(tax_cap).unwrap(),
+    );
+
+    let after_amount = (Asset {
+        info: AssetInfo::NativeToken {
+            denom: "uusd".to_string(),
+        },
+        amount,
+    })
+    .deduct_tax(&deps.as_ref().querier)
+    .unwrap();
+
+    assert_eq!(expected_after_amount, after_amount.amount);
+}
+
+#[test]
+fn test_query_pool() {
+    let total_share_amount = Uint128::from(111u128);
+    let asset_0_amount = Uint128::from(222u128);
+    let asset_1_amount = Uint128::from(333u128);
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: asset_0_amount,
+    }]);
+
+    deps.querier.with_token_balances(&[
+        (
+            &String::from("asset0000"),
+            &[(&String::from(MOCK_CONTRACT_ADDR), &asset_1_amount)],
+        ),
+        (
+            &String::from("liquidity0000"),
+            &[(&String::from(MOCK_CONTRACT_ADDR), &total_share_amount)],
+        ),
+    ]);
+
+    let msg = InstantiateMsg {
+        asset_infos: [
+            AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            AssetInfo::Token {
+                contract_addr: Addr::unchecked("asset0000"),
+            },
+        ],
+        token_code_id: 10u64,
+        factory_addr: String::from("factory"),
+        init_params: Some(to_binary(&StablePoolParams { amp: 100 }).unwrap()),
+    };
+
+    let env = mock_env();
+    let info = mock_info("addr0000", &[]);
+    // We can just call .unwrap() to assert this was a success
+    let _res = instantiate(deps.as_mut(), env, info, msg).unwrap();
+
+    // Store the liquidity token
+    store_liquidity_token(deps.as_mut(), 1, "liquidity0000".to_string());
+
+    // Start tracking the pool balances
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        ExecuteMsg::Sync {},
+    )
+    .unwrap();
+
+    let res: PoolResponse = query_pool(deps.as_ref()).unwrap();
+
+    assert_eq!(
+        res.assets,
+        [
+            Asset {
+                info: AssetInfo::NativeToken {
+                    denom: "uusd".to_string(),
+                },
+                amount: asset_0_amount
+            },
+            Asset {
+                info: AssetInfo::Token {
+                    contract_addr: Addr::unchecked("asset0000"),
+                },
+                amount: asset_1_amount
+            }
+        ]
+    );
+    assert_eq!(res.total_share, total_share_amount);
+}
+
+#[test]
+fn test_query_share() {
+    let total_share_amount = Uint128::from(500u128);
+    let asset_0_amount = Uint128::from(250u128);
+    let asset_1_amount = Uint128::from(1000u128);
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: asset_0_amount,
+    }]);
+
+    deps.querier.with_token_balances(&[
+        (
+            &String::from("asset0000"),
+            &[(&String::from(MOCK_CONTRACT_ADDR), &asset_1_amount)],
+        ),
+        (
+            &String::from("liquidity0000"),
+            &[(&String::from(MOCK_CONTRACT_ADDR), &total_share_amount)],
+        ),
+    ]);
+
+    let msg = InstantiateMsg {
+        asset_infos: [
+            AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            AssetInfo::Token {
+                contract_addr: Addr::unchecked("asset0000"),
+            },
+        ],
+        token_code_id: 10u64,
+        factory_addr: String::from("factory"),
+        init_params: Some(to_binary(&StablePoolParams { amp: 100 }).unwrap()),
+    };
+
+    let env = mock_env();
+    let info = mock_info("addr0000", &[]);
+    // We can just call .unwrap() to assert this was a success
+    let _res = instantiate(deps.as_mut(), env, info, msg).unwrap();
+
+    // Store the liquidity token
+    store_liquidity_token(deps.as_mut(), 1, "liquidity0000".to_string());
+
+    // Start tracking the pool balances
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        ExecuteMsg::Sync {},
+    )
+    .unwrap();
+
+    let res = query_share(deps.as_ref(), Uint128::new(250)).unwrap();
+
+    assert_eq!(res[0].amount, Uint128::new(125));
+    assert_eq!(res[1].amount, Uint128::new(500));
+}
+
+#[test]
+fn test_accumulate_prices() {
+    struct Case {
+        block_time: u64,
+        block_time_last: u64,
+        last0: u128,
+        last1: u128,
+        x_amount: u128,
+        y_amount: u128,
+    }
+
+    struct Result {
+        block_time_last: u64,
+        cumulative_price_x: u128,
+        cumulative_price_y: u128,
+        is_some: bool,
+    }
+
+    let price_precision = 10u128.pow(TWAP_PRECISION.into());
+
+    let test_cases: Vec<(Case, Result)> = vec![
+        (
+            Case {
+                block_time: 1000,
+                block_time_last: 0,
+                last0: 0,
+                last1: 0,
+                x_amount: 250_000000,
+                y_amount: 500_000000,
+            },
+            Result {
+                block_time_last: 1000,
+                cumulative_price_x: 1008,
+                cumulative_price_y: 991,
+                is_some: true,
+            },
+        ),
+        // Same block height, no changes
+        (
+            Case {
+                block_time: 1000,
+                block_time_last: 1000,
+                last0: 1 * price_precision,
+                last1: 2 * price_precision,
+                x_amount: 250_000000,
+                y_amount: 500_000000,
+            },
+            Result {
+                block_time_last: 1000,
+                cumulative_price_x: 1,
+                cumulative_price_y: 2,
+                is_some: false,
+            },
+        ),
+        (
+            Case {
+                block_time: 1500,
+                block_time_last: 1000,
+                last0: 500 * price_precision,
+                last1: 2000 * price_precision,
+                x_amount: 250_000000,
+                y_amount: 500_000000,
+            },
+            Result {
+                block_time_last: 1500,
+                cumulative_price_x: 1004,
+                cumulative_price_y: 2495,
+                is_some: true,
+            },
+        ),
+    ];
+
+    for test_case in test_cases {
+        let (case, result) = test_case;
+
+        let env = mock_env_with_block_time(case.block_time);
+        let config = accumulate_prices(
+            env.clone(),
+            &Config {
+                pair_info: PairInfo {
+                    asset_infos: [
+                        AssetInfo::NativeToken {
+                            denom: "uusd".to_string(),
+                        },
+                        AssetInfo::Token {
+                            contract_addr: Addr::unchecked("asset0000"),
+                        },
+                    ],
+                    contract_addr: Addr::unchecked("pair"),
+                    liquidity_token: Addr::unchecked("lp_token"),
+                    pair_type: PairType::Stable {},
+                },
+                factory_addr: Addr::unchecked("factory"),
+                block_time_last: case.block_time_last,
+                price0_cumulative_last: Uint128::new(case.last0),
+                price1_cumulative_last: Uint128::new(case.last1),
+                init_amp: 100 * AMP_PRECISION,
+                init_amp_time: env.block.time.seconds(),
+                next_amp: 100 * AMP_PRECISION,
+                next_amp_time: env.block.time.seconds(),
+            },
+            Uint128::new(case.x_amount),
+            6,
+            Uint128::new(case.y_amount),
+            6,
+        )
+        .unwrap();
+
+        assert_eq!(result.is_some, config.is_some());
+
+        if let Some(config) = config {
+            assert_eq!(config.2, result.block_time_last);
+            assert_eq!(
+                config.0 / Uint128::from(price_precision),
+                Uint128::new(result.cumulative_price_x)
+            );
+            assert_eq!(
+                config.1 / Uint128::from(price_precision),
+                Uint128::new(result.cumulative_price_y)
+            );
+        }
+    }
+}
+
+#[test]
+fn spot_price_impact_and_depth() {
+    let amp = 100 * AMP_PRECISION;
+
+    // The price is 1 for a balanced pool and the scarce asset is more expensive otherwise
+    assert_eq!(
+        calc_spot_price(1_000_000_000_000, 1_000_000_000_000, amp).unwrap(),
+        Decimal256::one()
+    );
+    let spot_price = calc_spot_price(2_000_000_000_000, 1_000_000_000_000, amp).unwrap();
+    assert!(spot_price < Decimal256::one());
+    // The spot price is close to the price of a small swap
+    let small_swap_price = Decimal256::from_ratio(
+        calc_ask_amount(2_000_000_000_000, 1_000_000_000_000, 1_000_000, amp).unwrap(),
+        1_000_000u128,
+    );
+    assert!(spot_price + Decimal256::permille(1) > small_swap_price);
+    assert!(small_swap_price + Decimal256::permille(1) > spot_price);
+
+    // The price drops by 1% after swapping the depth amount
+    let depth = calc_depth(
+        1_000_000_000_000,
+        1_000_000_000_000,
+        Decimal256::percent(99),
+        amp,
+    )
+    .unwrap();
+    let ask_amount = calc_ask_amount(1_000_000_000_000, 1_000_000_000_000, depth, amp).unwrap();
+    let price_after = calc_spot_price(
+        1_000_000_000_000 + depth,
+        1_000_000_000_000 - ask_amount,
+        amp,
+    )
+    .unwrap();
+    assert!(price_after > Decimal256::from_str("0.9899").unwrap());
+    assert!(price_after < Decimal256::from_str("0.9901").unwrap());
+    // The stableswap curve is much deeper than the constant product one, which needs about 0.5% of the pool
+    assert!(depth > 100_000_000_000);
+
+    let mut deps = mock_dependencies(&[]);
+    deps.querier.with_token_balances(&[(
+        &String::from("asset0000"),
+        &[(&String::from(MOCK_CONTRACT_ADDR), &Uint128::zero())],
+    )]);
+    let uusd = AssetInfo::NativeToken {
+        denom: "uusd".to_string(),
+    };
+    let token = AssetInfo::Token {
+        contract_addr: Addr::unchecked("asset0000"),
+    };
+    let msg = InstantiateMsg {
+        asset_infos: [uusd.clone(), token.clone()],
+        token_code_id: 10u64,
+        factory_addr: String::from("factory"),
+        init_params: Some(to_binary(&StablePoolParams { amp: 100 }).unwrap()),
+    };
+    instantiate(deps.as_mut(), mock_env(), mock_info("addr0000", &[]), msg).unwrap();
+    store_liquidity_token(deps.as_mut(), 1, "liquidity0000".to_string());
+
+    // There is no price in an empty pool
+    let err = query_spot_price(deps.as_ref(), mock_env(), uusd.clone()).unwrap_err();
+    assert_eq!(err, StdError::generic_err("The pool has no liquidity"));
+
+    store_reserves(
+        deps.as_mut(),
+        [
+            Uint128::new(1_000_000_000_000),
+            Uint128::new(1_000_000_000_000),
+        ],
+    );
+    let res = query_spot_price(deps.as_ref(), mock_env(), token.clone()).unwrap();
+    assert_eq!(res.price, Decimal::one());
+
+    let res = query_price_impact(
+        deps.as_ref(),
+        mock_env(),
+        Asset {
+            info: uusd.clone(),
+            amount: Uint128::new(depth),
+        },
+    )
+    .unwrap();
+    assert_eq!(res.spot_price, Decimal::one());
+    assert!(res.price_impact > Decimal::zero());
+    assert!(res.price_impact < Decimal::percent(1));
+    assert_eq!(res.execution_price, Decimal::from_ratio(ask_amount, depth));
+
+    let res = query_depth(deps.as_ref(), mock_env(), uusd, Decimal::percent(1)).unwrap();
+    assert_eq!(res.offer_amount, Uint128::new(depth));
+    // The commission is deducted from the return amount
+    assert_eq!(
+        res.return_amount,
+        Uint128::new(ask_amount) - Uint128::new(ask_amount) * Decimal::permille(3)
+    );
+}
+
+#[test]
+fn simulate_provide_and_withdraw() {
+    let total_share = Uint128::new(2_000_000_000);
+    let pool_amount = Uint128::new(1_000_000_000);
+    let provide_amount = Uint128::new(100_000_000);
+    let withdraw_amount = Uint128::new(500_000_000);
+
+    let mut deps = mock_dependencies(&[]);
+    deps.querier.with_token_balances(&[
+        (
+            &String::from("liquidity0000"),
+            &[(&String::from("addr0000"), &total_share)],
+        ),
+        (
+            &String::from("asset0000"),
+            &[(&String::from(MOCK_CONTRACT_ADDR), &pool_amount)],
+        ),
+    ]);
+    let uusd = AssetInfo::NativeToken {
+        denom: "uusd".to_string(),
+    };
+    let token = AssetInfo::Token {
+        contract_addr: Addr::unchecked("asset0000"),
+    };
+    let msg = InstantiateMsg {
+        asset_infos: [uusd.clone(), token.clone()],
+        token_code_id: 10u64,
+        factory_addr: String::from("factory"),
+        init_params: Some(to_binary(&StablePoolParams { amp: 100 }).unwrap()),
+    };
+    instantiate(deps.as_mut(), mock_env(), mock_info("addr0000", &[]), msg).unwrap();
+    store_liquidity_token(deps.as_mut(), 1, "liquidity0000".to_string());
+    store_reserves(deps.as_mut(), [pool_amount, pool_amount]);
+
+    let assets = [
+        Asset {
+            info: uusd.clone(),
+            amount: provide_amount,
+        },
+        Asset {
+            info: token.clone(),
+            amount: provide_amount,
+        },
+    ];
+
+    // An asset that doesn't belong to the pool is rejected
+    let err = query_simulate_provide(
+        deps.as_ref(),
+        mock_env(),
+        [
+            assets[0].clone(),
+            Asset {
+                info: AssetInfo::NativeToken {
+                    denom: "uluna".to_string(),
+                },
+                amount: provide_amount,
+            },
+        ],
+        None,
+    )
+    .unwrap_err();
+    assert_eq!(
+        err,
+        StdError::generic_err(ContractError::AssetMismatch {}.to_string())
+    );
+
+    // The pool doesn't charge imbalance fees
+    let simulation =
+        query_simulate_provide(deps.as_ref(), mock_env(), assets.clone(), None).unwrap();
+    assert_eq!(simulation.share, total_share * Decimal::percent(10));
+    assert!(simulation.fees.iter().all(|fee| fee.amount.is_zero()));
+
+    let msg = ExecuteMsg::ProvideLiquidity {
+        assets,
+        slippage_tolerance: None,
+        auto_stake: None,
+        receiver: None,
+        min_lp_to_receive: None,
+        deadline: None,
+    };
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: provide_amount,
+        }],
+    );
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert!(res
+        .attributes
+        .contains(&attr("share", simulation.share.to_string())));
+
+    let reserves = RESERVES.load(deps.as_ref().storage).unwrap();
+    let simulation = query_simulate_withdraw(deps.as_ref(), withdraw_amount).unwrap();
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: String::from("addr0000"),
+        msg: to_binary(&Cw20HookMsg::WithdrawLiquidity {
+            min_assets_out: None,
+            deadline: None,
+        })
+        .unwrap(),
+        amount: withdraw_amount,
+    });
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("liquidity0000", &[]),
+        msg,
+    )
+    .unwrap();
+    let reserves_after = RESERVES.load(deps.as_ref().storage).unwrap();
+    assert_eq!(simulation[0].amount, reserves[0] - reserves_after[0]);
+    assert_eq!(simulation[1].amount, reserves[1] - reserves_after[1]);
+}
+
+#[test]
+fn withdraw_liquidity_one_coin_and_imbalanced() {
+    let total_share = Uint128::new(2_000_000_000);
+    let pool_amount = Uint128::new(1_000_000_000);
+    let lp_amount = Uint128::new(100_000_000);
+
+    let mut deps = mock_dependencies(&[]);
+    deps.querier.with_token_balances(&[
+        (
+            &String::from("liquidity0000"),
+            &[(&String::from("addr0000"), &total_share)],
+        ),
+        (
+            &String::from("asset0000"),
+            &[(&String::from(MOCK_CONTRACT_ADDR), &pool_amount)],
+        ),
+    ]);
+    let uusd = AssetInfo::NativeToken {
+        denom: "uusd".to_string(),
+    };
+    let token = AssetInfo::Token {
+        contract_addr: Addr::unchecked("asset0000"),
+    };
+    let msg = InstantiateMsg {
+        asset_infos: [uusd.clone(), token.clone()],
+        token_code_id: 10u64,
+        factory_addr: String::from("factory"),
+        init_params: Some(to_binary(&StablePoolParams { amp: 100 }).unwrap()),
+    };
+    instantiate(deps.as_mut(), mock_env(), mock_info("addr0000", &[]), msg).unwrap();
+    store_liquidity_token(deps.as_mut(), 1, "liquidity0000".to_string());
+    store_reserves(deps.as_mut(), [pool_amount, pool_amount]);
+
+    // Withdrawing in one coin returns less than the proportional value and charges a fee
+    let simulation =
+        query_simulate_withdraw_one_coin(deps.as_ref(), mock_env(), lp_amount, uusd.clone())
+            .unwrap();
+    assert!(
+        simulation.return_amount
+            < pool_amount.multiply_ratio(lp_amount * Uint128::new(2), total_share)
+    );
+    assert!(!simulation.fee_amount.is_zero());
+
+    let withdraw_one_coin = |min_return: Option<Uint128>| {
+        ExecuteMsg::Receive(Cw20ReceiveMsg {
+            sender: String::from("addr0000"),
+            msg: to_binary(&Cw20HookMsg::WithdrawLiquidityOneCoin {
+                ask_asset_info: uusd.clone(),
+                min_return,
+                deadline: None,
+            })
+            .unwrap(),
+            amount: lp_amount,
+        })
+    };
+
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("liquidity0000", &[]),
+        withdraw_one_coin(Some(simulation.return_amount + Uint128::new(1))),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::MinReturnAssertion {});
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("liquidity0000", &[]),
+        withdraw_one_coin(Some(simulation.return_amount)),
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages[0].msg,
+        CosmosMsg::Bank(BankMsg::Send {
+            to_address: String::from("addr0000"),
+            amount: vec![Coin {
+                denom: "uusd".to_string(),
+                amount: simulation.return_amount,
+            }],
+        })
+    );
+    assert_eq!(
+        RESERVES.load(deps.as_ref().storage).unwrap(),
+        [pool_amount - simulation.return_amount, pool_amount]
+    );
+
+    // Withdrawing exact amounts burns only the required LP tokens and returns the rest
+    let assets = vec![Asset {
+        info: token.clone(),
+        amount: Uint128::new(50_000_000),
+    }];
+    let simulation =
+        query_simulate_withdraw_imbalanced(deps.as_ref(), mock_env(), assets.clone()).unwrap();
+    assert!(!simulation.fees[1].amount.is_zero());
+
+    let withdraw_imbalanced = |amount: Uint128| {
+        ExecuteMsg::Receive(Cw20ReceiveMsg {
+            sender: String::from("addr0000"),
+            msg: to_binary(&Cw20HookMsg::WithdrawLiquidityImbalanced {
+                assets: assets.clone(),
+                deadline: None,
+            })
+            .unwrap(),
+            amount,
+        })
+    };
+
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("liquidity0000", &[]),
+        withdraw_imbalanced(simulation.lp_amount - Uint128::new(1)),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::MaxLpToBurnAssertion {});
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("liquidity0000", &[]),
+        withdraw_imbalanced(lp_amount),
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages[1].msg,
+        CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: String::from("liquidity0000"),
+            msg: to_binary(&Cw20ExecuteMsg::Burn {
+                amount: simulation.lp_amount,
+            })
+            .unwrap(),
+            funds: vec![],
+        })
+    );
+    assert_eq!(
+        res.messages[2].msg,
+        CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: String::from("liquidity0000"),
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: String::from("addr0000"),
+                amount: lp_amount - simulation.lp_amount,
+            })
+            .unwrap(),
+            funds: vec![],
+        })
+    );
+}
+
+#[test]
+fn dynamic_fees() {
+    let amp = 100 * AMP_PRECISION;
+    let fee_rate = Decimal::permille(3);
+    let fee_multiplier = Decimal::from_ratio(5u64, 1u64);
+
+    // The fee rate is the base one when the pool stays balanced and grows as the pool diverges
+    let balanced = calc_dynamic_fee_rate(
+        1_000_000_000_000,
+        1_000_000_000_000,
+        1_000_000_000_000,
+        1_000_000_000_000,
+        amp,
+        fee_rate,
+        fee_multiplier,
+    )
+    .unwrap();
+    assert_eq!(balanced, fee_rate);
+    let ask_amount =
+        calc_ask_amount(1_000_000_000_000, 1_000_000_000_000, 900_000_000_000, amp).unwrap();
+    let diverged = calc_dynamic_fee_rate(
+        1_000_000_000_000,
+        1_000_000_000_000,
+        1_900_000_000_000,
+        1_000_000_000_000 - ask_amount,
+        amp,
+        fee_rate,
+        fee_multiplier,
+    )
+    .unwrap();
+    assert!(diverged > fee_rate);
+    // The fee rate stays below the base fee rate times the multiplier
+    assert!(diverged < Decimal::permille(15));
+
+    let mut deps = mock_dependencies(&[]);
+    deps.querier.with_token_balances(&[(
+        &String::from("asset0000"),
+        &[(&String::from(MOCK_CONTRACT_ADDR), &Uint128::zero())],
+    )]);
+    let uusd = AssetInfo::NativeToken {
+        denom: "uusd".to_string(),
+    };
+    let token = AssetInfo::Token {
+        contract_addr: Addr::unchecked("asset0000"),
+    };
+    let msg = InstantiateMsg {
+        asset_infos: [uusd.clone(), token.clone()],
+        token_code_id: 10u64,
+        factory_addr: String::from("factory"),
+        init_params: Some(to_binary(&StablePoolParams { amp: 100 }).unwrap()),
+    };
+    instantiate(deps.as_mut(), mock_env(), mock_info("addr0000", &[]), msg).unwrap();
+    store_liquidity_token(deps.as_mut(), 1, "liquidity0000".to_string());
+    store_reserves(
+        deps.as_mut(),
+        [
+            Uint128::new(1_000_000_000_000),
+            Uint128::new(1_000_000_000_000),
+        ],
+    );
+
+    let offer_asset = Asset {
+        info: uusd.clone(),
+        amount: Uint128::new(900_000_000_000),
+    };
+    let flat = query_simulation(deps.as_ref(), mock_env(), offer_asset.clone()).unwrap();
+    assert_eq!(flat.commission_amount, Uint128::new(ask_amount) * fee_rate);
+
+    let update_fee_multiplier = |fee_multiplier: Option<Decimal>| ExecuteMsg::UpdateConfig {
+        params: to_binary(&StablePoolUpdateParams::SetFeeMultiplier { fee_multiplier }).unwrap(),
+    };
+
+    // Only the factory owner can enable dynamic fees
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        update_fee_multiplier(Some(fee_multiplier)),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("owner", &[]),
+        update_fee_multiplier(Some(Decimal::from_ratio(MAX_FEE_MULTIPLIER + 1, 1u64))),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::IncorrectFeeMultiplier {});
+
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("owner", &[]),
+        update_fee_multiplier(Some(fee_multiplier)),
+    )
+    .unwrap();
+    let config: StablePoolConfig = from_binary(
+        &query_config(deps.as_ref(), mock_env())
+            .unwrap()
+            .params
+            .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(config.fee_multiplier, Some(fee_multiplier));
+
+    // The simulations report the dynamic fee
+    let dynamic = query_simulation(deps.as_ref(), mock_env(), offer_asset).unwrap();
+    assert_eq!(
+        dynamic.commission_amount,
+        Uint128::new(ask_amount) * diverged
+    );
+    assert_eq!(
+        dynamic.return_amount + dynamic.commission_amount,
+        flat.return_amount + flat.commission_amount
+    );
+
+    let reverse = query_reverse_simulation(
+        deps.as_ref(),
+        mock_env(),
+        Asset {
+            info: token,
+            amount: dynamic.return_amount,
+        },
+    )
+    .unwrap();
+    assert!(reverse.commission_amount > flat.commission_amount);
+
+    // Small swaps in a balanced pool are charged about the base fee
+    let small = query_simulation(
+        deps.as_ref(),
+        mock_env(),
+        Asset {
+            info: uusd,
+            amount: Uint128::new(1_000_000),
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        small.commission_amount,
+        (small.return_amount + small.commission_amount) * fee_rate
+    );
+
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("owner", &[]),
+        update_fee_multiplier(None),
+    )
+    .unwrap();
+    let config: StablePoolConfig = from_binary(
+        &query_config(deps.as_ref(), mock_env())
+            .unwrap()
+            .params
+            .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(config.fee_multiplier, None);
+}
+
+#[test]
+fn amp_history_and_virtual_price() {
+    let mut deps = mock_dependencies(&[]);
+    deps.querier.with_token_balances(&[
+        (
+            &String::from("asset0000"),
+            &[(&String::from(MOCK_CONTRACT_ADDR), &Uint128::zero())],
+        ),
+        (
+            &String::from("liquidity0000"),
+            &[(&String::from("addr0000"), &Uint128::new(2_000_000_000_000))],
+        ),
+    ]);
+    let msg = InstantiateMsg {
+        asset_infos: [
+            AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            AssetInfo::Token {
+                contract_addr: Addr::unchecked("asset0000"),
+            },
+        ],
+        token_code_id: 10u64,
+        factory_addr: String::from("factory"),
+        init_params: Some(to_binary(&StablePoolParams { amp: 100 }).unwrap()),
+    };
+    let init_time = mock_env().block.time.seconds();
+    instantiate(deps.as_mut(), mock_env(), mock_info("addr0000", &[]), msg).unwrap();
+    store_liquidity_token(deps.as_mut(), 1, "liquidity0000".to_string());
+    store_reserves(
+        deps.as_mut(),
+        [
+            Uint128::new(1_000_000_000_000),
+            Uint128::new(1_000_000_000_000),
+        ],
+    );
+
+    // A balanced pool is worth exactly its LP supply
+    let d = query_current_d(deps.as_ref(), mock_env()).unwrap().d;
+    assert_eq!(d, Uint128::new(2_000_000_000_000));
+    let virtual_price = query_virtual_price(deps.as_ref(), mock_env())
+        .unwrap()
+        .virtual_price;
+    assert_eq!(virtual_price, Decimal::one());
+
+    // Fees left in the pool increase the virtual price
+    store_reserves(
+        deps.as_mut(),
+        [
+            Uint128::new(1_001_000_000_000),
+            Uint128::new(1_000_000_000_000),
+        ],
+    );
+    let virtual_price = query_virtual_price(deps.as_ref(), mock_env())
+        .unwrap()
+        .virtual_price;
+    assert!(virtual_price > Decimal::one());
+    assert!(virtual_price < Decimal::from_ratio(10_005u64, 10_000u64));
+
+    assert!(query_amp_history(deps.as_ref(), None, None)
+        .unwrap()
+        .history
+        .is_empty());
+
+    let start_time = init_time + 86400;
+    let end_time = start_time + 2 * 86400;
+    execute(
+        deps.as_mut(),
+        mock_env_with_block_time(start_time),
+        mock_info("owner", &[]),
+        ExecuteMsg::UpdateConfig {
+            params: to_binary(&StablePoolUpdateParams::StartChangingAmp {
+                next_amp: 200,
+                next_amp_time: end_time,
+            })
+            .unwrap(),
+        },
+    )
+    .unwrap();
+
+    // The invariant changes along with the amplification
+    let stop_time = start_time + 86400;
+    let ramping_d = query_current_d(deps.as_ref(), mock_env_with_block_time(stop_time))
+        .unwrap()
+        .d;
+    assert_ne!(
+        ramping_d,
+        query_current_d(deps.as_ref(), mock_env_with_block_time(start_time))
+            .unwrap()
+            .d
+    );
+
+    execute(
+        deps.as_mut(),
+        mock_env_with_block_time(stop_time),
+        mock_info("owner", &[]),
+        ExecuteMsg::UpdateConfig {
+            params: to_binary(&StablePoolUpdateParams::StopChangingAmp {}).unwrap(),
+        },
+    )
+    .unwrap();
+
+    let history = query_amp_history(deps.as_ref(), None, None)
+        .unwrap()
+        .history;
+    assert_eq!(
+        history,
+        vec![
+            AmpRamp {
+                id: 1,
+                sender: Addr::unchecked("owner"),
+                from_amp: Decimal::from_ratio(100u64, 1u64),
+                to_amp: Decimal::from_ratio(200u64, 1u64),
+                start_time,
+                end_time,
+            },
+            AmpRamp {
+                id: 2,
+                sender: Addr::unchecked("owner"),
+                from_amp: Decimal::from_ratio(150u64, 1u64),
+                to_amp: Decimal::from_ratio(150u64, 1u64),
+                start_time: stop_time,
+                end_time: stop_time,
+            },
+        ]
+    );
+
+    let history = query_amp_history(deps.as_ref(), Some(1), Some(1))
+        .unwrap()
+        .history;
+    assert_eq!(history.len(), 1);
+    assert_eq!(history[0].id, 2);
+
+    // The virtual price is not defined for a pool without liquidity
+    deps.querier.with_token_balances(&[(
+        &String::from("liquidity0000"),
+        &[(&String::from("addr0000"), &Uint128::zero())],
+    )]);
+    assert_eq!(
+        query_virtual_price(deps.as_ref(), mock_env()).unwrap_err(),
+        StdError::generic_err("The pool has no liquidity")
+    );
 }
 
 fn mock_env_with_block_time(time: u64) -> Env {
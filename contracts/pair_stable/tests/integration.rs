@@ -5,7 +5,7 @@ use astroport::factory::{
 };
 use astroport::pair::{
     ConfigResponse, CumulativePricesResponse, Cw20HookMsg, ExecuteMsg, InstantiateMsg, QueryMsg,
-    StablePoolConfig, StablePoolParams, StablePoolUpdateParams, TWAP_PRECISION,
+    StablePoolConfig, StablePoolParams, StablePoolUpdateParams, MINIMUM_LIQUIDITY, TWAP_PRECISION,
 };
 
 use astroport::token::InstantiateMsg as TokenInstantiateMsg;
@@ -75,6 +75,35 @@ fn instantiate_pair(mut router: &mut TerraApp, owner: &Addr) -> Addr {
     let token_contract_code_id = store_token_code(&mut router);
 
     let pair_contract_code_id = store_pair_code(&mut router);
+    let factory_code_id = store_factory_code(&mut router);
+
+    // The pair caches its fees from the factory at instantiation
+    let init_msg = FactoryInstantiateMsg {
+        fee_address: None,
+        pair_configs: vec![PairConfig {
+            code_id: pair_contract_code_id,
+            maker_fee_bps: 0,
+            pair_type: PairType::Stable {},
+            total_fee_bps: 0,
+            is_disabled: false,
+            is_generator_disabled: false,
+            maker_fee_as_lp: false,
+        }],
+        token_code_id: token_contract_code_id,
+        generator_address: None,
+        owner: owner.to_string(),
+        whitelist_code_id: 234u64,
+    };
+    let factory = router
+        .instantiate_contract(
+            factory_code_id,
+            owner.clone(),
+            &init_msg,
+            &[],
+            "FACTORY",
+            None,
+        )
+        .unwrap();
 
     let msg = InstantiateMsg {
         asset_infos: [
@@ -86,7 +115,7 @@ fn instantiate_pair(mut router: &mut TerraApp, owner: &Addr) -> Addr {
             },
         ],
         token_code_id: token_contract_code_id,
-        factory_addr: String::from("factory"),
+        factory_addr: factory.to_string(),
         init_params: None,
     };
 
@@ -112,7 +141,7 @@ fn instantiate_pair(mut router: &mut TerraApp, owner: &Addr) -> Addr {
             },
         ],
         token_code_id: token_contract_code_id,
-        factory_addr: String::from("factory"),
+        factory_addr: factory.to_string(),
         init_params: Some(to_binary(&StablePoolParams { amp: 100 }).unwrap()),
     };
 
@@ -131,8 +160,8 @@ fn instantiate_pair(mut router: &mut TerraApp, owner: &Addr) -> Addr {
         .wrap()
         .query_wasm_smart(pair.clone(), &QueryMsg::Pair {})
         .unwrap();
-    assert_eq!("contract #0", res.contract_addr);
-    assert_eq!("contract #1", res.liquidity_token);
+    assert_eq!("contract #1", res.contract_addr);
+    assert_eq!("contract #2", res.liquidity_token);
 
     pair
 }
@@ -150,11 +179,11 @@ fn test_provide_and_withdraw_liquidity() {
             vec![
                 Coin {
                     denom: "uusd".to_string(),
-                    amount: Uint128::new(233u128),
+                    amount: Uint128::new(233_000u128),
                 },
                 Coin {
                     denom: "uluna".to_string(),
-                    amount: Uint128::new(200u128),
+                    amount: Uint128::new(200_000u128),
                 },
             ],
         )
@@ -188,18 +217,18 @@ fn test_provide_and_withdraw_liquidity() {
             vec![
                 Coin {
                     denom: "uusd".to_string(),
-                    amount: Uint128::new(100u128),
+                    amount: Uint128::new(100_000u128),
                 },
                 Coin {
                     denom: "uluna".to_string(),
-                    amount: Uint128::new(100u128),
+                    amount: Uint128::new(100_000u128),
                 },
             ],
         )
         .unwrap();
 
     // Provide liquidity
-    let (msg, coins) = provide_liquidity_msg(Uint128::new(100), Uint128::new(100), None);
+    let (msg, coins) = provide_liquidity_msg(Uint128::new(100_000), Uint128::new(100_000), None);
     let res = router
         .execute_contract(alice_address.clone(), pair_instance.clone(), &msg, &coins)
         .unwrap();
@@ -211,23 +240,33 @@ fn test_provide_and_withdraw_liquidity() {
     assert_eq!(res.events[1].attributes[3], attr("receiver", "alice"),);
     assert_eq!(
         res.events[1].attributes[4],
-        attr("assets", "100uusd, 100uluna")
+        attr("assets", "100000uusd, 100000uluna")
     );
     assert_eq!(
         res.events[1].attributes[5],
-        attr("share", 100u128.to_string())
+        attr("share", 99_000u128.to_string())
     );
+    // The minimum liquidity is locked in the pair
     assert_eq!(res.events[3].attributes[1], attr("action", "mint"));
-    assert_eq!(res.events[3].attributes[2], attr("to", "alice"));
+    assert_eq!(
+        res.events[3].attributes[2],
+        attr("to", pair_instance.as_str())
+    );
     assert_eq!(
         res.events[3].attributes[3],
-        attr("amount", 100u128.to_string())
+        attr("amount", MINIMUM_LIQUIDITY.to_string())
+    );
+    assert_eq!(res.events[5].attributes[1], attr("action", "mint"));
+    assert_eq!(res.events[5].attributes[2], attr("to", "alice"));
+    assert_eq!(
+        res.events[5].attributes[3],
+        attr("amount", 99_000u128.to_string())
     );
 
     // Provide liquidity for a custom receiver
     let (msg, coins) = provide_liquidity_msg(
-        Uint128::new(100),
-        Uint128::new(100),
+        Uint128::new(100_000),
+        Uint128::new(100_000),
         Some("bob".to_string()),
     );
     let res = router
@@ -241,15 +280,18 @@ fn test_provide_and_withdraw_liquidity() {
     assert_eq!(res.events[1].attributes[3], attr("receiver", "bob"),);
     assert_eq!(
         res.events[1].attributes[4],
-        attr("assets", "100uusd, 100uluna")
+        attr("assets", "100000uusd, 100000uluna")
     );
     assert_eq!(
         res.events[1].attributes[5],
-        attr("share", 50u128.to_string())
+        attr("share", 100_000u128.to_string())
     );
     assert_eq!(res.events[3].attributes[1], attr("action", "mint"));
     assert_eq!(res.events[3].attributes[2], attr("to", "bob"));
-    assert_eq!(res.events[3].attributes[3], attr("amount", 50.to_string()));
+    assert_eq!(
+        res.events[3].attributes[3],
+        attr("amount", 100_000.to_string())
+    );
 }
 
 fn provide_liquidity_msg(
@@ -275,6 +317,8 @@ fn provide_liquidity_msg(
         slippage_tolerance: None,
         auto_stake: None,
         receiver,
+        min_lp_to_receive: None,
+        deadline: None,
     };
 
     let coins = [
@@ -370,6 +414,7 @@ fn test_compatibility_of_tokens_with_different_precision() {
             pair_type: PairType::Stable {},
             is_disabled: false,
             is_generator_disabled: false,
+            maker_fee_as_lp: false,
         }],
         token_code_id,
         generator_address: Some(String::from("generator")),
@@ -413,6 +458,7 @@ fn test_compatibility_of_tokens_with_different_precision() {
                 contract_addr: token_y_instance.clone(),
             },
         ],
+        pair_type: Some(PairType::Stable {}),
     };
 
     let res: PairInfo = app
@@ -458,6 +504,8 @@ fn test_compatibility_of_tokens_with_different_precision() {
         slippage_tolerance: None,
         auto_stake: None,
         receiver: None,
+        min_lp_to_receive: None,
+        deadline: None,
     };
 
     app.execute_contract(owner.clone(), pair_instance.clone(), &msg, &[])
@@ -471,6 +519,8 @@ fn test_compatibility_of_tokens_with_different_precision() {
             belief_price: None,
             max_spread: None,
             to: Some(user.to_string()),
+            min_return: None,
+            deadline: None,
         })
         .unwrap(),
         amount: x_offer,
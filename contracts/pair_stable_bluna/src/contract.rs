@@ -4,14 +4,16 @@ use crate::math::{
     MIN_AMP_CHANGING_TIME, N_COINS,
 };
 use crate::state::{
-    Config, BLUNA_REWARD_GLOBAL_INDEX, BLUNA_REWARD_HOLDER, BLUNA_REWARD_USER_INDEXES, CONFIG,
+    CachedRate, Config, BLUNA_REWARD_GLOBAL_INDEX, BLUNA_REWARD_HOLDER, BLUNA_REWARD_USER_INDEXES,
+    CONFIG, EXCHANGE_RATE, FACTORY_PARAMS, RATE_PROVIDER, RESERVES, REWARD_GLOBAL_INDEXES,
+    REWARD_SOURCE, REWARD_USER_INDEXES,
 };
 
 use cosmwasm_bignumber::Decimal256;
 use cosmwasm_std::{
     attr, entry_point, from_binary, to_binary, Addr, Binary, Coin, CosmosMsg, Decimal, Deps,
-    DepsMut, Env, MessageInfo, Reply, ReplyOn, Response, StdError, StdResult, SubMsg, Uint128,
-    Uint256, WasmMsg,
+    DepsMut, Env, MessageInfo, QuerierWrapper, Reply, ReplyOn, Response, StdError, StdResult,
+    Storage, SubMsg, Timestamp, Uint128, Uint256, WasmMsg,
 };
 
 use crate::response::MsgInstantiateContractResponse;
@@ -21,18 +23,23 @@ use astroport::factory::PairType;
 use astroport::generator::{
     Cw20HookMsg as GeneratorHookMsg, PoolInfoResponse, QueryMsg as GeneratorQueryMsg,
 };
+use astroport::observation::{
+    observe, set_observations_size, write_observation, MAX_OBSERVATIONS_SIZE,
+};
 use astroport::pair::{
-    ConfigResponse, CumulativePricesResponse, Cw20HookMsg, InstantiateMsg, PoolResponse,
-    ReverseSimulationResponse, SimulationResponse, DEFAULT_SLIPPAGE, MAX_ALLOWED_SLIPPAGE,
-    TWAP_PRECISION,
+    ConfigResponse, CumulativePricesResponse, Cw20HookMsg, InstantiateMsg, Observation,
+    ObserveResponse, PoolResponse, ReverseSimulationResponse, SimulateProvideResponse,
+    SimulationResponse, DEFAULT_SLIPPAGE, MAX_ALLOWED_SLIPPAGE, MINIMUM_LIQUIDITY, TWAP_PRECISION,
 };
 use astroport::pair_stable_bluna::{
-    ExecuteMsg, MigrateMsg, QueryMsg, StablePoolConfig, StablePoolParams, StablePoolUpdateParams,
+    ExchangeRateResponse, ExecuteMsg, MigrateMsg, QueryMsg, RateProvider, RateProviderParams,
+    RateProviderQueryMsg, RewardSource, RewardSourceParams, StablePoolConfig, StablePoolParams,
+    StablePoolUpdateParams,
 };
 use astroport::whitelist::InstantiateMsg as WhitelistInstantiateMsg;
 
 use astroport::querier::{
-    query_factory_config, query_fee_info, query_supply, query_token_precision,
+    query_factory_config, query_factory_params, query_supply, query_token_precision,
 };
 use astroport::{token::InstantiateMsg as TokenInstantiateMsg, U256};
 use cw2::{get_contract_version, set_contract_version};
@@ -50,6 +57,8 @@ const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
 /// `reply` call code IDs used for sub-messages.
 const INSTANTIATE_TOKEN_REPLY_ID: u64 = 1;
 const INSTANTIATE_BLUNA_REWARD_HOLDER_REPLY_ID: u64 = 2;
+/// The fractional part of [`Decimal`] used to divide amounts by exchange rates.
+const DECIMAL_FRACTIONAL: u128 = 1_000_000_000_000_000_000;
 
 /// ## Description
 /// Creates a new contract with the specified parameters in the [`InstantiateMsg`].
@@ -114,8 +123,18 @@ pub fn instantiate(
     };
 
     CONFIG.save(deps.storage, &config)?;
+    RESERVES.save(deps.storage, &[Uint128::zero(), Uint128::zero()])?;
+    FACTORY_PARAMS.save(
+        deps.storage,
+        &query_factory_params(
+            &deps.querier,
+            config.factory_addr.clone(),
+            env.contract.address.clone(),
+            config.pair_info.pair_type.clone(),
+        )?,
+    )?;
 
-    let token_name = format_lp_token_name(msg.asset_infos, &deps.querier)?;
+    let token_name = format_lp_token_name(&msg.asset_infos, &deps.querier)?;
 
     // Create LP token
     messages.push(SubMsg {
@@ -212,6 +231,8 @@ pub fn reply(deps: DepsMut, _env: Env, msg: Reply) -> Result<Response, ContractE
 ///             slippage_tolerance,
 ///             auto_stake,
 ///             receiver,
+///             min_lp_to_receive,
+///             deadline,
 ///         }** Provides liquidity in the pair with the specified input parameters.
 ///
 /// * **ExecuteMsg::Swap {
@@ -219,20 +240,28 @@ pub fn reply(deps: DepsMut, _env: Env, msg: Reply) -> Result<Response, ContractE
 ///             belief_price,
 ///             max_spread,
 ///             to,
+///             min_return,
+///             deadline,
 ///         }** Performs a swap operation with the specified parameters.
 ///
 /// * **ExecuteMsg::ClaimReward {
 ///             receiver,
 ///             user_share,
 ///             total_share,
-///         }** Claims bLUNA rewards and sends them to the receiver.
+///         }** Claims the pool rewards and sends them to the receiver.
 ///
 /// * **ExecuteMsg::HandleReward {
-///             previous_reward_balance,
+///             previous_reward_balances,
 ///             user_share,
 ///             total_share,
 ///             user,
-///         }** Handles and distributes bLUNA rewards.
+///         }** Handles and distributes the pool rewards.
+///
+/// * **ExecuteMsg::RefreshFactoryParams {}** Updates the fee and Generator information cached from the factory.
+///
+/// * **ExecuteMsg::Sync {}** Sets the tracked pool reserves to the current balances of the pair.
+///
+/// * **ExecuteMsg::Skim { to }** Sends the balances of the pair that exceed the tracked pool reserves to a recipient.
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn execute(
     deps: DepsMut,
@@ -248,6 +277,8 @@ pub fn execute(
             slippage_tolerance,
             auto_stake,
             receiver,
+            min_lp_to_receive,
+            deadline,
         } => provide_liquidity(
             deps,
             env,
@@ -256,12 +287,16 @@ pub fn execute(
             slippage_tolerance,
             auto_stake,
             receiver,
+            min_lp_to_receive,
+            deadline,
         ),
         ExecuteMsg::Swap {
             offer_asset,
             belief_price,
             max_spread,
             to,
+            min_return,
+            deadline,
         } => {
             offer_asset.info.check(deps.api)?;
             if !offer_asset.is_native_token() {
@@ -283,6 +318,8 @@ pub fn execute(
                 belief_price,
                 max_spread,
                 to_addr,
+                min_return,
+                deadline,
             )
         }
         ExecuteMsg::ClaimReward { receiver } => claim_reward(deps, env, info, receiver),
@@ -292,7 +329,7 @@ pub fn execute(
             total_share,
         } => claim_reward_by_generator(deps, env, info, user, user_share, total_share),
         ExecuteMsg::HandleReward {
-            previous_reward_balance,
+            previous_reward_balances,
             user,
             user_share,
             total_share,
@@ -301,12 +338,15 @@ pub fn execute(
             deps,
             env,
             info,
-            previous_reward_balance,
+            previous_reward_balances,
             user,
             user_share,
             total_share,
             receiver,
         ),
+        ExecuteMsg::RefreshFactoryParams {} => refresh_factory_params(deps, env),
+        ExecuteMsg::Sync {} => sync(deps, env),
+        ExecuteMsg::Skim { to } => skim(deps, env, info, to),
     }
 }
 
@@ -334,6 +374,8 @@ pub fn receive_cw20(
             belief_price,
             max_spread,
             to,
+            min_return,
+            deadline,
         }) => {
             // Only an asset contract can execute this message
             let mut authorized: bool = false;
@@ -369,15 +411,27 @@ pub fn receive_cw20(
                 belief_price,
                 max_spread,
                 to_addr,
+                min_return,
+                deadline,
             )
         }
-        Ok(Cw20HookMsg::WithdrawLiquidity {}) => withdraw_liquidity(
+        Ok(Cw20HookMsg::WithdrawLiquidity {
+            min_assets_out,
+            deadline,
+        }) => withdraw_liquidity(
             deps,
             env,
             info,
             Addr::unchecked(cw20_msg.sender),
             cw20_msg.amount,
+            min_assets_out,
+            deadline,
         ),
+        Ok(Cw20HookMsg::SwapExactOut { .. })
+        | Ok(Cw20HookMsg::WithdrawLiquidityOneCoin { .. })
+        | Ok(Cw20HookMsg::WithdrawLiquidityImbalanced { .. }) => {
+            Err(ContractError::NonSupported {})
+        }
         Err(err) => Err(ContractError::Std(err)),
     }
 }
@@ -401,16 +455,28 @@ pub fn receive_cw20(
 ///
 /// * **receiver** is an object of type [`Option<String>`]. This is the address that receives LP tokens.
 /// If this address isn't specified, the function will default to the caller.
+///
+/// * **min_lp_to_receive** is an [`Option`] field of type [`Uint128`]. This is the minimum amount of
+/// LP tokens that must be minted, otherwise the transaction fails.
+///
+/// * **deadline** is an [`Option`] field of type [`u64`]. This is the block time (in seconds) after which
+/// the transaction fails.
 /// NOTE - the address that wants to swap should approve the pair contract to pull the offer token.
+#[allow(clippy::too_many_arguments)]
 pub fn provide_liquidity(
-    deps: DepsMut,
+    mut deps: DepsMut,
     env: Env,
     info: MessageInfo,
     assets: [Asset; 2],
     slippage_tolerance: Option<Decimal>,
     auto_stake: Option<bool>,
     receiver: Option<String>,
+    min_lp_to_receive: Option<Uint128>,
+    deadline: Option<u64>,
 ) -> Result<Response, ContractError> {
+    assert_deposits_allowed(deps.storage)?;
+    assert_deadline(&env, deadline)?;
+
     assets[0].info.check(deps.api)?;
     assets[1].info.check(deps.api)?;
 
@@ -420,28 +486,14 @@ pub fn provide_liquidity(
     }
 
     let mut config: Config = CONFIG.load(deps.storage)?;
-    let mut pools: [Asset; 2] = config
-        .pair_info
-        .query_pools(&deps.querier, env.contract.address.clone())?;
-    let deposits: [Uint128; 2] = [
-        assets
-            .iter()
-            .find(|a| a.info.equal(&pools[0].info))
-            .map(|a| a.amount)
-            .expect("Wrong asset info is given"),
-        assets
-            .iter()
-            .find(|a| a.info.equal(&pools[1].info))
-            .map(|a| a.amount)
-            .expect("Wrong asset info is given"),
-    ];
+    let pools = query_reserves(deps.storage, &config)?;
+    let deposits = get_deposits(&assets, &pools)?;
 
-    if deposits[0].is_zero() || deposits[1].is_zero() {
-        return Err(ContractError::InvalidZeroAmount {});
-    }
+    refresh_exchange_rate(deps.branch(), &env)?;
+    let rates = get_rates(deps.as_ref(), &env, &pools)?;
 
     let mut messages: Vec<CosmosMsg> = vec![];
-    for (i, pool) in pools.iter_mut().enumerate() {
+    for (i, pool) in pools.iter().enumerate() {
         // If the pool is a token contract, then we need to execute a TransferFrom msg to receive funds
         if let AssetInfo::Token { contract_addr, .. } = &pool.info {
             messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
@@ -453,75 +505,41 @@ pub fn provide_liquidity(
                 })?,
                 funds: vec![],
             }))
-        } else {
-            // If the asset is a native token, the pool balance already increased
-            // To calculate the pool balance properly, we should subtract the user deposit from the recorded pool token amount
-            pool.amount = pool.amount.checked_sub(deposits[i])?;
         }
     }
 
-    // Assert slippage tolerance
-    assert_slippage_tolerance(&slippage_tolerance, &deposits, &pools)?;
-
     let token_precision_0 = query_token_precision(&deps.querier, pools[0].info.clone())?;
     let token_precision_1 = query_token_precision(&deps.querier, pools[1].info.clone())?;
 
-    let greater_precision = token_precision_0.max(token_precision_1);
-
-    let deposit_amount_0 = adjust_precision(deposits[0], token_precision_0, greater_precision)?;
-    let deposit_amount_1 = adjust_precision(deposits[1], token_precision_1, greater_precision)?;
-
     let total_share = query_supply(&deps.querier, config.pair_info.liquidity_token.clone())?;
-    let share = if total_share.is_zero() {
-        let liquidity_token_precision = query_token_precision(
-            &deps.querier,
-            AssetInfo::Token {
-                contract_addr: config.pair_info.liquidity_token.clone(),
-            },
-        )?;
-
-        // Initial share = collateral amount
-        adjust_precision(
-            Uint128::new(
-                (U256::from(deposit_amount_0.u128()) * U256::from(deposit_amount_1.u128()))
-                    .integer_sqrt()
-                    .as_u128(),
-            ),
-            greater_precision,
-            liquidity_token_precision,
-        )?
-    } else {
-        let leverage = compute_current_amp(&config, &env)?
-            .checked_mul(u64::from(N_COINS))
-            .unwrap();
-
-        let mut pool_amount_0 =
-            adjust_precision(pools[0].amount, token_precision_0, greater_precision)?;
-        let mut pool_amount_1 =
-            adjust_precision(pools[1].amount, token_precision_1, greater_precision)?;
-
-        let d_before_addition_liquidity =
-            compute_d(leverage, pool_amount_0.u128(), pool_amount_1.u128()).unwrap();
-
-        pool_amount_0 = pool_amount_0.checked_add(deposit_amount_0)?;
-        pool_amount_1 = pool_amount_1.checked_add(deposit_amount_1)?;
+    let share = calculate_share(
+        deps.as_ref(),
+        &env,
+        &config,
+        &deposits,
+        &pools,
+        [token_precision_0, token_precision_1],
+        rates,
+        total_share,
+        slippage_tolerance,
+    )?;
 
-        let d_after_addition_liquidity =
-            compute_d(leverage, pool_amount_0.u128(), pool_amount_1.u128()).unwrap();
+    if total_share.is_zero() {
+        // Lock the minimum liquidity in the pair forever, so the share price can't be inflated
+        messages.extend(mint_liquidity_token_message(
+            deps.as_ref(),
+            &config,
+            env.clone(),
+            env.contract.address.clone(),
+            MINIMUM_LIQUIDITY,
+            false,
+        )?);
+    }
 
-        // d after adding liquidity may be less than or equal to d before adding liquidity because of rounding
-        if d_before_addition_liquidity >= d_after_addition_liquidity {
-            return Err(ContractError::LiquidityAmountTooSmall {});
+    if let Some(min_lp_to_receive) = min_lp_to_receive {
+        if share < min_lp_to_receive {
+            return Err(ContractError::MinLpToReceiveAssertion {});
         }
-
-        total_share.multiply_ratio(
-            d_after_addition_liquidity - d_before_addition_liquidity,
-            d_before_addition_liquidity,
-        )
-    };
-
-    if share.is_zero() {
-        return Err(ContractError::LiquidityAmountTooSmall {});
     }
 
     // Mint LP token for the caller (or for the receiver if it was set)
@@ -543,13 +561,23 @@ pub fn provide_liquidity(
         token_precision_0,
         pools[1].amount,
         token_precision_1,
+        rates,
     )? {
         config.price0_cumulative_last = price0_cumulative_new;
         config.price1_cumulative_last = price1_cumulative_new;
         config.block_time_last = block_time;
         CONFIG.save(deps.storage, &config)?;
+        write_price_observation(deps.storage, &config)?;
     }
 
+    RESERVES.save(
+        deps.storage,
+        &[
+            pools[0].amount.checked_add(deposits[0])?,
+            pools[1].amount.checked_add(deposits[1])?,
+        ],
+    )?;
+
     Ok(Response::new().add_messages(messages).add_attributes(vec![
         attr("action", "provide_liquidity"),
         attr("sender", info.sender.as_str()),
@@ -596,8 +624,7 @@ fn mint_liquidity_token_message(
     }
 
     // Mint for the contract and stake into the Generator
-    let generator =
-        query_factory_config(&deps.querier, config.clone().factory_addr)?.generator_address;
+    let generator = FACTORY_PARAMS.load(deps.storage)?.generator_address;
 
     if generator.is_none() {
         return Err(ContractError::AutoStakeError {});
@@ -624,6 +651,132 @@ fn mint_liquidity_token_message(
     ])
 }
 
+/// ## Description
+/// Returns the deposit amounts in the order of the pool assets. Both pool assets must be deposited.
+/// ## Params
+/// * **assets** is an array with two objects of type [`Asset`]. These are the assets to deposit.
+///
+/// * **pools** are an array of [`Asset`] type items. These are the assets available in the pool.
+fn get_deposits(assets: &[Asset; 2], pools: &[Asset; 2]) -> Result<[Uint128; 2], ContractError> {
+    let deposit = |pool: &Asset| {
+        assets
+            .iter()
+            .find(|a| a.info.equal(&pool.info))
+            .map(|a| a.amount)
+            .ok_or(ContractError::AssetMismatch {})
+    };
+    let deposits = [deposit(&pools[0])?, deposit(&pools[1])?];
+
+    if deposits[0].is_zero() || deposits[1].is_zero() {
+        return Err(ContractError::InvalidZeroAmount {});
+    }
+
+    Ok(deposits)
+}
+
+/// ## Description
+/// Returns the amount of LP tokens minted for a deposit. The share is proportional to the growth of the
+/// stableswap invariant D. The first deposit mints the geometric mean of the deposited amounts minus
+/// the [`MINIMUM_LIQUIDITY`] that is locked in the pair.
+/// ## Params
+/// * **deps** is an object of type [`Deps`].
+///
+/// * **env** is an object of type [`Env`].
+///
+/// * **config** is an object of type [`Config`].
+///
+/// * **deposits** is an array with two objects of type [`Uint128`]. These are the deposits in the order of the pool assets.
+///
+/// * **pools** are an array of [`Asset`] type items. These are the assets available in the pool.
+///
+/// * **token_precisions** is an array with two objects of type [`u8`]. These are the precisions of the pool assets.
+///
+/// * **rates** is an array with two objects of type [`Decimal`]. These are the exchange rates of the pool assets.
+///
+/// * **total_share** is an object of type [`Uint128`]. This is the total amount of LP tokens.
+///
+/// * **slippage_tolerance** is an [`Option`] field of type [`Decimal`]. It is used to specify how much
+/// the pool price can move until the provide liquidity transaction goes through.
+#[allow(clippy::too_many_arguments)]
+fn calculate_share(
+    deps: Deps,
+    env: &Env,
+    config: &Config,
+    deposits: &[Uint128; 2],
+    pools: &[Asset; 2],
+    token_precisions: [u8; 2],
+    rates: [Decimal; 2],
+    total_share: Uint128,
+    slippage_tolerance: Option<Decimal>,
+) -> Result<Uint128, ContractError> {
+    // Assert slippage tolerance
+    assert_slippage_tolerance(&slippage_tolerance, deposits, pools)?;
+
+    let [token_precision_0, token_precision_1] = token_precisions;
+    let greater_precision = token_precision_0.max(token_precision_1);
+
+    let deposit_amount_0 =
+        adjust_precision(deposits[0], token_precision_0, greater_precision)? * rates[0];
+    let deposit_amount_1 =
+        adjust_precision(deposits[1], token_precision_1, greater_precision)? * rates[1];
+
+    let share = if total_share.is_zero() {
+        let liquidity_token_precision = query_token_precision(
+            &deps.querier,
+            AssetInfo::Token {
+                contract_addr: config.pair_info.liquidity_token.clone(),
+            },
+        )?;
+
+        // Initial share = collateral amount
+        adjust_precision(
+            Uint128::new(
+                (U256::from(deposit_amount_0.u128()) * U256::from(deposit_amount_1.u128()))
+                    .integer_sqrt()
+                    .as_u128(),
+            ),
+            greater_precision,
+            liquidity_token_precision,
+        )?
+        .checked_sub(MINIMUM_LIQUIDITY)
+        .map_err(|_| ContractError::MinimumLiquidityAmountError {})?
+    } else {
+        let leverage = compute_current_amp(config, env)?
+            .checked_mul(u64::from(N_COINS))
+            .unwrap();
+
+        let mut pool_amount_0 =
+            adjust_precision(pools[0].amount, token_precision_0, greater_precision)? * rates[0];
+        let mut pool_amount_1 =
+            adjust_precision(pools[1].amount, token_precision_1, greater_precision)? * rates[1];
+
+        let d_before_addition_liquidity =
+            compute_d(leverage, pool_amount_0.u128(), pool_amount_1.u128()).unwrap();
+
+        pool_amount_0 = pool_amount_0.checked_add(deposit_amount_0)?;
+        pool_amount_1 = pool_amount_1.checked_add(deposit_amount_1)?;
+
+        let d_after_addition_liquidity =
+            compute_d(leverage, pool_amount_0.u128(), pool_amount_1.u128()).unwrap();
+
+        // d after adding liquidity may be less than or equal to d before adding liquidity because of rounding
+        if d_before_addition_liquidity >= d_after_addition_liquidity {
+            return Err(ContractError::LiquidityAmountTooSmall {});
+        }
+
+        total_share.multiply_ratio(
+            d_after_addition_liquidity - d_before_addition_liquidity,
+            d_before_addition_liquidity,
+        )
+    };
+
+    if share.is_zero() {
+        return Err(ContractError::LiquidityAmountTooSmall {});
+    }
+
+    Ok(share)
+}
+
 /// ## Description
 /// Withdraw liquidity from the pool. Returns a [`ContractError`] on failure,
 /// otherwise returns a [`Response`] with the specified attributes if the operation was successful.
@@ -637,13 +790,23 @@ fn mint_liquidity_token_message(
 /// * **sender** is an object of type [`Addr`]. This is the address that will receive the withdrawn liquidity.
 ///
 /// * **amount** is an object of type [`Uint128`]. This is the amount of LP tokens to burn and withdraw liquidity with.
+///
+/// * **min_assets_out** is an [`Option`] field of type [`Vec<Asset>`]. These are the minimum amounts of assets
+/// to receive, otherwise the transaction fails.
+///
+/// * **deadline** is an [`Option`] field of type [`u64`]. This is the block time (in seconds) after which
+/// the transaction fails.
 pub fn withdraw_liquidity(
-    deps: DepsMut,
+    mut deps: DepsMut,
     env: Env,
     info: MessageInfo,
     sender: Addr,
     amount: Uint128,
+    min_assets_out: Option<Vec<Asset>>,
+    deadline: Option<u64>,
 ) -> Result<Response, ContractError> {
+    assert_deadline(&env, deadline)?;
+
     let mut config: Config = CONFIG.load(deps.storage).unwrap();
 
     if info.sender != config.pair_info.liquidity_token {
@@ -652,6 +815,10 @@ pub fn withdraw_liquidity(
 
     let (pools, total_share) = pool_info(deps.as_ref(), config.clone())?;
     let refund_assets = get_share_in_assets(&pools, amount, total_share);
+    assert_min_assets_out(&refund_assets, min_assets_out)?;
+
+    refresh_exchange_rate(deps.branch(), &env)?;
+    let rates = get_rates(deps.as_ref(), &env, &pools)?;
 
     // Accumulate prices for the assets in the pool
     if let Some((price0_cumulative_new, price1_cumulative_new, block_time)) = accumulate_prices(
@@ -661,13 +828,23 @@ pub fn withdraw_liquidity(
         query_token_precision(&deps.querier, pools[0].info.clone())?,
         pools[1].amount,
         query_token_precision(&deps.querier, pools[1].info.clone())?,
+        rates,
     )? {
         config.price0_cumulative_last = price0_cumulative_new;
         config.price1_cumulative_last = price1_cumulative_new;
         config.block_time_last = block_time;
         CONFIG.save(deps.storage, &config)?;
+        write_price_observation(deps.storage, &config)?;
     }
 
+    RESERVES.save(
+        deps.storage,
+        &[
+            pools[0].amount.checked_sub(refund_assets[0].amount)?,
+            pools[1].amount.checked_sub(refund_assets[1].amount)?,
+        ],
+    )?;
+
     let messages: Vec<CosmosMsg> = vec![
         refund_assets[0]
             .clone()
@@ -748,9 +925,13 @@ pub fn get_share_in_assets(
 /// * **max_spread** is an object of type [`Option<Decimal>`]. This is the maximum spread allowed for the swap.
 ///
 /// * **to** is an object of type [`Option<Addr>`]. This is the address that receives ask tokens.
+///
+/// * **min_return** is an object of type [`Option<Uint128>`]. This is the minimum amount of ask assets to receive.
+///
+/// * **deadline** is an object of type [`Option<u64>`]. This is the block time (in seconds) after which the swap fails.
 #[allow(clippy::too_many_arguments)]
 pub fn swap(
-    deps: DepsMut,
+    mut deps: DepsMut,
     env: Env,
     info: MessageInfo,
     sender: Addr,
@@ -758,53 +939,50 @@ pub fn swap(
     belief_price: Option<Decimal>,
     max_spread: Option<Decimal>,
     to: Option<Addr>,
+    min_return: Option<Uint128>,
+    deadline: Option<u64>,
 ) -> Result<Response, ContractError> {
+    assert_swaps_allowed(deps.storage)?;
+    assert_deadline(&env, deadline)?;
+
     offer_asset.assert_sent_native_token_balance(&info)?;
 
     let mut config: Config = CONFIG.load(deps.storage)?;
+    let pools = query_reserves(deps.storage, &config)?;
 
-    // If the asset balance already increased
-    // We should subtract the user deposit from the pool offer asset amount
-    let pools: Vec<Asset> = config
-        .pair_info
-        .query_pools(&deps.querier, env.clone().contract.address)?
-        .iter()
-        .map(|p| {
-            let mut p = p.clone();
-            if p.info.equal(&offer_asset.info) {
-                p.amount = p.amount.checked_sub(offer_asset.amount).unwrap();
-            }
-
-            p
-        })
-        .collect();
+    refresh_exchange_rate(deps.branch(), &env)?;
+    let rates = get_rates(deps.as_ref(), &env, &pools)?;
 
     let offer_pool: Asset;
     let ask_pool: Asset;
+    let offer_rate: Decimal;
+    let ask_rate: Decimal;
 
     if offer_asset.info.equal(&pools[0].info) {
         offer_pool = pools[0].clone();
         ask_pool = pools[1].clone();
+        offer_rate = rates[0];
+        ask_rate = rates[1];
     } else if offer_asset.info.equal(&pools[1].info) {
         offer_pool = pools[1].clone();
         ask_pool = pools[0].clone();
+        offer_rate = rates[1];
+        ask_rate = rates[0];
     } else {
         return Err(ContractError::AssetMismatch {});
     }
 
-    // Get fee info from the factory
-    let fee_info = query_fee_info(
-        &deps.querier,
-        config.factory_addr.clone(),
-        config.pair_info.pair_type.clone(),
-    )?;
+    // Get the fee info cached from the factory
+    let fee_info = FACTORY_PARAMS.load(deps.storage)?.fee_info;
 
     let offer_amount = offer_asset.amount;
     let (return_amount, spread_amount, commission_amount) = compute_swap(
         offer_pool.amount,
         query_token_precision(&deps.querier, offer_pool.info)?,
+        offer_rate,
         ask_pool.amount,
         query_token_precision(&deps.querier, ask_pool.info.clone())?,
+        ask_rate,
         offer_amount,
         fee_info.total_fee_rate,
         compute_current_amp(&config, &env)?,
@@ -819,6 +997,13 @@ pub fn swap(
         spread_amount,
     )?;
 
+    // Check the minimum return amount (if it was specified)
+    if let Some(min_return) = min_return {
+        if return_amount < min_return {
+            return Err(ContractError::MinReturnAssertion {});
+        }
+    }
+
     // Compute the tax for the ask asset
     let return_asset = Asset {
         info: ask_pool.info.clone(),
@@ -853,13 +1038,23 @@ pub fn swap(
         query_token_precision(&deps.querier, pools[0].info.clone())?,
         pools[1].amount,
         query_token_precision(&deps.querier, pools[1].info.clone())?,
+        rates,
     )? {
         config.price0_cumulative_last = price0_cumulative_new;
         config.price1_cumulative_last = price1_cumulative_new;
         config.block_time_last = block_time;
         CONFIG.save(deps.storage, &config)?;
+        write_price_observation(deps.storage, &config)?;
     }
 
+    save_reserves_after_swap(
+        deps.storage,
+        &pools,
+        &offer_asset.info,
+        offer_amount,
+        return_amount.checked_add(maker_fee_amount)?,
+    )?;
+
     Ok(Response::new()
         .add_messages(
             // 1. send collateral token from the contract to a user
@@ -879,6 +1074,99 @@ pub fn swap(
         .add_attribute("maker_fee_amount", maker_fee_amount.to_string()))
 }
 
+/// ## Description
+/// Sets the tracked pool reserves to the current balances of the pair, so that assets sent to the pair
+/// directly are added to the pool. Cumulative prices are accumulated using the reserves before the sync.
+/// Returns a [`ContractError`] on failure, otherwise returns a [`Response`] with the specified
+/// attributes if the operation was successful.
+/// ## Params
+/// * **deps** is an object of type [`DepsMut`].
+///
+/// * **env** is an object of type [`Env`].
+pub fn sync(mut deps: DepsMut, env: Env) -> Result<Response, ContractError> {
+    let mut config: Config = CONFIG.load(deps.storage)?;
+    let reserves = query_reserves(deps.storage, &config)?;
+
+    refresh_exchange_rate(deps.branch(), &env)?;
+    let rates = get_rates(deps.as_ref(), &env, &reserves)?;
+
+    // Accumulate prices for the assets in the pool
+    if let Some((price0_cumulative_new, price1_cumulative_new, block_time)) = accumulate_prices(
+        env.clone(),
+        &config,
+        reserves[0].amount,
+        query_token_precision(&deps.querier, reserves[0].info.clone())?,
+        reserves[1].amount,
+        query_token_precision(&deps.querier, reserves[1].info.clone())?,
+        rates,
+    )? {
+        config.price0_cumulative_last = price0_cumulative_new;
+        config.price1_cumulative_last = price1_cumulative_new;
+        config.block_time_last = block_time;
+        CONFIG.save(deps.storage, &config)?;
+        write_price_observation(deps.storage, &config)?;
+    }
+
+    let pools = config
+        .pair_info
+        .query_pools(&deps.querier, env.contract.address)?;
+    RESERVES.save(deps.storage, &[pools[0].amount, pools[1].amount])?;
+
+    Ok(Response::new()
+        .add_attribute("action", "sync")
+        .add_attribute("reserves", format!("{}, {}", pools[0], pools[1])))
+}
+
+/// ## Description
+/// Sends the balances of the pair that exceed the tracked pool reserves to a recipient.
+/// Returns a [`ContractError`] on failure, otherwise returns a [`Response`] with the specified
+/// attributes if the operation was successful.
+/// ## Params
+/// * **deps** is an object of type [`DepsMut`].
+///
+/// * **env** is an object of type [`Env`].
+///
+/// * **info** is an object of type [`MessageInfo`].
+///
+/// * **to** is an [`Option`] field of type [`String`]. This is the recipient of the excess assets.
+/// If no custom recipient is specified, the assets are sent to the function caller.
+pub fn skim(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    to: Option<String>,
+) -> Result<Response, ContractError> {
+    let config: Config = CONFIG.load(deps.storage)?;
+    let reserves = query_reserves(deps.storage, &config)?;
+    let pools = config
+        .pair_info
+        .query_pools(&deps.querier, env.contract.address)?;
+
+    let to = addr_validate_to_lower(deps.api, &to.unwrap_or_else(|| info.sender.to_string()))?;
+
+    let mut messages: Vec<CosmosMsg> = vec![];
+    let mut excess_assets: Vec<Asset> = vec![];
+    for (pool, reserve) in pools.iter().zip(reserves.iter()) {
+        let excess = Asset {
+            info: pool.info.clone(),
+            amount: pool.amount.saturating_sub(reserve.amount),
+        };
+        if !excess.amount.is_zero() {
+            messages.push(excess.clone().into_msg(&deps.querier, to.clone())?);
+        }
+        excess_assets.push(excess);
+    }
+
+    Ok(Response::new()
+        .add_messages(messages)
+        .add_attribute("action", "skim")
+        .add_attribute("receiver", to.as_str())
+        .add_attribute(
+            "assets",
+            format!("{}, {}", excess_assets[0], excess_assets[1]),
+        ))
+}
+
 /// ## Description
 /// Accumulate token prices for the assets in the pool.
 /// Note that this function shifts **block_time** when any of the token prices is zero in order to not
@@ -895,6 +1183,8 @@ pub fn swap(
 /// * **y** is the balance of asset\[\1] in the pool.
 ///
 /// * **y_precision** is the precision for the y token.
+///
+/// * **rates** is an array with two objects of type [`Decimal`]. These are the exchange rates of the x and y tokens.
 pub fn accumulate_prices(
     env: Env,
     config: &Config,
@@ -902,6 +1192,7 @@ pub fn accumulate_prices(
     x_precision: u8,
     y: Uint128,
     y_precision: u8,
+    rates: [Decimal; 2],
 ) -> StdResult<Option<(Uint128, Uint128, u64)>> {
     let block_time = env.block.time.seconds();
     if block_time <= config.block_time_last {
@@ -910,8 +1201,9 @@ pub fn accumulate_prices(
 
     // We have to shift block_time when any price is zero in order to not fill an accumulator with a null price for that period
     let greater_precision = x_precision.max(y_precision).max(TWAP_PRECISION);
-    let x = adjust_precision(x, x_precision, greater_precision)?;
-    let y = adjust_precision(y, y_precision, greater_precision)?;
+    let x = adjust_precision(x, x_precision, greater_precision)? * rates[0];
+    let y = adjust_precision(y, y_precision, greater_precision)? * rates[1];
+    let one = adjust_precision(Uint128::new(1), 0, greater_precision)?;
 
     let time_elapsed = Uint128::from(block_time - config.block_time_last);
 
@@ -921,27 +1213,23 @@ pub fn accumulate_prices(
     if !x.is_zero() && !y.is_zero() {
         let current_amp = compute_current_amp(config, &env)?;
         pcl0 = config.price0_cumulative_last.wrapping_add(adjust_precision(
-            time_elapsed.checked_mul(Uint128::new(
-                calc_ask_amount(
-                    x.u128(),
-                    y.u128(),
-                    adjust_precision(Uint128::new(1), 0, greater_precision)?.u128(),
-                    current_amp,
-                )
-                .unwrap(),
+            time_elapsed.checked_mul(unscale_by_rate(
+                Uint128::new(
+                    calc_ask_amount(x.u128(), y.u128(), (one * rates[0]).u128(), current_amp)
+                        .unwrap(),
+                ),
+                rates[1],
             ))?,
             greater_precision,
             TWAP_PRECISION,
         )?);
         pcl1 = config.price1_cumulative_last.wrapping_add(adjust_precision(
-            time_elapsed.checked_mul(Uint128::new(
-                calc_ask_amount(
-                    y.u128(),
-                    x.u128(),
-                    adjust_precision(Uint128::new(1), 0, greater_precision)?.u128(),
-                    current_amp,
-                )
-                .unwrap(),
+            time_elapsed.checked_mul(unscale_by_rate(
+                Uint128::new(
+                    calc_ask_amount(y.u128(), x.u128(), (one * rates[1]).u128(), current_amp)
+                        .unwrap(),
+                ),
+                rates[0],
             ))?,
             greater_precision,
             TWAP_PRECISION,
@@ -951,6 +1239,24 @@ pub fn accumulate_prices(
     Ok(Some((pcl0, pcl1, block_time)))
 }
 
+/// ## Description
+/// Stores an observation of the last cumulative prices of the pool assets. Prices are accumulated at most
+/// once per block time, so at most one observation is written per block.
+/// ## Params
+/// * **storage** is an object of type [`Storage`].
+///
+/// * **config** is an object of type [`Config`].
+pub fn write_price_observation(storage: &mut dyn Storage, config: &Config) -> StdResult<()> {
+    write_observation(
+        storage,
+        Observation {
+            timestamp: config.block_time_last,
+            price0_cumulative: config.price0_cumulative_last.into(),
+            price1_cumulative: config.price1_cumulative_last.into(),
+        },
+    )
+}
+
 /// ## Description
 /// Calculates the amount of fees the Maker contract gets according to specified pair parameters.
 /// Returns a [`None`] if the Maker fee is zero, otherwise returns a [`Asset`] struct with the specified attributes.
@@ -1002,11 +1308,26 @@ pub fn calculate_maker_fee(
 /// * **QueryMsg::CumulativePrices {}** Returns information about cumulative prices for the assets in the
 /// pool using a [`CumulativePricesResponse`] object.
 ///
+/// * **QueryMsg::Observe { seconds_ago }** Returns the cumulative prices at the specified number of seconds ago
+/// using an [`ObserveResponse`] object.
+///
 /// * **QueryMsg::Config {}** Returns the configuration for the pair contract using a [`ConfigResponse`] object.
 ///
-/// * **QueryMsg::PendingReward {}** Returns the amount of bLUNA pending rewards for a specific address using an [`Asset`] object.
-#[cfg_attr(not(feature = "library"), entry_point)]
-pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
+/// * **QueryMsg::PendingReward { user }** Returns the pending amount of the first reward asset for a specific address
+/// using an [`Asset`] object.
+///
+/// * **QueryMsg::PendingRewards { user }** Returns the pending amounts of all reward assets for a specific address
+/// in a [`Vec<Asset>`] object.
+///
+/// * **QueryMsg::SimulateProvide { assets, slippage_tolerance }** Returns the amount of LP tokens minted
+/// for a deposit using a [`SimulateProvideResponse`] object.
+///
+/// * **QueryMsg::SimulateWithdraw { lp_amount }** Returns the amounts of assets received for burning
+/// LP tokens in a [`Vec<Asset>`] object.
+///
+/// * **QueryMsg::PairStatus {}** Returns the trading state of the pair set in the factory using a [`astroport::factory::PairStatus`] object.
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
     match msg {
         QueryMsg::Pair {} => to_binary(&query_pair_info(deps)?),
         QueryMsg::Pool {} => to_binary(&query_pool(deps)?),
@@ -1018,8 +1339,23 @@ pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
             to_binary(&query_reverse_simulation(deps, env, ask_asset)?)
         }
         QueryMsg::CumulativePrices {} => to_binary(&query_cumulative_prices(deps, env)?),
+        QueryMsg::Observe { seconds_ago } => to_binary(&query_observe(deps, env, seconds_ago)?),
         QueryMsg::Config {} => to_binary(&query_config(deps, env)?),
         QueryMsg::PendingReward { user } => to_binary(&query_pending_reward(deps, env, user)?),
+        QueryMsg::PendingRewards { user } => to_binary(&query_pending_rewards(deps, env, user)?),
+        QueryMsg::SimulateProvide {
+            assets,
+            slippage_tolerance,
+        } => to_binary(&query_simulate_provide(
+            deps,
+            env,
+            assets,
+            slippage_tolerance,
+        )?),
+        QueryMsg::SimulateWithdraw { lp_amount } => {
+            to_binary(&query_simulate_withdraw(deps, lp_amount)?)
+        }
+        QueryMsg::PairStatus {} => to_binary(&FACTORY_PARAMS.load(deps.storage)?.pair_status),
     }
 }
 
@@ -1072,36 +1408,40 @@ pub fn query_share(deps: Deps, amount: Uint128) -> StdResult<[Asset; 2]> {
 /// * **offer_asset** is an object of type [`Asset`]. This is the asset to swap as well as an amount of the said asset.
 pub fn query_simulation(deps: Deps, env: Env, offer_asset: Asset) -> StdResult<SimulationResponse> {
     let config: Config = CONFIG.load(deps.storage)?;
-    let contract_addr = config.pair_info.contract_addr.clone();
+    let pools = query_reserves(deps.storage, &config)?;
 
-    let pools: [Asset; 2] = config.pair_info.query_pools(&deps.querier, contract_addr)?;
+    let rates = get_rates(deps, &env, &pools)?;
 
     let offer_pool: Asset;
     let ask_pool: Asset;
+    let offer_rate: Decimal;
+    let ask_rate: Decimal;
     if offer_asset.info.equal(&pools[0].info) {
         offer_pool = pools[0].clone();
         ask_pool = pools[1].clone();
+        offer_rate = rates[0];
+        ask_rate = rates[1];
     } else if offer_asset.info.equal(&pools[1].info) {
         offer_pool = pools[1].clone();
         ask_pool = pools[0].clone();
+        offer_rate = rates[1];
+        ask_rate = rates[0];
     } else {
         return Err(StdError::generic_err(
             "Given offer asset doesn't belong to pairs",
         ));
     }
 
-    // Get fee info from factory
-    let fee_info = query_fee_info(
-        &deps.querier,
-        config.factory_addr.clone(),
-        config.pair_info.pair_type.clone(),
-    )?;
+    // Get the fee info cached from the factory
+    let fee_info = FACTORY_PARAMS.load(deps.storage)?.fee_info;
 
     let (return_amount, spread_amount, commission_amount) = compute_swap(
         offer_pool.amount,
         query_token_precision(&deps.querier, offer_pool.info)?,
+        offer_rate,
         ask_pool.amount,
         query_token_precision(&deps.querier, ask_pool.info)?,
+        ask_rate,
         offer_asset.amount,
         fee_info.total_fee_rate,
         compute_current_amp(&config, &env)?,
@@ -1127,36 +1467,40 @@ pub fn query_reverse_simulation(
     ask_asset: Asset,
 ) -> StdResult<ReverseSimulationResponse> {
     let config: Config = CONFIG.load(deps.storage)?;
-    let contract_addr = config.pair_info.contract_addr.clone();
+    let pools = query_reserves(deps.storage, &config)?;
 
-    let pools: [Asset; 2] = config.pair_info.query_pools(&deps.querier, contract_addr)?;
+    let rates = get_rates(deps, &env, &pools)?;
 
     let offer_pool: Asset;
     let ask_pool: Asset;
+    let offer_rate: Decimal;
+    let ask_rate: Decimal;
     if ask_asset.info.equal(&pools[0].info) {
         ask_pool = pools[0].clone();
         offer_pool = pools[1].clone();
+        ask_rate = rates[0];
+        offer_rate = rates[1];
     } else if ask_asset.info.equal(&pools[1].info) {
         ask_pool = pools[1].clone();
         offer_pool = pools[0].clone();
+        ask_rate = rates[1];
+        offer_rate = rates[0];
     } else {
         return Err(StdError::generic_err(
             "Given ask asset doesn't belong to pairs",
         ));
     }
 
-    // Get fee info from the factory
-    let fee_info = query_fee_info(
-        &deps.querier,
-        config.factory_addr.clone(),
-        config.pair_info.pair_type.clone(),
-    )?;
+    // Get the fee info cached from the factory
+    let fee_info = FACTORY_PARAMS.load(deps.storage)?.fee_info;
 
     let (offer_amount, spread_amount, commission_amount) = compute_offer_amount(
         offer_pool.amount,
         query_token_precision(&deps.querier, offer_pool.info)?,
+        offer_rate,
         ask_pool.amount,
         query_token_precision(&deps.querier, ask_pool.info)?,
+        ask_rate,
         ask_asset.amount,
         fee_info.total_fee_rate,
         compute_current_amp(&config, &env)?,
@@ -1182,6 +1526,7 @@ pub fn query_cumulative_prices(deps: Deps, env: Env) -> StdResult<CumulativePric
     let mut price0_cumulative_last = config.price0_cumulative_last;
     let mut price1_cumulative_last = config.price1_cumulative_last;
 
+    let rates = get_rates(deps, &env, &assets)?;
     if let Some((price0_cumulative_new, price1_cumulative_new, _)) = accumulate_prices(
         env,
         &config,
@@ -1189,6 +1534,7 @@ pub fn query_cumulative_prices(deps: Deps, env: Env) -> StdResult<CumulativePric
         query_token_precision(&deps.querier, assets[0].info.clone())?,
         assets[1].amount,
         query_token_precision(&deps.querier, assets[1].info.clone())?,
+        rates,
     )? {
         price0_cumulative_last = price0_cumulative_new;
         price1_cumulative_last = price1_cumulative_new;
@@ -1199,36 +1545,119 @@ pub fn query_cumulative_prices(deps: Deps, env: Env) -> StdResult<CumulativePric
         total_share,
         price0_cumulative_last,
         price1_cumulative_last,
+        price0_cumulative_last_u256: None,
+        price1_cumulative_last_u256: None,
     };
 
     Ok(resp)
 }
 
+/// ## Description
+/// Returns the cumulative prices at the specified number of seconds ago in an [`ObserveResponse`] object.
+/// These are the same 128-bit accumulators with [`TWAP_PRECISION`] decimals as in the cumulative prices query.
+/// Cumulative prices after the last price accumulation are extrapolated using the current reserves and
+/// cumulative prices between two stored observations are interpolated linearly.
+/// ## Params
+/// * **deps** is an object of type [`Deps`].
+///
+/// * **env** is an object of type [`Env`].
+///
+/// * **seconds_ago** is a [`Vec`] of type [`u64`]. These are the observation times relative to the current block time.
+pub fn query_observe(deps: Deps, env: Env, seconds_ago: Vec<u64>) -> StdResult<ObserveResponse> {
+    let config: Config = CONFIG.load(deps.storage)?;
+    let pools = query_reserves(deps.storage, &config)?;
+    let token_precision_0 = query_token_precision(&deps.querier, pools[0].info.clone())?;
+    let token_precision_1 = query_token_precision(&deps.querier, pools[1].info.clone())?;
+    let rates = get_rates(deps, &env, &pools)?;
+
+    let last = Observation {
+        timestamp: config.block_time_last,
+        price0_cumulative: config.price0_cumulative_last.into(),
+        price1_cumulative: config.price1_cumulative_last.into(),
+    };
+
+    let observations = observe(
+        deps.storage,
+        env.block.time.seconds(),
+        last.clone(),
+        seconds_ago,
+        |target| {
+            let mut env = env.clone();
+            env.block.time = Timestamp::from_seconds(target);
+            match accumulate_prices(
+                env,
+                &config,
+                pools[0].amount,
+                token_precision_0,
+                pools[1].amount,
+                token_precision_1,
+                rates,
+            )? {
+                Some((price0_cumulative, price1_cumulative, _)) => Ok(Observation {
+                    timestamp: target,
+                    price0_cumulative: price0_cumulative.into(),
+                    price1_cumulative: price1_cumulative.into(),
+                }),
+                None => Ok(Observation {
+                    timestamp: target,
+                    ..last.clone()
+                }),
+            }
+        },
+    )?;
+
+    Ok(ObserveResponse { observations })
+}
+
 /// ## Description
 /// Returns the pair contract configuration in a [`ConfigResponse`] object.
 /// ## Params
 /// * **deps** is an object of type [`Deps`].
 pub fn query_config(deps: Deps, env: Env) -> StdResult<ConfigResponse> {
     let config: Config = CONFIG.load(deps.storage)?;
+    let rate_provider = RATE_PROVIDER.may_load(deps.storage)?;
     Ok(ConfigResponse {
         block_time_last: config.block_time_last,
         params: Some(to_binary(&StablePoolConfig {
             amp: Decimal::from_ratio(compute_current_amp(&config, &env)?, AMP_PRECISION),
             bluna_rewarder: config.bluna_rewarder,
             generator: config.generator,
+            rate_provider: rate_provider.clone(),
+            exchange_rate: match rate_provider {
+                Some(rate_provider) => load_exchange_rate(deps, &env, &rate_provider)?.rate,
+                None => Decimal::one(),
+            },
+            reward_source: REWARD_SOURCE.may_load(deps.storage)?,
+            reward_holder: BLUNA_REWARD_HOLDER.may_load(deps.storage)?,
         })?),
     })
 }
 
 /// ## Description
-/// Returns the amount of bLUNA pending rewards for a specific address using a [`Asset`] object.
+/// Returns the pending amount of the first reward asset for a specific address using a [`Asset`] object.
 /// ## Params
 /// * **deps** is an object of type [`Deps`].
 ///
 /// * **env** is an object of type [`Env`].
 ///
-/// * **user** is an object of type [`String`]. This is the address for which we query the amount of pending bLUNA rewards to claim.
-pub fn query_pending_reward(deps: Deps, _env: Env, user: String) -> StdResult<Asset> {
+/// * **user** is an object of type [`String`]. This is the address for which we query the amount of pending rewards to claim.
+pub fn query_pending_reward(deps: Deps, env: Env, user: String) -> StdResult<Asset> {
+    query_pending_rewards(deps, env, user)?
+        .into_iter()
+        .next()
+        .ok_or_else(|| StdError::generic_err("The pool has no reward assets"))
+}
+
+/// ## Description
+/// Returns the pending amounts of all reward assets for a specific address in a vector that contains
+/// objects of type [`Asset`].
+/// ## Params
+/// * **deps** is an object of type [`Deps`].
+///
+/// * **_env** is an object of type [`Env`].
+///
+/// * **user** is an object of type [`String`]. This is the address for which we query the amounts of pending rewards to claim.
+pub fn query_pending_rewards(deps: Deps, _env: Env, user: String) -> StdResult<Vec<Asset>> {
     use cosmwasm_std::Decimal256;
 
     let user = addr_validate_to_lower(deps.api, &user)?;
@@ -1243,26 +1672,27 @@ pub fn query_pending_reward(deps: Deps, _env: Env, user: String) -> StdResult<As
         },
     )?;
 
-    let global_index = BLUNA_REWARD_GLOBAL_INDEX
-        .may_load(deps.storage)?
-        .unwrap_or_default();
-
-    let user_index_opt = BLUNA_REWARD_USER_INDEXES.may_load(deps.storage, &user)?;
-
-    let user_index = if let Some(user_index) = user_index_opt {
-        user_index
-    } else if user_share.is_zero() {
-        global_index
-    } else {
-        Decimal256::zero()
-    };
-
-    Ok(Asset {
-        info: AssetInfo::NativeToken {
-            denom: "uusd".to_string(),
-        },
-        amount: ((global_index - user_index) * Uint256::from(user_share)).try_into()?,
-    })
+    load_reward_source(deps, &config)?
+        .reward_assets
+        .into_iter()
+        .map(|info| {
+            let global_index = load_reward_global_index(deps.storage, &info)?;
+
+            let user_index =
+                if let Some(user_index) = load_reward_user_index(deps.storage, &user, &info)? {
+                    user_index
+                } else if user_share.is_zero() {
+                    global_index
+                } else {
+                    Decimal256::zero()
+                };
+
+            Ok(Asset {
+                info,
+                amount: ((global_index - user_index) * Uint256::from(user_share)).try_into()?,
+            })
+        })
+        .collect()
 }
 
 /// ## Description
@@ -1286,30 +1716,39 @@ pub fn amount_of(coins: &[Coin], denom: String) -> Uint128 {
 ///
 /// * **offer_precision** is an object of type [`u8`]. This is the token precision used for the offer amount.
 ///
+/// * **offer_rate** is an object of type [`Decimal`]. This is the exchange rate of the offer asset.
+///
 /// * **ask_pool** is an object of type [`Uint128`]. This is the total amount of ask assets in the pool.
 ///
 /// * **ask_precision** is an object of type [`u8`]. This is the token precision used for the ask amount.
 ///
+/// * **ask_rate** is an object of type [`Decimal`]. This is the exchange rate of the ask asset.
+///
 /// * **offer_amount** is an object of type [`Uint128`]. This is the amount of offer assets to swap.
 ///
 /// * **commission_rate** is an object of type [`Decimal`]. This is the total amount of fees charged for the swap.
 ///
 /// * **amp** is an object of type [`u64`]. This is the pool amplification used to calculate the swap result.
+#[allow(clippy::too_many_arguments)]
 fn compute_swap(
     offer_pool: Uint128,
     offer_precision: u8,
+    offer_rate: Decimal,
     ask_pool: Uint128,
     ask_precision: u8,
+    ask_rate: Decimal,
     offer_amount: Uint128,
     commission_rate: Decimal,
     amp: u64,
 ) -> StdResult<(Uint128, Uint128, Uint128)> {
     // offer => ask
 
+    // The balances are scaled by the exchange rates, so the pool keeps the 1:1 peg in the base asset
     let greater_precision = offer_precision.max(ask_precision);
-    let offer_pool = adjust_precision(offer_pool, offer_precision, greater_precision)?;
-    let ask_pool = adjust_precision(ask_pool, ask_precision, greater_precision)?;
-    let offer_amount = adjust_precision(offer_amount, offer_precision, greater_precision)?;
+    let offer_pool = adjust_precision(offer_pool, offer_precision, greater_precision)? * offer_rate;
+    let ask_pool = adjust_precision(ask_pool, ask_precision, greater_precision)? * ask_rate;
+    let offer_amount =
+        adjust_precision(offer_amount, offer_precision, greater_precision)? * offer_rate;
 
     let return_amount = Uint128::new(
         calc_ask_amount(offer_pool.u128(), ask_pool.u128(), offer_amount.u128(), amp).unwrap(),
@@ -1323,9 +1762,21 @@ fn compute_swap(
     // The commission will be absorbed by the pool
     let return_amount: Uint128 = return_amount.checked_sub(commission_amount).unwrap();
 
-    let return_amount = adjust_precision(return_amount, greater_precision, ask_precision)?;
-    let spread_amount = adjust_precision(spread_amount, greater_precision, ask_precision)?;
-    let commission_amount = adjust_precision(commission_amount, greater_precision, ask_precision)?;
+    let return_amount = adjust_precision(
+        unscale_by_rate(return_amount, ask_rate),
+        greater_precision,
+        ask_precision,
+    )?;
+    let spread_amount = adjust_precision(
+        unscale_by_rate(spread_amount, ask_rate),
+        greater_precision,
+        ask_precision,
+    )?;
+    let commission_amount = adjust_precision(
+        unscale_by_rate(commission_amount, ask_rate),
+        greater_precision,
+        ask_precision,
+    )?;
 
     Ok((return_amount, spread_amount, commission_amount))
 }
@@ -1337,28 +1788,36 @@ fn compute_swap(
 ///
 /// * **offer_precision** is an object of type [`u8`]. This is the token precision used for the offer amount.
 ///
+/// * **offer_rate** is an object of type [`Decimal`]. This is the exchange rate of the offer asset.
+///
 /// * **ask_pool** is an object of type [`Uint128`]. This is the total amount of ask assets in the pool.
 ///
 /// * **ask_precision** is an object of type [`u8`]. This is the token precision used for the ask amount.
 ///
+/// * **ask_rate** is an object of type [`Decimal`]. This is the exchange rate of the ask asset.
+///
 /// * **ask_amount** is an object of type [`Uint128`]. This is the amount of ask assets to swap to.
 ///
 /// * **commission_rate** is an object of type [`Decimal`]. This is the total amount of fees charged for the swap.
+#[allow(clippy::too_many_arguments)]
 fn compute_offer_amount(
     offer_pool: Uint128,
     offer_precision: u8,
+    offer_rate: Decimal,
     ask_pool: Uint128,
     ask_precision: u8,
+    ask_rate: Decimal,
     ask_amount: Uint128,
     commission_rate: Decimal,
     amp: u64,
 ) -> StdResult<(Uint128, Uint128, Uint128)> {
     // ask => offer
 
+    // The balances are scaled by the exchange rates, so the pool keeps the 1:1 peg in the base asset
     let greater_precision = offer_precision.max(ask_precision);
-    let offer_pool = adjust_precision(offer_pool, offer_precision, greater_precision)?;
-    let ask_pool = adjust_precision(ask_pool, ask_precision, greater_precision)?;
-    let ask_amount = adjust_precision(ask_amount, ask_precision, greater_precision)?;
+    let offer_pool = adjust_precision(offer_pool, offer_precision, greater_precision)? * offer_rate;
+    let ask_pool = adjust_precision(ask_pool, ask_precision, greater_precision)? * ask_rate;
+    let ask_amount = adjust_precision(ask_amount, ask_precision, greater_precision)? * ask_rate;
 
     let one_minus_commission = Decimal256::one() - Decimal256::from(commission_rate);
     let inv_one_minus_commission: Decimal = (Decimal256::one() / one_minus_commission).into();
@@ -1379,9 +1838,21 @@ fn compute_offer_amount(
 
     let commission_amount = before_commission_deduction * commission_rate;
 
-    let offer_amount = adjust_precision(offer_amount, greater_precision, offer_precision)?;
-    let spread_amount = adjust_precision(spread_amount, greater_precision, ask_precision)?;
-    let commission_amount = adjust_precision(commission_amount, greater_precision, ask_precision)?;
+    let offer_amount = adjust_precision(
+        unscale_by_rate(offer_amount, offer_rate),
+        greater_precision,
+        offer_precision,
+    )?;
+    let spread_amount = adjust_precision(
+        unscale_by_rate(spread_amount, ask_rate),
+        greater_precision,
+        ask_precision,
+    )?;
+    let commission_amount = adjust_precision(
+        unscale_by_rate(commission_amount, ask_rate),
+        greater_precision,
+        ask_precision,
+    )?;
 
     Ok((offer_amount, spread_amount, commission_amount))
 }
@@ -1459,6 +1930,71 @@ pub fn assert_max_spread(
     Ok(())
 }
 
+/// ## Description
+/// Returns a [`ContractError`] on failure if the current block time is past the specified deadline.
+/// ## Params
+/// * **env** is an object of type [`Env`].
+///
+/// * **deadline** is an [`Option`] field of type [`u64`]. This is the block time (in seconds) after which
+/// the operation fails.
+pub fn assert_deadline(env: &Env, deadline: Option<u64>) -> Result<(), ContractError> {
+    if let Some(deadline) = deadline {
+        if env.block.time.seconds() > deadline {
+            return Err(ContractError::DeadlineExpired {});
+        }
+    }
+
+    Ok(())
+}
+
+/// ## Description
+/// Returns a [`ContractError`] if the factory paused swaps in the pair.
+/// ## Params
+/// * **storage** is a reference to an object implementing [`Storage`].
+fn assert_swaps_allowed(storage: &dyn Storage) -> Result<(), ContractError> {
+    if !FACTORY_PARAMS.load(storage)?.pair_status.swaps_allowed() {
+        return Err(ContractError::SwapsPaused {});
+    }
+
+    Ok(())
+}
+
+/// ## Description
+/// Returns a [`ContractError`] if the factory paused deposits in the pair.
+/// ## Params
+/// * **storage** is a reference to an object implementing [`Storage`].
+fn assert_deposits_allowed(storage: &dyn Storage) -> Result<(), ContractError> {
+    if !FACTORY_PARAMS.load(storage)?.pair_status.deposits_allowed() {
+        return Err(ContractError::DepositsPaused {});
+    }
+
+    Ok(())
+}
+
+/// ## Description
+/// Returns a [`ContractError`] on failure if the amount of any withdrawn asset is less than the specified minimum.
+/// ## Params
+/// * **refund_assets** are an array of [`Asset`] type items. These are the assets withdrawn from the pool.
+///
+/// * **min_assets_out** is an [`Option`] field of type [`Vec<Asset>`]. These are the minimum amounts of
+/// assets to receive.
+fn assert_min_assets_out(
+    refund_assets: &[Asset],
+    min_assets_out: Option<Vec<Asset>>,
+) -> Result<(), ContractError> {
+    for min_asset in min_assets_out.unwrap_or_default() {
+        let refund_asset = refund_assets
+            .iter()
+            .find(|a| a.info.equal(&min_asset.info))
+            .ok_or(ContractError::AssetMismatch {})?;
+        if refund_asset.amount < min_asset.amount {
+            return Err(ContractError::MinAssetsOutAssertion {});
+        }
+    }
+
+    Ok(())
+}
+
 /// ## Description
 /// This is an internal function that enforces slippage tolerance for swaps.
 /// Returns a [`ContractError`] on failure, otherwise returns [`Ok`].
@@ -1478,13 +2014,59 @@ fn assert_slippage_tolerance(
 }
 
 /// ## Description
-/// Used for the contract migration. Returns a default object of type [`Response`].
+/// Updates the fee and Generator information that the pair caches from the factory.
+/// The factory pushes this message to its pairs when their parameters change, but anyone can execute it.
 /// ## Params
-/// * **_deps** is an object of type [`DepsMut`].
+/// * **deps** is an object of type [`DepsMut`].
 ///
-/// * **_env** is an object of type [`Env`].
+/// * **env** is an object of type [`Env`].
+pub fn refresh_factory_params(deps: DepsMut, env: Env) -> Result<Response, ContractError> {
+    let config: Config = CONFIG.load(deps.storage)?;
+    let factory_params = query_factory_params(
+        &deps.querier,
+        config.factory_addr,
+        env.contract.address,
+        config.pair_info.pair_type,
+    )?;
+    FACTORY_PARAMS.save(deps.storage, &factory_params)?;
+
+    let fee_address = factory_params
+        .fee_info
+        .fee_address
+        .map(|addr| addr.to_string())
+        .unwrap_or_default();
+    let generator_address = factory_params
+        .generator_address
+        .map(|addr| addr.to_string())
+        .unwrap_or_default();
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "refresh_factory_params"),
+        attr(
+            "total_fee_rate",
+            factory_params.fee_info.total_fee_rate.to_string(),
+        ),
+        attr(
+            "maker_fee_rate",
+            factory_params.fee_info.maker_fee_rate.to_string(),
+        ),
+        attr("fee_address", fee_address),
+        attr("generator_address", generator_address),
+        attr("pair_status", factory_params.pair_status.to_string()),
+    ]))
+}
+
+/// ## Description
+/// Used for the contract migration. Stableswap pairs are migrated to bLUNA pairs and bLUNA pairs
+/// that were instantiated before the factory parameters were cached start caching them. The pool reserves
+/// are tracked from the current balances.
+/// Returns a [`ContractError`] if the stored contract version can't be migrated.
+/// ## Params
+/// * **deps** is an object of type [`DepsMut`].
+///
+/// * **env** is an object of type [`Env`].
 ///
-/// * **_msg** is an object of type [`MigrateMsg`].
+/// * **msg** is an object of type [`MigrateMsg`].
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn migrate(deps: DepsMut, env: Env, msg: MigrateMsg) -> Result<Response, ContractError> {
     let contract_version = get_contract_version(deps.storage)?;
@@ -1507,6 +2089,15 @@ pub fn migrate(deps: DepsMut, env: Env, msg: MigrateMsg) -> Result<Response, Con
                         &env,
                         &config.factory_addr,
                     )?);
+                save_factory_params(deps.storage, &deps.querier, &env)?;
+                save_reserves_from_balances(deps.storage, &deps.querier, &env)?;
+            }
+            _ => return Err(ContractError::MigrationError {}),
+        },
+        "astroport-pair-stable-bluna" => match contract_version.version.as_ref() {
+            "1.0.1" => {
+                save_factory_params(deps.storage, &deps.querier, &env)?;
+                save_reserves_from_balances(deps.storage, &deps.querier, &env)?;
             }
             _ => return Err(ContractError::MigrationError {}),
         },
@@ -1520,6 +2111,171 @@ pub fn migrate(deps: DepsMut, env: Env, msg: MigrateMsg) -> Result<Response, Con
         .add_attribute("new_contract_version", CONTRACT_VERSION))
 }
 
+/// ## Description
+/// Caches the factory parameters of a pair that was instantiated before they were cached.
+/// ## Params
+/// * **storage** is an object of type [`Storage`].
+///
+/// * **querier** is an object of type [`QuerierWrapper`].
+///
+/// * **env** is an object of type [`Env`].
+fn save_factory_params(
+    storage: &mut dyn Storage,
+    querier: &QuerierWrapper,
+    env: &Env,
+) -> StdResult<()> {
+    let config: Config = CONFIG.load(storage)?;
+    FACTORY_PARAMS.save(
+        storage,
+        &query_factory_params(
+            querier,
+            config.factory_addr,
+            env.contract.address.clone(),
+            config.pair_info.pair_type,
+        )?,
+    )
+}
+
+/// ## Description
+/// Starts tracking the pool reserves of a pair that was instantiated before they were tracked.
+/// ## Params
+/// * **storage** is an object of type [`Storage`].
+///
+/// * **querier** is an object of type [`QuerierWrapper`].
+///
+/// * **env** is an object of type [`Env`].
+fn save_reserves_from_balances(
+    storage: &mut dyn Storage,
+    querier: &QuerierWrapper,
+    env: &Env,
+) -> StdResult<()> {
+    let config: Config = CONFIG.load(storage)?;
+    let pools = config
+        .pair_info
+        .query_pools(querier, env.contract.address.clone())?;
+    RESERVES.save(storage, &[pools[0].amount, pools[1].amount])
+}
+
+/// ## Description
+/// Returns the amount of LP tokens minted for a deposit in an object of type [`SimulateProvideResponse`].
+/// The share is calculated in the same way as in [`provide_liquidity`]. The pool doesn't charge
+/// imbalance fees, so the returned fees are always zero.
+/// ## Params
+/// * **deps** is an object of type [`Deps`].
+///
+/// * **env** is an object of type [`Env`].
+///
+/// * **assets** is an array with two objects of type [`Asset`]. These are the assets to deposit.
+///
+/// * **slippage_tolerance** is an [`Option`] field of type [`Decimal`]. It is used to specify how much
+/// the pool price can move until the provide liquidity transaction goes through.
+pub fn query_simulate_provide(
+    deps: Deps,
+    env: Env,
+    assets: [Asset; 2],
+    slippage_tolerance: Option<Decimal>,
+) -> StdResult<SimulateProvideResponse> {
+    let config: Config = CONFIG.load(deps.storage)?;
+    let (pools, total_share) = pool_info(deps, config.clone())?;
+    let deposits = get_deposits(&assets, &pools)?;
+
+    let token_precisions = [
+        query_token_precision(&deps.querier, pools[0].info.clone())?,
+        query_token_precision(&deps.querier, pools[1].info.clone())?,
+    ];
+    let rates = get_rates(deps, &env, &pools)?;
+    let share = calculate_share(
+        deps,
+        &env,
+        &config,
+        &deposits,
+        &pools,
+        token_precisions,
+        rates,
+        total_share,
+        slippage_tolerance,
+    )?;
+
+    Ok(SimulateProvideResponse {
+        share,
+        fees: pools
+            .iter()
+            .map(|pool| Asset {
+                info: pool.info.clone(),
+                amount: Uint128::zero(),
+            })
+            .collect(),
+    })
+}
+
+/// ## Description
+/// Returns the amounts of assets received for burning LP tokens in a [`Vec<Asset>`] object.
+/// The amounts are calculated in the same way as in [`withdraw_liquidity`].
+/// ## Params
+/// * **deps** is an object of type [`Deps`].
+///
+/// * **lp_amount** is an object of type [`Uint128`]. This is the amount of LP tokens to burn.
+pub fn query_simulate_withdraw(deps: Deps, lp_amount: Uint128) -> StdResult<Vec<Asset>> {
+    let config: Config = CONFIG.load(deps.storage)?;
+    let (pools, total_share) = pool_info(deps, config)?;
+
+    Ok(get_share_in_assets(&pools, lp_amount, total_share).to_vec())
+}
+
+/// ## Description
+/// Returns the tracked reserves of the pool assets in the order of the pair asset infos.
+/// ## Params
+/// * **storage** is an object of type [`Storage`].
+///
+/// * **config** is an object of type [`Config`].
+pub fn query_reserves(storage: &dyn Storage, config: &Config) -> StdResult<[Asset; 2]> {
+    let reserves = RESERVES.load(storage)?;
+
+    Ok([
+        Asset {
+            info: config.pair_info.asset_infos[0].clone(),
+            amount: reserves[0],
+        },
+        Asset {
+            info: config.pair_info.asset_infos[1].clone(),
+            amount: reserves[1],
+        },
+    ])
+}
+
+/// ## Description
+/// Updates the tracked pool reserves after a swap: the offer amount is added to the offer pool and
+/// the amount that left the pool is subtracted from the ask pool.
+/// ## Params
+/// * **storage** is an object of type [`Storage`].
+///
+/// * **pools** are the tracked reserves before the swap.
+///
+/// * **offer_info** is an object of type [`AssetInfo`]. This is the offer asset of the swap.
+///
+/// * **offer_amount** is an object of type [`Uint128`]. This is the amount of offer assets added to the pool.
+///
+/// * **ask_amount** is an object of type [`Uint128`]. This is the amount of ask assets that left the pool,
+/// including the Maker fee.
+fn save_reserves_after_swap(
+    storage: &mut dyn Storage,
+    pools: &[Asset; 2],
+    offer_info: &AssetInfo,
+    offer_amount: Uint128,
+    ask_amount: Uint128,
+) -> StdResult<()> {
+    let mut reserves = [pools[0].amount, pools[1].amount];
+    for (reserve, pool) in reserves.iter_mut().zip(pools.iter()) {
+        if pool.info.equal(offer_info) {
+            *reserve = reserve.checked_add(offer_amount)?;
+        } else {
+            *reserve = reserve.checked_sub(ask_amount)?;
+        }
+    }
+
+    RESERVES.save(storage, &reserves)
+}
+
 /// ## Description
 /// Returns information about the pool.
 /// ## Params
@@ -1527,8 +2283,7 @@ pub fn migrate(deps: DepsMut, env: Env, msg: MigrateMsg) -> Result<Response, Con
 ///
 /// * **config** is an object of type [`Config`].
 pub fn pool_info(deps: Deps, config: Config) -> StdResult<([Asset; 2], Uint128)> {
-    let contract_addr = config.pair_info.contract_addr.clone();
-    let pools: [Asset; 2] = config.pair_info.query_pools(&deps.querier, contract_addr)?;
+    let pools = query_reserves(deps.storage, &config)?;
     let total_share: Uint128 = query_supply(&deps.querier, config.pair_info.liquidity_token)?;
 
     Ok((pools, total_share))
@@ -1572,11 +2327,218 @@ pub fn update_config(
                 Ok(cfg)
             })?;
         }
+        StablePoolUpdateParams::RateProvider { rate_provider } => {
+            set_rate_provider(deps, env, &config, rate_provider)?
+        }
+        StablePoolUpdateParams::RewardSource { reward_source } => {
+            set_reward_source(deps, &config, reward_source)?
+        }
+        StablePoolUpdateParams::SetObservationsSize { size } => {
+            if size == 0 || size > MAX_OBSERVATIONS_SIZE {
+                return Err(ContractError::InvalidObservationsSize {});
+            }
+            set_observations_size(deps.storage, size)?
+        }
     }
 
     Ok(Response::default())
 }
 
+/// ## Description
+/// Sets the rate provider of the pool's yield-bearing asset or removes it if `rate_provider` is not set.
+/// The new provider is queried right away, so the pool never uses a rate from the previous provider.
+/// Returns a [`ContractError`] on failure, otherwise returns [`Ok`].
+/// ## Params
+/// * **deps** is an object of type [`DepsMut`].
+///
+/// * **env** is an object of type [`Env`].
+///
+/// * **config** is an object of type [`Config`].
+///
+/// * **rate_provider** is an [`Option`] field of type [`RateProviderParams`]. This is the new rate provider.
+fn set_rate_provider(
+    deps: DepsMut,
+    env: Env,
+    config: &Config,
+    rate_provider: Option<RateProviderParams>,
+) -> Result<(), ContractError> {
+    EXCHANGE_RATE.remove(deps.storage);
+
+    match rate_provider {
+        Some(params) => {
+            if !config
+                .pair_info
+                .asset_infos
+                .iter()
+                .any(|asset_info| asset_info.equal(&params.asset_info))
+            {
+                return Err(ContractError::AssetMismatch {});
+            }
+
+            let rate_provider = RateProvider {
+                address: addr_validate_to_lower(deps.api, &params.address)?,
+                asset_info: params.asset_info,
+                max_rate_age: params.max_rate_age,
+            };
+            let exchange_rate = load_exchange_rate(deps.as_ref(), &env, &rate_provider)?;
+
+            RATE_PROVIDER.save(deps.storage, &rate_provider)?;
+            EXCHANGE_RATE.save(deps.storage, &exchange_rate)?;
+        }
+        None => RATE_PROVIDER.remove(deps.storage),
+    }
+
+    Ok(())
+}
+
+/// ## Description
+/// Sets a custom reward source or removes it if `reward_source` is not set, so the pool claims bLUNA
+/// rewards from the bLUNA rewarder. Returns a [`ContractError`] on failure, otherwise returns [`Ok`].
+///
+/// The claim message is sent by the pair, so the source can't be a pool token or the LP token
+/// because the message could transfer the pool reserves.
+/// ## Params
+/// * **deps** is an object of type [`DepsMut`].
+///
+/// * **config** is an object of type [`Config`].
+///
+/// * **reward_source** is an [`Option`] field of type [`RewardSourceParams`]. This is the new reward source.
+fn set_reward_source(
+    deps: DepsMut,
+    config: &Config,
+    reward_source: Option<RewardSourceParams>,
+) -> Result<(), ContractError> {
+    match reward_source {
+        Some(params) => {
+            let contract = addr_validate_to_lower(deps.api, &params.contract)?;
+            let is_pool_token = config.pair_info.asset_infos.iter().any(|asset_info| {
+                matches!(asset_info, AssetInfo::Token { contract_addr } if *contract_addr == contract)
+            });
+            if is_pool_token || contract == config.pair_info.liquidity_token {
+                return Err(ContractError::InvalidRewardSource {});
+            }
+
+            if params.reward_assets.is_empty() {
+                return Err(ContractError::InvalidRewardAssets {});
+            }
+            for (i, asset_info) in params.reward_assets.iter().enumerate() {
+                asset_info.check(deps.api)?;
+                if params.reward_assets[..i]
+                    .iter()
+                    .chain(config.pair_info.asset_infos.iter())
+                    .any(|other| other.equal(asset_info))
+                {
+                    return Err(ContractError::InvalidRewardAssets {});
+                }
+            }
+
+            REWARD_SOURCE.save(
+                deps.storage,
+                &RewardSource {
+                    contract,
+                    claim_msg: params.claim_msg,
+                    reward_assets: params.reward_assets,
+                },
+            )?;
+        }
+        None => REWARD_SOURCE.remove(deps.storage),
+    }
+
+    Ok(())
+}
+
+/// ## Description
+/// Returns the exchange rate of the yield-bearing asset. The cached rate is used while it's not older than
+/// the maximum rate age, otherwise the rate is queried from the rate provider.
+/// ## Params
+/// * **deps** is an object of type [`Deps`].
+///
+/// * **env** is an object of type [`Env`].
+///
+/// * **rate_provider** is an object of type [`RateProvider`].
+fn load_exchange_rate(
+    deps: Deps,
+    env: &Env,
+    rate_provider: &RateProvider,
+) -> Result<CachedRate, ContractError> {
+    let block_time = env.block.time.seconds();
+
+    if let Some(cached_rate) = EXCHANGE_RATE.may_load(deps.storage)? {
+        if block_time
+            <= cached_rate
+                .updated_at
+                .saturating_add(rate_provider.max_rate_age)
+        {
+            return Ok(cached_rate);
+        }
+    }
+
+    let response: ExchangeRateResponse = deps.querier.query_wasm_smart(
+        &rate_provider.address,
+        &RateProviderQueryMsg::ExchangeRate {
+            asset_info: rate_provider.asset_info.clone(),
+        },
+    )?;
+    if response.rate.is_zero() {
+        return Err(ContractError::InvalidExchangeRate {});
+    }
+
+    Ok(CachedRate {
+        rate: response.rate,
+        updated_at: block_time,
+    })
+}
+
+/// ## Description
+/// Queries the exchange rate from the rate provider and caches it if the cached rate is older than
+/// the maximum rate age. Returns a [`ContractError`] on failure.
+/// ## Params
+/// * **deps** is an object of type [`DepsMut`].
+///
+/// * **env** is an object of type [`Env`].
+fn refresh_exchange_rate(deps: DepsMut, env: &Env) -> Result<(), ContractError> {
+    if let Some(rate_provider) = RATE_PROVIDER.may_load(deps.storage)? {
+        let exchange_rate = load_exchange_rate(deps.as_ref(), env, &rate_provider)?;
+        EXCHANGE_RATE.save(deps.storage, &exchange_rate)?;
+    }
+
+    Ok(())
+}
+
+/// ## Description
+/// Returns the exchange rates that scale the pool balances in the order of the pool assets.
+/// The rate of an asset without a rate provider is 1.
+/// ## Params
+/// * **deps** is an object of type [`Deps`].
+///
+/// * **env** is an object of type [`Env`].
+///
+/// * **pools** is a slice of [`Asset`] type items. These are the assets available in the pool.
+fn get_rates(deps: Deps, env: &Env, pools: &[Asset]) -> Result<[Decimal; 2], ContractError> {
+    let mut rates = [Decimal::one(); 2];
+
+    if let Some(rate_provider) = RATE_PROVIDER.may_load(deps.storage)? {
+        let exchange_rate = load_exchange_rate(deps, env, &rate_provider)?;
+        for (pool, rate) in pools.iter().zip(rates.iter_mut()) {
+            if pool.info.equal(&rate_provider.asset_info) {
+                *rate = exchange_rate.rate;
+            }
+        }
+    }
+
+    Ok(rates)
+}
+
+/// ## Description
+/// Converts an amount scaled by an exchange rate back to the amount of the asset.
+/// ## Params
+/// * **amount** is an object of type [`Uint128`]. This is the scaled amount.
+///
+/// * **rate** is an object of type [`Decimal`]. This is the exchange rate of the asset.
+fn unscale_by_rate(amount: Uint128, rate: Decimal) -> Uint128 {
+    amount.multiply_ratio(DECIMAL_FRACTIONAL, Uint128::new(DECIMAL_FRACTIONAL) * rate)
+}
+
 /// ## Description
 /// Start changing the AMP value. Returns a [`ContractError`] on failure, otherwise returns [`Ok`].
 /// ## Params
@@ -1715,7 +2677,102 @@ fn get_bluna_reward_holder_instantiating_message(
 }
 
 /// ## Description
-/// Returns a vector of messages that are meant to claim bLUNA rewards for a specific user.
+/// Returns the reward source of the pool. If no custom source is set, bLUNA `uusd` rewards are claimed
+/// from the bLUNA rewarder.
+/// ## Params
+/// * **deps** is an object of type [`Deps`].
+///
+/// * **config** is an object of type [`Config`].
+fn load_reward_source(deps: Deps, config: &Config) -> StdResult<RewardSource> {
+    if let Some(reward_source) = REWARD_SOURCE.may_load(deps.storage)? {
+        return Ok(reward_source);
+    }
+
+    Ok(RewardSource {
+        contract: config.bluna_rewarder.clone(),
+        claim_msg: to_binary(&anchor_basset::reward::ExecuteMsg::ClaimRewards {
+            recipient: Some(BLUNA_REWARD_HOLDER.load(deps.storage)?.to_string()),
+        })?,
+        reward_assets: vec![AssetInfo::NativeToken {
+            denom: "uusd".to_string(),
+        }],
+    })
+}
+
+/// ## Description
+/// Returns true if the reward asset is the bLUNA `uusd` reward whose indexes are stored in the bLUNA index storage.
+/// ## Params
+/// * **asset_info** is an object of type [`AssetInfo`].
+fn is_bluna_reward(asset_info: &AssetInfo) -> bool {
+    matches!(asset_info, AssetInfo::NativeToken { denom } if denom == "uusd")
+}
+
+/// ## Description
+/// Returns the global reward index of a reward asset.
+/// ## Params
+/// * **storage** is a reference to an object implementing [`Storage`].
+///
+/// * **asset_info** is an object of type [`AssetInfo`]. This is the reward asset.
+fn load_reward_global_index(
+    storage: &dyn Storage,
+    asset_info: &AssetInfo,
+) -> StdResult<cosmwasm_std::Decimal256> {
+    let global_index = if is_bluna_reward(asset_info) {
+        BLUNA_REWARD_GLOBAL_INDEX.may_load(storage)?
+    } else {
+        REWARD_GLOBAL_INDEXES.may_load(storage, asset_info.as_bytes())?
+    };
+
+    Ok(global_index.unwrap_or_default())
+}
+
+/// ## Description
+/// Returns the reward index of a user for a reward asset.
+/// ## Params
+/// * **storage** is a reference to an object implementing [`Storage`].
+///
+/// * **user** is an object of type [`Addr`].
+///
+/// * **asset_info** is an object of type [`AssetInfo`]. This is the reward asset.
+fn load_reward_user_index(
+    storage: &dyn Storage,
+    user: &Addr,
+    asset_info: &AssetInfo,
+) -> StdResult<Option<cosmwasm_std::Decimal256>> {
+    if is_bluna_reward(asset_info) {
+        BLUNA_REWARD_USER_INDEXES.may_load(storage, user)
+    } else {
+        REWARD_USER_INDEXES.may_load(storage, (user, asset_info.as_bytes()))
+    }
+}
+
+/// ## Description
+/// Saves the global reward index of a reward asset as both the global and the user index.
+/// ## Params
+/// * **storage** is a mutable reference to an object implementing [`Storage`].
+///
+/// * **user** is an object of type [`Addr`].
+///
+/// * **asset_info** is an object of type [`AssetInfo`]. This is the reward asset.
+///
+/// * **global_index** is an object of type [`Decimal256`]. This is the new global reward index.
+fn save_reward_indexes(
+    storage: &mut dyn Storage,
+    user: &Addr,
+    asset_info: &AssetInfo,
+    global_index: &cosmwasm_std::Decimal256,
+) -> StdResult<()> {
+    if is_bluna_reward(asset_info) {
+        BLUNA_REWARD_GLOBAL_INDEX.save(storage, global_index)?;
+        BLUNA_REWARD_USER_INDEXES.save(storage, user, global_index)
+    } else {
+        REWARD_GLOBAL_INDEXES.save(storage, asset_info.as_bytes(), global_index)?;
+        REWARD_USER_INDEXES.save(storage, (user, asset_info.as_bytes()), global_index)
+    }
+}
+
+/// ## Description
+/// Returns a vector of messages that are meant to claim the pool rewards for a specific user.
 /// Returns a [`ContractError`] on failure, otherwise returns the vector that contains objects
 /// of type [`CosmosMsg`].
 /// ## Params
@@ -1723,46 +2780,49 @@ fn get_bluna_reward_holder_instantiating_message(
 ///
 /// * **env** is an object of type [`Env`].
 ///
-/// * **bluna_rewarder** is an object of type [`str`]. This is the contract that distributes bLUNA rewards.
+/// * **reward_source** is an object of type [`RewardSource`]. This is the source to claim the rewards from.
 ///
 /// * **user** is an object of type [`Addr`]. This is the address for which we return messages that are meant to claim rewards.
 ///
-/// * **user_share** is an object of type [`Uint128`]. This is the share of the bLUNA rewards that the `user` is entitled to.
+/// * **user_share** is an object of type [`Uint128`]. This is the share of the rewards that the `user` is entitled to.
 ///
-/// * **total_share** is an object of type [`Uint128`]. This is the total amount of bLUNA rewards entitled to bLUNA LPs.
+/// * **total_share** is an object of type [`Uint128`]. This is the total amount of rewards entitled to LPs.
 ///
-/// * **receiver** is an object of type [`Option<Addr>`]. This is a custom address that can receive the bLUNA rewards.
+/// * **receiver** is an object of type [`Option<Addr>`]. This is a custom address that can receive the rewards.
 /// If it's not present, the function defaults to the `user`.
-fn get_bluna_reward_handling_messages(
+fn get_reward_handling_messages(
     deps: Deps,
     env: &Env,
-    bluna_rewarder: &str,
+    reward_source: RewardSource,
     user: Addr,
     user_share: Uint128,
     total_share: Uint128,
     receiver: Option<Addr>,
 ) -> Result<Vec<CosmosMsg>, ContractError> {
-    let bluna_reward_holder = BLUNA_REWARD_HOLDER.load(deps.storage)?;
-
-    let reward_balance = astroport::querier::query_balance(
-        &deps.querier,
-        bluna_reward_holder.clone(),
-        "uusd".to_string(),
-    )?;
+    let reward_holder = BLUNA_REWARD_HOLDER.load(deps.storage)?;
+
+    let previous_reward_balances = reward_source
+        .reward_assets
+        .into_iter()
+        .map(|info| {
+            Ok(Asset {
+                amount: info.query_pool(&deps.querier, reward_holder.clone())?,
+                info,
+            })
+        })
+        .collect::<StdResult<Vec<Asset>>>()?;
 
     Ok(vec![
         CosmosMsg::Wasm(WasmMsg::Execute {
-            contract_addr: bluna_rewarder.to_string(),
-            msg: to_binary(&anchor_basset::reward::ExecuteMsg::ClaimRewards {
-                recipient: Some(bluna_reward_holder.to_string()),
-            })?,
+            contract_addr: reward_source.contract.to_string(),
+            msg: reward_source.claim_msg,
             funds: vec![],
         }),
         CosmosMsg::Wasm(WasmMsg::Execute {
             contract_addr: env.contract.address.to_string(),
             funds: vec![],
             msg: to_binary(&ExecuteMsg::HandleReward {
-                previous_reward_balance: reward_balance,
+                previous_reward_balances,
                 user,
                 user_share,
                 total_share,
@@ -1773,7 +2833,7 @@ fn get_bluna_reward_handling_messages(
 }
 
 /// ## Description
-/// Claims bLUNA rewards on behalf of the function caller and sends them to the specified receiver.
+/// Claims the pool rewards on behalf of the function caller and sends them to the specified receiver.
 /// Returns A [`ContractError`] on failure, otherwise returns A [`Response`] with the specified attributes if the operation was successful.
 /// ## Params
 /// * **deps** is an object of type [`Deps`].
@@ -1782,7 +2842,7 @@ fn get_bluna_reward_handling_messages(
 ///
 /// * **info** is an object of type [`MessageInfo`].
 ///
-/// * **receiver** is an object of type [`Option<String>`]. This is the address that receives the bLUNA rewards.
+/// * **receiver** is an object of type [`Option<String>`]. This is the address that receives the rewards.
 fn claim_reward(
     deps: DepsMut,
     env: Env,
@@ -1814,21 +2874,19 @@ fn claim_reward(
         },
     )?;
 
-    Ok(
-        Response::new().add_messages(get_bluna_reward_handling_messages(
-            deps.as_ref(),
-            &env,
-            config.bluna_rewarder.as_str(),
-            info.sender,
-            user_share,
-            pool_info.lp_supply,
-            receiver,
-        )?),
-    )
+    Ok(Response::new().add_messages(get_reward_handling_messages(
+        deps.as_ref(),
+        &env,
+        load_reward_source(deps.as_ref(), &config)?,
+        info.sender,
+        user_share,
+        pool_info.lp_supply,
+        receiver,
+    )?))
 }
 
 /// ## Description
-/// Claims bLUNA rewards on behalf of stakers that deposited their LP tokens in the Generator contract.
+/// Claims the pool rewards on behalf of stakers that deposited their LP tokens in the Generator contract.
 /// Returns a [`ContractError`] on failure, otherwise returns a [`Response`] with the
 /// specified attributes if the operation was successful.
 /// ## Params
@@ -1839,11 +2897,11 @@ fn claim_reward(
 /// * **info** is an object of type [`MessageInfo`].
 ///
 /// * **user** is an object of type [`String`].
-/// This is the user for which to claim bLUNA rewards that were accrued by the Generator on their behalf.
+/// This is the user for which to claim the rewards that were accrued by the Generator on their behalf.
 ///
-/// * **user_share** is an object of type [`Uint128`]. This is the user's share of bLUNA rewards.
+/// * **user_share** is an object of type [`Uint128`]. This is the user's share of the rewards.
 ///
-/// * **total_share** is an object of type [`Uint128`]. This is the total share of bLUNA rewards that need to be distributed.
+/// * **total_share** is an object of type [`Uint128`]. This is the total share of the rewards that need to be distributed.
 fn claim_reward_by_generator(
     deps: DepsMut,
     env: Env,
@@ -1860,21 +2918,19 @@ fn claim_reward_by_generator(
         return Err(StdError::generic_err("Only the generator can use this method!").into());
     }
 
-    Ok(
-        Response::new().add_messages(get_bluna_reward_handling_messages(
-            deps.as_ref(),
-            &env,
-            config.bluna_rewarder.as_str(),
-            user,
-            user_share,
-            total_share,
-            None,
-        )?),
-    )
+    Ok(Response::new().add_messages(get_reward_handling_messages(
+        deps.as_ref(),
+        &env,
+        load_reward_source(deps.as_ref(), &config)?,
+        user,
+        user_share,
+        total_share,
+        None,
+    )?))
 }
 
 /// ## Description
-/// Handles and distributes bLUNA rewards.
+/// Handles and distributes the pool rewards. Every reward asset has its own global and user indexes.
 /// Returns a [`ContractError`] on failure, otherwise returns a [`Response`] with the
 /// specified attributes if the operation was successful.
 /// ## Params
@@ -1884,22 +2940,22 @@ fn claim_reward_by_generator(
 ///
 /// * **info** is an object of type [`MessageInfo`].
 ///
-/// * **previous_reward_balance** is an object of type [`Uint128`].
-/// This is the previous bLUNA rewards balance that had to be distributed to LPs.
+/// * **previous_reward_balances** is a vector with items of type [`Asset`].
+/// These are the reward holder balances of the reward assets before the rewards were claimed.
 ///
-/// * **user** is an object of type [`Addr`]. This is the address for which we distribute bLUNA rewards.
+/// * **user** is an object of type [`Addr`]. This is the address for which we distribute the rewards.
 ///
-/// * **user_share** is an object of type [`Uint128`]. This is the user's share of bLUNA rewards.
+/// * **user_share** is an object of type [`Uint128`]. This is the user's share of the rewards.
 ///
-/// * **total_share** is an object of type [`Uint128`]. This is the total share of bLUNA rewards that need to be distributed.
+/// * **total_share** is an object of type [`Uint128`]. This is the total share of the rewards that need to be distributed.
 ///
-/// * **receiver** is an object of type [`Option<Addr>`]. This is the address that will receive bLUNA rewards.
+/// * **receiver** is an object of type [`Option<Addr>`]. This is the address that will receive the rewards.
 #[allow(clippy::too_many_arguments)]
 pub fn handle_reward(
     deps: DepsMut,
     env: Env,
     info: MessageInfo,
-    previous_reward_balance: Uint128,
+    previous_reward_balances: Vec<Asset>,
     user: Addr,
     user_share: Uint128,
     total_share: Uint128,
@@ -1913,46 +2969,49 @@ pub fn handle_reward(
 
     let receiver = receiver.unwrap_or_else(|| user.clone());
 
-    let bluna_reward_holder = BLUNA_REWARD_HOLDER.load(deps.storage)?;
+    let reward_holder = BLUNA_REWARD_HOLDER.load(deps.storage)?;
 
-    let reward_balance = astroport::querier::query_balance(
-        &deps.querier,
-        bluna_reward_holder.clone(),
-        "uusd".to_string(),
-    )?;
+    let mut claimed_rewards: Vec<String> = vec![];
+    let mut sent_rewards: Vec<String> = vec![];
+    let mut transfer_msgs: Vec<CosmosMsg> = vec![];
+    for previous_reward_balance in previous_reward_balances {
+        let reward_info = previous_reward_balance.info;
+        let reward_balance = reward_info.query_pool(&deps.querier, reward_holder.clone())?;
 
-    let bluna_reward_global_index = BLUNA_REWARD_GLOBAL_INDEX
-        .may_load(deps.storage)?
-        .unwrap_or_default();
-    let bluna_reward_user_index = BLUNA_REWARD_USER_INDEXES.may_load(deps.storage, &user)?;
+        let (reward_global_index, latest_reward_amount, user_reward) = calc_user_reward(
+            reward_balance,
+            previous_reward_balance.amount,
+            user_share,
+            total_share,
+            load_reward_global_index(deps.storage, &reward_info)?,
+            load_reward_user_index(deps.storage, &user, &reward_info)?,
+        )?;
 
-    let (bluna_reward_global_index, latest_reward_amount, user_reward) = calc_user_reward(
-        reward_balance,
-        previous_reward_balance,
-        user_share,
-        total_share,
-        bluna_reward_global_index,
-        bluna_reward_user_index,
-    )?;
+        save_reward_indexes(deps.storage, &user, &reward_info, &reward_global_index)?;
+
+        claimed_rewards.push(format!("{}{}", latest_reward_amount, reward_info));
+        sent_rewards.push(format!("{}{}", user_reward, reward_info));
 
-    BLUNA_REWARD_GLOBAL_INDEX.save(deps.storage, &bluna_reward_global_index)?;
-    BLUNA_REWARD_USER_INDEXES.save(deps.storage, &user, &bluna_reward_global_index)?;
+        if !user_reward.is_zero() {
+            transfer_msgs.push(
+                Asset {
+                    info: reward_info,
+                    amount: user_reward,
+                }
+                .into_msg(&deps.querier, receiver.clone())?,
+            );
+        }
+    }
 
     let mut response =
-        Response::new().add_attribute("bluna_claimed_reward_to_pool", latest_reward_amount);
+        Response::new().add_attribute("claimed_reward_to_pool", claimed_rewards.join(", "));
 
-    if !user_reward.is_zero() {
+    if !transfer_msgs.is_empty() {
         response.messages.push(SubMsg::new(WasmMsg::Execute {
-            contract_addr: bluna_reward_holder.to_string(),
+            contract_addr: reward_holder.to_string(),
             funds: vec![],
             msg: to_binary(&ExecuteMsg::Execute {
-                msgs: vec![Asset {
-                    info: AssetInfo::NativeToken {
-                        denom: "uusd".to_string(),
-                    },
-                    amount: user_reward,
-                }
-                .into_msg(&deps.querier, receiver.clone())?],
+                msgs: transfer_msgs,
             })?,
         }));
     }
@@ -1960,11 +3019,11 @@ pub fn handle_reward(
     Ok(response
         .add_attribute("user", user)
         .add_attribute("receiver", receiver)
-        .add_attribute("sent_bluna_reward", user_reward))
+        .add_attribute("sent_reward", sent_rewards.join(", ")))
 }
 
 /// ## Description
-/// Calculate the amount of bLUNA rewards that a user can claim.
+/// Calculate the amount of rewards that a user can claim.
 /// Returns a [`ContractError`] on failure, otherwise returns the tuple values
 /// [bluna_reward_global_index, latest_reward_amount and user_reward].
 /// ## Params
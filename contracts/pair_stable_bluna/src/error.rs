@@ -1,4 +1,6 @@
 use crate::math::{MAX_AMP, MAX_AMP_CHANGE, MIN_AMP_CHANGING_TIME};
+use astroport::observation::MAX_OBSERVATIONS_SIZE;
+use astroport::pair::MINIMUM_LIQUIDITY;
 use cosmwasm_std::{OverflowError, StdError};
 use thiserror::Error;
 
@@ -12,6 +14,9 @@ pub enum ContractError {
     #[error("Unauthorized")]
     Unauthorized {},
 
+    #[error("Operation non supported")]
+    NonSupported {},
+
     #[error("Doubling assets in asset infos")]
     DoublingAssets {},
 
@@ -62,6 +67,39 @@ pub enum ContractError {
 
     #[error("Contract can't be migrated!")]
     MigrationError {},
+
+    #[error("Transaction deadline has expired")]
+    DeadlineExpired {},
+
+    #[error("Swaps are paused in the pair")]
+    SwapsPaused {},
+
+    #[error("Deposits are paused in the pair")]
+    DepositsPaused {},
+
+    #[error("Return amount is less than min_return")]
+    MinReturnAssertion {},
+
+    #[error("Minted LP amount is less than min_lp_to_receive")]
+    MinLpToReceiveAssertion {},
+
+    #[error("Withdrawn amount is less than min_assets_out")]
+    MinAssetsOutAssertion {},
+
+    #[error("Initial liquidity must be more than {}", MINIMUM_LIQUIDITY)]
+    MinimumLiquidityAmountError {},
+
+    #[error("Observations size must be between 1 and {}", MAX_OBSERVATIONS_SIZE)]
+    InvalidObservationsSize {},
+
+    #[error("The rate provider returned a zero exchange rate")]
+    InvalidExchangeRate {},
+
+    #[error("Reward assets must be non-empty, unique and different from the pool assets")]
+    InvalidRewardAssets {},
+
+    #[error("The reward source can't be a pool token or the LP token")]
+    InvalidRewardSource {},
 }
 
 impl From<OverflowError> for ContractError {
@@ -69,3 +107,12 @@ impl From<OverflowError> for ContractError {
         StdError::from(o).into()
     }
 }
+
+impl From<ContractError> for StdError {
+    fn from(err: ContractError) -> Self {
+        match err {
+            ContractError::Std(err) => err,
+            err => StdError::generic_err(err.to_string()),
+        }
+    }
+}
@@ -5,8 +5,9 @@ use cosmwasm_std::{
 };
 use std::collections::HashMap;
 
-use astroport::factory::QueryMsg::{Config, FeeInfo};
-use astroport::factory::{ConfigResponse, FeeInfoResponse};
+use astroport::factory::QueryMsg::{Config, FeeInfo, PairStatus as PairStatusQuery};
+use astroport::factory::{ConfigResponse, FeeInfoResponse, PairStatus};
+use astroport::pair_stable_bluna::{ExchangeRateResponse, RateProviderQueryMsg};
 use cw20::{BalanceResponse, Cw20QueryMsg, TokenInfoResponse};
 use terra_cosmwasm::{TaxCapResponse, TaxRateResponse, TerraQuery, TerraQueryWrapper, TerraRoute};
 
@@ -29,6 +30,7 @@ pub struct WasmMockQuerier {
     base: MockQuerier<TerraQueryWrapper>,
     token_querier: TokenQuerier,
     tax_querier: TaxQuerier,
+    exchange_rate: Decimal,
 }
 
 #[derive(Clone, Default)]
@@ -136,6 +138,7 @@ impl WasmMockQuerier {
                                 fee_address: Some(Addr::unchecked("fee_address")),
                                 total_fee_bps: 30,
                                 maker_fee_bps: 1660,
+                                maker_fee_as_lp: false,
                             })
                             .into(),
                         ),
@@ -147,11 +150,24 @@ impl WasmMockQuerier {
                                 fee_address: Some(Addr::unchecked("fee_address")),
                                 generator_address: Some(Addr::unchecked("gen_address")),
                                 whitelist_code_id: 666,
+                                guardian: None,
                             })
                             .into(),
                         ),
+                        PairStatusQuery { .. } => {
+                            SystemResult::Ok(to_binary(&PairStatus::Active).into())
+                        }
                         _ => panic!("DO NOT ENTER HERE"),
                     }
+                } else if contract_addr == "rate_provider" {
+                    match from_binary(&msg).unwrap() {
+                        RateProviderQueryMsg::ExchangeRate { .. } => SystemResult::Ok(
+                            to_binary(&ExchangeRateResponse {
+                                rate: self.exchange_rate,
+                            })
+                            .into(),
+                        ),
+                    }
                 } else {
                     match from_binary(&msg).unwrap() {
                         Cw20QueryMsg::TokenInfo {} => {
@@ -214,6 +230,7 @@ impl WasmMockQuerier {
             base,
             token_querier: TokenQuerier::default(),
             tax_querier: TaxQuerier::default(),
+            exchange_rate: Decimal::one(),
         }
     }
 
@@ -227,6 +244,11 @@ impl WasmMockQuerier {
         self.tax_querier = TaxQuerier::new(rate, caps);
     }
 
+    // Configure the exchange rate returned by the rate provider
+    pub fn with_exchange_rate(&mut self, rate: Decimal) {
+        self.exchange_rate = rate;
+    }
+
     pub fn with_balance(&mut self, balances: &[(&String, &[Coin])]) {
         for (addr, balance) in balances {
             self.base.update_balance(addr.to_string(), balance.to_vec());
@@ -1,5 +1,7 @@
 use astroport::asset::PairInfo;
-use cosmwasm_std::{Addr, Decimal256, Uint128};
+use astroport::pair_stable_bluna::{RateProvider, RewardSource};
+use astroport::querier::FactoryParams;
+use cosmwasm_std::{Addr, Decimal, Decimal256, Uint128};
 use cw_storage_plus::{Item, Map};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
@@ -37,3 +39,38 @@ pub const CONFIG: Item<Config> = Item::new("config");
 pub const BLUNA_REWARD_HOLDER: Item<Addr> = Item::new("bluna_reward_holder");
 pub const BLUNA_REWARD_GLOBAL_INDEX: Item<Decimal256> = Item::new("bluna_reward_global_index");
 pub const BLUNA_REWARD_USER_INDEXES: Map<&Addr, Decimal256> = Map::new("bluna_reward_user_indexes");
+
+/// Stores the custom reward source of the pool. The bLUNA rewarder from the config is used if it's not set
+pub const REWARD_SOURCE: Item<RewardSource> = Item::new("reward_source");
+
+/// Stores the global reward indexes by the reward asset. The bLUNA `uusd` reward index is stored in
+/// [`BLUNA_REWARD_GLOBAL_INDEX`]
+pub const REWARD_GLOBAL_INDEXES: Map<&[u8], Decimal256> = Map::new("reward_global_indexes");
+
+/// Stores the user reward indexes by the user and the reward asset. The bLUNA `uusd` reward indexes are
+/// stored in [`BLUNA_REWARD_USER_INDEXES`]
+pub const REWARD_USER_INDEXES: Map<(&Addr, &[u8]), Decimal256> = Map::new("reward_user_indexes");
+
+/// Stores the tracked pool reserves in the order of the pair asset infos. Assets sent to the pair
+/// directly are not counted until they are synced
+pub const RESERVES: Item<[Uint128; 2]> = Item::new("reserves");
+
+/// ## Description
+/// Stores the fee and Generator information cached from the factory
+pub const FACTORY_PARAMS: Item<FactoryParams> = Item::new("factory_params");
+
+/// Stores the rate provider of the pool's yield-bearing asset
+pub const RATE_PROVIDER: Item<RateProvider> = Item::new("rate_provider");
+
+/// ## Description
+/// This structure stores the exchange rate cached from the rate provider.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct CachedRate {
+    /// The exchange rate of the yield-bearing asset
+    pub rate: Decimal,
+    /// The block time (in seconds) when the rate was queried
+    pub updated_at: u64,
+}
+
+/// Stores the exchange rate cached from the rate provider
+pub const EXCHANGE_RATE: Item<CachedRate> = Item::new("exchange_rate");
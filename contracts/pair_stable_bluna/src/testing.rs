@@ -1,25 +1,29 @@
 use crate::contract::{
-    accumulate_prices, assert_max_spread, calc_user_reward, execute, instantiate, query_pair_info,
-    query_pool, query_reverse_simulation, query_share, query_simulation, reply,
+    accumulate_prices, assert_max_spread, calc_user_reward, execute, instantiate, query,
+    query_config, query_pair_info, query_pool, query_reverse_simulation, query_share,
+    query_simulation, reply,
 };
 use crate::error::ContractError;
 use crate::math::{calc_ask_amount, calc_offer_amount, AMP_PRECISION};
 use crate::mock_querier::mock_dependencies;
 use crate::response::MsgInstantiateContractResponse;
-use crate::state::Config;
+use crate::state::{Config, RESERVES};
 use astroport::asset::{Asset, AssetInfo, PairInfo};
 use astroport::pair::{
-    Cw20HookMsg, InstantiateMsg, PoolResponse, ReverseSimulationResponse, SimulationResponse,
-    TWAP_PRECISION,
+    CumulativePricesResponse, Cw20HookMsg, InstantiateMsg, ObserveResponse, PoolResponse,
+    ReverseSimulationResponse, SimulationResponse, MINIMUM_LIQUIDITY, TWAP_PRECISION,
+};
+use astroport::pair_stable_bluna::{
+    ExecuteMsg, QueryMsg, RateProvider, RateProviderParams, RewardSource, RewardSourceParams,
+    StablePoolConfig, StablePoolParams, StablePoolUpdateParams,
 };
-use astroport::pair_stable_bluna::{ExecuteMsg, StablePoolParams};
 use astroport::token::InstantiateMsg as TokenInstantiateMsg;
 use astroport::whitelist::InstantiateMsg as WhitelistInstantiateMsg;
 use cosmwasm_std::testing::{mock_env, mock_info, MOCK_CONTRACT_ADDR};
 use cosmwasm_std::{
-    attr, to_binary, Addr, BankMsg, BlockInfo, Coin, ContractResult, CosmosMsg, Decimal,
-    Decimal256, DepsMut, Env, Reply, ReplyOn, StdError, SubMsg, SubMsgExecutionResponse, Timestamp,
-    Uint128, WasmMsg,
+    attr, from_binary, to_binary, Addr, BankMsg, BlockInfo, Coin, ContractResult, CosmosMsg,
+    Decimal, Decimal256, DepsMut, Empty, Env, Reply, ReplyOn, StdError, SubMsg,
+    SubMsgExecutionResponse, Timestamp, Uint128, Uint256, WasmMsg,
 };
 use cw20::{Cw20ExecuteMsg, Cw20ReceiveMsg, MinterResponse};
 use protobuf::Message;
@@ -46,6 +50,10 @@ fn store_liquidity_token(deps: DepsMut, msg_id: u64, contract_addr: String) {
     reply(deps, mock_env(), reply_msg.clone()).unwrap();
 }
 
+fn store_reserves(deps: DepsMut, reserves: [Uint128; 2]) {
+    RESERVES.save(deps.storage, &reserves).unwrap();
+}
+
 #[test]
 fn proper_initialization() {
     let mut deps = mock_dependencies(&[]);
@@ -216,6 +224,8 @@ fn provide_liquidity() {
         slippage_tolerance: None,
         auto_stake: None,
         receiver: None,
+        min_lp_to_receive: None,
+        deadline: None,
     };
 
     let env = mock_env();
@@ -228,7 +238,8 @@ fn provide_liquidity() {
     );
     let res = execute(deps.as_mut(), env.clone().clone(), info, msg).unwrap();
     let transfer_from_msg = res.messages.get(0).expect("no message");
-    let mint_msg = res.messages.get(1).expect("no message");
+    let mint_min_liquidity_msg = res.messages.get(1).expect("no message");
+    let mint_msg = res.messages.get(2).expect("no message");
     assert_eq!(
         transfer_from_msg,
         &SubMsg {
@@ -248,6 +259,24 @@ fn provide_liquidity() {
             reply_on: ReplyOn::Never
         }
     );
+    assert_eq!(
+        mint_min_liquidity_msg,
+        &SubMsg {
+            msg: WasmMsg::Execute {
+                contract_addr: String::from("liquidity0000"),
+                msg: to_binary(&Cw20ExecuteMsg::Mint {
+                    recipient: String::from(MOCK_CONTRACT_ADDR),
+                    amount: MINIMUM_LIQUIDITY,
+                })
+                .unwrap(),
+                funds: vec![],
+            }
+            .into(),
+            id: 0,
+            gas_limit: None,
+            reply_on: ReplyOn::Never,
+        }
+    );
     assert_eq!(
         mint_msg,
         &SubMsg {
@@ -255,7 +284,7 @@ fn provide_liquidity() {
                 contract_addr: String::from("liquidity0000"),
                 msg: to_binary(&Cw20ExecuteMsg::Mint {
                     recipient: String::from("addr0000"),
-                    amount: Uint128::from(100_000000000000000000u128),
+                    amount: Uint128::from(100_000000000000000000u128) - MINIMUM_LIQUIDITY,
                 })
                 .unwrap(),
                 funds: vec![],
@@ -286,6 +315,8 @@ fn provide_liquidity() {
         slippage_tolerance: None,
         auto_stake: None,
         receiver: None,
+        min_lp_to_receive: None,
+        deadline: None,
     };
 
     let env = mock_env();
@@ -360,7 +391,11 @@ fn withdraw_liquidity() {
     // Withdraw liquidity
     let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
         sender: String::from("addr0000"),
-        msg: to_binary(&Cw20HookMsg::WithdrawLiquidity {}).unwrap(),
+        msg: to_binary(&Cw20HookMsg::WithdrawLiquidity {
+            min_assets_out: None,
+            deadline: None,
+        })
+        .unwrap(),
         amount: Uint128::new(100u128),
     });
 
@@ -501,6 +536,8 @@ fn try_native_to_token() {
         belief_price: None,
         max_spread: Some(Decimal::percent(50)),
         to: None,
+        min_return: None,
+        deadline: None,
     };
     let env = mock_env_with_block_time(1000);
     let info = mock_info(
@@ -662,6 +699,8 @@ fn try_token_to_native() {
         belief_price: None,
         max_spread: None,
         to: None,
+        min_return: None,
+        deadline: None,
     };
     let env = mock_env_with_block_time(1000);
     let info = mock_info("addr0000", &[]);
@@ -676,6 +715,8 @@ fn try_token_to_native() {
             belief_price: None,
             max_spread: None,
             to: None,
+            min_return: None,
+            deadline: None,
         })
         .unwrap(),
     });
@@ -813,6 +854,8 @@ fn try_token_to_native() {
             belief_price: None,
             max_spread: None,
             to: None,
+            min_return: None,
+            deadline: None,
         })
         .unwrap(),
     });
@@ -822,6 +865,227 @@ fn try_token_to_native() {
     assert_eq!(res, ContractError::Unauthorized {});
 }
 
+#[test]
+fn execution_guards() {
+    let total_share = Uint128::new(20000000000u128);
+    let asset_pool_amount = Uint128::new(20000000000u128);
+    let collateral_pool_amount = Uint128::new(20000000000u128);
+    let offer_amount = Uint128::new(1000000000u128);
+
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: collateral_pool_amount,
+    }]);
+    deps.querier.with_token_balances(&[
+        (
+            &String::from("liquidity0000"),
+            &[(&String::from(MOCK_CONTRACT_ADDR), &total_share)],
+        ),
+        (
+            &String::from("asset0000"),
+            &[(&String::from(MOCK_CONTRACT_ADDR), &asset_pool_amount)],
+        ),
+    ]);
+
+    let msg = InstantiateMsg {
+        asset_infos: [
+            AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            AssetInfo::Token {
+                contract_addr: Addr::unchecked("asset0000"),
+            },
+        ],
+        token_code_id: 10u64,
+        factory_addr: "factory".to_string(),
+        init_params: Some(
+            to_binary(&StablePoolParams {
+                amp: 100,
+                bluna_rewarder: "bluna_rewarder".to_string(),
+                generator: "generator".to_string(),
+            })
+            .unwrap(),
+        ),
+    };
+
+    let env = mock_env_with_block_time(100);
+    let info = mock_info("addr0000", &[]);
+    instantiate(deps.as_mut(), env, info, msg).unwrap();
+    store_liquidity_token(deps.as_mut(), 1, "liquidity0000".to_string());
+    store_reserves(deps.as_mut(), [collateral_pool_amount, asset_pool_amount]);
+
+    let offer_asset = Asset {
+        info: AssetInfo::NativeToken {
+            denom: "uusd".to_string(),
+        },
+        amount: offer_amount,
+    };
+    let simulation_res = query_simulation(
+        deps.as_ref(),
+        mock_env_with_block_time(1000),
+        offer_asset.clone(),
+    )
+    .unwrap();
+
+    deps.querier.with_balance(&[(
+        &String::from(MOCK_CONTRACT_ADDR),
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: collateral_pool_amount + offer_amount,
+        }],
+    )]);
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: offer_amount,
+        }],
+    );
+    let swap_msg = |min_return: Option<Uint128>, deadline: Option<u64>| ExecuteMsg::Swap {
+        offer_asset: offer_asset.clone(),
+        belief_price: None,
+        max_spread: Some(Decimal::percent(50)),
+        to: None,
+        min_return,
+        deadline,
+    };
+
+    // The deadline is in the past
+    let res = execute(
+        deps.as_mut(),
+        mock_env_with_block_time(1000),
+        info.clone(),
+        swap_msg(None, Some(999)),
+    )
+    .unwrap_err();
+    assert_eq!(res, ContractError::DeadlineExpired {});
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env_with_block_time(1000),
+        info.clone(),
+        swap_msg(
+            Some(simulation_res.return_amount + Uint128::new(1u128)),
+            None,
+        ),
+    )
+    .unwrap_err();
+    assert_eq!(res, ContractError::MinReturnAssertion {});
+
+    execute(
+        deps.as_mut(),
+        mock_env_with_block_time(1000),
+        info.clone(),
+        swap_msg(Some(simulation_res.return_amount), Some(1000)),
+    )
+    .unwrap();
+
+    // Provide liquidity in the pool ratio
+    store_reserves(deps.as_mut(), [collateral_pool_amount, asset_pool_amount]);
+    let provide_msg =
+        |min_lp_to_receive: Option<Uint128>, deadline: Option<u64>| ExecuteMsg::ProvideLiquidity {
+            assets: [
+                Asset {
+                    info: AssetInfo::NativeToken {
+                        denom: "uusd".to_string(),
+                    },
+                    amount: offer_amount,
+                },
+                Asset {
+                    info: AssetInfo::Token {
+                        contract_addr: Addr::unchecked("asset0000"),
+                    },
+                    amount: offer_amount,
+                },
+            ],
+            slippage_tolerance: None,
+            auto_stake: None,
+            receiver: None,
+            min_lp_to_receive,
+            deadline,
+        };
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env_with_block_time(1000),
+        info.clone(),
+        provide_msg(None, Some(999)),
+    )
+    .unwrap_err();
+    assert_eq!(res, ContractError::DeadlineExpired {});
+
+    // The pool is balanced, so the deposit doubling the pool size mints 1000000000 LP tokens
+    let res = execute(
+        deps.as_mut(),
+        mock_env_with_block_time(1000),
+        info.clone(),
+        provide_msg(Some(Uint128::new(1000000001u128)), None),
+    )
+    .unwrap_err();
+    assert_eq!(res, ContractError::MinLpToReceiveAssertion {});
+
+    execute(
+        deps.as_mut(),
+        mock_env_with_block_time(1000),
+        info,
+        provide_msg(Some(Uint128::new(1000000000u128)), None),
+    )
+    .unwrap();
+
+    // Withdraw 1000000000 LP tokens for 1000000000 uusd and 1000000000 asset0000
+    store_reserves(deps.as_mut(), [collateral_pool_amount, asset_pool_amount]);
+    deps.querier.with_balance(&[(
+        &String::from(MOCK_CONTRACT_ADDR),
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: collateral_pool_amount,
+        }],
+    )]);
+    let withdraw_msg = |min_assets_out: Option<Vec<Asset>>, deadline: Option<u64>| {
+        ExecuteMsg::Receive(Cw20ReceiveMsg {
+            sender: String::from("addr0000"),
+            msg: to_binary(&Cw20HookMsg::WithdrawLiquidity {
+                min_assets_out,
+                deadline,
+            })
+            .unwrap(),
+            amount: Uint128::new(1000000000u128),
+        })
+    };
+    let asset_out = |amount: u128| Asset {
+        info: AssetInfo::Token {
+            contract_addr: Addr::unchecked("asset0000"),
+        },
+        amount: Uint128::new(amount),
+    };
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env_with_block_time(1000),
+        mock_info("liquidity0000", &[]),
+        withdraw_msg(None, Some(999)),
+    )
+    .unwrap_err();
+    assert_eq!(res, ContractError::DeadlineExpired {});
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env_with_block_time(1000),
+        mock_info("liquidity0000", &[]),
+        withdraw_msg(Some(vec![asset_out(1000000001u128)]), None),
+    )
+    .unwrap_err();
+    assert_eq!(res, ContractError::MinAssetsOutAssertion {});
+
+    execute(
+        deps.as_mut(),
+        mock_env_with_block_time(1000),
+        mock_info("liquidity0000", &[]),
+        withdraw_msg(Some(vec![asset_out(1000000000u128)]), Some(1000)),
+    )
+    .unwrap();
+}
+
 #[test]
 fn test_max_spread() {
     assert_max_spread(
@@ -939,6 +1203,7 @@ fn test_query_pool() {
 
     // Store liquidity token
     store_liquidity_token(deps.as_mut(), 1, "liquidity0000".to_string());
+    store_reserves(deps.as_mut(), [asset_0_amount, asset_1_amount]);
 
     let res: PoolResponse = query_pool(deps.as_ref()).unwrap();
 
@@ -1011,6 +1276,7 @@ fn test_query_share() {
 
     // Store the liquidity token
     store_liquidity_token(deps.as_mut(), 1, "liquidity0000".to_string());
+    store_reserves(deps.as_mut(), [asset_0_amount, asset_1_amount]);
 
     let res = query_share(deps.as_ref(), Uint128::new(250)).unwrap();
 
@@ -1125,6 +1391,7 @@ fn test_accumulate_prices() {
             6,
             Uint128::new(case.y_amount),
             6,
+            [Decimal::one(); 2],
         )
         .unwrap();
 
@@ -1144,14 +1411,830 @@ fn test_accumulate_prices() {
     }
 }
 
-fn mock_env_with_block_time(time: u64) -> Env {
-    let mut env = mock_env();
-    env.block = BlockInfo {
-        height: 1,
-        time: Timestamp::from_seconds(time),
-        chain_id: "columbus".to_string(),
+#[test]
+fn rate_provider() {
+    let uusd_pool = Uint128::new(1_100_000_000_000);
+    let token_pool = Uint128::new(1_000_000_000_000);
+
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: uusd_pool,
+    }]);
+    deps.querier.with_token_balances(&[
+        (
+            &String::from("asset0000"),
+            &[(&String::from(MOCK_CONTRACT_ADDR), &token_pool)],
+        ),
+        (
+            &String::from("liquidity0000"),
+            &[(&String::from(MOCK_CONTRACT_ADDR), &Uint128::new(1_000_000))],
+        ),
+    ]);
+    deps.querier
+        .with_exchange_rate(Decimal::from_str("1.1").unwrap());
+
+    let token = AssetInfo::Token {
+        contract_addr: Addr::unchecked("asset0000"),
     };
-    env
+    let msg = InstantiateMsg {
+        asset_infos: [
+            AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            token.clone(),
+        ],
+        token_code_id: 10u64,
+        factory_addr: "factory".to_string(),
+        init_params: Some(
+            to_binary(&StablePoolParams {
+                amp: 100,
+                bluna_rewarder: "bluna_rewarder".to_string(),
+                generator: "generator".to_string(),
+            })
+            .unwrap(),
+        ),
+    };
+    instantiate(deps.as_mut(), mock_env(), mock_info("addr0000", &[]), msg).unwrap();
+    store_liquidity_token(deps.as_mut(), 1, "liquidity0000".to_string());
+    store_reserves(deps.as_mut(), [uusd_pool, token_pool]);
+
+    let offer_asset = Asset {
+        info: token.clone(),
+        amount: Uint128::new(1_000_000),
+    };
+    // The pool assumes a 1:1 peg without a rate provider
+    let res = query_simulation(deps.as_ref(), mock_env(), offer_asset.clone()).unwrap();
+    assert!(res.return_amount + res.commission_amount < Uint128::new(1_010_000));
+
+    let set_rate_provider = |asset_info: AssetInfo| ExecuteMsg::UpdateConfig {
+        params: to_binary(&StablePoolUpdateParams::RateProvider {
+            rate_provider: Some(RateProviderParams {
+                address: "rate_provider".to_string(),
+                asset_info,
+                max_rate_age: 3600,
+            }),
+        })
+        .unwrap(),
+    };
+
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        set_rate_provider(token.clone()),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("owner", &[]),
+        set_rate_provider(AssetInfo::NativeToken {
+            denom: "uluna".to_string(),
+        }),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::AssetMismatch {});
+
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("owner", &[]),
+        set_rate_provider(token.clone()),
+    )
+    .unwrap();
+    let config: StablePoolConfig = from_binary(
+        &query_config(deps.as_ref(), mock_env())
+            .unwrap()
+            .params
+            .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        config.rate_provider,
+        Some(RateProvider {
+            address: Addr::unchecked("rate_provider"),
+            asset_info: token.clone(),
+            max_rate_age: 3600,
+        })
+    );
+    assert_eq!(config.exchange_rate, Decimal::from_str("1.1").unwrap());
+
+    // The balances are balanced in the base asset, so the swap is priced at the exchange rate
+    let res = query_simulation(deps.as_ref(), mock_env(), offer_asset.clone()).unwrap();
+    assert_eq!(
+        res.return_amount + res.commission_amount,
+        Uint128::new(1_100_000)
+    );
+    let res = query_reverse_simulation(
+        deps.as_ref(),
+        mock_env(),
+        Asset {
+            info: AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            amount: Uint128::new(1_100_000),
+        },
+    )
+    .unwrap();
+    // 1_000_000 / (1 - 0.3%) rounded down
+    assert_eq!(res.offer_amount, Uint128::new(1_003_008));
+
+    // The cached rate is used until it's older than the maximum age
+    deps.querier
+        .with_exchange_rate(Decimal::from_str("1.2").unwrap());
+    let res = query_simulation(deps.as_ref(), mock_env(), offer_asset.clone()).unwrap();
+    assert_eq!(
+        res.return_amount + res.commission_amount,
+        Uint128::new(1_100_000)
+    );
+
+    let mut env = mock_env();
+    env.block.time = env.block.time.plus_seconds(3601);
+    let config: StablePoolConfig = from_binary(
+        &query_config(deps.as_ref(), env.clone())
+            .unwrap()
+            .params
+            .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(config.exchange_rate, Decimal::from_str("1.2").unwrap());
+    let res = query_simulation(deps.as_ref(), env, offer_asset.clone()).unwrap();
+    assert!(res.return_amount + res.commission_amount > Uint128::new(1_190_000));
+
+    // A rate that never expires doesn't overflow the age check
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("owner", &[]),
+        ExecuteMsg::UpdateConfig {
+            params: to_binary(&StablePoolUpdateParams::RateProvider {
+                rate_provider: Some(RateProviderParams {
+                    address: "rate_provider".to_string(),
+                    asset_info: token.clone(),
+                    max_rate_age: u64::MAX,
+                }),
+            })
+            .unwrap(),
+        },
+    )
+    .unwrap();
+    deps.querier
+        .with_exchange_rate(Decimal::from_str("1.3").unwrap());
+    let mut env = mock_env();
+    env.block.time = env.block.time.plus_seconds(3601);
+    let res = query_simulation(deps.as_ref(), env, offer_asset).unwrap();
+    assert!(res.return_amount + res.commission_amount < Uint128::new(1_210_000));
+
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("owner", &[]),
+        ExecuteMsg::UpdateConfig {
+            params: to_binary(&StablePoolUpdateParams::RateProvider {
+                rate_provider: None,
+            })
+            .unwrap(),
+        },
+    )
+    .unwrap();
+    let config: StablePoolConfig = from_binary(
+        &query_config(deps.as_ref(), mock_env())
+            .unwrap()
+            .params
+            .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(config.rate_provider, None);
+    assert_eq!(config.exchange_rate, Decimal::one());
+}
+
+#[test]
+fn sync_and_skim() {
+    let total_share = Uint128::new(20000000000u128);
+    let asset_pool_amount = Uint128::new(30000000000u128);
+    let collateral_pool_amount = Uint128::new(20000000000u128);
+    let donation_amount = Uint128::new(1000000000u128);
+
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: collateral_pool_amount,
+    }]);
+    deps.querier.with_token_balances(&[
+        (
+            &String::from("liquidity0000"),
+            &[(&String::from(MOCK_CONTRACT_ADDR), &total_share)],
+        ),
+        (
+            &String::from("asset0000"),
+            &[(&String::from(MOCK_CONTRACT_ADDR), &asset_pool_amount)],
+        ),
+    ]);
+
+    let msg = InstantiateMsg {
+        asset_infos: [
+            AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            AssetInfo::Token {
+                contract_addr: Addr::unchecked("asset0000"),
+            },
+        ],
+        token_code_id: 10u64,
+        factory_addr: String::from("factory"),
+        init_params: Some(
+            to_binary(&StablePoolParams {
+                amp: 100,
+                bluna_rewarder: "bluna_rewarder".to_string(),
+                generator: "generator".to_string(),
+            })
+            .unwrap(),
+        ),
+    };
+
+    let env = mock_env();
+    let info = mock_info("addr0000", &[]);
+    instantiate(deps.as_mut(), env, info, msg).unwrap();
+    store_liquidity_token(deps.as_mut(), 1, "liquidity0000".to_string());
+
+    // The reserves are tracked from zero, so the balances of the pair are not counted
+    let res: PoolResponse = query_pool(deps.as_ref()).unwrap();
+    assert_eq!(res.assets[0].amount, Uint128::zero());
+    assert_eq!(res.assets[1].amount, Uint128::zero());
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        ExecuteMsg::Sync {},
+    )
+    .unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "sync"),
+            attr("reserves", "20000000000uusd, 30000000000asset0000"),
+        ]
+    );
+
+    let offer_asset = Asset {
+        info: AssetInfo::NativeToken {
+            denom: "uusd".to_string(),
+        },
+        amount: Uint128::new(1000000000u128),
+    };
+    let simulation_res = query_simulation(deps.as_ref(), mock_env(), offer_asset.clone()).unwrap();
+
+    // A direct transfer to the pair doesn't move the price
+    deps.querier.with_token_balances(&[
+        (
+            &String::from("liquidity0000"),
+            &[(&String::from(MOCK_CONTRACT_ADDR), &total_share)],
+        ),
+        (
+            &String::from("asset0000"),
+            &[(
+                &String::from(MOCK_CONTRACT_ADDR),
+                &(asset_pool_amount + donation_amount),
+            )],
+        ),
+    ]);
+    assert_eq!(
+        query_simulation(deps.as_ref(), mock_env(), offer_asset.clone()).unwrap(),
+        simulation_res
+    );
+    let res: PoolResponse = query_pool(deps.as_ref()).unwrap();
+    assert_eq!(res.assets[1].amount, asset_pool_amount);
+
+    // The excess balance is sent to the recipient
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        ExecuteMsg::Skim {
+            to: Some(String::from("addr0001")),
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(WasmMsg::Execute {
+            contract_addr: String::from("asset0000"),
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: String::from("addr0001"),
+                amount: donation_amount,
+            })
+            .unwrap(),
+            funds: vec![],
+        })]
+    );
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "skim"),
+            attr("receiver", "addr0001"),
+            attr("assets", "0uusd, 1000000000asset0000"),
+        ]
+    );
+
+    // Syncing adds the excess balance to the pool instead
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        ExecuteMsg::Sync {},
+    )
+    .unwrap();
+    let res: PoolResponse = query_pool(deps.as_ref()).unwrap();
+    assert_eq!(res.assets[0].amount, collateral_pool_amount);
+    assert_eq!(res.assets[1].amount, asset_pool_amount + donation_amount);
+    assert_ne!(
+        query_simulation(deps.as_ref(), mock_env(), offer_asset).unwrap(),
+        simulation_res
+    );
+
+    // Nothing is left to skim
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        ExecuteMsg::Skim { to: None },
+    )
+    .unwrap();
+    assert!(res.messages.is_empty());
+}
+
+fn mock_env_with_block_time(time: u64) -> Env {
+    let mut env = mock_env();
+    env.block = BlockInfo {
+        height: 1,
+        time: Timestamp::from_seconds(time),
+        chain_id: "columbus".to_string(),
+    };
+    env
+}
+
+#[test]
+fn observe_cumulative_prices() {
+    let total_share = Uint128::new(20000000000u128);
+    let asset_pool_amount = Uint128::new(30000000000u128);
+    let collateral_pool_amount = Uint128::new(20000000000u128);
+
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: collateral_pool_amount,
+    }]);
+    deps.querier.with_token_balances(&[
+        (
+            &String::from("liquidity0000"),
+            &[(&String::from(MOCK_CONTRACT_ADDR), &total_share)],
+        ),
+        (
+            &String::from("asset0000"),
+            &[(&String::from(MOCK_CONTRACT_ADDR), &asset_pool_amount)],
+        ),
+    ]);
+
+    let msg = InstantiateMsg {
+        asset_infos: [
+            AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            AssetInfo::Token {
+                contract_addr: Addr::unchecked("asset0000"),
+            },
+        ],
+        token_code_id: 10u64,
+        factory_addr: String::from("factory"),
+        init_params: Some(
+            to_binary(&StablePoolParams {
+                amp: 100,
+                bluna_rewarder: "bluna_rewarder".to_string(),
+                generator: "generator".to_string(),
+            })
+            .unwrap(),
+        ),
+    };
+
+    instantiate(
+        deps.as_mut(),
+        mock_env_with_block_time(1000),
+        mock_info("addr0000", &[]),
+        msg,
+    )
+    .unwrap();
+    store_liquidity_token(deps.as_mut(), 1, "liquidity0000".to_string());
+
+    // Observations are written at 1000 (with empty reserves) and at 2000 (with the synced reserves)
+    for block_time in [1000, 2000] {
+        execute(
+            deps.as_mut(),
+            mock_env_with_block_time(block_time),
+            mock_info("addr0000", &[]),
+            ExecuteMsg::Sync {},
+        )
+        .unwrap();
+    }
+
+    let cumulative_prices = |block_time: u64| -> (Uint256, Uint256) {
+        let res: CumulativePricesResponse = from_binary(
+            &query(
+                deps.as_ref(),
+                mock_env_with_block_time(block_time),
+                QueryMsg::CumulativePrices {},
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        (
+            res.price0_cumulative_last.into(),
+            res.price1_cumulative_last.into(),
+        )
+    };
+    let (price0_at_2000, price1_at_2000) = cumulative_prices(2000);
+    assert!(!price0_at_2000.is_zero());
+
+    let env = mock_env_with_block_time(3000);
+    let res: ObserveResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            env.clone(),
+            QueryMsg::Observe {
+                seconds_ago: vec![0, 500, 1500, 2000],
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    let prices: Vec<(u64, Uint256, Uint256)> = res
+        .observations
+        .into_iter()
+        .map(|o| (o.timestamp, o.price0_cumulative, o.price1_cumulative))
+        .collect();
+    assert_eq!(
+        prices,
+        vec![
+            // Extrapolated using the current reserves
+            (3000, cumulative_prices(3000).0, cumulative_prices(3000).1),
+            (2500, cumulative_prices(2500).0, cumulative_prices(2500).1),
+            // Interpolated between the observations
+            (
+                1500,
+                price0_at_2000.multiply_ratio(1u64, 2u64),
+                price1_at_2000.multiply_ratio(1u64, 2u64)
+            ),
+            (1000, Uint256::zero(), Uint256::zero()),
+        ]
+    );
+
+    let err = query(
+        deps.as_ref(),
+        env.clone(),
+        QueryMsg::Observe {
+            seconds_ago: vec![2001],
+        },
+    )
+    .unwrap_err();
+    assert_eq!(
+        err,
+        StdError::generic_err("The observation time is before the oldest stored observation")
+    );
+
+    // The factory owner can reduce the number of stored observations
+    let err = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("owner", &[]),
+        ExecuteMsg::UpdateConfig {
+            params: to_binary(&StablePoolUpdateParams::SetObservationsSize { size: 0 }).unwrap(),
+        },
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::InvalidObservationsSize {});
+
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("owner", &[]),
+        ExecuteMsg::UpdateConfig {
+            params: to_binary(&StablePoolUpdateParams::SetObservationsSize { size: 1 }).unwrap(),
+        },
+    )
+    .unwrap();
+
+    // Only the latest observation is kept
+    let err = query(
+        deps.as_ref(),
+        env,
+        QueryMsg::Observe {
+            seconds_ago: vec![1500],
+        },
+    )
+    .unwrap_err();
+    assert_eq!(
+        err,
+        StdError::generic_err("The observation time is before the oldest stored observation")
+    );
+}
+
+#[test]
+fn reward_source() {
+    let mut deps = mock_dependencies(&[]);
+    deps.querier.with_token_balances(&[
+        (
+            &String::from("asset0000"),
+            &[(&String::from(MOCK_CONTRACT_ADDR), &Uint128::zero())],
+        ),
+        (
+            &String::from("reward0000"),
+            &[(&String::from("reward_holder"), &Uint128::new(200))],
+        ),
+    ]);
+    deps.querier.with_balance(&[(
+        &String::from("reward_holder"),
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::new(100),
+        }],
+    )]);
+
+    let msg = InstantiateMsg {
+        asset_infos: [
+            AssetInfo::NativeToken {
+                denom: "uluna".to_string(),
+            },
+            AssetInfo::Token {
+                contract_addr: Addr::unchecked("asset0000"),
+            },
+        ],
+        token_code_id: 10u64,
+        factory_addr: "factory".to_string(),
+        init_params: Some(
+            to_binary(&StablePoolParams {
+                amp: 100,
+                bluna_rewarder: "bluna_rewarder".to_string(),
+                generator: "generator".to_string(),
+            })
+            .unwrap(),
+        ),
+    };
+    instantiate(deps.as_mut(), mock_env(), mock_info("addr0000", &[]), msg).unwrap();
+    store_liquidity_token(deps.as_mut(), 1, "liquidity0000".to_string());
+    // Store the reward holder
+    store_liquidity_token(deps.as_mut(), 2, "reward_holder".to_string());
+
+    let uusd = AssetInfo::NativeToken {
+        denom: "uusd".to_string(),
+    };
+    let reward_token = AssetInfo::Token {
+        contract_addr: Addr::unchecked("reward0000"),
+    };
+    let claim_msg = to_binary(&"claim_rewards").unwrap();
+    let reward_source_msg =
+        |contract: &str, reward_assets: Vec<AssetInfo>| ExecuteMsg::UpdateConfig {
+            params: to_binary(&StablePoolUpdateParams::RewardSource {
+                reward_source: Some(RewardSourceParams {
+                    contract: contract.to_string(),
+                    claim_msg: claim_msg.clone(),
+                    reward_assets,
+                }),
+            })
+            .unwrap(),
+        };
+    let set_reward_source =
+        |reward_assets: Vec<AssetInfo>| reward_source_msg("rewarder", reward_assets);
+
+    // The bLUNA rewarder is used by default
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("generator", &[]),
+        ExecuteMsg::ClaimRewardByGenerator {
+            user: "user0000".to_string(),
+            user_share: Uint128::new(10),
+            total_share: Uint128::new(100),
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages[0].msg,
+        CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "bluna_rewarder".to_string(),
+            msg: to_binary(&anchor_basset::reward::ExecuteMsg::ClaimRewards {
+                recipient: Some("reward_holder".to_string()),
+            })
+            .unwrap(),
+            funds: vec![],
+        })
+    );
+
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        set_reward_source(vec![uusd.clone()]),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("owner", &[]),
+        set_reward_source(vec![]),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::InvalidRewardAssets {});
+
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("owner", &[]),
+        set_reward_source(vec![reward_token.clone(), reward_token.clone()]),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::InvalidRewardAssets {});
+
+    // The claim message is sent by the pair, so it can't be sent to the pool tokens
+    for contract in ["asset0000", "liquidity0000"] {
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("owner", &[]),
+            reward_source_msg(contract, vec![reward_token.clone()]),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::InvalidRewardSource {});
+    }
+
+    // The pool assets can't be paid as rewards
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("owner", &[]),
+        set_reward_source(vec![
+            reward_token.clone(),
+            AssetInfo::Token {
+                contract_addr: Addr::unchecked("asset0000"),
+            },
+        ]),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::InvalidRewardAssets {});
+
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("owner", &[]),
+        set_reward_source(vec![uusd.clone(), reward_token.clone()]),
+    )
+    .unwrap();
+    let config: StablePoolConfig = from_binary(
+        &query_config(deps.as_ref(), mock_env())
+            .unwrap()
+            .params
+            .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        config.reward_source,
+        Some(RewardSource {
+            contract: Addr::unchecked("rewarder"),
+            claim_msg: claim_msg.clone(),
+            reward_assets: vec![uusd.clone(), reward_token.clone()],
+        })
+    );
+    assert_eq!(config.reward_holder, Some(Addr::unchecked("reward_holder")));
+
+    // The custom claim message is sent and the balances of every reward asset are remembered
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("generator", &[]),
+        ExecuteMsg::ClaimRewardByGenerator {
+            user: "user0000".to_string(),
+            user_share: Uint128::new(10),
+            total_share: Uint128::new(100),
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages[0].msg,
+        CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "rewarder".to_string(),
+            msg: claim_msg,
+            funds: vec![],
+        })
+    );
+    let handle_reward_msg = ExecuteMsg::HandleReward {
+        previous_reward_balances: vec![
+            Asset {
+                info: uusd.clone(),
+                amount: Uint128::new(100),
+            },
+            Asset {
+                info: reward_token.clone(),
+                amount: Uint128::new(200),
+            },
+        ],
+        user: Addr::unchecked("user0000"),
+        user_share: Uint128::new(10),
+        total_share: Uint128::new(100),
+        receiver: None,
+    };
+    assert_eq!(
+        res.messages[1].msg,
+        CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: MOCK_CONTRACT_ADDR.to_string(),
+            msg: to_binary(&handle_reward_msg).unwrap(),
+            funds: vec![],
+        })
+    );
+
+    // The claim adds rewards to the holder
+    deps.querier.with_token_balances(&[(
+        &String::from("reward0000"),
+        &[(&String::from("reward_holder"), &Uint128::new(2200))],
+    )]);
+    deps.querier.with_balance(&[(
+        &String::from("reward_holder"),
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::new(1100),
+        }],
+    )]);
+
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        handle_reward_msg.clone(),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    // The user receives their share of every reward asset
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(MOCK_CONTRACT_ADDR, &[]),
+        handle_reward_msg,
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(WasmMsg::Execute {
+            contract_addr: "reward_holder".to_string(),
+            funds: vec![],
+            msg: to_binary(&astroport::whitelist::ExecuteMsg::Execute {
+                msgs: vec![
+                    CosmosMsg::<Empty>::Bank(BankMsg::Send {
+                        to_address: "user0000".to_string(),
+                        amount: vec![Coin {
+                            denom: "uusd".to_string(),
+                            amount: Uint128::new(100),
+                        }],
+                    }),
+                    CosmosMsg::Wasm(WasmMsg::Execute {
+                        contract_addr: "reward0000".to_string(),
+                        msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                            recipient: "user0000".to_string(),
+                            amount: Uint128::new(200),
+                        })
+                        .unwrap(),
+                        funds: vec![],
+                    }),
+                ],
+            })
+            .unwrap(),
+        })]
+    );
+    assert_eq!(
+        res.attributes[0],
+        attr("claimed_reward_to_pool", "1000uusd, 2000reward0000")
+    );
+
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("owner", &[]),
+        ExecuteMsg::UpdateConfig {
+            params: to_binary(&StablePoolUpdateParams::RewardSource {
+                reward_source: None,
+            })
+            .unwrap(),
+        },
+    )
+    .unwrap();
+    let config: StablePoolConfig = from_binary(
+        &query_config(deps.as_ref(), mock_env())
+            .unwrap()
+            .params
+            .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(config.reward_source, None);
 }
 
 #[test]
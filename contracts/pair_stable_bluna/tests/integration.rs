@@ -93,6 +93,7 @@ fn create_pair_with_same_assets() {
             pair_type: PairType::Stable {},
             is_disabled: false,
             is_generator_disabled: false,
+            maker_fee_as_lp: false,
         }],
         token_code_id: token_contract_code_id,
         generator_address: Some(String::from("generator")),
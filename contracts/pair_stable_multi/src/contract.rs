@@ -0,0 +1,1873 @@
+use crate::error::ContractError;
+use crate::math::{
+    calc_ask_amount, calc_offer_amount, compute_d, AMP_PRECISION, MAX_AMP, MAX_AMP_CHANGE,
+    MIN_AMP_CHANGING_TIME,
+};
+use crate::state::{Config, CONFIG, FACTORY_PARAMS, RESERVES};
+
+use cosmwasm_bignumber::Decimal256;
+use cosmwasm_std::{
+    attr, entry_point, from_binary, to_binary, Addr, Binary, CosmosMsg, Decimal, Deps, DepsMut,
+    Env, MessageInfo, OverflowError, Reply, ReplyOn, Response, StdError, StdResult, Storage,
+    SubMsg, Uint128, WasmMsg,
+};
+
+use crate::response::MsgInstantiateContractResponse;
+use astroport::asset::{
+    addr_validate_to_lower, format_lp_token_name, Asset, AssetInfo, MultiPairInfo,
+};
+use astroport::factory::PairType;
+
+use astroport::generator::Cw20HookMsg as GeneratorHookMsg;
+use astroport::pair::{
+    ReverseSimulationResponse, SimulateProvideResponse, SimulationResponse, StablePoolParams,
+    StablePoolUpdateParams, DEFAULT_SLIPPAGE, MAX_ALLOWED_SLIPPAGE, MINIMUM_LIQUIDITY,
+};
+use astroport::pair_stable_multi::{
+    ConfigResponse, Cw20HookMsg, ExecuteMsg, InstantiateMsg, MigrateMsg, PoolResponse, QueryMsg,
+    MAX_ASSETS_NUM, MIN_ASSETS_NUM,
+};
+use astroport::querier::{
+    query_factory_config, query_factory_params, query_supply, query_token_precision,
+};
+use astroport::token::InstantiateMsg as TokenInstantiateMsg;
+use cw2::set_contract_version;
+use cw20::{Cw20ExecuteMsg, Cw20ReceiveMsg, MinterResponse};
+use protobuf::Message;
+use std::cmp::Ordering;
+use std::str::FromStr;
+use std::vec;
+
+/// Contract name that is used for migration.
+const CONTRACT_NAME: &str = "astroport-pair-stable-multi";
+/// Contract version that is used for migration.
+const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
+/// A `reply` call code ID of sub-message.
+const INSTANTIATE_TOKEN_REPLY_ID: u64 = 1;
+
+/// ## Description
+/// Creates a new contract with the specified parameters in [`InstantiateMsg`].
+/// Returns a [`Response`] with the specified attributes if the operation was successful,
+/// or a [`ContractError`] if the contract was not created.
+/// ## Params
+/// * **deps** is an object of type [`DepsMut`].
+///
+/// * **env** is an object of type [`Env`].
+///
+/// * **_info** is an object of type [`MessageInfo`].
+/// * **msg** is a message of type [`InstantiateMsg`] which contains the parameters for creating the contract.
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn instantiate(
+    deps: DepsMut,
+    env: Env,
+    _info: MessageInfo,
+    msg: InstantiateMsg,
+) -> Result<Response, ContractError> {
+    if msg.asset_infos.len() < MIN_ASSETS_NUM || msg.asset_infos.len() > MAX_ASSETS_NUM {
+        return Err(ContractError::InvalidNumberOfAssets {});
+    }
+
+    for (i, asset_info) in msg.asset_infos.iter().enumerate() {
+        asset_info.check(deps.api)?;
+
+        if msg.asset_infos[i + 1..].contains(asset_info) {
+            return Err(ContractError::DoublingAssets {});
+        }
+    }
+
+    if msg.init_params.is_none() {
+        return Err(ContractError::InitParamsNotFound {});
+    }
+
+    let params: StablePoolParams = from_binary(&msg.init_params.unwrap())?;
+
+    if params.amp == 0 || params.amp > MAX_AMP {
+        return Err(ContractError::IncorrectAmp {});
+    }
+
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+
+    let config = Config {
+        pair_info: MultiPairInfo {
+            contract_addr: env.contract.address.clone(),
+            liquidity_token: Addr::unchecked(""),
+            asset_infos: msg.asset_infos.clone(),
+            pair_type: PairType::StableMulti {},
+        },
+        factory_addr: addr_validate_to_lower(deps.api, msg.factory_addr.as_str())?,
+        init_amp: params.amp * AMP_PRECISION,
+        init_amp_time: env.block.time.seconds(),
+        next_amp: params.amp * AMP_PRECISION,
+        next_amp_time: env.block.time.seconds(),
+    };
+
+    CONFIG.save(deps.storage, &config)?;
+    RESERVES.save(deps.storage, &vec![Uint128::zero(); msg.asset_infos.len()])?;
+    FACTORY_PARAMS.save(
+        deps.storage,
+        &query_factory_params(
+            &deps.querier,
+            config.factory_addr.clone(),
+            env.contract.address.clone(),
+            config.pair_info.pair_type.clone(),
+        )?,
+    )?;
+
+    let token_name = format_lp_token_name(&msg.asset_infos, &deps.querier)?;
+
+    // Create LP token
+    let sub_msg: Vec<SubMsg> = vec![SubMsg {
+        msg: WasmMsg::Instantiate {
+            code_id: msg.token_code_id,
+            msg: to_binary(&TokenInstantiateMsg {
+                name: token_name,
+                symbol: "uLP".to_string(),
+                decimals: 6,
+                initial_balances: vec![],
+                mint: Some(MinterResponse {
+                    minter: env.contract.address.to_string(),
+                    cap: None,
+                }),
+            })?,
+            funds: vec![],
+            admin: None,
+            label: String::from("Astroport LP token"),
+        }
+        .into(),
+        id: INSTANTIATE_TOKEN_REPLY_ID,
+        gas_limit: None,
+        reply_on: ReplyOn::Success,
+    }];
+
+    Ok(Response::new().add_submessages(sub_msg))
+}
+
+/// # Description
+/// The entry point to the contract for processing replies from submessages.
+/// # Params
+/// * **deps** is an object of type [`DepsMut`].
+///
+/// * **_env** is an object of type [`Env`].
+///
+/// * **msg** is an object of type [`Reply`]. This is the reply from the submessage.
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn reply(deps: DepsMut, _env: Env, msg: Reply) -> Result<Response, ContractError> {
+    let mut config: Config = CONFIG.load(deps.storage)?;
+
+    if config.pair_info.liquidity_token != Addr::unchecked("") {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let data = msg.result.unwrap().data.unwrap();
+    let res: MsgInstantiateContractResponse =
+        Message::parse_from_bytes(data.as_slice()).map_err(|_| {
+            StdError::parse_err("MsgInstantiateContractResponse", "failed to parse data")
+        })?;
+
+    config.pair_info.liquidity_token =
+        addr_validate_to_lower(deps.api, res.get_contract_address())?;
+
+    CONFIG.save(deps.storage, &config)?;
+
+    Ok(Response::new().add_attribute("liquidity_token_addr", config.pair_info.liquidity_token))
+}
+
+/// ## Description
+/// Exposes all the execute functions available in the contract.
+/// ## Params
+/// * **deps** is an object of type [`Deps`].
+///
+/// * **env** is an object of type [`Env`].
+///
+/// * **info** is an object of type [`MessageInfo`].
+///
+/// * **msg** is an object of type [`ExecuteMsg`].
+///
+/// ## Queries
+/// * **ExecuteMsg::UpdateConfig { params: Binary }** Updates the contract configuration with the specified
+/// input parameters.
+///
+/// * **ExecuteMsg::Receive(msg)** Receives a message of type [`Cw20ReceiveMsg`] and processes
+/// it depending on the received template.
+///
+/// * **ExecuteMsg::ProvideLiquidity {
+///             assets,
+///             auto_stake,
+///             receiver,
+///             min_lp_to_receive,
+///             deadline,
+///         }** Provides liquidity in the pair using the specified input parameters.
+///
+/// * **ExecuteMsg::Swap {
+///             offer_asset,
+///             ask_asset_info,
+///             belief_price,
+///             max_spread,
+///             to,
+///             min_return,
+///             deadline,
+///         }** Performs an swap using the specified parameters.
+///
+/// * **ExecuteMsg::RefreshFactoryParams {}** Updates the fee and Generator information cached from the factory.
+///
+/// * **ExecuteMsg::Sync {}** Sets the tracked pool reserves to the current balances of the pair.
+///
+/// * **ExecuteMsg::Skim { to }** Sends the balances of the pair that exceed the tracked pool reserves to a recipient.
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn execute(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: ExecuteMsg,
+) -> Result<Response, ContractError> {
+    match msg {
+        ExecuteMsg::UpdateConfig { params } => update_config(deps, env, info, params),
+        ExecuteMsg::Receive(msg) => receive_cw20(deps, env, info, msg),
+        ExecuteMsg::ProvideLiquidity {
+            assets,
+            auto_stake,
+            receiver,
+            min_lp_to_receive,
+            deadline,
+        } => provide_liquidity(
+            deps,
+            env,
+            info,
+            assets,
+            auto_stake,
+            receiver,
+            min_lp_to_receive,
+            deadline,
+        ),
+        ExecuteMsg::Swap {
+            offer_asset,
+            ask_asset_info,
+            belief_price,
+            max_spread,
+            to,
+            min_return,
+            deadline,
+        } => {
+            offer_asset.info.check(deps.api)?;
+            if !offer_asset.is_native_token() {
+                return Err(ContractError::Unauthorized {});
+            }
+
+            let to_addr = if let Some(to_addr) = to {
+                Some(addr_validate_to_lower(deps.api, &to_addr)?)
+            } else {
+                None
+            };
+
+            swap(
+                deps,
+                env,
+                info.clone(),
+                info.sender,
+                offer_asset,
+                ask_asset_info,
+                belief_price,
+                max_spread,
+                to_addr,
+                min_return,
+                deadline,
+            )
+        }
+        ExecuteMsg::RefreshFactoryParams {} => refresh_factory_params(deps, env),
+        ExecuteMsg::Sync {} => sync(deps, env),
+        ExecuteMsg::Skim { to } => skim(deps, env, info, to),
+    }
+}
+
+/// ## Description
+/// Receives a message of type [`Cw20ReceiveMsg`] and processes it depending on the received template.
+/// If no template is not found in the received message, then an [`ContractError`] is returned,
+/// otherwise it returns a [`Response`] with the specified attributes if the operation was successful
+/// ## Params
+/// * **deps** is an object of type [`DepsMut`].
+///
+/// * **env** is an object of type [`Env`].
+///
+/// * **info** is an object of type [`MessageInfo`].
+///
+/// * **cw20_msg** is an object of type [`Cw20ReceiveMsg`]. This is the CW20 receive message to process.
+pub fn receive_cw20(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    cw20_msg: Cw20ReceiveMsg,
+) -> Result<Response, ContractError> {
+    let contract_addr = info.sender.clone();
+    match from_binary(&cw20_msg.msg) {
+        Ok(Cw20HookMsg::Swap {
+            ask_asset_info,
+            belief_price,
+            max_spread,
+            to,
+            min_return,
+            deadline,
+        }) => {
+            // Only an asset (token) contract can execute this message
+            let config: Config = CONFIG.load(deps.storage)?;
+            let authorized = config.pair_info.asset_infos.iter().any(|pool| {
+                matches!(pool, AssetInfo::Token { contract_addr } if contract_addr == &info.sender)
+            });
+
+            if !authorized {
+                return Err(ContractError::Unauthorized {});
+            }
+
+            let to_addr = if let Some(to_addr) = to {
+                Some(addr_validate_to_lower(deps.api, to_addr.as_str())?)
+            } else {
+                None
+            };
+
+            swap(
+                deps,
+                env,
+                info,
+                Addr::unchecked(cw20_msg.sender),
+                Asset {
+                    info: AssetInfo::Token { contract_addr },
+                    amount: cw20_msg.amount,
+                },
+                ask_asset_info,
+                belief_price,
+                max_spread,
+                to_addr,
+                min_return,
+                deadline,
+            )
+        }
+        Ok(Cw20HookMsg::WithdrawLiquidity {
+            min_assets_out,
+            deadline,
+        }) => withdraw_liquidity(
+            deps,
+            env,
+            info,
+            Addr::unchecked(cw20_msg.sender),
+            cw20_msg.amount,
+            min_assets_out,
+            deadline,
+        ),
+        Err(err) => Err(ContractError::Std(err)),
+    }
+}
+
+/// ## Description
+/// Provides liquidity with the specified input parameters. Any subset of the pool assets can be provided
+/// in any proportion. Imbalanced deposits are charged a fee on the part of the deposit that differs from
+/// the current pool ratio, the same way a swap would be charged.
+/// Returns a [`ContractError`] on failure, otherwise returns a [`Response`] with the
+/// specified attributes if the operation was successful.
+/// ## Params
+/// * **deps** is an object of type [`DepsMut`].
+///
+/// * **env** is an object of type [`Env`].
+///
+/// * **info** is an object of type [`MessageInfo`].
+///
+/// * **assets** is a vector with items of type [`Asset`]. These are the assets to deposit.
+///
+/// * **auto_stake** is object of type [`Option<bool>`]. Determines whether the resulting LP tokens are automatically staked in
+/// the Generator contract to receive token incentives.
+///
+/// * **receiver** is object of type [`Option<String>`]. This is the address that receives LP tokens.
+/// If this address isn't specified, the function will default to the caller.
+///
+/// * **min_lp_to_receive** is an [`Option`] field of type [`Uint128`]. This is the minimum amount of
+/// LP tokens that must be minted, otherwise the transaction fails.
+///
+/// * **deadline** is an [`Option`] field of type [`u64`]. This is the block time (in seconds) after which
+/// the transaction fails.
+/// NOTE - the address that wants to provide liquidity should approve the pair contract to pull its relevant tokens.
+#[allow(clippy::too_many_arguments)]
+pub fn provide_liquidity(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    assets: Vec<Asset>,
+    auto_stake: Option<bool>,
+    receiver: Option<String>,
+    min_lp_to_receive: Option<Uint128>,
+    deadline: Option<u64>,
+) -> Result<Response, ContractError> {
+    assert_deadline(&env, deadline)?;
+    assert_deposits_allowed(deps.storage)?;
+    let auto_stake = auto_stake.unwrap_or(false);
+    for asset in assets.iter() {
+        asset.info.check(deps.api)?;
+        asset.assert_sent_native_token_balance(&info)?;
+    }
+
+    let config: Config = CONFIG.load(deps.storage)?;
+    let pools = query_reserves(deps.storage, &config)?;
+    let deposits = get_deposits(&assets, &pools)?;
+
+    let mut messages: Vec<CosmosMsg> = vec![];
+    for (pool, deposit) in pools.iter().zip(deposits.iter()) {
+        if deposit.is_zero() {
+            continue;
+        }
+
+        // If the pool is a token contract, then we need to execute a TransferFrom msg to receive funds
+        if let AssetInfo::Token { contract_addr, .. } = &pool.info {
+            messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: contract_addr.to_string(),
+                msg: to_binary(&Cw20ExecuteMsg::TransferFrom {
+                    owner: info.sender.to_string(),
+                    recipient: env.contract.address.to_string(),
+                    amount: *deposit,
+                })?,
+                funds: vec![],
+            }))
+        }
+    }
+
+    let total_share = query_supply(&deps.querier, config.pair_info.liquidity_token.clone())?;
+    let (share, _) = calculate_share(deps.as_ref(), &env, &config, &deposits, &pools, total_share)?;
+
+    if let Some(min_lp_to_receive) = min_lp_to_receive {
+        if share < min_lp_to_receive {
+            return Err(ContractError::MinLpToReceiveAssertion {});
+        }
+    }
+
+    if total_share.is_zero() {
+        // Lock the minimum liquidity in the pair forever, so the share price can't be inflated
+        messages.extend(mint_liquidity_token_message(
+            deps.as_ref(),
+            &config,
+            env.clone(),
+            env.contract.address.clone(),
+            MINIMUM_LIQUIDITY,
+            false,
+        )?);
+    }
+
+    RESERVES.save(
+        deps.storage,
+        &pools
+            .iter()
+            .zip(deposits.iter())
+            .map(|(pool, deposit)| pool.amount.checked_add(*deposit))
+            .collect::<Result<Vec<Uint128>, OverflowError>>()?,
+    )?;
+
+    // Mint LP token for the caller (or for the receiver if it was set)
+    let receiver = receiver.unwrap_or_else(|| info.sender.to_string());
+    messages.extend(mint_liquidity_token_message(
+        deps.as_ref(),
+        &config,
+        env,
+        addr_validate_to_lower(deps.api, receiver.as_str())?,
+        share,
+        auto_stake,
+    )?);
+
+    Ok(Response::new().add_messages(messages).add_attributes(vec![
+        attr("action", "provide_liquidity"),
+        attr("sender", info.sender.as_str()),
+        attr("receiver", receiver.as_str()),
+        attr("assets", format_assets(&assets)),
+        attr("share", share.to_string()),
+    ]))
+}
+
+/// ## Description
+/// Returns the deposit amounts in the order of the pool assets. Pool assets that aren't deposited
+/// get a zero amount, but at least one of the deposits must be non-zero.
+/// ## Params
+/// * **assets** is a slice of [`Asset`] type items. These are the assets to deposit.
+///
+/// * **pools** is a slice of [`Asset`] type items. These are the assets available in the pool.
+fn get_deposits(assets: &[Asset], pools: &[Asset]) -> Result<Vec<Uint128>, ContractError> {
+    for (i, asset) in assets.iter().enumerate() {
+        if assets[i + 1..].iter().any(|a| a.info.equal(&asset.info)) {
+            return Err(ContractError::DoublingAssets {});
+        }
+    }
+
+    if assets
+        .iter()
+        .any(|a| !pools.iter().any(|pool| pool.info.equal(&a.info)))
+    {
+        return Err(ContractError::AssetMismatch {});
+    }
+
+    let deposits: Vec<Uint128> = pools
+        .iter()
+        .map(|pool| {
+            assets
+                .iter()
+                .find(|a| a.info.equal(&pool.info))
+                .map(|a| a.amount)
+                .unwrap_or_default()
+        })
+        .collect();
+
+    if deposits.iter().all(|deposit| deposit.is_zero()) {
+        return Err(ContractError::InvalidZeroAmount {});
+    }
+
+    Ok(deposits)
+}
+
+/// ## Description
+/// Returns the amount of LP tokens minted for a deposit together with the imbalance fees charged
+/// on it. The fees are returned in the order of the pool assets and in their token precisions.
+/// The first deposit must contain all pool assets and mints the invariant of the deposit minus
+/// the [`MINIMUM_LIQUIDITY`] that is locked in the pair.
+/// ## Params
+/// * **deps** is an object of type [`Deps`].
+///
+/// * **env** is an object of type [`Env`].
+///
+/// * **config** is an object of type [`Config`].
+///
+/// * **deposits** is a slice of [`Uint128`]. These are the deposits in the order of the pool assets.
+///
+/// * **pools** is a slice of [`Asset`] type items. These are the assets available in the pool
+/// without the deposits.
+///
+/// * **total_share** is an object of type [`Uint128`]. This is the total amount of LP tokens.
+fn calculate_share(
+    deps: Deps,
+    env: &Env,
+    config: &Config,
+    deposits: &[Uint128],
+    pools: &[Asset],
+    total_share: Uint128,
+) -> Result<(Uint128, Vec<Uint128>), ContractError> {
+    let precisions = query_precisions(deps, pools)?;
+    let greater_precision = *precisions.iter().max().unwrap();
+
+    let old_balances = normalize_amounts(
+        pools.iter().map(|pool| pool.amount),
+        &precisions,
+        greater_precision,
+    )?;
+    let deposits = normalize_amounts(deposits.iter().cloned(), &precisions, greater_precision)?;
+    let new_balances = old_balances
+        .iter()
+        .zip(deposits.iter())
+        .map(|(balance, deposit)| balance.checked_add(*deposit))
+        .collect::<Result<Vec<Uint128>, OverflowError>>()?;
+
+    let leverage = compute_current_amp(config, env)?
+        .checked_mul(pools.len() as u64)
+        .unwrap();
+
+    let (share, fees) = if total_share.is_zero() {
+        // The initial deposit must contain all pool assets
+        if deposits.iter().any(|deposit| deposit.is_zero()) {
+            return Err(ContractError::InvalidZeroAmount {});
+        }
+
+        let liquidity_token_precision = query_token_precision(
+            &deps.querier,
+            AssetInfo::Token {
+                contract_addr: config.pair_info.liquidity_token.clone(),
+            },
+        )?;
+
+        // Initial share = the invariant of the initial deposit
+        let share = adjust_precision(
+            Uint128::new(invariant(leverage, &new_balances)?),
+            greater_precision,
+            liquidity_token_precision,
+        )?
+        .checked_sub(MINIMUM_LIQUIDITY)
+        .map_err(|_| ContractError::MinimumLiquidityAmountError {})?;
+
+        (share, vec![Uint128::zero(); pools.len()])
+    } else {
+        let d_before_addition_liquidity = invariant(leverage, &old_balances)?;
+        let d_after_addition_liquidity = invariant(leverage, &new_balances)?;
+
+        // d after adding liquidity may be less than or equal to d before adding liquidity because of rounding
+        if d_before_addition_liquidity >= d_after_addition_liquidity {
+            return Err(ContractError::LiquidityAmountTooSmall {});
+        }
+
+        // Get the fee info cached from the factory
+        let fee_info = FACTORY_PARAMS.load(deps.storage)?.fee_info;
+
+        // Charge the swap fee on the difference between the deposit and a proportional one
+        let n_coins = pools.len() as u128;
+        let fees = old_balances
+            .iter()
+            .zip(new_balances.iter())
+            .map(|(old_balance, new_balance)| {
+                let ideal_balance = old_balance
+                    .multiply_ratio(d_after_addition_liquidity, d_before_addition_liquidity);
+                let difference = if ideal_balance > *new_balance {
+                    ideal_balance - *new_balance
+                } else {
+                    *new_balance - ideal_balance
+                };
+
+                (difference * fee_info.total_fee_rate).multiply_ratio(n_coins, 4 * (n_coins - 1))
+            })
+            .collect::<Vec<Uint128>>();
+        let balances_after_fee = new_balances
+            .iter()
+            .zip(fees.iter())
+            .map(|(new_balance, fee)| new_balance.checked_sub(*fee))
+            .collect::<Result<Vec<Uint128>, OverflowError>>()?;
+
+        let d_after_fee = invariant(leverage, &balances_after_fee)?;
+        if d_before_addition_liquidity >= d_after_fee {
+            return Err(ContractError::LiquidityAmountTooSmall {});
+        }
+
+        let share = total_share.multiply_ratio(
+            d_after_fee - d_before_addition_liquidity,
+            d_before_addition_liquidity,
+        );
+
+        (share, fees)
+    };
+
+    if share.is_zero() {
+        return Err(ContractError::LiquidityAmountTooSmall {});
+    }
+
+    let fees = fees
+        .into_iter()
+        .zip(precisions.iter())
+        .map(|(fee, precision)| adjust_precision(fee, greater_precision, *precision))
+        .collect::<StdResult<Vec<Uint128>>>()?;
+
+    Ok((share, fees))
+}
+
+/// # Description
+/// Mint LP tokens for a beneficiary and auto deposit them into the Generator contract (if requested).
+/// # Params
+/// * **deps** is an object of type [`Deps`].
+///
+/// * **config** is an object of type [`Config`].
+///
+/// * **env** is an object of type [`Env`].
+///
+/// * **recipient** is an object of type [`Addr`]. This is the LP token recipient.
+///
+/// * **amount** is an object of type [`Uint128`]. This is the amount of LP tokens to mint.
+///
+/// * **auto_stake** is a field of type [`bool`]. Determines whether or not LP tokens will be automatically staked in the Generator contract.
+fn mint_liquidity_token_message(
+    deps: Deps,
+    config: &Config,
+    env: Env,
+    recipient: Addr,
+    amount: Uint128,
+    auto_stake: bool,
+) -> Result<Vec<CosmosMsg>, ContractError> {
+    let lp_token = config.pair_info.liquidity_token.clone();
+
+    // If no auto-stake - just mint LP tokens for the recipient and return
+    if !auto_stake {
+        return Ok(vec![CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: lp_token.to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Mint {
+                recipient: recipient.to_string(),
+                amount,
+            })?,
+            funds: vec![],
+        })]);
+    }
+
+    // Mint for the contract and stake into the Generator
+    let generator = FACTORY_PARAMS.load(deps.storage)?.generator_address;
+
+    if generator.is_none() {
+        return Err(ContractError::AutoStakeError {});
+    }
+
+    Ok(vec![
+        CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: lp_token.to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Mint {
+                recipient: env.contract.address.to_string(),
+                amount,
+            })?,
+            funds: vec![],
+        }),
+        CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: lp_token.to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Send {
+                contract: generator.unwrap().to_string(),
+                amount,
+                msg: to_binary(&GeneratorHookMsg::DepositFor(recipient))?,
+            })?,
+            funds: vec![],
+        }),
+    ])
+}
+
+/// ## Description
+/// Withdraw liquidity from the pool proportionally to the pool balances. Returns a [`ContractError`] on failure,
+/// otherwise returns a [`Response`] with the specified attributes if the operation was successful.
+/// ## Params
+/// * **deps** is an object of type [`DepsMut`].
+///
+/// * **env** is an object of type [`Env`].
+///
+/// * **info** is an object of type [`MessageInfo`].
+///
+/// * **sender** is an object of type [`Addr`]. This is the address that will receive the withdrawn liquidity.
+///
+/// * **amount** is an object of type [`Uint128`]. This is the amount of LP tokens to burn and withdraw liquidity with.
+///
+/// * **min_assets_out** is an [`Option`] field of type [`Vec<Asset>`]. These are the minimum amounts of assets
+/// to receive, otherwise the transaction fails.
+///
+/// * **deadline** is an [`Option`] field of type [`u64`]. This is the block time (in seconds) after which
+/// the transaction fails.
+pub fn withdraw_liquidity(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    sender: Addr,
+    amount: Uint128,
+    min_assets_out: Option<Vec<Asset>>,
+    deadline: Option<u64>,
+) -> Result<Response, ContractError> {
+    assert_deadline(&env, deadline)?;
+
+    let config: Config = CONFIG.load(deps.storage)?;
+
+    if info.sender != config.pair_info.liquidity_token {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let (pools, total_share) = pool_info(deps.as_ref(), config.clone())?;
+    let refund_assets = get_share_in_assets(&pools, amount, total_share);
+    assert_min_assets_out(&refund_assets, min_assets_out)?;
+
+    RESERVES.save(
+        deps.storage,
+        &pools
+            .iter()
+            .zip(refund_assets.iter())
+            .map(|(pool, refund)| pool.amount.checked_sub(refund.amount))
+            .collect::<Result<Vec<Uint128>, OverflowError>>()?,
+    )?;
+
+    let mut messages: Vec<CosmosMsg> = refund_assets
+        .iter()
+        .filter(|asset| !asset.amount.is_zero())
+        .map(|asset| asset.clone().into_msg(&deps.querier, sender.clone()))
+        .collect::<StdResult<Vec<CosmosMsg>>>()?;
+    messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: config.pair_info.liquidity_token.to_string(),
+        msg: to_binary(&Cw20ExecuteMsg::Burn { amount })?,
+        funds: vec![],
+    }));
+
+    let attributes = vec![
+        attr("action", "withdraw_liquidity"),
+        attr("sender", sender.as_str()),
+        attr("withdrawn_share", amount.to_string()),
+        attr("refund_assets", format_assets(&refund_assets)),
+    ];
+
+    Ok(Response::new()
+        .add_messages(messages)
+        .add_attributes(attributes))
+}
+
+/// ## Description
+/// Return the amount of tokens that a specific amount of LP tokens would withdraw.
+/// ## Params
+/// * **pools** is a slice of [`Asset`] type items. These are the assets available in the pool.
+///
+/// * **amount** is an object of type [`Uint128`]. This is the amount of LP tokens to calculate underlying amounts for.
+///
+/// * **total_share** is an object of type [`Uint128`]. This is the total amount of LP tokens currently issued by the pool.
+pub fn get_share_in_assets(pools: &[Asset], amount: Uint128, total_share: Uint128) -> Vec<Asset> {
+    let mut share_ratio = Decimal::zero();
+    if !total_share.is_zero() {
+        share_ratio = Decimal::from_ratio(amount, total_share);
+    }
+
+    pools
+        .iter()
+        .map(|pool| Asset {
+            info: pool.info.clone(),
+            amount: pool.amount * share_ratio,
+        })
+        .collect()
+}
+
+/// ## Description
+/// Performs a swap with the specified parameters.
+/// Returns a [`ContractError`] on failure, otherwise returns a [`Response`] with the
+/// specified attributes if the operation was successful.
+/// ## Params
+/// * **deps** is an object of type [`DepsMut`].
+///
+/// * **env** is an object of type [`Env`].
+///
+/// * **info** is an object of type [`MessageInfo`].
+///
+/// * **sender** is an object of type [`Addr`]. This is the default recipient of the swap operation.
+///
+/// * **offer_asset** is an object of type [`Asset`]. This is the asset to swap.
+///
+/// * **ask_asset_info** is an object of type [`AssetInfo`]. This is the asset to swap to.
+///
+/// * **belief_price** is an object of type [`Option<Decimal>`]. This is used to calculate the maximum spread.
+///
+/// * **max_spread** is an object of type [`Option<Decimal>`]. This is the maximum spread allowed for the swap.
+///
+/// * **to** is an object of type [`Option<Addr>`]. This is the address that receives ask tokens.
+///
+/// * **min_return** is an object of type [`Option<Uint128>`]. This is the minimum amount of ask assets to receive.
+///
+/// * **deadline** is an object of type [`Option<u64>`]. This is the block time (in seconds) after which the swap fails.
+/// NOTE - the address that wants to swap should approve the pair contract to pull the offer token.
+#[allow(clippy::too_many_arguments)]
+pub fn swap(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    sender: Addr,
+    offer_asset: Asset,
+    ask_asset_info: AssetInfo,
+    belief_price: Option<Decimal>,
+    max_spread: Option<Decimal>,
+    to: Option<Addr>,
+    min_return: Option<Uint128>,
+    deadline: Option<u64>,
+) -> Result<Response, ContractError> {
+    assert_deadline(&env, deadline)?;
+    assert_swaps_allowed(deps.storage)?;
+    offer_asset.assert_sent_native_token_balance(&info)?;
+
+    let config: Config = CONFIG.load(deps.storage)?;
+
+    let pools = query_reserves(deps.storage, &config)?;
+
+    let (offer_idx, ask_idx) = find_swap_indexes(&pools, &offer_asset.info, &ask_asset_info)?;
+
+    // Get the fee info cached from the factory
+    let fee_info = FACTORY_PARAMS.load(deps.storage)?.fee_info;
+
+    let offer_amount = offer_asset.amount;
+    let (return_amount, spread_amount, commission_amount) = compute_swap(
+        &pools,
+        &query_precisions(deps.as_ref(), &pools)?,
+        offer_idx,
+        ask_idx,
+        offer_amount,
+        fee_info.total_fee_rate,
+        compute_current_amp(&config, &env)?,
+    )?;
+
+    // Check the max spread limit (if it was specified)
+    assert_max_spread(
+        belief_price,
+        max_spread,
+        offer_amount,
+        return_amount + commission_amount,
+        spread_amount,
+    )?;
+
+    // Check the minimum return amount (if it was specified)
+    if let Some(min_return) = min_return {
+        if return_amount < min_return {
+            return Err(ContractError::MinReturnAssertion {});
+        }
+    }
+
+    // Compute the tax for the ask asset
+    let return_asset = Asset {
+        info: ask_asset_info.clone(),
+        amount: return_amount,
+    };
+
+    let tax_amount = return_asset.compute_tax(&deps.querier)?;
+
+    let receiver = to.unwrap_or_else(|| sender.clone());
+
+    let mut messages: Vec<CosmosMsg> =
+        vec![return_asset.into_msg(&deps.querier, receiver.clone())?];
+
+    // Maker fee
+    let mut maker_fee_amount = Uint128::new(0);
+    if let Some(fee_address) = fee_info.fee_address {
+        if let Some(f) = calculate_maker_fee(
+            ask_asset_info.clone(),
+            commission_amount,
+            fee_info.maker_fee_rate,
+        ) {
+            messages.push(f.clone().into_msg(&deps.querier, fee_address)?);
+            maker_fee_amount = f.amount;
+        }
+    }
+
+    let mut reserves: Vec<Uint128> = pools.iter().map(|pool| pool.amount).collect();
+    reserves[offer_idx] = reserves[offer_idx].checked_add(offer_amount)?;
+    reserves[ask_idx] = reserves[ask_idx].checked_sub(return_amount + maker_fee_amount)?;
+    RESERVES.save(deps.storage, &reserves)?;
+
+    Ok(Response::new()
+        .add_messages(
+            // 1. send collateral token from the contract to a user
+            // 2. send inactive commission to collector
+            messages,
+        )
+        .add_attribute("action", "swap")
+        .add_attribute("sender", sender.as_str())
+        .add_attribute("receiver", receiver.as_str())
+        .add_attribute("offer_asset", offer_asset.info.to_string())
+        .add_attribute("ask_asset", ask_asset_info.to_string())
+        .add_attribute("offer_amount", offer_amount.to_string())
+        .add_attribute("return_amount", return_amount.to_string())
+        .add_attribute("tax_amount", tax_amount.to_string())
+        .add_attribute("spread_amount", spread_amount.to_string())
+        .add_attribute("commission_amount", commission_amount.to_string())
+        .add_attribute("maker_fee_amount", maker_fee_amount.to_string()))
+}
+
+/// ## Description
+/// Sets the tracked pool reserves to the current balances of the pair, so that assets sent to the pair
+/// directly are added to the pool.
+/// Returns a [`ContractError`] on failure, otherwise returns a [`Response`] with the specified
+/// attributes if the operation was successful.
+/// ## Params
+/// * **deps** is an object of type [`DepsMut`].
+///
+/// * **env** is an object of type [`Env`].
+pub fn sync(deps: DepsMut, env: Env) -> Result<Response, ContractError> {
+    let config: Config = CONFIG.load(deps.storage)?;
+    let pools = config
+        .pair_info
+        .query_pools(&deps.querier, env.contract.address)?;
+    RESERVES.save(
+        deps.storage,
+        &pools
+            .iter()
+            .map(|pool| pool.amount)
+            .collect::<Vec<Uint128>>(),
+    )?;
+
+    Ok(Response::new()
+        .add_attribute("action", "sync")
+        .add_attribute("reserves", format_assets(&pools)))
+}
+
+/// ## Description
+/// Sends the balances of the pair that exceed the tracked pool reserves to a recipient.
+/// Returns a [`ContractError`] on failure, otherwise returns a [`Response`] with the specified
+/// attributes if the operation was successful.
+/// ## Params
+/// * **deps** is an object of type [`DepsMut`].
+///
+/// * **env** is an object of type [`Env`].
+///
+/// * **info** is an object of type [`MessageInfo`].
+///
+/// * **to** is an [`Option`] field of type [`String`]. This is the recipient of the excess assets.
+/// If no custom recipient is specified, the assets are sent to the function caller.
+pub fn skim(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    to: Option<String>,
+) -> Result<Response, ContractError> {
+    let config: Config = CONFIG.load(deps.storage)?;
+    let reserves = query_reserves(deps.storage, &config)?;
+    let pools = config
+        .pair_info
+        .query_pools(&deps.querier, env.contract.address)?;
+
+    let to = addr_validate_to_lower(deps.api, &to.unwrap_or_else(|| info.sender.to_string()))?;
+
+    let excess_assets: Vec<Asset> = pools
+        .iter()
+        .zip(reserves.iter())
+        .map(|(pool, reserve)| Asset {
+            info: pool.info.clone(),
+            amount: pool.amount.saturating_sub(reserve.amount),
+        })
+        .collect();
+    let messages: Vec<CosmosMsg> = excess_assets
+        .iter()
+        .filter(|asset| !asset.amount.is_zero())
+        .map(|asset| asset.clone().into_msg(&deps.querier, to.clone()))
+        .collect::<StdResult<Vec<CosmosMsg>>>()?;
+
+    Ok(Response::new()
+        .add_messages(messages)
+        .add_attribute("action", "skim")
+        .add_attribute("receiver", to.as_str())
+        .add_attribute("assets", format_assets(&excess_assets)))
+}
+
+/// ## Description
+/// Calculates the amount of fees the Maker contract gets according to specified pair parameters.
+/// Returns a [`None`] if the Maker fee is zero, otherwise returns a [`Asset`] struct with the specified attributes.
+/// ## Params
+/// * **pool_info** is an object of type [`AssetInfo`]. Contains information about the pool asset for which the commission will be calculated.
+///
+/// * **commission_amount** is an object of type [`Env`]. This is the total amount of fees charged for a swap.
+///
+/// * **maker_commission_rate** is an object of type [`MessageInfo`]. This is the percentage of fees that go to the Maker contract.
+pub fn calculate_maker_fee(
+    pool_info: AssetInfo,
+    commission_amount: Uint128,
+    maker_commission_rate: Decimal,
+) -> Option<Asset> {
+    let maker_fee: Uint128 = commission_amount * maker_commission_rate;
+    if maker_fee.is_zero() {
+        return None;
+    }
+
+    Some(Asset {
+        info: pool_info,
+        amount: maker_fee,
+    })
+}
+
+/// ## Description
+/// Exposes all the queries available in the contract.
+/// ## Params
+/// * **deps** is an object of type [`Deps`].
+///
+/// * **env** is an object of type [`Env`].
+///
+/// * **msg** is an object of type [`QueryMsg`].
+///
+/// ## Queries
+/// * **QueryMsg::Pair {}** Returns information about the pair in an object of type [`MultiPairInfo`].
+///
+/// * **QueryMsg::Pool {}** Returns information about the amount of assets in the pair contract as
+/// well as the amount of LP tokens issued using an object of type [`PoolResponse`].
+///
+/// * **QueryMsg::Share { amount }** Returns the amount of assets that could be withdrawn from the pool
+/// using a specific amount of LP tokens. The result is returned in a vector that contains objects of type [`Asset`].
+///
+/// * **QueryMsg::Simulation { offer_asset, ask_asset_info }** Returns the result of a swap simulation using a [`SimulationResponse`] object.
+///
+/// * **QueryMsg::ReverseSimulation { offer_asset_info, ask_asset }** Returns the result of a reverse swap simulation using
+/// a [`ReverseSimulationResponse`] object.
+///
+/// * **QueryMsg::Config {}** Returns the configuration for the pair contract using a [`ConfigResponse`] object.
+///
+/// * **QueryMsg::SimulateProvide { assets }** Returns the amount of LP tokens minted for a deposit and
+/// the imbalance fees charged on it using a [`SimulateProvideResponse`] object.
+///
+/// * **QueryMsg::SimulateWithdraw { lp_amount }** Returns the amounts of assets received for burning
+/// LP tokens in a [`Vec<Asset>`] object.
+///
+/// * **QueryMsg::PairStatus {}** Returns the trading state of the pair set in the factory using a [`astroport::factory::PairStatus`] object.
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
+    match msg {
+        QueryMsg::Pair {} => to_binary(&query_pair_info(deps)?),
+        QueryMsg::Pool {} => to_binary(&query_pool(deps)?),
+        QueryMsg::Share { amount } => to_binary(&query_share(deps, amount)?),
+        QueryMsg::Simulation {
+            offer_asset,
+            ask_asset_info,
+        } => to_binary(&query_simulation(deps, env, offer_asset, ask_asset_info)?),
+        QueryMsg::ReverseSimulation {
+            offer_asset_info,
+            ask_asset,
+        } => to_binary(&query_reverse_simulation(
+            deps,
+            env,
+            offer_asset_info,
+            ask_asset,
+        )?),
+        QueryMsg::Config {} => to_binary(&query_config(deps, env)?),
+        QueryMsg::SimulateProvide { assets } => {
+            to_binary(&query_simulate_provide(deps, env, assets)?)
+        }
+        QueryMsg::SimulateWithdraw { lp_amount } => {
+            to_binary(&query_simulate_withdraw(deps, lp_amount)?)
+        }
+        QueryMsg::PairStatus {} => to_binary(&FACTORY_PARAMS.load(deps.storage)?.pair_status),
+    }
+}
+
+/// ## Description
+/// Returns information about the pair contract in an object of type [`MultiPairInfo`].
+/// ## Params
+/// * **deps** is an object of type [`Deps`].
+pub fn query_pair_info(deps: Deps) -> StdResult<MultiPairInfo> {
+    let config: Config = CONFIG.load(deps.storage)?;
+    Ok(config.pair_info)
+}
+
+/// ## Description
+/// Returns the amounts of assets in the pair contract as well as the amount of LP
+/// tokens currently minted in an object of type [`PoolResponse`].
+/// ## Params
+/// * **deps** is an object of type [`Deps`].
+pub fn query_pool(deps: Deps) -> StdResult<PoolResponse> {
+    let config: Config = CONFIG.load(deps.storage)?;
+    let (assets, total_share) = pool_info(deps, config)?;
+
+    let resp = PoolResponse {
+        assets,
+        total_share,
+    };
+
+    Ok(resp)
+}
+
+/// ## Description
+/// Returns the amount of assets that could be withdrawn from the pool using a specific amount of LP tokens.
+/// The result is returned in a vector that contains objects of type [`Asset`].
+/// ## Params
+/// * **deps** is an object of type [`Deps`].
+///
+/// * **amount** is an object of type [`Uint128`]. This is the amount of LP tokens for which we calculate associated amounts of assets.
+pub fn query_share(deps: Deps, amount: Uint128) -> StdResult<Vec<Asset>> {
+    let config: Config = CONFIG.load(deps.storage)?;
+    let (pools, total_share) = pool_info(deps, config)?;
+    let refund_assets = get_share_in_assets(&pools, amount, total_share);
+
+    Ok(refund_assets)
+}
+
+/// ## Description
+/// Returns the amount of LP tokens minted for a deposit and the imbalance fees charged on it
+/// in an object of type [`SimulateProvideResponse`]. The share is calculated in the same way
+/// as in [`provide_liquidity`].
+/// ## Params
+/// * **deps** is an object of type [`Deps`].
+///
+/// * **env** is an object of type [`Env`].
+///
+/// * **assets** is a vector with items of type [`Asset`]. These are the assets to deposit.
+pub fn query_simulate_provide(
+    deps: Deps,
+    env: Env,
+    assets: Vec<Asset>,
+) -> StdResult<SimulateProvideResponse> {
+    let config: Config = CONFIG.load(deps.storage)?;
+    let (pools, total_share) = pool_info(deps, config.clone())?;
+    let deposits = get_deposits(&assets, &pools)?;
+
+    let (share, fees) = calculate_share(deps, &env, &config, &deposits, &pools, total_share)?;
+
+    Ok(SimulateProvideResponse {
+        share,
+        fees: pools
+            .iter()
+            .zip(fees)
+            .map(|(pool, fee)| Asset {
+                info: pool.info.clone(),
+                amount: fee,
+            })
+            .collect(),
+    })
+}
+
+/// ## Description
+/// Returns the amounts of assets received for burning LP tokens in a [`Vec<Asset>`] object.
+/// The amounts are calculated in the same way as in [`withdraw_liquidity`].
+/// ## Params
+/// * **deps** is an object of type [`Deps`].
+///
+/// * **lp_amount** is an object of type [`Uint128`]. This is the amount of LP tokens to burn.
+pub fn query_simulate_withdraw(deps: Deps, lp_amount: Uint128) -> StdResult<Vec<Asset>> {
+    let config: Config = CONFIG.load(deps.storage)?;
+    let (pools, total_share) = pool_info(deps, config)?;
+
+    Ok(get_share_in_assets(&pools, lp_amount, total_share))
+}
+
+/// ## Description
+/// Returns information about a swap simulation in a [`SimulationResponse`] object.
+/// ## Params
+/// * **deps** is an object of type [`Deps`].
+///
+/// * **env** is an object of type [`Env`].
+///
+/// * **offer_asset** is an object of type [`Asset`]. This is the asset to swap as well as an amount of the said asset.
+///
+/// * **ask_asset_info** is an object of type [`AssetInfo`]. This is the asset to swap to.
+pub fn query_simulation(
+    deps: Deps,
+    env: Env,
+    offer_asset: Asset,
+    ask_asset_info: AssetInfo,
+) -> StdResult<SimulationResponse> {
+    let config: Config = CONFIG.load(deps.storage)?;
+    let pools = query_reserves(deps.storage, &config)?;
+
+    let (offer_idx, ask_idx) = find_swap_indexes(&pools, &offer_asset.info, &ask_asset_info)
+        .map_err(|_| StdError::generic_err("Given assets don't belong to the pool"))?;
+
+    // Get the fee info cached from the factory
+    let fee_info = FACTORY_PARAMS.load(deps.storage)?.fee_info;
+
+    let (return_amount, spread_amount, commission_amount) = compute_swap(
+        &pools,
+        &query_precisions(deps, &pools)?,
+        offer_idx,
+        ask_idx,
+        offer_asset.amount,
+        fee_info.total_fee_rate,
+        compute_current_amp(&config, &env)?,
+    )?;
+
+    Ok(SimulationResponse {
+        return_amount,
+        spread_amount,
+        commission_amount,
+    })
+}
+
+/// ## Description
+/// Returns information about a reverse swap simulation in a [`ReverseSimulationResponse`] object.
+/// ## Params
+/// * **deps** is an object of type [`Deps`].
+///
+/// * **env** is an object of type [`Env`].
+///
+/// * **offer_asset_info** is an object of type [`AssetInfo`]. This is the asset to swap.
+///
+/// * **ask_asset** is an object of type [`Asset`]. This is the asset to swap to as well as the desired
+/// amount of ask assets to receive from the swap.
+pub fn query_reverse_simulation(
+    deps: Deps,
+    env: Env,
+    offer_asset_info: AssetInfo,
+    ask_asset: Asset,
+) -> StdResult<ReverseSimulationResponse> {
+    let config: Config = CONFIG.load(deps.storage)?;
+    let pools = query_reserves(deps.storage, &config)?;
+
+    let (offer_idx, ask_idx) = find_swap_indexes(&pools, &offer_asset_info, &ask_asset.info)
+        .map_err(|_| StdError::generic_err("Given assets don't belong to the pool"))?;
+
+    // Get the fee info cached from the factory
+    let fee_info = FACTORY_PARAMS.load(deps.storage)?.fee_info;
+
+    let (offer_amount, spread_amount, commission_amount) = compute_offer_amount(
+        &pools,
+        &query_precisions(deps, &pools)?,
+        offer_idx,
+        ask_idx,
+        ask_asset.amount,
+        fee_info.total_fee_rate,
+        compute_current_amp(&config, &env)?,
+    )?;
+
+    Ok(ReverseSimulationResponse {
+        offer_amount,
+        spread_amount,
+        commission_amount,
+    })
+}
+
+/// ## Description
+/// Returns the pair contract configuration in a [`ConfigResponse`] object.
+/// ## Params
+/// * **deps** is an object of type [`Deps`].
+///
+/// * **env** is an object of type [`Env`].
+pub fn query_config(deps: Deps, env: Env) -> StdResult<ConfigResponse> {
+    let config: Config = CONFIG.load(deps.storage)?;
+    Ok(ConfigResponse {
+        amp: Decimal::from_ratio(compute_current_amp(&config, &env)?, AMP_PRECISION),
+    })
+}
+
+/// ## Description
+/// Returns the indexes of the offer and ask assets in `pools`.
+/// Returns a [`ContractError`] if any of the assets doesn't belong to the pool or if they are the same asset.
+/// ## Params
+/// * **pools** is a slice of [`Asset`] type items. These are the assets available in the pool.
+///
+/// * **offer_asset_info** is an object of type [`AssetInfo`]. This is the asset to swap.
+///
+/// * **ask_asset_info** is an object of type [`AssetInfo`]. This is the asset to swap to.
+fn find_swap_indexes(
+    pools: &[Asset],
+    offer_asset_info: &AssetInfo,
+    ask_asset_info: &AssetInfo,
+) -> Result<(usize, usize), ContractError> {
+    if offer_asset_info.equal(ask_asset_info) {
+        return Err(ContractError::DoublingAssets {});
+    }
+
+    let offer_idx = pools
+        .iter()
+        .position(|pool| pool.info.equal(offer_asset_info))
+        .ok_or(ContractError::AssetMismatch {})?;
+    let ask_idx = pools
+        .iter()
+        .position(|pool| pool.info.equal(ask_asset_info))
+        .ok_or(ContractError::AssetMismatch {})?;
+
+    Ok((offer_idx, ask_idx))
+}
+
+/// ## Description
+/// Returns the result of a swap.
+/// ## Params
+/// * **pools** is a slice of [`Asset`] type items. These are the assets available in the pool.
+///
+/// * **precisions** is a slice of [`u8`]. These are the token precisions of the pool assets.
+///
+/// * **offer_idx** is an object of type [`usize`]. This is the index of the offer asset in `pools`.
+///
+/// * **ask_idx** is an object of type [`usize`]. This is the index of the ask asset in `pools`.
+///
+/// * **offer_amount** is an object of type [`Uint128`]. This is the amount of offer assets to swap.
+///
+/// * **commission_rate** is an object of type [`Decimal`]. This is the total amount of fees charged for the swap.
+///
+/// * **amp** is an object of type [`u64`]. This is the pool amplification used to calculate the swap result.
+fn compute_swap(
+    pools: &[Asset],
+    precisions: &[u8],
+    offer_idx: usize,
+    ask_idx: usize,
+    offer_amount: Uint128,
+    commission_rate: Decimal,
+    amp: u64,
+) -> StdResult<(Uint128, Uint128, Uint128)> {
+    // offer => ask
+
+    let greater_precision = *precisions.iter().max().unwrap();
+    let balances = normalize_amounts(
+        pools.iter().map(|pool| pool.amount),
+        precisions,
+        greater_precision,
+    )?;
+    let offer_amount = adjust_precision(offer_amount, precisions[offer_idx], greater_precision)?;
+
+    let return_amount = Uint128::new(
+        calc_ask_amount(
+            &to_u128(&balances),
+            offer_idx,
+            ask_idx,
+            offer_amount.u128(),
+            amp,
+        )
+        .ok_or_else(|| StdError::generic_err("Failed to calculate the swap result"))?,
+    );
+
+    // We assume the assets should stay in a 1:1 ratio, so the true exchange rate is 1. So any exchange rate <1 could be considered the spread
+    let spread_amount = offer_amount.saturating_sub(return_amount);
+
+    let commission_amount: Uint128 = return_amount * commission_rate;
+
+    // The commission will be absorbed by the pool
+    let return_amount: Uint128 = return_amount.checked_sub(commission_amount)?;
+
+    let ask_precision = precisions[ask_idx];
+    let return_amount = adjust_precision(return_amount, greater_precision, ask_precision)?;
+    let spread_amount = adjust_precision(spread_amount, greater_precision, ask_precision)?;
+    let commission_amount = adjust_precision(commission_amount, greater_precision, ask_precision)?;
+
+    Ok((return_amount, spread_amount, commission_amount))
+}
+
+/// ## Description
+/// Returns an amount of offer assets for a specified amount of ask assets.
+/// ## Params
+/// * **pools** is a slice of [`Asset`] type items. These are the assets available in the pool.
+///
+/// * **precisions** is a slice of [`u8`]. These are the token precisions of the pool assets.
+///
+/// * **offer_idx** is an object of type [`usize`]. This is the index of the offer asset in `pools`.
+///
+/// * **ask_idx** is an object of type [`usize`]. This is the index of the ask asset in `pools`.
+///
+/// * **ask_amount** is an object of type [`Uint128`]. This is the amount of ask assets to swap to.
+///
+/// * **commission_rate** is an object of type [`Decimal`]. This is the total amount of fees charged for the swap.
+///
+/// * **amp** is an object of type [`u64`]. This is the pool amplification used to calculate the swap result.
+fn compute_offer_amount(
+    pools: &[Asset],
+    precisions: &[u8],
+    offer_idx: usize,
+    ask_idx: usize,
+    ask_amount: Uint128,
+    commission_rate: Decimal,
+    amp: u64,
+) -> StdResult<(Uint128, Uint128, Uint128)> {
+    // ask => offer
+
+    let greater_precision = *precisions.iter().max().unwrap();
+    let balances = normalize_amounts(
+        pools.iter().map(|pool| pool.amount),
+        precisions,
+        greater_precision,
+    )?;
+    let ask_amount = adjust_precision(ask_amount, precisions[ask_idx], greater_precision)?;
+
+    let one_minus_commission = Decimal256::one() - Decimal256::from(commission_rate);
+    let inv_one_minus_commission: Decimal = (Decimal256::one() / one_minus_commission).into();
+    let before_commission_deduction = ask_amount * inv_one_minus_commission;
+
+    let offer_amount = Uint128::new(
+        calc_offer_amount(
+            &to_u128(&balances),
+            offer_idx,
+            ask_idx,
+            before_commission_deduction.u128(),
+            amp,
+        )
+        .ok_or_else(|| StdError::generic_err("Failed to calculate the swap result"))?,
+    );
+
+    // We assume the assets should stay in a 1:1 ratio, so the true exchange rate is 1. Any exchange rate < 1 could be considered the spread
+    let spread_amount = offer_amount.saturating_sub(before_commission_deduction);
+
+    let commission_amount = before_commission_deduction * commission_rate;
+
+    let ask_precision = precisions[ask_idx];
+    let offer_amount = adjust_precision(offer_amount, greater_precision, precisions[offer_idx])?;
+    let spread_amount = adjust_precision(spread_amount, greater_precision, ask_precision)?;
+    let commission_amount = adjust_precision(commission_amount, greater_precision, ask_precision)?;
+
+    Ok((offer_amount, spread_amount, commission_amount))
+}
+
+/// ## Description
+/// Returns the stableswap invariant for the specified balances. Returns a [`StdError`] if it cannot be calculated.
+/// ## Params
+/// * **leverage** is an object of type [`u64`]. This is the amplification multiplied by the amount of assets.
+///
+/// * **balances** is a slice of [`Uint128`]. These are the pool balances using the same precision.
+fn invariant(leverage: u64, balances: &[Uint128]) -> StdResult<u128> {
+    compute_d(leverage, &to_u128(balances))
+        .ok_or_else(|| StdError::generic_err("Failed to calculate the D invariant"))
+}
+
+/// ## Description
+/// Converts a slice of [`Uint128`] values to a vector of [`u128`] values.
+fn to_u128(values: &[Uint128]) -> Vec<u128> {
+    values.iter().map(|value| value.u128()).collect()
+}
+
+/// ## Description
+/// Returns the token precisions of all pool assets.
+/// ## Params
+/// * **deps** is an object of type [`Deps`].
+///
+/// * **pools** is a slice of [`Asset`] type items. These are the assets available in the pool.
+fn query_precisions(deps: Deps, pools: &[Asset]) -> StdResult<Vec<u8>> {
+    pools
+        .iter()
+        .map(|pool| query_token_precision(&deps.querier, pool.info.clone()))
+        .collect()
+}
+
+/// ## Description
+/// Returns the amounts adjusted from their token precisions to `new_precision`.
+/// ## Params
+/// * **amounts** is an iterator over items of type [`Uint128`].
+///
+/// * **precisions** is a slice of [`u8`]. These are the current precisions of the amounts.
+///
+/// * **new_precision** is an object of type [`u8`]. This is the precision to use for all amounts.
+fn normalize_amounts(
+    amounts: impl Iterator<Item = Uint128>,
+    precisions: &[u8],
+    new_precision: u8,
+) -> StdResult<Vec<Uint128>> {
+    amounts
+        .zip(precisions.iter())
+        .map(|(amount, precision)| adjust_precision(amount, *precision, new_precision))
+        .collect()
+}
+
+/// ## Description
+/// Return a value using a newly specified precision.
+/// ## Params
+/// * **value** is an object of type [`Uint128`]. This is the value that will have its precision adjusted.
+///
+/// * **current_precision** is an object of type [`u8`]. This is the `value`'s current precision
+///
+/// * **new_precision** is an object of type [`u8`]. This is the new precision to use when returning the `value`.
+fn adjust_precision(
+    value: Uint128,
+    current_precision: u8,
+    new_precision: u8,
+) -> StdResult<Uint128> {
+    Ok(match current_precision.cmp(&new_precision) {
+        Ordering::Equal => value,
+        Ordering::Less => value.checked_mul(Uint128::new(
+            10_u128.pow((new_precision - current_precision) as u32),
+        ))?,
+        Ordering::Greater => value.checked_div(Uint128::new(
+            10_u128.pow((current_precision - new_precision) as u32),
+        ))?,
+    })
+}
+
+/// ## Description
+/// Returns the assets formatted as a comma separated string.
+/// ## Params
+/// * **assets** is a slice of [`Asset`] type items.
+fn format_assets(assets: &[Asset]) -> String {
+    assets
+        .iter()
+        .map(|asset| asset.to_string())
+        .collect::<Vec<String>>()
+        .join(", ")
+}
+
+/// ## Description
+/// Returns a [`ContractError`] if the factory paused swaps in the pair.
+/// ## Params
+/// * **storage** is a reference to an object implementing [`Storage`].
+fn assert_swaps_allowed(storage: &dyn Storage) -> Result<(), ContractError> {
+    if !FACTORY_PARAMS.load(storage)?.pair_status.swaps_allowed() {
+        return Err(ContractError::SwapsPaused {});
+    }
+
+    Ok(())
+}
+
+/// ## Description
+/// Returns a [`ContractError`] if the factory paused deposits in the pair.
+/// ## Params
+/// * **storage** is a reference to an object implementing [`Storage`].
+fn assert_deposits_allowed(storage: &dyn Storage) -> Result<(), ContractError> {
+    if !FACTORY_PARAMS.load(storage)?.pair_status.deposits_allowed() {
+        return Err(ContractError::DepositsPaused {});
+    }
+
+    Ok(())
+}
+
+/// ## Description
+/// Returns a [`ContractError`] on failure.
+/// If `belief_price` and `max_spread` are both specified, we compute a new spread,
+/// otherwise we just use the swap spread to check `max_spread`.
+/// ## Params
+/// * **belief_price** is an object of type [`Option<Decimal>`]. This is the belief price used in the swap.
+///
+/// * **max_spread** is an object of type [`Option<Decimal>`]. This is the
+/// max spread allowed so that the swap can be executed successfuly.
+///
+/// * **offer_amount** is an object of type [`Uint128`]. This is the amount of assets to swap.
+///
+/// * **return_amount** is an object of type [`Uint128`]. This is the amount of assets to receive from the swap.
+///
+/// * **spread_amount** is an object of type [`Uint128`]. This is the spread used in the swap.
+pub fn assert_max_spread(
+    belief_price: Option<Decimal>,
+    max_spread: Option<Decimal>,
+    offer_amount: Uint128,
+    return_amount: Uint128,
+    spread_amount: Uint128,
+) -> Result<(), ContractError> {
+    let default_spread = Decimal::from_str(DEFAULT_SLIPPAGE)?;
+    let max_allowed_spread = Decimal::from_str(MAX_ALLOWED_SLIPPAGE)?;
+
+    let max_spread = max_spread.unwrap_or(default_spread);
+    if max_spread.gt(&max_allowed_spread) {
+        return Err(ContractError::AllowedSpreadAssertion {});
+    }
+
+    if let Some(belief_price) = belief_price {
+        let expected_return =
+            offer_amount * Decimal::from(Decimal256::one() / Decimal256::from(belief_price));
+        let spread_amount = expected_return
+            .checked_sub(return_amount)
+            .unwrap_or_else(|_| Uint128::zero());
+
+        if return_amount < expected_return
+            && Decimal::from_ratio(spread_amount, expected_return) > max_spread
+        {
+            return Err(ContractError::MaxSpreadAssertion {});
+        }
+    } else if Decimal::from_ratio(spread_amount, return_amount + spread_amount) > max_spread {
+        return Err(ContractError::MaxSpreadAssertion {});
+    }
+
+    Ok(())
+}
+
+/// ## Description
+/// Returns a [`ContractError`] on failure if the current block time is past the specified deadline.
+/// ## Params
+/// * **env** is an object of type [`Env`].
+///
+/// * **deadline** is an [`Option`] field of type [`u64`]. This is the block time (in seconds) after which
+/// the operation fails.
+pub fn assert_deadline(env: &Env, deadline: Option<u64>) -> Result<(), ContractError> {
+    if let Some(deadline) = deadline {
+        if env.block.time.seconds() > deadline {
+            return Err(ContractError::DeadlineExpired {});
+        }
+    }
+
+    Ok(())
+}
+
+/// ## Description
+/// Returns a [`ContractError`] on failure if the amount of any withdrawn asset is less than the specified minimum.
+/// ## Params
+/// * **refund_assets** are an array of [`Asset`] type items. These are the assets withdrawn from the pool.
+///
+/// * **min_assets_out** is an [`Option`] field of type [`Vec<Asset>`]. These are the minimum amounts of
+/// assets to receive.
+fn assert_min_assets_out(
+    refund_assets: &[Asset],
+    min_assets_out: Option<Vec<Asset>>,
+) -> Result<(), ContractError> {
+    for min_asset in min_assets_out.unwrap_or_default() {
+        let refund_asset = refund_assets
+            .iter()
+            .find(|a| a.info.equal(&min_asset.info))
+            .ok_or(ContractError::AssetMismatch {})?;
+        if refund_asset.amount < min_asset.amount {
+            return Err(ContractError::MinAssetsOutAssertion {});
+        }
+    }
+
+    Ok(())
+}
+
+/// ## Description
+/// Updates the fee and Generator information that the pair caches from the factory.
+/// The factory pushes this message to its pairs when their parameters change, but anyone can execute it.
+/// ## Params
+/// * **deps** is an object of type [`DepsMut`].
+///
+/// * **env** is an object of type [`Env`].
+pub fn refresh_factory_params(deps: DepsMut, env: Env) -> Result<Response, ContractError> {
+    let config: Config = CONFIG.load(deps.storage)?;
+    let factory_params = query_factory_params(
+        &deps.querier,
+        config.factory_addr,
+        env.contract.address,
+        config.pair_info.pair_type,
+    )?;
+    FACTORY_PARAMS.save(deps.storage, &factory_params)?;
+
+    let fee_address = factory_params
+        .fee_info
+        .fee_address
+        .map(|addr| addr.to_string())
+        .unwrap_or_default();
+    let generator_address = factory_params
+        .generator_address
+        .map(|addr| addr.to_string())
+        .unwrap_or_default();
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "refresh_factory_params"),
+        attr(
+            "total_fee_rate",
+            factory_params.fee_info.total_fee_rate.to_string(),
+        ),
+        attr(
+            "maker_fee_rate",
+            factory_params.fee_info.maker_fee_rate.to_string(),
+        ),
+        attr("fee_address", fee_address),
+        attr("generator_address", generator_address),
+        attr("pair_status", factory_params.pair_status.to_string()),
+    ]))
+}
+
+/// ## Description
+/// Used for contract migration. Returns a default object of type [`Response`].
+/// ## Params
+/// * **_deps** is an object of type [`DepsMut`].
+///
+/// * **_env** is an object of type [`Env`].
+///
+/// * **_msg** is an object of type [`MigrateMsg`].
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn migrate(_deps: DepsMut, _env: Env, _msg: MigrateMsg) -> StdResult<Response> {
+    Ok(Response::default())
+}
+
+/// ## Description
+/// Returns the tracked reserves of the pool assets in the order of the pair asset infos.
+/// ## Params
+/// * **storage** is an object of type [`Storage`].
+///
+/// * **config** is an object of type [`Config`].
+pub fn query_reserves(storage: &dyn Storage, config: &Config) -> StdResult<Vec<Asset>> {
+    let reserves = RESERVES.load(storage)?;
+
+    Ok(config
+        .pair_info
+        .asset_infos
+        .iter()
+        .zip(reserves)
+        .map(|(info, amount)| Asset {
+            info: info.clone(),
+            amount,
+        })
+        .collect())
+}
+
+/// ## Description
+/// Returns information about the pool.
+/// ## Params
+/// * **deps** is an object of type [`Deps`].
+///
+/// * **config** is an object of type [`Config`].
+pub fn pool_info(deps: Deps, config: Config) -> StdResult<(Vec<Asset>, Uint128)> {
+    let pools = query_reserves(deps.storage, &config)?;
+    let total_share: Uint128 = query_supply(&deps.querier, config.pair_info.liquidity_token)?;
+
+    Ok((pools, total_share))
+}
+
+/// ## Description
+/// Updates the pool configuration with the specified parameters in the `params` variable.
+/// Returns a [`ContractError`] as a failure, otherwise returns a [`Response`] with the specified
+/// attributes if the operation was successful
+/// ## Params
+/// * **deps** is an object of type [`DepsMut`].
+///
+/// * **env** is an object of type [`Env`].
+///
+/// * **info** is an object of type [`MessageInfo`].
+///
+/// * **params** is an object of type [`Binary`]. These are the the new parameter values.
+pub fn update_config(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    params: Binary,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    let factory_config = query_factory_config(&deps.querier, config.factory_addr.clone())?;
+
+    if info.sender != factory_config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    match from_binary::<StablePoolUpdateParams>(&params)? {
+        StablePoolUpdateParams::StartChangingAmp {
+            next_amp,
+            next_amp_time,
+        } => start_changing_amp(config, deps, env, next_amp, next_amp_time)?,
+        StablePoolUpdateParams::StopChangingAmp {} => stop_changing_amp(config, deps, env)?,
+        StablePoolUpdateParams::SetFeeMultiplier { .. }
+        | StablePoolUpdateParams::SetObservationsSize { .. } => {
+            return Err(ContractError::NonSupported {})
+        }
+    }
+
+    Ok(Response::default())
+}
+
+/// ## Description
+/// Start changing the AMP value. Returns a [`ContractError`] on failure, otherwise returns [`Ok`].
+/// ## Params
+/// * **mut config** is an object of type [`Config`]. This is a mutable reference to the pool configuration.
+///
+/// * **deps** is an object of type [`DepsMut`].
+///
+/// * **env** is an object of type [`Env`].
+///
+/// * **next_amp** is an object of type [`u64`]. This is the new value for AMP.
+///
+/// * **next_amp_time** is an object of type [`u64`]. This is the end time when the pool amplification will be equal to `next_amp`.
+fn start_changing_amp(
+    mut config: Config,
+    deps: DepsMut,
+    env: Env,
+    next_amp: u64,
+    next_amp_time: u64,
+) -> Result<(), ContractError> {
+    if next_amp == 0 || next_amp > MAX_AMP {
+        return Err(ContractError::IncorrectAmp {});
+    }
+
+    let current_amp = compute_current_amp(&config, &env)?;
+
+    let next_amp_with_precision = next_amp * AMP_PRECISION;
+
+    if next_amp_with_precision * MAX_AMP_CHANGE < current_amp
+        || next_amp_with_precision > current_amp * MAX_AMP_CHANGE
+    {
+        return Err(ContractError::MaxAmpChangeAssertion {});
+    }
+
+    let block_time = env.block.time.seconds();
+
+    if block_time < config.init_amp_time + MIN_AMP_CHANGING_TIME
+        || next_amp_time < block_time + MIN_AMP_CHANGING_TIME
+    {
+        return Err(ContractError::MinAmpChangingTimeAssertion {});
+    }
+
+    config.init_amp = current_amp;
+    config.next_amp = next_amp_with_precision;
+    config.init_amp_time = block_time;
+    config.next_amp_time = next_amp_time;
+
+    CONFIG.save(deps.storage, &config)?;
+
+    Ok(())
+}
+
+/// ## Description
+/// Stop changing the AMP value. Returns [`Ok`].
+/// ## Params
+/// * **mut config** is an object of type [`Config`]. This is a mutable reference to the pool configuration.
+///
+/// * **deps** is an object of type [`DepsMut`].
+///
+/// * **env** is an object of type [`Env`].
+fn stop_changing_amp(mut config: Config, deps: DepsMut, env: Env) -> StdResult<()> {
+    let current_amp = compute_current_amp(&config, &env)?;
+    let block_time = env.block.time.seconds();
+
+    config.init_amp = current_amp;
+    config.next_amp = current_amp;
+    config.init_amp_time = block_time;
+    config.next_amp_time = block_time;
+
+    // now (block_time < next_amp_time) is always False, so we return the saved AMP
+    CONFIG.save(deps.storage, &config)?;
+
+    Ok(())
+}
+
+/// ## Description
+/// Compute the current pool amplification coefficient (AMP).
+/// ## Params
+/// * **config** is an object of type [`Config`].
+///
+/// * **env** is an object of type [`Env`].
+fn compute_current_amp(config: &Config, env: &Env) -> StdResult<u64> {
+    let block_time = env.block.time.seconds();
+
+    if block_time < config.next_amp_time {
+        let elapsed_time =
+            Uint128::from(block_time).checked_sub(Uint128::from(config.init_amp_time))?;
+        let time_range =
+            Uint128::from(config.next_amp_time).checked_sub(Uint128::from(config.init_amp_time))?;
+        let init_amp = Uint128::from(config.init_amp);
+        let next_amp = Uint128::from(config.next_amp);
+
+        if config.next_amp > config.init_amp {
+            let amp_range = next_amp - init_amp;
+            let res = init_amp + (amp_range * elapsed_time).checked_div(time_range)?;
+            Ok(res.u128() as u64)
+        } else {
+            let amp_range = init_amp - next_amp;
+            let res = init_amp - (amp_range * elapsed_time).checked_div(time_range)?;
+            Ok(res.u128() as u64)
+        }
+    } else {
+        Ok(config.next_amp)
+    }
+}
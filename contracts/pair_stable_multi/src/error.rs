@@ -0,0 +1,104 @@
+use crate::math::{MAX_AMP, MAX_AMP_CHANGE, MIN_AMP_CHANGING_TIME};
+use astroport::pair::MINIMUM_LIQUIDITY;
+use astroport::pair_stable_multi::{MAX_ASSETS_NUM, MIN_ASSETS_NUM};
+use cosmwasm_std::{OverflowError, StdError};
+use thiserror::Error;
+
+/// ## Description
+/// This enum describes multi-asset stableswap pair contract errors!
+#[derive(Error, Debug, PartialEq)]
+pub enum ContractError {
+    #[error("{0}")]
+    Std(#[from] StdError),
+
+    #[error("Unauthorized")]
+    Unauthorized {},
+
+    #[error("Operation non supported")]
+    NonSupported {},
+
+    #[error("Doubling assets in asset infos")]
+    DoublingAssets {},
+
+    #[error(
+        "The pool must contain from {} to {} assets",
+        MIN_ASSETS_NUM,
+        MAX_ASSETS_NUM
+    )]
+    InvalidNumberOfAssets {},
+
+    #[error("Event of zero transfer")]
+    InvalidZeroAmount {},
+
+    #[error("Insufficient amount of liquidity")]
+    LiquidityAmountTooSmall {},
+
+    #[error("Provided spread amount exceeds allowed limit")]
+    AllowedSpreadAssertion {},
+
+    #[error("Operation exceeds max spread limit")]
+    MaxSpreadAssertion {},
+
+    #[error("Asset mismatch between the requested and the stored asset in contract")]
+    AssetMismatch {},
+
+    #[error(
+        "Amp coefficient must be greater than 0 and less than or equal to {}",
+        MAX_AMP
+    )]
+    IncorrectAmp {},
+
+    #[error(
+        "The difference between the old and new amp value must not exceed {} times",
+        MAX_AMP_CHANGE
+    )]
+    MaxAmpChangeAssertion {},
+
+    #[error(
+        "Amp coefficient cannot be changed more often than once per {} seconds",
+        MIN_AMP_CHANGING_TIME
+    )]
+    MinAmpChangingTimeAssertion {},
+
+    #[error("You need to provide init params")]
+    InitParamsNotFound {},
+
+    #[error("Generator address is not set in factory. Cannot autostake")]
+    AutoStakeError {},
+
+    #[error("Initial liquidity must be more than {}", MINIMUM_LIQUIDITY)]
+    MinimumLiquidityAmountError {},
+
+    #[error("Swaps are paused in the pair")]
+    SwapsPaused {},
+
+    #[error("Deposits are paused in the pair")]
+    DepositsPaused {},
+
+    #[error("Transaction deadline has expired")]
+    DeadlineExpired {},
+
+    #[error("Return amount is less than min_return")]
+    MinReturnAssertion {},
+
+    #[error("Minted LP amount is less than min_lp_to_receive")]
+    MinLpToReceiveAssertion {},
+
+    #[error("Withdrawn amount is less than min_assets_out")]
+    MinAssetsOutAssertion {},
+}
+
+impl From<OverflowError> for ContractError {
+    fn from(o: OverflowError) -> Self {
+        StdError::from(o).into()
+    }
+}
+
+impl From<ContractError> for StdError {
+    fn from(err: ContractError) -> Self {
+        match err {
+            ContractError::Std(err) => err,
+            err => StdError::generic_err(err.to_string()),
+        }
+    }
+}
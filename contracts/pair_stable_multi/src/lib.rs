@@ -0,0 +1,13 @@
+pub mod contract;
+pub mod math;
+pub mod state;
+
+pub mod error;
+
+mod response;
+
+#[cfg(test)]
+mod testing;
+
+#[cfg(test)]
+mod mock_querier;
@@ -0,0 +1,208 @@
+use std::convert::TryFrom;
+
+use astroport::U256;
+
+const ITERATIONS: u8 = 64;
+
+pub const MAX_AMP: u64 = 1_000_000;
+pub const MAX_AMP_CHANGE: u64 = 10;
+pub const MIN_AMP_CHANGING_TIME: u64 = 86400;
+pub const AMP_PRECISION: u64 = 100;
+
+/// ## Description
+/// Calculates the ask amount (the amount of tokens swapped to).
+/// ## Params
+/// * **pools** is a slice of [`u128`]. These are the amounts of all tokens currently in the pool.
+///
+/// * **offer_idx** is an object of type [`usize`]. This is the index of the offer token in `pools`.
+///
+/// * **ask_idx** is an object of type [`usize`]. This is the index of the ask token in `pools`.
+///
+/// * **offer_amount** is an object of type [`u128`]. This is the amount of offer tokens to swap.
+///
+/// * **amp** is an object of type [`u64`]. This is the pool's amplification parameter.
+pub fn calc_ask_amount(
+    pools: &[u128],
+    offer_idx: usize,
+    ask_idx: usize,
+    offer_amount: u128,
+    amp: u64,
+) -> Option<u128> {
+    let leverage = amp.checked_mul(pools.len() as u64)?;
+    let d = compute_d(leverage, pools)?;
+
+    let mut new_pools = pools.to_vec();
+    new_pools[offer_idx] = new_pools[offer_idx].checked_add(offer_amount)?;
+
+    let new_ask_pool = compute_new_balance(leverage, &new_pools, ask_idx, d)?;
+
+    pools[ask_idx].checked_sub(new_ask_pool)
+}
+
+/// ## Description
+/// Calculates the amount to be swapped (the offer amount).
+/// ## Params
+/// * **pools** is a slice of [`u128`]. These are the amounts of all tokens currently in the pool.
+///
+/// * **offer_idx** is an object of type [`usize`]. This is the index of the offer token in `pools`.
+///
+/// * **ask_idx** is an object of type [`usize`]. This is the index of the ask token in `pools`.
+///
+/// * **ask_amount** is an object of type [`u128`]. This is the amount of ask tokens to receive.
+///
+/// * **amp** is an object of type [`u64`]. This is the pool's amplification parameter.
+pub fn calc_offer_amount(
+    pools: &[u128],
+    offer_idx: usize,
+    ask_idx: usize,
+    ask_amount: u128,
+    amp: u64,
+) -> Option<u128> {
+    let leverage = amp.checked_mul(pools.len() as u64)?;
+    let d = compute_d(leverage, pools)?;
+
+    let mut new_pools = pools.to_vec();
+    new_pools[ask_idx] = new_pools[ask_idx].checked_sub(ask_amount)?;
+
+    let new_offer_pool = compute_new_balance(leverage, &new_pools, offer_idx, d)?;
+
+    new_offer_pool.checked_sub(pools[offer_idx])
+}
+
+/// ## Description
+/// Computes the stableswap invariant (D) for any number of coins.
+///
+/// * **Equation**
+///
+/// A * sum(x_i) * n**n + D = A * D * n**n + D**(n+1) / (n**n * prod(x_i))
+///
+/// ## Params
+/// * **leverage** is an object of type [`u64`]. This is the amplification multiplied by the amount of coins.
+///
+/// * **pools** is a slice of [`u128`]. These are the amounts of all tokens in the pool.
+pub fn compute_d(leverage: u64, pools: &[u128]) -> Option<u128> {
+    let n_coins = pools.len() as u8;
+    let sum_x = pools.iter().try_fold(0u128, |acc, x| acc.checked_add(*x))?; // sum(x_i), a.k.a S
+    if sum_x == 0 {
+        return Some(0);
+    }
+
+    let pools_times_coins = pools
+        .iter()
+        .map(|x| checked_u8_mul(&U256::from(*x), n_coins)?.checked_add(U256::one()))
+        .collect::<Option<Vec<U256>>>()?;
+
+    let mut d_previous: U256;
+    let mut d: U256 = sum_x.into();
+
+    // Newton's method to approximate D
+    for _ in 0..ITERATIONS {
+        let mut d_product = d;
+        for x_times_coins in pools_times_coins.iter() {
+            d_product = d_product.checked_mul(d)?.checked_div(*x_times_coins)?;
+        }
+        d_previous = d;
+        // d = (leverage * sum_x + d_p * n_coins) * d / ((leverage - 1) * d + (n_coins + 1) * d_p);
+        d = calculate_step(&d, leverage, sum_x, &d_product, n_coins)?;
+        // Equality with the precision of 1
+        if d == d_previous {
+            break;
+        }
+    }
+    u128::try_from(d).ok()
+}
+
+/// ## Description
+/// Helper function used to calculate the D invariant as a last step in the `compute_d` public function.
+///
+/// * **Equation**:
+///
+/// d = (leverage * sum_x + d_product * n_coins) * initial_d / ((leverage - 1) * initial_d + (n_coins + 1) * d_product)
+fn calculate_step(
+    initial_d: &U256,
+    leverage: u64,
+    sum_x: u128,
+    d_product: &U256,
+    n_coins: u8,
+) -> Option<U256> {
+    let leverage_mul = U256::from(leverage).checked_mul(sum_x.into())? / AMP_PRECISION;
+    let d_p_mul = checked_u8_mul(d_product, n_coins)?;
+
+    let l_val = leverage_mul.checked_add(d_p_mul)?.checked_mul(*initial_d)?;
+
+    let leverage_sub =
+        initial_d.checked_mul((leverage.checked_sub(AMP_PRECISION)?).into())? / AMP_PRECISION;
+    let n_coins_sum = checked_u8_mul(d_product, n_coins.checked_add(1)?)?;
+
+    let r_val = leverage_sub.checked_add(n_coins_sum)?;
+
+    l_val.checked_div(r_val)
+}
+
+/// ## Description
+/// Computes the new balance of the token at `unknown_idx` so that the invariant stays equal to `d_val`
+/// while the balances of all other tokens are set to the values in `pools`.
+///
+/// * **Solve for y**
+///
+/// y**2 + y * (sum' - (A*n**n - 1) * D / (A * n**n)) = D ** (n + 1) / (n ** (2 * n) * prod' * A)
+///
+/// y**2 + b*y = c
+///
+/// where sum' and prod' are the sum and the product of all balances except the unknown one.
+fn compute_new_balance(
+    leverage: u64,
+    pools: &[u128],
+    unknown_idx: usize,
+    d_val: u128,
+) -> Option<u128> {
+    let n_coins = pools.len() as u8;
+
+    // Upscale to U256
+    let leverage: U256 = leverage.into();
+    let d_val: U256 = d_val.into();
+
+    // c = D ** (n + 1) / (n ** (2 * n) * prod' * A)
+    let mut c = d_val;
+    let mut sum = U256::zero();
+    for (i, pool) in pools.iter().enumerate() {
+        if i == unknown_idx {
+            continue;
+        }
+        let pool = U256::from(*pool);
+        sum = sum.checked_add(pool)?;
+        c = c
+            .checked_mul(d_val)?
+            .checked_div(checked_u8_mul(&pool, n_coins)?)?;
+    }
+    c = c
+        .checked_mul(d_val)?
+        .checked_mul(U256::from(AMP_PRECISION))?
+        .checked_div(checked_u8_mul(&leverage, n_coins)?)?;
+
+    // b = sum' - (A*n**n - 1) * D / (A * n**n)
+    let b = sum.checked_add(
+        d_val
+            .checked_mul(U256::from(AMP_PRECISION))?
+            .checked_div(leverage)?,
+    )?;
+
+    // Solve for y by approximating: y**2 + b*y = c
+    let mut y_prev: U256;
+    let mut y = d_val;
+    for _ in 0..ITERATIONS {
+        y_prev = y;
+        y = (y.checked_mul(y)?.checked_add(c)?)
+            .checked_div(checked_u8_mul(&y, 2)?.checked_add(b)?.checked_sub(d_val)?)?;
+        if y == y_prev {
+            break;
+        }
+    }
+    u128::try_from(y).ok()
+}
+
+/// ## Description
+/// Returns self multiplied by b.
+fn checked_u8_mul(a: &U256, b: u8) -> Option<U256> {
+    a.checked_mul(U256::from(b))
+}
@@ -0,0 +1,34 @@
+use astroport::asset::MultiPairInfo;
+use astroport::querier::FactoryParams;
+use cosmwasm_std::{Addr, Uint128};
+use cw_storage_plus::Item;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// ## Description
+/// This structure stores the main multi-asset stableswap pair parameters.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Config {
+    /// The pair information stored in a [`MultiPairInfo`] struct
+    pub pair_info: MultiPairInfo,
+    /// The factory contract address
+    pub factory_addr: Addr,
+    // This is the current amplification used in the pool
+    pub init_amp: u64,
+    // This is the start time when amplification starts to scale up or down
+    pub init_amp_time: u64,
+    // This is the target amplification to reach at `next_amp_time`
+    pub next_amp: u64,
+    // This is the timestamp when the current pool amplification should be `next_amp`
+    pub next_amp_time: u64,
+}
+
+pub const CONFIG: Item<Config> = Item::new("config");
+
+/// Stores the tracked pool reserves in the order of the pair asset infos. Assets sent to the pair
+/// directly are not counted until they are synced
+pub const RESERVES: Item<Vec<Uint128>> = Item::new("reserves");
+
+/// ## Description
+/// Stores the fee and Generator information cached from the factory
+pub const FACTORY_PARAMS: Item<FactoryParams> = Item::new("factory_params");
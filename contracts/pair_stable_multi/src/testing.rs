@@ -0,0 +1,1424 @@
+use crate::contract::{
+    execute, instantiate, query, query_config, query_pair_info, query_pool,
+    query_reverse_simulation, query_share, query_simulate_provide, query_simulate_withdraw,
+    query_simulation, reply,
+};
+use crate::error::ContractError;
+use crate::math::{calc_ask_amount, calc_offer_amount, compute_d, AMP_PRECISION};
+use crate::mock_querier::mock_dependencies;
+use crate::state::RESERVES;
+
+use crate::response::MsgInstantiateContractResponse;
+use astroport::asset::{Asset, AssetInfo, MultiPairInfo};
+use astroport::factory::{PairStatus, PairType};
+use astroport::pair::{StablePoolParams, MINIMUM_LIQUIDITY};
+use astroport::pair_stable_multi::{
+    ConfigResponse, Cw20HookMsg, ExecuteMsg, InstantiateMsg, PoolResponse, QueryMsg,
+};
+use astroport::token::InstantiateMsg as TokenInstantiateMsg;
+use cosmwasm_std::testing::{mock_env, mock_info, MOCK_CONTRACT_ADDR};
+use cosmwasm_std::{
+    attr, from_binary, to_binary, Addr, BankMsg, Coin, ContractResult, CosmosMsg, Decimal, DepsMut,
+    Reply, ReplyOn, StdError, SubMsg, SubMsgExecutionResponse, Uint128, WasmMsg,
+};
+use cw20::{Cw20ExecuteMsg, Cw20ReceiveMsg, MinterResponse};
+use protobuf::Message;
+
+fn store_liquidity_token(deps: DepsMut, msg_id: u64, contract_addr: String) {
+    let data = MsgInstantiateContractResponse {
+        contract_address: contract_addr,
+        data: vec![],
+        unknown_fields: Default::default(),
+        cached_size: Default::default(),
+    }
+    .write_to_bytes()
+    .unwrap();
+
+    let reply_msg = Reply {
+        id: msg_id,
+        result: ContractResult::Ok(SubMsgExecutionResponse {
+            events: vec![],
+            data: Some(data.into()),
+        }),
+    };
+
+    let _res = reply(deps, mock_env(), reply_msg.clone()).unwrap();
+}
+
+fn store_reserves(deps: DepsMut, reserves: &[u128]) {
+    let reserves: Vec<Uint128> = reserves
+        .iter()
+        .map(|amount| Uint128::new(*amount))
+        .collect();
+    RESERVES.save(deps.storage, &reserves).unwrap();
+}
+
+fn asset_infos() -> Vec<AssetInfo> {
+    vec![
+        AssetInfo::NativeToken {
+            denom: "uusd".to_string(),
+        },
+        AssetInfo::Token {
+            contract_addr: Addr::unchecked("asset0000"),
+        },
+        AssetInfo::Token {
+            contract_addr: Addr::unchecked("asset0001"),
+        },
+    ]
+}
+
+fn instantiate_msg(asset_infos: Vec<AssetInfo>) -> InstantiateMsg {
+    InstantiateMsg {
+        asset_infos,
+        token_code_id: 10u64,
+        factory_addr: String::from("factory"),
+        init_params: Some(to_binary(&StablePoolParams { amp: 100 }).unwrap()),
+    }
+}
+
+#[test]
+fn proper_initialization() {
+    let mut deps = mock_dependencies(&[]);
+
+    deps.querier.with_token_balances(&[
+        (
+            &String::from("asset0000"),
+            &[(&String::from(MOCK_CONTRACT_ADDR), &Uint128::new(123u128))],
+        ),
+        (
+            &String::from("asset0001"),
+            &[(&String::from(MOCK_CONTRACT_ADDR), &Uint128::new(123u128))],
+        ),
+    ]);
+
+    let env = mock_env();
+    let info = mock_info("addr0000", &[]);
+
+    // Two assets are not enough for a multi-asset pool
+    let res = instantiate(
+        deps.as_mut(),
+        env.clone(),
+        info.clone(),
+        instantiate_msg(asset_infos()[..2].to_vec()),
+    )
+    .unwrap_err();
+    assert_eq!(res, ContractError::InvalidNumberOfAssets {});
+
+    // Six assets are too many
+    let mut too_many_assets = asset_infos();
+    for i in 2..5 {
+        too_many_assets.push(AssetInfo::Token {
+            contract_addr: Addr::unchecked(format!("asset000{}", i)),
+        });
+    }
+    let res = instantiate(
+        deps.as_mut(),
+        env.clone(),
+        info.clone(),
+        instantiate_msg(too_many_assets),
+    )
+    .unwrap_err();
+    assert_eq!(res, ContractError::InvalidNumberOfAssets {});
+
+    // The same asset cannot be used twice
+    let mut doubling_assets = asset_infos();
+    doubling_assets[2] = doubling_assets[1].clone();
+    let res = instantiate(
+        deps.as_mut(),
+        env.clone(),
+        info.clone(),
+        instantiate_msg(doubling_assets),
+    )
+    .unwrap_err();
+    assert_eq!(res, ContractError::DoublingAssets {});
+
+    let res = instantiate(deps.as_mut(), env, info, instantiate_msg(asset_infos())).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg {
+            msg: WasmMsg::Instantiate {
+                code_id: 10u64,
+                msg: to_binary(&TokenInstantiateMsg {
+                    name: "UUSD-MAPP-MAPP-LP".to_string(),
+                    symbol: "uLP".to_string(),
+                    decimals: 6,
+                    initial_balances: vec![],
+                    mint: Some(MinterResponse {
+                        minter: String::from(MOCK_CONTRACT_ADDR),
+                        cap: None,
+                    }),
+                })
+                .unwrap(),
+                funds: vec![],
+                admin: None,
+                label: String::from("Astroport LP token"),
+            }
+            .into(),
+            id: 1,
+            gas_limit: None,
+            reply_on: ReplyOn::Success
+        },]
+    );
+
+    // Store liquidity token
+    store_liquidity_token(deps.as_mut(), 1, "liquidity0000".to_string());
+
+    // It worked, let's query the state
+    let pair_info: MultiPairInfo = query_pair_info(deps.as_ref()).unwrap();
+    assert_eq!(Addr::unchecked("liquidity0000"), pair_info.liquidity_token);
+    assert_eq!(pair_info.asset_infos, asset_infos());
+    assert_eq!(pair_info.pair_type, PairType::StableMulti {});
+
+    let config: ConfigResponse = query_config(deps.as_ref(), mock_env()).unwrap();
+    assert_eq!(config.amp, Decimal::from_ratio(100u128, 1u128));
+}
+
+#[test]
+fn provide_liquidity() {
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::new(100_000_000u128),
+    }]);
+
+    deps.querier.with_token_balances(&[
+        (
+            &String::from("asset0000"),
+            &[(&String::from(MOCK_CONTRACT_ADDR), &Uint128::new(0))],
+        ),
+        (
+            &String::from("asset0001"),
+            &[(&String::from(MOCK_CONTRACT_ADDR), &Uint128::new(0))],
+        ),
+        (
+            &String::from("liquidity0000"),
+            &[(&String::from(MOCK_CONTRACT_ADDR), &Uint128::new(0))],
+        ),
+    ]);
+
+    let env = mock_env();
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), env, info, instantiate_msg(asset_infos())).unwrap();
+
+    // Store the liquidity token
+    store_liquidity_token(deps.as_mut(), 1, "liquidity0000".to_string());
+
+    let deposit = |info: AssetInfo, amount: u128| Asset {
+        info,
+        amount: Uint128::new(amount),
+    };
+
+    // The initial deposit must contain all pool assets
+    let msg = ExecuteMsg::ProvideLiquidity {
+        assets: vec![
+            deposit(asset_infos()[0].clone(), 100_000_000u128),
+            deposit(asset_infos()[1].clone(), 100_000_000u128),
+        ],
+        auto_stake: None,
+        receiver: None,
+        min_lp_to_receive: None,
+        deadline: None,
+    };
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::new(100_000_000u128),
+        }],
+    );
+    let res = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap_err();
+    assert_eq!(res, ContractError::InvalidZeroAmount {});
+
+    // Assets that don't belong to the pool are rejected
+    let msg = ExecuteMsg::ProvideLiquidity {
+        assets: vec![
+            deposit(asset_infos()[0].clone(), 100_000_000u128),
+            deposit(
+                AssetInfo::Token {
+                    contract_addr: Addr::unchecked("asset0002"),
+                },
+                100_000_000u128,
+            ),
+        ],
+        auto_stake: None,
+        receiver: None,
+        min_lp_to_receive: None,
+        deadline: None,
+    };
+    let res = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap_err();
+    assert_eq!(res, ContractError::AssetMismatch {});
+
+    // Successfully provide the initial liquidity
+    let msg = ExecuteMsg::ProvideLiquidity {
+        assets: vec![
+            deposit(asset_infos()[0].clone(), 100_000_000u128),
+            deposit(asset_infos()[1].clone(), 100_000_000u128),
+            deposit(asset_infos()[2].clone(), 100_000_000u128),
+        ],
+        auto_stake: None,
+        receiver: None,
+        min_lp_to_receive: None,
+        deadline: None,
+    };
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(res.messages.len(), 4);
+    for (i, contract_addr) in ["asset0000", "asset0001"].iter().enumerate() {
+        assert_eq!(
+            res.messages[i],
+            SubMsg::new(WasmMsg::Execute {
+                contract_addr: contract_addr.to_string(),
+                msg: to_binary(&Cw20ExecuteMsg::TransferFrom {
+                    owner: String::from("addr0000"),
+                    recipient: String::from(MOCK_CONTRACT_ADDR),
+                    amount: Uint128::new(100_000_000u128),
+                })
+                .unwrap(),
+                funds: vec![],
+            })
+        );
+    }
+    // The minimum liquidity is locked in the pair
+    assert_eq!(
+        res.messages[2],
+        SubMsg::new(WasmMsg::Execute {
+            contract_addr: String::from("liquidity0000"),
+            msg: to_binary(&Cw20ExecuteMsg::Mint {
+                recipient: String::from(MOCK_CONTRACT_ADDR),
+                amount: MINIMUM_LIQUIDITY,
+            })
+            .unwrap(),
+            funds: vec![],
+        })
+    );
+    // The invariant of a balanced pool is equal to the sum of the balances
+    assert_eq!(
+        res.messages[3],
+        SubMsg::new(WasmMsg::Execute {
+            contract_addr: String::from("liquidity0000"),
+            msg: to_binary(&Cw20ExecuteMsg::Mint {
+                recipient: String::from("addr0000"),
+                amount: Uint128::new(300_000_000u128) - MINIMUM_LIQUIDITY,
+            })
+            .unwrap(),
+            funds: vec![],
+        })
+    );
+
+    // Provide a single asset. The deposit is imbalanced, so a fee is charged on it
+    deps.querier.with_balance(&[(
+        &String::from(MOCK_CONTRACT_ADDR),
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::new(100_000_000u128),
+        }],
+    )]);
+    deps.querier.with_token_balances(&[
+        (
+            &String::from("asset0000"),
+            &[(
+                &String::from(MOCK_CONTRACT_ADDR),
+                &Uint128::new(100_000_000u128),
+            )],
+        ),
+        (
+            &String::from("asset0001"),
+            &[(
+                &String::from(MOCK_CONTRACT_ADDR),
+                &Uint128::new(100_000_000u128),
+            )],
+        ),
+        (
+            &String::from("liquidity0000"),
+            &[(
+                &String::from(MOCK_CONTRACT_ADDR),
+                &Uint128::new(300_000_000u128),
+            )],
+        ),
+    ]);
+
+    let msg = ExecuteMsg::ProvideLiquidity {
+        assets: vec![deposit(asset_infos()[2].clone(), 30_000_000u128)],
+        auto_stake: None,
+        receiver: Some(String::from("addr0001")),
+        min_lp_to_receive: None,
+        deadline: None,
+    };
+    let res = execute(deps.as_mut(), mock_env(), mock_info("addr0000", &[]), msg).unwrap();
+    assert_eq!(res.messages.len(), 2);
+    assert_eq!(
+        res.messages[0],
+        SubMsg::new(WasmMsg::Execute {
+            contract_addr: String::from("asset0001"),
+            msg: to_binary(&Cw20ExecuteMsg::TransferFrom {
+                owner: String::from("addr0000"),
+                recipient: String::from(MOCK_CONTRACT_ADDR),
+                amount: Uint128::new(30_000_000u128),
+            })
+            .unwrap(),
+            funds: vec![],
+        })
+    );
+
+    let share = res
+        .attributes
+        .iter()
+        .find(|attr| attr.key == "share")
+        .unwrap()
+        .value
+        .parse::<u128>()
+        .unwrap();
+    // Less than a balanced deposit of the same value would receive, but not by much
+    assert!(share < 30_000_000u128);
+    assert!(share > 29_500_000u128);
+    assert_eq!(
+        res.messages[1],
+        SubMsg::new(WasmMsg::Execute {
+            contract_addr: String::from("liquidity0000"),
+            msg: to_binary(&Cw20ExecuteMsg::Mint {
+                recipient: String::from("addr0001"),
+                amount: Uint128::new(share),
+            })
+            .unwrap(),
+            funds: vec![],
+        })
+    );
+
+    // Zero deposits are rejected
+    let msg = ExecuteMsg::ProvideLiquidity {
+        assets: vec![deposit(asset_infos()[2].clone(), 0)],
+        auto_stake: None,
+        receiver: None,
+        min_lp_to_receive: None,
+        deadline: None,
+    };
+    let res = execute(deps.as_mut(), mock_env(), mock_info("addr0000", &[]), msg).unwrap_err();
+    assert_eq!(res, ContractError::InvalidZeroAmount {});
+
+    // Native tokens must be sent along with the message
+    let msg = ExecuteMsg::ProvideLiquidity {
+        assets: vec![deposit(asset_infos()[0].clone(), 10_000_000u128)],
+        auto_stake: None,
+        receiver: None,
+        min_lp_to_receive: None,
+        deadline: None,
+    };
+    let res = execute(deps.as_mut(), mock_env(), mock_info("addr0000", &[]), msg).unwrap_err();
+    assert_eq!(
+        res,
+        ContractError::Std(StdError::generic_err(
+            "Native token balance mismatch between the argument and the transferred"
+        ))
+    );
+}
+
+#[test]
+fn withdraw_liquidity() {
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::new(100u128),
+    }]);
+
+    deps.querier.with_tax(
+        Decimal::zero(),
+        &[(&"uusd".to_string(), &Uint128::from(1000000u128))],
+    );
+    deps.querier.with_token_balances(&[
+        (
+            &String::from("liquidity0000"),
+            &[(&String::from("addr0000"), &Uint128::new(300u128))],
+        ),
+        (
+            &String::from("asset0000"),
+            &[(&String::from(MOCK_CONTRACT_ADDR), &Uint128::new(200u128))],
+        ),
+        (
+            &String::from("asset0001"),
+            &[(&String::from(MOCK_CONTRACT_ADDR), &Uint128::new(300u128))],
+        ),
+    ]);
+
+    let env = mock_env();
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), env, info, instantiate_msg(asset_infos())).unwrap();
+
+    // Store the liquidity token
+    store_liquidity_token(deps.as_mut(), 1, "liquidity0000".to_string());
+    store_reserves(deps.as_mut(), &[100, 200, 300]);
+
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: String::from("addr0000"),
+        msg: to_binary(&Cw20HookMsg::WithdrawLiquidity {
+            min_assets_out: None,
+            deadline: None,
+        })
+        .unwrap(),
+        amount: Uint128::new(150u128),
+    });
+
+    // Only the LP token can be used to withdraw liquidity
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("asset0000", &[]),
+        msg.clone(),
+    )
+    .unwrap_err();
+    assert_eq!(res, ContractError::Unauthorized {});
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("liquidity0000", &[]),
+        msg,
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+                to_address: String::from("addr0000"),
+                amount: vec![Coin {
+                    denom: "uusd".to_string(),
+                    amount: Uint128::from(50u128),
+                }],
+            })),
+            SubMsg::new(WasmMsg::Execute {
+                contract_addr: String::from("asset0000"),
+                msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                    recipient: String::from("addr0000"),
+                    amount: Uint128::from(100u128),
+                })
+                .unwrap(),
+                funds: vec![],
+            }),
+            SubMsg::new(WasmMsg::Execute {
+                contract_addr: String::from("asset0001"),
+                msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                    recipient: String::from("addr0000"),
+                    amount: Uint128::from(150u128),
+                })
+                .unwrap(),
+                funds: vec![],
+            }),
+            SubMsg::new(WasmMsg::Execute {
+                contract_addr: String::from("liquidity0000"),
+                msg: to_binary(&Cw20ExecuteMsg::Burn {
+                    amount: Uint128::from(150u128),
+                })
+                .unwrap(),
+                funds: vec![],
+            }),
+        ]
+    );
+    assert_eq!(
+        res.attributes[3],
+        attr("refund_assets", "50uusd, 100asset0000, 150asset0001")
+    );
+
+    // The LP token supply is mocked, so the reserves are reset to match it
+    store_reserves(deps.as_mut(), &[100, 200, 300]);
+    let share = query_share(deps.as_ref(), Uint128::new(30u128)).unwrap();
+    assert_eq!(
+        share.iter().map(|a| a.amount.u128()).collect::<Vec<_>>(),
+        vec![10u128, 20u128, 30u128]
+    );
+}
+
+#[test]
+fn swap_between_any_assets() {
+    let pool_amount = Uint128::new(1_000_000_000_000u128);
+    let offer_amount = Uint128::new(1_000_000_000u128);
+
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: pool_amount,
+    }]);
+
+    deps.querier.with_tax(
+        Decimal::zero(),
+        &[(&"uusd".to_string(), &Uint128::from(1000000u128))],
+    );
+    deps.querier.with_token_balances(&[
+        (
+            &String::from("liquidity0000"),
+            &[(&String::from(MOCK_CONTRACT_ADDR), &pool_amount)],
+        ),
+        (
+            &String::from("asset0000"),
+            &[(
+                &String::from(MOCK_CONTRACT_ADDR),
+                &(pool_amount + offer_amount), /* user deposit must be pre-applied */
+            )],
+        ),
+        (
+            &String::from("asset0001"),
+            &[(&String::from(MOCK_CONTRACT_ADDR), &pool_amount)],
+        ),
+    ]);
+
+    let env = mock_env();
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), env, info, instantiate_msg(asset_infos())).unwrap();
+
+    // Store the liquidity token
+    store_liquidity_token(deps.as_mut(), 1, "liquidity0000".to_string());
+    store_reserves(deps.as_mut(), &[pool_amount.u128(); 3]);
+
+    let expected_ret_amount = Uint128::new(
+        calc_ask_amount(
+            &[pool_amount.u128(); 3],
+            1,
+            2,
+            offer_amount.u128(),
+            100 * AMP_PRECISION,
+        )
+        .unwrap(),
+    );
+    let expected_spread_amount = offer_amount.saturating_sub(expected_ret_amount);
+    let expected_commission_amount = expected_ret_amount.multiply_ratio(3u128, 1000u128); // 0.3%
+    let expected_maker_fee_amount = expected_commission_amount.multiply_ratio(166u128, 1000u128);
+    let expected_return_amount = expected_ret_amount
+        .checked_sub(expected_commission_amount)
+        .unwrap();
+
+    // Token -> token swap
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: String::from("addr0000"),
+        amount: offer_amount,
+        msg: to_binary(&Cw20HookMsg::Swap {
+            ask_asset_info: asset_infos()[2].clone(),
+            belief_price: None,
+            max_spread: None,
+            to: None,
+            min_return: None,
+            deadline: None,
+        })
+        .unwrap(),
+    });
+
+    // Only the pool tokens can be swapped
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("asset0002", &[]),
+        msg.clone(),
+    )
+    .unwrap_err();
+    assert_eq!(res, ContractError::Unauthorized {});
+
+    let res = execute(deps.as_mut(), mock_env(), mock_info("asset0000", &[]), msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "swap"),
+            attr("sender", "addr0000"),
+            attr("receiver", "addr0000"),
+            attr("offer_asset", "asset0000"),
+            attr("ask_asset", "asset0001"),
+            attr("offer_amount", offer_amount.to_string()),
+            attr("return_amount", expected_return_amount.to_string()),
+            attr("tax_amount", "0"),
+            attr("spread_amount", expected_spread_amount.to_string()),
+            attr("commission_amount", expected_commission_amount.to_string()),
+            attr("maker_fee_amount", expected_maker_fee_amount.to_string()),
+        ]
+    );
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::new(WasmMsg::Execute {
+                contract_addr: String::from("asset0001"),
+                msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                    recipient: String::from("addr0000"),
+                    amount: expected_return_amount,
+                })
+                .unwrap(),
+                funds: vec![],
+            }),
+            SubMsg::new(WasmMsg::Execute {
+                contract_addr: String::from("asset0001"),
+                msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                    recipient: String::from("fee_address"),
+                    amount: expected_maker_fee_amount,
+                })
+                .unwrap(),
+                funds: vec![],
+            }),
+        ]
+    );
+
+    // A balanced pool swaps at nearly 1:1
+    assert!(expected_ret_amount < offer_amount);
+    assert!(expected_ret_amount > offer_amount.multiply_ratio(999u128, 1000u128));
+
+    // Simulations return the same result as the swap
+    deps.querier.with_token_balances(&[
+        (
+            &String::from("liquidity0000"),
+            &[(&String::from(MOCK_CONTRACT_ADDR), &pool_amount)],
+        ),
+        (
+            &String::from("asset0000"),
+            &[(&String::from(MOCK_CONTRACT_ADDR), &pool_amount)],
+        ),
+        (
+            &String::from("asset0001"),
+            &[(&String::from(MOCK_CONTRACT_ADDR), &pool_amount)],
+        ),
+    ]);
+    store_reserves(deps.as_mut(), &[pool_amount.u128(); 3]);
+
+    let simulation = query_simulation(
+        deps.as_ref(),
+        mock_env(),
+        Asset {
+            info: asset_infos()[1].clone(),
+            amount: offer_amount,
+        },
+        asset_infos()[2].clone(),
+    )
+    .unwrap();
+    assert_eq!(simulation.return_amount, expected_return_amount);
+    assert_eq!(simulation.spread_amount, expected_spread_amount);
+    assert_eq!(simulation.commission_amount, expected_commission_amount);
+
+    let reverse_simulation = query_reverse_simulation(
+        deps.as_ref(),
+        mock_env(),
+        asset_infos()[0].clone(),
+        Asset {
+            info: asset_infos()[2].clone(),
+            amount: expected_return_amount,
+        },
+    )
+    .unwrap();
+    let diff = reverse_simulation.offer_amount.u128() as i128 - offer_amount.u128() as i128;
+    assert!(diff.abs() <= 2);
+
+    // Unknown assets cannot be simulated
+    let res = query_simulation(
+        deps.as_ref(),
+        mock_env(),
+        Asset {
+            info: asset_infos()[1].clone(),
+            amount: offer_amount,
+        },
+        AssetInfo::Token {
+            contract_addr: Addr::unchecked("asset0002"),
+        },
+    )
+    .unwrap_err();
+    assert_eq!(
+        res,
+        StdError::generic_err("Given assets don't belong to the pool")
+    );
+
+    // Native -> token swap
+    deps.querier.with_balance(&[(
+        &String::from(MOCK_CONTRACT_ADDR),
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: pool_amount + offer_amount, /* user deposit must be pre-applied */
+        }],
+    )]);
+    let msg = ExecuteMsg::Swap {
+        offer_asset: Asset {
+            info: asset_infos()[0].clone(),
+            amount: offer_amount,
+        },
+        ask_asset_info: asset_infos()[1].clone(),
+        belief_price: None,
+        max_spread: None,
+        to: Some(String::from("addr0001")),
+        min_return: None,
+        deadline: None,
+    };
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: offer_amount,
+        }],
+    );
+    let res = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+    assert_eq!(
+        res.messages[0],
+        SubMsg::new(WasmMsg::Execute {
+            contract_addr: String::from("asset0000"),
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: String::from("addr0001"),
+                amount: expected_return_amount,
+            })
+            .unwrap(),
+            funds: vec![],
+        })
+    );
+
+    // The offer and ask assets must be different
+    let msg = ExecuteMsg::Swap {
+        offer_asset: Asset {
+            info: asset_infos()[0].clone(),
+            amount: offer_amount,
+        },
+        ask_asset_info: asset_infos()[0].clone(),
+        belief_price: None,
+        max_spread: None,
+        to: None,
+        min_return: None,
+        deadline: None,
+    };
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+    assert_eq!(res, ContractError::DoublingAssets {});
+}
+
+#[test]
+fn math_matches_the_two_coin_case_for_balanced_pools() {
+    let amp = 100 * AMP_PRECISION;
+
+    // The invariant of a balanced pool is the sum of its balances
+    for n in 3..=5 {
+        let pools = vec![1_000_000_000_000u128; n];
+        assert_eq!(
+            compute_d(amp * n as u64, &pools).unwrap(),
+            1_000_000_000_000u128 * n as u128
+        );
+    }
+
+    // Empty pools have a zero invariant
+    assert_eq!(compute_d(amp * 3, &[0, 0, 0]).unwrap(), 0);
+
+    let pools = [
+        1_000_000_000_000u128,
+        2_000_000_000_000u128,
+        500_000_000_000u128,
+    ];
+
+    // The ask amount and the offer amount calculations are consistent
+    let ask_amount = calc_ask_amount(&pools, 2, 1, 1_000_000_000u128, amp).unwrap();
+    let offer_amount = calc_offer_amount(&pools, 2, 1, ask_amount, amp).unwrap();
+    assert!((offer_amount as i128 - 1_000_000_000i128).abs() <= 2);
+
+    // Swapping into a scarce asset gives less than swapping out of it
+    let scarce_ask_amount = calc_ask_amount(&pools, 1, 2, 1_000_000_000u128, amp).unwrap();
+    assert!(scarce_ask_amount < 1_000_000_000u128);
+    assert!(ask_amount > 1_000_000_000u128);
+
+    // The pool cannot be drained
+    assert_eq!(calc_offer_amount(&pools, 0, 2, pools[2], amp), None);
+}
+
+#[test]
+fn query_pool_returns_all_assets() {
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::new(111u128),
+    }]);
+
+    deps.querier.with_token_balances(&[
+        (
+            &String::from("asset0000"),
+            &[(&String::from(MOCK_CONTRACT_ADDR), &Uint128::new(222u128))],
+        ),
+        (
+            &String::from("asset0001"),
+            &[(&String::from(MOCK_CONTRACT_ADDR), &Uint128::new(333u128))],
+        ),
+        (
+            &String::from("liquidity0000"),
+            &[(&String::from(MOCK_CONTRACT_ADDR), &Uint128::new(444u128))],
+        ),
+    ]);
+
+    let env = mock_env();
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), env, info, instantiate_msg(asset_infos())).unwrap();
+
+    // Store the liquidity token
+    store_liquidity_token(deps.as_mut(), 1, "liquidity0000".to_string());
+    store_reserves(deps.as_mut(), &[111, 222, 333]);
+
+    let res: PoolResponse = query_pool(deps.as_ref()).unwrap();
+    assert_eq!(
+        res.assets,
+        asset_infos()
+            .into_iter()
+            .zip([111u128, 222u128, 333u128])
+            .map(|(info, amount)| Asset {
+                info,
+                amount: Uint128::new(amount),
+            })
+            .collect::<Vec<Asset>>()
+    );
+    assert_eq!(res.total_share, Uint128::new(444u128));
+}
+
+#[test]
+fn sync_and_skim() {
+    let pool_amount = Uint128::new(1_000_000_000u128);
+    let donation_amount = Uint128::new(1_000_000u128);
+
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: pool_amount,
+    }]);
+    deps.querier.with_token_balances(&[
+        (
+            &String::from("liquidity0000"),
+            &[(&String::from(MOCK_CONTRACT_ADDR), &pool_amount)],
+        ),
+        (
+            &String::from("asset0000"),
+            &[(&String::from(MOCK_CONTRACT_ADDR), &pool_amount)],
+        ),
+        (
+            &String::from("asset0001"),
+            &[(&String::from(MOCK_CONTRACT_ADDR), &pool_amount)],
+        ),
+    ]);
+
+    let info = mock_info("addr0000", &[]);
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        instantiate_msg(asset_infos()),
+    )
+    .unwrap();
+    store_liquidity_token(deps.as_mut(), 1, "liquidity0000".to_string());
+
+    // The reserves are tracked from zero, so the balances of the pair are not counted
+    let res: PoolResponse = query_pool(deps.as_ref()).unwrap();
+    assert!(res.assets.iter().all(|asset| asset.amount.is_zero()));
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        ExecuteMsg::Sync {},
+    )
+    .unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "sync"),
+            attr(
+                "reserves",
+                "1000000000uusd, 1000000000asset0000, 1000000000asset0001"
+            ),
+        ]
+    );
+
+    let offer_asset = Asset {
+        info: asset_infos()[0].clone(),
+        amount: Uint128::new(1_000_000u128),
+    };
+    let simulation = query_simulation(
+        deps.as_ref(),
+        mock_env(),
+        offer_asset.clone(),
+        asset_infos()[2].clone(),
+    )
+    .unwrap();
+
+    // A direct transfer to the pair doesn't move the price
+    deps.querier.with_token_balances(&[
+        (
+            &String::from("liquidity0000"),
+            &[(&String::from(MOCK_CONTRACT_ADDR), &pool_amount)],
+        ),
+        (
+            &String::from("asset0000"),
+            &[(&String::from(MOCK_CONTRACT_ADDR), &pool_amount)],
+        ),
+        (
+            &String::from("asset0001"),
+            &[(
+                &String::from(MOCK_CONTRACT_ADDR),
+                &(pool_amount + donation_amount),
+            )],
+        ),
+    ]);
+    assert_eq!(
+        query_simulation(
+            deps.as_ref(),
+            mock_env(),
+            offer_asset.clone(),
+            asset_infos()[2].clone(),
+        )
+        .unwrap(),
+        simulation
+    );
+
+    // The excess balance is sent to the recipient
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        ExecuteMsg::Skim {
+            to: Some(String::from("addr0001")),
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(WasmMsg::Execute {
+            contract_addr: String::from("asset0001"),
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: String::from("addr0001"),
+                amount: donation_amount,
+            })
+            .unwrap(),
+            funds: vec![],
+        })]
+    );
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "skim"),
+            attr("receiver", "addr0001"),
+            attr("assets", "0uusd, 0asset0000, 1000000asset0001"),
+        ]
+    );
+
+    // Syncing adds the excess balance to the pool instead
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        ExecuteMsg::Sync {},
+    )
+    .unwrap();
+    let res: PoolResponse = query_pool(deps.as_ref()).unwrap();
+    assert_eq!(res.assets[2].amount, pool_amount + donation_amount);
+    assert_ne!(
+        query_simulation(
+            deps.as_ref(),
+            mock_env(),
+            offer_asset,
+            asset_infos()[2].clone(),
+        )
+        .unwrap(),
+        simulation
+    );
+
+    // Nothing is left to skim
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        ExecuteMsg::Skim { to: None },
+    )
+    .unwrap();
+    assert!(res.messages.is_empty());
+}
+
+#[test]
+fn simulate_provide_and_withdraw() {
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::new(100_000_000u128),
+    }]);
+
+    deps.querier.with_token_balances(&[
+        (
+            &String::from("asset0000"),
+            &[(
+                &String::from(MOCK_CONTRACT_ADDR),
+                &Uint128::new(100_000_000u128),
+            )],
+        ),
+        (
+            &String::from("asset0001"),
+            &[(
+                &String::from(MOCK_CONTRACT_ADDR),
+                &Uint128::new(100_000_000u128),
+            )],
+        ),
+        (
+            &String::from("liquidity0000"),
+            &[(
+                &String::from(MOCK_CONTRACT_ADDR),
+                &Uint128::new(300_000_000u128),
+            )],
+        ),
+    ]);
+
+    let env = mock_env();
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), env, info, instantiate_msg(asset_infos())).unwrap();
+
+    // Store the liquidity token
+    store_liquidity_token(deps.as_mut(), 1, "liquidity0000".to_string());
+    store_reserves(deps.as_mut(), &[100_000_000; 3]);
+
+    let deposit = |info: AssetInfo, amount: u128| Asset {
+        info,
+        amount: Uint128::new(amount),
+    };
+
+    // Assets can't be deposited twice
+    let err = query_simulate_provide(
+        deps.as_ref(),
+        mock_env(),
+        vec![
+            deposit(asset_infos()[1].clone(), 10_000_000u128),
+            deposit(asset_infos()[1].clone(), 10_000_000u128),
+        ],
+    )
+    .unwrap_err();
+    assert_eq!(
+        err,
+        StdError::generic_err(ContractError::DoublingAssets {}.to_string())
+    );
+
+    // A balanced deposit isn't charged a fee
+    let res = query_simulate_provide(
+        deps.as_ref(),
+        mock_env(),
+        asset_infos()
+            .into_iter()
+            .map(|info| deposit(info, 10_000_000u128))
+            .collect(),
+    )
+    .unwrap();
+    assert_eq!(res.share, Uint128::new(30_000_000u128));
+    assert!(res.fees.iter().all(|fee| fee.amount.is_zero()));
+
+    // An imbalanced deposit is charged a fee on every pool asset
+    let res = query_simulate_provide(
+        deps.as_ref(),
+        mock_env(),
+        vec![deposit(asset_infos()[2].clone(), 30_000_000u128)],
+    )
+    .unwrap();
+    assert!(res.fees.iter().all(|fee| !fee.amount.is_zero()));
+    assert_eq!(
+        res.fees
+            .iter()
+            .map(|fee| fee.info.clone())
+            .collect::<Vec<AssetInfo>>(),
+        asset_infos()
+    );
+
+    // The simulated share matches the share minted by the provide
+    let msg = ExecuteMsg::ProvideLiquidity {
+        assets: vec![deposit(asset_infos()[2].clone(), 30_000_000u128)],
+        auto_stake: None,
+        receiver: None,
+        min_lp_to_receive: None,
+        deadline: None,
+    };
+    let provide_res = execute(deps.as_mut(), mock_env(), mock_info("addr0000", &[]), msg).unwrap();
+    assert!(provide_res
+        .attributes
+        .contains(&attr("share", res.share.to_string())));
+
+    // The simulated withdrawal matches the refund of the withdrawal
+    store_reserves(deps.as_mut(), &[100_000_000; 3]);
+    let res = query_simulate_withdraw(deps.as_ref(), Uint128::new(30_000_000u128)).unwrap();
+    assert_eq!(
+        res,
+        query_share(deps.as_ref(), Uint128::new(30_000_000u128)).unwrap()
+    );
+    assert!(res
+        .iter()
+        .all(|asset| asset.amount == Uint128::new(10_000_000u128)));
+}
+
+#[test]
+fn pair_status_circuit_breaker() {
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::new(100u128),
+    }]);
+
+    deps.querier.with_tax(
+        Decimal::zero(),
+        &[(&"uusd".to_string(), &Uint128::from(1000000u128))],
+    );
+    deps.querier.with_token_balances(&[
+        (
+            &String::from("liquidity0000"),
+            &[(&String::from("addr0000"), &Uint128::new(300u128))],
+        ),
+        (
+            &String::from("asset0000"),
+            &[(&String::from(MOCK_CONTRACT_ADDR), &Uint128::new(200u128))],
+        ),
+        (
+            &String::from("asset0001"),
+            &[(&String::from(MOCK_CONTRACT_ADDR), &Uint128::new(300u128))],
+        ),
+    ]);
+
+    let info = mock_info("addr0000", &[]);
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        instantiate_msg(asset_infos()),
+    )
+    .unwrap();
+    store_liquidity_token(deps.as_mut(), 1, "liquidity0000".to_string());
+
+    // The pair picks the factory state up once its cached parameters are refreshed
+    deps.querier.with_pair_status(PairStatus::WithdrawOnly);
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("anyone", &[]),
+        ExecuteMsg::RefreshFactoryParams {},
+    )
+    .unwrap();
+    let status: PairStatus =
+        from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::PairStatus {}).unwrap()).unwrap();
+    assert_eq!(status, PairStatus::WithdrawOnly);
+
+    let msg = ExecuteMsg::Swap {
+        offer_asset: Asset {
+            info: asset_infos()[0].clone(),
+            amount: Uint128::new(10u128),
+        },
+        ask_asset_info: asset_infos()[1].clone(),
+        belief_price: None,
+        max_spread: None,
+        to: None,
+        min_return: None,
+        deadline: None,
+    };
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::new(10u128),
+        }],
+    );
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+    assert_eq!(res, ContractError::SwapsPaused {});
+
+    let msg = ExecuteMsg::ProvideLiquidity {
+        assets: vec![Asset {
+            info: asset_infos()[1].clone(),
+            amount: Uint128::new(10u128),
+        }],
+        auto_stake: None,
+        receiver: None,
+        min_lp_to_receive: None,
+        deadline: None,
+    };
+    let res = execute(deps.as_mut(), mock_env(), mock_info("addr0000", &[]), msg).unwrap_err();
+    assert_eq!(res, ContractError::DepositsPaused {});
+
+    // LPs can still exit the pool
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: String::from("addr0000"),
+        msg: to_binary(&Cw20HookMsg::WithdrawLiquidity {
+            min_assets_out: None,
+            deadline: None,
+        })
+        .unwrap(),
+        amount: Uint128::new(150u128),
+    });
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("liquidity0000", &[]),
+        msg,
+    )
+    .unwrap();
+}
+
+#[test]
+fn execution_guards() {
+    let pool_amount = Uint128::new(1_000_000_000u128);
+    let offer_amount = Uint128::new(1_000_000u128);
+
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: pool_amount,
+    }]);
+    deps.querier.with_token_balances(&[
+        (
+            &String::from("liquidity0000"),
+            &[(
+                &String::from(MOCK_CONTRACT_ADDR),
+                &(pool_amount * Uint128::new(3)),
+            )],
+        ),
+        (
+            &String::from("asset0000"),
+            &[(&String::from(MOCK_CONTRACT_ADDR), &pool_amount)],
+        ),
+        (
+            &String::from("asset0001"),
+            &[(&String::from(MOCK_CONTRACT_ADDR), &pool_amount)],
+        ),
+    ]);
+
+    let info = mock_info("addr0000", &[]);
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        instantiate_msg(asset_infos()),
+    )
+    .unwrap();
+    store_liquidity_token(deps.as_mut(), 1, "liquidity0000".to_string());
+    store_reserves(deps.as_mut(), &[pool_amount.u128(); 3]);
+
+    let env = mock_env();
+    let expired = Some(env.block.time.seconds() - 1);
+
+    let simulation = query_simulation(
+        deps.as_ref(),
+        env.clone(),
+        Asset {
+            info: asset_infos()[0].clone(),
+            amount: offer_amount,
+        },
+        asset_infos()[1].clone(),
+    )
+    .unwrap();
+
+    // The user deposit must be pre-applied to the native balance
+    deps.querier.with_balance(&[(
+        &String::from(MOCK_CONTRACT_ADDR),
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: pool_amount + offer_amount,
+        }],
+    )]);
+
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: offer_amount,
+        }],
+    );
+    let swap_msg = |min_return: Option<Uint128>, deadline: Option<u64>| ExecuteMsg::Swap {
+        offer_asset: Asset {
+            info: asset_infos()[0].clone(),
+            amount: offer_amount,
+        },
+        ask_asset_info: asset_infos()[1].clone(),
+        belief_price: None,
+        max_spread: None,
+        to: None,
+        min_return,
+        deadline,
+    };
+
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        info.clone(),
+        swap_msg(None, expired),
+    )
+    .unwrap_err();
+    assert_eq!(res, ContractError::DeadlineExpired {});
+
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        info.clone(),
+        swap_msg(Some(simulation.return_amount + Uint128::new(1)), None),
+    )
+    .unwrap_err();
+    assert_eq!(res, ContractError::MinReturnAssertion {});
+
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        info,
+        swap_msg(
+            Some(simulation.return_amount),
+            Some(env.block.time.seconds()),
+        ),
+    )
+    .unwrap();
+
+    let deposit = vec![Asset {
+        info: asset_infos()[2].clone(),
+        amount: Uint128::new(10_000_000u128),
+    }];
+    let simulation = query_simulate_provide(deps.as_ref(), env.clone(), deposit.clone()).unwrap();
+    let provide_msg =
+        |min_lp_to_receive: Option<Uint128>, deadline: Option<u64>| ExecuteMsg::ProvideLiquidity {
+            assets: deposit.clone(),
+            auto_stake: None,
+            receiver: None,
+            min_lp_to_receive,
+            deadline,
+        };
+
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("addr0000", &[]),
+        provide_msg(None, expired),
+    )
+    .unwrap_err();
+    assert_eq!(res, ContractError::DeadlineExpired {});
+
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("addr0000", &[]),
+        provide_msg(Some(simulation.share + Uint128::new(1)), None),
+    )
+    .unwrap_err();
+    assert_eq!(res, ContractError::MinLpToReceiveAssertion {});
+
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("addr0000", &[]),
+        provide_msg(Some(simulation.share), None),
+    )
+    .unwrap();
+    assert!(res
+        .attributes
+        .contains(&attr("share", simulation.share.to_string())));
+
+    let lp_amount = Uint128::new(30_000_000u128);
+    let refund_assets = query_simulate_withdraw(deps.as_ref(), lp_amount).unwrap();
+    let withdraw_msg = |min_assets_out: Option<Vec<Asset>>, deadline: Option<u64>| {
+        ExecuteMsg::Receive(Cw20ReceiveMsg {
+            sender: String::from("addr0000"),
+            msg: to_binary(&Cw20HookMsg::WithdrawLiquidity {
+                min_assets_out,
+                deadline,
+            })
+            .unwrap(),
+            amount: lp_amount,
+        })
+    };
+
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("liquidity0000", &[]),
+        withdraw_msg(None, expired),
+    )
+    .unwrap_err();
+    assert_eq!(res, ContractError::DeadlineExpired {});
+
+    let mut min_assets_out = refund_assets.clone();
+    min_assets_out[2].amount += Uint128::new(1);
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("liquidity0000", &[]),
+        withdraw_msg(Some(min_assets_out), None),
+    )
+    .unwrap_err();
+    assert_eq!(res, ContractError::MinAssetsOutAssertion {});
+
+    execute(
+        deps.as_mut(),
+        env,
+        mock_info("liquidity0000", &[]),
+        withdraw_msg(Some(refund_assets), None),
+    )
+    .unwrap();
+}
@@ -0,0 +1,349 @@
+use astroport::asset::{Asset, AssetInfo, MultiPairInfo};
+use astroport::factory::{
+    ExecuteMsg as FactoryExecuteMsg, InstantiateMsg as FactoryInstantiateMsg, PairConfig, PairType,
+    QueryMsg as FactoryQueryMsg,
+};
+use astroport::pair::{SimulationResponse, StablePoolParams, MINIMUM_LIQUIDITY};
+use astroport::pair_stable_multi::{
+    Cw20HookMsg, ExecuteMsg, InstantiateMsg, PoolResponse, QueryMsg,
+};
+
+use astroport::token::InstantiateMsg as TokenInstantiateMsg;
+use cosmwasm_std::testing::{mock_env, MockApi, MockStorage};
+use cosmwasm_std::{to_binary, Addr, Uint128};
+use cw20::{BalanceResponse, Cw20Coin, Cw20ExecuteMsg, Cw20QueryMsg, MinterResponse};
+use terra_multi_test::{AppBuilder, BankKeeper, ContractWrapper, Executor, TerraApp, TerraMock};
+
+const OWNER: &str = "owner";
+
+fn mock_app() -> TerraApp {
+    let env = mock_env();
+    let api = MockApi::default();
+    let bank = BankKeeper::new();
+    let storage = MockStorage::new();
+    let custom = TerraMock::luna_ust_case();
+
+    AppBuilder::new()
+        .with_api(api)
+        .with_block(env.block)
+        .with_bank(bank)
+        .with_storage(storage)
+        .with_custom(custom)
+        .build()
+}
+
+fn store_token_code(app: &mut TerraApp) -> u64 {
+    let astro_token_contract = Box::new(ContractWrapper::new_with_empty(
+        astroport_token::contract::execute,
+        astroport_token::contract::instantiate,
+        astroport_token::contract::query,
+    ));
+
+    app.store_code(astro_token_contract)
+}
+
+fn store_pair_code(app: &mut TerraApp) -> u64 {
+    let pair_contract = Box::new(
+        ContractWrapper::new_with_empty(
+            astroport_pair_stable_multi::contract::execute,
+            astroport_pair_stable_multi::contract::instantiate,
+            astroport_pair_stable_multi::contract::query,
+        )
+        .with_reply_empty(astroport_pair_stable_multi::contract::reply),
+    );
+
+    app.store_code(pair_contract)
+}
+
+fn store_factory_code(app: &mut TerraApp) -> u64 {
+    let factory_contract = Box::new(
+        ContractWrapper::new_with_empty(
+            astroport_factory::contract::execute,
+            astroport_factory::contract::instantiate,
+            astroport_factory::contract::query,
+        )
+        .with_reply_empty(astroport_factory::contract::reply),
+    );
+
+    app.store_code(factory_contract)
+}
+
+fn instantiate_token(app: &mut TerraApp, token_code_id: u64, name: &str, amount: Uint128) -> Addr {
+    let init_msg = TokenInstantiateMsg {
+        name: name.to_string(),
+        symbol: name.to_string(),
+        decimals: 6,
+        initial_balances: vec![Cw20Coin {
+            address: OWNER.to_string(),
+            amount,
+        }],
+        mint: Some(MinterResponse {
+            minter: String::from(OWNER),
+            cap: None,
+        }),
+    };
+
+    app.instantiate_contract(
+        token_code_id,
+        Addr::unchecked(OWNER),
+        &init_msg,
+        &[],
+        name,
+        None,
+    )
+    .unwrap()
+}
+
+fn query_token_balance(app: &TerraApp, token: &Addr, address: &Addr) -> Uint128 {
+    let res: BalanceResponse = app
+        .wrap()
+        .query_wasm_smart(
+            token,
+            &Cw20QueryMsg::Balance {
+                address: address.to_string(),
+            },
+        )
+        .unwrap();
+
+    res.balance
+}
+
+#[test]
+fn create_provide_swap_and_withdraw() {
+    let mut app = mock_app();
+    let owner = Addr::unchecked(OWNER);
+    let user = Addr::unchecked("user");
+
+    let token_code_id = store_token_code(&mut app);
+    let pair_code_id = store_pair_code(&mut app);
+    let factory_code_id = store_factory_code(&mut app);
+
+    let tokens: Vec<Addr> = ["USDC", "USDT", "DAI"]
+        .iter()
+        .map(|name| {
+            instantiate_token(
+                &mut app,
+                token_code_id,
+                name,
+                Uint128::new(1_001_000_000_000u128),
+            )
+        })
+        .collect();
+
+    let init_msg = FactoryInstantiateMsg {
+        fee_address: None,
+        pair_configs: vec![PairConfig {
+            code_id: pair_code_id,
+            maker_fee_bps: 0,
+            total_fee_bps: 4,
+            pair_type: PairType::StableMulti {},
+            is_disabled: false,
+            is_generator_disabled: false,
+            maker_fee_as_lp: false,
+        }],
+        token_code_id,
+        generator_address: None,
+        owner: owner.to_string(),
+        whitelist_code_id: 234u64,
+    };
+
+    let factory_instance = app
+        .instantiate_contract(
+            factory_code_id,
+            owner.clone(),
+            &init_msg,
+            &[],
+            "FACTORY",
+            None,
+        )
+        .unwrap();
+
+    let asset_infos: Vec<AssetInfo> = tokens
+        .iter()
+        .map(|token| AssetInfo::Token {
+            contract_addr: token.clone(),
+        })
+        .collect();
+
+    let msg = FactoryExecuteMsg::CreateMultiPair {
+        pair_type: PairType::StableMulti {},
+        asset_infos: asset_infos.clone(),
+        init_params: Some(to_binary(&StablePoolParams { amp: 100 }).unwrap()),
+    };
+
+    app.execute_contract(owner.clone(), factory_instance.clone(), &msg, &[])
+        .unwrap();
+
+    let res: MultiPairInfo = app
+        .wrap()
+        .query_wasm_smart(
+            &factory_instance,
+            &FactoryQueryMsg::MultiPair {
+                asset_infos: asset_infos.clone(),
+                pair_type: None,
+            },
+        )
+        .unwrap();
+    assert_eq!(res.asset_infos, asset_infos);
+    assert_eq!(res.pair_type, PairType::StableMulti {});
+
+    let pair_instance = res.contract_addr;
+    let lp_token = res.liquidity_token;
+
+    // The pool can only be created once
+    let err = app
+        .execute_contract(owner.clone(), factory_instance.clone(), &msg, &[])
+        .unwrap_err();
+    assert_eq!(err.to_string(), "Pair was already created");
+
+    for token in tokens.iter() {
+        let msg = Cw20ExecuteMsg::IncreaseAllowance {
+            spender: pair_instance.to_string(),
+            expires: None,
+            amount: Uint128::new(1_000_000_000_000u128),
+        };
+
+        app.execute_contract(owner.clone(), token.clone(), &msg, &[])
+            .unwrap();
+    }
+
+    // Provide a balanced initial deposit
+    let msg = ExecuteMsg::ProvideLiquidity {
+        assets: asset_infos
+            .iter()
+            .map(|info| Asset {
+                info: info.clone(),
+                amount: Uint128::new(1_000_000_000_000u128),
+            })
+            .collect(),
+        auto_stake: None,
+        receiver: None,
+        min_lp_to_receive: None,
+        deadline: None,
+    };
+
+    app.execute_contract(owner.clone(), pair_instance.clone(), &msg, &[])
+        .unwrap();
+
+    // The minimum liquidity is locked in the pair
+    assert_eq!(
+        query_token_balance(&app, &lp_token, &owner),
+        Uint128::new(3_000_000_000_000u128) - MINIMUM_LIQUIDITY
+    );
+    assert_eq!(
+        query_token_balance(&app, &lp_token, &pair_instance),
+        MINIMUM_LIQUIDITY
+    );
+
+    let res: PoolResponse = app
+        .wrap()
+        .query_wasm_smart(&pair_instance, &QueryMsg::Pool {})
+        .unwrap();
+    assert_eq!(res.total_share, Uint128::new(3_000_000_000_000u128));
+    assert!(res
+        .assets
+        .iter()
+        .all(|asset| asset.amount == Uint128::new(1_000_000_000_000u128)));
+
+    // Swap USDC to DAI
+    let offer_amount = Uint128::new(1_000_000_000u128);
+    app.execute_contract(
+        owner.clone(),
+        tokens[0].clone(),
+        &Cw20ExecuteMsg::Transfer {
+            recipient: user.to_string(),
+            amount: offer_amount,
+        },
+        &[],
+    )
+    .unwrap();
+
+    let simulation: SimulationResponse = app
+        .wrap()
+        .query_wasm_smart(
+            &pair_instance,
+            &QueryMsg::Simulation {
+                offer_asset: Asset {
+                    info: asset_infos[0].clone(),
+                    amount: offer_amount,
+                },
+                ask_asset_info: asset_infos[2].clone(),
+            },
+        )
+        .unwrap();
+
+    let msg = Cw20ExecuteMsg::Send {
+        contract: pair_instance.to_string(),
+        msg: to_binary(&Cw20HookMsg::Swap {
+            ask_asset_info: asset_infos[2].clone(),
+            belief_price: None,
+            max_spread: None,
+            to: None,
+            min_return: None,
+            deadline: None,
+        })
+        .unwrap(),
+        amount: offer_amount,
+    };
+
+    app.execute_contract(user.clone(), tokens[0].clone(), &msg, &[])
+        .unwrap();
+
+    let dai_balance = query_token_balance(&app, &tokens[2], &user);
+    assert_eq!(dai_balance, simulation.return_amount);
+    // A balanced pool swaps close to 1:1, the 0.04% fee included
+    assert!(dai_balance > Uint128::new(999_000_000u128));
+    assert!(dai_balance < offer_amount);
+
+    // Withdraw a third of the liquidity
+    let msg = Cw20ExecuteMsg::Send {
+        contract: pair_instance.to_string(),
+        msg: to_binary(&Cw20HookMsg::WithdrawLiquidity {
+            min_assets_out: None,
+            deadline: None,
+        })
+        .unwrap(),
+        amount: Uint128::new(1_000_000_000_000u128),
+    };
+
+    app.execute_contract(owner.clone(), lp_token.clone(), &msg, &[])
+        .unwrap();
+
+    assert_eq!(
+        query_token_balance(&app, &lp_token, &owner),
+        Uint128::new(2_000_000_000_000u128) - MINIMUM_LIQUIDITY
+    );
+    assert_eq!(
+        query_token_balance(&app, &tokens[1], &owner),
+        Uint128::new(1_000_000_000u128 + 333_333_333_333u128)
+    );
+}
+
+#[test]
+fn create_pair_with_wrong_number_of_assets() {
+    let mut app = mock_app();
+    let owner = Addr::unchecked(OWNER);
+
+    let token_code_id = store_token_code(&mut app);
+    let pair_code_id = store_pair_code(&mut app);
+
+    let msg = InstantiateMsg {
+        asset_infos: vec![
+            AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            AssetInfo::NativeToken {
+                denom: "uluna".to_string(),
+            },
+        ],
+        token_code_id,
+        factory_addr: String::from("factory"),
+        init_params: Some(to_binary(&StablePoolParams { amp: 100 }).unwrap()),
+    };
+
+    let err = app
+        .instantiate_contract(pair_code_id, owner, &msg, &[], String::from("PAIR"), None)
+        .unwrap_err();
+
+    assert_eq!(err.to_string(), "The pool must contain from 3 to 5 assets");
+}
@@ -0,0 +1,2300 @@
+use crate::error::ContractError;
+use crate::state::{Config, CONFIG, FACTORY_PARAMS, RESERVES};
+use astroport::weighted_math::{
+    calc_ask_amount, calc_offer_amount, calc_share_for_single_asset, calc_single_asset_for_share,
+    calc_spot_price, MAX_IN_RATIO, MAX_OUT_RATIO, ONE,
+};
+
+use cosmwasm_bignumber::{Decimal256, Uint256};
+use cosmwasm_std::{
+    attr, entry_point, from_binary, to_binary, Addr, Binary, CosmosMsg, Decimal, Deps, DepsMut,
+    Env, MessageInfo, Reply, ReplyOn, Response, StdError, StdResult, Storage, SubMsg, Uint128,
+    WasmMsg,
+};
+
+use crate::response::MsgInstantiateContractResponse;
+use astroport::asset::{addr_validate_to_lower, format_lp_token_name, Asset, AssetInfo, PairInfo};
+use astroport::factory::PairType;
+use astroport::generator::Cw20HookMsg as GeneratorHookMsg;
+use astroport::pair::{ConfigResponse, DEFAULT_SLIPPAGE, MAX_ALLOWED_SLIPPAGE, MINIMUM_LIQUIDITY};
+use astroport::pair::{
+    CumulativePricesResponse, ExecuteMsg, InstantiateMsg, MigrateMsg, PoolResponse, QueryMsg,
+    ReverseSimulationResponse, SimulateProvideResponse, SimulationResponse, TWAP_PRECISION,
+};
+use astroport::pair_weighted::{Cw20HookMsg, WeightedPoolConfig, WeightedPoolParams, MIN_WEIGHT};
+use astroport::querier::{query_factory_params, query_supply};
+use astroport::{token::InstantiateMsg as TokenInstantiateMsg, U256};
+use cw2::set_contract_version;
+use cw20::{Cw20ExecuteMsg, Cw20ReceiveMsg, MinterResponse};
+use protobuf::Message;
+use std::str::FromStr;
+use std::vec;
+
+/// Contract name that is used for migration.
+const CONTRACT_NAME: &str = "astroport-pair-weighted";
+/// Contract version that is used for migration.
+const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
+/// A `reply` call code ID used for sub-messages.
+const INSTANTIATE_TOKEN_REPLY_ID: u64 = 1;
+
+/// ## Description
+/// Creates a new contract with the specified parameters in the [`InstantiateMsg`].
+/// Returns the [`Response`] with the specified attributes if the operation was successful, or a [`ContractError`] if
+/// the contract was not created.
+/// ## Params
+/// * **deps** is an object of type [`DepsMut`].
+///
+/// * **env** is an object of type [`Env`].
+///
+/// * **_info** is an object of type [`MessageInfo`].
+/// * **msg** is a message of type [`InstantiateMsg`] which contains the basic settings for creating a contract.
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn instantiate(
+    deps: DepsMut,
+    env: Env,
+    _info: MessageInfo,
+    msg: InstantiateMsg,
+) -> Result<Response, ContractError> {
+    msg.asset_infos[0].check(deps.api)?;
+    msg.asset_infos[1].check(deps.api)?;
+
+    if msg.asset_infos[0] == msg.asset_infos[1] {
+        return Err(ContractError::DoublingAssets {});
+    }
+
+    if msg.init_params.is_none() {
+        return Err(ContractError::InitParamsNotFound {});
+    }
+
+    let params: WeightedPoolParams = from_binary(&msg.init_params.unwrap())?;
+    let min_weight = Decimal::percent(MIN_WEIGHT);
+    if params.weights.iter().any(|w| *w < min_weight)
+        || params.weights[0] + params.weights[1] != Decimal::one()
+    {
+        return Err(ContractError::InvalidWeights {});
+    }
+
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+
+    let config = Config {
+        pair_info: PairInfo {
+            contract_addr: env.contract.address.clone(),
+            liquidity_token: Addr::unchecked(""),
+            asset_infos: msg.asset_infos.clone(),
+            pair_type: PairType::Weighted {},
+        },
+        factory_addr: addr_validate_to_lower(deps.api, msg.factory_addr.as_str())?,
+        block_time_last: 0,
+        price0_cumulative_last: Uint128::zero(),
+        price1_cumulative_last: Uint128::zero(),
+        weights: params.weights,
+    };
+
+    CONFIG.save(deps.storage, &config)?;
+    RESERVES.save(deps.storage, &[Uint128::zero(), Uint128::zero()])?;
+    FACTORY_PARAMS.save(
+        deps.storage,
+        &query_factory_params(
+            &deps.querier,
+            config.factory_addr.clone(),
+            env.contract.address.clone(),
+            config.pair_info.pair_type.clone(),
+        )?,
+    )?;
+
+    let token_name = format_lp_token_name(&msg.asset_infos, &deps.querier)?;
+
+    // Create the LP token contract
+    let sub_msg: Vec<SubMsg> = vec![SubMsg {
+        msg: WasmMsg::Instantiate {
+            code_id: msg.token_code_id,
+            msg: to_binary(&TokenInstantiateMsg {
+                name: token_name,
+                symbol: "uLP".to_string(),
+                decimals: 6,
+                initial_balances: vec![],
+                mint: Some(MinterResponse {
+                    minter: env.contract.address.to_string(),
+                    cap: None,
+                }),
+            })?,
+            funds: vec![],
+            admin: None,
+            label: String::from("Astroport LP token"),
+        }
+        .into(),
+        id: INSTANTIATE_TOKEN_REPLY_ID,
+        gas_limit: None,
+        reply_on: ReplyOn::Success,
+    }];
+
+    Ok(Response::new().add_submessages(sub_msg))
+}
+
+/// # Description
+/// The entry point to the contract for processing replies from submessages.
+/// # Params
+/// * **deps** is an object of type [`DepsMut`].
+///
+/// * **_env** is an object of type [`Env`].
+///
+/// * **msg** is an object of type [`Reply`].
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn reply(deps: DepsMut, _env: Env, msg: Reply) -> Result<Response, ContractError> {
+    let mut config: Config = CONFIG.load(deps.storage)?;
+
+    if config.pair_info.liquidity_token != Addr::unchecked("") {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let data = msg.result.unwrap().data.unwrap();
+    let res: MsgInstantiateContractResponse =
+        Message::parse_from_bytes(data.as_slice()).map_err(|_| {
+            StdError::parse_err("MsgInstantiateContractResponse", "failed to parse data")
+        })?;
+
+    config.pair_info.liquidity_token =
+        addr_validate_to_lower(deps.api, res.get_contract_address())?;
+
+    CONFIG.save(deps.storage, &config)?;
+
+    Ok(Response::new().add_attribute("liquidity_token_addr", config.pair_info.liquidity_token))
+}
+
+/// ## Description
+/// Exposes all the execute functions available in the contract.
+/// ## Params
+/// * **deps** is an object of type [`Deps`].
+///
+/// * **env** is an object of type [`Env`].
+///
+/// * **info** is an object of type [`MessageInfo`].
+///
+/// * **msg** is an object of type [`ExecuteMsg`].
+///
+/// ## Queries
+/// * **ExecuteMsg::UpdateConfig { params: Binary }** Not supported.
+///
+/// * **ExecuteMsg::Receive(msg)** Receives a message of type [`Cw20ReceiveMsg`] and processes
+/// it depending on the received template.
+///
+/// * **ExecuteMsg::ProvideLiquidity {
+///             assets,
+///             slippage_tolerance,
+///             auto_stake,
+///             receiver,
+///             min_lp_to_receive,
+///             deadline,
+///         }** Provides liquidity in the pair with the specified input parameters.
+///
+/// * **ExecuteMsg::ProvideSingleSided {
+///             asset,
+///             min_lp_to_receive,
+///             auto_stake,
+///             receiver,
+///             deadline,
+///         }** Provides liquidity in the pair using only one of the pool assets.
+///
+/// * **ExecuteMsg::Swap {
+///             offer_asset,
+///             belief_price,
+///             max_spread,
+///             to,
+///             min_return,
+///             deadline,
+///         }** Performs a swap operation with the specified parameters.
+///
+/// * **ExecuteMsg::SwapExactOut {
+///             ask_asset,
+///             max_offer,
+///             to,
+///             deadline,
+///         }** Performs a swap that returns exactly the specified ask asset.
+///
+/// * **ExecuteMsg::FlashLoan { .. }** Not supported.
+///
+/// * **ExecuteMsg::AssertFlashLoanRepaid {}** Not supported.
+///
+/// * **ExecuteMsg::Sync {}** Sets the tracked pool reserves to the current balances of the pair.
+///
+/// * **ExecuteMsg::Skim { to }** Sends the balances of the pair that exceed the tracked pool reserves to a recipient.
+///
+/// * **ExecuteMsg::RefreshFactoryParams {}** Updates the fee and Generator information cached from the factory.
+///
+/// * **ExecuteMsg::SetProtocolFeeAccrual { .. }** Not supported.
+///
+/// * **ExecuteMsg::ClaimProtocolFees {}** Not supported.
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn execute(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: ExecuteMsg,
+) -> Result<Response, ContractError> {
+    match msg {
+        ExecuteMsg::UpdateConfig { .. }
+        | ExecuteMsg::FlashLoan { .. }
+        | ExecuteMsg::AssertFlashLoanRepaid {}
+        | ExecuteMsg::SetProtocolFeeAccrual { .. }
+        | ExecuteMsg::ClaimProtocolFees {} => Err(ContractError::NonSupported {}),
+        ExecuteMsg::Receive(msg) => receive_cw20(deps, env, info, msg),
+        ExecuteMsg::RefreshFactoryParams {} => refresh_factory_params(deps, env),
+        ExecuteMsg::Sync {} => sync(deps, env),
+        ExecuteMsg::Skim { to } => skim(deps, env, info, to),
+        ExecuteMsg::ProvideLiquidity {
+            assets,
+            slippage_tolerance,
+            auto_stake,
+            receiver,
+            min_lp_to_receive,
+            deadline,
+        } => provide_liquidity(
+            deps,
+            env,
+            info,
+            assets,
+            slippage_tolerance,
+            auto_stake,
+            receiver,
+            min_lp_to_receive,
+            deadline,
+        ),
+        ExecuteMsg::ProvideSingleSided {
+            asset,
+            min_lp_to_receive,
+            auto_stake,
+            receiver,
+            deadline,
+        } => provide_single_sided(
+            deps,
+            env,
+            info,
+            asset,
+            min_lp_to_receive,
+            auto_stake,
+            receiver,
+            deadline,
+        ),
+        ExecuteMsg::Swap {
+            offer_asset,
+            belief_price,
+            max_spread,
+            to,
+            min_return,
+            deadline,
+        } => {
+            offer_asset.info.check(deps.api)?;
+            if !offer_asset.is_native_token() {
+                return Err(ContractError::Unauthorized {});
+            }
+
+            let to_addr = if let Some(to_addr) = to {
+                Some(addr_validate_to_lower(deps.api, &to_addr)?)
+            } else {
+                None
+            };
+
+            swap(
+                deps,
+                env,
+                info.clone(),
+                info.sender,
+                offer_asset,
+                belief_price,
+                max_spread,
+                to_addr,
+                min_return,
+                deadline,
+            )
+        }
+        ExecuteMsg::SwapExactOut {
+            ask_asset,
+            max_offer,
+            to,
+            deadline,
+        } => {
+            ask_asset.info.check(deps.api)?;
+
+            let to_addr = if let Some(to_addr) = to {
+                Some(addr_validate_to_lower(deps.api, &to_addr)?)
+            } else {
+                None
+            };
+
+            swap_exact_out(
+                deps,
+                env,
+                info.clone(),
+                info.sender,
+                ask_asset,
+                max_offer,
+                false,
+                to_addr,
+                deadline,
+            )
+        }
+    }
+}
+
+/// ## Description
+/// Receives a message of type [`Cw20ReceiveMsg`] and processes it depending on the received template.
+/// If the template is not found in the received message, then an [`ContractError`] is returned,
+/// otherwise it returns the [`Response`] with the specified attributes if the operation was successful.
+/// ## Params
+/// * **deps** is an object of type [`DepsMut`].
+///
+/// * **env** is an object of type [`Env`].
+///
+/// * **info** is an object of type [`MessageInfo`].
+///
+/// * **cw20_msg** is an object of type [`Cw20ReceiveMsg`]. This is the CW20 message that has to be processed.
+pub fn receive_cw20(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    cw20_msg: Cw20ReceiveMsg,
+) -> Result<Response, ContractError> {
+    let contract_addr = info.sender.clone();
+    match from_binary(&cw20_msg.msg) {
+        Ok(Cw20HookMsg::Swap {
+            belief_price,
+            max_spread,
+            to,
+            min_return,
+            deadline,
+        }) => {
+            // Only asset contract can execute this message
+            let mut authorized: bool = false;
+            let config: Config = CONFIG.load(deps.storage)?;
+
+            for pool in config.pair_info.asset_infos {
+                if let AssetInfo::Token { contract_addr, .. } = &pool {
+                    if contract_addr == &info.sender {
+                        authorized = true;
+                    }
+                }
+            }
+
+            if !authorized {
+                return Err(ContractError::Unauthorized {});
+            }
+
+            let to_addr = if let Some(to_addr) = to {
+                Some(addr_validate_to_lower(deps.api, to_addr.as_str())?)
+            } else {
+                None
+            };
+
+            swap(
+                deps,
+                env,
+                info,
+                Addr::unchecked(cw20_msg.sender),
+                Asset {
+                    info: AssetInfo::Token { contract_addr },
+                    amount: cw20_msg.amount,
+                },
+                belief_price,
+                max_spread,
+                to_addr,
+                min_return,
+                deadline,
+            )
+        }
+        Ok(Cw20HookMsg::SwapExactOut {
+            ask_asset,
+            to,
+            deadline,
+        }) => {
+            let to_addr = if let Some(to_addr) = to {
+                Some(addr_validate_to_lower(deps.api, to_addr.as_str())?)
+            } else {
+                None
+            };
+
+            swap_exact_out(
+                deps,
+                env,
+                info,
+                Addr::unchecked(cw20_msg.sender),
+                ask_asset,
+                cw20_msg.amount,
+                true,
+                to_addr,
+                deadline,
+            )
+        }
+        Ok(Cw20HookMsg::WithdrawLiquidity {
+            min_assets_out,
+            deadline,
+        }) => withdraw_liquidity(
+            deps,
+            env,
+            info,
+            Addr::unchecked(cw20_msg.sender),
+            cw20_msg.amount,
+            min_assets_out,
+            deadline,
+        ),
+        Ok(Cw20HookMsg::WithdrawSingleSided {
+            ask_asset_info,
+            min_ask_amount,
+            deadline,
+        }) => withdraw_single_sided(
+            deps,
+            env,
+            info,
+            Addr::unchecked(cw20_msg.sender),
+            cw20_msg.amount,
+            ask_asset_info,
+            min_ask_amount,
+            deadline,
+        ),
+        Err(err) => Err(ContractError::Std(err)),
+    }
+}
+
+/// ## Description
+/// Provides liquidity in the pair with the specified input parameters.
+/// Returns a [`ContractError`] on failure, otherwise returns a [`Response`] with the specified
+/// attributes if the operation was successful.
+/// ## Params
+/// * **deps** is an object of type [`DepsMut`].
+///
+/// * **env** is an object of type [`Env`].
+///
+/// * **info** is an object of type [`MessageInfo`].
+///
+/// * **assets** is an array with two objects of type [`Asset`]. These are the assets available in the pool.
+///
+/// * **slippage_tolerance** is an [`Option`] field of type [`Decimal`]. It is used to specify how much
+/// the pool price can move until the provide liquidity transaction goes through.
+///
+/// * **auto_stake** is an [`Option`] field of type [`bool`]. Determines whether the LP tokens minted after
+/// liquidity provision are automatically staked in the Generator contract on behalf of the LP token receiver.
+///
+/// * **receiver** is an [`Option`] field of type [`String`]. This is the receiver of the LP tokens.
+/// If no custom receiver is specified, the pair will mint LP tokens for the function caller.
+///
+/// * **min_lp_to_receive** is an [`Option`] field of type [`Uint128`]. This is the minimum amount of
+/// LP tokens that must be minted, otherwise the transaction fails.
+///
+/// * **deadline** is an [`Option`] field of type [`u64`]. This is the block time (in seconds) after which
+/// the transaction fails.
+// NOTE - the address that wants to provide liquidity should approve the pair contract to pull its relevant tokens.
+#[allow(clippy::too_many_arguments)]
+pub fn provide_liquidity(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    assets: [Asset; 2],
+    slippage_tolerance: Option<Decimal>,
+    auto_stake: Option<bool>,
+    receiver: Option<String>,
+    min_lp_to_receive: Option<Uint128>,
+    deadline: Option<u64>,
+) -> Result<Response, ContractError> {
+    assert_deposits_allowed(deps.storage)?;
+    assert_deadline(&env, deadline)?;
+
+    assets[0].info.check(deps.api)?;
+    assets[1].info.check(deps.api)?;
+
+    let auto_stake = auto_stake.unwrap_or(false);
+    for asset in assets.iter() {
+        asset.assert_sent_native_token_balance(&info)?;
+    }
+
+    let mut config: Config = CONFIG.load(deps.storage)?;
+    let pools = query_reserves(deps.storage, &config)?;
+    let deposits = get_deposits(&assets, &pools)?;
+
+    let mut messages: Vec<CosmosMsg> = vec![];
+    for (i, pool) in pools.iter().enumerate() {
+        // If the asset is a token contract, then we need to execute a TransferFrom msg to receive assets
+        if let AssetInfo::Token { contract_addr, .. } = &pool.info {
+            messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: contract_addr.to_string(),
+                msg: to_binary(&Cw20ExecuteMsg::TransferFrom {
+                    owner: info.sender.to_string(),
+                    recipient: env.contract.address.to_string(),
+                    amount: deposits[i],
+                })?,
+                funds: vec![],
+            }));
+        }
+    }
+
+    let total_share = query_supply(&deps.querier, config.pair_info.liquidity_token.clone())?;
+    let share = calculate_share(&deposits, &pools, total_share, slippage_tolerance)?;
+
+    if total_share.is_zero() {
+        // Lock the minimum liquidity in the pair forever, so the share price can't be inflated
+        messages.extend(mint_liquidity_token_message(
+            deps.as_ref(),
+            &config,
+            env.clone(),
+            env.contract.address.clone(),
+            MINIMUM_LIQUIDITY,
+            false,
+        )?);
+    }
+
+    if let Some(min_lp_to_receive) = min_lp_to_receive {
+        if share < min_lp_to_receive {
+            return Err(ContractError::MinLpToReceiveAssertion {});
+        }
+    }
+
+    // Mint LP tokens for the sender or for the receiver (if set)
+    let receiver = receiver.unwrap_or_else(|| info.sender.to_string());
+    messages.extend(mint_liquidity_token_message(
+        deps.as_ref(),
+        &config,
+        env.clone(),
+        addr_validate_to_lower(deps.api, receiver.as_str())?,
+        share,
+        auto_stake,
+    )?);
+
+    // Accumulate prices for the assets in the pool
+    if let Some((price0_cumulative_new, price1_cumulative_new, block_time)) =
+        accumulate_prices(env, &config, pools[0].amount, pools[1].amount)?
+    {
+        config.price0_cumulative_last = price0_cumulative_new;
+        config.price1_cumulative_last = price1_cumulative_new;
+        config.block_time_last = block_time;
+        CONFIG.save(deps.storage, &config)?;
+    }
+
+    RESERVES.save(
+        deps.storage,
+        &[
+            pools[0].amount.checked_add(deposits[0])?,
+            pools[1].amount.checked_add(deposits[1])?,
+        ],
+    )?;
+
+    Ok(Response::new().add_messages(messages).add_attributes(vec![
+        attr("action", "provide_liquidity"),
+        attr("sender", info.sender.as_str()),
+        attr("receiver", receiver.as_str()),
+        attr("assets", format!("{}, {}", assets[0], assets[1])),
+        attr("share", share.to_string()),
+    ]))
+}
+
+/// ## Description
+/// Returns the deposit amounts in the order of the pool assets. Both pool assets must be deposited.
+/// ## Params
+/// * **assets** is an array with two objects of type [`Asset`]. These are the assets to deposit.
+///
+/// * **pools** are an array of [`Asset`] type items. These are the assets available in the pool.
+fn get_deposits(assets: &[Asset; 2], pools: &[Asset; 2]) -> Result<[Uint128; 2], ContractError> {
+    let deposit = |pool: &Asset| {
+        assets
+            .iter()
+            .find(|a| a.info.equal(&pool.info))
+            .map(|a| a.amount)
+            .ok_or(ContractError::AssetMismatch {})
+    };
+    let deposits = [deposit(&pools[0])?, deposit(&pools[1])?];
+
+    if deposits[0].is_zero() || deposits[1].is_zero() {
+        return Err(ContractError::InvalidZeroAmount {});
+    }
+
+    Ok(deposits)
+}
+
+/// ## Description
+/// Returns the amount of LP tokens minted for a deposit of both pool assets. The first deposit mints
+/// the geometric mean of the deposited amounts minus the [`MINIMUM_LIQUIDITY`] that is locked in the pair.
+/// ## Params
+/// * **deposits** is an array with two objects of type [`Uint128`]. These are the deposits in the order of the pool assets.
+///
+/// * **pools** are an array of [`Asset`] type items. These are the assets available in the pool
+/// without the deposits.
+///
+/// * **total_share** is an object of type [`Uint128`]. This is the total amount of LP tokens.
+///
+/// * **slippage_tolerance** is an [`Option`] field of type [`Decimal`]. It is used to specify how much
+/// the pool price can move until the provide liquidity transaction goes through.
+fn calculate_share(
+    deposits: &[Uint128; 2],
+    pools: &[Asset; 2],
+    total_share: Uint128,
+    slippage_tolerance: Option<Decimal>,
+) -> Result<Uint128, ContractError> {
+    let share = if total_share.is_zero() {
+        // Initial share = collateral amount
+        Uint128::new(
+            (U256::from(deposits[0].u128()) * U256::from(deposits[1].u128()))
+                .integer_sqrt()
+                .as_u128(),
+        )
+        .checked_sub(MINIMUM_LIQUIDITY)
+        .map_err(|_| ContractError::MinimumLiquidityAmountError {})?
+    } else {
+        // Assert slippage tolerance
+        assert_slippage_tolerance(slippage_tolerance, deposits, pools)?;
+
+        // The pool invariant is kept when both assets are deposited in the current pool ratio,
+        // so the share is computed the same way as in the constant product pool
+        std::cmp::min(
+            deposits[0].multiply_ratio(total_share, pools[0].amount),
+            deposits[1].multiply_ratio(total_share, pools[1].amount),
+        )
+    };
+
+    if share.is_zero() {
+        return Err(ContractError::InvalidZeroAmount {});
+    }
+
+    Ok(share)
+}
+
+/// ## Description
+/// Provides liquidity in the pair using only one of the pool assets. LP tokens are minted according
+/// to the weighted math, so the deposit is charged a fee only for the part that would have to be
+/// swapped to the other pool asset.
+/// Returns a [`ContractError`] on failure, otherwise returns a [`Response`] with the specified
+/// attributes if the operation was successful.
+/// ## Params
+/// * **deps** is an object of type [`DepsMut`].
+///
+/// * **env** is an object of type [`Env`].
+///
+/// * **info** is an object of type [`MessageInfo`].
+///
+/// * **asset** is an object of type [`Asset`]. This is the asset to provide.
+///
+/// * **min_lp_to_receive** is an [`Option`] field of type [`Uint128`]. This is the minimum amount of
+/// LP tokens that must be minted, otherwise the transaction fails.
+///
+/// * **auto_stake** is an [`Option`] field of type [`bool`]. Determines whether the LP tokens minted after
+/// liquidity provision are automatically staked in the Generator contract on behalf of the LP token receiver.
+///
+/// * **receiver** is an [`Option`] field of type [`String`]. This is the receiver of the LP tokens.
+/// If no custom receiver is specified, the pair will mint LP tokens for the function caller.
+///
+/// * **deadline** is an [`Option`] field of type [`u64`]. This is the block time (in seconds) after which
+/// the transaction fails.
+// NOTE - the address that wants to provide liquidity should approve the pair contract to pull its relevant tokens.
+#[allow(clippy::too_many_arguments)]
+pub fn provide_single_sided(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    asset: Asset,
+    min_lp_to_receive: Option<Uint128>,
+    auto_stake: Option<bool>,
+    receiver: Option<String>,
+    deadline: Option<u64>,
+) -> Result<Response, ContractError> {
+    assert_deposits_allowed(deps.storage)?;
+    assert_deadline(&env, deadline)?;
+    asset.info.check(deps.api)?;
+    asset.assert_sent_native_token_balance(&info)?;
+
+    if asset.amount.is_zero() {
+        return Err(ContractError::InvalidZeroAmount {});
+    }
+
+    let auto_stake = auto_stake.unwrap_or(false);
+    let mut config: Config = CONFIG.load(deps.storage)?;
+    let pools = query_reserves(deps.storage, &config)?;
+
+    let offer_index = if asset.info.equal(&pools[0].info) {
+        0
+    } else if asset.info.equal(&pools[1].info) {
+        1
+    } else {
+        return Err(ContractError::AssetMismatch {});
+    };
+
+    let mut messages: Vec<CosmosMsg> = vec![];
+    if let AssetInfo::Token { contract_addr, .. } = &asset.info {
+        messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: contract_addr.to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::TransferFrom {
+                owner: info.sender.to_string(),
+                recipient: env.contract.address.to_string(),
+                amount: asset.amount,
+            })?,
+            funds: vec![],
+        }));
+    }
+
+    let total_share = query_supply(&deps.querier, config.pair_info.liquidity_token.clone())?;
+    if total_share.is_zero() || pools[0].amount.is_zero() || pools[1].amount.is_zero() {
+        return Err(ContractError::SingleSidedEmptyPool {});
+    }
+
+    let offer_pool = pools[offer_index].amount;
+    if asset.amount > offer_pool.multiply_ratio(MAX_IN_RATIO, 100u64) {
+        return Err(ContractError::MaxInRatioAssertion {});
+    }
+
+    // Get the fee info cached from the factory
+    let fee_info = FACTORY_PARAMS.load(deps.storage)?.fee_info;
+
+    let (share, commission_amount) = calc_share_for_single_asset(
+        offer_pool.u128(),
+        to_fixed(config.weights[offer_index]),
+        total_share.u128(),
+        asset.amount.u128(),
+        to_fixed(fee_info.total_fee_rate),
+    )
+    .ok_or(ContractError::MathOverflow {})?;
+    let share = Uint128::new(share);
+    let commission_amount = Uint128::new(commission_amount);
+
+    if share.is_zero() {
+        return Err(ContractError::InvalidZeroAmount {});
+    }
+
+    if let Some(min_lp_to_receive) = min_lp_to_receive {
+        if share < min_lp_to_receive {
+            return Err(ContractError::MinLpToReceiveAssertion {});
+        }
+    }
+
+    let mut maker_fee_amount = Uint128::zero();
+    if let Some(fee_address) = fee_info.fee_address {
+        if let Some(f) = calculate_maker_fee(
+            asset.info.clone(),
+            commission_amount,
+            fee_info.maker_fee_rate,
+        ) {
+            maker_fee_amount = f.amount;
+            messages.push(f.into_msg(&deps.querier, fee_address)?);
+        }
+    }
+
+    // Mint LP tokens for the sender or for the receiver (if set)
+    let receiver = receiver.unwrap_or_else(|| info.sender.to_string());
+    messages.extend(mint_liquidity_token_message(
+        deps.as_ref(),
+        &config,
+        env.clone(),
+        addr_validate_to_lower(deps.api, receiver.as_str())?,
+        share,
+        auto_stake,
+    )?);
+
+    // Accumulate prices for the assets in the pool
+    if let Some((price0_cumulative_new, price1_cumulative_new, block_time)) =
+        accumulate_prices(env, &config, pools[0].amount, pools[1].amount)?
+    {
+        config.price0_cumulative_last = price0_cumulative_new;
+        config.price1_cumulative_last = price1_cumulative_new;
+        config.block_time_last = block_time;
+        CONFIG.save(deps.storage, &config)?;
+    }
+
+    // The Maker fee is paid out of the deposit, the rest of the deposit stays in the pool
+    let mut reserves = [pools[0].amount, pools[1].amount];
+    reserves[offer_index] = reserves[offer_index].checked_add(asset.amount - maker_fee_amount)?;
+    RESERVES.save(deps.storage, &reserves)?;
+
+    Ok(Response::new().add_messages(messages).add_attributes(vec![
+        attr("action", "provide_single_sided"),
+        attr("sender", info.sender.as_str()),
+        attr("receiver", receiver.as_str()),
+        attr("asset", asset.to_string()),
+        attr("commission_amount", commission_amount.to_string()),
+        attr("maker_fee_amount", maker_fee_amount.to_string()),
+        attr("share", share.to_string()),
+    ]))
+}
+
+/// # Description
+/// Mint LP tokens for a beneficiary and auto stake the tokens in the Generator contract (if auto staking is specified).
+/// # Params
+/// * **deps** is an object of type [`Deps`].
+///
+/// * **config** is an object of type [`Config`].
+///
+/// * **env** is an object of type [`Env`].
+///
+/// * **recipient** is an object of type [`Addr`]. This is the LP token recipient.
+///
+/// * **amount** is an object of type [`Uint128`]. This is the amount of LP tokens that will be minted for the recipient.
+///
+/// * **auto_stake** is the field of type [`bool`]. Determines whether the newly minted LP tokens will
+/// be automatically staked in the Generator on behalf of the recipient.
+fn mint_liquidity_token_message(
+    deps: Deps,
+    config: &Config,
+    env: Env,
+    recipient: Addr,
+    amount: Uint128,
+    auto_stake: bool,
+) -> Result<Vec<CosmosMsg>, ContractError> {
+    let lp_token = config.pair_info.liquidity_token.clone();
+
+    // If no auto-stake - just mint to recipient
+    if !auto_stake {
+        return Ok(vec![CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: lp_token.to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Mint {
+                recipient: recipient.to_string(),
+                amount,
+            })?,
+            funds: vec![],
+        })]);
+    }
+
+    // Mint for the pair contract and stake into the Generator contract
+    let generator = FACTORY_PARAMS.load(deps.storage)?.generator_address;
+
+    if generator.is_none() {
+        return Err(ContractError::AutoStakeError {});
+    }
+
+    Ok(vec![
+        CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: lp_token.to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Mint {
+                recipient: env.contract.address.to_string(),
+                amount,
+            })?,
+            funds: vec![],
+        }),
+        CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: lp_token.to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Send {
+                contract: generator.unwrap().to_string(),
+                amount,
+                msg: to_binary(&GeneratorHookMsg::DepositFor(recipient))?,
+            })?,
+            funds: vec![],
+        }),
+    ])
+}
+
+/// ## Description
+/// Withdraw liquidity from the pool. Returns a [`ContractError`] on failure,
+/// otherwise returns a [`Response`] with the specified attributes if the operation was successful.
+/// ## Params
+/// * **deps** is an object of type [`DepsMut`].
+///
+/// * **env** is an object of type [`Env`].
+///
+/// * **info** is an object of type [`MessageInfo`].
+///
+/// * **sender** is an object of type [`Addr`]. This is the address that will receive assets back from the pair contract.
+///
+/// * **amount** is an object of type [`Uint128`]. This is the amount of LP tokens to burn.
+///
+/// * **min_assets_out** is an [`Option`] field of type [`Vec<Asset>`]. These are the minimum amounts of assets
+/// to receive, otherwise the transaction fails.
+///
+/// * **deadline** is an [`Option`] field of type [`u64`]. This is the block time (in seconds) after which
+/// the transaction fails.
+pub fn withdraw_liquidity(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    sender: Addr,
+    amount: Uint128,
+    min_assets_out: Option<Vec<Asset>>,
+    deadline: Option<u64>,
+) -> Result<Response, ContractError> {
+    assert_deadline(&env, deadline)?;
+
+    let mut config: Config = CONFIG.load(deps.storage).unwrap();
+
+    if info.sender != config.pair_info.liquidity_token {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let (pools, total_share) = pool_info(deps.as_ref(), config.clone())?;
+    let refund_assets = get_share_in_assets(&pools, amount, total_share);
+    assert_min_assets_out(&refund_assets, min_assets_out)?;
+
+    RESERVES.save(
+        deps.storage,
+        &[
+            pools[0].amount.checked_sub(refund_assets[0].amount)?,
+            pools[1].amount.checked_sub(refund_assets[1].amount)?,
+        ],
+    )?;
+
+    // Accumulate prices for the pair assets
+    if let Some((price0_cumulative_new, price1_cumulative_new, block_time)) =
+        accumulate_prices(env, &config, pools[0].amount, pools[1].amount)?
+    {
+        config.price0_cumulative_last = price0_cumulative_new;
+        config.price1_cumulative_last = price1_cumulative_new;
+        config.block_time_last = block_time;
+        CONFIG.save(deps.storage, &config)?;
+    }
+
+    // Update the pool info
+    let messages: Vec<CosmosMsg> = vec![
+        refund_assets[0]
+            .clone()
+            .into_msg(&deps.querier, sender.clone())?,
+        refund_assets[1]
+            .clone()
+            .into_msg(&deps.querier, sender.clone())?,
+        CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: config.pair_info.liquidity_token.to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Burn { amount })?,
+            funds: vec![],
+        }),
+    ];
+
+    let attributes = vec![
+        attr("action", "withdraw_liquidity"),
+        attr("sender", sender.as_str()),
+        attr("withdrawn_share", amount.to_string()),
+        attr(
+            "refund_assets",
+            format!("{}, {}", refund_assets[0], refund_assets[1]),
+        ),
+    ];
+
+    Ok(Response::new()
+        .add_messages(messages)
+        .add_attributes(attributes))
+}
+
+/// ## Description
+/// Withdraw liquidity from the pool in a single asset. The withdrawal is charged a fee only for the part
+/// that would have to be swapped from the other pool asset.
+/// Returns a [`ContractError`] on failure, otherwise returns a [`Response`] with the specified
+/// attributes if the operation was successful.
+/// ## Params
+/// * **deps** is an object of type [`DepsMut`].
+///
+/// * **env** is an object of type [`Env`].
+///
+/// * **info** is an object of type [`MessageInfo`].
+///
+/// * **sender** is an object of type [`Addr`]. This is the address that will receive assets back from the pair contract.
+///
+/// * **amount** is an object of type [`Uint128`]. This is the amount of LP tokens to burn.
+///
+/// * **ask_asset_info** is an object of type [`AssetInfo`]. This is the asset to withdraw.
+///
+/// * **min_ask_amount** is an [`Option`] field of type [`Uint128`]. This is the minimum amount of
+/// assets to withdraw, otherwise the transaction fails.
+///
+/// * **deadline** is an [`Option`] field of type [`u64`]. This is the block time (in seconds) after which
+/// the transaction fails.
+#[allow(clippy::too_many_arguments)]
+pub fn withdraw_single_sided(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    sender: Addr,
+    amount: Uint128,
+    ask_asset_info: AssetInfo,
+    min_ask_amount: Option<Uint128>,
+    deadline: Option<u64>,
+) -> Result<Response, ContractError> {
+    assert_deadline(&env, deadline)?;
+
+    let mut config: Config = CONFIG.load(deps.storage)?;
+
+    if info.sender != config.pair_info.liquidity_token {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let (pools, total_share) = pool_info(deps.as_ref(), config.clone())?;
+    let ask_index = if ask_asset_info.equal(&pools[0].info) {
+        0
+    } else if ask_asset_info.equal(&pools[1].info) {
+        1
+    } else {
+        return Err(ContractError::AssetMismatch {});
+    };
+
+    if total_share.is_zero() || pools[ask_index].amount.is_zero() {
+        return Err(ContractError::SingleSidedEmptyPool {});
+    }
+
+    // Get the fee info cached from the factory
+    let fee_info = FACTORY_PARAMS.load(deps.storage)?.fee_info;
+
+    let ask_pool = pools[ask_index].amount;
+    let (return_amount, commission_amount) = calc_single_asset_for_share(
+        ask_pool.u128(),
+        to_fixed(config.weights[ask_index]),
+        total_share.u128(),
+        amount.u128(),
+        to_fixed(fee_info.total_fee_rate),
+    )
+    .ok_or(ContractError::MathOverflow {})?;
+    let return_amount = Uint128::new(return_amount);
+    let commission_amount = Uint128::new(commission_amount);
+
+    if return_amount > ask_pool.multiply_ratio(MAX_OUT_RATIO, 100u64) {
+        return Err(ContractError::MaxOutRatioAssertion {});
+    }
+
+    if return_amount.is_zero() {
+        return Err(ContractError::InvalidZeroAmount {});
+    }
+
+    if let Some(min_ask_amount) = min_ask_amount {
+        if return_amount < min_ask_amount {
+            return Err(ContractError::MinAskAmountAssertion {});
+        }
+    }
+
+    let return_asset = Asset {
+        info: ask_asset_info.clone(),
+        amount: return_amount,
+    };
+    let mut messages: Vec<CosmosMsg> = vec![
+        return_asset
+            .clone()
+            .into_msg(&deps.querier, sender.clone())?,
+        CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: config.pair_info.liquidity_token.to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Burn { amount })?,
+            funds: vec![],
+        }),
+    ];
+
+    let mut maker_fee_amount = Uint128::zero();
+    if let Some(fee_address) = fee_info.fee_address {
+        if let Some(f) =
+            calculate_maker_fee(ask_asset_info, commission_amount, fee_info.maker_fee_rate)
+        {
+            maker_fee_amount = f.amount;
+            messages.push(f.into_msg(&deps.querier, fee_address)?);
+        }
+    }
+
+    // Accumulate prices for the pair assets
+    if let Some((price0_cumulative_new, price1_cumulative_new, block_time)) =
+        accumulate_prices(env, &config, pools[0].amount, pools[1].amount)?
+    {
+        config.price0_cumulative_last = price0_cumulative_new;
+        config.price1_cumulative_last = price1_cumulative_new;
+        config.block_time_last = block_time;
+        CONFIG.save(deps.storage, &config)?;
+    }
+
+    let mut reserves = [pools[0].amount, pools[1].amount];
+    reserves[ask_index] = reserves[ask_index].checked_sub(return_amount + maker_fee_amount)?;
+    RESERVES.save(deps.storage, &reserves)?;
+
+    Ok(Response::new().add_messages(messages).add_attributes(vec![
+        attr("action", "withdraw_single_sided"),
+        attr("sender", sender.as_str()),
+        attr("withdrawn_share", amount.to_string()),
+        attr("return_asset", return_asset.to_string()),
+        attr("commission_amount", commission_amount.to_string()),
+        attr("maker_fee_amount", maker_fee_amount.to_string()),
+    ]))
+}
+
+/// ## Description
+/// Returns the amount of pool assets that correspond to an amount of LP tokens.
+/// ## Params
+/// * **pools** are an array of [`Asset`] type items. These are the assets in the pool.
+///
+/// * **amount** is an object of type [`Uint128`]. This is the amount of LP tokens to compute a corresponding amount of assets for.
+///
+/// * **total_share** is an object of type [`Uint128`]. This is the total amount of LP tokens currently minted.
+pub fn get_share_in_assets(
+    pools: &[Asset; 2],
+    amount: Uint128,
+    total_share: Uint128,
+) -> Vec<Asset> {
+    let mut share_ratio = Decimal::zero();
+    if !total_share.is_zero() {
+        share_ratio = Decimal::from_ratio(amount, total_share);
+    }
+
+    pools
+        .iter()
+        .map(|a| Asset {
+            info: a.info.clone(),
+            amount: a.amount * share_ratio,
+        })
+        .collect()
+}
+
+/// ## Description
+/// Performs an swap operation with the specified parameters. The trader must approve the
+/// pool contract to transfer offer assets from their wallet.
+/// Returns an [`ContractError`] on failure, otherwise returns the [`Response`] with the specified attributes if the operation was successful.
+/// ## Params
+/// * **deps** is an object of type [`DepsMut`].
+///
+/// * **env** is an object of type [`Env`].
+///
+/// * **info** is an object of type [`MessageInfo`].
+///
+/// * **sender** is an object of type [`Addr`]. This is the sender of the swap operation.
+///
+/// * **offer_asset** is an object of type [`Asset`]. Proposed asset for swapping.
+///
+/// * **belief_price** is an object of type [`Option<Decimal>`]. Used to calculate the maximum swap spread.
+///
+/// * **max_spread** is an object of type [`Option<Decimal>`]. Sets the maximum spread of the swap operation.
+///
+/// * **to** is an object of type [`Option<Addr>`]. Sets the recipient of the swap operation.
+///
+/// * **min_return** is an object of type [`Option<Uint128>`]. This is the minimum amount of ask assets to receive.
+///
+/// * **deadline** is an object of type [`Option<u64>`]. This is the block time (in seconds) after which the swap fails.
+/// NOTE - the address that wants to swap should approve the pair contract to pull the offer token.
+#[allow(clippy::too_many_arguments)]
+pub fn swap(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    sender: Addr,
+    offer_asset: Asset,
+    belief_price: Option<Decimal>,
+    max_spread: Option<Decimal>,
+    to: Option<Addr>,
+    min_return: Option<Uint128>,
+    deadline: Option<u64>,
+) -> Result<Response, ContractError> {
+    assert_swaps_allowed(deps.storage)?;
+    assert_deadline(&env, deadline)?;
+
+    offer_asset.assert_sent_native_token_balance(&info)?;
+
+    let mut config: Config = CONFIG.load(deps.storage)?;
+
+    let pools = query_reserves(deps.storage, &config)?;
+
+    let (offer_index, ask_index) = if offer_asset.info.equal(&pools[0].info) {
+        (0, 1)
+    } else if offer_asset.info.equal(&pools[1].info) {
+        (1, 0)
+    } else {
+        return Err(ContractError::AssetMismatch {});
+    };
+    let offer_pool = pools[offer_index].clone();
+    let ask_pool = pools[ask_index].clone();
+
+    // Get the fee info cached from the factory
+    let fee_info = FACTORY_PARAMS.load(deps.storage)?.fee_info;
+
+    let offer_amount = offer_asset.amount;
+    let (return_amount, spread_amount, commission_amount) = compute_swap(
+        offer_pool.amount,
+        config.weights[offer_index],
+        ask_pool.amount,
+        config.weights[ask_index],
+        offer_amount,
+        fee_info.total_fee_rate,
+    )?;
+
+    // Check the max spread limit (if it was specified)
+    assert_max_spread(
+        belief_price,
+        max_spread,
+        offer_amount,
+        return_amount + commission_amount,
+        spread_amount,
+    )?;
+
+    // Check the minimum return amount (if it was specified)
+    if let Some(min_return) = min_return {
+        if return_amount < min_return {
+            return Err(ContractError::MinReturnAssertion {});
+        }
+    }
+
+    // Compute the tax for the receiving asset (if it is a native one)
+    let return_asset = Asset {
+        info: ask_pool.info.clone(),
+        amount: return_amount,
+    };
+
+    let tax_amount = return_asset.compute_tax(&deps.querier)?;
+    let receiver = to.unwrap_or_else(|| sender.clone());
+    let mut messages: Vec<CosmosMsg> =
+        vec![return_asset.into_msg(&deps.querier, receiver.clone())?];
+
+    // Compute the Maker fee
+    let mut maker_fee_amount = Uint128::new(0);
+    if let Some(fee_address) = fee_info.fee_address {
+        if let Some(f) = calculate_maker_fee(
+            ask_pool.info.clone(),
+            commission_amount,
+            fee_info.maker_fee_rate,
+        ) {
+            messages.push(f.clone().into_msg(&deps.querier, fee_address)?);
+            maker_fee_amount = f.amount;
+        }
+    }
+
+    // Accumulate prices for the assets in the pool
+    if let Some((price0_cumulative_new, price1_cumulative_new, block_time)) =
+        accumulate_prices(env, &config, pools[0].amount, pools[1].amount)?
+    {
+        config.price0_cumulative_last = price0_cumulative_new;
+        config.price1_cumulative_last = price1_cumulative_new;
+        config.block_time_last = block_time;
+        CONFIG.save(deps.storage, &config)?;
+    }
+
+    save_reserves_after_swap(
+        deps.storage,
+        &pools,
+        &offer_asset.info,
+        offer_amount,
+        return_amount + maker_fee_amount,
+    )?;
+
+    Ok(Response::new()
+        .add_messages(
+            // 1. send collateral tokens from the contract to a user
+            // 2. send inactive commission fees to the Maker ontract
+            messages,
+        )
+        .add_attribute("action", "swap")
+        .add_attribute("sender", sender.as_str())
+        .add_attribute("receiver", receiver.as_str())
+        .add_attribute("offer_asset", offer_asset.info.to_string())
+        .add_attribute("ask_asset", ask_pool.info.to_string())
+        .add_attribute("offer_amount", offer_amount.to_string())
+        .add_attribute("return_amount", return_amount.to_string())
+        .add_attribute("tax_amount", tax_amount.to_string())
+        .add_attribute("spread_amount", spread_amount.to_string())
+        .add_attribute("commission_amount", commission_amount.to_string())
+        .add_attribute("maker_fee_amount", maker_fee_amount.to_string()))
+}
+
+/// ## Description
+/// Performs a swap that returns exactly the specified ask asset. The offer amount is calculated
+/// the same way as in a reverse simulation and must not exceed `max_offer`. Native offer assets and
+/// token offer assets sent via [`Cw20HookMsg::SwapExactOut`] are received in advance, so the excess is
+/// sent back to the sender, otherwise only the required amount of offer tokens is pulled from the sender.
+/// Returns an [`ContractError`] on failure, otherwise returns the [`Response`] with the specified attributes if the operation was successful.
+/// ## Params
+/// * **deps** is an object of type [`DepsMut`].
+///
+/// * **env** is an object of type [`Env`].
+///
+/// * **info** is an object of type [`MessageInfo`].
+///
+/// * **sender** is an object of type [`Addr`]. This is the sender of the swap operation.
+///
+/// * **ask_asset** is an object of type [`Asset`]. This is the asset and the exact amount to receive from the swap.
+///
+/// * **max_offer** is an object of type [`Uint128`]. This is the maximum amount of offer assets to pay for the ask asset.
+///
+/// * **offer_received** is an object of type [`bool`]. Determines whether the offer tokens were already sent to the pair.
+///
+/// * **to** is an object of type [`Option<Addr>`]. Sets the recipient of the swap operation.
+///
+/// * **deadline** is an [`Option`] field of type [`u64`]. This is the block time (in seconds) after which
+/// the transaction fails.
+/// NOTE - the address that wants to swap should approve the pair contract to pull the offer token.
+#[allow(clippy::too_many_arguments)]
+pub fn swap_exact_out(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    sender: Addr,
+    ask_asset: Asset,
+    max_offer: Uint128,
+    offer_received: bool,
+    to: Option<Addr>,
+    deadline: Option<u64>,
+) -> Result<Response, ContractError> {
+    assert_swaps_allowed(deps.storage)?;
+    assert_deadline(&env, deadline)?;
+    if ask_asset.amount.is_zero() {
+        return Err(ContractError::InvalidZeroAmount {});
+    }
+
+    let mut config: Config = CONFIG.load(deps.storage)?;
+    let pools = query_reserves(deps.storage, &config)?;
+
+    let (offer_index, ask_index) = if ask_asset.info.equal(&pools[0].info) {
+        (1, 0)
+    } else if ask_asset.info.equal(&pools[1].info) {
+        (0, 1)
+    } else {
+        return Err(ContractError::AssetMismatch {});
+    };
+
+    let offer_info = pools[offer_index].info.clone();
+    let offer_received = match &offer_info {
+        AssetInfo::NativeToken { .. } => {
+            Asset {
+                info: offer_info.clone(),
+                amount: max_offer,
+            }
+            .assert_sent_native_token_balance(&info)?;
+            true
+        }
+        AssetInfo::Token { contract_addr } => {
+            // Only the offer token contract can send the offer assets in advance
+            if offer_received && contract_addr != &info.sender {
+                return Err(ContractError::Unauthorized {});
+            }
+            offer_received
+        }
+    };
+
+    let offer_pool = pools[offer_index].clone();
+    let ask_pool = pools[ask_index].clone();
+
+    // Get the fee info cached from the factory
+    let fee_info = FACTORY_PARAMS.load(deps.storage)?.fee_info;
+
+    let (offer_amount, spread_amount, commission_amount) = compute_offer_amount(
+        offer_pool.amount,
+        config.weights[offer_index],
+        ask_pool.amount,
+        config.weights[ask_index],
+        ask_asset.amount,
+        fee_info.total_fee_rate,
+    )?;
+
+    if offer_amount > max_offer {
+        return Err(ContractError::MaxOfferAssertion {});
+    }
+
+    let mut messages: Vec<CosmosMsg> = vec![];
+    if !offer_received {
+        messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: offer_info.to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::TransferFrom {
+                owner: sender.to_string(),
+                recipient: env.contract.address.to_string(),
+                amount: offer_amount,
+            })?,
+            funds: vec![],
+        }));
+    }
+
+    let tax_amount = ask_asset.compute_tax(&deps.querier)?;
+    let receiver = to.unwrap_or_else(|| sender.clone());
+    messages.push(
+        ask_asset
+            .clone()
+            .into_msg(&deps.querier, receiver.clone())?,
+    );
+
+    // Send back the offer assets that were not used in the swap
+    let refund_amount = if offer_received {
+        max_offer - offer_amount
+    } else {
+        Uint128::zero()
+    };
+    if !refund_amount.is_zero() {
+        let refund_asset = Asset {
+            info: offer_info.clone(),
+            amount: refund_amount,
+        };
+        messages.push(refund_asset.into_msg(&deps.querier, sender.clone())?);
+    }
+
+    // Compute the Maker fee
+    let mut maker_fee_amount = Uint128::new(0);
+    if let Some(fee_address) = fee_info.fee_address {
+        if let Some(f) = calculate_maker_fee(
+            ask_pool.info.clone(),
+            commission_amount,
+            fee_info.maker_fee_rate,
+        ) {
+            messages.push(f.clone().into_msg(&deps.querier, fee_address)?);
+            maker_fee_amount = f.amount;
+        }
+    }
+
+    // Accumulate prices for the assets in the pool
+    if let Some((price0_cumulative_new, price1_cumulative_new, block_time)) =
+        accumulate_prices(env, &config, pools[0].amount, pools[1].amount)?
+    {
+        config.price0_cumulative_last = price0_cumulative_new;
+        config.price1_cumulative_last = price1_cumulative_new;
+        config.block_time_last = block_time;
+        CONFIG.save(deps.storage, &config)?;
+    }
+
+    save_reserves_after_swap(
+        deps.storage,
+        &pools,
+        &offer_info,
+        offer_amount,
+        ask_asset.amount + maker_fee_amount,
+    )?;
+
+    Ok(Response::new()
+        .add_messages(
+            // 1. pull offer tokens from the sender (if they were not sent in advance)
+            // 2. send ask assets from the contract to a user
+            // 3. send back the unused offer assets
+            // 4. send inactive commission fees to the Maker ontract
+            messages,
+        )
+        .add_attribute("action", "swap")
+        .add_attribute("sender", sender.as_str())
+        .add_attribute("receiver", receiver.as_str())
+        .add_attribute("offer_asset", offer_info.to_string())
+        .add_attribute("ask_asset", ask_pool.info.to_string())
+        .add_attribute("offer_amount", offer_amount.to_string())
+        .add_attribute("return_amount", ask_asset.amount.to_string())
+        .add_attribute("refund_amount", refund_amount.to_string())
+        .add_attribute("tax_amount", tax_amount.to_string())
+        .add_attribute("spread_amount", spread_amount.to_string())
+        .add_attribute("commission_amount", commission_amount.to_string())
+        .add_attribute("maker_fee_amount", maker_fee_amount.to_string()))
+}
+
+/// ## Description
+/// Sets the tracked pool reserves to the current balances of the pair, so that assets sent to the pair
+/// directly are added to the pool. Cumulative prices are accumulated using the reserves before the sync.
+/// Returns a [`ContractError`] on failure, otherwise returns a [`Response`] with the specified
+/// attributes if the operation was successful.
+/// ## Params
+/// * **deps** is an object of type [`DepsMut`].
+///
+/// * **env** is an object of type [`Env`].
+pub fn sync(deps: DepsMut, env: Env) -> Result<Response, ContractError> {
+    let mut config: Config = CONFIG.load(deps.storage)?;
+    let reserves = query_reserves(deps.storage, &config)?;
+
+    // Accumulate prices for the assets in the pool
+    if let Some((price0_cumulative_new, price1_cumulative_new, block_time)) =
+        accumulate_prices(env.clone(), &config, reserves[0].amount, reserves[1].amount)?
+    {
+        config.price0_cumulative_last = price0_cumulative_new;
+        config.price1_cumulative_last = price1_cumulative_new;
+        config.block_time_last = block_time;
+        CONFIG.save(deps.storage, &config)?;
+    }
+
+    let pools = config
+        .pair_info
+        .query_pools(&deps.querier, env.contract.address)?;
+    RESERVES.save(deps.storage, &[pools[0].amount, pools[1].amount])?;
+
+    Ok(Response::new()
+        .add_attribute("action", "sync")
+        .add_attribute("reserves", format!("{}, {}", pools[0], pools[1])))
+}
+
+/// ## Description
+/// Sends the balances of the pair that exceed the tracked pool reserves to a recipient.
+/// Returns a [`ContractError`] on failure, otherwise returns a [`Response`] with the specified
+/// attributes if the operation was successful.
+/// ## Params
+/// * **deps** is an object of type [`DepsMut`].
+///
+/// * **env** is an object of type [`Env`].
+///
+/// * **info** is an object of type [`MessageInfo`].
+///
+/// * **to** is an [`Option`] field of type [`String`]. This is the recipient of the excess assets.
+/// If no custom recipient is specified, the assets are sent to the function caller.
+pub fn skim(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    to: Option<String>,
+) -> Result<Response, ContractError> {
+    let config: Config = CONFIG.load(deps.storage)?;
+    let reserves = query_reserves(deps.storage, &config)?;
+    let pools = config
+        .pair_info
+        .query_pools(&deps.querier, env.contract.address)?;
+
+    let to = addr_validate_to_lower(deps.api, &to.unwrap_or_else(|| info.sender.to_string()))?;
+
+    let mut messages: Vec<CosmosMsg> = vec![];
+    let mut excess_assets: Vec<Asset> = vec![];
+    for (pool, reserve) in pools.iter().zip(reserves.iter()) {
+        let excess = Asset {
+            info: pool.info.clone(),
+            amount: pool.amount.saturating_sub(reserve.amount),
+        };
+        if !excess.amount.is_zero() {
+            messages.push(excess.clone().into_msg(&deps.querier, to.clone())?);
+        }
+        excess_assets.push(excess);
+    }
+
+    Ok(Response::new()
+        .add_messages(messages)
+        .add_attribute("action", "skim")
+        .add_attribute("receiver", to.as_str())
+        .add_attribute(
+            "assets",
+            format!("{}, {}", excess_assets[0], excess_assets[1]),
+        ))
+}
+
+/// ## Description
+/// Accumulate token prices for the assets in the pool. The prices are the weighted spot prices of the assets.
+/// Note that this function shifts **block_time** when any of the token prices is zero in order to not
+/// fill an accumulator with a null price for that period.
+/// ## Params
+/// * **env** is an object of type [`Env`].
+///
+/// * **config** is an object of type [`Config`].
+///
+/// * **x** is an object of type [`Uint128`]. This is the balance of asset\[\0] in the pool.
+///
+/// * **y** is an object of type [`Uint128`]. This is the balance of asset\[\1] in the pool.
+pub fn accumulate_prices(
+    env: Env,
+    config: &Config,
+    x: Uint128,
+    y: Uint128,
+) -> StdResult<Option<(Uint128, Uint128, u64)>> {
+    let block_time = env.block.time.seconds();
+    if block_time <= config.block_time_last {
+        return Ok(None);
+    }
+
+    // We have to shift block_time when any price is zero in order to not fill an accumulator with a null price for that period
+    let time_elapsed = Uint128::from(block_time - config.block_time_last);
+
+    let mut pcl0 = config.price0_cumulative_last;
+    let mut pcl1 = config.price1_cumulative_last;
+
+    // Scale the pool balances by the asset weights: price0 = (y / w1) / (x / w0)
+    let x_weighted = x * config.weights[1];
+    let y_weighted = y * config.weights[0];
+
+    if !x_weighted.is_zero() && !y_weighted.is_zero() {
+        let price_precision = Uint128::from(10u128.pow(TWAP_PRECISION.into()));
+        pcl0 = config.price0_cumulative_last.wrapping_add(
+            time_elapsed
+                .checked_mul(price_precision)?
+                .multiply_ratio(y_weighted, x_weighted),
+        );
+        pcl1 = config.price1_cumulative_last.wrapping_add(
+            time_elapsed
+                .checked_mul(price_precision)?
+                .multiply_ratio(x_weighted, y_weighted),
+        );
+    };
+
+    Ok(Some((pcl0, pcl1, block_time)))
+}
+
+/// ## Description
+/// Calculates the amount of fees the Maker contract gets according to specified pair parameters.
+/// Returns a [`None`] if the Maker fee is zero, otherwise returns a [`Asset`] struct with the specified attributes.
+/// ## Params
+/// * **pool_info** is an object of type [`AssetInfo`]. Contains information about the pool asset for which the commission will be calculated.
+///
+/// * **commission_amount** is an object of type [`Env`]. This is the total amount of fees charged for a swap.
+///
+/// * **maker_commission_rate** is an object of type [`MessageInfo`]. This is the percentage of fees that go to the Maker contract.
+pub fn calculate_maker_fee(
+    pool_info: AssetInfo,
+    commission_amount: Uint128,
+    maker_commission_rate: Decimal,
+) -> Option<Asset> {
+    let maker_fee: Uint128 = commission_amount * maker_commission_rate;
+    if maker_fee.is_zero() {
+        return None;
+    }
+
+    Some(Asset {
+        info: pool_info,
+        amount: maker_fee,
+    })
+}
+
+/// ## Description
+/// Exposes all the queries available in the contract.
+/// ## Params
+/// * **deps** is an object of type [`Deps`].
+///
+/// * **_env** is an object of type [`Env`].
+///
+/// * **msg** is an object of type [`QueryMsg`].
+///
+/// ## Queries
+/// * **QueryMsg::Pair {}** Returns information about the pair in an object of type [`PairInfo`].
+///
+/// * **QueryMsg::Pool {}** Returns information about the amount of assets in the pair contract as
+/// well as the amount of LP tokens issued using an object of type [`PoolResponse`].
+///
+/// * **QueryMsg::Share { amount }** Returns the amount of assets that could be withdrawn from the pool
+/// using a specific amount of LP tokens. The result is returned in a vector that contains objects of type [`Asset`].
+///
+/// * **QueryMsg::Simulation { offer_asset }** Returns the result of a swap simulation using a [`SimulationResponse`] object.
+///
+/// * **QueryMsg::ReverseSimulation { ask_asset }** Returns the result of a reverse swap simulation  using
+/// a [`ReverseSimulationResponse`] object.
+///
+/// * **QueryMsg::CumulativePrices {}** Returns information about cumulative prices for the assets in the
+/// pool using a [`CumulativePricesResponse`] object.
+///
+/// * **QueryMsg::Config {}** Returns the configuration for the pair contract using a [`ConfigResponse`] object.
+///
+/// * **QueryMsg::ProtocolFees {}** Not supported.
+///
+/// * **QueryMsg::Observe { .. }** Not supported.
+///
+/// * **QueryMsg::SpotPrice { .. }**, **QueryMsg::PriceImpact { .. }** and **QueryMsg::Depth { .. }** Not supported.
+///
+/// * **QueryMsg::SimulateProvide { assets, slippage_tolerance }** Returns the amount of LP tokens minted
+/// for a deposit using a [`SimulateProvideResponse`] object.
+///
+/// * **QueryMsg::SimulateWithdraw { lp_amount }** Returns the amounts of assets received for burning
+/// LP tokens in a [`Vec<Asset>`] object.
+///
+/// * **QueryMsg::SimulateWithdrawOneCoin { .. }** and **QueryMsg::SimulateWithdrawImbalanced { .. }** Not supported.
+///
+/// * **QueryMsg::VirtualPrice {}**, **QueryMsg::CurrentD {}** and **QueryMsg::AmpHistory { .. }** Not supported.
+///
+/// * **QueryMsg::PairStatus {}** Returns the trading state of the pair set in the factory using a [`astroport::factory::PairStatus`] object.
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
+    match msg {
+        QueryMsg::Pair {} => to_binary(&query_pair_info(deps)?),
+        QueryMsg::Pool {} => to_binary(&query_pool(deps)?),
+        QueryMsg::Share { amount } => to_binary(&query_share(deps, amount)?),
+        QueryMsg::Simulation { offer_asset } => to_binary(&query_simulation(deps, offer_asset)?),
+        QueryMsg::ReverseSimulation { ask_asset } => {
+            to_binary(&query_reverse_simulation(deps, ask_asset)?)
+        }
+        QueryMsg::CumulativePrices {} => to_binary(&query_cumulative_prices(deps, env)?),
+        QueryMsg::Config {} => to_binary(&query_config(deps)?),
+        QueryMsg::ProtocolFees {} => Err(StdError::generic_err("Operation non supported")),
+        QueryMsg::Observe { .. }
+        | QueryMsg::SpotPrice { .. }
+        | QueryMsg::PriceImpact { .. }
+        | QueryMsg::Depth { .. } => Err(StdError::generic_err("Operation non supported")),
+        QueryMsg::SimulateProvide {
+            assets,
+            slippage_tolerance,
+        } => to_binary(&query_simulate_provide(deps, assets, slippage_tolerance)?),
+        QueryMsg::SimulateWithdraw { lp_amount } => {
+            to_binary(&query_simulate_withdraw(deps, lp_amount)?)
+        }
+        QueryMsg::SimulateWithdrawOneCoin { .. }
+        | QueryMsg::SimulateWithdrawImbalanced { .. }
+        | QueryMsg::VirtualPrice {}
+        | QueryMsg::CurrentD {}
+        | QueryMsg::AmpHistory { .. } => Err(StdError::generic_err("Operation non supported")),
+        QueryMsg::PairStatus {} => to_binary(&FACTORY_PARAMS.load(deps.storage)?.pair_status),
+    }
+}
+
+/// ## Description
+/// Returns information about the pair contract in an object of type [`PairInfo`].
+/// ## Params
+/// * **deps** is an object of type [`Deps`].
+pub fn query_pair_info(deps: Deps) -> StdResult<PairInfo> {
+    let config: Config = CONFIG.load(deps.storage)?;
+    Ok(config.pair_info)
+}
+
+/// ## Description
+/// Returns the amounts of assets in the pair contract as well as the amount of LP
+/// tokens currently minted in an object of type [`PoolResponse`].
+/// ## Params
+/// * **deps** is an object of type [`Deps`].
+pub fn query_pool(deps: Deps) -> StdResult<PoolResponse> {
+    let config: Config = CONFIG.load(deps.storage)?;
+    let (assets, total_share) = pool_info(deps, config)?;
+
+    let resp = PoolResponse {
+        assets,
+        total_share,
+    };
+
+    Ok(resp)
+}
+
+/// ## Description
+/// Returns the amount of assets that could be withdrawn from the pool using a specific amount of LP tokens.
+/// The result is returned in a vector that contains objects of type [`Asset`].
+/// ## Params
+/// * **deps** is an object of type [`Deps`].
+///
+/// * **amount** is an object of type [`Uint128`]. This is the amount of LP tokens for which we calculate associated amounts of assets.
+pub fn query_share(deps: Deps, amount: Uint128) -> StdResult<Vec<Asset>> {
+    let config: Config = CONFIG.load(deps.storage)?;
+    let (pools, total_share) = pool_info(deps, config)?;
+    let refund_assets = get_share_in_assets(&pools, amount, total_share);
+
+    Ok(refund_assets)
+}
+
+/// ## Description
+/// Returns information about a swap simulation in a [`SimulationResponse`] object.
+/// ## Params
+/// * **deps** is an object of type [`Deps`].
+///
+/// * **offer_asset** is an object of type [`Asset`]. This is the asset to swap as well as an amount of the said asset.
+pub fn query_simulation(deps: Deps, offer_asset: Asset) -> StdResult<SimulationResponse> {
+    let config: Config = CONFIG.load(deps.storage)?;
+    let pools = query_reserves(deps.storage, &config)?;
+
+    let (offer_index, ask_index) = if offer_asset.info.equal(&pools[0].info) {
+        (0, 1)
+    } else if offer_asset.info.equal(&pools[1].info) {
+        (1, 0)
+    } else {
+        return Err(StdError::generic_err(
+            "Given offer asset does not belong in the pair",
+        ));
+    };
+
+    // Get the fee info cached from the factory
+    let fee_info = FACTORY_PARAMS.load(deps.storage)?.fee_info;
+
+    let (return_amount, spread_amount, commission_amount) = compute_swap(
+        pools[offer_index].amount,
+        config.weights[offer_index],
+        pools[ask_index].amount,
+        config.weights[ask_index],
+        offer_asset.amount,
+        fee_info.total_fee_rate,
+    )
+    .map_err(|e| StdError::generic_err(e.to_string()))?;
+
+    Ok(SimulationResponse {
+        return_amount,
+        spread_amount,
+        commission_amount,
+    })
+}
+
+/// ## Description
+/// Returns information about a reverse swap simulation in a [`ReverseSimulationResponse`] object.
+/// ## Params
+/// * **deps** is an object of type [`Deps`].
+///
+/// * **ask_asset** is an object of type [`Asset`]. This is the asset to swap to as well as the desired
+/// amount of ask assets to receive from the swap.
+pub fn query_reverse_simulation(
+    deps: Deps,
+    ask_asset: Asset,
+) -> StdResult<ReverseSimulationResponse> {
+    let config: Config = CONFIG.load(deps.storage)?;
+    let pools = query_reserves(deps.storage, &config)?;
+
+    let (offer_index, ask_index) = if ask_asset.info.equal(&pools[0].info) {
+        (1, 0)
+    } else if ask_asset.info.equal(&pools[1].info) {
+        (0, 1)
+    } else {
+        return Err(StdError::generic_err(
+            "Given ask asset doesn't belong to pairs",
+        ));
+    };
+
+    // Get the fee info cached from the factory
+    let fee_info = FACTORY_PARAMS.load(deps.storage)?.fee_info;
+
+    let (offer_amount, spread_amount, commission_amount) = compute_offer_amount(
+        pools[offer_index].amount,
+        config.weights[offer_index],
+        pools[ask_index].amount,
+        config.weights[ask_index],
+        ask_asset.amount,
+        fee_info.total_fee_rate,
+    )
+    .map_err(|e| StdError::generic_err(e.to_string()))?;
+
+    Ok(ReverseSimulationResponse {
+        offer_amount,
+        spread_amount,
+        commission_amount,
+    })
+}
+
+/// ## Description
+/// Returns information about cumulative prices for the assets in the pool using a [`CumulativePricesResponse`] object.
+/// ## Params
+/// * **deps** is an object of type [`Deps`].
+///
+/// * **env** is an object of type [`Env`].
+pub fn query_cumulative_prices(deps: Deps, env: Env) -> StdResult<CumulativePricesResponse> {
+    let config: Config = CONFIG.load(deps.storage)?;
+    let (assets, total_share) = pool_info(deps, config.clone())?;
+
+    let mut price0_cumulative_last = config.price0_cumulative_last;
+    let mut price1_cumulative_last = config.price1_cumulative_last;
+
+    if let Some((price0_cumulative_new, price1_cumulative_new, _)) =
+        accumulate_prices(env, &config, assets[0].amount, assets[1].amount)?
+    {
+        price0_cumulative_last = price0_cumulative_new;
+        price1_cumulative_last = price1_cumulative_new;
+    }
+
+    let resp = CumulativePricesResponse {
+        assets,
+        total_share,
+        price0_cumulative_last,
+        price1_cumulative_last,
+        price0_cumulative_last_u256: None,
+        price1_cumulative_last_u256: None,
+    };
+
+    Ok(resp)
+}
+
+/// ## Description
+/// Returns the pair contract configuration in a [`ConfigResponse`] object.
+/// The asset weights are returned in a [`WeightedPoolConfig`] object serialized in `params`.
+/// ## Params
+/// * **deps** is an object of type [`Deps`].
+pub fn query_config(deps: Deps) -> StdResult<ConfigResponse> {
+    let config: Config = CONFIG.load(deps.storage)?;
+    Ok(ConfigResponse {
+        block_time_last: config.block_time_last,
+        params: Some(to_binary(&WeightedPoolConfig {
+            weights: config.weights,
+        })?),
+    })
+}
+
+/// ## Description
+/// Returns the result of a swap.
+/// ## Params
+/// * **offer_pool** is an object of type [`Uint128`]. This is the total amount of offer assets in the pool.
+///
+/// * **offer_weight** is an object of type [`Decimal`]. This is the normalized weight of the offer asset.
+///
+/// * **ask_pool** is an object of type [`Uint128`]. This is the total amount of ask assets in the pool.
+///
+/// * **ask_weight** is an object of type [`Decimal`]. This is the normalized weight of the ask asset.
+///
+/// * **offer_amount** is an object of type [`Uint128`]. This is the amount of offer assets to swap.
+///
+/// * **commission_rate** is an object of type [`Decimal`]. This is the total amount of fees charged for the swap.
+pub fn compute_swap(
+    offer_pool: Uint128,
+    offer_weight: Decimal,
+    ask_pool: Uint128,
+    ask_weight: Decimal,
+    offer_amount: Uint128,
+    commission_rate: Decimal,
+) -> Result<(Uint128, Uint128, Uint128), ContractError> {
+    if offer_amount > offer_pool.multiply_ratio(MAX_IN_RATIO, 100u64) {
+        return Err(ContractError::MaxInRatioAssertion {});
+    }
+
+    let return_amount = Uint128::new(
+        calc_ask_amount(
+            offer_pool.u128(),
+            to_fixed(offer_weight),
+            ask_pool.u128(),
+            to_fixed(ask_weight),
+            offer_amount.u128(),
+        )
+        .ok_or(ContractError::MathOverflow {})?,
+    );
+
+    // Calculate spread & commission
+    let spot_price = calc_spot_price(
+        offer_pool.u128(),
+        to_fixed(offer_weight),
+        ask_pool.u128(),
+        to_fixed(ask_weight),
+    )
+    .ok_or(ContractError::MathOverflow {})?;
+    let spread_amount = offer_amount
+        .multiply_ratio(spot_price, ONE)
+        .saturating_sub(return_amount);
+    let commission_amount: Uint128 = return_amount * commission_rate;
+
+    // The commision (minus the part that goes to the Maker contract) will be absorbed by the pool
+    let return_amount: Uint128 = return_amount.checked_sub(commission_amount)?;
+    Ok((return_amount, spread_amount, commission_amount))
+}
+
+/// ## Description
+/// Returns an amount of offer assets for a specified amount of ask assets.
+/// ## Params
+/// * **offer_pool** is an object of type [`Uint128`]. This is the total amount of offer assets in the pool.
+///
+/// * **offer_weight** is an object of type [`Decimal`]. This is the normalized weight of the offer asset.
+///
+/// * **ask_pool** is an object of type [`Uint128`]. This is the total amount of ask assets in the pool.
+///
+/// * **ask_weight** is an object of type [`Decimal`]. This is the normalized weight of the ask asset.
+///
+/// * **ask_amount** is an object of type [`Uint128`]. This is the amount of ask assets to swap to.
+///
+/// * **commission_rate** is an object of type [`Decimal`]. This is the total amount of fees charged for the swap.
+fn compute_offer_amount(
+    offer_pool: Uint128,
+    offer_weight: Decimal,
+    ask_pool: Uint128,
+    ask_weight: Decimal,
+    ask_amount: Uint128,
+    commission_rate: Decimal,
+) -> Result<(Uint128, Uint128, Uint128), ContractError> {
+    let one_minus_commission = ONE
+        .checked_sub(to_fixed(commission_rate))
+        .filter(|v| *v != 0)
+        .ok_or(ContractError::MathOverflow {})?;
+    let before_commission_deduction = ask_amount.multiply_ratio(ONE, one_minus_commission);
+
+    if before_commission_deduction > ask_pool.multiply_ratio(MAX_OUT_RATIO, 100u64) {
+        return Err(ContractError::MaxOutRatioAssertion {});
+    }
+
+    let offer_amount = Uint128::new(
+        calc_offer_amount(
+            offer_pool.u128(),
+            to_fixed(offer_weight),
+            ask_pool.u128(),
+            to_fixed(ask_weight),
+            before_commission_deduction.u128(),
+        )
+        .ok_or(ContractError::MathOverflow {})?,
+    );
+
+    let spot_price = calc_spot_price(
+        offer_pool.u128(),
+        to_fixed(offer_weight),
+        ask_pool.u128(),
+        to_fixed(ask_weight),
+    )
+    .ok_or(ContractError::MathOverflow {})?;
+    let spread_amount = offer_amount
+        .multiply_ratio(spot_price, ONE)
+        .saturating_sub(before_commission_deduction);
+    let commission_amount = before_commission_deduction * commission_rate;
+    Ok((offer_amount, spread_amount, commission_amount))
+}
+
+/// ## Description
+/// Converts a [`Decimal`] into a fixed point number with 18 decimals used in the weighted math.
+fn to_fixed(value: Decimal) -> u128 {
+    (Uint128::new(ONE) * value).u128()
+}
+
+/// ## Description
+/// Returns a [`ContractError`] if the factory paused swaps in the pair.
+/// ## Params
+/// * **storage** is a reference to an object implementing [`Storage`].
+fn assert_swaps_allowed(storage: &dyn Storage) -> Result<(), ContractError> {
+    if !FACTORY_PARAMS.load(storage)?.pair_status.swaps_allowed() {
+        return Err(ContractError::SwapsPaused {});
+    }
+
+    Ok(())
+}
+
+/// ## Description
+/// Returns a [`ContractError`] if the factory paused deposits in the pair.
+/// ## Params
+/// * **storage** is a reference to an object implementing [`Storage`].
+fn assert_deposits_allowed(storage: &dyn Storage) -> Result<(), ContractError> {
+    if !FACTORY_PARAMS.load(storage)?.pair_status.deposits_allowed() {
+        return Err(ContractError::DepositsPaused {});
+    }
+
+    Ok(())
+}
+
+/// ## Description
+/// Returns a [`ContractError`] on failure.
+/// If `belief_price` and `max_spread` are both specified, we compute a new spread,
+/// otherwise we just use the swap spread to check `max_spread`.
+/// ## Params
+/// * **belief_price** is an object of type [`Option<Decimal>`]. This is the belief price used in the swap.
+///
+/// * **max_spread** is an object of type [`Option<Decimal>`]. This is the
+/// max spread allowed so that the swap can be executed successfuly.
+///
+/// * **offer_amount** is an object of type [`Uint128`]. This is the amount of assets to swap.
+///
+/// * **return_amount** is an object of type [`Uint128`]. This is the amount of assets to receive from the swap.
+///
+/// * **spread_amount** is an object of type [`Uint128`]. This is the spread used in the swap.
+pub fn assert_max_spread(
+    belief_price: Option<Decimal>,
+    max_spread: Option<Decimal>,
+    offer_amount: Uint128,
+    return_amount: Uint128,
+    spread_amount: Uint128,
+) -> Result<(), ContractError> {
+    let default_spread = Decimal::from_str(DEFAULT_SLIPPAGE)?;
+    let max_allowed_spread = Decimal::from_str(MAX_ALLOWED_SLIPPAGE)?;
+
+    let max_spread = max_spread.unwrap_or(default_spread);
+    if max_spread.gt(&max_allowed_spread) {
+        return Err(ContractError::AllowedSpreadAssertion {});
+    }
+
+    if let Some(belief_price) = belief_price {
+        let expected_return =
+            offer_amount * Decimal::from(Decimal256::one() / Decimal256::from(belief_price));
+        let spread_amount = expected_return
+            .checked_sub(return_amount)
+            .unwrap_or_else(|_| Uint128::zero());
+
+        if return_amount < expected_return
+            && Decimal::from_ratio(spread_amount, expected_return) > max_spread
+        {
+            return Err(ContractError::MaxSpreadAssertion {});
+        }
+    } else if Decimal::from_ratio(spread_amount, return_amount + spread_amount) > max_spread {
+        return Err(ContractError::MaxSpreadAssertion {});
+    }
+
+    Ok(())
+}
+
+/// ## Description
+/// Returns a [`ContractError`] on failure if the current block time is past the specified deadline.
+/// ## Params
+/// * **env** is an object of type [`Env`].
+///
+/// * **deadline** is an [`Option`] field of type [`u64`]. This is the block time (in seconds) after which
+/// the operation fails.
+pub fn assert_deadline(env: &Env, deadline: Option<u64>) -> Result<(), ContractError> {
+    if let Some(deadline) = deadline {
+        if env.block.time.seconds() > deadline {
+            return Err(ContractError::DeadlineExpired {});
+        }
+    }
+
+    Ok(())
+}
+
+/// ## Description
+/// Returns a [`ContractError`] on failure if the amount of any withdrawn asset is less than the specified minimum.
+/// ## Params
+/// * **refund_assets** are an array of [`Asset`] type items. These are the assets withdrawn from the pool.
+///
+/// * **min_assets_out** is an [`Option`] field of type [`Vec<Asset>`]. These are the minimum amounts of
+/// assets to receive.
+fn assert_min_assets_out(
+    refund_assets: &[Asset],
+    min_assets_out: Option<Vec<Asset>>,
+) -> Result<(), ContractError> {
+    for min_asset in min_assets_out.unwrap_or_default() {
+        let refund_asset = refund_assets
+            .iter()
+            .find(|a| a.info.equal(&min_asset.info))
+            .ok_or(ContractError::AssetMismatch {})?;
+        if refund_asset.amount < min_asset.amount {
+            return Err(ContractError::MinAssetsOutAssertion {});
+        }
+    }
+
+    Ok(())
+}
+
+/// ## Description
+/// This is an internal function that enforces slippage tolerance for swaps.
+/// Returns a [`ContractError`] on failure, otherwise returns [`Ok`].
+/// ## Params
+/// * **slippage_tolerance** is an object of type [`Option<Decimal>`]. This is the slippage tolerance to enforce.
+///
+/// * **deposits** are an array of [`Uint128`] type items. These are offer and ask amounts for a swap.
+///
+/// * **pools** are an array of [`Asset`] type items. These are total amounts of assets in the pool.
+fn assert_slippage_tolerance(
+    slippage_tolerance: Option<Decimal>,
+    deposits: &[Uint128; 2],
+    pools: &[Asset; 2],
+) -> Result<(), ContractError> {
+    let default_slippage = Decimal::from_str(DEFAULT_SLIPPAGE)?;
+    let max_allowed_slippage = Decimal::from_str(MAX_ALLOWED_SLIPPAGE)?;
+
+    let slippage_tolerance = slippage_tolerance.unwrap_or(default_slippage);
+    if slippage_tolerance.gt(&max_allowed_slippage) {
+        return Err(ContractError::AllowedSpreadAssertion {});
+    }
+
+    let slippage_tolerance: Decimal256 = slippage_tolerance.into();
+    let one_minus_slippage_tolerance = Decimal256::one() - slippage_tolerance;
+    let deposits: [Uint256; 2] = [deposits[0].into(), deposits[1].into()];
+    let pools: [Uint256; 2] = [pools[0].amount.into(), pools[1].amount.into()];
+
+    // Ensure each price does not change more than what the slippage tolerance allows
+    if Decimal256::from_ratio(deposits[0], deposits[1]) * one_minus_slippage_tolerance
+        > Decimal256::from_ratio(pools[0], pools[1])
+        || Decimal256::from_ratio(deposits[1], deposits[0]) * one_minus_slippage_tolerance
+            > Decimal256::from_ratio(pools[1], pools[0])
+    {
+        return Err(ContractError::MaxSlippageAssertion {});
+    }
+
+    Ok(())
+}
+
+/// ## Description
+/// Updates the fee and Generator information that the pair caches from the factory.
+/// The factory pushes this message to its pairs when their parameters change, but anyone can execute it.
+/// ## Params
+/// * **deps** is an object of type [`DepsMut`].
+///
+/// * **env** is an object of type [`Env`].
+pub fn refresh_factory_params(deps: DepsMut, env: Env) -> Result<Response, ContractError> {
+    let config: Config = CONFIG.load(deps.storage)?;
+    let factory_params = query_factory_params(
+        &deps.querier,
+        config.factory_addr,
+        env.contract.address,
+        config.pair_info.pair_type,
+    )?;
+    FACTORY_PARAMS.save(deps.storage, &factory_params)?;
+
+    let fee_address = factory_params
+        .fee_info
+        .fee_address
+        .map(|addr| addr.to_string())
+        .unwrap_or_default();
+    let generator_address = factory_params
+        .generator_address
+        .map(|addr| addr.to_string())
+        .unwrap_or_default();
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "refresh_factory_params"),
+        attr(
+            "total_fee_rate",
+            factory_params.fee_info.total_fee_rate.to_string(),
+        ),
+        attr(
+            "maker_fee_rate",
+            factory_params.fee_info.maker_fee_rate.to_string(),
+        ),
+        attr("fee_address", fee_address),
+        attr("generator_address", generator_address),
+        attr("pair_status", factory_params.pair_status.to_string()),
+    ]))
+}
+
+/// ## Description
+/// Used for the contract migration. Returns a default object of type [`Response`].
+/// ## Params
+/// * **_deps** is an object of type [`DepsMut`].
+///
+/// * **_env** is an object of type [`Env`].
+///
+/// * **_msg** is an object of type [`MigrateMsg`].
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn migrate(_deps: DepsMut, _env: Env, _msg: MigrateMsg) -> StdResult<Response> {
+    Ok(Response::default())
+}
+
+/// ## Description
+/// Returns the amount of LP tokens minted for a deposit in an object of type [`SimulateProvideResponse`].
+/// The share is calculated in the same way as in [`provide_liquidity`], so the returned fees are always zero.
+/// ## Params
+/// * **deps** is an object of type [`Deps`].
+///
+/// * **assets** is an array with two objects of type [`Asset`]. These are the assets to deposit.
+///
+/// * **slippage_tolerance** is an [`Option`] field of type [`Decimal`]. It is used to specify how much
+/// the pool price can move until the provide liquidity transaction goes through.
+pub fn query_simulate_provide(
+    deps: Deps,
+    assets: [Asset; 2],
+    slippage_tolerance: Option<Decimal>,
+) -> StdResult<SimulateProvideResponse> {
+    let config: Config = CONFIG.load(deps.storage)?;
+    let (pools, total_share) = pool_info(deps, config)?;
+    let deposits = get_deposits(&assets, &pools)?;
+
+    let share = calculate_share(&deposits, &pools, total_share, slippage_tolerance)?;
+
+    Ok(SimulateProvideResponse {
+        share,
+        fees: pools
+            .iter()
+            .map(|pool| Asset {
+                info: pool.info.clone(),
+                amount: Uint128::zero(),
+            })
+            .collect(),
+    })
+}
+
+/// ## Description
+/// Returns the amounts of assets received for burning LP tokens in a [`Vec<Asset>`] object.
+/// The amounts are calculated in the same way as in [`withdraw_liquidity`].
+/// ## Params
+/// * **deps** is an object of type [`Deps`].
+///
+/// * **lp_amount** is an object of type [`Uint128`]. This is the amount of LP tokens to burn.
+pub fn query_simulate_withdraw(deps: Deps, lp_amount: Uint128) -> StdResult<Vec<Asset>> {
+    let config: Config = CONFIG.load(deps.storage)?;
+    let (pools, total_share) = pool_info(deps, config)?;
+
+    Ok(get_share_in_assets(&pools, lp_amount, total_share))
+}
+
+/// ## Description
+/// Returns the tracked reserves of the pool assets in the order of the pair asset infos.
+/// ## Params
+/// * **storage** is an object of type [`Storage`].
+///
+/// * **config** is an object of type [`Config`].
+pub fn query_reserves(storage: &dyn Storage, config: &Config) -> StdResult<[Asset; 2]> {
+    let reserves = RESERVES.load(storage)?;
+
+    Ok([
+        Asset {
+            info: config.pair_info.asset_infos[0].clone(),
+            amount: reserves[0],
+        },
+        Asset {
+            info: config.pair_info.asset_infos[1].clone(),
+            amount: reserves[1],
+        },
+    ])
+}
+
+/// ## Description
+/// Updates the tracked pool reserves after a swap: the offer amount is added to the offer pool and
+/// the amount that left the pool is subtracted from the ask pool.
+/// ## Params
+/// * **storage** is an object of type [`Storage`].
+///
+/// * **pools** are the tracked reserves before the swap.
+///
+/// * **offer_info** is an object of type [`AssetInfo`]. This is the offer asset of the swap.
+///
+/// * **offer_amount** is an object of type [`Uint128`]. This is the amount of offer assets added to the pool.
+///
+/// * **ask_amount** is an object of type [`Uint128`]. This is the amount of ask assets that left the pool,
+/// including the Maker fee.
+fn save_reserves_after_swap(
+    storage: &mut dyn Storage,
+    pools: &[Asset; 2],
+    offer_info: &AssetInfo,
+    offer_amount: Uint128,
+    ask_amount: Uint128,
+) -> StdResult<()> {
+    let mut reserves = [pools[0].amount, pools[1].amount];
+    for (reserve, pool) in reserves.iter_mut().zip(pools.iter()) {
+        if pool.info.equal(offer_info) {
+            *reserve = reserve.checked_add(offer_amount)?;
+        } else {
+            *reserve = reserve.checked_sub(ask_amount)?;
+        }
+    }
+
+    RESERVES.save(storage, &reserves)
+}
+
+/// ## Description
+/// Returns the total amount of assets in the pool as well as the total amount of LP tokens currently minted.
+/// ## Params
+/// * **deps** is an object of type [`Deps`].
+///
+/// * **config** is an object of type [`Config`].
+pub fn pool_info(deps: Deps, config: Config) -> StdResult<([Asset; 2], Uint128)> {
+    let pools = query_reserves(deps.storage, &config)?;
+    let total_share: Uint128 = query_supply(&deps.querier, config.pair_info.liquidity_token)?;
+
+    Ok((pools, total_share))
+}
@@ -0,0 +1,104 @@
+use astroport::pair::MINIMUM_LIQUIDITY;
+use astroport::pair_weighted::MIN_WEIGHT;
+use cosmwasm_std::{OverflowError, StdError};
+use thiserror::Error;
+
+use astroport::weighted_math::{MAX_IN_RATIO, MAX_OUT_RATIO};
+
+/// ## Description
+/// This enum describes weighted pair contract errors!
+#[derive(Error, Debug, PartialEq)]
+pub enum ContractError {
+    #[error("{0}")]
+    Std(#[from] StdError),
+
+    #[error("Unauthorized")]
+    Unauthorized {},
+
+    #[error("Operation non supported")]
+    NonSupported {},
+
+    #[error("Event of zero transfer")]
+    InvalidZeroAmount {},
+
+    #[error("Operation exceeds max spread limit")]
+    MaxSpreadAssertion {},
+
+    #[error("Provided spread amount exceeds allowed limit")]
+    AllowedSpreadAssertion {},
+
+    #[error("Operation exceeds max splippage tolerance")]
+    MaxSlippageAssertion {},
+
+    #[error("Doubling assets in asset infos")]
+    DoublingAssets {},
+
+    #[error("Asset mismatch between the requested and the stored asset in contract")]
+    AssetMismatch {},
+
+    #[error("Generator address is not set in factory. Cannot auto-stake")]
+    AutoStakeError {},
+
+    #[error("You need to provide init params")]
+    InitParamsNotFound {},
+
+    #[error(
+        "Weights must sum up to 1 and each weight must be at least {}%",
+        MIN_WEIGHT
+    )]
+    InvalidWeights {},
+
+    #[error("The offer amount cannot exceed {}% of the offer pool", MAX_IN_RATIO)]
+    MaxInRatioAssertion {},
+
+    #[error("The ask amount cannot exceed {}% of the ask pool", MAX_OUT_RATIO)]
+    MaxOutRatioAssertion {},
+
+    #[error("Single-sided liquidity cannot be provided to or withdrawn from an empty pool")]
+    SingleSidedEmptyPool {},
+
+    #[error("Minted LP amount is less than min_lp_to_receive")]
+    MinLpToReceiveAssertion {},
+
+    #[error("Withdrawn amount is less than min_ask_amount")]
+    MinAskAmountAssertion {},
+
+    #[error("Offer amount exceeds max_offer")]
+    MaxOfferAssertion {},
+
+    #[error("Weighted math overflow")]
+    MathOverflow {},
+
+    #[error("Transaction deadline has expired")]
+    DeadlineExpired {},
+
+    #[error("Return amount is less than min_return")]
+    MinReturnAssertion {},
+
+    #[error("Withdrawn amount is less than min_assets_out")]
+    MinAssetsOutAssertion {},
+
+    #[error("Initial liquidity must be more than {}", MINIMUM_LIQUIDITY)]
+    MinimumLiquidityAmountError {},
+
+    #[error("Swaps are paused in the pair")]
+    SwapsPaused {},
+
+    #[error("Deposits are paused in the pair")]
+    DepositsPaused {},
+}
+
+impl From<OverflowError> for ContractError {
+    fn from(o: OverflowError) -> Self {
+        StdError::from(o).into()
+    }
+}
+
+impl From<ContractError> for StdError {
+    fn from(err: ContractError) -> Self {
+        match err {
+            ContractError::Std(err) => err,
+            err => StdError::generic_err(err.to_string()),
+        }
+    }
+}
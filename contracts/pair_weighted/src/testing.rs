@@ -0,0 +1,907 @@
+use crate::contract::{
+    execute, instantiate, query, query_config, query_pair_info, query_pool,
+    query_reverse_simulation, query_simulate_provide, query_simulate_withdraw, query_simulation,
+    reply,
+};
+use crate::error::ContractError;
+use crate::mock_querier::mock_dependencies;
+use crate::response::MsgInstantiateContractResponse;
+use crate::state::RESERVES;
+use astroport::asset::{Asset, AssetInfo, PairInfo};
+use astroport::factory::{PairStatus, PairType};
+use astroport::weighted_math::{calc_ask_amount, calc_offer_amount, ONE};
+
+use astroport::pair::{ExecuteMsg, InstantiateMsg, QueryMsg};
+use astroport::pair_weighted::{Cw20HookMsg, WeightedPoolConfig, WeightedPoolParams};
+use cosmwasm_std::testing::{mock_env, mock_info, MOCK_CONTRACT_ADDR};
+use cosmwasm_std::{
+    attr, from_binary, to_binary, Addr, BankMsg, Coin, ContractResult, CosmosMsg, Decimal, DepsMut,
+    Reply, StdError, SubMsg, SubMsgExecutionResponse, Uint128, WasmMsg,
+};
+use cw20::{Cw20ExecuteMsg, Cw20ReceiveMsg};
+use protobuf::Message;
+
+fn store_liquidity_token(deps: DepsMut, msg_id: u64, contract_addr: String) {
+    let data = MsgInstantiateContractResponse {
+        contract_address: contract_addr,
+        data: vec![],
+        unknown_fields: Default::default(),
+        cached_size: Default::default(),
+    }
+    .write_to_bytes()
+    .unwrap();
+
+    let reply_msg = Reply {
+        id: msg_id,
+        result: ContractResult::Ok(SubMsgExecutionResponse {
+            events: vec![],
+            data: Some(data.into()),
+        }),
+    };
+
+    let _res = reply(deps, mock_env(), reply_msg.clone()).unwrap();
+}
+
+fn store_reserves(deps: DepsMut, reserves: [u128; 2]) {
+    RESERVES
+        .save(
+            deps.storage,
+            &[Uint128::new(reserves[0]), Uint128::new(reserves[1])],
+        )
+        .unwrap();
+}
+
+fn abs_diff(a: u128, b: u128) -> u128 {
+    a.max(b) - a.min(b)
+}
+
+fn instantiate_msg(weights: [Decimal; 2]) -> InstantiateMsg {
+    InstantiateMsg {
+        factory_addr: String::from("factory"),
+        asset_infos: [
+            AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            AssetInfo::Token {
+                contract_addr: Addr::unchecked("asset0000"),
+            },
+        ],
+        token_code_id: 10u64,
+        init_params: Some(to_binary(&WeightedPoolParams { weights }).unwrap()),
+    }
+}
+
+#[test]
+fn proper_initialization() {
+    let mut deps = mock_dependencies(&[]);
+
+    deps.querier.with_token_balances(&[(
+        &String::from("asset0000"),
+        &[(&String::from(MOCK_CONTRACT_ADDR), &Uint128::new(123u128))],
+    )]);
+
+    // Init params are required
+    let mut msg = instantiate_msg([Decimal::percent(80), Decimal::percent(20)]);
+    msg.init_params = None;
+    let res = instantiate(deps.as_mut(), mock_env(), mock_info("addr0000", &[]), msg).unwrap_err();
+    assert_eq!(res, ContractError::InitParamsNotFound {});
+
+    // Weights must sum up to 1
+    let msg = instantiate_msg([Decimal::percent(80), Decimal::percent(30)]);
+    let res = instantiate(deps.as_mut(), mock_env(), mock_info("addr0000", &[]), msg).unwrap_err();
+    assert_eq!(res, ContractError::InvalidWeights {});
+
+    // Each weight must be at least 1%
+    let msg = instantiate_msg([Decimal::one(), Decimal::zero()]);
+    let res = instantiate(deps.as_mut(), mock_env(), mock_info("addr0000", &[]), msg).unwrap_err();
+    assert_eq!(res, ContractError::InvalidWeights {});
+
+    let msg = instantiate_msg([Decimal::percent(80), Decimal::percent(20)]);
+    let res = instantiate(deps.as_mut(), mock_env(), mock_info("addr0000", &[]), msg).unwrap();
+    assert_eq!(res.messages.len(), 1);
+
+    // Store liquidity token
+    store_liquidity_token(deps.as_mut(), 1, "liquidity0000".to_string());
+
+    let pair_info: PairInfo = query_pair_info(deps.as_ref()).unwrap();
+    assert_eq!(Addr::unchecked("liquidity0000"), pair_info.liquidity_token);
+    assert_eq!(pair_info.pair_type, PairType::Weighted {});
+
+    let config = query_config(deps.as_ref()).unwrap();
+    let params: WeightedPoolConfig = from_binary(&config.params.unwrap()).unwrap();
+    assert_eq!(params.weights, [Decimal::percent(80), Decimal::percent(20)]);
+}
+
+#[test]
+fn swap_uses_weighted_math() {
+    let total_share = Uint128::new(1_000_000_000u128);
+    let offer_amount = Uint128::new(10_000_000u128);
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::new(4_000_000_000u128) + offer_amount, /* user deposit must be pre-applied */
+    }]);
+
+    deps.querier.with_token_balances(&[
+        (
+            &String::from("liquidity0000"),
+            &[(&String::from(MOCK_CONTRACT_ADDR), &total_share)],
+        ),
+        (
+            &String::from("asset0000"),
+            &[(
+                &String::from(MOCK_CONTRACT_ADDR),
+                &Uint128::new(1_000_000_000u128),
+            )],
+        ),
+    ]);
+
+    // 80/20 pool with the same value of both assets: 1 asset0000 = 1 uusd
+    let msg = instantiate_msg([Decimal::percent(80), Decimal::percent(20)]);
+    instantiate(deps.as_mut(), mock_env(), mock_info("addr0000", &[]), msg).unwrap();
+    store_liquidity_token(deps.as_mut(), 1, "liquidity0000".to_string());
+    store_reserves(deps.as_mut(), [4_000_000_000u128, 1_000_000_000u128]);
+
+    let msg = ExecuteMsg::Swap {
+        offer_asset: Asset {
+            info: AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            amount: offer_amount,
+        },
+        belief_price: None,
+        max_spread: Some(Decimal::percent(5)),
+        to: None,
+        min_return: None,
+        deadline: None,
+    };
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: offer_amount,
+        }],
+    );
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // ask_amount = 1_000_000_000 * (1 - (4_000_000_000 / 4_010_000_000) ^ 4) = 9_937_811
+    let expected_ask_amount = 9_937_811u128;
+    let expected_commission_amount = expected_ask_amount * 3 / 1000;
+    let expected_return_amount = expected_ask_amount - expected_commission_amount;
+    let expected_spread_amount = offer_amount.u128() - expected_ask_amount;
+    assert_eq!(
+        res.messages[0],
+        SubMsg::new(WasmMsg::Execute {
+            contract_addr: String::from("asset0000"),
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: String::from("addr0000"),
+                amount: Uint128::new(expected_return_amount),
+            })
+            .unwrap(),
+            funds: vec![],
+        })
+    );
+    assert_eq!(
+        res.attributes[6..10],
+        [
+            attr("return_amount", expected_return_amount.to_string()),
+            attr("tax_amount", "0"),
+            attr("spread_amount", expected_spread_amount.to_string()),
+            attr("commission_amount", expected_commission_amount.to_string()),
+        ]
+    );
+
+    // The simulation queries use the same math (the reserves are reset to the ones before the swap)
+    store_reserves(deps.as_mut(), [4_000_000_000u128, 1_000_000_000u128]);
+    let simulation = query_simulation(
+        deps.as_ref(),
+        Asset {
+            info: AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            amount: offer_amount,
+        },
+    )
+    .unwrap();
+    assert_eq!(simulation.return_amount.u128(), expected_return_amount);
+    assert_eq!(
+        simulation.commission_amount.u128(),
+        expected_commission_amount
+    );
+
+    let reverse_simulation = query_reverse_simulation(
+        deps.as_ref(),
+        Asset {
+            info: AssetInfo::Token {
+                contract_addr: Addr::unchecked("asset0000"),
+            },
+            amount: simulation.return_amount,
+        },
+    )
+    .unwrap();
+    assert!(abs_diff(reverse_simulation.offer_amount.u128(), offer_amount.u128()) <= 1);
+
+    // The offer amount is limited by the max in ratio
+    let res = query_simulation(
+        deps.as_ref(),
+        Asset {
+            info: AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            amount: Uint128::new(1_500_000_000u128),
+        },
+    )
+    .unwrap_err();
+    assert_eq!(
+        res,
+        StdError::generic_err(ContractError::MaxInRatioAssertion {}.to_string())
+    );
+}
+
+#[test]
+fn swap_exact_out() {
+    let total_share = Uint128::new(1_000_000_000u128);
+    let ask_amount = Uint128::new(10_000_000u128);
+    let max_offer = Uint128::new(50_000_000u128);
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::new(4_000_000_000u128),
+    }]);
+
+    deps.querier.with_token_balances(&[
+        (
+            &String::from("liquidity0000"),
+            &[(&String::from(MOCK_CONTRACT_ADDR), &total_share)],
+        ),
+        (
+            &String::from("asset0000"),
+            &[(
+                &String::from(MOCK_CONTRACT_ADDR),
+                &Uint128::new(1_000_000_000u128),
+            )],
+        ),
+    ]);
+
+    let msg = instantiate_msg([Decimal::percent(80), Decimal::percent(20)]);
+    instantiate(deps.as_mut(), mock_env(), mock_info("addr0000", &[]), msg).unwrap();
+    store_liquidity_token(deps.as_mut(), 1, "liquidity0000".to_string());
+    store_reserves(deps.as_mut(), [4_000_000_000u128, 1_000_000_000u128]);
+
+    let ask_asset = Asset {
+        info: AssetInfo::NativeToken {
+            denom: "uusd".to_string(),
+        },
+        amount: ask_amount,
+    };
+    let reverse_simulation = query_reverse_simulation(deps.as_ref(), ask_asset.clone()).unwrap();
+    let expected_offer_amount = reverse_simulation.offer_amount;
+
+    // The required offer amount exceeds max_offer
+    let msg = ExecuteMsg::SwapExactOut {
+        ask_asset: ask_asset.clone(),
+        max_offer: expected_offer_amount - Uint128::new(1u128),
+        to: None,
+        deadline: None,
+    };
+    let res = execute(deps.as_mut(), mock_env(), mock_info("addr0000", &[]), msg).unwrap_err();
+    assert_eq!(res, ContractError::MaxOfferAssertion {});
+
+    // The sent tokens are the max offer amount and the excess is sent back
+    deps.querier.with_token_balances(&[
+        (
+            &String::from("liquidity0000"),
+            &[(&String::from(MOCK_CONTRACT_ADDR), &total_share)],
+        ),
+        (
+            &String::from("asset0000"),
+            &[(
+                &String::from(MOCK_CONTRACT_ADDR),
+                &(Uint128::new(1_000_000_000u128) + max_offer),
+            )],
+        ),
+    ]);
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: String::from("addr0000"),
+        amount: max_offer,
+        msg: to_binary(&Cw20HookMsg::SwapExactOut {
+            ask_asset,
+            to: None,
+            deadline: None,
+        })
+        .unwrap(),
+    });
+    let res = execute(deps.as_mut(), mock_env(), mock_info("asset0000", &[]), msg).unwrap();
+    assert_eq!(
+        res.messages[0..2],
+        [
+            SubMsg::new(BankMsg::Send {
+                to_address: String::from("addr0000"),
+                amount: vec![Coin {
+                    denom: "uusd".to_string(),
+                    amount: ask_amount,
+                }],
+            }),
+            SubMsg::new(WasmMsg::Execute {
+                contract_addr: String::from("asset0000"),
+                msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                    recipient: String::from("addr0000"),
+                    amount: max_offer - expected_offer_amount,
+                })
+                .unwrap(),
+                funds: vec![],
+            }),
+        ]
+    );
+    assert_eq!(
+        res.attributes[5..7],
+        [
+            attr("offer_amount", expected_offer_amount.to_string()),
+            attr("return_amount", ask_amount.to_string()),
+        ]
+    );
+}
+
+#[test]
+fn math_matches_constant_product_for_equal_weights() {
+    let half = ONE / 2;
+    for (offer_pool, ask_pool, offer_amount) in [
+        (1_000_000u128, 1_000_000u128, 1_000u128),
+        (
+            1_000_000_000_000u128,
+            3_000_000_000_000u128,
+            250_000_000_000u128,
+        ),
+        (5_000_000_000u128, 700_000_000u128, 1_000_000u128),
+    ] {
+        let xyk_ask_amount = ask_pool - offer_pool * ask_pool / (offer_pool + offer_amount);
+        let ask_amount = calc_ask_amount(offer_pool, half, ask_pool, half, offer_amount).unwrap();
+        assert!(abs_diff(ask_amount, xyk_ask_amount) <= 1);
+
+        let xyk_offer_amount = offer_pool * ask_pool / (ask_pool - xyk_ask_amount) - offer_pool;
+        let offer_amount =
+            calc_offer_amount(offer_pool, half, ask_pool, half, xyk_ask_amount).unwrap();
+        assert!(abs_diff(offer_amount, xyk_offer_amount) <= 1);
+    }
+}
+
+#[test]
+fn provide_and_withdraw_single_sided() {
+    let total_share = Uint128::new(1_000_000_000u128);
+    let deposit = Uint128::new(100_000_000u128);
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::new(4_000_000_000u128) + deposit, /* user deposit must be pre-applied */
+    }]);
+
+    deps.querier.with_token_balances(&[
+        (
+            &String::from("liquidity0000"),
+            &[(&String::from(MOCK_CONTRACT_ADDR), &total_share)],
+        ),
+        (
+            &String::from("asset0000"),
+            &[(
+                &String::from(MOCK_CONTRACT_ADDR),
+                &Uint128::new(1_000_000_000u128),
+            )],
+        ),
+    ]);
+
+    let msg = instantiate_msg([Decimal::percent(80), Decimal::percent(20)]);
+    instantiate(deps.as_mut(), mock_env(), mock_info("addr0000", &[]), msg).unwrap();
+    store_liquidity_token(deps.as_mut(), 1, "liquidity0000".to_string());
+    store_reserves(deps.as_mut(), [4_000_000_000u128, 1_000_000_000u128]);
+
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: deposit,
+        }],
+    );
+    let offer_asset = Asset {
+        info: AssetInfo::NativeToken {
+            denom: "uusd".to_string(),
+        },
+        amount: deposit,
+    };
+
+    // share = 1_000_000_000 * ((4_000_000_000 + 100_000_000 * (1 - 0.2 * 0.003)) / 4_000_000_000) ^ 0.8 - 1_000_000_000
+    // The fee is charged only on the 20% of the deposit that would be swapped to asset0000
+    let expected_share = Uint128::new(19_938_552u128);
+    let msg = ExecuteMsg::ProvideSingleSided {
+        asset: offer_asset.clone(),
+        min_lp_to_receive: Some(expected_share + Uint128::new(1)),
+        auto_stake: None,
+        receiver: None,
+        deadline: None,
+    };
+    let res = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap_err();
+    assert_eq!(res, ContractError::MinLpToReceiveAssertion {});
+
+    let msg = ExecuteMsg::ProvideSingleSided {
+        asset: offer_asset,
+        min_lp_to_receive: Some(expected_share),
+        auto_stake: None,
+        receiver: None,
+        deadline: None,
+    };
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+                to_address: String::from("fee_address"),
+                amount: vec![Coin {
+                    denom: "uusd".to_string(),
+                    amount: Uint128::new(9_960u128),
+                }],
+            })),
+            SubMsg::new(WasmMsg::Execute {
+                contract_addr: String::from("liquidity0000"),
+                msg: to_binary(&Cw20ExecuteMsg::Mint {
+                    recipient: String::from("addr0000"),
+                    amount: expected_share,
+                })
+                .unwrap(),
+                funds: vec![],
+            }),
+        ]
+    );
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "provide_single_sided"),
+            attr("sender", "addr0000"),
+            attr("receiver", "addr0000"),
+            attr("asset", "100000000uusd"),
+            attr("commission_amount", "60000"),
+            attr("maker_fee_amount", "9960"),
+            attr("share", expected_share.to_string()),
+        ]
+    );
+
+    // Withdraw the minted LP tokens in asset0000 only
+    deps.querier.with_token_balances(&[
+        (
+            &String::from("liquidity0000"),
+            &[(
+                &String::from(MOCK_CONTRACT_ADDR),
+                &(total_share + expected_share),
+            )],
+        ),
+        (
+            &String::from("asset0000"),
+            &[(
+                &String::from(MOCK_CONTRACT_ADDR),
+                &Uint128::new(1_000_000_000u128),
+            )],
+        ),
+    ]);
+
+    // Only the LP token can be used for withdrawals
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: String::from("addr0000"),
+        amount: expected_share,
+        msg: to_binary(&Cw20HookMsg::WithdrawSingleSided {
+            ask_asset_info: AssetInfo::Token {
+                contract_addr: Addr::unchecked("asset0000"),
+            },
+            min_ask_amount: None,
+            deadline: None,
+        })
+        .unwrap(),
+    });
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("asset0000", &[]),
+        msg.clone(),
+    )
+    .unwrap_err();
+    assert_eq!(res, ContractError::Unauthorized {});
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("liquidity0000", &[]),
+        msg,
+    )
+    .unwrap();
+
+    // ask_amount = 1_000_000_000 * (1 - (1_000_000_000 / 1_019_938_552) ^ 5) * (1 - 0.8 * 0.003)
+    let expected_return_amount = Uint128::new(93_770_730u128);
+    assert_eq!(
+        res.messages[0],
+        SubMsg::new(WasmMsg::Execute {
+            contract_addr: String::from("asset0000"),
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: String::from("addr0000"),
+                amount: expected_return_amount,
+            })
+            .unwrap(),
+            funds: vec![],
+        })
+    );
+    assert_eq!(
+        res.messages[1],
+        SubMsg::new(WasmMsg::Execute {
+            contract_addr: String::from("liquidity0000"),
+            msg: to_binary(&Cw20ExecuteMsg::Burn {
+                amount: expected_share,
+            })
+            .unwrap(),
+            funds: vec![],
+        })
+    );
+
+    // The slippage guard is enforced
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: String::from("addr0000"),
+        amount: expected_share,
+        msg: to_binary(&Cw20HookMsg::WithdrawSingleSided {
+            ask_asset_info: AssetInfo::Token {
+                contract_addr: Addr::unchecked("asset0000"),
+            },
+            min_ask_amount: Some(expected_return_amount + Uint128::new(1)),
+            deadline: None,
+        })
+        .unwrap(),
+    });
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("liquidity0000", &[]),
+        msg,
+    )
+    .unwrap_err();
+    assert_eq!(res, ContractError::MinAskAmountAssertion {});
+
+    // The deadline is checked before anything else
+    let env = mock_env();
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: String::from("addr0000"),
+        amount: expected_share,
+        msg: to_binary(&Cw20HookMsg::WithdrawSingleSided {
+            ask_asset_info: AssetInfo::Token {
+                contract_addr: Addr::unchecked("asset0000"),
+            },
+            min_ask_amount: None,
+            deadline: Some(env.block.time.seconds() - 1),
+        })
+        .unwrap(),
+    });
+    let res = execute(deps.as_mut(), env, mock_info("liquidity0000", &[]), msg).unwrap_err();
+    assert_eq!(res, ContractError::DeadlineExpired {});
+}
+
+#[test]
+fn simulate_provide_and_withdraw() {
+    let total_share = Uint128::new(1_000_000_000u128);
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::new(4_000_000_000u128),
+    }]);
+
+    deps.querier.with_token_balances(&[
+        (
+            &String::from("liquidity0000"),
+            &[(&String::from(MOCK_CONTRACT_ADDR), &total_share)],
+        ),
+        (
+            &String::from("asset0000"),
+            &[(
+                &String::from(MOCK_CONTRACT_ADDR),
+                &Uint128::new(1_000_000_000u128),
+            )],
+        ),
+    ]);
+
+    let msg = instantiate_msg([Decimal::percent(80), Decimal::percent(20)]);
+    instantiate(deps.as_mut(), mock_env(), mock_info("addr0000", &[]), msg).unwrap();
+    store_liquidity_token(deps.as_mut(), 1, "liquidity0000".to_string());
+    store_reserves(deps.as_mut(), [4_000_000_000u128, 1_000_000_000u128]);
+
+    let assets = [
+        Asset {
+            info: AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            amount: Uint128::new(400_000_000u128),
+        },
+        Asset {
+            info: AssetInfo::Token {
+                contract_addr: Addr::unchecked("asset0000"),
+            },
+            amount: Uint128::new(100_000_000u128),
+        },
+    ];
+
+    // A deposit in the pool ratio mints a proportional share without fees
+    let res = query_simulate_provide(deps.as_ref(), assets.clone(), None).unwrap();
+    assert_eq!(res.share, Uint128::new(100_000_000u128));
+    assert!(res.fees.iter().all(|fee| fee.amount.is_zero()));
+
+    // Both pool assets must be deposited
+    let err = query_simulate_provide(
+        deps.as_ref(),
+        [
+            assets[0].clone(),
+            Asset {
+                info: assets[1].info.clone(),
+                amount: Uint128::zero(),
+            },
+        ],
+        None,
+    )
+    .unwrap_err();
+    assert_eq!(
+        err,
+        StdError::generic_err(ContractError::InvalidZeroAmount {}.to_string())
+    );
+
+    let res = query_simulate_withdraw(deps.as_ref(), Uint128::new(100_000_000u128)).unwrap();
+    assert_eq!(res[0].amount, Uint128::new(400_000_000u128));
+    assert_eq!(res[1].amount, Uint128::new(100_000_000u128));
+}
+
+#[test]
+fn sync_and_skim() {
+    let total_share = Uint128::new(1_000_000_000u128);
+    let asset_pool_amount = Uint128::new(1_000_000_000u128);
+    let donation_amount = Uint128::new(1_000_000u128);
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::new(4_000_000_000u128),
+    }]);
+
+    deps.querier.with_token_balances(&[
+        (
+            &String::from("liquidity0000"),
+            &[(&String::from(MOCK_CONTRACT_ADDR), &total_share)],
+        ),
+        (
+            &String::from("asset0000"),
+            &[(&String::from(MOCK_CONTRACT_ADDR), &asset_pool_amount)],
+        ),
+    ]);
+
+    let msg = instantiate_msg([Decimal::percent(80), Decimal::percent(20)]);
+    instantiate(deps.as_mut(), mock_env(), mock_info("addr0000", &[]), msg).unwrap();
+    store_liquidity_token(deps.as_mut(), 1, "liquidity0000".to_string());
+
+    // The reserves are tracked from zero, so the balances of the pair are not counted
+    let res = query_pool(deps.as_ref()).unwrap();
+    assert_eq!(res.assets[0].amount, Uint128::zero());
+    assert_eq!(res.assets[1].amount, Uint128::zero());
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        ExecuteMsg::Sync {},
+    )
+    .unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "sync"),
+            attr("reserves", "4000000000uusd, 1000000000asset0000"),
+        ]
+    );
+
+    let offer_asset = Asset {
+        info: AssetInfo::NativeToken {
+            denom: "uusd".to_string(),
+        },
+        amount: Uint128::new(10_000_000u128),
+    };
+    let simulation = query_simulation(deps.as_ref(), offer_asset.clone()).unwrap();
+
+    // A direct transfer to the pair doesn't move the price
+    deps.querier.with_token_balances(&[
+        (
+            &String::from("liquidity0000"),
+            &[(&String::from(MOCK_CONTRACT_ADDR), &total_share)],
+        ),
+        (
+            &String::from("asset0000"),
+            &[(
+                &String::from(MOCK_CONTRACT_ADDR),
+                &(asset_pool_amount + donation_amount),
+            )],
+        ),
+    ]);
+    assert_eq!(
+        query_simulation(deps.as_ref(), offer_asset.clone()).unwrap(),
+        simulation
+    );
+
+    // The excess balance is sent to the recipient
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        ExecuteMsg::Skim {
+            to: Some(String::from("addr0001")),
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(WasmMsg::Execute {
+            contract_addr: String::from("asset0000"),
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: String::from("addr0001"),
+                amount: donation_amount,
+            })
+            .unwrap(),
+            funds: vec![],
+        })]
+    );
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "skim"),
+            attr("receiver", "addr0001"),
+            attr("assets", "0uusd, 1000000asset0000"),
+        ]
+    );
+
+    // Syncing adds the excess balance to the pool instead
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        ExecuteMsg::Sync {},
+    )
+    .unwrap();
+    let res = query_pool(deps.as_ref()).unwrap();
+    assert_eq!(res.assets[1].amount, asset_pool_amount + donation_amount);
+    assert_ne!(
+        query_simulation(deps.as_ref(), offer_asset).unwrap(),
+        simulation
+    );
+
+    // Nothing is left to skim
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        ExecuteMsg::Skim { to: None },
+    )
+    .unwrap();
+    assert!(res.messages.is_empty());
+}
+
+#[test]
+fn pair_status_circuit_breaker() {
+    let total_share = Uint128::new(1_000_000_000u128);
+    let deposit = Uint128::new(10_000_000u128);
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::new(4_000_000_000u128) + deposit, /* user deposit must be pre-applied */
+    }]);
+
+    deps.querier.with_token_balances(&[
+        (
+            &String::from("liquidity0000"),
+            &[(&String::from(MOCK_CONTRACT_ADDR), &total_share)],
+        ),
+        (
+            &String::from("asset0000"),
+            &[(
+                &String::from(MOCK_CONTRACT_ADDR),
+                &Uint128::new(1_000_000_000u128),
+            )],
+        ),
+    ]);
+
+    let msg = instantiate_msg([Decimal::percent(80), Decimal::percent(20)]);
+    instantiate(deps.as_mut(), mock_env(), mock_info("addr0000", &[]), msg).unwrap();
+    store_liquidity_token(deps.as_mut(), 1, "liquidity0000".to_string());
+    store_reserves(deps.as_mut(), [4_000_000_000u128, 1_000_000_000u128]);
+
+    let offer_asset = Asset {
+        info: AssetInfo::NativeToken {
+            denom: "uusd".to_string(),
+        },
+        amount: deposit,
+    };
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: deposit,
+        }],
+    );
+    let swap_msg = ExecuteMsg::Swap {
+        offer_asset: offer_asset.clone(),
+        belief_price: None,
+        max_spread: Some(Decimal::percent(5)),
+        to: None,
+        min_return: None,
+        deadline: None,
+    };
+    let provide_msg = ExecuteMsg::ProvideSingleSided {
+        asset: offer_asset,
+        min_lp_to_receive: None,
+        auto_stake: None,
+        receiver: None,
+        deadline: None,
+    };
+
+    // The pair picks the factory state up once its cached parameters are refreshed
+    deps.querier.with_pair_status(PairStatus::WithdrawOnly);
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("anyone", &[]),
+        ExecuteMsg::RefreshFactoryParams {},
+    )
+    .unwrap();
+    let status: PairStatus =
+        from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::PairStatus {}).unwrap()).unwrap();
+    assert_eq!(status, PairStatus::WithdrawOnly);
+
+    let res = execute(deps.as_mut(), mock_env(), info.clone(), swap_msg.clone()).unwrap_err();
+    assert_eq!(res, ContractError::SwapsPaused {});
+    let res = execute(deps.as_mut(), mock_env(), info.clone(), provide_msg.clone()).unwrap_err();
+    assert_eq!(res, ContractError::DepositsPaused {});
+
+    // LPs can still exit the pool, subject to their execution guards
+    let withdraw_msg = |min_assets_out: Option<Vec<Asset>>, deadline: Option<u64>| {
+        ExecuteMsg::Receive(Cw20ReceiveMsg {
+            sender: String::from("addr0000"),
+            msg: to_binary(&Cw20HookMsg::WithdrawLiquidity {
+                min_assets_out,
+                deadline,
+            })
+            .unwrap(),
+            amount: Uint128::new(100u128),
+        })
+    };
+    let env = mock_env();
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("liquidity0000", &[]),
+        withdraw_msg(None, Some(env.block.time.seconds() - 1)),
+    )
+    .unwrap_err();
+    assert_eq!(res, ContractError::DeadlineExpired {});
+
+    let min_assets_out = vec![Asset {
+        info: AssetInfo::NativeToken {
+            denom: "uusd".to_string(),
+        },
+        amount: Uint128::MAX,
+    }];
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("liquidity0000", &[]),
+        withdraw_msg(Some(min_assets_out), None),
+    )
+    .unwrap_err();
+    assert_eq!(res, ContractError::MinAssetsOutAssertion {});
+
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("liquidity0000", &[]),
+        withdraw_msg(None, Some(env.block.time.seconds())),
+    )
+    .unwrap();
+
+    // Once the pair is resumed, swaps and deposits go through again
+    deps.querier.with_pair_status(PairStatus::Active);
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("anyone", &[]),
+        ExecuteMsg::RefreshFactoryParams {},
+    )
+    .unwrap();
+    execute(deps.as_mut(), mock_env(), info.clone(), swap_msg).unwrap();
+    execute(deps.as_mut(), mock_env(), info, provide_msg).unwrap();
+}
@@ -3,14 +3,16 @@ use crate::querier::{query_cumulative_prices, query_pair_info, query_prices};
 use crate::state::{Config, PriceCumulativeLast, CONFIG, PRICE_LAST};
 use astroport::asset::{addr_validate_to_lower, Asset, AssetInfo};
 use astroport::oracle::{ExecuteMsg, InstantiateMsg, MigrateMsg, QueryMsg};
-use astroport::pair::TWAP_PRECISION;
+use astroport::pair::{CumulativePricesResponse, TWAP_PRECISION, TWAP_PRECISION_U256};
 use astroport::querier::query_token_precision;
+use astroport::Uint256WrappingOps;
 use cosmwasm_bignumber::{Decimal256, Uint256};
 use cosmwasm_std::{
     entry_point, to_binary, Binary, Deps, DepsMut, Env, MessageInfo, Response, StdError, StdResult,
     Uint128,
 };
 use cw2::set_contract_version;
+use std::str::FromStr;
 
 /// Contract name that is used for migration.
 const CONTRACT_NAME: &str = "astroport-oracle";
@@ -62,6 +64,8 @@ pub fn instantiate(
     let price = PriceCumulativeLast {
         price0_cumulative_last: prices.price0_cumulative_last,
         price1_cumulative_last: prices.price1_cumulative_last,
+        price0_cumulative_last_u256: prices.price0_cumulative_last_u256,
+        price1_cumulative_last_u256: prices.price1_cumulative_last_u256,
         price_0_average: Decimal256::zero(),
         price_1_average: Decimal256::zero(),
         block_timestamp_last: env.block.time.seconds(),
@@ -115,27 +119,14 @@ pub fn update(deps: DepsMut, env: Env) -> Result<Response, ContractError> {
         return Err(ContractError::WrongPeriod {});
     }
 
-    let price_0_average = Decimal256::from_ratio(
-        Uint256::from(
-            prices
-                .price0_cumulative_last
-                .wrapping_sub(price_last.price0_cumulative_last),
-        ),
-        time_elapsed,
-    );
-
-    let price_1_average = Decimal256::from_ratio(
-        Uint256::from(
-            prices
-                .price1_cumulative_last
-                .wrapping_sub(price_last.price1_cumulative_last),
-        ),
-        time_elapsed,
-    );
+    let (price_0_average, price_1_average) =
+        compute_price_averages(&price_last, &prices, time_elapsed)?;
 
     let prices = PriceCumulativeLast {
         price0_cumulative_last: prices.price0_cumulative_last,
         price1_cumulative_last: prices.price1_cumulative_last,
+        price0_cumulative_last_u256: prices.price0_cumulative_last_u256,
+        price1_cumulative_last_u256: prices.price1_cumulative_last_u256,
         price_0_average,
         price_1_average,
         block_timestamp_last: env.block.time.seconds(),
@@ -144,6 +135,76 @@ pub fn update(deps: DepsMut, env: Env) -> Result<Response, ContractError> {
     Ok(Response::default())
 }
 
+/// ## Description
+/// Computes the average prices of the pool assets since the last update. The 256-bit cumulative
+/// prices are used if both the last and the current values are set, otherwise the legacy ones
+/// are used. The averages are scaled by [`TWAP_PRECISION`] decimals in both cases.
+/// ## Params
+/// * **price_last** is an object of type [`PriceCumulativeLast`]. These are the prices saved on the last update.
+///
+/// * **prices** is an object of type [`CumulativePricesResponse`]. These are the current cumulative prices of the pair.
+///
+/// * **time_elapsed** is an object of type [`u64`]. This is the time since the last update.
+fn compute_price_averages(
+    price_last: &PriceCumulativeLast,
+    prices: &CumulativePricesResponse,
+    time_elapsed: u64,
+) -> StdResult<(Decimal256, Decimal256)> {
+    if let (
+        Some(price0_cumulative_last),
+        Some(price1_cumulative_last),
+        Some(price0_cumulative_current),
+        Some(price1_cumulative_current),
+    ) = (
+        price_last.price0_cumulative_last_u256,
+        price_last.price1_cumulative_last_u256,
+        prices.price0_cumulative_last_u256,
+        prices.price1_cumulative_last_u256,
+    ) {
+        // The 256-bit prices have more decimals, so the time is scaled by the difference in precisions
+        let time_elapsed = Uint256::from(time_elapsed)
+            * Uint256::from(10_u128.pow((TWAP_PRECISION_U256 - TWAP_PRECISION).into()));
+
+        return Ok((
+            Decimal256::from_ratio(
+                Uint256::from_str(
+                    &price0_cumulative_current
+                        .wrapping_sub(price0_cumulative_last)
+                        .to_string(),
+                )?,
+                time_elapsed,
+            ),
+            Decimal256::from_ratio(
+                Uint256::from_str(
+                    &price1_cumulative_current
+                        .wrapping_sub(price1_cumulative_last)
+                        .to_string(),
+                )?,
+                time_elapsed,
+            ),
+        ));
+    }
+
+    Ok((
+        Decimal256::from_ratio(
+            Uint256::from(
+                prices
+                    .price0_cumulative_last
+                    .wrapping_sub(price_last.price0_cumulative_last),
+            ),
+            time_elapsed,
+        ),
+        Decimal256::from_ratio(
+            Uint256::from(
+                prices
+                    .price1_cumulative_last
+                    .wrapping_sub(price_last.price1_cumulative_last),
+            ),
+            time_elapsed,
+        ),
+    ))
+}
+
 /// ## Description
 /// Exposes all the queries available in the contract.
 /// ## Params
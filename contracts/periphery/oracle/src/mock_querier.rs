@@ -6,7 +6,7 @@ use astroport::pair::QueryMsg::CumulativePrices;
 use cosmwasm_std::testing::{MockApi, MockQuerier, MockStorage, MOCK_CONTRACT_ADDR};
 use cosmwasm_std::{
     from_binary, from_slice, to_binary, Addr, Coin, OwnedDeps, Querier, QuerierResult,
-    QueryRequest, SystemError, SystemResult, Uint128, WasmQuery,
+    QueryRequest, SystemError, SystemResult, Uint128, Uint256, WasmQuery,
 };
 use std::collections::HashMap;
 use terra_cosmwasm::TerraQueryWrapper;
@@ -52,6 +52,8 @@ impl TokenQuerier {
                 total_share: total,
                 price0_cumulative_last: price0,
                 price1_cumulative_last: price1,
+                price0_cumulative_last_u256: None,
+                price1_cumulative_last_u256: None,
             },
         );
     }
@@ -79,7 +81,7 @@ impl WasmMockQuerier {
             QueryRequest::Wasm(WasmQuery::Smart { contract_addr, msg }) => {
                 if contract_addr == "factory" {
                     match from_binary(&msg).unwrap() {
-                        Pair { asset_infos } => SystemResult::Ok(
+                        Pair { asset_infos, .. } => SystemResult::Ok(
                             to_binary(&PairInfo {
                                 asset_infos,
                                 contract_addr: Addr::unchecked("pair"),
@@ -127,4 +129,10 @@ impl WasmMockQuerier {
     ) {
         self.token_querier.set(pair, assert, total, price1, price2)
     }
+
+    pub fn set_cumulative_price_u256(&mut self, pair: Addr, price0: Uint256, price1: Uint256) {
+        let prices = self.token_querier.pairs.get_mut(pair.as_str()).unwrap();
+        prices.price0_cumulative_last_u256 = Some(price0);
+        prices.price1_cumulative_last_u256 = Some(price1);
+    }
 }
@@ -18,7 +18,10 @@ pub fn query_pair_info(
 ) -> StdResult<PairInfo> {
     querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
         contract_addr: factory_contract.to_string(),
-        msg: to_binary(&FactoryQueryMsg::Pair { asset_infos })?,
+        msg: to_binary(&FactoryQueryMsg::Pair {
+            asset_infos,
+            pair_type: None,
+        })?,
     }))
 }
 
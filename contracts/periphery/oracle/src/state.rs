@@ -3,7 +3,7 @@ use serde::{Deserialize, Serialize};
 
 use astroport::asset::{AssetInfo, PairInfo};
 use cosmwasm_bignumber::Decimal256;
-use cosmwasm_std::{Addr, Uint128};
+use cosmwasm_std::{Addr, Uint128, Uint256};
 use cw_storage_plus::Item;
 
 /// ## Description
@@ -20,6 +20,10 @@ pub struct PriceCumulativeLast {
     pub price0_cumulative_last: Uint128,
     /// The last cumulative price 1 asset in pool
     pub price1_cumulative_last: Uint128,
+    /// The last 256-bit cumulative price 0 asset in pool. Not set if the pair doesn't track 256-bit prices
+    pub price0_cumulative_last_u256: Option<Uint256>,
+    /// The last 256-bit cumulative price 1 asset in pool. Not set if the pair doesn't track 256-bit prices
+    pub price1_cumulative_last_u256: Option<Uint256>,
     /// The average price 0 asset in pool
     pub price_0_average: Decimal256,
     /// The average price 1 asset in pool
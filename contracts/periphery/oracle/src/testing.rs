@@ -1,10 +1,11 @@
-use crate::contract::{execute, instantiate};
+use crate::contract::{execute, instantiate, query};
 use crate::mock_querier::mock_dependencies;
 use astroport::asset::{Asset, AssetInfo};
-use astroport::oracle::{ExecuteMsg, InstantiateMsg};
+use astroport::oracle::{ExecuteMsg, InstantiateMsg, QueryMsg};
+use astroport::Uint256WrappingOps;
 use cosmwasm_bignumber::{Decimal256, Uint256};
 use cosmwasm_std::testing::{mock_env, mock_info};
-use cosmwasm_std::{Addr, Uint128};
+use cosmwasm_std::{from_binary, Addr, Uint128, Uint256 as StdUint256};
 use std::ops::Mul;
 
 #[test]
@@ -77,3 +78,93 @@ fn oracle_overflow() {
     env.block.time = env.block.time.plus_seconds(86400);
     execute(deps.as_mut(), env, info, ExecuteMsg::Update {}).unwrap();
 }
+
+#[test]
+fn oracle_u256_prices() {
+    let mut deps = mock_dependencies(&[]);
+    let info = mock_info("addr0000", &[]);
+
+    let mut env = mock_env();
+    let factory = Addr::unchecked("factory");
+    let astro_asset_info = AssetInfo::Token {
+        contract_addr: Addr::unchecked("astro-token"),
+    };
+    let usdc_asset_info = AssetInfo::Token {
+        contract_addr: Addr::unchecked("usdc-token"),
+    };
+    let asset = [
+        Asset {
+            info: astro_asset_info.clone(),
+            amount: Uint128::zero(),
+        },
+        Asset {
+            info: usdc_asset_info.clone(),
+            amount: Uint128::zero(),
+        },
+    ];
+
+    let instantiate_msg = InstantiateMsg {
+        factory_contract: factory.to_string(),
+        asset_infos: [astro_asset_info.clone(), usdc_asset_info.clone()],
+    };
+
+    // The 256-bit accumulators are close to the overflow
+    let max = StdUint256::from_be_bytes([u8::MAX; 32]);
+    deps.querier.set_cumulative_price(
+        Addr::unchecked("pair"),
+        asset.clone(),
+        Uint128::zero(),
+        Uint128::zero(),
+        Uint128::zero(),
+    );
+    deps.querier
+        .set_cumulative_price_u256(Addr::unchecked("pair"), max, max);
+    instantiate(deps.as_mut(), env.clone(), info.clone(), instantiate_msg).unwrap();
+
+    // A day later one ASTRO costs 0.000000001 USDC. The price is lost with the legacy 6 decimals
+    // of precision, but not with the 256-bit accumulators
+    let time_elapsed = StdUint256::from(86400u64);
+    let price_precision = StdUint256::from(10u128.pow(18));
+    deps.querier.set_cumulative_price(
+        Addr::unchecked("pair"),
+        asset,
+        Uint128::zero(),
+        Uint128::zero(),
+        Uint128::zero(),
+    );
+    deps.querier.set_cumulative_price_u256(
+        Addr::unchecked("pair"),
+        max.wrapping_add(time_elapsed * price_precision / StdUint256::from(1_000_000_000u128)),
+        max.wrapping_add(time_elapsed * price_precision * StdUint256::from(1_000_000_000u128)),
+    );
+    env.block.time = env.block.time.plus_seconds(86400);
+    execute(deps.as_mut(), env.clone(), info, ExecuteMsg::Update {}).unwrap();
+
+    let res: Uint256 = from_binary(
+        &query(
+            deps.as_ref(),
+            env.clone(),
+            QueryMsg::Consult {
+                token: astro_asset_info,
+                amount: Uint128::new(1_000_000_000_000),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(res, Uint256::from(1000u128));
+
+    let res: Uint256 = from_binary(
+        &query(
+            deps.as_ref(),
+            env,
+            QueryMsg::Consult {
+                token: usdc_asset_info,
+                amount: Uint128::new(1000),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(res, Uint256::from(1_000_000_000_000u128));
+}
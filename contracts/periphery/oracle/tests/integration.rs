@@ -102,6 +102,7 @@ fn instantiate_contracts(router: &mut TerraApp, owner: Addr) -> (Addr, Addr, u64
                 maker_fee_bps: 0,
                 is_disabled: false,
                 is_generator_disabled: false,
+                maker_fee_as_lp: false,
             },
             PairConfig {
                 code_id: pair_stable_code_id,
@@ -110,6 +111,7 @@ fn instantiate_contracts(router: &mut TerraApp, owner: Addr) -> (Addr, Addr, u64
                 maker_fee_bps: 0,
                 is_disabled: false,
                 is_generator_disabled: false,
+                maker_fee_as_lp: false,
             },
         ],
         token_code_id: 1u64,
@@ -296,6 +298,7 @@ fn create_pair(
             contract_addr: factory_instance.clone().to_string(),
             msg: to_binary(&astroport::factory::QueryMsg::Pair {
                 asset_infos: asset_infos.clone(),
+                pair_type: None,
             })
             .unwrap(),
         }))
@@ -334,6 +337,8 @@ fn create_pair(
                 slippage_tolerance: None,
                 auto_stake: None,
                 receiver: None,
+                min_lp_to_receive: None,
+                deadline: None,
             },
             &funds,
         )
@@ -401,6 +406,7 @@ fn create_pair_stable(
             contract_addr: factory_instance.clone().to_string(),
             msg: to_binary(&astroport::factory::QueryMsg::Pair {
                 asset_infos: asset_infos.clone(),
+                pair_type: None,
             })
             .unwrap(),
         }))
@@ -439,6 +445,8 @@ fn create_pair_stable(
                 slippage_tolerance: None,
                 auto_stake: None,
                 receiver: None,
+                min_lp_to_receive: None,
+                deadline: None,
             },
             &funds,
         )
@@ -509,6 +517,8 @@ fn change_provide_liquidity(
                 slippage_tolerance: Some(Decimal::percent(50)),
                 auto_stake: None,
                 receiver: None,
+                min_lp_to_receive: None,
+                deadline: None,
             },
             &vec![],
         )
@@ -566,6 +576,7 @@ fn consult() {
             contract_addr: factory_instance.clone().to_string(),
             msg: to_binary(&astroport::factory::QueryMsg::Pair {
                 asset_infos: asset_infos.clone(),
+                pair_type: None,
             })
             .unwrap(),
         }))
@@ -697,6 +708,7 @@ fn consult_pair_stable() {
             contract_addr: factory_instance.clone().to_string(),
             msg: to_binary(&astroport::factory::QueryMsg::Pair {
                 asset_infos: asset_infos.clone(),
+                pair_type: None,
             })
             .unwrap(),
         }))
@@ -813,11 +825,11 @@ fn consult2() {
         [
             Asset {
                 info: asset_infos[0].clone(),
-                amount: Uint128::from(1000_u128),
+                amount: Uint128::from(1_000_000_u128),
             },
             Asset {
                 info: asset_infos[1].clone(),
-                amount: Uint128::from(1000_u128),
+                amount: Uint128::from(1_000_000_u128),
             },
         ],
     );
@@ -828,6 +840,7 @@ fn consult2() {
             contract_addr: factory_instance.clone().to_string(),
             msg: to_binary(&astroport::factory::QueryMsg::Pair {
                 asset_infos: asset_infos.clone(),
+                pair_type: None,
             })
             .unwrap(),
         }))
@@ -840,8 +853,8 @@ fn consult2() {
         pair_info.contract_addr.clone(),
         astro_token_instance.clone(),
         usdc_token_instance.clone(),
-        Uint128::from(1000_u128),
-        Uint128::from(1000_u128),
+        Uint128::from(1_000_000_u128),
+        Uint128::from(1_000_000_u128),
     );
     router.update_block(next_day);
 
@@ -881,8 +894,8 @@ fn consult2() {
 
     // Change pair liquidity
     for (amount1, amount2) in [
-        (Uint128::from(1000_u128), Uint128::from(500_u128)),
-        (Uint128::from(1000_u128), Uint128::from(500_u128)),
+        (Uint128::from(1_000_000_u128), Uint128::from(500_000_u128)),
+        (Uint128::from(1_000_000_u128), Uint128::from(500_000_u128)),
     ] {
         change_provide_liquidity(
             &mut router,
@@ -934,8 +947,8 @@ fn consult2() {
 
     // Change pair liquidity
     for (amount1, amount2) in [
-        (Uint128::from(250_u128), Uint128::from(350_u128)),
-        (Uint128::from(250_u128), Uint128::from(350_u128)),
+        (Uint128::from(250_000_u128), Uint128::from(350_000_u128)),
+        (Uint128::from(250_000_u128), Uint128::from(350_000_u128)),
     ] {
         change_provide_liquidity(
             &mut router,
@@ -146,6 +146,8 @@ pub fn asset_into_swap_msg(
                     belief_price: None,
                     max_spread,
                     to,
+                    min_return: None,
+                    deadline: None,
                 })?,
             }))
         }
@@ -160,6 +162,8 @@ pub fn asset_into_swap_msg(
                     belief_price: None,
                     max_spread,
                     to,
+                    min_return: None,
+                    deadline: None,
                 })?,
             })?,
         })),
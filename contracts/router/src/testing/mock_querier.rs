@@ -196,7 +196,7 @@ impl WasmMockQuerier {
 
     fn handle_default(&self, msg: &Binary) -> QuerierResult {
         match from_binary(&msg).unwrap() {
-            QueryMsg::Pair { asset_infos } => {
+            QueryMsg::Pair { asset_infos, .. } => {
                 let key = asset_infos[0].to_string() + asset_infos[1].to_string().as_str();
                 match self.astroport_factory_querier.pairs.get(&key) {
                     Some(v) => SystemResult::Ok(ContractResult::from(to_binary(&PairInfo {
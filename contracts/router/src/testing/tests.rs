@@ -484,6 +484,8 @@ fn execute_swap_operation() {
                         belief_price: None,
                         max_spread: None,
                         to: Some(String::from("addr0000")),
+                        min_return: None,
+                        deadline: None,
                     })
                     .unwrap()
                 })
@@ -441,6 +441,7 @@ pub fn execute_setup_pools(
                 cfg.factory.clone(),
                 &FactoryQueryMsg::Pair {
                     asset_infos: pair_info.asset_infos.clone(),
+                    pair_type: Some(pair_info.pair_type.clone()),
                 },
             )
             .map_err(|_| {
@@ -423,6 +423,7 @@ fn disabling_pool() {
             maker_fee_bps: 10,
             is_disabled: false,
             is_generator_disabled: true,
+            maker_fee_as_lp: false,
         },
     };
 
@@ -452,6 +453,7 @@ fn disabling_pool() {
             maker_fee_bps: 10,
             is_disabled: false,
             is_generator_disabled: false,
+            maker_fee_as_lp: false,
         },
     };
 
@@ -2232,6 +2234,7 @@ fn setup_pools() {
                     denom: "eur".to_string(),
                 },
             ],
+            pair_type: None,
         },
         &[],
     )
@@ -2453,6 +2456,7 @@ fn instantiate_factory(
             maker_fee_bps: 10,
             is_disabled: false,
             is_generator_disabled: false,
+            maker_fee_as_lp: false,
         }],
         token_code_id,
         fee_address: None,
@@ -2779,6 +2783,7 @@ fn create_pair(app: &mut TerraApp, factory: &Addr, assets: [AssetInfo; 2]) -> (A
             factory,
             &FactoryQueryMsg::Pair {
                 asset_infos: assets,
+                pair_type: None,
             },
         )
         .unwrap();
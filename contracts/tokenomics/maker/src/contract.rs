@@ -12,12 +12,14 @@ use astroport::asset::{
     PairInfo, ULUNA_DENOM, UUSD_DENOM,
 };
 use astroport::common::{claim_ownership, drop_ownership_proposal, propose_new_owner};
-use astroport::factory::UpdateAddr;
+use astroport::factory::{PairsResponse, QueryMsg as FactoryQueryMsg, UpdateAddr};
 use astroport::maker::{
     AssetWithLimit, BalancesResponse, ConfigResponse, ExecuteMsg, InstantiateMsg, MigrateMsg,
-    QueryMsg,
+    PairsPage, QueryMsg,
+};
+use astroport::pair::{
+    ExecuteMsg as PairExecuteMsg, ProtocolFeesResponse, QueryMsg as PairQueryMsg,
 };
-use astroport::pair::QueryMsg as PairQueryMsg;
 use cosmwasm_std::{
     attr, entry_point, to_binary, Addr, Attribute, Binary, CosmosMsg, Decimal, Deps, DepsMut, Env,
     MessageInfo, Order, QueryRequest, Response, StdError, StdResult, SubMsg, Uint128, Uint64,
@@ -110,8 +112,8 @@ pub fn instantiate(
 /// * **msg** is an object of type [`ExecuteMsg`].
 ///
 /// ## Queries
-/// * **ExecuteMsg::Collect { assets }** Swaps collected fee tokens to ASTRO
-/// and distributes the ASTRO between xASTRO and vxASTRO stakers.
+/// * **ExecuteMsg::Collect { assets, claim_from_pairs }** Claims the fees accrued in the specified factory pairs,
+/// swaps collected fee tokens to ASTRO and distributes the ASTRO between xASTRO and vxASTRO stakers.
 ///
 /// * **ExecuteMsg::UpdateConfig {
 ///             factory_contract,
@@ -142,7 +144,13 @@ pub fn execute(
     msg: ExecuteMsg,
 ) -> Result<Response, ContractError> {
     match msg {
-        ExecuteMsg::Collect { assets } => collect(deps, env, assets),
+        ExecuteMsg::Collect {
+            assets,
+            claim_from_pairs,
+        } => match claim_from_pairs {
+            Some(page) => claim_and_collect(deps, env, assets, page),
+            None => collect(deps, env, assets),
+        },
         ExecuteMsg::UpdateConfig {
             factory_contract,
             staking_contract,
@@ -278,6 +286,74 @@ fn collect(
     Ok(response.add_attribute("action", "collect"))
 }
 
+/// # Description
+/// Claims the Maker fees accrued in a page of the factory pairs and collects the specified fee tokens
+/// once the claimed fees are received. Pairs that don't accrue fees are skipped. Returns a [`ContractError`]
+/// on failure, otherwise returns a [`Response`] object if the operation was successful.
+/// # Params
+/// * **deps** is an object of type [`DepsMut`].
+///
+/// * **env** is an object of type [`Env`].
+///
+/// * **assets** is a vector that contains objects of type [`AssetWithLimit`]. These are the fee tokens being swapped to ASTRO.
+///
+/// * **page** is an object of type [`PairsPage`]. These are the factory pairs to claim the fees from.
+fn claim_and_collect(
+    deps: DepsMut,
+    env: Env,
+    assets: Vec<AssetWithLimit>,
+    page: PairsPage,
+) -> Result<Response, ContractError> {
+    let cfg = CONFIG.load(deps.storage)?;
+
+    let pairs: PairsResponse = deps.querier.query_wasm_smart(
+        &cfg.factory_contract,
+        &FactoryQueryMsg::Pairs {
+            start_after: page.start_after,
+            limit: page.limit,
+        },
+    )?;
+
+    let mut messages: Vec<CosmosMsg> = vec![];
+    let mut claimed_pairs: Vec<String> = vec![];
+    for pair in pairs.pairs {
+        // Pairs that don't support the fee accrual fail the query
+        let protocol_fees: ProtocolFeesResponse = match deps
+            .querier
+            .query_wasm_smart(&pair.contract_addr, &PairQueryMsg::ProtocolFees {})
+        {
+            Ok(protocol_fees) => protocol_fees,
+            Err(_) => continue,
+        };
+
+        if protocol_fees.assets.iter().all(|a| a.amount.is_zero()) {
+            continue;
+        }
+
+        messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: pair.contract_addr.to_string(),
+            msg: to_binary(&PairExecuteMsg::ClaimProtocolFees {})?,
+            funds: vec![],
+        }));
+        claimed_pairs.push(pair.contract_addr.to_string());
+    }
+
+    // The swaps must see the claimed fees, so they are collected in a separate call
+    messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: env.contract.address.to_string(),
+        msg: to_binary(&ExecuteMsg::Collect {
+            assets,
+            claim_from_pairs: None,
+        })?,
+        funds: vec![],
+    }));
+
+    Ok(Response::new()
+        .add_messages(messages)
+        .add_attribute("action", "claim_protocol_fees")
+        .add_attribute("pairs", claimed_pairs.join(",")))
+}
+
 /// ## Description
 /// This enum describes available token types that can be used as a SwapTarget.
 enum SwapTarget {
@@ -49,6 +49,8 @@ pub fn build_swap_msg(
                 belief_price: None,
                 max_spread: Some(cfg.max_spread),
                 to: None,
+                min_return: None,
+                deadline: None,
             })?,
             funds: vec![Coin {
                 denom: from.to_string(),
@@ -65,6 +67,8 @@ pub fn build_swap_msg(
                     belief_price: None,
                     max_spread: Some(cfg.max_spread),
                     to: None,
+                    min_return: None,
+                    deadline: None,
                 })?,
             })?,
             funds: vec![],
@@ -4,8 +4,10 @@ use astroport::asset::{
 };
 use astroport::factory::{PairConfig, PairType, UpdateAddr};
 use astroport::maker::{
-    AssetWithLimit, BalancesResponse, ConfigResponse, ExecuteMsg, InstantiateMsg, QueryMsg,
+    AssetWithLimit, BalancesResponse, ConfigResponse, ExecuteMsg, InstantiateMsg, PairsPage,
+    QueryMsg,
 };
+use astroport::pair::ProtocolFeesResponse;
 use astroport::token::InstantiateMsg as TokenInstantiateMsg;
 use cosmwasm_std::testing::{mock_env, MockApi, MockStorage};
 use cosmwasm_std::{
@@ -99,6 +101,7 @@ fn instantiate_contracts(
             maker_fee_bps: 0,
             is_disabled: false,
             is_generator_disabled: false,
+            maker_fee_as_lp: false,
         }],
         token_code_id: 1u64,
         fee_address: None,
@@ -338,6 +341,7 @@ fn create_pair(
             contract_addr: factory_instance.clone().to_string(),
             msg: to_binary(&astroport::factory::QueryMsg::Pair {
                 asset_infos: asset_infos.clone(),
+                pair_type: None,
             })
             .unwrap(),
         }))
@@ -386,6 +390,8 @@ fn create_pair(
                 slippage_tolerance: None,
                 auto_stake: None,
                 receiver: None,
+                min_lp_to_receive: None,
+                deadline: None,
             },
             &funds,
         )
@@ -587,7 +593,10 @@ fn test_maker_collect(
         .execute_contract(
             Addr::unchecked("anyone"),
             maker_instance.clone(),
-            &ExecuteMsg::Collect { assets },
+            &ExecuteMsg::Collect {
+                assets,
+                claim_from_pairs: None,
+            },
             &[],
         )
         .unwrap();
@@ -1115,7 +1124,10 @@ fn collect_err_no_swap_pair() {
         )
         .unwrap();
 
-    let msg = ExecuteMsg::Collect { assets };
+    let msg = ExecuteMsg::Collect {
+        assets,
+        claim_from_pairs: None,
+    };
 
     let e = router
         .execute_contract(maker_instance.clone(), maker_instance.clone(), &msg, &[])
@@ -1462,6 +1474,7 @@ fn collect_with_asset_limit() {
             maker_instance.clone(),
             &ExecuteMsg::Collect {
                 assets: assets_with_duplicate.clone(),
+                claim_from_pairs: None,
             },
             &[],
         )
@@ -1474,6 +1487,7 @@ fn collect_with_asset_limit() {
             maker_instance.clone(),
             &ExecuteMsg::Collect {
                 assets: assets.clone(),
+                claim_from_pairs: None,
             },
             &[],
         )
@@ -1802,7 +1816,10 @@ fn distribute_initially_accrued_fees() {
         .execute_contract(
             Addr::unchecked("anyone"),
             maker_instance.clone(),
-            &ExecuteMsg::Collect { assets },
+            &ExecuteMsg::Collect {
+                assets,
+                claim_from_pairs: None,
+            },
             &[],
         )
         .unwrap();
@@ -1853,6 +1870,7 @@ fn distribute_initially_accrued_fees() {
             maker_instance.clone(),
             &ExecuteMsg::Collect {
                 assets: assets.clone(),
+                claim_from_pairs: None,
             },
             &[],
         )
@@ -1869,6 +1887,7 @@ fn distribute_initially_accrued_fees() {
             maker_instance.clone(),
             &ExecuteMsg::Collect {
                 assets: assets.clone(),
+                claim_from_pairs: None,
             },
             &[],
         )
@@ -1883,6 +1902,7 @@ fn distribute_initially_accrued_fees() {
             maker_instance.clone(),
             &ExecuteMsg::Collect {
                 assets: assets.clone(),
+                claim_from_pairs: None,
             },
             &[],
         )
@@ -1908,6 +1928,7 @@ fn distribute_initially_accrued_fees() {
             maker_instance.clone(),
             &ExecuteMsg::Collect {
                 assets: assets.clone(),
+                claim_from_pairs: None,
             },
             &[],
         )
@@ -1941,6 +1962,7 @@ fn distribute_initially_accrued_fees() {
             maker_instance.clone(),
             &ExecuteMsg::Collect {
                 assets: assets.clone(),
+                claim_from_pairs: None,
             },
             &[],
         )
@@ -1966,6 +1988,7 @@ fn distribute_initially_accrued_fees() {
             maker_instance.clone(),
             &ExecuteMsg::Collect {
                 assets: assets.clone(),
+                claim_from_pairs: None,
             },
             &[],
         )
@@ -1995,7 +2018,10 @@ fn distribute_initially_accrued_fees() {
         .execute_contract(
             Addr::unchecked("anyone"),
             maker_instance.clone(),
-            &ExecuteMsg::Collect { assets },
+            &ExecuteMsg::Collect {
+                assets,
+                claim_from_pairs: None,
+            },
             &[],
         )
         .unwrap();
@@ -2015,3 +2041,191 @@ fn distribute_initially_accrued_fees() {
         .iter()
         .any(|a| a.key == "preupgrade_astro_distribution"));
 }
+
+#[test]
+fn collect_accrued_protocol_fees() {
+    let mut router = mock_app();
+    let owner = Addr::unchecked("owner");
+    let staking = Addr::unchecked("staking");
+    let governance_percent = Uint64::new(10);
+    let trader = Addr::unchecked("trader");
+
+    let (astro_token_instance, factory_instance, maker_instance, governance_instance) =
+        instantiate_contracts(
+            &mut router,
+            owner.clone(),
+            staking.clone(),
+            governance_percent,
+            None,
+        );
+
+    // The Maker receives a part of the swap fees
+    router
+        .execute_contract(
+            owner.clone(),
+            factory_instance.clone(),
+            &astroport::factory::ExecuteMsg::UpdateConfig {
+                token_code_id: None,
+                fee_address: Some(maker_instance.to_string()),
+                generator_address: None,
+                whitelist_code_id: None,
+            },
+            &[],
+        )
+        .unwrap();
+    let pair_config: astroport::factory::ConfigResponse = router
+        .wrap()
+        .query_wasm_smart(&factory_instance, &astroport::factory::QueryMsg::Config {})
+        .unwrap();
+    router
+        .execute_contract(
+            owner.clone(),
+            factory_instance.clone(),
+            &astroport::factory::ExecuteMsg::UpdatePairConfig {
+                config: PairConfig {
+                    total_fee_bps: 30,
+                    maker_fee_bps: 3333,
+                    ..pair_config.pair_configs[0].clone()
+                },
+            },
+            &[],
+        )
+        .unwrap();
+
+    let uusd_asset = String::from(UUSD_DENOM);
+    let uluna_asset = String::from(ULUNA_DENOM);
+    let astro_pair = create_pair(
+        &mut router,
+        owner.clone(),
+        Addr::unchecked("user0000"),
+        &factory_instance,
+        [
+            native_asset(uusd_asset.clone(), Uint128::from(100_000_u128)),
+            token_asset(astro_token_instance.clone(), Uint128::from(100_000_u128)),
+        ],
+    );
+    create_pair(
+        &mut router,
+        owner.clone(),
+        Addr::unchecked("user0001"),
+        &factory_instance,
+        [
+            native_asset(uluna_asset, Uint128::from(100_000_u128)),
+            native_asset(uusd_asset.clone(), Uint128::from(100_000_u128)),
+        ],
+    );
+
+    router
+        .execute_contract(
+            owner.clone(),
+            maker_instance.clone(),
+            &ExecuteMsg::EnableRewards { blocks: 1 },
+            &[],
+        )
+        .unwrap();
+
+    // Only the factory owner can enable the accrual in the pair
+    let msg = astroport::pair::ExecuteMsg::SetProtocolFeeAccrual { enabled: true };
+    router
+        .execute_contract(trader.clone(), astro_pair.contract_addr.clone(), &msg, &[])
+        .unwrap_err();
+    router
+        .execute_contract(owner.clone(), astro_pair.contract_addr.clone(), &msg, &[])
+        .unwrap();
+
+    // The Maker fee of the swap stays in the pair
+    let offer_amount = Uint128::from(10_000_u128);
+    router
+        .init_bank_balance(
+            &trader,
+            vec![Coin {
+                denom: uusd_asset.clone(),
+                amount: offer_amount,
+            }],
+        )
+        .unwrap();
+    router
+        .execute_contract(
+            trader.clone(),
+            astro_pair.contract_addr.clone(),
+            &astroport::pair::ExecuteMsg::Swap {
+                offer_asset: native_asset(uusd_asset.clone(), offer_amount),
+                belief_price: None,
+                max_spread: Some(Decimal::percent(50)),
+                to: None,
+                min_return: None,
+                deadline: None,
+            },
+            &[Coin {
+                denom: uusd_asset,
+                amount: offer_amount,
+            }],
+        )
+        .unwrap();
+
+    let protocol_fees: ProtocolFeesResponse = router
+        .wrap()
+        .query_wasm_smart(
+            &astro_pair.contract_addr,
+            &astroport::pair::QueryMsg::ProtocolFees {},
+        )
+        .unwrap();
+    assert!(protocol_fees.accrual_enabled);
+    let maker_fee_amount = protocol_fees.assets[1].amount;
+    assert!(!maker_fee_amount.is_zero());
+    check_balance(
+        &mut router,
+        maker_instance.clone(),
+        astro_token_instance.clone(),
+        Uint128::zero(),
+    );
+
+    // The Maker claims the fees from all the factory pairs and distributes them in one call
+    router
+        .execute_contract(
+            Addr::unchecked("anyone"),
+            maker_instance.clone(),
+            &ExecuteMsg::Collect {
+                assets: vec![AssetWithLimit {
+                    info: token_asset_info(astro_token_instance.clone()),
+                    limit: None,
+                }],
+                claim_from_pairs: Some(PairsPage {
+                    start_after: None,
+                    limit: None,
+                }),
+            },
+            &[],
+        )
+        .unwrap();
+
+    let protocol_fees: ProtocolFeesResponse = router
+        .wrap()
+        .query_wasm_smart(
+            &astro_pair.contract_addr,
+            &astroport::pair::QueryMsg::ProtocolFees {},
+        )
+        .unwrap();
+    assert_eq!(protocol_fees.assets[1].amount, Uint128::zero());
+
+    let governance_amount =
+        maker_fee_amount.multiply_ratio(Uint128::from(governance_percent), Uint128::new(100));
+    check_balance(
+        &mut router,
+        maker_instance,
+        astro_token_instance.clone(),
+        Uint128::zero(),
+    );
+    check_balance(
+        &mut router,
+        governance_instance,
+        astro_token_instance.clone(),
+        governance_amount,
+    );
+    check_balance(
+        &mut router,
+        staking,
+        astro_token_instance,
+        maker_fee_amount - governance_amount,
+    );
+}
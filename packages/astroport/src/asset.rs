@@ -309,6 +309,46 @@ impl PairInfo {
     }
 }
 
+/// ## Description
+/// This structure stores the main parameters for an Astroport pool with more than two assets
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct MultiPairInfo {
+    /// Asset information for the assets in the pool
+    pub asset_infos: Vec<AssetInfo>,
+    /// Pair contract address
+    pub contract_addr: Addr,
+    /// Pair LP token address
+    pub liquidity_token: Addr,
+    /// The pool type available in [`PairType`]
+    pub pair_type: PairType,
+}
+
+impl MultiPairInfo {
+    /// ## Description
+    /// Returns the balance for each asset in the pool.
+    /// ## Params
+    /// * **self** is the type of the caller object
+    ///
+    /// * **querier** is an object of type [`QuerierWrapper`]
+    ///
+    /// * **contract_addr** is pair's pool address.
+    pub fn query_pools(
+        &self,
+        querier: &QuerierWrapper,
+        contract_addr: Addr,
+    ) -> StdResult<Vec<Asset>> {
+        self.asset_infos
+            .iter()
+            .map(|asset_info| {
+                Ok(Asset {
+                    amount: asset_info.query_pool(querier, contract_addr.clone())?,
+                    info: asset_info.clone(),
+                })
+            })
+            .collect()
+    }
+}
+
 /// ## Description
 /// Returns a lowercased, validated address upon success. Otherwise returns [`Err`]
 /// ## Params
@@ -330,11 +370,11 @@ const TOKEN_SYMBOL_MAX_LENGTH: usize = 4;
 /// ## Description
 /// Returns a formatted LP token name
 /// ## Params
-/// * **asset_infos** is an array with two items the type of [`AssetInfo`].
+/// * **asset_infos** is a slice with items of type [`AssetInfo`].
 ///
 /// * **querier** is an object of type [`QuerierWrapper`].
 pub fn format_lp_token_name(
-    asset_infos: [AssetInfo; 2],
+    asset_infos: &[AssetInfo],
     querier: &QuerierWrapper,
 ) -> StdResult<String> {
     let mut short_symbols: Vec<String> = vec![];
@@ -345,13 +385,13 @@ pub fn format_lp_token_name(
                 short_symbol = denom.chars().take(TOKEN_SYMBOL_MAX_LENGTH).collect();
             }
             AssetInfo::Token { contract_addr } => {
-                let token_symbol = query_token_symbol(querier, contract_addr)?;
+                let token_symbol = query_token_symbol(querier, contract_addr.clone())?;
                 short_symbol = token_symbol.chars().take(TOKEN_SYMBOL_MAX_LENGTH).collect();
             }
         }
         short_symbols.push(short_symbol);
     }
-    Ok(format!("{}-{}-LP", short_symbols[0], short_symbols[1]).to_uppercase())
+    Ok(format!("{}-LP", short_symbols.join("-")).to_uppercase())
 }
 
 /// ## Description
@@ -8,9 +8,13 @@ use std::fmt::{Display, Formatter, Result};
 /// This enum describes available pair types.
 /// ## Available pool types
 /// ```
-/// # use astroport::factory::PairType::{Custom, Stable, Xyk};
+/// # use astroport::factory::PairType::{Concentrated, Custom, Stable, Lbp, StableMulti, Weighted, Xyk};
 /// Xyk {};
 /// Stable {};
+/// StableMulti {};
+/// Weighted {};
+/// Lbp {};
+/// Concentrated {};
 /// Custom(String::from("Custom"));
 /// ```
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -20,6 +24,14 @@ pub enum PairType {
     Xyk {},
     /// Stable pair type
     Stable {},
+    /// Stable pool type with more than two assets
+    StableMulti {},
+    /// Weighted pair type
+    Weighted {},
+    /// Liquidity bootstrapping pair type
+    Lbp {},
+    /// Concentrated liquidity pair type
+    Concentrated {},
     /// Custom pair type
     Custom(String),
 }
@@ -30,6 +42,10 @@ impl Display for PairType {
         match self {
             PairType::Xyk {} => fmt.write_str("xyk"),
             PairType::Stable {} => fmt.write_str("stable"),
+            PairType::StableMulti {} => fmt.write_str("stable_multi"),
+            PairType::Weighted {} => fmt.write_str("weighted"),
+            PairType::Lbp {} => fmt.write_str("lbp"),
+            PairType::Concentrated {} => fmt.write_str("concentrated"),
             PairType::Custom(pair_type) => fmt.write_str(format!("custom-{}", pair_type).as_str()),
         }
     }
@@ -53,6 +69,9 @@ pub struct PairConfig {
     /// Setting this to true means that pairs of this type will not be able
     /// to get an ASTRO generator
     pub is_generator_disabled: bool,
+    /// Setting this to true means that pairs of this type collect the Maker fee by minting LP tokens
+    /// to the fee address on liquidity events instead of sending a part of every swap fee. Only XYK pairs support it
+    pub maker_fee_as_lp: bool,
 }
 
 impl PairConfig {
@@ -65,6 +84,78 @@ impl PairConfig {
     }
 }
 
+/// ## Description
+/// This structure stores fee settings that override the pair type fees for a specific pair.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct FeeOverride {
+    /// The total fees (in bps) charged by the pair
+    pub total_fee_bps: u16,
+    /// The amount of fees (in bps) collected by the Maker contract from the pair
+    pub maker_fee_bps: u16,
+}
+
+impl FeeOverride {
+    /// ## Description
+    /// This method is used to check fee bps.
+    /// ## Params
+    /// `&self` is the type of the caller object.
+    pub fn valid_fee_bps(&self) -> bool {
+        self.total_fee_bps <= 10_000 && self.maker_fee_bps <= 10_000
+    }
+}
+
+/// ## Description
+/// This enum describes the trading states of a pair that the factory can set to halt it during an incident.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum PairStatus {
+    /// Swaps, deposits and withdrawals are allowed
+    Active,
+    /// Swaps are not allowed
+    SwapsPaused,
+    /// Deposits are not allowed
+    DepositsPaused,
+    /// Only withdrawals are allowed
+    WithdrawOnly,
+}
+
+impl PairStatus {
+    /// ## Description
+    /// Returns true if the pair allows swaps.
+    /// ## Params
+    /// `&self` is the type of the caller object.
+    pub fn swaps_allowed(&self) -> bool {
+        matches!(self, PairStatus::Active | PairStatus::DepositsPaused)
+    }
+
+    /// ## Description
+    /// Returns true if the pair allows deposits.
+    /// ## Params
+    /// `&self` is the type of the caller object.
+    pub fn deposits_allowed(&self) -> bool {
+        matches!(self, PairStatus::Active | PairStatus::SwapsPaused)
+    }
+}
+
+// `#[default]` on enum variants requires a newer toolchain than the pinned one
+#[allow(clippy::derivable_impls)]
+impl Default for PairStatus {
+    fn default() -> Self {
+        PairStatus::Active
+    }
+}
+
+impl Display for PairStatus {
+    fn fmt(&self, fmt: &mut Formatter) -> Result {
+        match self {
+            PairStatus::Active => fmt.write_str("active"),
+            PairStatus::SwapsPaused => fmt.write_str("swaps_paused"),
+            PairStatus::DepositsPaused => fmt.write_str("deposits_paused"),
+            PairStatus::WithdrawOnly => fmt.write_str("withdraw_only"),
+        }
+    }
+}
+
 /// ## Description
 /// This structure stores the basic settings for creating a new factory contract.
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -113,10 +204,67 @@ pub enum ExecuteMsg {
         /// Optional binary serialised parameters for custom pool types
         init_params: Option<Binary>,
     },
+    /// CreateMultiPair instantiates a new pair contract for a pool with more than two assets.
+    CreateMultiPair {
+        /// The pair type (exposed in [`PairType`])
+        pair_type: PairType,
+        /// The assets to create the pool for
+        asset_infos: Vec<AssetInfo>,
+        /// Optional binary serialised parameters for custom pool types
+        init_params: Option<Binary>,
+    },
+    /// SetPairFeeOverride sets fees for a specific pair that take precedence over its pair type fees.
+    SetPairFeeOverride {
+        /// The pair contract address
+        pair_addr: String,
+        /// The total fees (in bps) charged by the pair
+        total_fee_bps: u16,
+        /// The amount of fees (in bps) collected by the Maker contract from the pair
+        maker_fee_bps: u16,
+    },
+    /// RemovePairFeeOverride removes the fee override of a specific pair so it uses its pair type fees again.
+    RemovePairFeeOverride {
+        /// The pair contract address
+        pair_addr: String,
+    },
+    /// SetGuardian sets the address that can halt pairs in an incident or removes it.
+    SetGuardian {
+        /// The new guardian address
+        guardian: Option<String>,
+    },
+    /// SetPairStatus sets the trading state of a specific pair. The guardian can only halt a pair, while the owner
+    /// can set any state.
+    SetPairStatus {
+        /// The pair contract address
+        pair_addr: String,
+        /// The new state of the pair
+        status: PairStatus,
+    },
+    /// RefreshPairs pushes the current fee and Generator information to the next batch of pairs whose cached
+    /// parameters are outdated after a config update. Anyone can execute it.
+    RefreshPairs {
+        /// The maximum number of pairs to refresh. It is an [`Option`] type.
+        limit: Option<u32>,
+    },
+    /// MigratePairs moves the next batch of pairs registered before v1.3.0 to the storage where pairs are also
+    /// keyed by their type. The factory accepts no other messages until all pairs are moved. Anyone can execute it.
+    MigratePairs {
+        /// The maximum number of pairs to move. It is an [`Option`] type.
+        limit: Option<u32>,
+    },
     /// Deregister removes a previously created pair.
     Deregister {
         /// The assets for which we deregister a pool
         asset_infos: [AssetInfo; 2],
+        /// The type of the pool to deregister. It can be omitted if only one pool exists for the assets
+        pair_type: Option<PairType>,
+    },
+    /// DeregisterMultiPair removes a previously created pool with more than two assets.
+    DeregisterMultiPair {
+        /// The assets for which we deregister a pool. The order of the assets doesn't matter
+        asset_infos: Vec<AssetInfo>,
+        /// The type of the pool to deregister. It can be omitted if only one pool exists for the assets
+        pair_type: Option<PairType>,
     },
     /// ProposeNewOwner creates a proposal to change contract ownership.
     /// The validity period for the proposal is set in the `expires_in` variable.
@@ -143,6 +291,22 @@ pub enum QueryMsg {
     Pair {
         /// The assets for which we return a pair
         asset_infos: [AssetInfo; 2],
+        /// The type of the pair. If not specified, the pool is picked by pair type in this order: xyk, stable,
+        /// stable multi, weighted, concentrated, custom and finally LBP
+        pair_type: Option<PairType>,
+    },
+    /// PairsByAssets returns all pools registered for the specified assets in a [`PairsResponse`] object.
+    PairsByAssets {
+        /// The assets for which we return pools
+        asset_infos: [AssetInfo; 2],
+    },
+    /// MultiPair returns information about a specific pool with more than two assets in a [`super::asset::MultiPairInfo`] object.
+    MultiPair {
+        /// The assets for which we return a pool
+        asset_infos: Vec<AssetInfo>,
+        /// The type of the pool. If not specified, the pool is picked by pair type in this order: xyk, stable,
+        /// stable multi, weighted, concentrated, custom and finally LBP
+        pair_type: Option<PairType>,
     },
     /// Pairs returns an array of pairs and their information according to the specified parameters in `start_after` and `limit` variables.
     Pairs {
@@ -155,6 +319,18 @@ pub enum QueryMsg {
     FeeInfo {
         /// The pair type for which we return fee information. Pair type is a [`PairType`] struct
         pair_type: PairType,
+        /// The pair contract address. If specified and the pair has a fee override, the override is returned
+        pair_addr: Option<String>,
+    },
+    /// PairFeeOverride returns the fee override of a specific pair in an [`Option`] of type [`FeeOverride`]
+    PairFeeOverride {
+        /// The pair contract address
+        pair_addr: String,
+    },
+    /// PairStatus returns the trading state of a specific pair in a [`PairStatus`] object
+    PairStatus {
+        /// The pair contract address
+        pair_addr: String,
     },
 }
 
@@ -174,6 +350,8 @@ pub struct ConfigResponse {
     pub generator_address: Option<Addr>,
     /// CW1 whitelist contract code id used to store 3rd party rewards for staking Astroport LP tokens
     pub whitelist_code_id: u64,
+    /// The address that can halt pairs in an incident
+    pub guardian: Option<Addr>,
 }
 
 /// ## Description
@@ -201,6 +379,8 @@ pub struct FeeInfoResponse {
     pub total_fee_bps: u16,
     /// Amount of fees (in bps) sent to the Maker contract
     pub maker_fee_bps: u16,
+    /// Whether the Maker fee is collected by minting LP tokens to the fee address
+    pub maker_fee_as_lp: bool,
 }
 
 /// ## Description
@@ -4,14 +4,20 @@ pub mod factory;
 pub mod generator;
 pub mod generator_proxy;
 pub mod maker;
+pub mod observation;
 pub mod oracle;
 pub mod pair;
+pub mod pair_concentrated;
+pub mod pair_lbp;
 pub mod pair_stable_bluna;
+pub mod pair_stable_multi;
+pub mod pair_weighted;
 pub mod querier;
 pub mod router;
 pub mod staking;
 pub mod token;
 pub mod vesting;
+pub mod weighted_math;
 pub mod whitelist;
 pub mod xastro_token;
 
@@ -62,5 +68,36 @@ mod decimal_checked_ops {
     }
 }
 
+mod uint256_wrapping_ops {
+    use crate::U256;
+    use cosmwasm_std::Uint256;
+    pub trait Uint256WrappingOps {
+        fn wrapping_add(self, other: Uint256) -> Uint256;
+        fn wrapping_sub(self, other: Uint256) -> Uint256;
+    }
+
+    impl Uint256WrappingOps for Uint256 {
+        fn wrapping_add(self, other: Uint256) -> Uint256 {
+            let (sum, _) = to_u256(self).overflowing_add(to_u256(other));
+            from_u256(sum)
+        }
+        fn wrapping_sub(self, other: Uint256) -> Uint256 {
+            let (difference, _) = to_u256(self).overflowing_sub(to_u256(other));
+            from_u256(difference)
+        }
+    }
+
+    fn to_u256(value: Uint256) -> U256 {
+        U256::from_big_endian(&value.to_be_bytes())
+    }
+
+    fn from_u256(value: U256) -> Uint256 {
+        let mut bytes = [0u8; 32];
+        value.to_big_endian(&mut bytes);
+        Uint256::from_be_bytes(bytes)
+    }
+}
+
 pub use decimal_checked_ops::DecimalCheckedOps;
+pub use uint256_wrapping_ops::Uint256WrappingOps;
 pub use uints::U256;
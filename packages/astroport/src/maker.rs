@@ -33,6 +33,8 @@ pub enum ExecuteMsg {
     Collect {
         /// The assets to swap to ASTRO
         assets: Vec<AssetWithLimit>,
+        /// The page of factory pairs to claim the accrued Maker fees from before the swaps
+        claim_from_pairs: Option<PairsPage>,
     },
     /// Updates general settings
     UpdateConfig {
@@ -131,3 +133,13 @@ pub struct AssetWithLimit {
     /// The amount of tokens to swap
     pub limit: Option<Uint128>,
 }
+
+/// ## Description
+/// This struct describes a page of the pairs registered in the factory.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PairsPage {
+    /// The pair to start reading after
+    pub start_after: Option<[AssetInfo; 2]>,
+    /// The number of pairs to read
+    pub limit: Option<u32>,
+}
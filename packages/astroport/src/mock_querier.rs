@@ -236,7 +236,7 @@ impl DefaultQueryHandler {
                 contract_addr: _,
                 msg,
             }) => match from_binary(&msg).unwrap() {
-                FactoryQueryMsg::Pair { asset_infos } => {
+                FactoryQueryMsg::Pair { asset_infos, .. } => {
                     let key = asset_infos[0].to_string() + asset_infos[1].to_string().as_str();
                     match self.astroport_factory_querier.pairs.get(&key) {
                         Some(v) => SystemResult::Ok(to_binary(&v).into()),
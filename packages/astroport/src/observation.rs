@@ -0,0 +1,194 @@
+use crate::pair::Observation;
+use crate::Uint256WrappingOps;
+use cosmwasm_std::{StdError, StdResult, Storage};
+use cw_storage_plus::{Item, Map, U64Key};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// The number of price observations that a pair keeps by default
+pub const DEFAULT_OBSERVATIONS_SIZE: u32 = 100;
+/// The maximum number of price observations that a pair can keep
+pub const MAX_OBSERVATIONS_SIZE: u32 = 10_000;
+
+/// ## Description
+/// This structure describes the bounds of the price observation buffer. Observations are stored under
+/// sequential indexes and the oldest ones are removed when the buffer is full.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ObservationBuffer {
+    /// The maximum number of observations kept in the buffer
+    pub size: u32,
+    /// The index of the oldest stored observation
+    pub head: u64,
+    /// The index under which the next observation will be stored
+    pub tail: u64,
+}
+
+/// ## Description
+/// Stores the bounds of the price observation buffer
+pub const OBSERVATION_BUFFER: Item<ObservationBuffer> = Item::new("observation_buffer");
+
+/// ## Description
+/// Stores the price observations by their index in the observation buffer
+pub const OBSERVATIONS: Map<U64Key, Observation> = Map::new("observations");
+
+/// ## Description
+/// Returns the price observation buffer. Pairs that were instantiated before the buffer was introduced
+/// start with an empty buffer of the default size.
+/// ## Params
+/// * **storage** is an object of type [`Storage`].
+pub fn load_observation_buffer(storage: &dyn Storage) -> StdResult<ObservationBuffer> {
+    Ok(OBSERVATION_BUFFER
+        .may_load(storage)?
+        .unwrap_or(ObservationBuffer {
+            size: DEFAULT_OBSERVATIONS_SIZE,
+            head: 0,
+            tail: 0,
+        }))
+}
+
+/// ## Description
+/// Stores a new price observation and removes the oldest observations that don't fit in the buffer.
+/// ## Params
+/// * **storage** is an object of type [`Storage`].
+///
+/// * **observation** is an object of type [`Observation`]. This is the observation to store.
+pub fn write_observation(storage: &mut dyn Storage, observation: Observation) -> StdResult<()> {
+    let mut buffer = load_observation_buffer(storage)?;
+    OBSERVATIONS.save(storage, U64Key::new(buffer.tail), &observation)?;
+    buffer.tail += 1;
+    trim_observations(storage, &mut buffer);
+    OBSERVATION_BUFFER.save(storage, &buffer)
+}
+
+/// ## Description
+/// Sets the maximum number of price observations kept in the buffer and removes the oldest observations
+/// that don't fit in it anymore. The size must be validated by the caller.
+/// ## Params
+/// * **storage** is an object of type [`Storage`].
+///
+/// * **size** is an object of type [`u32`]. This is the new buffer size.
+pub fn set_observations_size(storage: &mut dyn Storage, size: u32) -> StdResult<()> {
+    let mut buffer = load_observation_buffer(storage)?;
+    buffer.size = size;
+    trim_observations(storage, &mut buffer);
+    OBSERVATION_BUFFER.save(storage, &buffer)
+}
+
+/// ## Description
+/// Removes the oldest observations until the number of stored observations fits in the buffer size.
+/// ## Params
+/// * **storage** is an object of type [`Storage`].
+///
+/// * **buffer** is an object of type [`ObservationBuffer`]. It is updated in place.
+fn trim_observations(storage: &mut dyn Storage, buffer: &mut ObservationBuffer) {
+    while buffer.tail - buffer.head > buffer.size as u64 {
+        OBSERVATIONS.remove(storage, U64Key::new(buffer.head));
+        buffer.head += 1;
+    }
+}
+
+/// ## Description
+/// Returns the cumulative prices at each of the specified number of seconds ago. Cumulative prices after
+/// the last price accumulation are computed by `extrapolate` and cumulative prices between two stored
+/// observations are interpolated linearly.
+/// ## Params
+/// * **storage** is an object of type [`Storage`].
+///
+/// * **block_time** is an object of type [`u64`]. This is the current block time.
+///
+/// * **last** is an object of type [`Observation`]. These are the cumulative prices at the last price accumulation.
+///
+/// * **seconds_ago** is a [`Vec`] of type [`u64`]. These are the observation times relative to the current block time.
+///
+/// * **extrapolate** is a closure that returns the cumulative prices at a time that is not before
+/// the last price accumulation.
+pub fn observe<F>(
+    storage: &dyn Storage,
+    block_time: u64,
+    last: Observation,
+    seconds_ago: Vec<u64>,
+    extrapolate: F,
+) -> StdResult<Vec<Observation>>
+where
+    F: Fn(u64) -> StdResult<Observation>,
+{
+    let buffer = load_observation_buffer(storage)?;
+
+    seconds_ago
+        .into_iter()
+        .map(|seconds| {
+            let target = block_time.checked_sub(seconds).ok_or_else(|| {
+                StdError::generic_err("The observation time is before the genesis block")
+            })?;
+
+            if target >= last.timestamp {
+                extrapolate(target)
+            } else {
+                interpolate_observation(storage, &buffer, last.clone(), target)
+            }
+        })
+        .collect()
+}
+
+/// ## Description
+/// Returns the cumulative prices at a time that is before the last price accumulation. The prices are
+/// interpolated between the closest stored observations.
+/// ## Params
+/// * **storage** is an object of type [`Storage`].
+///
+/// * **buffer** is an object of type [`ObservationBuffer`].
+///
+/// * **last** is an object of type [`Observation`]. These are the cumulative prices at the last price accumulation.
+///
+/// * **target** is an object of type [`u64`]. This is the time to return the cumulative prices at.
+fn interpolate_observation(
+    storage: &dyn Storage,
+    buffer: &ObservationBuffer,
+    last: Observation,
+    target: u64,
+) -> StdResult<Observation> {
+    // Binary search for the latest observation at or before the target time
+    let (mut low, mut high) = (buffer.head, buffer.tail);
+    let mut before: Option<(u64, Observation)> = None;
+    while low < high {
+        let mid = low + (high - low) / 2;
+        let observation = OBSERVATIONS.load(storage, U64Key::new(mid))?;
+        if observation.timestamp <= target {
+            before = Some((mid, observation));
+            low = mid + 1;
+        } else {
+            high = mid;
+        }
+    }
+
+    let (index, before) = before.ok_or_else(|| {
+        StdError::generic_err("The observation time is before the oldest stored observation")
+    })?;
+    if before.timestamp == target {
+        return Ok(before);
+    }
+
+    let after = if index + 1 < buffer.tail {
+        OBSERVATIONS.load(storage, U64Key::new(index + 1))?
+    } else {
+        last
+    };
+
+    let time_elapsed = target - before.timestamp;
+    let time_between = after.timestamp - before.timestamp;
+    Ok(Observation {
+        timestamp: target,
+        price0_cumulative: before.price0_cumulative.wrapping_add(
+            after
+                .price0_cumulative
+                .wrapping_sub(before.price0_cumulative)
+                .multiply_ratio(time_elapsed, time_between),
+        ),
+        price1_cumulative: before.price1_cumulative.wrapping_add(
+            after
+                .price1_cumulative
+                .wrapping_sub(before.price1_cumulative)
+                .multiply_ratio(time_elapsed, time_between),
+        ),
+    })
+}
@@ -3,7 +3,7 @@ use serde::{Deserialize, Serialize};
 
 use crate::asset::{Asset, AssetInfo};
 
-use cosmwasm_std::{Binary, Decimal, Uint128};
+use cosmwasm_std::{Addr, Binary, Decimal, Uint128, Uint256};
 use cw20::Cw20ReceiveMsg;
 
 /// The default swap slippage
@@ -13,6 +13,11 @@ pub const MAX_ALLOWED_SLIPPAGE: &str = "0.5";
 
 // Decimal precision for TWAP results
 pub const TWAP_PRECISION: u8 = 6;
+// Decimal precision for 256-bit TWAP results
+pub const TWAP_PRECISION_U256: u8 = 18;
+
+/// The amount of LP tokens that is locked in the pair forever on the first liquidity provision
+pub const MINIMUM_LIQUIDITY: Uint128 = Uint128::new(1_000);
 
 /// ## Description
 /// This structure describes the parameters used for creating a contract.
@@ -46,6 +51,24 @@ pub enum ExecuteMsg {
         auto_stake: Option<bool>,
         /// The receiver of LP tokens
         receiver: Option<String>,
+        /// The minimum amount of LP tokens to receive, otherwise the transaction fails
+        min_lp_to_receive: Option<Uint128>,
+        /// The block time (in seconds) after which the transaction fails
+        deadline: Option<u64>,
+    },
+    /// ProvideSingleSided allows someone to provide liquidity using only one of the pool assets.
+    /// The optimal portion of the asset is swapped internally before LP tokens are minted
+    ProvideSingleSided {
+        /// The asset to provide
+        asset: Asset,
+        /// The minimum amount of LP tokens to receive, otherwise the transaction fails
+        min_lp_to_receive: Option<Uint128>,
+        /// Determines whether the LP tokens minted for the user is auto_staked in the Generator contract
+        auto_stake: Option<bool>,
+        /// The receiver of LP tokens
+        receiver: Option<String>,
+        /// The block time (in seconds) after which the transaction fails
+        deadline: Option<u64>,
     },
     /// Swap performs a swap in the pool
     Swap {
@@ -53,9 +76,72 @@ pub enum ExecuteMsg {
         belief_price: Option<Decimal>,
         max_spread: Option<Decimal>,
         to: Option<String>,
+        /// The minimum amount of ask assets to receive, otherwise the transaction fails
+        min_return: Option<Uint128>,
+        /// The block time (in seconds) after which the transaction fails
+        deadline: Option<u64>,
+    },
+    /// SwapExactOut performs a swap that returns exactly `ask_asset`. The other pool asset is offered:
+    /// native offer assets must be sent with the message and the excess is refunded,
+    /// token offer assets are pulled from the sender, so the pair must be allowed to spend them
+    SwapExactOut {
+        /// The asset to receive from the swap
+        ask_asset: Asset,
+        /// The maximum amount of offer assets to pay for the ask asset
+        max_offer: Uint128,
+        /// The receiver of the ask asset
+        to: Option<String>,
+        /// The block time (in seconds) after which the transaction fails
+        deadline: Option<u64>,
     },
     /// Update the pair configuration
     UpdateConfig { params: Binary },
+    /// FlashLoan sends an asset from the pool to a receiver contract and calls it back with a
+    /// [`FlashLoanReceiverMsg`]. The pool invariant plus the fee must be restored by the end of the transaction
+    FlashLoan {
+        /// The asset to borrow
+        asset: Asset,
+        /// The contract that receives the borrowed asset and the callback. Defaults to the sender
+        receiver: Option<String>,
+        /// The message that is passed to the receiver in the callback
+        msg: Binary,
+    },
+    /// Callback that checks that a flash loan was repaid. Can only be executed by the pair itself
+    AssertFlashLoanRepaid {},
+    /// Sync sets the tracked pool reserves to the current balances of the pair
+    Sync {},
+    /// Skim sends the balances of the pair that exceed the tracked pool reserves to a recipient
+    Skim {
+        /// The recipient of the excess assets. Defaults to the sender
+        to: Option<String>,
+    },
+    /// RefreshFactoryParams updates the fee and Generator information that the pair caches from the factory
+    RefreshFactoryParams {},
+    /// SetProtocolFeeAccrual switches between sending the Maker fee on every swap and accruing it in the pair.
+    /// Only the factory owner can execute this
+    SetProtocolFeeAccrual {
+        /// Whether the Maker fees are accrued in the pair
+        enabled: bool,
+    },
+    /// ClaimProtocolFees sends the Maker fees accrued in the pair to the fee address. Only the fee address can execute this
+    ClaimProtocolFees {},
+}
+
+/// ## Description
+/// This structure describes the callback message that a pair sends to a flash loan receiver.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum FlashLoanReceiverMsg {
+    /// The receiver must send assets back to the pair so that the pool invariant grows at least
+    /// by the value of `fee` paid in the borrowed asset. Repaying `asset` plus `fee` is always enough
+    FlashLoanCallback {
+        /// The borrowed asset
+        asset: Asset,
+        /// The flash loan fee in the borrowed asset
+        fee: Uint128,
+        /// The message that was specified by the borrower
+        msg: Binary,
+    },
 }
 
 /// ## Description
@@ -68,9 +154,41 @@ pub enum Cw20HookMsg {
         belief_price: Option<Decimal>,
         max_spread: Option<Decimal>,
         to: Option<String>,
+        /// The minimum amount of ask assets to receive, otherwise the transaction fails
+        min_return: Option<Uint128>,
+        /// The block time (in seconds) after which the transaction fails
+        deadline: Option<u64>,
     },
     /// Withdraw liquidity from the pool
-    WithdrawLiquidity {},
+    WithdrawLiquidity {
+        /// The minimum amounts of assets to receive, otherwise the transaction fails
+        min_assets_out: Option<Vec<Asset>>,
+        /// The block time (in seconds) after which the transaction fails
+        deadline: Option<u64>,
+    },
+    /// Swap the sent tokens for exactly `ask_asset`. The sent amount is the maximum offer amount
+    /// and the excess is sent back
+    SwapExactOut {
+        ask_asset: Asset,
+        to: Option<String>,
+        /// The block time (in seconds) after which the transaction fails
+        deadline: Option<u64>,
+    },
+    /// Withdraw liquidity from the pool in a single asset. Supported only by stableswap pools
+    WithdrawLiquidityOneCoin {
+        ask_asset_info: AssetInfo,
+        /// The minimum amount of the ask asset to receive, otherwise the transaction fails
+        min_return: Option<Uint128>,
+        /// The block time (in seconds) after which the transaction fails
+        deadline: Option<u64>,
+    },
+    /// Withdraw exact amounts of assets from the pool. The sent LP tokens are the maximum amount to burn
+    /// and the excess is sent back. Supported only by stableswap pools
+    WithdrawLiquidityImbalanced {
+        assets: Vec<Asset>,
+        /// The block time (in seconds) after which the transaction fails
+        deadline: Option<u64>,
+    },
 }
 
 /// ## Description
@@ -92,6 +210,50 @@ pub enum QueryMsg {
     ReverseSimulation { ask_asset: Asset },
     /// Returns information about the cumulative prices in a [`CumulativePricesResponse`] object
     CumulativePrices {},
+    /// Returns the Maker fees accrued in the pair in a [`ProtocolFeesResponse`] object
+    ProtocolFees {},
+    /// Returns the cumulative prices at the specified number of seconds ago (one per element
+    /// of `seconds_ago`) in an [`ObserveResponse`] object
+    Observe { seconds_ago: Vec<u64> },
+    /// Returns the price of one unit of `asset_info` in the other pool asset in a [`SpotPriceResponse`] object
+    SpotPrice { asset_info: AssetInfo },
+    /// Returns the price impact of swapping `offer_asset` in a [`PriceImpactResponse`] object
+    PriceImpact { offer_asset: Asset },
+    /// Returns the amount of `asset_info` that can be swapped before its price drops by `price_change`
+    /// in a [`DepthResponse`] object
+    Depth {
+        asset_info: AssetInfo,
+        price_change: Decimal,
+    },
+    /// Returns the amount of LP tokens minted for providing `assets` in a [`SimulateProvideResponse`] object
+    SimulateProvide {
+        assets: [Asset; 2],
+        slippage_tolerance: Option<Decimal>,
+    },
+    /// Returns the assets refunded for burning `lp_amount` LP tokens in a vector that contains objects of type [`Asset`]
+    SimulateWithdraw { lp_amount: Uint128 },
+    /// Returns the amount of `ask_asset_info` withdrawn for burning `lp_amount` LP tokens in a
+    /// [`SimulateWithdrawOneCoinResponse`] object
+    SimulateWithdrawOneCoin {
+        lp_amount: Uint128,
+        ask_asset_info: AssetInfo,
+    },
+    /// Returns the amount of LP tokens burned for withdrawing exact `assets` in a
+    /// [`SimulateWithdrawImbalancedResponse`] object
+    SimulateWithdrawImbalanced { assets: Vec<Asset> },
+    /// Returns the value of one LP token in the units of the stableswap invariant D in a
+    /// [`VirtualPriceResponse`] object
+    VirtualPrice {},
+    /// Returns the current stableswap invariant D in a [`CurrentDResponse`] object
+    CurrentD {},
+    /// Returns the amplification changes of a stableswap pool sorted by their identifiers in an
+    /// [`AmpHistoryResponse`] object
+    AmpHistory {
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    /// Returns the trading state of the pair set in the factory in a [`super::factory::PairStatus`] object
+    PairStatus {},
 }
 
 /// ## Description
@@ -104,6 +266,16 @@ pub struct PoolResponse {
     pub total_share: Uint128,
 }
 
+/// ## Description
+/// This struct is used to return a query result with the Maker fees accrued in a pair.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ProtocolFeesResponse {
+    /// Whether the pair accrues the Maker fees instead of sending them on every swap
+    pub accrual_enabled: bool,
+    /// The accrued fees that can be claimed by the fee address
+    pub assets: [Asset; 2],
+}
+
 /// ## Description
 /// This struct is used to return a query result with the general contract configuration.
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -150,6 +322,138 @@ pub struct CumulativePricesResponse {
     pub price0_cumulative_last: Uint128,
     /// The last value for the token1 cumulative price
     pub price1_cumulative_last: Uint128,
+    /// The last value for the token0 cumulative price with [`TWAP_PRECISION_U256`] decimals.
+    /// Pairs that don't track 256-bit cumulative prices don't set it
+    pub price0_cumulative_last_u256: Option<Uint256>,
+    /// The last value for the token1 cumulative price with [`TWAP_PRECISION_U256`] decimals.
+    /// Pairs that don't track 256-bit cumulative prices don't set it
+    pub price1_cumulative_last_u256: Option<Uint256>,
+}
+
+/// ## Description
+/// This structure is used to return a liquidity provision simulation response.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct SimulateProvideResponse {
+    /// The amount of LP tokens minted for the deposit
+    pub share: Uint128,
+    /// The fees charged for the part of the deposit that doesn't match the pool ratio. They are zero for pools
+    /// that don't charge fees on imbalanced deposits
+    pub fees: Vec<Asset>,
+}
+
+/// ## Description
+/// This structure is used to return a single asset withdrawal simulation response.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct SimulateWithdrawOneCoinResponse {
+    /// The amount of the ask asset withdrawn
+    pub return_amount: Uint128,
+    /// The fee charged because the withdrawal doesn't match the pool ratio
+    pub fee_amount: Uint128,
+}
+
+/// ## Description
+/// This structure is used to return an imbalanced withdrawal simulation response.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct SimulateWithdrawImbalancedResponse {
+    /// The amount of LP tokens burned for the withdrawal
+    pub lp_amount: Uint128,
+    /// The fees charged for the part of the withdrawal that doesn't match the pool ratio
+    pub fees: Vec<Asset>,
+}
+
+/// ## Description
+/// This structure is used to return a virtual price query response.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct VirtualPriceResponse {
+    /// The stableswap invariant D divided by the total amount of LP tokens
+    pub virtual_price: Decimal,
+}
+
+/// ## Description
+/// This structure is used to return a current D query response.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct CurrentDResponse {
+    /// The stableswap invariant D in the precision of the LP token
+    pub d: Uint128,
+}
+
+/// ## Description
+/// This structure describes a change of a stableswap pool's amplification.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct AmpRamp {
+    /// The identifier of the change
+    pub id: u64,
+    /// The address that changed the amplification
+    pub sender: Addr,
+    /// The amplification at the start of the change
+    pub from_amp: Decimal,
+    /// The amplification at the end of the change
+    pub to_amp: Decimal,
+    /// The block time (in seconds) when the change started
+    pub start_time: u64,
+    /// The block time (in seconds) when the amplification reaches `to_amp`
+    pub end_time: u64,
+}
+
+/// ## Description
+/// This structure is used to return an amplification history query response.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct AmpHistoryResponse {
+    /// The amplification changes sorted by their identifiers
+    pub history: Vec<AmpRamp>,
+}
+
+/// ## Description
+/// This structure is used to return a spot price query response.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct SpotPriceResponse {
+    /// The amount of the other pool asset for one unit of the asset, adjusted for the token decimals
+    pub price: Decimal,
+}
+
+/// ## Description
+/// This structure is used to return a price impact query response.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PriceImpactResponse {
+    /// The spot price of the offer asset in the ask asset before the swap
+    pub spot_price: Decimal,
+    /// The average price of the offer asset in the ask asset for the swap, excluding the commission
+    pub execution_price: Decimal,
+    /// The relative difference between the spot and the execution price
+    pub price_impact: Decimal,
+}
+
+/// ## Description
+/// This structure is used to return a depth query response.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct DepthResponse {
+    /// The amount of the asset that can be swapped before its price drops by the specified percentage
+    pub offer_amount: Uint128,
+    /// The amount of the other pool asset that is returned for `offer_amount` (minus the commission)
+    pub return_amount: Uint128,
+}
+
+/// ## Description
+/// This structure describes the 256-bit cumulative prices of a pool at a specific time.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Observation {
+    /// The block time (in seconds) of the observation
+    pub timestamp: u64,
+    /// The token0 cumulative price with [`TWAP_PRECISION_U256`] decimals in XYK pairs and
+    /// [`TWAP_PRECISION`] decimals in stableswap pairs
+    pub price0_cumulative: Uint256,
+    /// The token1 cumulative price with [`TWAP_PRECISION_U256`] decimals in XYK pairs and
+    /// [`TWAP_PRECISION`] decimals in stableswap pairs
+    pub price1_cumulative: Uint256,
+}
+
+/// ## Description
+/// This structure is used to return an observe query response.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ObserveResponse {
+    /// The cumulative prices in the order of the requested `seconds_ago` values.
+    /// A TWAP is the difference between two observations divided by the time between them
+    pub observations: Vec<Observation>,
 }
 
 /// ## Description
@@ -174,13 +478,36 @@ pub struct StablePoolParams {
 pub struct StablePoolConfig {
     /// The stableswap pool amplification
     pub amp: Decimal,
+    /// The off-peg fee multiplier if the pool uses dynamic fees
+    pub fee_multiplier: Option<Decimal>,
 }
 
 /// ## Description
-/// This enum stores the options available to start and stop changing a stableswap pool's amplification.
+/// This enum stores the options available to start and stop changing a stableswap pool's amplification
+/// and to configure its dynamic fees.
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum StablePoolUpdateParams {
-    StartChangingAmp { next_amp: u64, next_amp_time: u64 },
+    StartChangingAmp {
+        next_amp: u64,
+        next_amp_time: u64,
+    },
     StopChangingAmp {},
+    /// Enables dynamic fees with the specified off-peg fee multiplier or disables them if it's not set
+    SetFeeMultiplier {
+        fee_multiplier: Option<Decimal>,
+    },
+    /// Sets the maximum number of price observations that the pool keeps
+    SetObservationsSize {
+        size: u32,
+    },
+}
+
+/// ## Description
+/// This enum stores the options available to update a constant product pool's configuration.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum XykPoolUpdateParams {
+    /// Sets the maximum number of price observations that the pool keeps
+    SetObservationsSize { size: u32 },
 }
@@ -0,0 +1,220 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::asset::Asset;
+
+use cosmwasm_std::{Addr, Decimal, Uint128, Uint256};
+use cw20::Cw20ReceiveMsg;
+
+/// The minimum tick that can be used by a position. The price at this tick is 1.0001^-887272
+pub const MIN_TICK: i32 = -887_272;
+/// The maximum tick that can be used by a position. The price at this tick is 1.0001^887272
+pub const MAX_TICK: i32 = 887_272;
+/// The maximum allowed tick spacing
+pub const MAX_TICK_SPACING: u32 = 16_384;
+
+/// ## Description
+/// This structure describes the execute messages available in the contract.
+/// The `receive` and `swap` messages are the same as in [`super::pair::ExecuteMsg`].
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ExecuteMsg {
+    /// ## Description
+    /// Receives a message of type [`Cw20ReceiveMsg`]
+    Receive(Cw20ReceiveMsg),
+    /// Swap performs a swap in the pool
+    Swap {
+        offer_asset: Asset,
+        belief_price: Option<Decimal>,
+        max_spread: Option<Decimal>,
+        to: Option<String>,
+        /// The minimum amount of ask assets to receive, otherwise the transaction fails
+        min_return: Option<Uint128>,
+        /// The block time (in seconds) after which the transaction fails
+        deadline: Option<u64>,
+    },
+    /// CreatePosition provides liquidity in the price range between `lower_tick` and `upper_tick`.
+    /// The assets are the maximum amounts that can be deposited, the unused part is not pulled from the sender
+    CreatePosition {
+        /// The lower tick of the position's price range
+        lower_tick: i32,
+        /// The upper tick of the position's price range
+        upper_tick: i32,
+        /// The maximum amounts of assets to deposit
+        assets: [Asset; 2],
+        /// The owner of the new position
+        receiver: Option<String>,
+        /// The minimum amount of liquidity to add to the position, otherwise the transaction fails
+        min_lp_to_receive: Option<Uint128>,
+        /// The block time (in seconds) after which the transaction fails
+        deadline: Option<u64>,
+    },
+    /// IncreaseLiquidity adds liquidity to an existing position. Only the position owner can execute this
+    IncreaseLiquidity {
+        /// The position identifier
+        position_id: u64,
+        /// The maximum amounts of assets to deposit
+        assets: [Asset; 2],
+        /// The minimum amount of liquidity to add to the position, otherwise the transaction fails
+        min_lp_to_receive: Option<Uint128>,
+        /// The block time (in seconds) after which the transaction fails
+        deadline: Option<u64>,
+    },
+    /// DecreaseLiquidity removes liquidity from a position and sends the withdrawn assets
+    /// together with the collected fees to the position owner
+    DecreaseLiquidity {
+        /// The position identifier
+        position_id: u64,
+        /// The amount of liquidity to remove
+        liquidity: Uint128,
+        /// The minimum amounts of assets to withdraw (the collected fees excluded), otherwise the transaction fails
+        min_assets_out: Option<Vec<Asset>>,
+        /// The block time (in seconds) after which the transaction fails
+        deadline: Option<u64>,
+    },
+    /// CollectFees sends the fees earned by a position to the position owner
+    CollectFees {
+        /// The position identifier
+        position_id: u64,
+    },
+    /// TransferPosition changes the owner of a position
+    TransferPosition {
+        /// The position identifier
+        position_id: u64,
+        /// The new owner of the position
+        recipient: String,
+    },
+    /// RefreshFactoryParams updates the fee and Generator information that the pair caches from the factory
+    RefreshFactoryParams {},
+}
+
+/// ## Description
+/// This structure describes a CW20 hook message.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Cw20HookMsg {
+    /// Swap a given amount of asset
+    Swap {
+        belief_price: Option<Decimal>,
+        max_spread: Option<Decimal>,
+        to: Option<String>,
+        /// The minimum amount of ask assets to receive, otherwise the transaction fails
+        min_return: Option<Uint128>,
+        /// The block time (in seconds) after which the transaction fails
+        deadline: Option<u64>,
+    },
+}
+
+/// ## Description
+/// This structure describes the query messages available in the contract.
+/// All queries of [`super::pair::QueryMsg`] except `share` are supported with the same responses.
+/// Liquidity provision and withdrawal simulations work on positions instead of LP tokens.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum QueryMsg {
+    /// Returns information about a pair in an object of type [`super::asset::PairInfo`].
+    Pair {},
+    /// Returns the pool balances and the liquidity that is currently in range in a
+    /// [`super::pair::PoolResponse`] object.
+    Pool {},
+    /// Returns contract configuration settings in a [`super::pair::ConfigResponse`] structure.
+    /// The current pool state is returned in a [`ConcentratedPoolConfig`] object serialized in `params`.
+    Config {},
+    /// Returns information about a swap simulation in a [`super::pair::SimulationResponse`] object.
+    Simulation { offer_asset: Asset },
+    /// Returns information about a reverse swap simulation in a [`super::pair::ReverseSimulationResponse`] object.
+    ReverseSimulation { ask_asset: Asset },
+    /// Returns information about the cumulative prices in a [`super::pair::CumulativePricesResponse`] object
+    CumulativePrices {},
+    /// Returns the liquidity added to a position in the `lower_tick`..`upper_tick` range for depositing
+    /// at most `assets` together with the deposited amounts in a [`SimulateProvideResponse`] object
+    SimulateProvide {
+        lower_tick: i32,
+        upper_tick: i32,
+        assets: [Asset; 2],
+    },
+    /// Returns the assets withdrawn for removing `liquidity` from a position (the fees excluded)
+    /// in a vector that contains objects of type [`Asset`]
+    SimulateWithdraw {
+        position_id: u64,
+        liquidity: Uint128,
+    },
+    /// Returns information about a position in a [`PositionResponse`] object
+    Position { position_id: u64 },
+    /// Returns the positions of an owner in a [`PositionsResponse`] object
+    PositionsByOwner {
+        owner: String,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    /// Returns the trading state of the pair set in the factory in a [`super::factory::PairStatus`] object
+    PairStatus {},
+}
+
+/// ## Description
+/// This structure holds concentrated liquidity pool parameters.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct ConcentratedPoolParams {
+    /// Position ticks must be multiples of the tick spacing
+    pub tick_spacing: u32,
+    /// The initial price of asset 0 in terms of asset 1
+    pub initial_price: Decimal,
+}
+
+/// ## Description
+/// This structure stores a concentrated liquidity pool's configuration and state.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct ConcentratedPoolConfig {
+    /// Position ticks must be multiples of the tick spacing
+    pub tick_spacing: u32,
+    /// The current price of asset 0 in terms of asset 1
+    pub price: Decimal,
+    /// The square root of the current price as a Q64.96 fixed point number
+    pub sqrt_price: Uint256,
+    /// The current tick
+    pub tick: i32,
+    /// The liquidity that is currently in range
+    pub liquidity: Uint128,
+}
+
+/// ## Description
+/// This structure is used to return a position liquidity provision simulation response.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct SimulateProvideResponse {
+    /// The liquidity added to the position
+    pub liquidity: Uint128,
+    /// The amounts of assets deposited for the liquidity. Unused amounts are not deposited
+    pub assets: [Asset; 2],
+}
+
+/// ## Description
+/// This structure describes a liquidity position.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct PositionResponse {
+    /// The position identifier
+    pub position_id: u64,
+    /// The position owner
+    pub owner: Addr,
+    /// The lower tick of the position's price range
+    pub lower_tick: i32,
+    /// The upper tick of the position's price range
+    pub upper_tick: i32,
+    /// The position liquidity
+    pub liquidity: Uint128,
+    /// The amounts of assets that would be withdrawn if the whole liquidity was removed at the current price
+    pub assets: [Asset; 2],
+    /// The fees earned by the position that are not collected yet
+    pub fees: [Asset; 2],
+}
+
+/// ## Description
+/// This structure is used to return a list of positions.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct PositionsResponse {
+    pub positions: Vec<PositionResponse>,
+}
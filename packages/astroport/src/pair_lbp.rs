@@ -0,0 +1,118 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::asset::Asset;
+
+use cosmwasm_std::{Addr, Binary, Decimal, Uint128};
+use cw20::Cw20ReceiveMsg;
+
+/// The minimum normalized weight of an asset in a liquidity bootstrapping pool (1%)
+pub const MIN_WEIGHT: u64 = 1;
+
+/// ## Description
+/// This structure describes the execute messages available in the contract.
+/// The `receive`, `swap` and `update_config` messages are the same as in [`super::pair::ExecuteMsg`].
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ExecuteMsg {
+    /// ## Description
+    /// Receives a message of type [`Cw20ReceiveMsg`]
+    Receive(Cw20ReceiveMsg),
+    /// ProvideLiquidity allows the pool owner to deposit the assets sold in the pool.
+    /// No LP tokens are minted for the deposit
+    ProvideLiquidity {
+        /// The assets available in the pool
+        assets: [Asset; 2],
+    },
+    /// Swap performs a swap in the pool
+    Swap {
+        offer_asset: Asset,
+        belief_price: Option<Decimal>,
+        max_spread: Option<Decimal>,
+        to: Option<String>,
+        /// The minimum amount of ask assets to receive, otherwise the transaction fails
+        min_return: Option<Uint128>,
+        /// The block time (in seconds) after which the transaction fails
+        deadline: Option<u64>,
+    },
+    /// Update the pair configuration using [`LbpPoolUpdateParams`]
+    UpdateConfig { params: Binary },
+    /// WithdrawProceeds allows the pool owner to withdraw all pool assets after the end time
+    WithdrawProceeds {
+        /// The receiver of the pool assets
+        receiver: Option<String>,
+    },
+    /// RefreshFactoryParams updates the fee and Generator information that the pair caches from the factory
+    RefreshFactoryParams {},
+    /// Sync sets the tracked pool reserves to the current balances of the pair
+    Sync {},
+    /// Skim sends the balances of the pair that exceed the tracked pool reserves to a recipient
+    Skim {
+        /// The recipient of the excess assets. Defaults to the sender
+        to: Option<String>,
+    },
+}
+
+/// ## Description
+/// This structure describes a CW20 hook message.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Cw20HookMsg {
+    /// Swap a given amount of asset
+    Swap {
+        belief_price: Option<Decimal>,
+        max_spread: Option<Decimal>,
+        to: Option<String>,
+        /// The minimum amount of ask assets to receive, otherwise the transaction fails
+        min_return: Option<Uint128>,
+        /// The block time (in seconds) after which the transaction fails
+        deadline: Option<u64>,
+    },
+}
+
+/// ## Description
+/// This structure holds liquidity bootstrapping pool parameters.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct LbpPoolParams {
+    /// The pool owner that can provide liquidity, pause swaps and withdraw the proceeds
+    pub owner: String,
+    /// The normalized asset weights at `start_time` (in the same order as the asset infos)
+    pub start_weights: [Decimal; 2],
+    /// The normalized asset weights at `end_time` (in the same order as the asset infos)
+    pub end_weights: [Decimal; 2],
+    /// The timestamp when the weights start to change and swaps are enabled
+    pub start_time: u64,
+    /// The timestamp when the weights stop changing and swaps are disabled
+    pub end_time: u64,
+}
+
+/// ## Description
+/// This structure stores a liquidity bootstrapping pool's configuration.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct LbpPoolConfig {
+    /// The pool owner
+    pub owner: Addr,
+    /// The normalized asset weights at `start_time`
+    pub start_weights: [Decimal; 2],
+    /// The normalized asset weights at `end_time`
+    pub end_weights: [Decimal; 2],
+    /// The timestamp when the weights start to change
+    pub start_time: u64,
+    /// The timestamp when the weights stop changing
+    pub end_time: u64,
+    /// The normalized asset weights at the current block time
+    pub current_weights: [Decimal; 2],
+    /// Whether swaps are paused by the owner
+    pub is_paused: bool,
+}
+
+/// ## Description
+/// This enum stores the options available to pause and resume swaps in a liquidity bootstrapping pool.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum LbpPoolUpdateParams {
+    Pause {},
+    Resume {},
+}
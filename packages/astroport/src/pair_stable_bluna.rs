@@ -1,7 +1,7 @@
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use crate::asset::Asset;
+use crate::asset::{Asset, AssetInfo};
 
 use cosmwasm_std::{Addr, Binary, Decimal, Uint128};
 use cw20::Cw20ReceiveMsg;
@@ -24,6 +24,10 @@ pub enum ExecuteMsg {
         auto_stake: Option<bool>,
         /// The receiver of LP tokens
         receiver: Option<String>,
+        /// The minimum amount of LP tokens to receive, otherwise the transaction fails
+        min_lp_to_receive: Option<Uint128>,
+        /// The block time (in seconds) after which the transaction fails
+        deadline: Option<u64>,
     },
     /// Swap performs a swap in the pool
     Swap {
@@ -31,15 +35,19 @@ pub enum ExecuteMsg {
         belief_price: Option<Decimal>,
         max_spread: Option<Decimal>,
         to: Option<String>,
+        /// The minimum amount of ask assets to receive, otherwise the transaction fails
+        min_return: Option<Uint128>,
+        /// The block time (in seconds) after which the transaction fails
+        deadline: Option<u64>,
     },
     /// Update the pair configuration
     UpdateConfig { params: Binary },
-    /// Claims bLUNA rewards and sends them to the specified receiver
+    /// Claims the pool rewards and sends them to the specified receiver
     ClaimReward {
-        /// An address which will receive the bLUNA reward
+        /// An address which will receive the rewards
         receiver: Option<String>,
     },
-    /// Claims the bLUNA reward for a user that deposited their LP tokens in the Generator contract
+    /// Claims the pool rewards for a user that deposited their LP tokens in the Generator contract
     ClaimRewardByGenerator {
         /// The user whose LP tokens are/were staked in the Generator
         user: String,
@@ -48,14 +56,24 @@ pub enum ExecuteMsg {
         /// The total LP token amount already deposited by all users in the Generator
         total_share: Uint128,
     },
-    /// Callback for distributing bLUNA rewards
+    /// Callback for distributing the pool rewards
     HandleReward {
-        previous_reward_balance: Uint128,
+        /// The reward holder balances of every reward asset before the rewards were claimed
+        previous_reward_balances: Vec<Asset>,
         user: Addr,
         user_share: Uint128,
         total_share: Uint128,
         receiver: Option<Addr>,
     },
+    /// RefreshFactoryParams updates the fee and Generator information that the pair caches from the factory
+    RefreshFactoryParams {},
+    /// Sync sets the tracked pool reserves to the current balances of the pair
+    Sync {},
+    /// Skim sends the balances of the pair that exceed the tracked pool reserves to a recipient
+    Skim {
+        /// The recipient of the excess assets. Defaults to the sender
+        to: Option<String>,
+    },
 }
 
 /// ## Description
@@ -77,8 +95,23 @@ pub enum QueryMsg {
     ReverseSimulation { ask_asset: Asset },
     /// Returns information about cumulative prices (used for TWAPs) in a [`super::pair::CumulativePricesResponse`] object.
     CumulativePrices {},
-    /// Returns pending token rewards that can be claimed by a specific user using a [`Asset`] object.
+    /// Returns the cumulative prices at the specified number of seconds ago (one per element of `seconds_ago`)
+    /// in a [`super::pair::ObserveResponse`] object
+    Observe { seconds_ago: Vec<u64> },
+    /// Returns the pending amount of the first reward asset that can be claimed by a specific user using a [`Asset`] object.
     PendingReward { user: String },
+    /// Returns the pending amounts of all reward assets that can be claimed by a specific user in a vector
+    /// that contains objects of type [`Asset`].
+    PendingRewards { user: String },
+    /// Returns the amount of LP tokens minted for a deposit in a [`super::pair::SimulateProvideResponse`] object.
+    SimulateProvide {
+        assets: [Asset; 2],
+        slippage_tolerance: Option<Decimal>,
+    },
+    /// Returns the amounts of assets received for burning LP tokens in a vector that contains objects of type [`Asset`].
+    SimulateWithdraw { lp_amount: Uint128 },
+    /// Returns the trading state of the pair set in the factory in a [`super::factory::PairStatus`] object
+    PairStatus {},
 }
 
 /// ## Description
@@ -105,6 +138,14 @@ pub struct StablePoolConfig {
     pub bluna_rewarder: Addr,
     /// The Astroport Generator contract
     pub generator: Addr,
+    /// The rate provider of the pool's yield-bearing asset
+    pub rate_provider: Option<RateProvider>,
+    /// The exchange rate of the yield-bearing asset that scales its balance in the pool math
+    pub exchange_rate: Decimal,
+    /// The custom reward source of the pool. The bLUNA rewarder is used if it's not set
+    pub reward_source: Option<RewardSource>,
+    /// The contract that holds the claimed rewards until they are distributed
+    pub reward_holder: Option<Addr>,
 }
 
 /// ## Description
@@ -112,9 +153,95 @@ pub struct StablePoolConfig {
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum StablePoolUpdateParams {
-    StartChangingAmp { next_amp: u64, next_amp_time: u64 },
+    StartChangingAmp {
+        next_amp: u64,
+        next_amp_time: u64,
+    },
     StopChangingAmp {},
-    BlunaRewarder { address: String },
+    BlunaRewarder {
+        address: String,
+    },
+    /// Sets the rate provider of the pool's yield-bearing asset or removes it, so the pool assumes a 1:1 peg
+    RateProvider {
+        rate_provider: Option<RateProviderParams>,
+    },
+    /// Sets a custom reward source or removes it, so the pool claims bLUNA rewards from the bLUNA rewarder
+    RewardSource {
+        reward_source: Option<RewardSourceParams>,
+    },
+    /// Sets the maximum number of price observations that the pool keeps
+    SetObservationsSize {
+        size: u32,
+    },
+}
+
+/// ## Description
+/// This structure describes the parameters used to set a reward source.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct RewardSourceParams {
+    /// The contract that distributes rewards for the pool's yield-bearing asset
+    pub contract: String,
+    /// The message that claims the rewards from `contract`. It must send the rewards to the reward holder
+    pub claim_msg: Binary,
+    /// The assets that are paid as rewards
+    pub reward_assets: Vec<AssetInfo>,
+}
+
+/// ## Description
+/// This structure stores a source of the pool rewards.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct RewardSource {
+    /// The contract that distributes rewards for the pool's yield-bearing asset
+    pub contract: Addr,
+    /// The message that claims the rewards from `contract`
+    pub claim_msg: Binary,
+    /// The assets that are paid as rewards
+    pub reward_assets: Vec<AssetInfo>,
+}
+
+/// ## Description
+/// This structure describes the parameters used to set a rate provider.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct RateProviderParams {
+    /// The rate provider contract
+    pub address: String,
+    /// The yield-bearing pool asset whose exchange rate the provider returns
+    pub asset_info: AssetInfo,
+    /// The maximum age (in seconds) of the cached exchange rate
+    pub max_rate_age: u64,
+}
+
+/// ## Description
+/// This structure stores the rate provider of the pool's yield-bearing asset.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct RateProvider {
+    /// The rate provider contract
+    pub address: Addr,
+    /// The yield-bearing pool asset whose exchange rate the provider returns
+    pub asset_info: AssetInfo,
+    /// The maximum age (in seconds) of the cached exchange rate
+    pub max_rate_age: u64,
+}
+
+/// ## Description
+/// This structure describes the query messages that a rate provider contract must support.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum RateProviderQueryMsg {
+    /// Returns the amount of the base asset that one unit of `asset_info` is worth in an [`ExchangeRateResponse`] object.
+    ExchangeRate { asset_info: AssetInfo },
+}
+
+/// ## Description
+/// This structure holds the parameters that are returned from an exchange rate query.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ExchangeRateResponse {
+    /// The amount of the base asset that one unit of the yield-bearing asset is worth
+    pub rate: Decimal,
 }
 
 /// ## Description
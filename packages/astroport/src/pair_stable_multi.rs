@@ -0,0 +1,156 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::asset::{Asset, AssetInfo};
+
+use cosmwasm_std::{Binary, Decimal, Uint128};
+use cw20::Cw20ReceiveMsg;
+
+/// The minimum amount of assets in a multi-asset stableswap pool
+pub const MIN_ASSETS_NUM: usize = 3;
+/// The maximum amount of assets in a multi-asset stableswap pool
+pub const MAX_ASSETS_NUM: usize = 5;
+
+/// ## Description
+/// This structure describes the parameters used for creating a contract.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct InstantiateMsg {
+    /// Information about the assets in the pool
+    pub asset_infos: Vec<AssetInfo>,
+    /// The token contract code ID used for the tokens in the pool
+    pub token_code_id: u64,
+    /// The factory contract address
+    pub factory_addr: String,
+    /// Binary serialised [`super::pair::StablePoolParams`]
+    pub init_params: Option<Binary>,
+}
+
+/// ## Description
+/// This structure describes the execute messages available in the contract.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ExecuteMsg {
+    /// ## Description
+    /// Receives a message of type [`Cw20ReceiveMsg`]
+    Receive(Cw20ReceiveMsg),
+    /// ProvideLiquidity allows someone to provide liquidity in the pool.
+    /// Any subset of the pool assets can be provided in any proportion
+    ProvideLiquidity {
+        /// The assets to provide
+        assets: Vec<Asset>,
+        /// Determines whether the LP tokens minted for the user is auto_staked in the Generator contract
+        auto_stake: Option<bool>,
+        /// The receiver of LP tokens
+        receiver: Option<String>,
+        /// The minimum amount of LP tokens to receive, otherwise the transaction fails
+        min_lp_to_receive: Option<Uint128>,
+        /// The block time (in seconds) after which the transaction fails
+        deadline: Option<u64>,
+    },
+    /// Swap performs a swap in the pool
+    Swap {
+        offer_asset: Asset,
+        /// The asset to swap to
+        ask_asset_info: AssetInfo,
+        belief_price: Option<Decimal>,
+        max_spread: Option<Decimal>,
+        to: Option<String>,
+        /// The minimum amount of ask assets to receive, otherwise the transaction fails
+        min_return: Option<Uint128>,
+        /// The block time (in seconds) after which the transaction fails
+        deadline: Option<u64>,
+    },
+    /// Update the pair configuration
+    UpdateConfig { params: Binary },
+    /// RefreshFactoryParams updates the fee and Generator information that the pair caches from the factory
+    RefreshFactoryParams {},
+    /// Sync sets the tracked pool reserves to the current balances of the pair
+    Sync {},
+    /// Skim sends the balances of the pair that exceed the tracked pool reserves to a recipient
+    Skim {
+        /// The recipient of the excess assets. Defaults to the sender
+        to: Option<String>,
+    },
+}
+
+/// ## Description
+/// This structure describes a CW20 hook message.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Cw20HookMsg {
+    /// Swap a given amount of asset
+    Swap {
+        /// The asset to swap to
+        ask_asset_info: AssetInfo,
+        belief_price: Option<Decimal>,
+        max_spread: Option<Decimal>,
+        to: Option<String>,
+        /// The minimum amount of ask assets to receive, otherwise the transaction fails
+        min_return: Option<Uint128>,
+        /// The block time (in seconds) after which the transaction fails
+        deadline: Option<u64>,
+    },
+    /// Withdraw liquidity from the pool
+    WithdrawLiquidity {
+        /// The minimum amounts of assets to receive, otherwise the transaction fails
+        min_assets_out: Option<Vec<Asset>>,
+        /// The block time (in seconds) after which the transaction fails
+        deadline: Option<u64>,
+    },
+}
+
+/// ## Description
+/// This structure describes the query messages available in the contract.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum QueryMsg {
+    /// Returns information about a pair in an object of type [`super::asset::MultiPairInfo`].
+    Pair {},
+    /// Returns information about a pool in an object of type [`PoolResponse`].
+    Pool {},
+    /// Returns contract configuration settings in a custom [`ConfigResponse`] structure.
+    Config {},
+    /// Returns information about the share of the pool in a vector that contains objects of type [`Asset`].
+    Share { amount: Uint128 },
+    /// Returns information about a swap simulation in a [`super::pair::SimulationResponse`] object.
+    Simulation {
+        offer_asset: Asset,
+        ask_asset_info: AssetInfo,
+    },
+    /// Returns information about a reverse swap simulation in a [`super::pair::ReverseSimulationResponse`] object.
+    ReverseSimulation {
+        offer_asset_info: AssetInfo,
+        ask_asset: Asset,
+    },
+    /// Returns the amount of LP tokens minted for a deposit and the imbalance fees charged on it
+    /// in a [`super::pair::SimulateProvideResponse`] object.
+    SimulateProvide { assets: Vec<Asset> },
+    /// Returns the amounts of assets received for burning LP tokens in a vector that contains objects of type [`Asset`].
+    SimulateWithdraw { lp_amount: Uint128 },
+    /// Returns the trading state of the pair set in the factory in a [`super::factory::PairStatus`] object
+    PairStatus {},
+}
+
+/// ## Description
+/// This struct is used to return a query result with the total amount of LP tokens and the assets in a specific pool.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PoolResponse {
+    /// The assets in the pool together with asset amounts
+    pub assets: Vec<Asset>,
+    /// The total amount of LP tokens currently issued
+    pub total_share: Uint128,
+}
+
+/// ## Description
+/// This struct is used to return a query result with the general contract configuration.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ConfigResponse {
+    /// The current pool amplification
+    pub amp: Decimal,
+}
+
+/// ## Description
+/// This structure describes a migration message.
+/// We currently take no arguments for migrations.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct MigrateMsg {}
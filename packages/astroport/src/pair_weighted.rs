@@ -0,0 +1,70 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::asset::{Asset, AssetInfo};
+
+use cosmwasm_std::{Decimal, Uint128};
+
+/// The minimum normalized weight of an asset in a weighted pool (1%)
+pub const MIN_WEIGHT: u64 = 1;
+
+/// ## Description
+/// This structure describes a CW20 hook message.
+/// The `swap` and `withdraw_liquidity` hooks are the same as in [`super::pair::Cw20HookMsg`].
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Cw20HookMsg {
+    /// Swap a given amount of asset
+    Swap {
+        belief_price: Option<Decimal>,
+        max_spread: Option<Decimal>,
+        to: Option<String>,
+        /// The minimum amount of ask assets to receive, otherwise the transaction fails
+        min_return: Option<Uint128>,
+        /// The block time (in seconds) after which the transaction fails
+        deadline: Option<u64>,
+    },
+    /// Withdraw liquidity from the pool
+    WithdrawLiquidity {
+        /// The minimum amounts of assets to receive, otherwise the transaction fails
+        min_assets_out: Option<Vec<Asset>>,
+        /// The block time (in seconds) after which the transaction fails
+        deadline: Option<u64>,
+    },
+    /// Withdraw liquidity from the pool in a single asset
+    WithdrawSingleSided {
+        /// The asset to receive
+        ask_asset_info: AssetInfo,
+        /// The minimum amount of assets to receive, otherwise the transaction fails
+        min_ask_amount: Option<Uint128>,
+        /// The block time (in seconds) after which the transaction fails
+        deadline: Option<u64>,
+    },
+    /// Swap the sent tokens for exactly `ask_asset`. The sent amount is the maximum offer amount
+    /// and the excess is sent back
+    SwapExactOut {
+        ask_asset: Asset,
+        to: Option<String>,
+        /// The block time (in seconds) after which the transaction fails
+        deadline: Option<u64>,
+    },
+}
+
+/// ## Description
+/// This structure holds weighted pool parameters.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct WeightedPoolParams {
+    /// The normalized weights of the pool assets (in the same order as the asset infos).
+    /// The weights must sum up to 1
+    pub weights: [Decimal; 2],
+}
+
+/// ## Description
+/// This structure stores a weighted pool's configuration.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct WeightedPoolConfig {
+    /// The normalized weights of the pool assets
+    pub weights: [Decimal; 2],
+}
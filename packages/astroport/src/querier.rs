@@ -1,6 +1,6 @@
 use crate::asset::{Asset, AssetInfo, PairInfo};
 use crate::factory::{
-    ConfigResponse as FactoryConfigResponse, FeeInfoResponse, PairType, PairsResponse,
+    ConfigResponse as FactoryConfigResponse, FeeInfoResponse, PairStatus, PairType, PairsResponse,
     QueryMsg as FactoryQueryMsg,
 };
 use crate::pair::{QueryMsg as PairQueryMsg, ReverseSimulationResponse, SimulationResponse};
@@ -11,6 +11,8 @@ use cosmwasm_std::{
 };
 
 use cw20::{BalanceResponse as Cw20BalanceResponse, Cw20QueryMsg, TokenInfoResponse};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
 
 // It's defined at https://github.com/terra-money/core/blob/d8e277626e74f9d6417dcd598574686882f0274c/types/assets/assets.go#L15
 const NATIVE_TOKEN_PRECISION: u8 = 6;
@@ -143,6 +145,7 @@ pub fn query_factory_config(
 
 /// ## Description
 /// This structure holds parameters that describe the fee structure for a pool.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct FeeInfo {
     /// The fee address
     pub fee_address: Option<Addr>,
@@ -150,35 +153,115 @@ pub struct FeeInfo {
     pub total_fee_rate: Decimal,
     /// The amount of fees sent to the Maker contract
     pub maker_fee_rate: Decimal,
+    /// Whether the Maker fee is collected by minting LP tokens to the fee address
+    pub maker_fee_as_lp: bool,
 }
 
 /// ## Description
-/// Returns the fee information for a specific pair type.
+/// Returns the fee information for a specific pair. The pair's fee override is used if the factory has one,
+/// otherwise the fees of its pair type are returned.
 /// ## Params
 /// * **querier** is an object of type [`QuerierWrapper`].
 ///
 /// * **factory_contract** is an object of type [`Addr`].
 ///
-/// * **pair_type** is an object of type [`PairType`]. This is the pair type we return information for.
+/// * **pair_contract** is an object of type [`Addr`]. This is the pair we return information for.
+///
+/// * **pair_type** is an object of type [`PairType`]. This is the type of the pair.
 pub fn query_fee_info(
     querier: &QuerierWrapper,
     factory_contract: Addr,
+    pair_contract: Addr,
     pair_type: PairType,
 ) -> StdResult<FeeInfo> {
     let res: FeeInfoResponse = querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
         contract_addr: factory_contract.to_string(),
-        msg: to_binary(&FactoryQueryMsg::FeeInfo { pair_type })?,
+        msg: to_binary(&FactoryQueryMsg::FeeInfo {
+            pair_type,
+            pair_addr: Some(pair_contract.to_string()),
+        })?,
     }))?;
 
     Ok(FeeInfo {
         fee_address: res.fee_address,
         total_fee_rate: Decimal::from_ratio(Uint128::from(res.total_fee_bps), Uint128::new(10000)),
         maker_fee_rate: Decimal::from_ratio(Uint128::from(res.maker_fee_bps), Uint128::new(10000)),
+        maker_fee_as_lp: res.maker_fee_as_lp,
+    })
+}
+
+/// ## Description
+/// This structure holds the factory parameters that pairs cache locally instead of querying the factory on every swap.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct FactoryParams {
+    /// The fees of the pair
+    pub fee_info: FeeInfo,
+    /// The Generator contract address used to auto stake LP tokens
+    pub generator_address: Option<Addr>,
+    /// The trading state of the pair
+    #[serde(default)]
+    pub pair_status: PairStatus,
+}
+
+/// ## Description
+/// Returns the trading state of a specific pair set in the factory.
+/// ## Params
+/// * **querier** is an object of type [`QuerierWrapper`].
+///
+/// * **factory_contract** is an object of type [`Addr`].
+///
+/// * **pair_contract** is an object of type [`Addr`]. This is the pair we return the state for.
+pub fn query_pair_status(
+    querier: &QuerierWrapper,
+    factory_contract: Addr,
+    pair_contract: Addr,
+) -> StdResult<PairStatus> {
+    querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
+        contract_addr: factory_contract.to_string(),
+        msg: to_binary(&FactoryQueryMsg::PairStatus {
+            pair_addr: pair_contract.to_string(),
+        })?,
+    }))
+}
+
+/// ## Description
+/// Returns the factory parameters that a pair caches: its fee information, the Generator address and its
+/// trading state.
+/// ## Params
+/// * **querier** is an object of type [`QuerierWrapper`].
+///
+/// * **factory_contract** is an object of type [`Addr`].
+///
+/// * **pair_contract** is an object of type [`Addr`]. This is the pair we return information for.
+///
+/// * **pair_type** is an object of type [`PairType`]. This is the type of the pair.
+pub fn query_factory_params(
+    querier: &QuerierWrapper,
+    factory_contract: Addr,
+    pair_contract: Addr,
+    pair_type: PairType,
+) -> StdResult<FactoryParams> {
+    let fee_info = query_fee_info(
+        querier,
+        factory_contract.clone(),
+        pair_contract.clone(),
+        pair_type,
+    )?;
+    let generator_address =
+        query_factory_config(querier, factory_contract.clone())?.generator_address;
+    let pair_status = query_pair_status(querier, factory_contract, pair_contract)?;
+
+    Ok(FactoryParams {
+        fee_info,
+        generator_address,
+        pair_status,
     })
 }
 
 /// ## Description
 /// Accepts two tokens as input and returns a pair's information.
+/// If several pools exist for the tokens, the factory picks one by pair type in this order: xyk, stable, stable multi,
+/// weighted, concentrated, custom and finally LBP.
 /// ## Params
 /// * **querier** is an object of type [`QuerierWrapper`].
 ///
@@ -194,6 +277,7 @@ pub fn query_pair_info(
         contract_addr: factory_contract.to_string(),
         msg: to_binary(&FactoryQueryMsg::Pair {
             asset_infos: asset_infos.clone(),
+            pair_type: None,
         })?,
     }))
 }
@@ -5,9 +5,9 @@ use crate::querier::{
 };
 
 use crate::factory::PairType;
-use crate::DecimalCheckedOps;
+use crate::{DecimalCheckedOps, Uint256WrappingOps};
 use cosmwasm_std::testing::MOCK_CONTRACT_ADDR;
-use cosmwasm_std::{to_binary, Addr, BankMsg, Coin, CosmosMsg, Decimal, Uint128, WasmMsg};
+use cosmwasm_std::{to_binary, Addr, BankMsg, Coin, CosmosMsg, Decimal, Uint128, Uint256, WasmMsg};
 use cw20::Cw20ExecuteMsg;
 
 #[test]
@@ -323,7 +323,7 @@ fn test_format_lp_token_name() {
 
     deps.querier.with_cw20_query_handler();
 
-    let lp_name = format_lp_token_name(pair_info.asset_infos, &deps.as_ref().querier).unwrap();
+    let lp_name = format_lp_token_name(&pair_info.asset_infos, &deps.as_ref().querier).unwrap();
     assert_eq!(lp_name, "MAPP-UUSD-LP")
 }
 
@@ -352,3 +352,26 @@ fn test_decimal_checked_ops() {
             .is_err()
     );
 }
+
+#[test]
+fn test_uint256_wrapping_ops() {
+    let max = Uint256::from_be_bytes([u8::MAX; 32]);
+
+    assert_eq!(
+        Uint256::from(5u128).wrapping_add(Uint256::from(7u128)),
+        Uint256::from(12u128)
+    );
+    assert_eq!(
+        Uint256::from(7u128).wrapping_sub(Uint256::from(5u128)),
+        Uint256::from(2u128)
+    );
+
+    // Overflows wrap around
+    assert_eq!(max.wrapping_add(Uint256::from(3u128)), Uint256::from(2u128));
+    assert_eq!(Uint256::from(2u128).wrapping_sub(Uint256::from(3u128)), max);
+
+    // The difference of two accumulators is correct even if the later one has wrapped around
+    let before = max.wrapping_sub(Uint256::from(10u128));
+    let after = before.wrapping_add(Uint256::from(100u128));
+    assert_eq!(after.wrapping_sub(before), Uint256::from(100u128));
+}
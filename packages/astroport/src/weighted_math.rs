@@ -0,0 +1,304 @@
+use std::convert::TryFrom;
+
+use crate::U256;
+
+/// The fixed point precision used in the weighted math (18 decimals)
+pub const ONE: u128 = 1_000_000_000_000_000_000;
+/// The maximum offer amount relative to the offer pool (in percent)
+pub const MAX_IN_RATIO: u64 = 30;
+/// The maximum ask amount relative to the ask pool (in percent)
+pub const MAX_OUT_RATIO: u64 = 30;
+
+/// The minimum base accepted by [`bpow`]
+const MIN_BPOW_BASE: u128 = 1;
+/// The maximum base accepted by [`bpow`]
+const MAX_BPOW_BASE: u128 = 2 * ONE - 1;
+/// The precision of the series approximation used in [`bpow_approx`]
+const BPOW_PRECISION: u128 = ONE / 10_000_000_000;
+/// The maximum amount of series terms used in [`bpow_approx`]
+const ITERATIONS: u8 = 255;
+
+/// ## Description
+/// Calculates the ask amount (the amount of tokens swapped to) before any fees are charged.
+///
+/// * **Equation**
+///
+/// ask_amount = ask_pool * (1 - (offer_pool / (offer_pool + offer_amount)) ^ (offer_weight / ask_weight))
+///
+/// ## Params
+/// * **offer_pool** is an object of type [`u128`]. This is the amount of offer tokens currently in the pool.
+///
+/// * **offer_weight** is an object of type [`u128`]. This is the normalized offer token weight with 18 decimals.
+///
+/// * **ask_pool** is an object of type [`u128`]. This is the amount of ask tokens currently in the pool.
+///
+/// * **ask_weight** is an object of type [`u128`]. This is the normalized ask token weight with 18 decimals.
+///
+/// * **offer_amount** is an object of type [`u128`]. This is the amount of offer tokens to swap.
+pub fn calc_ask_amount(
+    offer_pool: u128,
+    offer_weight: u128,
+    ask_pool: u128,
+    ask_weight: u128,
+    offer_amount: u128,
+) -> Option<u128> {
+    let weight_ratio = bdiv(offer_weight.into(), ask_weight.into())?;
+    let y = bdiv(
+        offer_pool.into(),
+        U256::from(offer_pool).checked_add(offer_amount.into())?,
+    )?;
+    let pow = bpow(y, weight_ratio)?;
+    let ask_ratio = U256::from(ONE).checked_sub(pow)?;
+
+    to_u128(bmul(ask_pool.into(), ask_ratio)?)
+}
+
+/// ## Description
+/// Calculates the offer amount (the amount of tokens to swap) needed to receive a specific ask amount before any fees are charged.
+///
+/// * **Equation**
+///
+/// offer_amount = offer_pool * ((ask_pool / (ask_pool - ask_amount)) ^ (ask_weight / offer_weight) - 1)
+///
+/// ## Params
+/// * **offer_pool** is an object of type [`u128`]. This is the amount of offer tokens currently in the pool.
+///
+/// * **offer_weight** is an object of type [`u128`]. This is the normalized offer token weight with 18 decimals.
+///
+/// * **ask_pool** is an object of type [`u128`]. This is the amount of ask tokens currently in the pool.
+///
+/// * **ask_weight** is an object of type [`u128`]. This is the normalized ask token weight with 18 decimals.
+///
+/// * **ask_amount** is an object of type [`u128`]. This is the amount of ask tokens to receive.
+pub fn calc_offer_amount(
+    offer_pool: u128,
+    offer_weight: u128,
+    ask_pool: u128,
+    ask_weight: u128,
+    ask_amount: u128,
+) -> Option<u128> {
+    let weight_ratio = bdiv(ask_weight.into(), offer_weight.into())?;
+    let y = bdiv(
+        ask_pool.into(),
+        U256::from(ask_pool.checked_sub(ask_amount)?),
+    )?;
+    let offer_ratio = bpow(y, weight_ratio)?.checked_sub(ONE.into())?;
+
+    to_u128(bmul(offer_pool.into(), offer_ratio)?)
+}
+
+/// ## Description
+/// Calculates the amount of LP tokens minted for a single-sided deposit.
+/// Returns the amount of LP tokens to mint and the fee charged for the deposit.
+/// Only the part of the deposit that would have to be swapped to the other asset is charged a fee.
+///
+/// * **Equation**
+///
+/// share = total_share * ((offer_pool + offer_amount * (1 - (1 - offer_weight) * fee)) / offer_pool) ^ offer_weight - total_share
+///
+/// ## Params
+/// * **offer_pool** is an object of type [`u128`]. This is the amount of deposited tokens currently in the pool.
+///
+/// * **offer_weight** is an object of type [`u128`]. This is the normalized deposited token weight with 18 decimals.
+///
+/// * **total_share** is an object of type [`u128`]. This is the total amount of LP tokens currently minted.
+///
+/// * **offer_amount** is an object of type [`u128`]. This is the amount of tokens to deposit.
+///
+/// * **fee** is an object of type [`u128`]. This is the total fee rate with 18 decimals.
+pub fn calc_share_for_single_asset(
+    offer_pool: u128,
+    offer_weight: u128,
+    total_share: u128,
+    offer_amount: u128,
+    fee: u128,
+) -> Option<(u128, u128)> {
+    let zaz = bmul(
+        U256::from(ONE).checked_sub(offer_weight.into())?,
+        fee.into(),
+    )?;
+    let amount_after_fee = bmul(offer_amount.into(), U256::from(ONE).checked_sub(zaz)?)?;
+
+    let new_offer_pool = U256::from(offer_pool).checked_add(amount_after_fee)?;
+    let pool_ratio = bdiv(new_offer_pool, offer_pool.into())?;
+    let share_ratio = bpow(pool_ratio, offer_weight.into())?;
+    let new_total_share = bmul(share_ratio, total_share.into())?;
+
+    Some((
+        to_u128(new_total_share.checked_sub(total_share.into())?)?,
+        offer_amount.checked_sub(to_u128(amount_after_fee)?)?,
+    ))
+}
+
+/// ## Description
+/// Calculates the amount of tokens withdrawn when a specific amount of LP tokens is burned for a single asset.
+/// Returns the amount to withdraw and the fee charged for it.
+/// Only the part of the withdrawal that would have to be swapped from the other asset is charged a fee.
+///
+/// * **Equation**
+///
+/// ask_amount = ask_pool * (1 - ((total_share - share) / total_share) ^ (1 / ask_weight)) * (1 - (1 - ask_weight) * fee)
+///
+/// ## Params
+/// * **ask_pool** is an object of type [`u128`]. This is the amount of withdrawn tokens currently in the pool.
+///
+/// * **ask_weight** is an object of type [`u128`]. This is the normalized withdrawn token weight with 18 decimals.
+///
+/// * **total_share** is an object of type [`u128`]. This is the total amount of LP tokens currently minted.
+///
+/// * **share** is an object of type [`u128`]. This is the amount of LP tokens to burn.
+///
+/// * **fee** is an object of type [`u128`]. This is the total fee rate with 18 decimals.
+pub fn calc_single_asset_for_share(
+    ask_pool: u128,
+    ask_weight: u128,
+    total_share: u128,
+    share: u128,
+    fee: u128,
+) -> Option<(u128, u128)> {
+    let new_total_share = total_share.checked_sub(share)?;
+    let share_ratio = bdiv(new_total_share.into(), total_share.into())?;
+    let pool_ratio = bpow(share_ratio, bdiv(ONE.into(), ask_weight.into())?)?;
+    let new_ask_pool = bmul(pool_ratio, ask_pool.into())?;
+    let amount_before_fee = U256::from(ask_pool).checked_sub(new_ask_pool)?;
+
+    let zaz = bmul(U256::from(ONE).checked_sub(ask_weight.into())?, fee.into())?;
+    let amount = bmul(amount_before_fee, U256::from(ONE).checked_sub(zaz)?)?;
+
+    Some((
+        to_u128(amount)?,
+        to_u128(amount_before_fee.checked_sub(amount)?)?,
+    ))
+}
+
+/// ## Description
+/// Returns the spot price of the offer token denominated in the ask token with 18 decimals.
+///
+/// * **Equation**
+///
+/// price = (ask_pool / ask_weight) / (offer_pool / offer_weight)
+pub fn calc_spot_price(
+    offer_pool: u128,
+    offer_weight: u128,
+    ask_pool: u128,
+    ask_weight: u128,
+) -> Option<u128> {
+    let numer = bdiv(ask_pool.into(), ask_weight.into())?;
+    let denom = bdiv(offer_pool.into(), offer_weight.into())?;
+
+    to_u128(bdiv(numer, denom)?)
+}
+
+/// ## Description
+/// Multiplies two fixed point numbers with 18 decimals.
+fn bmul(a: U256, b: U256) -> Option<U256> {
+    a.checked_mul(b)?
+        .checked_add(U256::from(ONE / 2))?
+        .checked_div(ONE.into())
+}
+
+/// ## Description
+/// Divides two fixed point numbers with 18 decimals.
+fn bdiv(a: U256, b: U256) -> Option<U256> {
+    a.checked_mul(ONE.into())?
+        .checked_add(b / 2)?
+        .checked_div(b)
+}
+
+/// ## Description
+/// Returns the absolute difference between `a` and `b` and whether the result is negative.
+fn bsub_sign(a: U256, b: U256) -> (U256, bool) {
+    if a >= b {
+        (a - b, false)
+    } else {
+        (b - a, true)
+    }
+}
+
+/// ## Description
+/// Raises a fixed point number to a whole power using exponentiation by squaring.
+fn bpowi(a: U256, n: u128) -> Option<U256> {
+    let mut a = a;
+    let mut n = n;
+    let mut z = if n % 2 == 1 { a } else { U256::from(ONE) };
+
+    n /= 2;
+    while n != 0 {
+        a = bmul(a, a)?;
+        if n % 2 == 1 {
+            z = bmul(z, a)?;
+        }
+        n /= 2;
+    }
+
+    Some(z)
+}
+
+/// ## Description
+/// Raises a fixed point number to a fixed point power. The whole part of the exponent is computed
+/// with [`bpowi`] and the fractional part is approximated with [`bpow_approx`].
+/// ## Params
+/// * **base** is an object of type [`U256`]. It must be in the (0, 2) range.
+///
+/// * **exp** is an object of type [`U256`]. This is the exponent.
+fn bpow(base: U256, exp: U256) -> Option<U256> {
+    if base < MIN_BPOW_BASE.into() || base > MAX_BPOW_BASE.into() {
+        return None;
+    }
+
+    let whole = exp / U256::from(ONE);
+    let remain = exp - whole * U256::from(ONE);
+
+    let whole_pow = bpowi(base, to_u128(whole)?)?;
+    if remain.is_zero() {
+        return Some(whole_pow);
+    }
+
+    let partial_result = bpow_approx(base, remain)?;
+    bmul(whole_pow, partial_result)
+}
+
+/// ## Description
+/// Approximates `base ^ exp` for a fractional exponent using the binomial series
+/// (1 + x) ^ exp = 1 + exp * x + exp * (exp - 1) * x ^ 2 / 2! + ...
+fn bpow_approx(base: U256, exp: U256) -> Option<U256> {
+    let one = U256::from(ONE);
+    let (x, x_neg) = bsub_sign(base, one);
+    let mut term = one;
+    let mut sum = term;
+    let mut negative = false;
+
+    for i in 1..=ITERATIONS {
+        let big_k = U256::from(i).checked_mul(one)?;
+        let (c, c_neg) = bsub_sign(exp, big_k - one);
+        term = bmul(term, bmul(c, x)?)?;
+        term = bdiv(term, big_k)?;
+        if term.is_zero() {
+            break;
+        }
+
+        if x_neg {
+            negative = !negative;
+        }
+        if c_neg {
+            negative = !negative;
+        }
+        if negative {
+            sum = sum.checked_sub(term)?;
+        } else {
+            sum = sum.checked_add(term)?;
+        }
+
+        if term < BPOW_PRECISION.into() {
+            break;
+        }
+    }
+
+    Some(sum)
+}
+
+/// ## Description
+/// Converts a [`U256`] into a [`u128`] returning [`None`] on overflow.
+fn to_u128(value: U256) -> Option<u128> {
+    u128::try_from(value).ok()
+}